@@ -0,0 +1,106 @@
+// Criterion benchmarks of the database calls behind the item API, run with `cargo bench`.
+// Pass a name filter to run only some of them: `cargo bench -- get_items`.
+// Criterion keeps the last run under target/criterion and reports the change against it;
+// `cargo bench -- --save-baseline main` and `--baseline main` compare against a named run instead
+use compareware::db::Database;
+use compareware::models::item::Item;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use std::collections::HashMap;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+// Properties per item, about what a small comparison selects
+const PROPERTIES_PER_ITEM: usize = 8;
+const URL: &str = "https://bench.compareware.org/laptops";
+
+fn item(index: usize) -> Item {
+    Item {
+        id: Uuid::new_v4().to_string(),
+        name: format!("Item {}", index),
+        description: format!("Description of item {}", index),
+        wikidata_id: Some(format!("Q{}", 1000 + index)),
+        custom_properties: (0..PROPERTIES_PER_ITEM)
+            .map(|property| (format!("P{}", property + 1), format!("value {}-{}", index, property)))
+            .collect(),
+        quantities: HashMap::new(),
+        group: None,
+        tip_recipient: None,
+        status: None,
+        sources: HashMap::new(),
+        updated_at: None,
+    }
+}
+
+async fn seeded_db(items: &[Item]) -> Database {
+    let db = Database::new(":memory:").unwrap();
+    db.create_schema().await.unwrap();
+    db.insert_items_by_url(URL, items).await.unwrap();
+    for property in items.first().map(|item| item.custom_properties.keys()).into_iter().flatten() {
+        db.add_selected_property(URL, property).await.unwrap();
+    }
+    db
+}
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap()
+}
+
+fn get_items_by_url(c: &mut Criterion) {
+    let runtime = runtime();
+    let mut group = c.benchmark_group("get_items_by_url");
+    for count in [10, 100, 1000] {
+        let db = runtime.block_on(seeded_db(&(0..count).map(item).collect::<Vec<_>>()));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, &count| {
+            b.to_async(&runtime).iter(|| async {
+                assert_eq!(db.get_items_by_url(URL).await.unwrap().len(), count);
+            });
+        });
+    }
+    group.finish();
+}
+
+fn insert_item_by_url(c: &mut Criterion) {
+    let runtime = runtime();
+    let db = runtime.block_on(seeded_db(&(0..100).map(item).collect::<Vec<_>>()));
+    let db = &db;
+    let mut index = 100;
+    c.bench_function("insert_item_by_url/new", |b| {
+        b.to_async(&runtime).iter_batched(
+            || {
+                index += 1;
+                item(index)
+            },
+            |new_item| async move { db.insert_item_by_url(URL, &new_item).await.unwrap() },
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+// Editing one cell of every column, as a batch save of the grid does
+fn grid_update(c: &mut Criterion) {
+    let runtime = runtime();
+    let mut group = c.benchmark_group("insert_items_by_url/grid_update");
+    for count in [10, 100] {
+        let mut items: Vec<Item> = (0..count).map(item).collect();
+        let db = runtime.block_on(seeded_db(&items));
+        let db = &db;
+        let mut revision = 0;
+        group.bench_function(BenchmarkId::from_parameter(count), |b| {
+            b.to_async(&runtime).iter_batched(
+                || {
+                    revision += 1;
+                    for item in &mut items {
+                        item.custom_properties.insert("P1".into(), format!("revision {}", revision));
+                    }
+                    items.clone()
+                },
+                |items| async move { db.insert_items_by_url(URL, &items).await.unwrap() },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, get_items_by_url, insert_item_by_url, grid_update);
+criterion_main!(benches);
@@ -0,0 +1,138 @@
+// HTTP load test of the item API against a running server:
+//
+//     cargo run --release --example load_test -- http://127.0.0.1:3000 [concurrency] [requests]
+//
+// Seeds a fresh comparison with items, then sends `requests` item reads and
+// writes, `concurrency` at a time, and prints their latency percentiles
+use compareware::models::access::EDIT_TOKEN_HEADER;
+use compareware::models::item::Item;
+use compareware::url_path::comparison_api_path;
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+const SEEDED_ITEMS: usize = 100;
+const DEFAULT_CONCURRENCY: usize = 16;
+const DEFAULT_REQUESTS: usize = 1000;
+
+fn item(index: usize) -> Item {
+    Item {
+        id: Uuid::new_v4().to_string(),
+        name: format!("Item {}", index),
+        description: format!("Description of item {}", index),
+        wikidata_id: None,
+        custom_properties: (1..=8)
+            .map(|property| (format!("P{}", property), format!("value {}-{}", index, property)))
+            .collect(),
+        quantities: HashMap::new(),
+        group: None,
+        tip_recipient: None,
+        status: None,
+        sources: HashMap::new(),
+        updated_at: None,
+    }
+}
+
+fn print_latencies(name: &str, mut latencies: Vec<Duration>, errors: usize) {
+    if latencies.is_empty() {
+        println!("{:<12} no successful requests, {} errors", name, errors);
+        return;
+    }
+    latencies.sort();
+    let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+    println!(
+        "{:<12} {:>6} ok {:>5} errors   p50 {:>10.3?}   p95 {:>10.3?}   p99 {:>10.3?}   max {:>10.3?}",
+        name,
+        latencies.len(),
+        errors,
+        percentile(50),
+        percentile(95),
+        percentile(99),
+        latencies[latencies.len() - 1]
+    );
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let mut args = std::env::args().skip(1);
+    let server = args.next().unwrap_or_else(|| "http://127.0.0.1:3000".into());
+    let concurrency: usize = args.next().and_then(|arg| arg.parse().ok()).unwrap_or(DEFAULT_CONCURRENCY);
+    let requests: usize = args.next().and_then(|arg| arg.parse().ok()).unwrap_or(DEFAULT_REQUESTS);
+
+    let client = reqwest::Client::new();
+    let comparison = format!("https://load-test.compareware.org/{}", Uuid::new_v4());
+    let items_url = format!(
+        "{}{}",
+        server.trim_end_matches('/'),
+        comparison_api_path(&comparison, &["items"])
+    );
+
+    let seeded: Vec<Item> = (0..SEEDED_ITEMS).map(item).collect();
+    let response = client
+        .post(format!("{}/batch", items_url))
+        .json(&seeded)
+        .send()
+        .await
+        .expect("Failed to reach the server");
+    assert!(response.status().is_success(), "Seeding failed: {}", response.status());
+    // Seeding creates the comparison, later writes need its edit key
+    let edit_key = response
+        .headers()
+        .get(EDIT_TOKEN_HEADER)
+        .and_then(|key| key.to_str().ok())
+        .expect("No edit key for the new comparison")
+        .to_string();
+    println!(
+        "Seeded {} items at {}, sending {} requests {} at a time",
+        SEEDED_ITEMS, comparison, requests, concurrency
+    );
+
+    // Every fourth request saves an item, the others read the whole comparison
+    let started = Instant::now();
+    let results: Vec<(bool, Result<Duration, ()>)> = stream::iter(0..requests)
+        .map(|request| {
+            let (client, items_url, edit_key) = (&client, &items_url, &edit_key);
+            async move {
+                let write = request % 4 == 0;
+                let sent = Instant::now();
+                let response = if write {
+                    client
+                        .post(items_url)
+                        .header(EDIT_TOKEN_HEADER, edit_key)
+                        .json(&item(SEEDED_ITEMS + request))
+                        .send()
+                        .await
+                } else {
+                    client.get(items_url).send().await
+                };
+                let result = match response {
+                    Ok(response) if response.status().is_success() => {
+                        let _ = response.bytes().await;
+                        Ok(sent.elapsed())
+                    }
+                    _ => Err(()),
+                };
+                (write, result)
+            }
+        })
+        .buffer_unordered(concurrency)
+        .collect()
+        .await;
+    let elapsed = started.elapsed();
+
+    for (name, write) in [("GET items", false), ("POST item", true)] {
+        let (latencies, errors): (Vec<_>, Vec<_>) = results
+            .iter()
+            .filter(|(is_write, _)| *is_write == write)
+            .map(|(_, result)| *result)
+            .partition(Result::is_ok);
+        print_latencies(name, latencies.into_iter().flatten().collect(), errors.len());
+    }
+    println!(
+        "{} requests in {:.2?} ({:.0} requests/s)",
+        requests,
+        elapsed,
+        requests as f64 / elapsed.as_secs_f64()
+    );
+}
@@ -0,0 +1,26 @@
+#!/bin/sh
+# Size budget of the client bundle, run after `cargo leptos build --release`:
+#
+#     scripts/check-wasm-size.sh [site root]
+#
+# Fails when the gzipped WASM in <site root>/pkg grows past the budget,
+# COMPAREWARE_WASM_BUDGET_KB kibibytes (800 by default)
+set -eu
+
+site_root="${1:-target/site}"
+budget_kb="${COMPAREWARE_WASM_BUDGET_KB:-800}"
+wasm="$site_root/pkg/compareware.wasm"
+
+if [ ! -f "$wasm" ]; then
+    echo "No bundle at $wasm, build it with cargo leptos build --release" >&2
+    exit 1
+fi
+
+raw_kb=$(( $(wc -c < "$wasm") / 1024 ))
+gzipped_kb=$(( $(gzip -9c "$wasm" | wc -c) / 1024 ))
+echo "compareware.wasm: ${raw_kb} KiB, ${gzipped_kb} KiB gzipped (budget ${budget_kb} KiB gzipped)"
+
+if [ "$gzipped_kb" -gt "$budget_kb" ]; then
+    echo "The client bundle is over its size budget. Put rarely used client code behind a cargo feature, or raise COMPAREWARE_WASM_BUDGET_KB if the growth is intended" >&2
+    exit 1
+fi
@@ -1,182 +1,4538 @@
 #[cfg(feature = "ssr")]
-use actix_web::{web, HttpResponse};
+use actix_web::{http::StatusCode, web, HttpResponse, ResponseError};
 #[cfg(feature = "ssr")]
-use crate::db::Database;
+use crate::db::{ComparisonHandle, Database, ItemsSave, PropertyRenaming};
 #[cfg(feature = "ssr")]
 use std::sync::Arc;
 #[cfg(feature = "ssr")]
-use tokio::sync::Mutex;
+use tokio::sync::RwLock;
 #[cfg(feature = "ssr")]
-use crate::models::item::Item;
+use crate::models::access::{EditAccess, EditToken, EditorRole, Visibility, VisibilitySetting, EDIT_TOKEN_HEADER, EDIT_TOKEN_PARAM};
+#[cfg(feature = "ssr")]
+use crate::models::backup::{BackupSummary, ComparisonArchive, ComparisonExport, DatabaseRestoreSummary, COMPARISON_EXPORT_VERSION, RestoreRequest, RestoreSummary, StorageBackupSummary, WorkspaceArchive};
+#[cfg(feature = "ssr")]
+use crate::database_backup::{database_copy, restore_database, DatabaseBackupError};
+#[cfg(feature = "ssr")]
+use crate::models::card::{Spec, SpecCard};
+#[cfg(feature = "ssr")]
+use crate::models::directory::{ComparisonAnnouncement, ComparisonIndex, ComparisonListing, ComparisonSummary, DirectoryEntry, COMPARISONS_PER_PAGE, default_title};
+#[cfg(feature = "ssr")]
+use crate::models::error::ErrorResponse;
+#[cfg(feature = "ssr")]
+use crate::models::history::{history_atom, HistoryChange, Revision, ANONYMOUS_ACTOR};
+#[cfg(feature = "ssr")]
+use crate::models::item::{filter_selected_properties, DeleteSummary, DeletedItem, Item, ItemQuery, ItemStatus, ItemRequest, PropertyValue, RefreshedValue, ValueSource, WikidataRefresh};
+#[cfg(feature = "ssr")]
+use crate::models::metadata::{ComparisonMetadata, Decision, DecisionRequest};
+#[cfg(feature = "ssr")]
+use crate::models::snapshot::{snapshot_path, Snapshot, SnapshotLink};
+#[cfg(feature = "ssr")]
+use crate::nostr::{build_zap_request, MyError, NostrClient, NostrReader};
+#[cfg(feature = "ssr")]
+use crate::zap::{fetch_pay_service, parse_lightning_address, request_invoice, ZapError, MSAT_PER_SAT};
+#[cfg(feature = "ssr")]
+use crate::models::zap::{TipInvoice, TipRequest};
+#[cfg(feature = "ssr")]
+use crate::models::review::{reviews_atom, ExportedReview, RatingSummary, ReviewRequest, MAX_RATING, MIN_RATING, REVIEW_FEED_SIZE};
+#[cfg(feature = "ssr")]
+use crate::models::search::{SearchHit, SnippetPart, MAX_SEARCH_LENGTH, SEARCH_RESULTS_LIMIT};
+#[cfg(feature = "ssr")]
+use crate::sparql::{fetch_claim_values, fetch_entity_labels, fetch_entity_terms, is_entity_id, property_label, query_items, suggest_properties, SparqlError};
+#[cfg(feature = "ssr")]
+use crate::wikidata_proxy::{Freshness, WikidataProxy};
+#[cfg(feature = "ssr")]
+use crate::request_limits::client_address;
+#[cfg(feature = "ssr")]
+use crate::wikidata_fixtures::{is_wikidata_request, FixtureError, WikidataFixtures};
+#[cfg(feature = "ssr")]
+use crate::models::property::{canonical_property, custom_property_problem, is_property_id, EntityClaims, EntityTerms, PropertyColumn, PropertyDeletion, PropertyOrder, PropertyOverride, PropertyRename, PropertySelection, PropertySettings, PropertySuggestion, ReusableValues};
+#[cfg(feature = "ssr")]
+use crate::models::quantity::Quantity;
+#[cfg(feature = "ssr")]
+use crate::models::wikidata::{entity_claims, referenced_entity_ids};
+#[cfg(feature = "ssr")]
+use crate::live::{connect, event_stream, LiveUpdates};
+#[cfg(feature = "ssr")]
+use crate::models::publishing::PublishingSettings;
+#[cfg(feature = "ssr")]
+use crate::publisher::ComparisonPublisher;
+#[cfg(feature = "ssr")]
+use crate::models::mirror::MirrorSettings;
+#[cfg(feature = "ssr")]
+use crate::mirror::ComparisonMirrors;
+#[cfg(feature = "ssr")]
+use crate::storage_backup::{StorageBackups, StorageError};
+#[cfg(feature = "ssr")]
+use crate::models::instance::{InstanceFeatures, InstanceInfo, InstanceSettings};
+#[cfg(feature = "ssr")]
+use crate::models::knowledge::KnowledgeSourceKind;
+#[cfg(feature = "ssr")]
+use crate::config::Config;
+#[cfg(feature = "ssr")]
+use crate::instance::InstanceConfig;
+#[cfg(feature = "ssr")]
+use crate::models::setup::{SetupRequest, SetupResult, SetupStatus};
+#[cfg(feature = "ssr")]
+use crate::setup::{demo_archive, setup_settings};
+#[cfg(feature = "ssr")]
+use crate::models::live::{ComparisonChange, LIVE_SESSION_HEADER};
+#[cfg(feature = "ssr")]
+use crate::models::presence::{CellLock, EditorMetrics, Heartbeat, PresenceState, UrlEditors};
+#[cfg(feature = "ssr")]
+use crate::models::diagnostics::{ClientErrorReport, ComparisonLoad, ComparisonRequests, ComparisonRows, HEAVIEST_COMPARISONS};
+#[cfg(feature = "ssr")]
+use crate::comparison_metrics::ComparisonMetrics;
+#[cfg(feature = "ssr")]
+use crate::api_keys::request_api_key;
+#[cfg(feature = "ssr")]
+use crate::models::api_key::{ApiKey, ApiKeyRequest, CreatedApiKey, API_KEY_PREFIX};
+#[cfg(feature = "ssr")]
+use crate::diagnostics::{Diagnostics, AUDIT_TAIL_LENGTH};
+#[cfg(feature = "ssr")]
+use crate::url_path::{comparison_api_path, copy_url};
+#[cfg(feature = "ssr")]
+use crate::models::export::{SignedLink, SignedLinkRequest};
+#[cfg(feature = "ssr")]
+use crate::export::{exporter, link_signature, negotiate, verify_link_signature, ExportSource, Exporter, EXPORTERS};
+#[cfg(feature = "ssr")]
+use crate::models::validation::{FieldError, Validate};
+#[cfg(feature = "ssr")]
+use crate::server_log;
 #[cfg(feature = "ssr")]
 use std::collections::HashMap;
 #[cfg(feature = "ssr")]
 use leptos::logging::log;
 
 #[cfg(feature = "ssr")]
-use serde::{Deserialize, Serialize};
+use actix_web::http::header::{CacheControl, CacheDirective, ETag, EntityTag, Header, HttpDate, IfModifiedSince, IfNoneMatch, LastModified};
 #[cfg(feature = "ssr")]
-#[derive(Serialize, Deserialize)]
-pub struct ItemRequest {
-    pub url: String,
-    pub item: Item,
+use serde::Deserialize;
+#[cfg(feature = "ssr")]
+use utoipa::OpenApi;
+
+// Errors returned by the API handlers, rendered as an `ErrorResponse` JSON body
+#[cfg(feature = "ssr")]
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("URL not found: {0}")]
+    UrlNotFound(String),
+    #[error("{0} not found")]
+    NotFound(String),
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    // Save based on an outdated copy, with the stored copies of the items changed since
+    #[error("{} items were changed since they were loaded", .0.len())]
+    Stale(Vec<Item>),
+    #[error("{message}")]
+    Validation {
+        message: String,
+        details: Option<serde_json::Value>,
+    },
+    #[error("Database error: {0}")]
+    Database(rusqlite::Error),
+    // Failure of the server outside of the database, such as a temporary file it couldn't write
+    #[error("Internal error: {0}")]
+    Internal(String),
+    #[error("{0}")]
+    Upstream(String),
+    #[error("{0}")]
+    Unauthorized(String),
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    NotConfigured(String),
+    #[error("{0}")]
+    NotAcceptable(String),
+    #[error("Too many Wikidata requests, retry in {retry_after} seconds")]
+    RateLimited { retry_after: u64 },
+    // Over the requests per minute of the client's IP, see request_limits
+    #[error("Too many requests, retry in {retry_after} seconds")]
+    TooManyRequests { retry_after: u64 },
+    // Failure made up by the fault injection of development and test servers
+    #[error("Injected failure, the request was not handled")]
+    InjectedFailure,
+}
+
+#[cfg(feature = "ssr")]
+impl ApiError {
+    // Shorthand for a validation error without field details
+    pub fn validation(message: impl Into<String>) -> Self {
+        ApiError::Validation {
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::UrlNotFound(_) => "url_not_found",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Stale(_) => "stale_item",
+            ApiError::Validation { .. } => "validation_failed",
+            ApiError::Database(_) => "database_error",
+            ApiError::Internal(_) => "internal_error",
+            ApiError::Upstream(_) => "upstream_error",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::NotConfigured(_) => "not_configured",
+            ApiError::NotAcceptable(_) => "not_acceptable",
+            ApiError::RateLimited { .. } => "rate_limited",
+            ApiError::TooManyRequests { .. } => "too_many_requests",
+            ApiError::InjectedFailure => "injected_failure",
+        }
+    }
+
+    fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            ApiError::Validation { details, .. } => details.clone(),
+            ApiError::Stale(items) => serde_json::to_value(items).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl From<rusqlite::Error> for ApiError {
+    fn from(err: rusqlite::Error) -> Self {
+        match err {
+            rusqlite::Error::QueryReturnedNoRows => ApiError::NotFound("Record".into()),
+            rusqlite::Error::SqliteFailure(e, ref message)
+                if e.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                ApiError::Conflict(message.clone().unwrap_or_else(|| e.to_string()))
+            }
+            err => ApiError::Database(err),
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl From<SparqlError> for ApiError {
+    fn from(err: SparqlError) -> Self {
+        match err {
+            SparqlError::EmptyQuery | SparqlError::MissingItemVariable => ApiError::validation(err.to_string()),
+            err => ApiError::Upstream(err.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl From<FixtureError> for ApiError {
+    fn from(err: FixtureError) -> Self {
+        match err {
+            FixtureError::Missing(url) => ApiError::NotFound(format!("Recorded response to {}", url)),
+            err => ApiError::Upstream(err.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl From<MyError> for ApiError {
+    fn from(err: MyError) -> Self {
+        match err {
+            MyError::BackupNotFound => ApiError::NotFound("Backup".into()),
+            err => ApiError::Upstream(err.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl From<ZapError> for ApiError {
+    fn from(err: ZapError) -> Self {
+        match err {
+            ZapError::InvalidLightningAddress(_) | ZapError::AmountOutOfRange { .. } => {
+                ApiError::validation(err.to_string())
+            }
+            err => ApiError::Upstream(err.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl From<DatabaseBackupError> for ApiError {
+    fn from(err: DatabaseBackupError) -> Self {
+        match err {
+            DatabaseBackupError::Database(e) => ApiError::Database(e),
+            DatabaseBackupError::Invalid(problem) => ApiError::validation(problem),
+            err @ (DatabaseBackupError::File(_) | DatabaseBackupError::Missing(_)) => ApiError::Internal(err.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl From<StorageError> for ApiError {
+    fn from(err: StorageError) -> Self {
+        match err {
+            StorageError::Database(e) => ApiError::Database(e),
+            err => ApiError::Upstream(err.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::UrlNotFound(_) | ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Stale(_) => StatusCode::PRECONDITION_FAILED,
+            ApiError::Validation { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Database(_) | ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotConfigured(_) | ApiError::InjectedFailure => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::RateLimited { .. } | ApiError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        server_log!("[API] Request failed ({}): {}", self.code(), self);
+        let mut response = HttpResponse::build(self.status_code());
+        if let ApiError::RateLimited { retry_after } | ApiError::TooManyRequests { retry_after } = self {
+            response.insert_header((actix_web::http::header::RETRY_AFTER, retry_after.to_string()));
+        }
+        response.json(ErrorResponse {
+            code: self.code().to_string(),
+            message: match self {
+                // SQL and file details stay in the server log above, clients only learn the code
+                ApiError::Database(_) => "Database error".to_string(),
+                ApiError::Internal(_) => "Internal error".to_string(),
+                other => other.to_string(),
+            },
+            details: self.details(),
+        })
+    }
+}
+
+// Take a token from the Wikidata budget of the requesting client
+#[cfg(feature = "ssr")]
+async fn check_wikidata_budget(proxy: &WikidataProxy, req: &actix_web::HttpRequest) -> Result<(), ApiError> {
+    proxy.limiter.check(&client_address(req)).await.map_err(|wait| ApiError::RateLimited {
+        retry_after: wait.as_secs_f64().ceil() as u64,
+    })
+}
+
+// Edit tokens are stored as hex-encoded SHA-256 hashes, like the admin token and API keys
+#[cfg(feature = "ssr")]
+pub(crate) fn hash_edit_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(feature = "ssr")]
+fn new_edit_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+// Hash of the edit token sent with the request, in the header or the query string
+#[cfg(feature = "ssr")]
+fn request_token_hash(req: &actix_web::HttpRequest) -> Option<String> {
+    let header = req
+        .headers()
+        .get(EDIT_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let token = header.or_else(|| {
+        web::Query::<HashMap<String, String>>::from_query(req.query_string())
+            .ok()?
+            .remove(EDIT_TOKEN_PARAM)
+    })?;
+    Some(token.trim())
+        .filter(|token| !token.is_empty())
+        .map(hash_edit_token)
+}
+
+// Edit access of a request: the role of its edit token, or a collaborator's for requests
+// authenticated with an API key that may edit the comparison
+#[cfg(feature = "ssr")]
+pub(crate) async fn request_access(db: &Database, url: &str, req: &actix_web::HttpRequest) -> Result<EditAccess, ApiError> {
+    let mut access = db.get_edit_access(url, request_token_hash(req).as_deref()).await?;
+    if access.role.is_none() && request_api_key(req).is_some_and(|key| key.allows(url)) {
+        access.role = Some(EditorRole::Collaborator);
+    }
+    Ok(access)
+}
+
+// Reject changes to protected comparisons unless the request carries one of their edit
+// tokens or an API key
+#[cfg(feature = "ssr")]
+async fn ensure_can_edit(db: &Database, url: &str, req: &actix_web::HttpRequest) -> Result<(), ApiError> {
+    let access = request_access(db, url, req).await?;
+    if access.can_edit() {
+        Ok(())
+    } else {
+        Err(ApiError::Forbidden("This comparison is protected, a valid edit token or API key is required".into()))
+    }
+}
+
+// Like ensure_can_edit, but the first write to a URL claims it: the URL is registered
+// with a new owner edit key, which is returned so it can be handed to the creator
+#[cfg(feature = "ssr")]
+async fn claim_or_ensure_can_edit(
+    db: &Database,
+    url: &str,
+    req: &actix_web::HttpRequest,
+) -> Result<Option<String>, ApiError> {
+    let key = new_edit_token();
+    if db.claim_url(url, &hash_edit_token(&key)).await? {
+        server_log!("[API] {} claimed by its creator", url);
+        return Ok(Some(key));
+    }
+    ensure_can_edit(db, url, req).await?;
+    Ok(None)
+}
+
+// Refuse items stored under other URLs before the request claims or edits `url`. Saving
+// them here would move them out of their comparison, whoever protects it
+#[cfg(feature = "ssr")]
+async fn ensure_items_of_url(db: &Database, url: &str, items: &[Item]) -> Result<(), ApiError> {
+    let ids: Vec<&str> = items.iter().map(|item| item.id.as_str()).collect();
+    match db.items_of_other_urls(url, &ids).await?.first() {
+        Some(id) => Err(ApiError::Conflict(format!("Item {} belongs to another comparison", id))),
+        None => Ok(()),
+    }
+}
+
+// Add the changes a request made to the edit history of a URL, under the role of its edit
+// token. Collaborators are told apart by the start of their token's hash. `edit_key` is
+// the owner key of a comparison the request just claimed, which it acted with
+#[cfg(feature = "ssr")]
+async fn record_history(
+    db: &Database,
+    url: &str,
+    req: &actix_web::HttpRequest,
+    edit_key: Option<&str>,
+    changes: &[HistoryChange],
+) -> Result<(), ApiError> {
+    let token_hash = edit_key.map(hash_edit_token).or_else(|| request_token_hash(req));
+    let access = db.get_edit_access(url, token_hash.as_deref()).await?;
+    let actor = match (access.role, token_hash, request_api_key(req)) {
+        (Some(EditorRole::Collaborator), Some(hash), _) => format!("{} {}", EditorRole::Collaborator.as_str(), &hash[..8]),
+        (Some(role), _, _) => role.as_str().to_string(),
+        (None, _, Some(key)) => format!("API key {}", key.name),
+        (None, _, None) => ANONYMOUS_ACTOR.to_string(),
+    };
+    db.record_revisions(url, &actor, changes, chrono::Utc::now().timestamp()).await?;
+    Ok(())
+}
+
+// History entries of saved items, with their values as stored: previews of long values
+// sent back unchanged stand for the full values they were cut from
+#[cfg(feature = "ssr")]
+async fn saved_item_changes(db: &Database, url: &str, items: &[Item]) -> Result<Vec<HistoryChange>, ApiError> {
+    let stored = db.get_items_by_url(url).await?;
+    Ok(stored
+        .into_iter()
+        .filter(|stored| items.iter().any(|item| item.id == stored.id))
+        .map(|item| HistoryChange::ItemSaved { item })
+        .collect())
+}
+
+// Successful response of a write, carrying the edit key of a newly claimed comparison
+#[cfg(feature = "ssr")]
+fn write_response(edit_key: Option<String>) -> actix_web::HttpResponseBuilder {
+    let mut response = HttpResponse::Ok();
+    if let Some(key) = edit_key {
+        response.insert_header((EDIT_TOKEN_HEADER, key));
+    }
+    response
+}
+
+// Reject requests for URLs that have never been written to
+#[cfg(feature = "ssr")]
+async fn ensure_url_exists(db: &Database, url: &str) -> Result<(), ApiError> {
+    if db.url_exists(url).await? {
+        Ok(())
+    } else {
+        Err(ApiError::UrlNotFound(url.to_string()))
+    }
+}
+
+// Handle on the comparison of a URL, for handlers working on it with several queries
+#[cfg(feature = "ssr")]
+async fn stored_comparison<'a>(db: &'a Database, url: &str) -> Result<ComparisonHandle<'a>, ApiError> {
+    db.comparison(url).await?.ok_or_else(|| ApiError::UrlNotFound(url.to_string()))
+}
+
+// Live session of the browser tab that sent a change, so it isn't echoed back to it
+#[cfg(feature = "ssr")]
+fn live_session(req: &actix_web::HttpRequest) -> Option<&str> {
+    req.headers().get(LIVE_SESSION_HEADER).and_then(|value| value.to_str().ok())
+}
+
+// Time of the If-Unmodified-Since header in milliseconds, the last one of its second
+#[cfg(feature = "ssr")]
+fn unmodified_since(req: &actix_web::HttpRequest) -> Option<i64> {
+    use actix_web::http::header::IfUnmodifiedSince;
+    let IfUnmodifiedSince(date) = IfUnmodifiedSince::parse(req).ok()?;
+    let since = std::time::SystemTime::from(date).duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(since.as_millis() as i64 + 999)
+}
+
+// Save items unless they changed since the client's copy, see Database::save_items_by_url
+#[cfg(feature = "ssr")]
+async fn save_items(db: &Database, url: &str, items: &[Item], req: &actix_web::HttpRequest) -> Result<Vec<Item>, ApiError> {
+    match db.save_items_by_url(url, items, unmodified_since(req)).await? {
+        ItemsSave::Saved(items) => Ok(items),
+        ItemsSave::Stale(stored) => Err(ApiError::Stale(stored)),
+        ItemsSave::Taken(ids) => Err(ApiError::Conflict(format!("Item {} belongs to another comparison", ids[0]))),
+    }
+}
+
+// ETag and Last-Modified of the items of a comparison at a version of its URL
+#[cfg(feature = "ssr")]
+fn version_validators(version: i64) -> (ETag, LastModified) {
+    let modified = std::time::UNIX_EPOCH + std::time::Duration::from_millis(version.max(0) as u64);
+    (ETag(EntityTag::new_strong(format!("{:x}", version))), LastModified(HttpDate::from(modified)))
+}
+
+// Whether the client's copy of a comparison's items at `version` is still current:
+// If-None-Match lists the ETag, or without it, If-Modified-Since is in the second of the
+// last change or later
+#[cfg(feature = "ssr")]
+fn not_modified(req: &actix_web::HttpRequest, etag: &ETag, version: i64) -> bool {
+    // A missing If-None-Match header parses as an empty list
+    match IfNoneMatch::parse(req) {
+        Ok(IfNoneMatch::Any) => return true,
+        Ok(IfNoneMatch::Items(tags)) if !tags.is_empty() => return tags.iter().any(|tag| tag.weak_eq(etag)),
+        _ => {}
+    }
+    let Ok(IfModifiedSince(since)) = IfModifiedSince::parse(req) else {
+        return false;
+    };
+    std::time::SystemTime::from(since)
+        .duration_since(std::time::UNIX_EPOCH)
+        .is_ok_and(|since| version / 1000 <= since.as_secs() as i64)
 }
 
 #[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/items",
+    tag = "items",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Items stored for the URL", body = [Item], headers(
+            ("ETag" = String, description = "Version of the items, for If-None-Match"),
+            ("Last-Modified" = String, description = "Time the items or properties last changed, for If-Modified-Since")
+        )),
+        (status = 304, description = "The items didn't change since the If-None-Match ETag or the If-Modified-Since time"),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 500, description = "Failed to fetch items", body = ErrorResponse)
+    )
+)]
 pub async fn get_items(
-    db: web::Data<Arc<Mutex<Database>>>,
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
     url: web::Query<String>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     log!("[SERVER] Received request for URL: {}", url);
 
-    let db = db.lock().await;
-    match db.get_items_by_url(&url).await {
-        Ok(items) => {
-            log!("[SERVER] Returning {} items for URL: {}", items.len(), url);
-            HttpResponse::Ok().json(items)
-        },
-        Err(err) => {
-            log!("[SERVER ERROR] Failed to fetch items for {}: {:?}", url, err);
-            HttpResponse::InternalServerError().body("Failed to fetch items")
+    let db = db.read().await;
+    ensure_url_exists(&db, &url).await?;
+    // Comparisons unchanged since versions were recorded are sent in full every time
+    let version = db.get_url_version(&url).await?;
+    let mut response = HttpResponse::Ok();
+    if let Some(version) = version {
+        let (etag, last_modified) = version_validators(version);
+        if not_modified(&req, &etag, version) {
+            return Ok(HttpResponse::NotModified().insert_header(etag).insert_header(last_modified).finish());
         }
+        response.insert_header(etag).insert_header(last_modified);
     }
+    let items = db.get_items_by_url(&url).await.map_err(|err| {
+        log!("[SERVER ERROR] Failed to fetch items for {}: {:?}", url, err);
+        ApiError::from(err)
+    })?;
+    log!("[SERVER] Returning {} items for URL: {}", items.len(), url);
+    Ok(response.insert_header(CacheControl(vec![CacheDirective::NoCache])).json(items))
 }
 
 #[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/items",
+    tag = "items",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    request_body = Item,
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Item saved", body = Item, headers(
+            ("X-Edit-Token" = String, description = "Owner edit key, only sent when the request created the comparison")
+        )),
+        (status = 409, description = "Conflicting item, or an item ID of another comparison", body = ErrorResponse),
+        (status = 412, description = "The item changed since its `updated_at` or the If-Unmodified-Since header, `details` holds the stored copy", body = ErrorResponse),
+        (status = 422, description = "Invalid item", body = ErrorResponse)
+    )
+)]
 pub async fn create_item(
-    db: web::Data<Arc<Mutex<Database>>>,
-    request: web::Json<ItemRequest>,
-) -> HttpResponse {
-    let db = db.lock().await;
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    live: web::Data<LiveUpdates>,
+    publisher: web::Data<ComparisonPublisher>,
+    request: ValidJson<ItemRequest>,
+) -> Result<HttpResponse, ApiError> {
     let url = request.url.clone();
     let item = request.item.clone();
     let item_id = request.item.id.clone();
     // request logging
-    log!("[API] Received item request - URL: {}, Item ID: {}", 
+    server_log!("[API] Received item request - URL: {}, Item ID: {}",
         request.url, request.item.id);
-    
+
     // raw JSON logging
     let raw_json = serde_json::to_string(&request.into_inner()).unwrap();
-    log!("[API] Raw request JSON: {}", raw_json);
+    server_log!("[API] Raw request JSON: {}", raw_json);
 
-    match db.insert_item_by_url(&url, &item).await {
-        Ok(_) => {
-            log!("[API] Successfully saved item ID: {}", item_id);
-            HttpResponse::Ok().json(item)
-        },
-        Err(e) => {
-            log!("[API] Database error: {:?}", e); 
-            HttpResponse::BadRequest().body(format!("Database error: {}", e))
-        }
+    let db = db.write().await;
+    ensure_items_of_url(&db, &url, std::slice::from_ref(&item)).await?;
+    let edit_key = claim_or_ensure_can_edit(&db, &url, &req).await?;
+    let item = save_items(&db, &url, std::slice::from_ref(&item), &req).await?.remove(0);
+    server_log!("[API] Successfully saved item ID: {}", item_id);
+    let changes = saved_item_changes(&db, &url, std::slice::from_ref(&item)).await?;
+    record_history(&db, &url, &req, edit_key.as_deref(), &changes).await?;
+    live.publish(&url, live_session(&req), ComparisonChange::item_saved(item.clone()));
+    publish_saved_comparison(&db, &publisher, &url).await?;
+    Ok(write_response(edit_key).json(item))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/items/batch",
+    tag = "items",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    request_body = [Item],
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Items saved", body = [Item], headers(
+            ("X-Edit-Token" = String, description = "Owner edit key, only sent when the request created the comparison")
+        )),
+        (status = 409, description = "Conflicting item, or an item ID of another comparison", body = ErrorResponse),
+        (status = 412, description = "Items changed since their `updated_at` or the If-Unmodified-Since header, `details` holds their stored copies and nothing was saved", body = ErrorResponse),
+        (status = 422, description = "Invalid item", body = ErrorResponse)
+    )
+)]
+pub async fn create_items_batch(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    live: web::Data<LiveUpdates>,
+    publisher: web::Data<ComparisonPublisher>,
+    url: web::Path<String>,
+    items: ValidJson<Vec<Item>>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    let items = items.into_inner();
+    server_log!("[API] Received batch save - URL: {}, {} items", url, items.len());
+
+    let db = db.write().await;
+    ensure_items_of_url(&db, &url, &items).await?;
+    let edit_key = claim_or_ensure_can_edit(&db, &url, &req).await?;
+    let items = save_items(&db, &url, &items, &req).await?;
+    server_log!("[API] Successfully saved {} items", items.len());
+    let changes = saved_item_changes(&db, &url, &items).await?;
+    record_history(&db, &url, &req, edit_key.as_deref(), &changes).await?;
+    for item in &items {
+        live.publish(&url, live_session(&req), ComparisonChange::item_saved(item.clone()));
+    }
+    publish_saved_comparison(&db, &publisher, &url).await?;
+    Ok(write_response(edit_key).json(items))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/items/import",
+    tag = "items",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    request_body = ItemQuery,
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid, or imports are turned off", body = ErrorResponse),
+        (status = 200, description = "Items created from the query results", body = [Item], headers(
+            ("X-Edit-Token" = String, description = "Owner edit key, only sent when the request created the comparison")
+        )),
+        (status = 422, description = "Invalid query", body = ErrorResponse),
+        (status = 429, description = "Wikidata request budget used up", body = ErrorResponse),
+        (status = 502, description = "Wikidata query service failed", body = ErrorResponse)
+    )
+)]
+#[allow(clippy::too_many_arguments)] // One extractor per piece of shared state
+pub async fn import_items_from_query(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    instance: web::Data<InstanceConfig>,
+    live: web::Data<LiveUpdates>,
+    publisher: web::Data<ComparisonPublisher>,
+    url: web::Path<String>,
+    request: ValidJson<ItemQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    server_log!("[API] Importing items for URL {} from query: {}", url, request.query);
+    let settings = instance.settings();
+    ensure_feature(settings.features.imports, "Imports")?;
+    ensure_can_edit(&*db.read().await, &url, &req).await?;
+
+    // Run the query before taking the database lock, it can take a while
+    check_wikidata_budget(&proxy, &req).await?;
+    let queried = query_items(&proxy.config, &request.query, &settings.wikidata_language).await?;
+
+    let db = db.write().await;
+    let edit_key = claim_or_ensure_can_edit(&db, &url, &req).await?;
+    // Skip entities that are already part of the comparison
+    let existing = db.get_items_by_url(&url).await?;
+    let items: Vec<Item> = queried
+        .into_iter()
+        .filter(|queried| {
+            !existing
+                .iter()
+                .any(|item| item.wikidata_id.as_deref() == Some(queried.wikidata_id.as_str()))
+        })
+        .map(|queried| Item {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: queried.label,
+            description: queried.description,
+            wikidata_id: Some(queried.wikidata_id),
+            custom_properties: HashMap::new(),
+            quantities: HashMap::new(),
+            group: None,
+            tip_recipient: None,
+            status: None,
+            sources: HashMap::from([
+                ("name".to_string(), ValueSource::Wikidata),
+                ("description".to_string(), ValueSource::Wikidata),
+            ]),
+            updated_at: None,
+        })
+        .collect();
+
+    db.insert_items_by_url(&url, &items).await?;
+    server_log!("[API] Imported {} items for URL {}", items.len(), url);
+    let changes = saved_item_changes(&db, &url, &items).await?;
+    record_history(&db, &url, &req, edit_key.as_deref(), &changes).await?;
+    for item in &items {
+        live.publish(&url, live_session(&req), ComparisonChange::item_saved(item.clone()));
     }
+    publish_saved_comparison(&db, &publisher, &url).await?;
+    Ok(write_response(edit_key).json(items))
 }
 
 #[cfg(feature = "ssr")]
+#[utoipa::path(
+    delete,
+    path = "/api/urls/{url}/items/{item_id}",
+    tag = "items",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        ("item_id" = String, Path, description = "Item ID")
+    ),
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Item moved to the trash, see the restore endpoint", body = DeleteSummary),
+        (status = 404, description = "Unknown URL or item", body = ErrorResponse),
+        (status = 500, description = "Failed to delete item", body = ErrorResponse)
+    )
+)]
 pub async fn delete_item(
-    db: web::Data<Arc<Mutex<Database>>>,
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    live: web::Data<LiveUpdates>,
     path: web::Path<(String, String)>, // (url, item_id)
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let (url, item_id) = path.into_inner();
-    log!("[API] Deleting item {} from URL {}", item_id, url);
-    let db = db.lock().await;
-    match db.delete_item_by_url(&url, &item_id).await {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            log!("[API] Delete error: {:?}", e);
-            HttpResponse::InternalServerError().body(e.to_string())
-        }
+    server_log!("[API] Deleting item {} from URL {}", item_id, url);
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+    let rows_affected = db.delete_item_by_url(&url, &item_id).await?;
+    if rows_affected == 0 {
+        return Err(ApiError::NotFound(format!("Item {}", item_id)));
     }
+    record_history(&db, &url, &req, None, &[HistoryChange::ItemDeleted { item_id: item_id.clone() }]).await?;
+    live.publish(&url, live_session(&req), ComparisonChange::ItemDeleted { item_id });
+    Ok(HttpResponse::Ok().json(DeleteSummary { rows_affected }))
 }
 
 #[cfg(feature = "ssr")]
-pub async fn delete_property(
-    db: web::Data<Arc<Mutex<Database>>>,
-    path: web::Path<(String, String)>, // (url, property)
-) -> HttpResponse {
-    let (url, property) = path.into_inner();
-    log!("[API] Deleting property {} from URL {}", property, url);
-    let db = db.lock().await;
-    match db.delete_property_by_url(&url, &property).await {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => {
-            log!("[API] Delete error: {:?}", e);
-            HttpResponse::InternalServerError().body(e.to_string())
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/trash",
+    tag = "items",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Recently deleted items, most recent first", body = [DeletedItem]),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 500, description = "Failed to fetch the deleted items", body = ErrorResponse)
+    )
+)]
+pub async fn get_deleted_items(
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let db = db.read().await;
+    ensure_url_exists(&db, &url).await?;
+    Ok(HttpResponse::Ok().json(db.get_deleted_items(&url).await?))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/items/{item_id}/restore",
+    tag = "items",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        ("item_id" = String, Path, description = "ID of an item in the trash")
+    ),
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Item back at its former position, with its values and ratings", body = Item),
+        (status = 404, description = "Unknown URL, or an item that isn't in the trash", body = ErrorResponse),
+        (status = 500, description = "Failed to restore the item", body = ErrorResponse)
+    )
+)]
+pub async fn restore_item(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    live: web::Data<LiveUpdates>,
+    path: web::Path<(String, String)>, // (url, item_id)
+) -> Result<HttpResponse, ApiError> {
+    let (url, item_id) = path.into_inner();
+    server_log!("[API] Restoring item {} of URL {}", item_id, url);
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+    let item = db
+        .restore_item(&url, &item_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Deleted item {}", item_id)))?;
+    record_history(&db, &url, &req, None, &[HistoryChange::ItemRestored { item: item.clone() }]).await?;
+    live.publish(&url, live_session(&req), ComparisonChange::item_saved(item.clone()));
+    Ok(HttpResponse::Ok().json(item))
+}
+
+// Query parameters of the edit history
+#[cfg(feature = "ssr")]
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct HistoryQuery {
+    /// Only list the changes of this item
+    pub item_id: Option<String>,
+    /// Only list revisions older than this revision ID, for the next page
+    pub before: Option<i64>,
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/history",
+    tag = "items",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL"), HistoryQuery),
+    responses(
+        (status = 200, description = "Page of the changes to the items and properties, newest first", body = [Revision]),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 500, description = "Failed to fetch the history", body = ErrorResponse)
+    )
+)]
+pub async fn get_history(
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+    query: web::Query<HistoryQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let db = db.read().await;
+    ensure_url_exists(&db, &url).await?;
+    let history = db.get_history(&url, query.item_id.as_deref(), query.before).await?;
+    Ok(HttpResponse::Ok().json(history))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/feed.atom",
+    tag = "items",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Atom feed of the newest changes to the items and properties, one entry per revision of the history", content_type = "application/atom+xml", body = String),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 500, description = "Failed to fetch the history", body = ErrorResponse)
+    )
+)]
+pub async fn get_history_feed(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let db = db.read().await;
+    ensure_url_exists(&db, &url).await?;
+    let history = db.get_history(&url, None, None).await?;
+    let title = db.get_metadata(&url).await?.title;
+    let title = if title.is_empty() { default_title(&url) } else { title };
+    let feed_url = {
+        let conn = req.connection_info();
+        format!("{}://{}{}", conn.scheme(), conn.host(), req.uri().path())
+    };
+    let atom = history_atom(&url, &title, &feed_url, &history, chrono::Utc::now().timestamp());
+    Ok(HttpResponse::Ok().content_type("application/atom+xml; charset=utf-8").body(atom))
+}
+
+// Query parameters of the exports
+#[cfg(feature = "ssr")]
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct ExportQuery {
+    /// Keep the values quantities were entered as in a column next to the converted ones, in the table formats
+    #[serde(default)]
+    pub original: bool,
+    /// Serve the export to show in a page, such as a frame, rather than to download
+    #[serde(default)]
+    pub embed: bool,
+    /// Include the items' ratings in the json format, reviewers told apart by labels rather than their IDs
+    #[serde(default)]
+    pub reviews: bool,
+    /// Expiry of a signed link, in seconds since the Unix epoch
+    pub expires: Option<i64>,
+    /// Signature of a signed link, from POST /api/urls/{url}/signed-links
+    pub signature: Option<String>,
+}
+
+// Exports of protected comparisons on private instances need an edit token or a signed link
+// to the export. A signature sent along is checked on any comparison
+#[cfg(feature = "ssr")]
+async fn ensure_can_export(
+    db: &Database,
+    instance: &InstanceConfig,
+    url: &str,
+    format: Option<&str>,
+    query: &ExportQuery,
+    req: &actix_web::HttpRequest,
+) -> Result<(), ApiError> {
+    if let Some(signature) = &query.signature {
+        let expires = query.expires.unwrap_or_default();
+        let key = db.get_link_signing_key().await?;
+        let signed = format.zip(key).is_some_and(|(format, key)| {
+            verify_link_signature(&key, url, format, query.embed, expires, signature)
+        });
+        if !signed {
+            return Err(ApiError::Forbidden("This link is not valid for this export".into()));
+        }
+        if expires < chrono::Utc::now().timestamp() {
+            return Err(ApiError::Forbidden("This link has expired".into()));
+        }
+        return Ok(());
+    }
+    if instance.settings().private {
+        let access = request_access(db, url, req).await?;
+        if access.protected && access.role.is_none() {
+            return Err(ApiError::Forbidden(
+                "Exports of protected comparisons on a private instance need an edit token or a signed link".into(),
+            ));
         }
     }
+    Ok(())
 }
 
+// The comparison as the exporters take it, headed by the Wikidata labels the cache knows,
+// with its title, or its path when it has none, for headings
 #[cfg(feature = "ssr")]
-pub async fn get_items_by_url(
-    db: web::Data<Arc<Mutex<Database>>>,
-    query: web::Query<HashMap<String, String>>,
-) -> HttpResponse {
-    let url = query.get("url").unwrap_or(&String::new()).to_string();
-    let db = db.lock().await;
-    match db.get_items_by_url(&url).await {
-        Ok(items) => HttpResponse::Ok().json(items),
-        Err(err) => {
-            leptos::logging::error!("Failed to fetch items by URL: {:?}", err);
-            HttpResponse::InternalServerError().body("Failed to fetch items by URL")
+async fn export_source(
+    db: &Database,
+    proxy: &WikidataProxy,
+    metrics: &ComparisonMetrics,
+    url: &str,
+    query: &ExportQuery,
+) -> Result<ExportSource, ApiError> {
+    ensure_url_exists(db, url).await?;
+    let now = chrono::Utc::now().timestamp();
+    let comparison = db.export_comparison(url).await?;
+    let property_ids: Vec<String> = comparison.selected_properties.iter().filter(|property| is_property_id(property)).cloned().collect();
+    let mut labels = proxy.cached_labels(db, &property_ids, now).await?;
+    metrics.record_cache_misses(url, property_ids.len() - labels.len());
+    let title = if comparison.metadata.title.is_empty() { default_title(url) } else { comparison.metadata.title.clone() };
+    let property_overrides = db.get_property_overrides(url).await?;
+    // Labels the comparison gives its properties head the columns instead of Wikidata's
+    for (property, overrides) in &property_overrides {
+        if let Some(label) = &overrides.label {
+            labels.insert(property.clone(), label.clone());
         }
     }
+    let mut reviews = if query.reviews { db.get_reviews(url).await? } else { Vec::new() };
+    for review in reviews.iter_mut() {
+        review.reviewer = reviewer_label(&review.reviewer);
+    }
+    let export = ComparisonExport {
+        version: COMPARISON_EXPORT_VERSION,
+        exported_at: now,
+        comparison,
+        property_settings: db.get_property_settings(url).await?,
+        property_overrides,
+        reviews,
+    };
+    Ok(ExportSource { export, title, labels, original: query.original })
+}
+
+#[cfg(feature = "ssr")]
+fn export_download(exporter: &dyn Exporter, source: &ExportSource, embed: bool) -> HttpResponse {
+    let disposition = if embed { "inline" } else { "attachment" };
+    HttpResponse::Ok()
+        .content_type(exporter.content_type())
+        .insert_header(("Content-Disposition", format!("{}; filename=\"{}\"", disposition, exporter.format().file_name())))
+        .body(exporter.export(source))
 }
 
 #[cfg(feature = "ssr")]
-pub async fn delete_item_by_url(
-    db: web::Data<Arc<Mutex<Database>>>,
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/export.{format}",
+    tag = "comparisons",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        ("format" = String, Path, description = "Name of the format: json, csv, xlsx, md or html"),
+        ExportQuery
+    ),
+    responses(
+        (status = 200, description = "The comparison in the format. json is a self-contained document with the items, selected properties in display order, property settings and metadata, and the ratings when asked for, for import.json. The others are tables with a row per item, quantities in the comparison's unit system, md and html headed by the title and description", content(
+            ("application/json" = ComparisonExport),
+            ("text/csv" = String),
+            ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" = Vec<u8>),
+            ("text/markdown" = String),
+            ("text/html" = String)
+        )),
+        (status = 403, description = "Signed link invalid or expired, or protected comparison of a private instance without an edit token or signed link", body = ErrorResponse),
+        (status = 404, description = "Unknown URL or format", body = ErrorResponse)
+    )
+)]
+pub async fn export_comparison(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    metrics: web::Data<ComparisonMetrics>,
+    instance: web::Data<InstanceConfig>,
+    path: web::Path<(String, String)>, // (url, format)
+    query: web::Query<ExportQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let (url, format) = path.into_inner();
+    let exporter = exporter(&format).ok_or_else(|| ApiError::NotFound(format!("Export format {}", format)))?;
+    let db = db.read().await;
+    ensure_can_export(&db, &instance, &url, Some(&format), &query, &req).await?;
+    let source = export_source(&db, &proxy, &metrics, &url, &query).await?;
+    Ok(export_download(exporter, &source, query.embed))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/export",
+    tag = "comparisons",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        ("Accept" = Option<String>, Header, description = "Media types of the export formats, JSON when missing or */*"),
+        ExportQuery
+    ),
+    responses(
+        (status = 200, description = "The comparison in the format the Accept header prefers, as from export.{format}", content(
+            ("application/json" = ComparisonExport),
+            ("text/csv" = String),
+            ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" = Vec<u8>),
+            ("text/markdown" = String),
+            ("text/html" = String)
+        )),
+        (status = 403, description = "Protected comparison of a private instance without an edit token. Signed links are only valid at export.{format}", body = ErrorResponse),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 406, description = "No export format has an accepted media type", body = ErrorResponse)
+    )
+)]
+pub async fn negotiate_export(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    metrics: web::Data<ComparisonMetrics>,
+    instance: web::Data<InstanceConfig>,
     url: web::Path<String>,
-    item_id: web::Path<String>,
-) -> HttpResponse {
-    let db = db.lock().await;
-    match db.delete_item_by_url(&url, &item_id).await {
-        Ok(_) => HttpResponse::Ok().body("Item deleted"),
-        Err(err) => {
-            leptos::logging::error!("Failed to delete item by URL: {:?}", err);
-            HttpResponse::InternalServerError().body("Failed to delete item by URL")
-        }
+    query: web::Query<ExportQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let accept = req.headers().get("Accept").and_then(|accept| accept.to_str().ok()).unwrap_or("*/*");
+    let exporter = negotiate(accept).ok_or_else(|| {
+        let types: Vec<&str> = EXPORTERS.iter().map(|exporter| exporter.content_type()).collect();
+        ApiError::NotAcceptable(format!("No export format is accepted, the formats are {}", types.join(", ")))
+    })?;
+    let db = db.read().await;
+    ensure_can_export(&db, &instance, &url, None, &query, &req).await?;
+    let source = export_source(&db, &proxy, &metrics, &url, &query).await?;
+    let mut response = export_download(exporter, &source, query.embed);
+    // Caches keep a copy per format
+    response.headers_mut().insert(actix_web::http::header::VARY, actix_web::http::header::HeaderValue::from_static("Accept"));
+    Ok(response)
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/signed-links",
+    tag = "comparisons",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    request_body = SignedLinkRequest,
+    responses(
+        (status = 200, description = "Link to export.{format} signed by the server, which opens the export until it expires without an edit token", body = SignedLink),
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Unknown URL or format", body = ErrorResponse),
+        (status = 422, description = "Lifetime out of range, or embed of a format other than html", body = ErrorResponse)
+    )
+)]
+pub async fn create_signed_link(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+    request: ValidJson<SignedLinkRequest>,
+) -> Result<HttpResponse, ApiError> {
+    if let Some(problem) = request.problem() {
+        return Err(ApiError::validation(problem));
+    }
+    let format = &request.format;
+    exporter(format).ok_or_else(|| ApiError::NotFound(format!("Export format {}", format)))?;
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+
+    let now = chrono::Utc::now().timestamp();
+    let key = db.link_signing_key(&format!("{}{}", new_edit_token(), new_edit_token()), now).await?;
+    let expires_at = now + request.lifetime_secs();
+    let signature = link_signature(&key, &url, format, request.embed, expires_at);
+    let mut path = format!(
+        "{}?expires={}&signature={}",
+        comparison_api_path(&url, &[&format!("export.{}", format)]),
+        expires_at,
+        signature
+    );
+    if request.embed {
+        path.push_str("&embed=true");
     }
+    server_log!("[API] Signed a link to the {} export of {} until {}", format, url, expires_at);
+    Ok(HttpResponse::Ok().json(SignedLink { path, expires_at }))
 }
 
 #[cfg(feature = "ssr")]
-pub async fn delete_property_by_url(
-    db: web::Data<Arc<Mutex<Database>>>,
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/import.json",
+    tag = "comparisons",
+    params(("url" = String, Path, description = "Percent-encoded URL of the new comparison")),
+    request_body = ComparisonExport,
+    responses(
+        (status = 200, description = "Comparison recreated under the URL, with new item IDs. Ratings are kept when reviews are turned on, each reviewer label of the export becoming a new reviewer", body = ComparisonArchive, headers(
+            ("X-Edit-Token" = String, description = "Owner edit key of the new comparison")
+        )),
+        (status = 409, description = "A comparison already exists at the URL", body = ErrorResponse),
+        (status = 422, description = "Export from a newer version, or an invalid item, title, property setting or rating", body = ErrorResponse)
+    )
+)]
+pub async fn import_comparison(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    instance: web::Data<InstanceConfig>,
+    live: web::Data<LiveUpdates>,
     url: web::Path<String>,
-    property: web::Path<String>,
-) -> HttpResponse {
-    let db = db.lock().await;
-    match db.delete_property_by_url(&url, &property).await {
-        Ok(_) => HttpResponse::Ok().body("Property deleted"),
-        Err(err) => {
-            leptos::logging::error!("Failed to delete property by URL: {:?}", err);
-            HttpResponse::InternalServerError().body("Failed to delete property by URL")
+    export: ValidJson<ComparisonExport>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    let mut export = export.into_inner();
+    if let Some(problem) = export.problem() {
+        return Err(ApiError::validation(problem));
+    }
+    for item in &mut export.comparison.items {
+        item.mark_imported();
+    }
+    // Ratings only come along where reviews are turned on
+    if !instance.settings().features.reviews {
+        export.reviews.clear();
+    }
+    export.comparison.metadata = export.comparison.metadata.trimmed();
+    if let Some(problem) = export.comparison.metadata.problem() {
+        return Err(ApiError::validation(problem));
+    }
+    for settings in export.property_settings.values_mut() {
+        *settings = settings.trimmed();
+        if let Some(problem) = settings.problem() {
+            return Err(ApiError::validation(problem));
+        }
+    }
+    for overrides in export.property_overrides.values_mut() {
+        *overrides = overrides.trimmed();
+        if let Some(problem) = overrides.problem() {
+            return Err(ApiError::validation(problem));
         }
     }
+
+    let db = db.write().await;
+    // Imports only create comparisons, they never merge into an existing one
+    let edit_key = new_edit_token();
+    if !db.claim_url(&url, &hash_edit_token(&edit_key)).await? {
+        return Err(ApiError::Conflict(format!("A comparison already exists at {}, import into a new URL", url)));
+    }
+    let comparison = fill_claimed_comparison(&db, &req, &url, &export, &edit_key).await?;
+    server_log!("[API] Imported {} items from {} into {}", comparison.items.len(), export.comparison.url, url);
+    live.publish(&url, live_session(&req), ComparisonChange::Reload);
+    Ok(write_response(Some(edit_key)).json(comparison))
 }
 
+// Store an export under a URL the request just claimed with `edit_key`, with new item and
+// reviewer IDs, and record its items in the history
 #[cfg(feature = "ssr")]
-pub async fn get_selected_properties(
-    db: web::Data<Arc<Mutex<Database>>>,
-    url: web::Path<String>,
-) -> HttpResponse {
-    let db = db.lock().await;
-    match db.get_selected_properties(&url).await {
-        Ok(properties) => HttpResponse::Ok().json(properties),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string())
+async fn fill_claimed_comparison(
+    db: &Database,
+    req: &actix_web::HttpRequest,
+    url: &str,
+    export: &ComparisonExport,
+    edit_key: &str,
+) -> Result<ComparisonArchive, ApiError> {
+    let ComparisonExport { comparison, reviews, .. } = export.imported_as(url);
+    db.restore_comparison(&comparison).await?;
+    for review in &reviews {
+        db.set_review(url, &review.item_id, &review.reviewer, review.rating, review.created_at).await?;
     }
+    for (property, settings) in &export.property_settings {
+        db.set_property_settings(url, property, settings).await?;
+    }
+    for (property, overrides) in &export.property_overrides {
+        db.set_property_override(url, property, overrides).await?;
+    }
+    let changes = saved_item_changes(db, url, &comparison.items).await?;
+    record_history(db, url, req, Some(edit_key), &changes).await?;
+    Ok(comparison)
 }
 
+// Copies of one comparison tried before giving up, `-copy` up to `-copy-100`
 #[cfg(feature = "ssr")]
-pub async fn add_selected_property(
-    db: web::Data<Arc<Mutex<Database>>>,
+const MAX_COPY_ATTEMPTS: usize = 100;
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/clone",
+    tag = "comparisons",
+    params(("url" = String, Path, description = "Percent-encoded URL of the comparison to copy")),
+    responses(
+        (status = 200, description = "Copy of the items, selected properties, property settings and labels, title, description and unit system under a new URL, the source's URL followed by -copy, without the decision", body = ComparisonArchive, headers(
+            ("X-Edit-Token" = String, description = "Owner edit key of the copy")
+        )),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 409, description = "Every URL tried for the copy is taken", body = ErrorResponse)
+    )
+)]
+pub async fn clone_comparison(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
     url: web::Path<String>,
-    property: web::Json<String>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let url = url.into_inner();
-    let property = property.into_inner();
-    
-    let db = db.lock().await;
-    match db.add_selected_property(&url, &property).await {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string())
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    let mut export = ComparisonExport {
+        version: COMPARISON_EXPORT_VERSION,
+        exported_at: chrono::Utc::now().timestamp(),
+        comparison: db.export_comparison(&url).await?,
+        property_settings: db.get_property_settings(&url).await?,
+        property_overrides: db.get_property_overrides(&url).await?,
+        reviews: Vec::new(),
+    };
+    // The copy is a starting point for a decision of its own
+    export.comparison.metadata.decision = None;
+
+    let edit_key = new_edit_token();
+    let key_hash = hash_edit_token(&edit_key);
+    let mut claimed = None;
+    for attempt in 1..=MAX_COPY_ATTEMPTS {
+        let candidate = copy_url(&url, attempt);
+        if db.claim_url(&candidate, &key_hash).await? {
+            claimed = Some(candidate);
+            break;
+        }
     }
-}
\ No newline at end of file
+    let copy = claimed.ok_or_else(|| ApiError::Conflict(format!("{} has too many copies already", url)))?;
+    let comparison = fill_claimed_comparison(&db, &req, &copy, &export, &edit_key).await?;
+    server_log!("[API] Copied {} items from {} to {}", comparison.items.len(), url, copy);
+    Ok(write_response(Some(edit_key)).json(comparison))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/items/{item_id}/values/{property}",
+    tag = "items",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        ("item_id" = String, Path, description = "Item ID"),
+        ("property" = String, Path, description = "Property ID, e.g. P2067")
+    ),
+    responses(
+        (status = 200, description = "Full value of the property, which item lists may only show a preview of", body = PropertyValue),
+        (status = 404, description = "Unknown URL, item or property value", body = ErrorResponse),
+        (status = 500, description = "Failed to fetch the value", body = ErrorResponse)
+    )
+)]
+pub async fn get_property_value(
+    db: web::Data<Arc<RwLock<Database>>>,
+    path: web::Path<(String, String, String)>, // (url, item_id, property)
+) -> Result<HttpResponse, ApiError> {
+    let (url, item_id, property) = path.into_inner();
+    let property = canonical_property(&property);
+    let db = db.read().await;
+    ensure_url_exists(&db, &url).await?;
+    let value = db
+        .get_property_value(&url, &item_id, &property)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Value of {} for item {}", property, item_id)))?;
+    Ok(HttpResponse::Ok().json(PropertyValue { item_id, property, value }))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/reusable/{wikidata_id}",
+    tag = "items",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL, left out of the lookup"),
+        ("wikidata_id" = String, Path, description = "Wikidata item ID, e.g. Q3962")
+    ),
+    responses(
+        (status = 200, description = "Values other comparisons on this instance hold for the item, to copy instead of refetching Wikidata", body = ReusableValues),
+        (status = 404, description = "No other comparison has the item", body = ErrorResponse),
+        (status = 422, description = "Invalid Wikidata ID", body = ErrorResponse)
+    )
+)]
+pub async fn get_reusable_values(
+    db: web::Data<Arc<RwLock<Database>>>,
+    path: web::Path<(String, String)>, // (url, wikidata_id)
+) -> Result<HttpResponse, ApiError> {
+    let (url, wikidata_id) = path.into_inner();
+    if !wikidata_id.starts_with('Q') || !is_entity_id(&wikidata_id) {
+        return Err(ApiError::validation(format!("{} is not a Wikidata item ID", wikidata_id)));
+    }
+    let reusable = db
+        .read()
+        .await
+        .get_reusable_values(&url, &wikidata_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Values of {} in other comparisons", wikidata_id)))?;
+    Ok(HttpResponse::Ok().json(reusable))
+}
+
+// Shared client for reading public events, connected on first use
+#[cfg(feature = "ssr")]
+async fn reader_client(reader: &NostrReader) -> Result<&NostrClient, ApiError> {
+    reader
+        .client()
+        .await
+        .map_err(|e| ApiError::Upstream(format!("Failed to connect to relays: {}", e)))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/items/{item_id}/tip",
+    tag = "items",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        ("item_id" = String, Path, description = "Item ID")
+    ),
+    request_body = TipRequest,
+    responses(
+        (status = 200, description = "Invoice for the tip", body = TipInvoice),
+        (status = 403, description = "Tips are turned off", body = ErrorResponse),
+        (status = 404, description = "Unknown item", body = ErrorResponse),
+        (status = 422, description = "Invalid amount or no lightning address to tip", body = ErrorResponse),
+        (status = 502, description = "Lightning service or relays failed", body = ErrorResponse)
+    )
+)]
+pub async fn tip_item(
+    db: web::Data<Arc<RwLock<Database>>>,
+    reader: web::Data<NostrReader>,
+    instance: web::Data<InstanceConfig>,
+    path: web::Path<(String, String)>, // (url, item_id)
+    request: ValidJson<TipRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let (url, item_id) = path.into_inner();
+    ensure_feature(instance.settings().features.tips, "Tips")?;
+    if request.amount_sats == 0 {
+        return Err(ApiError::validation("Tip amount must be at least 1 sat"));
+    }
+
+    let item = db
+        .write()
+        .await
+        .get_items_by_url(&url)
+        .await?
+        .into_iter()
+        .find(|item| item.id == item_id)
+        .ok_or_else(|| ApiError::NotFound("Item".into()))?;
+    let recipient = item
+        .tip_recipient
+        .as_deref()
+        .map(str::trim)
+        .filter(|recipient| !recipient.is_empty())
+        .ok_or_else(|| ApiError::validation("Item has no lightning address or Nostr public key to tip"))?;
+    server_log!("[API] Tip of {} sats for item {} to {}", request.amount_sats, item_id, recipient);
+
+    // Lightning addresses are used as they are, public keys are resolved
+    // through the lightning address of their Nostr profile
+    let (lightning_address, public_key) = if parse_lightning_address(recipient).is_some() {
+        (recipient.to_string(), None)
+    } else {
+        let public_key = nostr_sdk::PublicKey::parse(recipient).map_err(|_| {
+            ApiError::validation(format!("{} is neither a lightning address nor a Nostr public key", recipient))
+        })?;
+        let address = reader_client(&reader)
+            .await?
+            .fetch_lightning_address(public_key)
+            .await?
+            .ok_or_else(|| ApiError::validation("The Nostr profile has no lightning address"))?;
+        (address, Some(public_key))
+    };
+
+    // Send a zap request when the recipient is known on Nostr and the service supports zaps
+    let service = fetch_pay_service(&lightning_address).await?;
+    let comment = request.comment.as_deref().unwrap_or_default();
+    let zap_request = match public_key {
+        Some(public_key) if service.allows_nostr => {
+            let amount_msat = request.amount_sats.saturating_mul(MSAT_PER_SAT);
+            Some(nostr_sdk::JsonUtil::as_json(&build_zap_request(public_key, reader.relays(), amount_msat, comment)?))
+        }
+        _ => None,
+    };
+    let invoice = request_invoice(&service, request.amount_sats, request.comment.as_deref(), zap_request.as_deref()).await?;
+
+    Ok(HttpResponse::Ok().json(TipInvoice {
+        invoice,
+        lightning_address,
+        zap: zap_request.is_some(),
+    }))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    put,
+    path = "/api/urls/{url}/items/{item_id}/review",
+    tag = "items",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        ("item_id" = String, Path, description = "Item ID")
+    ),
+    request_body = ReviewRequest,
+    responses(
+        (status = 200, description = "Rating stored, with the item's updated average", body = RatingSummary),
+        (status = 403, description = "Reviews are turned off", body = ErrorResponse),
+        (status = 404, description = "Unknown item", body = ErrorResponse),
+        (status = 422, description = "Rating outside 1-5 or missing reviewer", body = ErrorResponse)
+    )
+)]
+pub async fn review_item(
+    db: web::Data<Arc<RwLock<Database>>>,
+    instance: web::Data<InstanceConfig>,
+    path: web::Path<(String, String)>, // (url, item_id)
+    request: ValidJson<ReviewRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let (url, item_id) = path.into_inner();
+    ensure_feature(instance.settings().features.reviews, "Reviews")?;
+    if !(MIN_RATING..=MAX_RATING).contains(&request.rating) {
+        return Err(ApiError::validation(format!(
+            "Rating must be between {} and {} stars",
+            MIN_RATING, MAX_RATING
+        )));
+    }
+    if request.reviewer_id.trim().is_empty() {
+        return Err(ApiError::validation("Reviewer ID must not be empty"));
+    }
+
+    let db = db.write().await;
+    db.set_review(&url, &item_id, &request.reviewer_id, request.rating, chrono::Utc::now().timestamp())
+        .await
+        .map_err(|err| match err {
+            rusqlite::Error::QueryReturnedNoRows => ApiError::NotFound("Item".into()),
+            err => err.into(),
+        })?;
+    let summary = db
+        .get_rating_summaries(&url, Some(&request.reviewer_id))
+        .await?
+        .remove(&item_id)
+        .unwrap_or_default();
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+// Query parameters of the rating summary endpoint
+#[cfg(feature = "ssr")]
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct RatingQuery {
+    /// Reviewer whose own ratings are included as `mine`
+    pub reviewer_id: Option<String>,
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/ratings",
+    tag = "items",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        RatingQuery
+    ),
+    responses(
+        (status = 200, description = "Rating summaries by item ID, unreviewed items are left out", body = HashMap<String, RatingSummary>)
+    )
+)]
+pub async fn get_ratings(
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+    query: web::Query<RatingQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let summaries = db
+        .read()
+        .await
+        .get_rating_summaries(&url, query.reviewer_id.as_deref())
+        .await?;
+    Ok(HttpResponse::Ok().json(summaries))
+}
+
+// Label telling a reviewer apart, the start of a hash of their ID. Never the ID itself,
+// which lets anyone rate in their name
+#[cfg(feature = "ssr")]
+fn reviewer_label(reviewer_id: &str) -> String {
+    format!("reviewer {}", &hash_edit_token(reviewer_id)[..8])
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/reviews.atom",
+    tag = "items",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Atom feed of the newest reviews across the comparison's items, reviewers told apart by the start of a hash of their ID", content_type = "application/atom+xml", body = String),
+        (status = 404, description = "Unknown URL", body = ErrorResponse)
+    )
+)]
+pub async fn get_reviews_feed(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let db = db.read().await;
+    ensure_url_exists(&db, &url).await?;
+    let mut reviews = db.get_recent_reviews(&url, REVIEW_FEED_SIZE).await?;
+    for review in reviews.iter_mut() {
+        review.reviewer = reviewer_label(&review.reviewer);
+    }
+    let title = db.get_metadata(&url).await?.title;
+    let title = if title.is_empty() { default_title(&url) } else { title };
+    let feed_url = {
+        let conn = req.connection_info();
+        format!("{}://{}{}", conn.scheme(), conn.host(), req.uri().path())
+    };
+    let atom = reviews_atom(&url, &title, &feed_url, &reviews, chrono::Utc::now().timestamp());
+    Ok(HttpResponse::Ok().content_type("application/atom+xml; charset=utf-8").body(atom))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/access",
+    tag = "access",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Whether the comparison is protected and the role of the request's edit token", body = EditAccess)
+    )
+)]
+pub async fn get_edit_access(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let access = request_access(&*db.read().await, &url, &req).await?;
+    Ok(HttpResponse::Ok().json(access))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/access",
+    tag = "access",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Comparison protected, with the owner's edit token", body = EditToken),
+        (status = 403, description = "The comparison holds items, and neither the admin token nor an API key allowed to edit it was sent", body = ErrorResponse),
+        (status = 409, description = "The comparison is already protected", body = ErrorResponse)
+    )
+)]
+pub async fn protect_comparison(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    let db = db.write().await;
+    // Everyone may edit an unprotected comparison, so protecting one that holds items would
+    // take it from the people editing it. That takes more than the edit access anyone has
+    let trusted = ensure_admin(&req).is_ok() || request_api_key(&req).is_some_and(|key| key.allows(&url));
+    if !trusted && !db.get_items_by_url(&url).await?.is_empty() {
+        return Err(ApiError::Forbidden(
+            "Comparisons holding items can only be protected with the admin token or an API key allowed to edit them".into(),
+        ));
+    }
+    let token = new_edit_token();
+    let protected = db.protect_comparison(&url, &hash_edit_token(&token)).await?;
+    if !protected {
+        return Err(ApiError::Conflict("The comparison is already protected".into()));
+    }
+    server_log!("[API] Protected {}", url);
+    Ok(HttpResponse::Ok().json(EditToken { token, role: EditorRole::Owner }))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/collaborators",
+    tag = "access",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Edit token for a new collaborator", body = EditToken),
+        (status = 403, description = "Only the owner's edit token may invite collaborators", body = ErrorResponse)
+    )
+)]
+pub async fn invite_collaborator(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    let db = db.write().await;
+    let access = db.get_edit_access(&url, request_token_hash(&req).as_deref()).await?;
+    if access.role != Some(EditorRole::Owner) {
+        return Err(ApiError::Forbidden("Only the owner of the comparison can invite collaborators".into()));
+    }
+    let token = new_edit_token();
+    db.add_editor(&url, &hash_edit_token(&token), EditorRole::Collaborator, chrono::Utc::now().timestamp())
+        .await?;
+    server_log!("[API] Invited a collaborator to {}", url);
+    Ok(HttpResponse::Ok().json(EditToken { token, role: EditorRole::Collaborator }))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    put,
+    path = "/api/urls/{url}/visibility",
+    tag = "access",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    request_body = VisibilitySetting,
+    responses(
+        (status = 200, description = "Visibility of the comparison as saved. Comparisons that stop being public are removed from the directory and no longer published", body = VisibilitySetting),
+        (status = 403, description = "Only the owner's edit token may change the visibility", body = ErrorResponse),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 502, description = "Relays rejected the removal from the directory, the visibility is unchanged", body = ErrorResponse),
+        (status = 503, description = "The comparison is listed and directory publishing is disabled, the visibility is unchanged", body = ErrorResponse)
+    )
+)]
+pub async fn set_visibility(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    reader: web::Data<NostrReader>,
+    config: web::Data<Config>,
+    url: web::Path<String>,
+    setting: ValidJson<VisibilitySetting>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    let access = db.get_edit_access(&url, request_token_hash(&req).as_deref()).await?;
+    if access.role != Some(EditorRole::Owner) {
+        return Err(ApiError::Forbidden("Only the owner of the comparison can change its visibility".into()));
+    }
+    // The directory only lists public comparisons, the listing goes before the visibility changes
+    if setting.visibility != Visibility::Public && db.get_listing(&url).await?.is_some() {
+        directory_client(&config, reader.relays()).await?.retract_announcement(&url).await?;
+        db.remove_listing(&url).await?;
+        server_log!("[API] Removed {} from the directory", url);
+    }
+    if !db.set_visibility(&url, setting.visibility).await? {
+        return Err(ApiError::UrlNotFound(url));
+    }
+    server_log!("[API] {} is now {}", url, setting.visibility.as_str());
+    Ok(HttpResponse::Ok().json(setting.into_inner()))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/snapshots",
+    tag = "snapshots",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Snapshot taken of the comparison as it is now", body = SnapshotLink),
+        (status = 403, description = "Snapshots are turned off, or the comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 429, description = "Over the snapshots per minute of the client's IP", body = ErrorResponse)
+    )
+)]
+pub async fn create_snapshot(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    metrics: web::Data<ComparisonMetrics>,
+    instance: web::Data<InstanceConfig>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    ensure_feature(instance.settings().features.snapshots, "Snapshots")?;
+    let db = db.write().await;
+    let comparison = stored_comparison(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+    let selected_properties = comparison.properties().await?;
+    let items = filter_selected_properties(comparison.items().await?, &selected_properties);
+
+    // Freeze the labels of Wikidata properties too, as far as the server knows them
+    let now = chrono::Utc::now().timestamp();
+    let property_ids: Vec<String> = selected_properties
+        .iter()
+        .filter(|property| is_property_id(property))
+        .cloned()
+        .collect();
+    let property_labels = proxy.cached_labels(&db, &property_ids, now).await?;
+    metrics.record_cache_misses(&url, property_ids.len() - property_labels.len());
+
+    let snapshot = Snapshot {
+        id: uuid::Uuid::new_v4().simple().to_string(),
+        url,
+        created_at: now,
+        items,
+        selected_properties,
+        property_labels,
+    };
+    db.insert_snapshot(&snapshot).await?;
+    server_log!("[API] Took snapshot {} of {}", snapshot.id, snapshot.url);
+    Ok(HttpResponse::Ok().json(SnapshotLink {
+        path: snapshot_path(&snapshot.id),
+        id: snapshot.id,
+    }))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/snapshots/{snapshot_id}",
+    tag = "snapshots",
+    params(("snapshot_id" = String, Path, description = "Snapshot ID")),
+    responses(
+        (status = 200, description = "The snapshot", body = Snapshot),
+        (status = 404, description = "Unknown snapshot", body = ErrorResponse)
+    )
+)]
+pub async fn get_snapshot(
+    db: web::Data<Arc<RwLock<Database>>>,
+    snapshot_id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let snapshot = db
+        .read()
+        .await
+        .get_snapshot(&snapshot_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Snapshot".into()))?;
+    Ok(HttpResponse::Ok().json(snapshot))
+}
+
+#[cfg(feature = "ssr")]
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct CardQuery {
+    /// `html` for an HTML fragment instead of JSON
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+// Seconds embedding sites and their visitors may cache a card
+#[cfg(feature = "ssr")]
+const CARD_MAX_AGE_SECS: u32 = 300;
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/instance",
+    tag = "public",
+    responses(
+        (status = 200, description = "Wikidata language, knowledge source, private mode and features of the instance", body = InstanceInfo)
+    )
+)]
+pub async fn get_instance_info(instance: web::Data<InstanceConfig>, proxy: web::Data<WikidataProxy>) -> HttpResponse {
+    let info = InstanceInfo {
+        wikidata_fixtures: proxy.config.fixture_mode.is_some(),
+        knowledge_source: proxy.config.knowledge_source,
+        ..instance.settings().info()
+    };
+    HttpResponse::Ok().json(info)
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/public/items/{wikidata_id}/card",
+    tag = "public",
+    params(
+        ("wikidata_id" = String, Path, description = "Wikidata ID of the item, e.g. Q3962"),
+        CardQuery
+    ),
+    responses(
+        (status = 200, description = "Spec card of the item built from all comparisons having it, as JSON or as an HTML fragment", body = SpecCard),
+        (status = 404, description = "No comparison on this instance has the item", body = ErrorResponse),
+        (status = 422, description = "Invalid Wikidata ID or format", body = ErrorResponse)
+    )
+)]
+pub async fn get_item_card(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    wikidata_id: web::Path<String>,
+    query: web::Query<CardQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let wikidata_id = wikidata_id.into_inner();
+    if !wikidata_id.starts_with('Q') || !is_entity_id(&wikidata_id) {
+        return Err(ApiError::validation(format!("{} is not a Wikidata item ID", wikidata_id)));
+    }
+    let html = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("html") => true,
+        Some(format) => return Err(ApiError::validation(format!("Unknown format {}, use json or html", format))),
+    };
+
+    let db = db.read().await;
+    let mut card = db
+        .get_spec_card(&wikidata_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Item {}", wikidata_id)))?;
+    // Label Wikidata properties with what the cache knows, without asking Wikidata
+    let property_ids: Vec<String> =
+        card.specs.iter().map(|spec| spec.property.clone()).filter(|property| is_property_id(property)).collect();
+    let labels = proxy.cached_labels(&db, &property_ids, chrono::Utc::now().timestamp()).await?;
+    for Spec { property, label, .. } in card.specs.iter_mut() {
+        if let Some(known) = labels.get(property) {
+            *label = known.clone();
+        }
+    }
+
+    // Embeddable from any site
+    let mut response = HttpResponse::Ok();
+    response
+        .insert_header((actix_web::http::header::ACCESS_CONTROL_ALLOW_ORIGIN, "*"))
+        .insert_header((actix_web::http::header::CACHE_CONTROL, format!("public, max-age={}", CARD_MAX_AGE_SECS)));
+    if html {
+        let conn = req.connection_info();
+        let source = format!("{}://{}/", conn.scheme(), conn.host());
+        Ok(response.content_type("text/html; charset=utf-8").body(card.to_html(&source)))
+    } else {
+        Ok(response.json(card))
+    }
+}
+
+// Publish the saved state of a comparison to its relays, if it enabled publishing
+#[cfg(feature = "ssr")]
+async fn publish_saved_comparison(db: &Database, publisher: &ComparisonPublisher, url: &str) -> Result<(), ApiError> {
+    if !publisher.is_available() {
+        return Ok(());
+    }
+    // Unlisted and private comparisons aren't published, whatever their settings
+    if db.get_edit_access(url, None).await?.visibility != Visibility::Public {
+        return Ok(());
+    }
+    let settings = db.get_publishing_settings(url).await?;
+    if settings.enabled {
+        publisher.publish(&settings.relays, db.export_comparison(url).await?);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/publishing",
+    tag = "directory",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Nostr publishing of the comparison, disabled when never set", body = PublishingSettings)
+    )
+)]
+pub async fn get_publishing_settings(
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let settings = db.read().await.get_publishing_settings(&url).await?;
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    put,
+    path = "/api/urls/{url}/publishing",
+    tag = "directory",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    request_body = PublishingSettings,
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid, or publishing is enabled on a comparison that isn't public", body = ErrorResponse),
+        (status = 200, description = "Settings stored, as cleaned up by the server. Enabling publishing publishes the comparison as saved so far", body = PublishingSettings),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 422, description = "No relay to publish to, too many relays, or an invalid relay URL", body = ErrorResponse),
+        (status = 503, description = "Publishing is unavailable, COMPAREWARE_NOSTR_SECRET_KEY is not set", body = ErrorResponse)
+    )
+)]
+pub async fn set_publishing_settings(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    publisher: web::Data<ComparisonPublisher>,
+    url: web::Path<String>,
+    settings: ValidJson<PublishingSettings>,
+) -> Result<HttpResponse, ApiError> {
+    let settings = settings.trimmed();
+    if let Some(problem) = settings.problem() {
+        return Err(ApiError::validation(problem));
+    }
+    if settings.enabled && !publisher.is_available() {
+        return Err(ApiError::NotConfigured(
+            "Publishing comparisons needs the workspace key, set COMPAREWARE_NOSTR_SECRET_KEY".into(),
+        ));
+    }
+
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+    if settings.enabled && db.get_edit_access(&url, None).await?.visibility != Visibility::Public {
+        return Err(ApiError::Forbidden("Only public comparisons can be published".into()));
+    }
+    let was_enabled = db.get_publishing_settings(&url).await?.enabled;
+    db.set_publishing_settings(&url, &settings).await?;
+    // Catch the relays up on the items saved before publishing was enabled
+    if settings.enabled && !was_enabled {
+        server_log!("[API] Publishing {} to {} relays", url, settings.relays.len());
+        publisher.publish(&settings.relays, db.export_comparison(&url).await?);
+    }
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/mirror",
+    tag = "directory",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Comparison whose published items are merged into this one, disabled when never set", body = MirrorSettings)
+    )
+)]
+pub async fn get_mirror_settings(
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let settings = db.read().await.get_mirror_settings(&url).await?;
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    put,
+    path = "/api/urls/{url}/mirror",
+    tag = "directory",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    request_body = MirrorSettings,
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Settings stored, as cleaned up by the server. Enabled mirrors subscribe to the relays right away", body = MirrorSettings),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 422, description = "Missing source URL, invalid publisher key, or invalid relays", body = ErrorResponse)
+    )
+)]
+pub async fn set_mirror_settings(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    mirrors: web::Data<ComparisonMirrors>,
+    url: web::Path<String>,
+    settings: ValidJson<MirrorSettings>,
+) -> Result<HttpResponse, ApiError> {
+    let settings = settings.trimmed();
+    if let Some(problem) = settings.problem() {
+        return Err(ApiError::validation(problem));
+    }
+    if settings.enabled && nostr_sdk::PublicKey::from_hex(&settings.author).is_err() {
+        return Err(ApiError::validation(format!("{} is not a valid public key", settings.author)));
+    }
+
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+    db.set_mirror_settings(&url, &settings).await?;
+    mirrors.apply(&url, &settings);
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+// Announcements are signed with the workspace key. Publishing is opt-in,
+// enabled by the publish_directory setting
+#[cfg(feature = "ssr")]
+async fn directory_client(config: &Config, relays: &[String]) -> Result<NostrClient, ApiError> {
+    if !config.publish_directory {
+        return Err(ApiError::NotConfigured(
+            "Directory publishing is disabled, set COMPAREWARE_PUBLISH_DIRECTORY=true".into(),
+        ));
+    }
+    let keys = workspace_keys(config)?;
+    NostrClient::with_keys(keys, relays)
+        .await
+        .map_err(|e| ApiError::Upstream(format!("Failed to connect to relays: {}", e)))
+}
+
+// Path of a comparison URL on its instance, e.g. "/laptops" for "https://host/laptops"
+#[cfg(feature = "ssr")]
+fn comparison_slug(url: &str) -> String {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    match without_scheme.find('/') {
+        Some(index) => without_scheme[index..].to_string(),
+        None => "/".to_string(),
+    }
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/metadata",
+    tag = "comparisons",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Title, description and decision of the comparison, empty when none were set", body = ComparisonMetadata)
+    )
+)]
+pub async fn get_metadata(
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let metadata = db.read().await.get_metadata(&url).await?;
+    Ok(HttpResponse::Ok().json(metadata))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    put,
+    path = "/api/urls/{url}/metadata",
+    tag = "comparisons",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    request_body = ComparisonMetadata,
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Title and description stored, as trimmed by the server, returned with the decision they leave as is", body = ComparisonMetadata, headers(
+            ("X-Edit-Token" = String, description = "Owner edit key, only sent when the request created the comparison")
+        )),
+        (status = 422, description = "Title or description too long, or title with control characters", body = ErrorResponse)
+    )
+)]
+pub async fn set_metadata(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+    metadata: ValidJson<ComparisonMetadata>,
+) -> Result<HttpResponse, ApiError> {
+    let metadata = metadata.trimmed();
+    if let Some(problem) = metadata.problem() {
+        return Err(ApiError::validation(problem));
+    }
+
+    let db = db.write().await;
+    let edit_key = claim_or_ensure_can_edit(&db, &url, &req).await?;
+    db.set_metadata(&url, &metadata).await?;
+    Ok(write_response(edit_key).json(db.get_metadata(&url).await?))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    put,
+    path = "/api/urls/{url}/decision",
+    tag = "comparisons",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    request_body = DecisionRequest,
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Comparison marked as decided, dated now", body = ComparisonMetadata),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 422, description = "Item missing from the comparison, or rationale too long", body = ErrorResponse)
+    )
+)]
+pub async fn set_decision(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+    decision: ValidJson<DecisionRequest>,
+) -> Result<HttpResponse, ApiError> {
+    if let Some(problem) = decision.problem() {
+        return Err(ApiError::validation(problem));
+    }
+    let DecisionRequest { item_id, rationale } = decision.into_inner();
+    let decision = Decision { item_id, decided_at: chrono::Utc::now().timestamp(), rationale: rationale.trim().to_string() };
+
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+    if !db.set_decision(&url, &decision).await? {
+        return Err(ApiError::validation(format!("Item {} is not part of the comparison", decision.item_id)));
+    }
+    server_log!("[API] Decided {} for item {}", url, decision.item_id);
+    Ok(HttpResponse::Ok().json(db.get_metadata(&url).await?))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    delete,
+    path = "/api/urls/{url}/decision",
+    tag = "comparisons",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Comparison reopened", body = ComparisonMetadata),
+        (status = 404, description = "Unknown URL, or a comparison that isn't decided", body = ErrorResponse)
+    )
+)]
+pub async fn clear_decision(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+    if !db.clear_decision(&url).await? {
+        return Err(ApiError::NotFound(format!("Decision of {}", url)));
+    }
+    Ok(HttpResponse::Ok().json(db.get_metadata(&url).await?))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/listing",
+    tag = "directory",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Directory listing of the comparison", body = ComparisonListing),
+        (status = 404, description = "The comparison is not listed", body = ErrorResponse)
+    )
+)]
+pub async fn get_listing(
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let listing = db
+        .read()
+        .await
+        .get_listing(&url)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Listing".into()))?;
+    Ok(HttpResponse::Ok().json(listing))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    put,
+    path = "/api/urls/{url}/listing",
+    tag = "directory",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    request_body = ComparisonListing,
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid, the comparison isn't public or the instance is private", body = ErrorResponse),
+        (status = 200, description = "Comparison announced in the directory", body = ComparisonAnnouncement),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 422, description = "Missing title", body = ErrorResponse),
+        (status = 502, description = "Relays rejected the announcement", body = ErrorResponse),
+        (status = 503, description = "Directory publishing is disabled", body = ErrorResponse)
+    )
+)]
+pub async fn publish_listing(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    reader: web::Data<NostrReader>,
+    config: web::Data<Config>,
+    instance: web::Data<InstanceConfig>,
+    url: web::Path<String>,
+    listing: ValidJson<ComparisonListing>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    if instance.settings().private {
+        return Err(ApiError::Forbidden("Comparisons of a private instance can't be listed in the directory".into()));
+    }
+    let listing = ComparisonListing {
+        title: listing.title.trim().to_string(),
+        category: listing.category.trim().to_string(),
+    };
+    if listing.title.is_empty() {
+        return Err(ApiError::validation("Listing title must not be empty"));
+    }
+
+    let item_count = {
+        let db = db.write().await;
+        ensure_url_exists(&db, &url).await?;
+        let access = request_access(&db, &url, &req).await?;
+        if !access.can_edit() {
+            return Err(ApiError::Forbidden("This comparison is protected, a valid edit token or API key is required".into()));
+        }
+        if access.visibility != Visibility::Public {
+            return Err(ApiError::Forbidden("Only public comparisons can be listed in the directory".into()));
+        }
+        db.get_items_by_url(&url)
+            .await?
+            .iter()
+            .filter(|item| !item.name.trim().is_empty())
+            .count()
+    };
+    let announcement = ComparisonAnnouncement {
+        slug: comparison_slug(&url),
+        url: url.clone(),
+        title: listing.title.clone(),
+        category: listing.category.clone(),
+        item_count,
+    };
+
+    // Announce before storing, so the listing only exists once relays know about it
+    directory_client(&config, reader.relays()).await?.publish_announcement(&announcement).await?;
+    db.write().await.set_listing(&url, &listing, chrono::Utc::now().timestamp()).await?;
+    server_log!("[API] Announced {} with {} items in the directory", url, item_count);
+    Ok(HttpResponse::Ok().json(announcement))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    delete,
+    path = "/api/urls/{url}/listing",
+    tag = "directory",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Comparison removed from the directory"),
+        (status = 404, description = "The comparison is not listed", body = ErrorResponse),
+        (status = 502, description = "Relays rejected the deletion", body = ErrorResponse),
+        (status = 503, description = "Directory publishing is disabled", body = ErrorResponse)
+    )
+)]
+pub async fn remove_listing(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    reader: web::Data<NostrReader>,
+    config: web::Data<Config>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    {
+        let db = db.write().await;
+        if db.get_listing(&url).await?.is_none() {
+            return Err(ApiError::NotFound("Listing".into()));
+        }
+        ensure_can_edit(&db, &url, &req).await?;
+    }
+
+    directory_client(&config, reader.relays()).await?.retract_announcement(&url).await?;
+    db.write().await.remove_listing(&url).await?;
+    server_log!("[API] Removed {} from the directory", url);
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/directory",
+    tag = "directory",
+    responses(
+        (status = 200, description = "Comparisons announced on the relays, newest first", body = [DirectoryEntry]),
+        (status = 502, description = "Failed to query the relays", body = ErrorResponse)
+    )
+)]
+pub async fn get_directory(reader: web::Data<NostrReader>) -> Result<HttpResponse, ApiError> {
+    // Reading the directory needs no workspace key
+    let entries = reader_client(&reader).await?.fetch_directory().await?;
+    server_log!("[API] Directory lists {} comparisons", entries.len());
+    Ok(HttpResponse::Ok().json(entries))
+}
+
+// Query parameters of the comparison index
+#[cfg(feature = "ssr")]
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct IndexQuery {
+    /// Text searched for in comparison titles and URLs
+    #[serde(default)]
+    pub search: String,
+    /// Page to return, starting at 1
+    pub page: Option<usize>,
+    /// Only list comparisons marked as decided
+    #[serde(default)]
+    pub decided: bool,
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls",
+    tag = "directory",
+    params(IndexQuery),
+    responses(
+        (status = 200, description = "Page of the comparisons stored on this instance, most recently updated first", body = ComparisonIndex),
+        (status = 401, description = "The instance is private and the admin token is missing or invalid", body = ErrorResponse),
+        (status = 422, description = "Page 0 was requested", body = ErrorResponse)
+    )
+)]
+pub async fn list_comparisons(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    instance: web::Data<InstanceConfig>,
+    query: web::Query<IndexQuery>,
+) -> Result<HttpResponse, ApiError> {
+    // Only admins see what a private instance stores
+    if instance.settings().private {
+        ensure_admin(&req)?;
+    }
+    let page = query.page.unwrap_or(1);
+    if page == 0 {
+        return Err(ApiError::validation("Pages start at 1"));
+    }
+    let index = db
+        .read()
+        .await
+        .get_comparison_index(&query.search, query.decided, page, COMPARISONS_PER_PAGE)
+        .await?;
+    Ok(HttpResponse::Ok().json(index))
+}
+
+// Query parameters of the search across all comparisons
+#[cfg(feature = "ssr")]
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct SearchQuery {
+    /// Words searched for in the names, descriptions and property values of the items
+    #[serde(default)]
+    pub q: String,
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/search",
+    tag = "directory",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Items with values containing words that start with the searched ones, best matches first", body = Vec<SearchHit>),
+        (status = 401, description = "The instance is private and the admin token is missing or invalid", body = ErrorResponse),
+        (status = 422, description = "The search is too long", body = ErrorResponse)
+    )
+)]
+pub async fn search_items(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    instance: web::Data<InstanceConfig>,
+    query: web::Query<SearchQuery>,
+) -> Result<HttpResponse, ApiError> {
+    // Only admins see what a private instance stores
+    if instance.settings().private {
+        ensure_admin(&req)?;
+    }
+    if query.q.chars().count() > MAX_SEARCH_LENGTH {
+        return Err(ApiError::validation(format!("Searches are limited to {} characters", MAX_SEARCH_LENGTH)));
+    }
+    let hits = db.read().await.search_items(&query.q, SEARCH_RESULTS_LIMIT).await?;
+    Ok(HttpResponse::Ok().json(hits))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    delete,
+    path = "/api/urls/{url}/properties/{property}",
+    tag = "properties",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        ("property" = String, Path, description = "Property ID, e.g. P18")
+    ),
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Property deleted, with the deletion to undo it with", body = PropertyDeletion),
+        (status = 404, description = "Unknown URL, or a property the comparison doesn't have", body = ErrorResponse),
+        (status = 500, description = "Failed to delete property", body = ErrorResponse)
+    )
+)]
+pub async fn delete_property(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    live: web::Data<LiveUpdates>,
+    path: web::Path<(String, String)>, // (url, property)
+) -> Result<HttpResponse, ApiError> {
+    let (url, property) = path.into_inner();
+    let property = canonical_property(&property);
+    server_log!("[API] Deleting property {} from URL {}", property, url);
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+    let deletion = db
+        .delete_property_by_url(&url, &property)
+        .await
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => ApiError::NotFound(format!("Property {}", property)),
+            e => ApiError::from(e),
+        })?
+        .ok_or_else(|| ApiError::NotFound(format!("Property {}", property)))?;
+    record_history(&db, &url, &req, None, &[HistoryChange::PropertyDeleted { property: property.clone() }]).await?;
+    live.publish(&url, live_session(&req), ComparisonChange::PropertyDeleted { property });
+    Ok(HttpResponse::Ok().json(deletion))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    patch,
+    path = "/api/urls/{url}/properties/{property}",
+    tag = "properties",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        ("property" = String, Path, description = "Name of the custom property")
+    ),
+    request_body = PropertyRename,
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Property renamed in place, with its values under the new name", body = PropertyColumn),
+        (status = 404, description = "Unknown URL, or a property the comparison doesn't have", body = ErrorResponse),
+        (status = 409, description = "The comparison already has a property with the new name", body = ErrorResponse),
+        (status = 422, description = "Wikidata or core property, or an invalid new name", body = ErrorResponse)
+    )
+)]
+pub async fn rename_property(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    live: web::Data<LiveUpdates>,
+    path: web::Path<(String, String)>, // (url, property)
+    rename: ValidJson<PropertyRename>,
+) -> Result<HttpResponse, ApiError> {
+    let (url, property) = path.into_inner();
+    let property = canonical_property(&property);
+    let rename_to = rename.into_inner().rename_to;
+    // Wikidata properties are named by Wikidata, and the core rows by the table
+    if is_property_id(&property) || ["name", "description"].contains(&property.as_str()) {
+        return Err(ApiError::validation(format!("Property {} can't be renamed, only custom properties can", property)));
+    }
+    validate_custom_property(&rename_to)?;
+    if ["name", "description"].contains(&rename_to.as_str()) {
+        return Err(unknown_property(&rename_to, format!("{} is a core property", rename_to)));
+    }
+    server_log!("[API] Renaming property {} of URL {} to {}", property, url, rename_to);
+
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+    let column = match db.rename_property(&url, &property, &rename_to).await? {
+        PropertyRenaming::Renamed(column) => column,
+        PropertyRenaming::Unknown => return Err(ApiError::NotFound(format!("Property {}", property))),
+        PropertyRenaming::Taken => return Err(ApiError::Conflict(format!("The comparison already has a property {}", rename_to))),
+    };
+    record_history(&db, &url, &req, None, &[HistoryChange::PropertyRenamed { property: property.clone(), rename_to: rename_to.clone() }]).await?;
+    live.publish(&url, live_session(&req), ComparisonChange::PropertyRenamed { property, rename_to });
+    Ok(HttpResponse::Ok().json(column))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/properties/deletions/{deletion_id}/undo",
+    tag = "properties",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        ("deletion_id" = i64, Path, description = "Deletion ID returned when the property was deleted")
+    ),
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Property selected again at its former position, with the values it had", body = PropertyColumn),
+        (status = 404, description = "Unknown URL, or a deletion that doesn't exist or was already undone", body = ErrorResponse),
+        (status = 500, description = "Failed to undo the deletion", body = ErrorResponse)
+    )
+)]
+pub async fn undo_property_deletion(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    live: web::Data<LiveUpdates>,
+    path: web::Path<(String, i64)>, // (url, deletion_id)
+) -> Result<HttpResponse, ApiError> {
+    let (url, deletion_id) = path.into_inner();
+    server_log!("[API] Undoing property deletion {} of URL {}", deletion_id, url);
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+    let column = db
+        .undo_property_deletion(&url, deletion_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Property deletion {}", deletion_id)))?;
+    record_history(&db, &url, &req, None, &[HistoryChange::PropertyRestored { property: column.property.clone() }]).await?;
+    // The property is back at its former position, which the order tells the other tabs
+    let properties = db.get_selected_properties(&url).await?;
+    live.publish(&url, live_session(&req), ComparisonChange::PropertyAdded { column: column.clone() });
+    live.publish(&url, live_session(&req), ComparisonChange::PropertiesReordered { properties });
+    Ok(HttpResponse::Ok().json(column))
+}
+
+#[cfg(feature = "ssr")]
+pub async fn get_items_by_url(
+    db: web::Data<Arc<RwLock<Database>>>,
+    query: web::Query<HashMap<String, String>>,
+) -> Result<HttpResponse, ApiError> {
+    let url = query.get("url").unwrap_or(&String::new()).to_string();
+    let db = db.read().await;
+    let items = db.get_items_by_url(&url).await.map_err(|err| {
+        leptos::logging::error!("Failed to fetch items by URL: {:?}", err);
+        ApiError::from(err)
+    })?;
+    Ok(HttpResponse::Ok().json(items))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/properties",
+    tag = "properties",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Properties selected for the URL", body = [String]),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 500, description = "Failed to fetch properties", body = ErrorResponse)
+    )
+)]
+pub async fn get_selected_properties(
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let db = db.read().await;
+    ensure_url_exists(&db, &url).await?;
+    let properties = db.get_selected_properties(&url).await?;
+    Ok(HttpResponse::Ok().json(properties))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/properties",
+    tag = "properties",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    request_body = PropertySelection,
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Property selected, with its values filled in from the Wikidata cache where items had none", body = PropertyColumn, headers(
+            ("X-Edit-Token" = String, description = "Owner edit key, only sent when the request created the comparison")
+        )),
+        (status = 422, description = "Unknown Wikidata property, unregistered custom property or invalid name", body = ErrorResponse),
+        (status = 429, description = "Wikidata request budget used up", body = ErrorResponse),
+        (status = 500, description = "Failed to select property", body = ErrorResponse),
+        (status = 502, description = "Wikidata query service failed", body = ErrorResponse)
+    )
+)]
+pub async fn add_selected_property(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    live: web::Data<LiveUpdates>,
+    url: web::Path<String>,
+    selection: ValidJson<PropertySelection>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    let PropertySelection { property, custom } = selection.into_inner();
+    // Wikidata properties may be sent as URIs, they are stored under their bare ID
+    let property = canonical_property(&property);
+    if property.trim().is_empty() {
+        return Err(ApiError::validation("Property must not be empty"));
+    }
+    // Check access before spending Wikidata budget on the property
+    ensure_can_edit(&*db.read().await, &url, &req).await?;
+
+    if is_property_id(&property) {
+        ensure_wikidata_property(&db, &proxy, &req, &property).await?;
+    } else if custom {
+        validate_custom_property(&property)?;
+    } else if !db.read().await.property_exists(&property).await? {
+        return Err(unknown_property(
+            &property,
+            format!("Unknown property {}, custom properties have to be registered with \"custom\": true", property),
+        ));
+    }
+
+    let db = db.write().await;
+    let edit_key = claim_or_ensure_can_edit(&db, &url, &req).await?;
+    // Backfill Wikidata properties from the cache only, entities it lacks are fetched by the client
+    let claims = if is_property_id(&property) {
+        let entity_ids = db.get_wikidata_ids(&url).await?;
+        proxy.cached_claims(&db, &entity_ids, chrono::Utc::now().timestamp()).await?
+    } else {
+        HashMap::new()
+    };
+    let column = db.add_property_column(&url, &property, &claims).await?;
+    record_history(&db, &url, &req, edit_key.as_deref(), &[HistoryChange::PropertyAdded { property }]).await?;
+    live.publish(&url, live_session(&req), ComparisonChange::PropertyAdded { column: column.clone() });
+    Ok(write_response(edit_key).json(column))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/items/refresh",
+    tag = "items",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Items whose names, descriptions or values from Wikidata changed, refreshed from the labels, descriptions and claims the server fetched from Wikidata anew, with the values that changed. Values typed in or imported are kept", body = WikidataRefresh),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 429, description = "Wikidata request budget used up", body = ErrorResponse),
+        (status = 500, description = "Failed to refresh the items", body = ErrorResponse),
+        (status = 502, description = "Wikidata failed", body = ErrorResponse)
+    )
+)]
+pub async fn refresh_from_wikidata(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    instance: web::Data<InstanceConfig>,
+    live: web::Data<LiveUpdates>,
+    publisher: web::Data<ComparisonPublisher>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    let entity_ids = {
+        let db = db.write().await;
+        ensure_url_exists(&db, &url).await?;
+        ensure_can_edit(&db, &url, &req).await?;
+        db.get_wikidata_ids(&url).await?
+    };
+    // Fetch the entities before taking the database lock again, it can take a while.
+    // The claims replace the cached ones
+    let (terms, claims) = if entity_ids.is_empty() {
+        (HashMap::new(), HashMap::new())
+    } else {
+        check_wikidata_budget(&proxy, &req).await?;
+        let language = instance.settings().wikidata_language;
+        let terms = fetch_entity_terms(&proxy.config, &entity_ids, &language).await?;
+        (terms, fetch_wikidata_claims(&db, &proxy, &req, &entity_ids).await?)
+    };
+
+    let db = db.write().await;
+    let refresh = db.refresh_from_wikidata(&url, &claims, &terms).await?;
+    server_log!("[API] Refreshed {} values of URL {} from Wikidata", refresh.changes.len(), url);
+    if refresh.items.is_empty() {
+        return Ok(HttpResponse::Ok().json(refresh));
+    }
+    let changes: Vec<HistoryChange> = refresh.items.iter().map(|item| HistoryChange::ItemSaved { item: item.clone() }).collect();
+    record_history(&db, &url, &req, None, &changes).await?;
+    for item in &refresh.items {
+        live.publish(&url, live_session(&req), ComparisonChange::item_saved(item.clone()));
+    }
+    publish_saved_comparison(&db, &publisher, &url).await?;
+    Ok(HttpResponse::Ok().json(refresh))
+}
+
+
+#[cfg(feature = "ssr")]
+fn unknown_property(property: &str, message: String) -> ApiError {
+    ApiError::Validation {
+        message,
+        details: Some(serde_json::json!({ "property": property })),
+    }
+}
+
+// Names of custom properties are shown as column headers, and must not pass for Wikidata IDs
+#[cfg(feature = "ssr")]
+fn validate_custom_property(property: &str) -> Result<(), ApiError> {
+    match custom_property_problem(property) {
+        Some(problem) => Err(unknown_property(property, format!("Custom property name {}", problem))),
+        None => Ok(()),
+    }
+}
+
+// Check a Wikidata property ID against the label cache, which only the server fills from
+// Wikidata's answers, asking Wikidata when it isn't cached. Labels found along the way are cached
+#[cfg(feature = "ssr")]
+async fn ensure_wikidata_property(
+    db: &RwLock<Database>,
+    proxy: &WikidataProxy,
+    req: &actix_web::HttpRequest,
+    property: &str,
+) -> Result<(), ApiError> {
+    let now = chrono::Utc::now().timestamp();
+    let ids = [property.to_string()];
+    if !proxy.cached_labels(&*db.read().await, &ids, now).await?.is_empty() {
+        return Ok(());
+    }
+
+    check_wikidata_budget(proxy, req).await?;
+    let Some(label) = property_label(&proxy.config, property).await? else {
+        return Err(unknown_property(property, format!("Unknown Wikidata property {}", property)));
+    };
+    let labels = HashMap::from([(property.to_string(), label)]);
+    proxy
+        .cache_labels(&*db.write().await, &labels, now + proxy.config.cache_ttl_secs, now)
+        .await?;
+    Ok(())
+}
+
+// Query parameters of the property suggestion endpoint
+#[cfg(feature = "ssr")]
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct SuggestionQuery {
+    /// Comma-separated Wikidata IDs of the compared items, e.g. `Q1,Q2`
+    #[serde(default)]
+    pub wikidata_ids: String,
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/properties/suggestions",
+    tag = "properties",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        SuggestionQuery
+    ),
+    responses(
+        (status = 200, description = "Properties used by the items' classes, most frequent first", body = [PropertySuggestion]),
+        (status = 429, description = "Wikidata request budget used up", body = ErrorResponse),
+        (status = 502, description = "Wikidata query service failed", body = ErrorResponse)
+    )
+)]
+pub async fn get_property_suggestions(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    metrics: web::Data<ComparisonMetrics>,
+    url: web::Path<String>,
+    query: web::Query<SuggestionQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    let mut wikidata_ids: Vec<String> = query
+        .wikidata_ids
+        .split(',')
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+    wikidata_ids.sort();
+    wikidata_ids.dedup();
+
+    // Cached suggestions are served while stale and refreshed in the background,
+    // only missing ones count against the client's budget
+    let key = wikidata_ids.join(",");
+    let now = chrono::Utc::now().timestamp();
+    let suggestions = match proxy.suggestions(&key, now).await {
+        Freshness::Fresh(suggestions) => suggestions,
+        Freshness::Stale(suggestions) => {
+            if proxy.start_refresh(&key).await {
+                let proxy = proxy.clone().into_inner();
+                actix_web::rt::spawn(async move {
+                    match suggest_properties(&proxy.config, &wikidata_ids).await {
+                        Ok(suggestions) => {
+                            proxy.store_suggestions(&key, suggestions, chrono::Utc::now().timestamp()).await
+                        }
+                        Err(e) => {
+                            server_log!("[API] Failed to refresh property suggestions: {}", e);
+                            proxy.end_refresh(&key).await;
+                        }
+                    }
+                });
+            }
+            suggestions
+        }
+        Freshness::Missing => {
+            metrics.record_cache_misses(&url, 1);
+            check_wikidata_budget(&proxy, &req).await?;
+            let suggestions = suggest_properties(&proxy.config, &wikidata_ids).await?;
+            proxy.store_suggestions(&key, suggestions.clone(), now).await;
+            suggestions
+        }
+    };
+    // Properties that are already part of the comparison are not worth suggesting
+    let selected = db.read().await.get_selected_properties(&url).await?;
+    let suggestions: Vec<PropertySuggestion> = suggestions
+        .into_iter()
+        .filter(|suggestion| !selected.contains(&suggestion.property))
+        .collect();
+    Ok(HttpResponse::Ok().json(suggestions))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    put,
+    path = "/api/urls/{url}/properties/order",
+    tag = "properties",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    request_body(content = [String], description = "Selected property IDs in display order"),
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Property order saved"),
+        (status = 404, description = "Unknown URL", body = ErrorResponse),
+        (status = 500, description = "Failed to save property order", body = ErrorResponse)
+    )
+)]
+pub async fn reorder_selected_properties(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    live: web::Data<LiveUpdates>,
+    url: web::Path<String>,
+    order: ValidJson<PropertyOrder>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    let order: Vec<String> = order.into_inner().0.iter().map(|property| canonical_property(property)).collect();
+    server_log!("[API] Reordering properties for URL {}: {:?}", url, order);
+
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+    db.reorder_selected_properties(&url, &order).await?;
+    live.publish(&url, live_session(&req), ComparisonChange::PropertiesReordered { properties: order });
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/properties/settings",
+    tag = "properties",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Language and spell-checking of the properties that changed them, by property ID or core property name", body = HashMap<String, PropertySettings>)
+    )
+)]
+pub async fn get_property_settings(
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let settings = db.read().await.get_property_settings(&url).await?;
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    put,
+    path = "/api/urls/{url}/properties/{property}/settings",
+    tag = "properties",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        ("property" = String, Path, description = "Property ID, custom property name, or \"name\" or \"description\"")
+    ),
+    request_body = PropertySettings,
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Settings stored, as cleaned up by the server. Empty settings restore the browser's defaults", body = PropertySettings),
+        (status = 404, description = "Unknown URL or property", body = ErrorResponse),
+        (status = 422, description = "Invalid language tag", body = ErrorResponse)
+    )
+)]
+pub async fn set_property_settings(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    live: web::Data<LiveUpdates>,
+    path: web::Path<(String, String)>, // (url, property)
+    settings: ValidJson<PropertySettings>,
+) -> Result<HttpResponse, ApiError> {
+    let (url, property) = path.into_inner();
+    let property = canonical_property(&property);
+    let settings = settings.trimmed();
+    if let Some(problem) = settings.problem() {
+        return Err(ApiError::validation(problem));
+    }
+
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+    if !db.set_property_settings(&url, &property, &settings).await? {
+        return Err(ApiError::NotFound(format!("Property {}", property)));
+    }
+    live.publish(
+        &url,
+        live_session(&req),
+        ComparisonChange::PropertySettingsChanged { property, settings: settings.clone() },
+    );
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/properties/overrides",
+    tag = "properties",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    responses(
+        (status = 200, description = "Labels and tooltips the comparison gives its properties instead of the defaults, by property ID, custom property name or core property name", body = HashMap<String, PropertyOverride>)
+    )
+)]
+pub async fn get_property_overrides(
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    let overrides = db.read().await.get_property_overrides(&url).await?;
+    Ok(HttpResponse::Ok().json(overrides))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    put,
+    path = "/api/urls/{url}/properties/{property}/override",
+    tag = "properties",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        ("property" = String, Path, description = "Property ID, custom property name, or \"name\" or \"description\"")
+    ),
+    request_body = PropertyOverride,
+    responses(
+        (status = 403, description = "Comparison is protected and the edit token is missing or invalid", body = ErrorResponse),
+        (status = 200, description = "Label and tooltip stored, as cleaned up by the server. An empty override restores the defaults", body = PropertyOverride),
+        (status = 404, description = "Unknown URL or property", body = ErrorResponse),
+        (status = 422, description = "Label or tooltip too long, or a label with control characters", body = ErrorResponse)
+    )
+)]
+pub async fn set_property_override(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    live: web::Data<LiveUpdates>,
+    path: web::Path<(String, String)>, // (url, property)
+    overrides: ValidJson<PropertyOverride>,
+) -> Result<HttpResponse, ApiError> {
+    let (url, property) = path.into_inner();
+    let property = canonical_property(&property);
+    let overrides = overrides.trimmed();
+    if let Some(problem) = overrides.problem() {
+        return Err(ApiError::validation(problem));
+    }
+
+    let db = db.write().await;
+    ensure_url_exists(&db, &url).await?;
+    ensure_can_edit(&db, &url, &req).await?;
+    if !db.set_property_override(&url, &property, &overrides).await? {
+        return Err(ApiError::NotFound(format!("Property {}", property)));
+    }
+    live.publish(
+        &url,
+        live_session(&req),
+        ComparisonChange::PropertyOverrideChanged { property, overrides: overrides.clone() },
+    );
+    Ok(HttpResponse::Ok().json(overrides))
+}
+
+// Sessions that haven't sent a heartbeat for this long lose their locks
+#[cfg(feature = "ssr")]
+pub const SESSION_TTL_SECS: i64 = 30;
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/urls/{url}/presence",
+    tag = "presence",
+    params(("url" = String, Path, description = "Percent-encoded comparison URL")),
+    request_body = Heartbeat,
+    responses(
+        (status = 200, description = "Current editors and cell locks of the URL", body = PresenceState),
+        (status = 422, description = "Invalid heartbeat", body = ErrorResponse)
+    )
+)]
+pub async fn presence_heartbeat(
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+    heartbeat: ValidJson<Heartbeat>,
+) -> Result<HttpResponse, ApiError> {
+    let url = url.into_inner();
+    let heartbeat = heartbeat.into_inner();
+    if heartbeat.session_id.trim().is_empty() {
+        return Err(ApiError::validation("Session ID must not be empty"));
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let db = db.write().await;
+    // Expire first so locks left by crashed tabs can be taken over right away
+    db.expire_stale_sessions(now - SESSION_TTL_SECS).await?;
+    let state = db.record_heartbeat(&url, &heartbeat, now).await?;
+    Ok(HttpResponse::Ok().json(state))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/presence/metrics",
+    tag = "presence",
+    responses(
+        (status = 200, description = "Concurrent editors per URL, private and unlisted comparisons included", body = EditorMetrics),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 500, description = "Failed to collect metrics", body = ErrorResponse),
+        (status = 503, description = "No admin token is configured", body = ErrorResponse)
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn editor_metrics(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+) -> Result<HttpResponse, ApiError> {
+    // The URLs of every comparison are listed, so only admins may see them
+    ensure_admin(&req)?;
+    let db = db.read().await;
+    let metrics = db.editor_metrics().await?;
+    Ok(HttpResponse::Ok().json(metrics))
+}
+
+#[cfg(feature = "ssr")]
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct LiveQuery {
+    /// Live session of the tab, whose own changes aren't sent back to it
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/ws/urls/{url}",
+    tag = "presence",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        LiveQuery
+    ),
+    responses(
+        (status = 101, description = "WebSocket receiving every change saved to the comparison by other tabs, as JSON text messages", body = ComparisonChange),
+        (status = 400, description = "Not a WebSocket handshake"),
+        (status = 403, description = "The comparison is private and the edit token is missing or invalid", body = ErrorResponse)
+    )
+)]
+pub async fn live_updates(
+    req: actix_web::HttpRequest,
+    payload: web::Payload,
+    db: web::Data<Arc<RwLock<Database>>>,
+    live: web::Data<LiveUpdates>,
+    url: web::Path<String>,
+    query: web::Query<LiveQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let url = url.into_inner();
+    // The socket is outside the /api/urls/{url} scope and its visibility check
+    if !request_access(&*db.read().await, &url, &req).await?.can_read() {
+        return Err(ApiError::Forbidden("This comparison is private, a valid edit token is required".into()).into());
+    }
+    server_log!("[API] Live updates socket opened for URL {}", url);
+    connect(live, url, query.into_inner().session, &req, payload)
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/urls/{url}/events",
+    tag = "presence",
+    params(
+        ("url" = String, Path, description = "Percent-encoded comparison URL"),
+        LiveQuery
+    ),
+    responses(
+        (status = 200, description = "Server-Sent Events stream with an event per change saved to the comparison, named after its type with the change as JSON data", body = ComparisonChange, content_type = "text/event-stream")
+    )
+)]
+pub async fn change_events(
+    live: web::Data<LiveUpdates>,
+    url: web::Path<String>,
+    query: web::Query<LiveQuery>,
+) -> HttpResponse {
+    let url = url.into_inner();
+    server_log!("[API] Change feed opened for URL {}", url);
+    event_stream(live, url, query.into_inner().session)
+}
+
+// Periodically expire stale edit sessions, so locks don't outlive tabs that
+// stopped sending heartbeats even when nobody else is editing the URL
+#[cfg(feature = "ssr")]
+pub async fn expire_stale_sessions_periodically(db: Arc<RwLock<Database>>) {
+    let mut interval = actix_web::rt::time::interval(std::time::Duration::from_secs(SESSION_TTL_SECS as u64));
+    loop {
+        interval.tick().await;
+        let cutoff = chrono::Utc::now().timestamp() - SESSION_TTL_SECS;
+        if let Err(e) = db.write().await.expire_stale_sessions(cutoff).await {
+            log!("[SERVER ERROR] Failed to expire stale edit sessions: {:?}", e);
+        }
+    }
+}
+
+// Entities per cache request, well below SQLite's limit on query parameters
+#[cfg(feature = "ssr")]
+const MAX_CACHED_ENTITIES: usize = 500;
+
+// Longest `ids` query accepted, room for MAX_CACHED_ENTITIES IDs of up to 11 characters and their commas
+#[cfg(feature = "ssr")]
+const MAX_ENTITY_IDS_LENGTH: usize = MAX_CACHED_ENTITIES * 12;
+
+#[cfg(feature = "ssr")]
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct CacheQuery {
+    /// Comma-separated Wikidata entity IDs, e.g. `Q42,P31`
+    #[serde(default)]
+    pub ids: String,
+}
+
+#[cfg(feature = "ssr")]
+fn parse_entity_ids(ids: &str) -> Result<Vec<String>, ApiError> {
+    if ids.len() > MAX_ENTITY_IDS_LENGTH {
+        return Err(ApiError::validation(format!(
+            "ids must be at most {} characters long",
+            MAX_ENTITY_IDS_LENGTH
+        )));
+    }
+    let mut entity_ids: Vec<String> = ids
+        .split(',')
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+    entity_ids.sort();
+    entity_ids.dedup();
+    check_entity_ids(entity_ids.iter())?;
+    Ok(entity_ids)
+}
+
+#[cfg(feature = "ssr")]
+fn check_entity_ids<'a>(mut ids: impl ExactSizeIterator<Item = &'a String>) -> Result<(), ApiError> {
+    if ids.len() > MAX_CACHED_ENTITIES {
+        return Err(ApiError::validation(format!(
+            "At most {} entities can be looked up per request",
+            MAX_CACHED_ENTITIES
+        )));
+    }
+    match ids.find(|id| !is_entity_id(id)) {
+        Some(id) => Err(ApiError::validation(format!("{} is not a Wikidata entity ID", id))),
+        None => Ok(()),
+    }
+}
+
+// Labels of entities from the cache, fetching the ones it lacks from Wikidata and caching them
+#[cfg(feature = "ssr")]
+async fn wikidata_labels(
+    db: &RwLock<Database>,
+    proxy: &WikidataProxy,
+    req: &actix_web::HttpRequest,
+    entity_ids: &[String],
+) -> Result<HashMap<String, String>, ApiError> {
+    let now = chrono::Utc::now().timestamp();
+    let mut labels = proxy.cached_labels(&*db.read().await, entity_ids, now).await?;
+    let missing: Vec<String> = entity_ids.iter().filter(|id| !labels.contains_key(*id)).cloned().collect();
+    if missing.is_empty() {
+        return Ok(labels);
+    }
+    check_wikidata_budget(proxy, req).await?;
+    let fetched = fetch_entity_labels(&proxy.config, &missing).await?;
+    proxy.cache_labels(&*db.write().await, &fetched, now + proxy.config.cache_ttl_secs, now).await?;
+    labels.extend(fetched);
+    Ok(labels)
+}
+
+// Claims of entities from the cache, fetching the ones it lacks from Wikidata and caching them
+#[cfg(feature = "ssr")]
+async fn wikidata_claims(
+    db: &RwLock<Database>,
+    proxy: &WikidataProxy,
+    req: &actix_web::HttpRequest,
+    entity_ids: &[String],
+) -> Result<HashMap<String, EntityClaims>, ApiError> {
+    let now = chrono::Utc::now().timestamp();
+    let mut claims = proxy.cached_claims(&*db.read().await, entity_ids, now).await?;
+    let missing: Vec<String> = entity_ids.iter().filter(|id| !claims.contains_key(*id)).cloned().collect();
+    if !missing.is_empty() {
+        claims.extend(fetch_wikidata_claims(db, proxy, req, &missing).await?);
+    }
+    Ok(claims)
+}
+
+// Claims of entities fetched from Wikidata, cached or not, with the labels of the entities
+// their values and units refer to. The cache is updated with them
+#[cfg(feature = "ssr")]
+async fn fetch_wikidata_claims(
+    db: &RwLock<Database>,
+    proxy: &WikidataProxy,
+    req: &actix_web::HttpRequest,
+    entity_ids: &[String],
+) -> Result<HashMap<String, EntityClaims>, ApiError> {
+    check_wikidata_budget(proxy, req).await?;
+    let values = fetch_claim_values(&proxy.config, entity_ids).await?;
+    let mut referenced_ids: Vec<String> = values
+        .values()
+        .flat_map(|(values, _)| values.values().flat_map(referenced_entity_ids))
+        .collect();
+    referenced_ids.sort();
+    referenced_ids.dedup();
+    let labels = wikidata_labels(db, proxy, req, &referenced_ids).await?;
+    let claims: HashMap<String, EntityClaims> = values
+        .into_iter()
+        .map(|(entity_id, (values, images))| (entity_id, entity_claims(values, images, &labels)))
+        .collect();
+    let now = chrono::Utc::now().timestamp();
+    proxy.cache_claims(&*db.write().await, &claims, now + proxy.config.cache_ttl_secs, now).await?;
+    Ok(claims)
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/wikidata/labels",
+    tag = "wikidata",
+    params(CacheQuery),
+    responses(
+        (status = 200, description = "English labels by entity ID, from the cache or fetched from Wikidata and cached. Entities without a label are left out", body = HashMap<String, String>),
+        (status = 422, description = "Invalid, too many or too long entity IDs", body = ErrorResponse),
+        (status = 429, description = "Wikidata request budget used up", body = ErrorResponse),
+        (status = 502, description = "Wikidata failed", body = ErrorResponse)
+    )
+)]
+pub async fn get_cached_labels(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    query: web::Query<CacheQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let ids = parse_entity_ids(&query.ids)?;
+    let labels = wikidata_labels(&db, &proxy, &req, &ids).await?;
+    server_log!("[API] Wikidata labels: {} of {} found", labels.len(), ids.len());
+    Ok(HttpResponse::Ok().json(labels))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/wikidata/claims",
+    tag = "wikidata",
+    params(CacheQuery),
+    responses(
+        (status = 200, description = "Property values by entity ID, from the cache or fetched from Wikidata and cached. Entities Wikidata doesn't know are left out",
+            body = HashMap<String, EntityClaims>),
+        (status = 422, description = "Invalid, too many or too long entity IDs", body = ErrorResponse),
+        (status = 429, description = "Wikidata request budget used up", body = ErrorResponse),
+        (status = 502, description = "Wikidata failed", body = ErrorResponse)
+    )
+)]
+pub async fn get_cached_claims(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    query: web::Query<CacheQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let ids = parse_entity_ids(&query.ids)?;
+    let claims = wikidata_claims(&db, &proxy, &req, &ids).await?;
+    server_log!("[API] Wikidata claims: {} of {} found", claims.len(), ids.len());
+    Ok(HttpResponse::Ok().json(claims))
+}
+
+#[cfg(feature = "ssr")]
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct FixtureQuery {
+    /// wikidata.org request of the pages, e.g. a `wbsearchentities` URL
+    pub url: String,
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/wikidata/fixtures",
+    tag = "wikidata",
+    params(FixtureQuery),
+    responses(
+        (status = 200, description = "Recorded Wikidata response, fetched and recorded first in record mode"),
+        (status = 404, description = "No response recorded for the request in replay mode", body = ErrorResponse),
+        (status = 422, description = "Not a Wikidata request of the pages", body = ErrorResponse),
+        (status = 502, description = "Recording the response failed", body = ErrorResponse),
+        (status = 503, description = "The server has no Wikidata fixture mode", body = ErrorResponse)
+    )
+)]
+pub async fn get_wikidata_fixture(
+    proxy: web::Data<WikidataProxy>,
+    query: web::Query<FixtureQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let fixtures = WikidataFixtures::from_config(&proxy.config)
+        .ok_or_else(|| ApiError::NotConfigured("Wikidata fixtures are off, set COMPAREWARE_WIKIDATA_FIXTURES".into()))?;
+    if !is_wikidata_request(&query.url) {
+        return Err(ApiError::validation(format!("{} is not a Wikidata request", query.url)));
+    }
+    Ok(HttpResponse::Ok().json(fixtures.get(&query.url, "application/json").await?))
+}
+
+// Admin actions require `Authorization: Bearer <token>` with COMPAREWARE_ADMIN_TOKEN or
+// the token chosen in the setup wizard, and are disabled while there is neither
+#[cfg(feature = "ssr")]
+fn ensure_admin(req: &actix_web::HttpRequest) -> Result<(), ApiError> {
+    let config = req.app_data::<web::Data<Config>>();
+    let token = config.and_then(|config| config.secrets.admin_token.as_deref());
+    let token_hash = req.app_data::<web::Data<InstanceConfig>>().and_then(|instance| instance.admin_token_hash());
+    if token.is_none() && token_hash.is_none() {
+        return Err(ApiError::NotConfigured("Admin actions are disabled, set COMPAREWARE_ADMIN_TOKEN or complete the setup at /setup".into()));
+    }
+    let provided = req
+        .headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let valid = provided.is_some_and(|provided| {
+        token == Some(provided) || token_hash.as_deref() == Some(hash_edit_token(provided).as_str())
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::Unauthorized("Missing or invalid admin token".into()))
+    }
+}
+
+// Refuse requests using a feature an admin turned off for the instance
+#[cfg(feature = "ssr")]
+fn ensure_feature(enabled: bool, feature: &str) -> Result<(), ApiError> {
+    if enabled {
+        Ok(())
+    } else {
+        Err(ApiError::Forbidden(format!("{} are turned off on this instance", feature)))
+    }
+}
+
+// The workspace key from COMPAREWARE_NOSTR_SECRET_KEY (hex or nsec)
+#[cfg(feature = "ssr")]
+fn workspace_keys(config: &Config) -> Result<nostr_sdk::Keys, ApiError> {
+    let secret_key = config
+        .secrets
+        .nostr_secret_key
+        .as_deref()
+        .ok_or_else(|| ApiError::NotConfigured("COMPAREWARE_NOSTR_SECRET_KEY is not set".into()))?;
+    nostr_sdk::Keys::parse(secret_key)
+        .map_err(|e| ApiError::NotConfigured(format!("Invalid COMPAREWARE_NOSTR_SECRET_KEY: {}", e)))
+}
+
+// Connect to the backup relays of the configuration with the workspace key
+#[cfg(feature = "ssr")]
+async fn backup_client(config: &Config) -> Result<(NostrClient, Vec<String>), ApiError> {
+    let keys = workspace_keys(config)?;
+    let relays = config.backup_relays.clone();
+    if relays.is_empty() {
+        return Err(ApiError::NotConfigured("COMPAREWARE_BACKUP_RELAYS is not set".into()));
+    }
+
+    let client = NostrClient::with_keys(keys, &relays)
+        .await
+        .map_err(|e| ApiError::Upstream(format!("Failed to connect to backup relays: {}", e)))?;
+    Ok((client, relays))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/admin/backup",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Backup published to the relays", body = BackupSummary),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 502, description = "Relays rejected the backup", body = ErrorResponse),
+        (status = 503, description = "Backups are not configured", body = ErrorResponse)
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn backup_workspace(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse, ApiError> {
+    ensure_admin(&req)?;
+    let (client, relays) = backup_client(&config).await?;
+
+    let archive = db.write().await.export_workspace(chrono::Utc::now().timestamp()).await?;
+    let archive_json = serde_json::to_string(&archive)
+        .map_err(|e| ApiError::Upstream(format!("Failed to serialize backup: {}", e)))?;
+
+    let backup_id = uuid::Uuid::new_v4().to_string();
+    let chunks = client.publish_backup(&backup_id, &archive_json).await?;
+    let detail = format!("Published backup {} of {} comparisons", backup_id, archive.comparisons.len());
+    db.write().await.record_audit("backup", &detail, chrono::Utc::now().timestamp()).await?;
+    server_log!("[API] {}", detail);
+    Ok(HttpResponse::Ok().json(BackupSummary {
+        backup_id,
+        chunks,
+        comparisons: archive.comparisons.len(),
+        relays,
+    }))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/admin/backup/storage",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Database copy and workspace archive uploaded, older uploads beyond the retention removed", body = StorageBackupSummary),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 502, description = "Storage rejected the upload", body = ErrorResponse),
+        (status = 503, description = "No bucket is configured", body = ErrorResponse)
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn backup_to_storage(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    backups: web::Data<StorageBackups>,
+) -> Result<HttpResponse, ApiError> {
+    ensure_admin(&req)?;
+    let summary = backups.back_up(&db).await?.ok_or_else(|| {
+        ApiError::NotConfigured(
+            "Backups to S3 are not configured, set COMPAREWARE_S3_BUCKET, COMPAREWARE_S3_ACCESS_KEY and COMPAREWARE_S3_SECRET_KEY".into(),
+        )
+    })?;
+    let detail = format!("Uploaded backup {} to storage, {} bytes", summary.name, summary.bytes);
+    db.write().await.record_audit("storage-backup", &detail, chrono::Utc::now().timestamp()).await?;
+    server_log!("[API] {}", detail);
+    Ok(HttpResponse::Ok().json(summary))
+}
+
+// Largest database accepted by PUT /api/admin/database, bigger ones are restored with --restore
+#[cfg(feature = "ssr")]
+pub const MAX_DATABASE_UPLOAD_BYTES: usize = 1024 * 1024 * 1024;
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/admin/database",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Consistent copy of the whole SQLite database, taken while the server keeps running", content_type = "application/vnd.sqlite3", body = Vec<u8>),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 500, description = "Failed to copy the database", body = ErrorResponse)
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn download_database(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+) -> Result<HttpResponse, ApiError> {
+    ensure_admin(&req)?;
+    let now = chrono::Utc::now();
+    let db = db.read().await;
+    let database = database_copy(&db).await?;
+    let detail = format!("Downloaded a copy of the database, {} bytes", database.len());
+    db.record_audit("database-backup", &detail, now.timestamp()).await?;
+    server_log!("[API] {}", detail);
+    let file_name = format!("compareware-{}.db", now.format("%Y%m%d-%H%M%S"));
+    Ok(HttpResponse::Ok()
+        .content_type("application/vnd.sqlite3")
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", file_name)))
+        .body(database))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    put,
+    path = "/api/admin/database",
+    tag = "admin",
+    request_body(content = Vec<u8>, content_type = "application/vnd.sqlite3", description = "SQLite database, such as a copy downloaded from GET /api/admin/database"),
+    responses(
+        (status = 200, description = "Database replaced by the upload and migrated to the schema of this build", body = DatabaseRestoreSummary),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 413, description = "Upload larger than 1 GiB"),
+        (status = 422, description = "Damaged file, not a CompareWare database, or one from a newer version", body = ErrorResponse),
+        (status = 500, description = "Failed to store the upload or to replace the database", body = ErrorResponse)
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn upload_database(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    instance: web::Data<InstanceConfig>,
+    live: web::Data<LiveUpdates>,
+    body: web::Bytes,
+) -> Result<HttpResponse, ApiError> {
+    ensure_admin(&req)?;
+    let db = db.write().await;
+    restore_database(&db, &body).await?;
+    instance.reload(&db, &proxy.limiter).await?;
+    let urls = db.get_urls().await?;
+    let detail = format!("Restored the database from an upload of {} bytes with {} comparisons", body.len(), urls.len());
+    db.record_audit("database-restore", &detail, chrono::Utc::now().timestamp()).await?;
+    server_log!("[API] {}", detail);
+    // Every open comparison may have changed
+    for url in &urls {
+        live.publish(url, None, ComparisonChange::Reload);
+    }
+    Ok(HttpResponse::Ok().json(DatabaseRestoreSummary {
+        bytes: body.len() as u64,
+        schema_version: db.schema_version().await?,
+        comparisons: urls.len(),
+    }))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/admin/settings",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Settings of the instance, the defaults until an admin saves them", body = InstanceSettings),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse)
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn get_instance_settings(
+    req: actix_web::HttpRequest,
+    instance: web::Data<InstanceConfig>,
+) -> Result<HttpResponse, ApiError> {
+    ensure_admin(&req)?;
+    Ok(HttpResponse::Ok().json(instance.settings()))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    put,
+    path = "/api/admin/settings",
+    tag = "admin",
+    request_body = InstanceSettings,
+    responses(
+        (status = 200, description = "Settings stored and applied, as cleaned up by the server", body = InstanceSettings),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 422, description = "Rate limits out of range or an invalid language tag", body = ErrorResponse)
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn set_instance_settings(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    instance: web::Data<InstanceConfig>,
+    settings: ValidJson<InstanceSettings>,
+) -> Result<HttpResponse, ApiError> {
+    ensure_admin(&req)?;
+    let settings = settings.trimmed();
+    if let Some(problem) = settings.problem() {
+        return Err(ApiError::validation(problem));
+    }
+    let db = db.write().await;
+    instance.update(&db, &proxy.limiter, settings.clone()).await?;
+    let detail = serde_json::to_string(&settings).unwrap_or_default();
+    db.record_audit("settings", &detail, chrono::Utc::now().timestamp()).await?;
+    server_log!("[API] Instance settings changed");
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+// Whether the setup of a new instance is still open: no admin token anywhere and an empty database
+#[cfg(feature = "ssr")]
+pub async fn setup_needed(config: &Config, db: &Database) -> Result<bool, ApiError> {
+    Ok(config.secrets.admin_token.is_none() && db.needs_setup().await?)
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/setup",
+    tag = "setup",
+    responses(
+        (status = 200, description = "Whether the instance still waits for its first run setup", body = SetupStatus)
+    )
+)]
+pub async fn get_setup_status(
+    db: web::Data<Arc<RwLock<Database>>>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse, ApiError> {
+    let needed = setup_needed(&config, &*db.read().await).await?;
+    Ok(HttpResponse::Ok().json(SetupStatus { needed }))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/setup",
+    tag = "setup",
+    request_body = SetupRequest,
+    responses(
+        (status = 200, description = "Instance set up, with the admin token shown this once", body = SetupResult),
+        (status = 409, description = "The instance is already set up or holds comparisons", body = ErrorResponse),
+        (status = 422, description = "Invalid language tag", body = ErrorResponse)
+    )
+)]
+pub async fn complete_setup(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    instance: web::Data<InstanceConfig>,
+    config: web::Data<Config>,
+    request: ValidJson<SetupRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let already_set_up = || ApiError::Conflict("This instance is already set up".into());
+    let db = db.write().await;
+    if !setup_needed(&config, &db).await? {
+        return Err(already_set_up());
+    }
+    let settings = setup_settings(&request, &instance.settings());
+    if let Some(problem) = settings.problem() {
+        return Err(ApiError::validation(problem));
+    }
+    let admin_token = new_edit_token();
+    if !instance.complete_setup(&db, &proxy.limiter, hash_edit_token(&admin_token), settings.clone()).await? {
+        return Err(already_set_up());
+    }
+    let now = chrono::Utc::now().timestamp();
+    let mut demo_comparisons = Vec::new();
+    if request.demo_data {
+        let site = {
+            let conn = req.connection_info();
+            format!("{}://{}", conn.scheme(), conn.host())
+        };
+        let archive = demo_archive(&site, now);
+        db.restore_workspace(&archive).await?;
+        demo_comparisons = archive.comparisons.into_iter().map(|comparison| comparison.url).collect();
+    }
+    let detail = format!(
+        "Completed the setup, {} instance in {}, {} demo comparisons",
+        if settings.private { "private" } else { "public" },
+        settings.wikidata_language,
+        demo_comparisons.len()
+    );
+    db.record_audit("setup", &detail, now).await?;
+    server_log!("[API] {}", detail);
+    Ok(HttpResponse::Ok().json(SetupResult { admin_token, demo_comparisons }))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/admin/restore",
+    tag = "admin",
+    request_body = RestoreRequest,
+    responses(
+        (status = 200, description = "Backup restored", body = RestoreSummary),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 404, description = "No backup found on the relays", body = ErrorResponse),
+        (status = 502, description = "Backup is incomplete or unreadable", body = ErrorResponse),
+        (status = 503, description = "Backups are not configured", body = ErrorResponse)
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn restore_workspace(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    live: web::Data<LiveUpdates>,
+    config: web::Data<Config>,
+    request: ValidJson<RestoreRequest>,
+) -> Result<HttpResponse, ApiError> {
+    ensure_admin(&req)?;
+    let (client, _) = backup_client(&config).await?;
+
+    let (backup_id, archive_json) = client.fetch_backup(request.backup_id.as_deref()).await?;
+    let archive: WorkspaceArchive = serde_json::from_str(&archive_json)
+        .map_err(|e| ApiError::Upstream(format!("Backup {} is not a valid archive: {}", backup_id, e)))?;
+
+    let db = db.write().await;
+    let items = db.restore_workspace(&archive).await?;
+    let detail = format!("Restored backup {} with {} items", backup_id, items);
+    db.record_audit("restore", &detail, chrono::Utc::now().timestamp()).await?;
+    server_log!("[API] {}", detail);
+    // Tabs that have a restored comparison open load it again
+    for comparison in &archive.comparisons {
+        live.publish(&comparison.url, None, ComparisonChange::Reload);
+    }
+    Ok(HttpResponse::Ok().json(RestoreSummary {
+        backup_id,
+        comparisons: archive.comparisons.len(),
+        items,
+    }))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/admin/diagnostics",
+    tag = "admin",
+    responses(
+        (status = 200, description = "Zip of the recent server log, the audit tail, the schema version, the configuration with secrets redacted and the errors browsers reported", content_type = "application/zip", body = Vec<u8>),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse)
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn download_diagnostics(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    diagnostics: web::Data<Diagnostics>,
+) -> Result<HttpResponse, ApiError> {
+    ensure_admin(&req)?;
+    let now = chrono::Utc::now();
+    let db = db.read().await;
+    db.record_audit("diagnostics", "Downloaded the diagnostics bundle", now.timestamp()).await?;
+    let bundle = diagnostics.bundle(db.schema_version().await?, &db.get_audit_tail(AUDIT_TAIL_LENGTH).await?, now.timestamp());
+    let file_name = format!("compareware-diagnostics-{}.zip", now.format("%Y%m%d-%H%M%S"));
+    Ok(HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", file_name)))
+        .body(bundle))
+}
+
+// Query parameters of the heaviest comparisons report
+#[cfg(feature = "ssr")]
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct HeaviestQuery {
+    /// Comparisons listed, 20 by default
+    pub limit: Option<usize>,
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/admin/reports/heaviest-comparisons",
+    tag = "admin",
+    params(HeaviestQuery),
+    responses(
+        (status = 200, description = "Comparisons taking the most time to answer since the server started, then holding the most values, with what would make them lighter", body = [ComparisonLoad]),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse)
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn heaviest_comparisons(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    metrics: web::Data<ComparisonMetrics>,
+    query: web::Query<HeaviestQuery>,
+) -> Result<HttpResponse, ApiError> {
+    ensure_admin(&req)?;
+    let mut requests = metrics.requests();
+    let mut loads: Vec<ComparisonLoad> = db
+        .read()
+        .await
+        .comparison_rows()
+        .await?
+        .into_iter()
+        .map(|rows| {
+            let measured = requests.remove(&rows.url).unwrap_or_default();
+            ComparisonLoad::new(rows, measured)
+        })
+        .collect();
+    loads.sort_by_key(|load| std::cmp::Reverse(load.weight()));
+    loads.truncate(query.limit.unwrap_or(HEAVIEST_COMPARISONS));
+    Ok(HttpResponse::Ok().json(loads))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    get,
+    path = "/api/admin/api-keys",
+    tag = "admin",
+    responses(
+        (status = 200, description = "API keys of the instance, newest first, without the keys themselves", body = [ApiKey]),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse)
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn list_api_keys(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+) -> Result<HttpResponse, ApiError> {
+    ensure_admin(&req)?;
+    Ok(HttpResponse::Ok().json(db.read().await.list_api_keys().await?))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/admin/api-keys",
+    tag = "admin",
+    request_body = ApiKeyRequest,
+    responses(
+        (status = 200, description = "API key created, the only answer carrying the key", body = CreatedApiKey),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 422, description = "Missing name or blank comparison URL", body = ErrorResponse)
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn create_api_key(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    request: ValidJson<ApiKeyRequest>,
+) -> Result<HttpResponse, ApiError> {
+    ensure_admin(&req)?;
+    if let Some(problem) = request.problem() {
+        return Err(ApiError::validation(problem));
+    }
+    let key = format!("{}{}", API_KEY_PREFIX, new_edit_token());
+    let now = chrono::Utc::now().timestamp();
+    let db = db.write().await;
+    let api_key = db.create_api_key(&request, &hash_edit_token(&key), now).await?;
+    let scope = api_key.url.as_deref().unwrap_or("every comparison");
+    db.record_audit("api-key", &format!("Created API key {} for {}", api_key.name, scope), now).await?;
+    server_log!("[API] Created API key {}", api_key.name);
+    Ok(HttpResponse::Ok().json(CreatedApiKey { key, api_key }))
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    delete,
+    path = "/api/admin/api-keys/{id}",
+    tag = "admin",
+    params(("id" = i64, Path, description = "ID of the API key")),
+    responses(
+        (status = 200, description = "API key revoked, requests sending it are refused from now on"),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 404, description = "No API key with this ID", body = ErrorResponse)
+    ),
+    security(("admin_token" = []))
+)]
+pub async fn delete_api_key(
+    req: actix_web::HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    id: web::Path<i64>,
+) -> Result<HttpResponse, ApiError> {
+    ensure_admin(&req)?;
+    let id = id.into_inner();
+    let db = db.write().await;
+    if !db.delete_api_key(id).await? {
+        return Err(ApiError::NotFound(format!("No API key with ID {}", id)));
+    }
+    db.record_audit("api-key", &format!("Revoked API key {}", id), chrono::Utc::now().timestamp()).await?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[cfg(feature = "ssr")]
+#[utoipa::path(
+    post,
+    path = "/api/diagnostics/errors",
+    tag = "public",
+    request_body = ClientErrorReport,
+    responses(
+        (status = 204, description = "Error kept for the diagnostics bundle with the reporting IP, as one line cut to 1000 characters. Each IP keeps its 10 newest reports"),
+        (status = 422, description = "Empty message", body = ErrorResponse),
+        (status = 429, description = "Too many changes from this IP, see the rate limits", body = ErrorResponse)
+    )
+)]
+pub async fn report_client_error(
+    req: actix_web::HttpRequest,
+    diagnostics: web::Data<Diagnostics>,
+    report: ValidJson<ClientErrorReport>,
+) -> Result<HttpResponse, ApiError> {
+    let report = report.trimmed();
+    if report.message.is_empty() {
+        return Err(ApiError::validation("An error report needs a message"));
+    }
+    diagnostics.report_client_error(&client_address(&req), report, chrono::Utc::now().timestamp());
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Turn malformed JSON bodies into structured validation errors
+#[cfg(feature = "ssr")]
+pub fn json_error_handler(err: actix_web::error::JsonPayloadError, _req: &actix_web::HttpRequest) -> actix_web::Error {
+    ApiError::Validation {
+        message: "Invalid JSON payload".to_string(),
+        details: Some(serde_json::Value::String(err.to_string())),
+    }
+    .into()
+}
+
+// JSON body that passed its field checks, see models::validation. A body failing them is
+// refused with a 422 listing the fields in error, the message naming the first one
+#[cfg(feature = "ssr")]
+pub struct ValidJson<T>(pub T);
+
+#[cfg(feature = "ssr")]
+impl<T> ValidJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl<T> std::ops::Deref for ValidJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl<T: serde::de::DeserializeOwned + Validate + 'static> actix_web::FromRequest for ValidJson<T> {
+    type Error = actix_web::Error;
+    type Future = futures::future::LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &actix_web::HttpRequest, payload: &mut actix_web::dev::Payload) -> Self::Future {
+        let body = web::Json::<T>::from_request(req, payload);
+        Box::pin(async move {
+            let body = body.await?.into_inner();
+            let errors = body.field_errors();
+            match errors.first() {
+                None => Ok(ValidJson(body)),
+                Some(first) => Err(ApiError::Validation {
+                    message: format!("{} {}", first.field, first.message).trim_start().to_string(),
+                    details: Some(serde_json::json!({ "fields": errors })),
+                }
+                .into()),
+            }
+        })
+    }
+}
+
+// OpenAPI description of the REST API
+#[cfg(feature = "ssr")]
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "CompareWare API", description = "Items and properties of CompareWare comparisons"),
+    paths(
+        get_items,
+        create_item,
+        create_items_batch,
+        import_items_from_query,
+        delete_item,
+        get_property_value,
+        get_reusable_values,
+        tip_item,
+        review_item,
+        get_ratings,
+        get_reviews_feed,
+        get_edit_access,
+        protect_comparison,
+        invite_collaborator,
+        set_visibility,
+        create_snapshot,
+        get_snapshot,
+        get_metadata,
+        set_metadata,
+        set_decision,
+        clear_decision,
+        get_listing,
+        publish_listing,
+        remove_listing,
+        get_publishing_settings,
+        set_publishing_settings,
+        get_mirror_settings,
+        set_mirror_settings,
+        get_directory,
+        list_comparisons,
+        search_items,
+        get_item_card,
+        get_instance_info,
+        get_selected_properties,
+        add_selected_property,
+        refresh_from_wikidata,
+        get_property_suggestions,
+        reorder_selected_properties,
+        get_property_settings,
+        set_property_settings,
+        get_property_overrides,
+        set_property_override,
+        delete_property,
+        rename_property,
+        undo_property_deletion,
+        get_deleted_items,
+        restore_item,
+        get_history,
+        get_history_feed,
+        export_comparison,
+        negotiate_export,
+        create_signed_link,
+        import_comparison,
+        clone_comparison,
+        presence_heartbeat,
+        editor_metrics,
+        live_updates,
+        change_events,
+        get_cached_labels,
+        get_cached_claims,
+        get_wikidata_fixture,
+        backup_workspace,
+        backup_to_storage,
+        download_database,
+        upload_database,
+        restore_workspace,
+        get_instance_settings,
+        set_instance_settings,
+        get_setup_status,
+        complete_setup,
+        download_diagnostics,
+        heaviest_comparisons,
+        list_api_keys,
+        create_api_key,
+        delete_api_key,
+        report_client_error
+    ),
+    components(schemas(Item, ItemStatus, ValueSource, WikidataRefresh, RefreshedValue, EntityTerms, ItemQuery, ItemRequest, ErrorResponse, PropertySelection, PropertySuggestion, Heartbeat, PresenceState, CellLock, EditorMetrics, UrlEditors,
+        WorkspaceArchive, ComparisonArchive, ComparisonExport, BackupSummary, StorageBackupSummary, DatabaseRestoreSummary, RestoreRequest, RestoreSummary, TipRequest, TipInvoice, Quantity, EntityClaims,
+        ReviewRequest, RatingSummary, ExportedReview, DeleteSummary, DeletedItem, HistoryChange, Revision, EditAccess, EditToken, EditorRole, Visibility, VisibilitySetting, Snapshot, SnapshotLink, SignedLinkRequest, SignedLink,
+        ComparisonListing, ComparisonAnnouncement, DirectoryEntry, ComparisonSummary, ComparisonIndex, SearchHit, SnippetPart, PropertyColumn, ComparisonMetadata, Decision, DecisionRequest, PropertyValue, PropertyDeletion, PropertyRename, PropertyOverride, ComparisonChange, SpecCard, Spec, PublishingSettings, ReusableValues, PropertySettings, MirrorSettings,
+        InstanceSettings, InstanceFeatures, InstanceInfo, KnowledgeSourceKind, SetupStatus, SetupRequest, SetupResult, ClientErrorReport,
+        ComparisonRows, ComparisonRequests, ComparisonLoad, ApiKey, ApiKeyRequest, CreatedApiKey, FieldError)),
+    modifiers(&AdminTokenAuth),
+    tags(
+        (name = "items", description = "Items of a comparison URL"),
+        (name = "properties", description = "Properties selected for a comparison URL"),
+        (name = "presence", description = "Edit sessions, cell locks and live updates"),
+        (name = "comparisons", description = "Title, description and decision of comparisons"),
+        (name = "access", description = "Edit protection of comparisons. Edits send an edit token, or an API key as a bearer token"),
+        (name = "snapshots", description = "Frozen, shareable copies of comparisons"),
+        (name = "directory", description = "Comparisons stored on this instance, public comparisons announced over Nostr and the publishing of comparisons to Nostr"),
+        (name = "public", description = "Embeddable summaries of the data on this instance and the features it offers"),
+        (name = "wikidata", description = "Server-side cache of Wikidata labels and claims"),
+        (name = "setup", description = "First run setup of a new instance, open until an admin token exists"),
+        (name = "admin", description = "Workspace administration, requires the admin token")
+    )
+)]
+pub struct ApiDoc;
+
+// Bearer token security schemes: the admin token of the admin endpoints, and the API keys
+// scripts edit comparisons with
+#[cfg(feature = "ssr")]
+struct AdminTokenAuth;
+
+#[cfg(feature = "ssr")]
+impl utoipa::Modify for AdminTokenAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "admin_token",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+            components.add_security_scheme(
+                "api_key",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("cwk_…").build()),
+            );
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub async fn openapi_json() -> HttpResponse {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
+
+// Swagger UI page for the OpenAPI spec, loaded from a CDN like the other frontend assets
+#[cfg(feature = "ssr")]
+pub async fn swagger_ui() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(
+            r##"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="utf-8"/>
+    <title>CompareWare API</title>
+    <link rel="stylesheet" href="https://cdnjs.cloudflare.com/ajax/libs/swagger-ui/5.17.14/swagger-ui.css"/>
+</head>
+<body>
+    <div id="swagger-ui"></div>
+    <script src="https://cdnjs.cloudflare.com/ajax/libs/swagger-ui/5.17.14/swagger-ui-bundle.js"></script>
+    <script>
+        window.onload = () => {
+            window.ui = SwaggerUIBundle({ url: "/api/openapi.json", dom_id: "#swagger-ui" });
+        };
+    </script>
+</body>
+</html>"##,
+        )
+}
+
+// Contract tests: each request below is one the pages send, with the path, method and
+// payload type of the component named next to it, against the routes of routes::configure.
+// A path, method or payload the server doesn't understand fails here instead of in a browser.
+// There is one test per group of handlers above, each on a server of its own
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use super::*;
+    use crate::app_state::AppState;
+    use crate::config::WikidataConfig;
+    use crate::models::quantity::UnitSystem;
+    use crate::models::wikidata::search_url;
+    use crate::routes::configure;
+    use crate::url_path::encode_segment;
+    use actix_http::Request;
+    use actix_web::body::MessageBody;
+    use actix_web::dev::{Service, ServiceResponse};
+    use actix_web::http::header;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::App;
+    use leptos::LeptosOptions;
+    use serde::de::DeserializeOwned;
+
+    const URL: &str = "https://example.com/laptops?sort=name";
+
+    // Send a request and decode the JSON answer, failing with the body when the status isn't the expected one
+    macro_rules! call {
+        ($app:expr, $request:expr, $status:expr) => {{
+            let response = test::call_service(&$app, $request.to_request()).await;
+            let status = response.status();
+            let body = test::read_body(response).await;
+            assert_eq!(status, $status, "{}", String::from_utf8_lossy(&body));
+            body
+        }};
+    }
+
+    fn decode<T: DeserializeOwned>(body: &[u8]) -> T {
+        serde_json::from_slice(body).unwrap_or_else(|e| panic!("{}: {}", e, String::from_utf8_lossy(body)))
+    }
+
+    fn path(segments: &[&str]) -> String {
+        comparison_api_path(URL, segments)
+    }
+
+    // with_edit_token of the pages, sending the edit token the browser keeps for the comparison
+    fn with_edit_token(request: TestRequest, token: &str) -> TestRequest {
+        request.insert_header((EDIT_TOKEN_HEADER, token))
+    }
+
+    fn bearer(token: &str) -> (&'static str, String) {
+        ("Authorization", format!("Bearer {}", token))
+    }
+
+    fn test_item(id: &str, wikidata_id: &str) -> Item {
+        Item {
+            id: id.into(),
+            // Items with the same name share their values within a comparison
+            name: format!("Laptop {}", id),
+            description: "Light".into(),
+            wikidata_id: Some(wikidata_id.into()),
+            custom_properties: HashMap::from([("weight".to_string(), "1.2 kg".to_string())]),
+            quantities: HashMap::new(),
+            group: None,
+            tip_recipient: None,
+            status: None,
+            sources: HashMap::new(),
+            updated_at: None,
+        }
+    }
+
+    // The routes on an empty in-memory database. Wikidata requests fail right away instead of leaving the sandbox
+    async fn test_app() -> (AppState, impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = actix_web::Error>) {
+        let config = Config {
+            wikidata: WikidataConfig {
+                endpoint: "http://127.0.0.1:9/sparql".into(),
+                api_endpoint: "http://127.0.0.1:9/w/api.php".into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let state = AppState::builder()
+            .config(config)
+            .database_path(":memory:")
+            .leptos_options(LeptosOptions::builder().output_name("compareware").build())
+            .nostr_relays(Vec::new())
+            .build()
+            .await
+            .unwrap();
+        let app = test::init_service(App::new().configure(|config| state.configure(config)).configure(configure)).await;
+        (state, app)
+    }
+
+    // setup_wizard: SetupWizard without demo data, the admin token it chose
+    async fn set_up(app: &impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = actix_web::Error>) -> String {
+        let setup = SetupRequest { private: false, wikidata_language: "en".into(), demo_data: false };
+        let result: SetupResult = decode(&call!(*app, TestRequest::post().uri("/api/setup").set_json(&setup), StatusCode::OK));
+        result.admin_token
+    }
+
+    // items_list: save_item_to_db and save_items_batch, the first write hands out the owner's edit token
+    async fn save_items(app: &impl Service<Request, Response = ServiceResponse<impl MessageBody>, Error = actix_web::Error>) -> String {
+        let response = test::call_service(app, TestRequest::post().uri(&path(&["items"])).set_json(test_item("item1", "Q42")).to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let token = response.headers().get(EDIT_TOKEN_HEADER).unwrap().to_str().unwrap().to_string();
+        let batch = vec![test_item("item2", "Q43"), test_item("item3", "Q44")];
+        call!(*app, with_edit_token(TestRequest::post().uri(&path(&["items", "batch"])), &token).set_json(&batch), StatusCode::OK);
+        token
+    }
+
+    #[actix_web::test]
+    async fn test_item_routes() {
+        log!("[TEST] Starting test_item_routes");
+        let (_state, app) = test_app().await;
+        let token = save_items(&app).await;
+
+        // items_list: load_items_from_db
+        let items: Vec<Item> = decode(&call!(app, TestRequest::get().uri(&path(&["items"])), StatusCode::OK));
+        assert_eq!(items.len(), 3);
+        // Browsers revalidate their copy and get a 304 while the comparison is unchanged
+        let response = test::call_service(&app, TestRequest::get().uri(&path(&["items"])).to_request()).await;
+        let etag = response.headers().get(header::ETAG).unwrap().clone();
+        let last_modified = response.headers().get(header::LAST_MODIFIED).unwrap().clone();
+        call!(app, TestRequest::get().uri(&path(&["items"])).insert_header((header::IF_NONE_MATCH, etag.clone())), StatusCode::NOT_MODIFIED);
+        call!(app, TestRequest::get().uri(&path(&["items"])).insert_header((header::IF_MODIFIED_SINCE, last_modified)), StatusCode::NOT_MODIFIED);
+        // items_list: ConflictDialog, saves based on an older version are refused with the stored copy
+        let mut stale = items[0].clone();
+        stale.updated_at = stale.updated_at.map(|version| version - 1);
+        let error: ErrorResponse = decode(&call!(app, with_edit_token(TestRequest::post().uri(&path(&["items"])), &token).set_json(&stale), StatusCode::PRECONDITION_FAILED));
+        assert_eq!(decode::<Vec<Item>>(error.details.unwrap().to_string().as_bytes()), vec![items[0].clone()]);
+        // API clients without versions send the time they loaded the items at instead
+        let unversioned = vec![Item { updated_at: None, ..items[1].clone() }];
+        let loaded = ("If-Unmodified-Since", "Sat, 01 Jan 2000 00:00:00 GMT");
+        call!(app, with_edit_token(TestRequest::post().uri(&path(&["items", "batch"])), &token).insert_header(loaded).set_json(&unversioned), StatusCode::PRECONDITION_FAILED);
+        let saved: Vec<Item> = decode(&call!(app, with_edit_token(TestRequest::post().uri(&path(&["items", "batch"])), &token).set_json(&items[1..]), StatusCode::OK));
+        assert!(saved[0].updated_at > items[1].updated_at);
+        // Bodies with invalid fields are refused, every field in error listed
+        let invalid = vec![Item { id: " ".into(), ..items[1].clone() }];
+        let error: ErrorResponse = decode(&call!(app, with_edit_token(TestRequest::post().uri(&path(&["items", "batch"])), &token).set_json(&invalid), StatusCode::UNPROCESSABLE_ENTITY));
+        assert_eq!(error.message, "[0].id must not be empty");
+        assert_eq!(error.details.unwrap()["fields"][0]["field"], "[0].id");
+        call!(app, TestRequest::get().uri(&path(&["items"])).insert_header((header::IF_NONE_MATCH, etag)), StatusCode::OK);
+
+        // items_list: remove_item
+        call!(app, with_edit_token(TestRequest::delete().uri(&path(&["items", "item3"])), &token), StatusCode::OK);
+        // trash: RecentlyDeleted
+        let deleted: Vec<DeletedItem> = decode(&call!(app, TestRequest::get().uri(&path(&["trash"])), StatusCode::OK));
+        assert_eq!(deleted[0].id, "item3");
+        let restored: Item = decode(&call!(app, with_edit_token(TestRequest::post().uri(&path(&["items", "item3", "restore"])), &token), StatusCode::OK));
+        assert_eq!(restored.id, "item3");
+        call!(app, with_edit_token(TestRequest::post().uri(&path(&["items", "item3", "restore"])), &token), StatusCode::NOT_FOUND);
+        call!(app, with_edit_token(TestRequest::delete().uri(&path(&["items", "item3"])), &token), StatusCode::OK);
+        // The items the pages saved are the ones left after the deletion
+        let items: Vec<Item> = decode(&call!(app, TestRequest::get().uri(&path(&["items"])), StatusCode::OK));
+        let ids: Vec<&str> = items.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, ["item1", "item2"]);
+        log!("[TEST] test_item_routes completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_history_routes() {
+        log!("[TEST] Starting test_history_routes");
+        let (_state, app) = test_app().await;
+        let token = save_items(&app).await;
+        call!(app, with_edit_token(TestRequest::delete().uri(&path(&["items", "item3"])), &token), StatusCode::OK);
+        call!(app, with_edit_token(TestRequest::post().uri(&path(&["items", "item3", "restore"])), &token), StatusCode::OK);
+        call!(app, with_edit_token(TestRequest::delete().uri(&path(&["items", "item3"])), &token), StatusCode::OK);
+
+        // history: ItemHistory
+        let history: Vec<Revision> = decode(&call!(app, TestRequest::get().uri(&format!("{}?item_id=item3", path(&["history"]))), StatusCode::OK));
+        assert!(matches!(history[0].change, HistoryChange::ItemDeleted { .. }));
+        assert!(matches!(&history[1].change, HistoryChange::ItemRestored { item } if item.id == "item3"));
+        assert_eq!(history[0].actor, "owner");
+        let older: Vec<Revision> = decode(&call!(app, TestRequest::get().uri(&format!("{}?before={}", path(&["history"]), history[0].id)), StatusCode::OK));
+        assert!(older.iter().all(|revision| revision.id < history[0].id));
+        // comparison_settings: ComparisonMeta links the feed of the same changes
+        let feed = String::from_utf8_lossy(&call!(app, TestRequest::get().uri(&path(&["feed.atom"])), StatusCode::OK)).to_string();
+        assert!(feed.contains("<title>Deleted item item3</title>"));
+        assert!(feed.contains("#item-item3\"/>"));
+        let newest: Vec<Revision> = decode(&call!(app, TestRequest::get().uri(&path(&["history"])), StatusCode::OK));
+        assert_eq!(feed.matches("<entry>").count(), newest.len());
+        log!("[TEST] test_history_routes completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_export_routes() {
+        log!("[TEST] Starting test_export_routes");
+        let (_state, app) = test_app().await;
+        let admin_token = set_up(&app).await;
+        let token = save_items(&app).await;
+        // Exports are headed by the title, list the selected properties and carry the ratings on request
+        let selection = PropertySelection { property: "weight".into(), custom: true };
+        call!(app, with_edit_token(TestRequest::post().uri(&path(&["properties"])), &token).set_json(&selection), StatusCode::OK);
+        let metadata = ComparisonMetadata { title: "Laptops".into(), ..Default::default() };
+        call!(app, with_edit_token(TestRequest::put().uri(&path(&["metadata"])), &token).set_json(&metadata), StatusCode::OK);
+        let review = ReviewRequest { reviewer_id: "reviewer1".into(), rating: 4 };
+        call!(app, TestRequest::put().uri(&path(&["items", "item1", "review"])).set_json(&review), StatusCode::OK);
+
+        // comparison_transfer: ComparisonTransfer
+        let export: ComparisonExport = decode(&call!(app, TestRequest::get().uri(&path(&["export.json"])), StatusCode::OK));
+        assert!(export.reviews.is_empty());
+        let export: ComparisonExport = decode(&call!(app, TestRequest::get().uri(&format!("{}?reviews=true", path(&["export.json"]))), StatusCode::OK));
+        assert_eq!(export.reviews.len(), 1);
+        assert!(export.reviews[0].reviewer.starts_with("reviewer ") && export.reviews[0].reviewer != "reviewer1");
+        let imported = comparison_api_path("https://imported.com", &["import.json"]);
+        let archive: ComparisonArchive = decode(&call!(app, TestRequest::post().uri(&imported).set_json(&export), StatusCode::OK));
+        assert_eq!(archive.items.len(), export.comparison.items.len());
+        let ratings = comparison_api_path("https://imported.com", &["ratings"]);
+        let ratings: HashMap<String, RatingSummary> = decode(&call!(app, TestRequest::get().uri(&ratings), StatusCode::OK));
+        assert_eq!(ratings.values().map(|summary| summary.count).sum::<u64>(), 1);
+        call!(app, TestRequest::post().uri(&imported).set_json(&export), StatusCode::CONFLICT);
+        let copy: ComparisonArchive = decode(&call!(app, TestRequest::post().uri(&path(&["clone"])), StatusCode::OK));
+        assert_eq!(copy.url, "https://example.com/laptops-copy");
+        assert_eq!(copy.items.len(), export.comparison.items.len());
+        let copy: ComparisonArchive = decode(&call!(app, TestRequest::post().uri(&path(&["clone"])), StatusCode::OK));
+        assert_eq!(copy.url, "https://example.com/laptops-copy-2");
+        let csv = call!(app, TestRequest::get().uri(&format!("{}?original=true", path(&["export.csv"]))), StatusCode::OK);
+        assert!(String::from_utf8_lossy(&csv).starts_with("Name,Description,"));
+        let xlsx = call!(app, TestRequest::get().uri(&path(&["export.xlsx"])), StatusCode::OK);
+        assert!(xlsx.starts_with(b"PK"));
+        let markdown = call!(app, TestRequest::get().uri(&path(&["export.md"])), StatusCode::OK);
+        assert!(String::from_utf8_lossy(&markdown).starts_with("# Laptops\n"));
+        let html = call!(app, TestRequest::get().uri(&path(&["export.html"])), StatusCode::OK);
+        assert!(String::from_utf8_lossy(&html).contains("<h1>Laptops</h1>"));
+        call!(app, TestRequest::get().uri(&path(&["export.pdf"])), StatusCode::NOT_FOUND);
+        let negotiated = call!(app, TestRequest::get().uri(&path(&["export"])).insert_header(("Accept", "text/csv")), StatusCode::OK);
+        assert!(String::from_utf8_lossy(&negotiated).starts_with("Name,Description,"));
+        call!(app, TestRequest::get().uri(&path(&["export"])).insert_header(("Accept", "application/pdf")), StatusCode::NOT_ACCEPTABLE);
+
+        // comparison_transfer: signed links, which open exports of private instances to anyone
+        let request = SignedLinkRequest { format: "csv".into(), embed: false, expires_in_secs: None };
+        let link: SignedLink = decode(&call!(app, with_edit_token(TestRequest::post().uri(&path(&["signed-links"])), &token).set_json(&request), StatusCode::OK));
+        let request = SignedLinkRequest { format: "html".into(), embed: true, expires_in_secs: Some(3600) };
+        let embed: SignedLink = decode(&call!(app, with_edit_token(TestRequest::post().uri(&path(&["signed-links"])), &token).set_json(&request), StatusCode::OK));
+        call!(app, TestRequest::post().uri(&path(&["signed-links"])).set_json(&request), StatusCode::FORBIDDEN);
+        let request = SignedLinkRequest { format: "csv".into(), embed: true, expires_in_secs: None };
+        call!(app, with_edit_token(TestRequest::post().uri(&path(&["signed-links"])), &token).set_json(&request), StatusCode::UNPROCESSABLE_ENTITY);
+        let settings: InstanceSettings = decode(&call!(app, TestRequest::get().uri("/api/admin/settings").insert_header(bearer(&admin_token)), StatusCode::OK));
+        let private = InstanceSettings { private: true, ..settings.clone() };
+        call!(app, TestRequest::put().uri("/api/admin/settings").insert_header(bearer(&admin_token)).set_json(&private), StatusCode::OK);
+        call!(app, TestRequest::get().uri(&path(&["export.csv"])), StatusCode::FORBIDDEN);
+        call!(app, with_edit_token(TestRequest::get().uri(&path(&["export.csv"])), &token), StatusCode::OK);
+        let csv = call!(app, TestRequest::get().uri(&link.path), StatusCode::OK);
+        assert!(String::from_utf8_lossy(&csv).starts_with("Name,Description,"));
+        call!(app, TestRequest::get().uri(&link.path.replace("export.csv", "export.xlsx")), StatusCode::FORBIDDEN);
+        let response = test::call_service(&app, TestRequest::get().uri(&embed.path).to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("Content-Disposition").unwrap().to_str().unwrap().starts_with("inline"));
+        log!("[TEST] test_export_routes completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_value_and_review_routes() {
+        log!("[TEST] Starting test_value_and_review_routes");
+        let (_state, app) = test_app().await;
+        save_items(&app).await;
+
+        // items_list: full text of a long value
+        let value = path(&["items", "item1", "values", "weight"]);
+        let value: PropertyValue = decode(&call!(app, TestRequest::get().uri(&value), StatusCode::OK));
+        assert_eq!(value.value, "1.2 kg");
+        // items_list: values other comparisons hold for the item's entity
+        call!(app, TestRequest::get().uri(&path(&["reusable", "Q42"])), StatusCode::NOT_FOUND);
+        // items_list: tips, the item names no recipient
+        let tip = TipRequest { amount_sats: 21, comment: None };
+        call!(app, TestRequest::post().uri(&path(&["items", "item1", "tip"])).set_json(&tip), StatusCode::UNPROCESSABLE_ENTITY);
+        // items_list: ratings and reviews
+        let review = ReviewRequest { reviewer_id: "reviewer1".into(), rating: 4 };
+        let _: RatingSummary = decode(&call!(app, TestRequest::put().uri(&path(&["items", "item1", "review"])).set_json(&review), StatusCode::OK));
+        let ratings = format!("{}?reviewer_id={}", path(&["ratings"]), encode_segment("reviewer1"));
+        let ratings: HashMap<String, RatingSummary> = decode(&call!(app, TestRequest::get().uri(&ratings), StatusCode::OK));
+        assert_eq!(ratings["item1"].mine, Some(4));
+        // comparison_settings: ComparisonMeta links the reviews feed
+        let feed = call!(app, TestRequest::get().uri(&path(&["reviews.atom"])), StatusCode::OK);
+        assert!(String::from_utf8_lossy(&feed).contains("4 stars for Laptop item1"));
+        log!("[TEST] test_value_and_review_routes completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_access_routes() {
+        log!("[TEST] Starting test_access_routes");
+        let (state, app) = test_app().await;
+        let admin_token = set_up(&app).await;
+        let token = save_items(&app).await;
+
+        // edit_access: load_edit_access and invite
+        let access: EditAccess = decode(&call!(app, with_edit_token(TestRequest::get().uri(&path(&["access"])), &token), StatusCode::OK));
+        assert!(access.can_edit());
+        let _: EditToken = decode(&call!(app, with_edit_token(TestRequest::post().uri(&path(&["collaborators"])), &token), StatusCode::OK));
+        // edit_access: protect, which comparisons others already edit only take with the admin token
+        let legacy = "https://example.com/legacy";
+        state.db.write().await.insert_item_by_url(legacy, &test_item("legacy1", "Q47")).await.unwrap();
+        let protect = comparison_api_path(legacy, &["access"]);
+        call!(app, TestRequest::post().uri(&protect), StatusCode::FORBIDDEN);
+        let _: EditToken = decode(&call!(app, TestRequest::post().uri(&protect).insert_header(bearer(&admin_token)), StatusCode::OK));
+        let unused = comparison_api_path("https://example.com/unused", &["access"]);
+        let _: EditToken = decode(&call!(app, TestRequest::post().uri(&unused), StatusCode::OK));
+        // Items of a protected comparison can't be moved away by saving them under another URL
+        let elsewhere = comparison_api_path("https://example.com/elsewhere", &["items"]);
+        call!(app, TestRequest::post().uri(&elsewhere).set_json(test_item("item1", "Q42")), StatusCode::CONFLICT);
+        let batch = comparison_api_path("https://example.com/elsewhere", &["items", "batch"]);
+        call!(app, TestRequest::post().uri(&batch).set_json(vec![test_item("item2", "Q43")]), StatusCode::CONFLICT);
+        let items: Vec<Item> = decode(&call!(app, with_edit_token(TestRequest::get().uri(&path(&["items"])), &token), StatusCode::OK));
+        assert_eq!(items.len(), 3);
+        // edit_access: EditAccessControls visibility, private comparisons only answer their editors
+        let private = VisibilitySetting { visibility: Visibility::Private };
+        call!(app, TestRequest::put().uri(&path(&["visibility"])).set_json(private), StatusCode::FORBIDDEN);
+        call!(app, with_edit_token(TestRequest::put().uri(&path(&["visibility"])), &token).set_json(private), StatusCode::OK);
+        call!(app, TestRequest::get().uri(&path(&["items"])), StatusCode::FORBIDDEN);
+        call!(app, TestRequest::get().uri(&path(&["export.json"])), StatusCode::FORBIDDEN);
+        let access: EditAccess = decode(&call!(app, TestRequest::get().uri(&path(&["access"])), StatusCode::OK));
+        assert!(!access.can_read());
+        call!(app, with_edit_token(TestRequest::get().uri(&path(&["items"])), &token), StatusCode::OK);
+        let public = VisibilitySetting { visibility: Visibility::Public };
+        call!(app, with_edit_token(TestRequest::put().uri(&path(&["visibility"])), &token).set_json(public), StatusCode::OK);
+        call!(app, TestRequest::get().uri(&path(&["items"])), StatusCode::OK);
+        let unknown = comparison_api_path("https://example.com/unknown", &["visibility"]);
+        call!(app, TestRequest::put().uri(&unknown).set_json(private), StatusCode::NOT_FOUND);
+        log!("[TEST] test_access_routes completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_metadata_routes() {
+        log!("[TEST] Starting test_metadata_routes");
+        let (_state, app) = test_app().await;
+        let token = save_items(&app).await;
+
+        // snapshot: SnapshotButton, for editors only
+        call!(app, TestRequest::post().uri(&path(&["snapshots"])), StatusCode::FORBIDDEN);
+        let _: SnapshotLink = decode(&call!(app, with_edit_token(TestRequest::post().uri(&path(&["snapshots"])), &token), StatusCode::OK));
+        // comparison_settings and decision
+        let metadata = ComparisonMetadata { title: "Laptops".into(), description: "Light ones".into(), unit_system: UnitSystem::Imperial, ..Default::default() };
+        call!(app, with_edit_token(TestRequest::put().uri(&path(&["metadata"])), &token).set_json(&metadata), StatusCode::OK);
+        let stored: ComparisonMetadata = decode(&call!(app, TestRequest::get().uri(&path(&["metadata"])), StatusCode::OK));
+        assert_eq!(stored.title, "Laptops");
+        let decision = DecisionRequest { item_id: "item1".into(), rationale: "Lightest".into() };
+        call!(app, with_edit_token(TestRequest::put().uri(&path(&["decision"])), &token).set_json(&decision), StatusCode::OK);
+        call!(app, with_edit_token(TestRequest::delete().uri(&path(&["decision"])), &token), StatusCode::OK);
+        log!("[TEST] test_metadata_routes completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_publishing_routes() {
+        log!("[TEST] Starting test_publishing_routes");
+        let (_state, app) = test_app().await;
+        let token = save_items(&app).await;
+
+        // publishing_dialog and mirror_dialog
+        let _: PublishingSettings = decode(&call!(app, TestRequest::get().uri(&path(&["publishing"])), StatusCode::OK));
+        let publishing = PublishingSettings { enabled: false, relays: vec!["wss://relay.example.com".into()] };
+        call!(app, with_edit_token(TestRequest::put().uri(&path(&["publishing"])), &token).set_json(&publishing), StatusCode::OK);
+        let _: MirrorSettings = decode(&call!(app, TestRequest::get().uri(&path(&["mirror"])), StatusCode::OK));
+        let mirror = MirrorSettings { relays: vec!["wss://relay.example.com".into()], ..Default::default() };
+        call!(app, with_edit_token(TestRequest::put().uri(&path(&["mirror"])), &token).set_json(&mirror), StatusCode::OK);
+        // listing_dialog: announcing needs the workspace key, so the comparison stays unlisted
+        call!(app, TestRequest::get().uri(&path(&["listing"])), StatusCode::NOT_FOUND);
+        let listing = ComparisonListing { title: "Laptops".into(), category: "Computers".into() };
+        call!(app, with_edit_token(TestRequest::put().uri(&path(&["listing"])), &token).set_json(&listing), StatusCode::SERVICE_UNAVAILABLE);
+        call!(app, with_edit_token(TestRequest::delete().uri(&path(&["listing"])), &token), StatusCode::NOT_FOUND);
+        // discover: the directory is read from the relays, there are none here
+        call!(app, TestRequest::get().uri("/api/directory"), StatusCode::BAD_GATEWAY);
+        log!("[TEST] test_publishing_routes completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_property_routes() {
+        log!("[TEST] Starting test_property_routes");
+        let (_state, app) = test_app().await;
+        let token = save_items(&app).await;
+
+        let _: Vec<PropertyColumn> = decode(&call!(app, TestRequest::get().uri(&path(&["properties"])), StatusCode::OK));
+        // items_list: add_property, remove_property and move_property
+        let selection = PropertySelection { property: "battery".into(), custom: true };
+        let _: PropertyColumn = decode(&call!(app, with_edit_token(TestRequest::post().uri(&path(&["properties"])), &token).set_json(&selection), StatusCode::OK));
+        let order = vec!["battery".to_string(), "weight".to_string()];
+        call!(app, with_edit_token(TestRequest::put().uri(&path(&["properties", "order"])), &token).set_json(&order), StatusCode::OK);
+        let deletion: PropertyDeletion = decode(&call!(app, with_edit_token(TestRequest::delete().uri(&path(&["properties", "battery"])), &token), StatusCode::OK));
+        // items_list: undo_property_deletion
+        let undo = path(&["properties", "deletions", &deletion.deletion_id.to_string(), "undo"]);
+        let _: PropertyColumn = decode(&call!(app, with_edit_token(TestRequest::post().uri(&undo), &token), StatusCode::OK));
+        // items_list: rename_property, refused for names in use and Wikidata properties
+        let rename = |rename_to: &str| PropertyRename { rename_to: rename_to.into() };
+        let renamed: PropertyColumn = decode(&call!(app, with_edit_token(TestRequest::patch().uri(&path(&["properties", "battery"])), &token).set_json(rename("battery life")), StatusCode::OK));
+        assert_eq!(renamed.property, "battery life");
+        call!(app, with_edit_token(TestRequest::patch().uri(&path(&["properties", "battery life"])), &token).set_json(rename("weight")), StatusCode::CONFLICT);
+        call!(app, with_edit_token(TestRequest::patch().uri(&path(&["properties", "P2067"])), &token).set_json(rename("mass")), StatusCode::UNPROCESSABLE_ENTITY);
+        call!(app, TestRequest::patch().uri(&path(&["properties", "battery life"])).set_json(rename("capacity")), StatusCode::FORBIDDEN);
+        // property_settings: PropertySettingsButton, for property rows and the core rows
+        let settings = PropertySettings { lang: Some("de".into()), spellcheck: Some(false), better: None, weight: None };
+        call!(app, with_edit_token(TestRequest::put().uri(&path(&["properties", "weight", "settings"])), &token).set_json(&settings), StatusCode::OK);
+        call!(app, with_edit_token(TestRequest::put().uri(&path(&["properties", "name", "settings"])), &token).set_json(&settings), StatusCode::OK);
+        // property_override: PropertyOverrideButton, labels and tooltips of the property rows
+        let overrides = PropertyOverride { label: Some(" Weight (kg) ".into()), description: Some("Without the charger".into()) };
+        let stored: PropertyOverride = decode(&call!(app, with_edit_token(TestRequest::put().uri(&path(&["properties", "weight", "override"])), &token).set_json(&overrides), StatusCode::OK));
+        assert_eq!(stored.label.as_deref(), Some("Weight (kg)"));
+        call!(app, TestRequest::put().uri(&path(&["properties", "weight", "override"])).set_json(&overrides), StatusCode::FORBIDDEN);
+        let all: HashMap<String, PropertyOverride> = decode(&call!(app, TestRequest::get().uri(&path(&["properties", "overrides"])), StatusCode::OK));
+        assert_eq!(all["weight"], stored);
+        // items_list: property suggestions, which ask Wikidata
+        let suggestions = format!("{}?wikidata_ids={}", path(&["properties", "suggestions"]), encode_segment("Q42,Q5"));
+        call!(app, TestRequest::get().uri(&suggestions), StatusCode::BAD_GATEWAY);
+        log!("[TEST] test_property_routes completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_presence_routes() {
+        log!("[TEST] Starting test_presence_routes");
+        let (_state, app) = test_app().await;
+        let admin_token = set_up(&app).await;
+        save_items(&app).await;
+
+        // items_list: presence heartbeats
+        let heartbeat = Heartbeat { session_id: "session1".into(), cell: Some("item1:weight".into()) };
+        let _: PresenceState = decode(&call!(app, TestRequest::post().uri(&path(&["presence"])).set_json(&heartbeat), StatusCode::OK));
+        // Editor counts list every comparison's URL, private ones too
+        let _: EditorMetrics = decode(&call!(app, TestRequest::get().uri("/api/presence/metrics").insert_header(bearer(&admin_token)), StatusCode::OK));
+        call!(app, TestRequest::get().uri("/api/presence/metrics"), StatusCode::UNAUTHORIZED);
+        log!("[TEST] test_presence_routes completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_wikidata_routes() {
+        log!("[TEST] Starting test_wikidata_routes");
+        let (state, app) = test_app().await;
+        let token = save_items(&app).await;
+
+        // items_list: Wikidata cache in front of wikidata.org, filled by the server only
+        let labels = HashMap::from([("Q42".to_string(), "Douglas Adams".to_string())]);
+        let claims = HashMap::from([("Q42".to_string(), EntityClaims::default())]);
+        let now = chrono::Utc::now().timestamp();
+        state.wikidata_proxy.cache_labels(&*state.db.write().await, &labels, now + 60, now).await.unwrap();
+        state.wikidata_proxy.cache_claims(&*state.db.write().await, &claims, now + 60, now).await.unwrap();
+        let cached: HashMap<String, String> = decode(&call!(app, TestRequest::get().uri("/api/wikidata/labels?ids=Q42"), StatusCode::OK));
+        assert_eq!(cached, labels);
+        let cached: HashMap<String, EntityClaims> = decode(&call!(app, TestRequest::get().uri("/api/wikidata/claims?ids=Q42"), StatusCode::OK));
+        assert_eq!(cached, claims);
+        // Entities missing from the cache are asked of Wikidata, which the sandbox can't reach
+        call!(app, TestRequest::get().uri("/api/wikidata/labels?ids=Q42,Q43"), StatusCode::BAD_GATEWAY);
+        call!(app, TestRequest::get().uri("/api/wikidata/claims?ids=Q43"), StatusCode::BAD_GATEWAY);
+        // Anonymous lookups only accept entity IDs, in bounded numbers
+        call!(app, TestRequest::get().uri("/api/wikidata/labels?ids=Q42,drop%20table"), StatusCode::UNPROCESSABLE_ENTITY);
+        let many = (1..=501).map(|n| format!("Q{}", n)).collect::<Vec<_>>().join(",");
+        call!(app, TestRequest::get().uri(&format!("/api/wikidata/claims?ids={}", many)), StatusCode::UNPROCESSABLE_ENTITY);
+        let long = format!("/api/wikidata/labels?ids={}", ",".repeat(6001));
+        call!(app, TestRequest::get().uri(&long), StatusCode::UNPROCESSABLE_ENTITY);
+        // items_list: wikidata_request_url, answered only by servers replaying fixtures
+        let fixture = format!("/api/wikidata/fixtures?url={}", encode_segment(&search_url("laptop", "en")));
+        call!(app, TestRequest::get().uri(&fixture), StatusCode::SERVICE_UNAVAILABLE);
+        // import_dialog: the query runs on Wikidata
+        let query = ItemQuery { query: "instances of smartphone".into() };
+        call!(app, with_edit_token(TestRequest::post().uri(&path(&["items", "import"])), &token).set_json(&query), StatusCode::BAD_GATEWAY);
+        // items_list: refreshing from Wikidata fetches the items' entities anew
+        call!(app, TestRequest::post().uri(&path(&["items", "refresh"])), StatusCode::FORBIDDEN);
+        call!(app, with_edit_token(TestRequest::post().uri(&path(&["items", "refresh"])), &token), StatusCode::BAD_GATEWAY);
+        // Comparisons without Wikidata items are up to date without asking
+        let plain = comparison_api_path("https://example.com/plain", &["items"]);
+        let response = test::call_service(&app, TestRequest::post().uri(&plain).set_json(Item { wikidata_id: None, ..test_item("plain1", "Q1") }).to_request()).await;
+        let plain_token = response.headers().get(EDIT_TOKEN_HEADER).unwrap().to_str().unwrap().to_string();
+        let refresh = comparison_api_path("https://example.com/plain", &["items", "refresh"]);
+        let refreshed: WikidataRefresh = decode(&call!(app, with_edit_token(TestRequest::post().uri(&refresh), &plain_token), StatusCode::OK));
+        assert!(refreshed.items.is_empty() && refreshed.changes.is_empty());
+        log!("[TEST] test_wikidata_routes completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_setup_routes() {
+        log!("[TEST] Starting test_setup_routes");
+        let (_state, app) = test_app().await;
+
+        // setup_wizard: SetupWizard, open while the database is empty and closed once done
+        let status: SetupStatus = decode(&call!(app, TestRequest::get().uri("/api/setup"), StatusCode::OK));
+        assert!(status.needed);
+        let setup = SetupRequest { private: false, wikidata_language: "en".into(), demo_data: true };
+        let result: SetupResult = decode(&call!(app, TestRequest::post().uri("/api/setup").set_json(&setup), StatusCode::OK));
+        assert_eq!(result.demo_comparisons.len(), 2);
+        call!(app, TestRequest::post().uri("/api/setup").set_json(&setup), StatusCode::CONFLICT);
+        // admin_settings: the token of the setup opens the admin pages
+        let _: InstanceSettings = decode(&call!(app, TestRequest::get().uri("/api/admin/settings").insert_header(bearer(&result.admin_token)), StatusCode::OK));
+        call!(app, TestRequest::get().uri("/api/admin/settings").insert_header(bearer("wrong")), StatusCode::UNAUTHORIZED);
+        // items_list: load_instance_info
+        let info: InstanceInfo = decode(&call!(app, TestRequest::get().uri("/api/instance"), StatusCode::OK));
+        assert!(!info.wikidata_fixtures);
+        log!("[TEST] test_setup_routes completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_admin_routes() {
+        log!("[TEST] Starting test_admin_routes");
+        let (_state, app) = test_app().await;
+        let admin_token = set_up(&app).await;
+        save_items(&app).await;
+
+        // items_list: report_client_error
+        let report = ClientErrorReport { message: "Failed to save item".into(), page: "/laptops".into() };
+        call!(app, TestRequest::post().uri("/api/diagnostics/errors").set_json(&report), StatusCode::NO_CONTENT);
+        // admin_settings: HeaviestComparisons, with the saves above, these loads and a suggestion request
+        // measured, the suggestions missing the Wikidata cache
+        for _ in 0..9 {
+            call!(app, TestRequest::get().uri(&path(&["items"])), StatusCode::OK);
+        }
+        let suggestions = format!("{}?wikidata_ids=Q42", path(&["properties", "suggestions"]));
+        call!(app, TestRequest::get().uri(&suggestions), StatusCode::BAD_GATEWAY);
+        let heaviest = "/api/admin/reports/heaviest-comparisons?limit=10";
+        let loads: Vec<ComparisonLoad> = decode(&call!(app, TestRequest::get().uri(heaviest).insert_header(bearer(&admin_token)), StatusCode::OK));
+        let load = loads.iter().find(|load| load.rows.url == URL).unwrap();
+        assert_eq!(load.rows.items, 3);
+        assert!(load.requests.requests > 10);
+        assert_eq!(load.requests.wikidata_cache_misses, 1);
+        call!(app, TestRequest::get().uri(heaviest), StatusCode::UNAUTHORIZED);
+
+        // admin_settings: ApiKeys, which let scripts edit the comparisons without edit tokens
+        let request = ApiKeyRequest { name: "Price sync".into(), url: Some(URL.into()) };
+        let created: CreatedApiKey = decode(&call!(app, TestRequest::post().uri("/api/admin/api-keys").insert_header(bearer(&admin_token)).set_json(&request), StatusCode::OK));
+        let keys: Vec<ApiKey> = decode(&call!(app, TestRequest::get().uri("/api/admin/api-keys").insert_header(bearer(&admin_token)), StatusCode::OK));
+        assert_eq!(keys, [created.api_key.clone()]);
+        let api_key = bearer(&created.key);
+        let item = Item { description: "Synced".into(), ..test_item("item4", "Q45") };
+        call!(app, TestRequest::post().uri(&path(&["items"])).insert_header(api_key.clone()).set_json(&item), StatusCode::OK);
+        call!(app, TestRequest::post().uri(&path(&["items"])).set_json(&item), StatusCode::FORBIDDEN);
+        let history: Vec<Revision> = decode(&call!(app, TestRequest::get().uri(&format!("{}?item_id=item4", path(&["history"]))), StatusCode::OK));
+        assert_eq!(history[0].actor, "API key Price sync");
+        // Keys limited to a comparison can't edit others, and revoked keys are refused
+        let other = comparison_api_path("https://example.com/phones", &["items"]);
+        call!(app, TestRequest::post().uri(&other).insert_header(api_key.clone()).set_json(test_item("item5", "Q46")), StatusCode::OK);
+        call!(app, TestRequest::post().uri(&other).insert_header(api_key.clone()).set_json(test_item("item5", "Q46")), StatusCode::FORBIDDEN);
+        let key_path = format!("/api/admin/api-keys/{}", created.api_key.id);
+        call!(app, TestRequest::delete().uri(&key_path).insert_header(bearer(&admin_token)), StatusCode::OK);
+        call!(app, TestRequest::delete().uri(&key_path).insert_header(bearer(&admin_token)), StatusCode::NOT_FOUND);
+        call!(app, TestRequest::get().uri(&path(&["items"])).insert_header(api_key), StatusCode::UNAUTHORIZED);
+        log!("[TEST] test_admin_routes completed successfully");
+    }
+}
@@ -0,0 +1,383 @@
+// Requests of the pages to the REST API of their own server, the browser's counterpart of
+// client::CompareWareClient. Each function is one operation, named like the client's
+// method, with the request and response types the server uses. Requests on a comparison
+// carry its edit token and the tab's live session, and every failure comes back as an
+// ApiError whose message can be shown to the visitor as it is
+use crate::components::edit_access::with_edit_token;
+use crate::models::access::{EditAccess, EditToken, VisibilitySetting, EDIT_TOKEN_HEADER};
+use crate::models::api_key::{ApiKey, ApiKeyRequest, CreatedApiKey};
+use crate::models::backup::{ComparisonArchive, ComparisonExport};
+use crate::models::diagnostics::{ClientErrorReport, ComparisonLoad};
+use crate::models::directory::{ComparisonAnnouncement, ComparisonListing, DirectoryEntry};
+use crate::models::error::ErrorResponse;
+use crate::models::export::{SignedLink, SignedLinkRequest};
+use crate::models::history::Revision;
+use crate::models::instance::{InstanceInfo, InstanceSettings};
+use crate::models::item::{DeleteSummary, DeletedItem, Item, ItemQuery, PropertyValue, WikidataRefresh};
+use crate::models::metadata::{ComparisonMetadata, DecisionRequest};
+use crate::models::mirror::MirrorSettings;
+use crate::models::presence::{Heartbeat, PresenceState};
+use crate::models::property::{PropertyColumn, PropertyDeletion, PropertyOverride, PropertyRename, PropertySelection, PropertySettings, PropertySuggestion, ReusableValues};
+use crate::models::publishing::PublishingSettings;
+use crate::models::review::{RatingSummary, ReviewRequest};
+use crate::models::setup::{SetupRequest, SetupResult};
+use crate::models::snapshot::SnapshotLink;
+use crate::models::zap::{TipInvoice, TipRequest};
+use crate::url_path::comparison_api_path;
+use gloo_net::http::{Method, Request, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ApiError {
+    // The request got no answer, or the answer couldn't be read
+    #[error("{0}")]
+    Network(String),
+    // The server answered with an error status, `error` as it sent it or made from the body
+    #[error("{}", .error.message)]
+    Status { status: u16, error: ErrorResponse },
+}
+
+impl ApiError {
+    // Status of the server's answer, None when there was none
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ApiError::Network(_) => None,
+            ApiError::Status { status, .. } => Some(*status),
+        }
+    }
+
+    // Stored copies of the items a save was refused for, saved by someone else since
+    // they were loaded
+    pub fn stale_items(&self) -> Option<Vec<Item>> {
+        match self {
+            ApiError::Status { status: 412, error } => serde_json::from_value(error.details.clone()?).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl From<gloo_net::Error> for ApiError {
+    fn from(err: gloo_net::Error) -> Self {
+        ApiError::Network(err.to_string())
+    }
+}
+
+// Answer of a write that creates the comparison when nothing was saved to it yet, with
+// the owner edit key the server hands out then
+pub struct Written<T> {
+    pub value: T,
+    pub edit_key: Option<String>,
+}
+
+// File downloaded from the server, named as the server suggests
+pub struct Download {
+    pub file_name: Option<String>,
+    pub bytes: Vec<u8>,
+}
+
+// Request on a comparison, see with_edit_token
+fn comparison_request(method: Method, url: &str, segments: &[&str]) -> RequestBuilder {
+    with_edit_token(RequestBuilder::new(&comparison_api_path(url, segments)).method(method), url)
+}
+
+// Request of the admin pages, with the admin token as bearer token
+fn admin_request(method: Method, path: &str, token: &str) -> RequestBuilder {
+    RequestBuilder::new(path).method(method).header("Authorization", &format!("Bearer {}", token.trim()))
+}
+
+// Message of an error answer that isn't a structured error
+fn status_message(status: u16, body: &str) -> String {
+    if body.is_empty() {
+        format!("Server error ({})", status)
+    } else {
+        format!("Server error ({}): {}", status, body)
+    }
+}
+
+// Send a request, turning error statuses into ApiError::Status
+async fn send(request: Request) -> Result<Response, ApiError> {
+    let response = request.send().await?;
+    if response.ok() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    let error = serde_json::from_str(&body).unwrap_or_else(|_| ErrorResponse {
+        code: "http".to_string(),
+        message: status_message(status, &body),
+        details: None,
+    });
+    Err(ApiError::Status { status, error })
+}
+
+async fn empty(request: RequestBuilder) -> Result<(), ApiError> {
+    send(request.build()?).await?;
+    Ok(())
+}
+
+async fn json<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, ApiError> {
+    Ok(send(request.build()?).await?.json().await?)
+}
+
+async fn json_with<T: DeserializeOwned>(request: RequestBuilder, body: &(impl Serialize + ?Sized)) -> Result<T, ApiError> {
+    Ok(send(request.json(body)?).await?.json().await?)
+}
+
+async fn written<T: DeserializeOwned>(request: Request) -> Result<Written<T>, ApiError> {
+    let response = send(request).await?;
+    let edit_key = response.headers().get(EDIT_TOKEN_HEADER);
+    Ok(Written { value: response.json().await?, edit_key })
+}
+
+// Items
+
+pub async fn get_items(url: &str) -> Result<Vec<Item>, ApiError> {
+    json(comparison_request(Method::GET, url, &["items"])).await
+}
+
+pub async fn save_item(url: &str, item: &Item) -> Result<Written<Item>, ApiError> {
+    written(comparison_request(Method::POST, url, &["items"]).json(item)?).await
+}
+
+pub async fn save_items(url: &str, items: &[Item]) -> Result<Written<Vec<Item>>, ApiError> {
+    written(comparison_request(Method::POST, url, &["items", "batch"]).json(items)?).await
+}
+
+pub async fn import_items(url: &str, query: &ItemQuery) -> Result<Written<Vec<Item>>, ApiError> {
+    written(comparison_request(Method::POST, url, &["items", "import"]).json(query)?).await
+}
+
+pub async fn refresh_from_wikidata(url: &str) -> Result<WikidataRefresh, ApiError> {
+    json(comparison_request(Method::POST, url, &["items", "refresh"])).await
+}
+
+pub async fn delete_item(url: &str, item_id: &str) -> Result<DeleteSummary, ApiError> {
+    json(comparison_request(Method::DELETE, url, &["items", item_id])).await
+}
+
+pub async fn restore_item(url: &str, item_id: &str) -> Result<Item, ApiError> {
+    json(comparison_request(Method::POST, url, &["items", item_id, "restore"])).await
+}
+
+pub async fn deleted_items(url: &str) -> Result<Vec<DeletedItem>, ApiError> {
+    json(comparison_request(Method::GET, url, &["trash"])).await
+}
+
+// Page of the history, older than revision `before` if set
+pub async fn history(url: &str, before: Option<i64>) -> Result<Vec<Revision>, ApiError> {
+    let before = before.map(|before| ("before", before.to_string()));
+    json(comparison_request(Method::GET, url, &["history"]).query(before)).await
+}
+
+pub async fn property_value(url: &str, item_id: &str, property: &str) -> Result<PropertyValue, ApiError> {
+    json(comparison_request(Method::GET, url, &["items", item_id, "values", property])).await
+}
+
+pub async fn reusable_values(url: &str, wikidata_id: &str) -> Result<ReusableValues, ApiError> {
+    json(comparison_request(Method::GET, url, &["reusable", wikidata_id])).await
+}
+
+pub async fn tip_item(url: &str, item_id: &str, tip: &TipRequest) -> Result<TipInvoice, ApiError> {
+    json_with(comparison_request(Method::POST, url, &["items", item_id, "tip"]), tip).await
+}
+
+pub async fn review_item(url: &str, item_id: &str, review: &ReviewRequest) -> Result<RatingSummary, ApiError> {
+    json_with(comparison_request(Method::PUT, url, &["items", item_id, "review"]), review).await
+}
+
+// Rating summaries by item ID, with the ratings of `reviewer_id` as `mine`
+pub async fn ratings(url: &str, reviewer_id: &str) -> Result<HashMap<String, RatingSummary>, ApiError> {
+    json(comparison_request(Method::GET, url, &["ratings"]).query([("reviewer_id", reviewer_id)])).await
+}
+
+// Properties
+
+pub async fn selected_properties(url: &str) -> Result<Vec<String>, ApiError> {
+    json(comparison_request(Method::GET, url, &["properties"])).await
+}
+
+pub async fn add_property(url: &str, selection: &PropertySelection) -> Result<Written<PropertyColumn>, ApiError> {
+    written(comparison_request(Method::POST, url, &["properties"]).json(selection)?).await
+}
+
+pub async fn reorder_properties(url: &str, properties: &[String]) -> Result<(), ApiError> {
+    send(comparison_request(Method::PUT, url, &["properties", "order"]).json(properties)?).await?;
+    Ok(())
+}
+
+pub async fn delete_property(url: &str, property: &str) -> Result<PropertyDeletion, ApiError> {
+    json(comparison_request(Method::DELETE, url, &["properties", property])).await
+}
+
+pub async fn rename_property(url: &str, property: &str, rename_to: &str) -> Result<PropertyColumn, ApiError> {
+    let rename = PropertyRename { rename_to: rename_to.to_string() };
+    json_with(comparison_request(Method::PATCH, url, &["properties", property]), &rename).await
+}
+
+pub async fn undo_property_deletion(url: &str, deletion_id: i64) -> Result<PropertyColumn, ApiError> {
+    json(comparison_request(Method::POST, url, &["properties", "deletions", &deletion_id.to_string(), "undo"])).await
+}
+
+pub async fn set_property_settings(url: &str, property: &str, settings: &PropertySettings) -> Result<PropertySettings, ApiError> {
+    json_with(comparison_request(Method::PUT, url, &["properties", property, "settings"]), settings).await
+}
+
+pub async fn set_property_override(url: &str, property: &str, overrides: &PropertyOverride) -> Result<PropertyOverride, ApiError> {
+    json_with(comparison_request(Method::PUT, url, &["properties", property, "override"]), overrides).await
+}
+
+pub async fn property_suggestions(url: &str, wikidata_ids: &[String]) -> Result<Vec<PropertySuggestion>, ApiError> {
+    let query = [("wikidata_ids", wikidata_ids.join(","))];
+    json(comparison_request(Method::GET, url, &["properties", "suggestions"]).query(query)).await
+}
+
+// Comparisons
+
+pub async fn set_metadata(url: &str, metadata: &ComparisonMetadata) -> Result<Written<ComparisonMetadata>, ApiError> {
+    written(comparison_request(Method::PUT, url, &["metadata"]).json(metadata)?).await
+}
+
+pub async fn set_decision(url: &str, decision: &DecisionRequest) -> Result<ComparisonMetadata, ApiError> {
+    json_with(comparison_request(Method::PUT, url, &["decision"]), decision).await
+}
+
+pub async fn clear_decision(url: &str) -> Result<ComparisonMetadata, ApiError> {
+    json(comparison_request(Method::DELETE, url, &["decision"])).await
+}
+
+// Access
+
+pub async fn edit_access(url: &str) -> Result<EditAccess, ApiError> {
+    json(comparison_request(Method::GET, url, &["access"])).await
+}
+
+pub async fn protect_comparison(url: &str) -> Result<EditToken, ApiError> {
+    json(comparison_request(Method::POST, url, &["access"])).await
+}
+
+pub async fn invite_collaborator(url: &str) -> Result<EditToken, ApiError> {
+    json(comparison_request(Method::POST, url, &["collaborators"])).await
+}
+
+pub async fn set_visibility(url: &str, setting: &VisibilitySetting) -> Result<VisibilitySetting, ApiError> {
+    json_with(comparison_request(Method::PUT, url, &["visibility"]), setting).await
+}
+
+// Snapshots, export and import
+
+pub async fn create_snapshot(url: &str) -> Result<SnapshotLink, ApiError> {
+    json(comparison_request(Method::POST, url, &["snapshots"])).await
+}
+
+pub async fn import_comparison(url: &str, export: &ComparisonExport) -> Result<Written<ComparisonArchive>, ApiError> {
+    written(comparison_request(Method::POST, url, &["import.json"]).json(export)?).await
+}
+
+pub async fn sign_link(url: &str, request: &SignedLinkRequest) -> Result<SignedLink, ApiError> {
+    json_with(comparison_request(Method::POST, url, &["signed-links"]), request).await
+}
+
+// Copy of a comparison at a new URL, the edit key being the copy's
+pub async fn clone_comparison(url: &str) -> Result<Written<ComparisonArchive>, ApiError> {
+    written(comparison_request(Method::POST, url, &["clone"]).build()?).await
+}
+
+// Directory and publishing
+
+pub async fn directory() -> Result<Vec<DirectoryEntry>, ApiError> {
+    json(RequestBuilder::new("/api/directory")).await
+}
+
+pub async fn listing(url: &str) -> Result<ComparisonListing, ApiError> {
+    json(comparison_request(Method::GET, url, &["listing"])).await
+}
+
+pub async fn publish_listing(url: &str, listing: &ComparisonListing) -> Result<ComparisonAnnouncement, ApiError> {
+    json_with(comparison_request(Method::PUT, url, &["listing"]), listing).await
+}
+
+pub async fn remove_listing(url: &str) -> Result<(), ApiError> {
+    empty(comparison_request(Method::DELETE, url, &["listing"])).await
+}
+
+pub async fn publishing_settings(url: &str) -> Result<PublishingSettings, ApiError> {
+    json(comparison_request(Method::GET, url, &["publishing"])).await
+}
+
+pub async fn set_publishing_settings(url: &str, settings: &PublishingSettings) -> Result<PublishingSettings, ApiError> {
+    json_with(comparison_request(Method::PUT, url, &["publishing"]), settings).await
+}
+
+pub async fn mirror_settings(url: &str) -> Result<MirrorSettings, ApiError> {
+    json(comparison_request(Method::GET, url, &["mirror"])).await
+}
+
+pub async fn set_mirror_settings(url: &str, settings: &MirrorSettings) -> Result<MirrorSettings, ApiError> {
+    json_with(comparison_request(Method::PUT, url, &["mirror"]), settings).await
+}
+
+// Presence
+
+pub async fn presence_heartbeat(url: &str, heartbeat: &Heartbeat) -> Result<PresenceState, ApiError> {
+    json_with(comparison_request(Method::POST, url, &["presence"]), heartbeat).await
+}
+
+// Instance
+
+pub async fn instance_info() -> Result<InstanceInfo, ApiError> {
+    json(RequestBuilder::new("/api/instance")).await
+}
+
+pub async fn report_client_error(report: &ClientErrorReport) -> Result<(), ApiError> {
+    send(Request::post("/api/diagnostics/errors").json(report)?).await?;
+    Ok(())
+}
+
+pub async fn complete_setup(setup: &SetupRequest) -> Result<SetupResult, ApiError> {
+    json_with(Request::post("/api/setup"), setup).await
+}
+
+// Wikidata "labels" or "claims" by entity ID, from the server's cache, which fetches the entities it lacks
+
+pub async fn cached_wikidata<T: DeserializeOwned>(kind: &str, ids: &[String]) -> Result<HashMap<String, T>, ApiError> {
+    json(Request::get(&format!("/api/wikidata/{}", kind)).query([("ids", ids.join(","))])).await
+}
+
+// Admin, with the admin token
+
+pub async fn instance_settings(token: &str) -> Result<InstanceSettings, ApiError> {
+    json(admin_request(Method::GET, "/api/admin/settings", token)).await
+}
+
+pub async fn set_instance_settings(token: &str, settings: &InstanceSettings) -> Result<InstanceSettings, ApiError> {
+    json_with(admin_request(Method::PUT, "/api/admin/settings", token), settings).await
+}
+
+// Zip of the server log, audit tail and configuration, for bug reports
+pub async fn download_diagnostics(token: &str) -> Result<Download, ApiError> {
+    let response = send(admin_request(Method::GET, "/api/admin/diagnostics", token).build()?).await?;
+    let file_name = response
+        .headers()
+        .get("content-disposition")
+        .and_then(|disposition| Some(disposition.split("filename=\"").nth(1)?.trim_end_matches('"').to_string()));
+    Ok(Download { file_name, bytes: response.binary().await? })
+}
+
+pub async fn heaviest_comparisons(token: &str) -> Result<Vec<ComparisonLoad>, ApiError> {
+    json(admin_request(Method::GET, "/api/admin/reports/heaviest-comparisons", token)).await
+}
+
+pub async fn api_keys(token: &str) -> Result<Vec<ApiKey>, ApiError> {
+    json(admin_request(Method::GET, "/api/admin/api-keys", token)).await
+}
+
+// New API key, the only time the key itself is returned
+pub async fn create_api_key(token: &str, request: &ApiKeyRequest) -> Result<CreatedApiKey, ApiError> {
+    json_with(admin_request(Method::POST, "/api/admin/api-keys", token), request).await
+}
+
+pub async fn revoke_api_key(token: &str, id: i64) -> Result<(), ApiError> {
+    empty(admin_request(Method::DELETE, &format!("/api/admin/api-keys/{}", id), token)).await
+}
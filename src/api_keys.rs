@@ -0,0 +1,51 @@
+// Authentication of scripts and integrations with the API keys admins create. A request
+// sending `Authorization: Bearer <key>` with one of them carries the key on to the handlers,
+// which let it edit the comparisons the key allows like a collaborator's edit token would.
+// Other bearer tokens, such as the admin token, are left to the handlers
+use crate::api::{hash_edit_token, ApiError};
+use crate::db::Database;
+use crate::models::api_key::{ApiKey, API_KEY_PREFIX};
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::middleware::Next;
+use actix_web::{web, HttpMessage, HttpRequest, ResponseError};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// Middleware of the /api scope, looking up the API key a request is sent with. Unknown
+// and revoked keys are refused rather than treated as anonymous requests
+pub async fn authenticate_api_keys(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let key = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|key| key.starts_with(API_KEY_PREFIX))
+        .map(hash_edit_token);
+    let db = req.app_data::<web::Data<Arc<RwLock<Database>>>>().cloned();
+    if let (Some(key_hash), Some(db)) = (key, db) {
+        let found = db.read().await.use_api_key(&key_hash, chrono::Utc::now().timestamp()).await;
+        let error = match found {
+            Ok(Some(api_key)) => {
+                req.extensions_mut().insert(api_key);
+                None
+            }
+            Ok(None) => Some(ApiError::Unauthorized("Unknown or revoked API key".into())),
+            Err(e) => Some(ApiError::from(e)),
+        };
+        if let Some(error) = error {
+            return Ok(req.into_response(error.error_response()).map_into_right_body());
+        }
+    }
+    next.call(req).await.map(ServiceResponse::map_into_left_body)
+}
+
+// API key the request was authenticated with, if any
+pub fn request_api_key(req: &HttpRequest) -> Option<ApiKey> {
+    req.extensions().get::<ApiKey>().cloned()
+}
@@ -1,59 +1,87 @@
 use leptos::*;
 use leptos_meta::*;
-use leptos_router::*; 
-use leptos::logging::log;
-use crate::components::items_list::{ItemsList, load_items_from_db};
+use leptos_router::*;
+use crate::components::admin_settings::AdminSettings;
+use crate::components::comparisons_index::{ComparisonsIndex, ItemSearch};
+use crate::components::discover::Discover;
+use crate::components::items_list::ItemsList;
+use crate::components::notifications::{provide_notifier, Notifications};
+use crate::components::perf_overlay::{provide_perf_monitor, PerfOverlay};
+use crate::components::setup_wizard::{SetupRedirect, SetupWizard};
+use crate::components::snapshot::SnapshotView;
 use crate::models::item::Item;
-use leptos::spawn_local;
-// use tokio::sync::mpsc;
-// use crate::nostr::NostrClient;
-// use nostr_sdk::serde_json;
 
 #[component]
 pub fn App() -> impl IntoView {
     provide_meta_context();
+    // Opt-in render and save instrumentation, see PerfOverlay
+    provide_perf_monitor();
+    // Toasts any page can show, see Notifications
+    provide_notifier();
 
     // Signal to manage the list of items
     let (items_signal, set_items) = create_signal(Vec::<Item>::new());
-    // let (tx, mut rx) = mpsc::channel::<String>(100);
-
-    // // Nostr client subscription for items
-    // spawn_local(async move {
-    //     let nostr_client = NostrClient::new("wss://relay.example.com").await.unwrap();
-    //     nostr_client.subscribe_to_items(tx.clone()).await.unwrap();
-
-    //     while let Some(content) = rx.recv().await {
-    //         if let Ok(item) = serde_json::from_str::<Item>(&content) {
-    //             set_items.update(|items| items.push(item));
-    //         }
-    //     }
-    // });
     view! {
         <Router>
+            <Notifications />
             <Routes>
-                <Route path="/*url" view=move || {
-                    let location = use_location();
-                    let current_url = move || location.pathname.get();
-                    
-                    // Proper async handling
-                    spawn_local({
-                        let current_url = current_url.clone();
-                        async move {
-                            match load_items_from_db(&current_url()).await {
-                                Ok(items) => set_items.set(items),
-                                Err(e) => log!("Error loading items: {}", e),
-                            }
-                        }
-                    });
+                // Index of the comparisons stored on this instance
+                <Route path="" view=move || view! {
+                    <Stylesheet href="/assets/style.css" />
+                    <div>
+                        <h1>{ "CompareWare" }</h1>
+                        <SetupRedirect />
+                        <a class="discover-link" href="/discover">{ "Discover comparisons" }</a>
+                        <ItemSearch />
+                        <ComparisonsIndex />
+                    </div>
+                }/>
+                // Directory of public comparisons announced over Nostr
+                <Route path="/discover" view=move || view! {
+                    <Stylesheet href="/assets/style.css" />
+                    <div>
+                        <h1>{ "CompareWare" }</h1>
+                        <Discover />
+                    </div>
+                }/>
+                // Runtime settings of the instance, for admins
+                <Route path="/admin" view=move || view! {
+                    <Stylesheet href="/assets/style.css" />
+                    <div>
+                        <h1>{ "CompareWare" }</h1>
+                        <AdminSettings />
+                    </div>
+                }/>
+                // First run setup of a new instance
+                <Route path="/setup" view=move || view! {
+                    <Stylesheet href="/assets/style.css" />
+                    <div>
+                        <h1>{ "CompareWare" }</h1>
+                        <SetupWizard />
+                    </div>
+                }/>
+                // Read-only snapshot of a comparison
+                <Route path="/s/:snapshot_id" view=move || view! {
+                    <Stylesheet href="/assets/style.css" />
+                    <div>
+                        <h1>{ "CompareWare" }</h1>
+                        <SnapshotView />
+                    </div>
+                }/>
+                // Rendered once the items are loaded, so the head carries the comparison's title and description
+                <Route path="/*url" ssr=SsrMode::Async view=move || {
+                    // Items are loaded by ItemsList through a server function,
+                    // so they are already part of the server-rendered page
                     view! {
                         <Stylesheet href="/assets/style.css" />
                         <Stylesheet href="https://cdnjs.cloudflare.com/ajax/libs/font-awesome/6.1.1/css/all.min.css" />
                         <div>
                             <h1>{ "CompareWare" }</h1>
-                            <ItemsList 
-                            url=current_url() 
-                            items=items_signal 
+                            <a class="discover-link" href="/discover">{ "Discover comparisons" }</a>
+                            <ItemsList
+                            items=items_signal
                             set_items=set_items />
+                            <PerfOverlay />
                         </div>
                     }
                 }/>
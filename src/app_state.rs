@@ -0,0 +1,204 @@
+use crate::api::expire_stale_sessions_periodically;
+use crate::app::App;
+use crate::comparison_metrics::ComparisonMetrics;
+use crate::config::Config;
+use crate::db::Database;
+use crate::diagnostics::Diagnostics;
+use crate::fault_injection::FaultInjection;
+use crate::instance::InstanceConfig;
+use crate::live::LiveUpdates;
+use crate::mirror::ComparisonMirrors;
+use crate::nostr::NostrReader;
+use crate::publisher::ComparisonPublisher;
+use crate::request_limits::RequestLimits;
+use crate::storage_backup::StorageBackups;
+use crate::wikidata_proxy::{RateLimiter, WikidataProxy};
+use crate::{server_error, server_log};
+use actix_web::web;
+use leptos::LeptosOptions;
+use leptos_router::RouteListing;
+use std::io;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// Server state, built once at startup and cloned into every worker. The clones
+// share the configuration, the database connection, the caches and the clients
+#[derive(Clone)]
+pub struct AppState {
+    pub leptos_options: web::Data<LeptosOptions>,
+    // Routes of the Leptos app, generated once instead of by every worker
+    pub routes: Vec<RouteListing>,
+    pub db: web::Data<Arc<RwLock<Database>>>,
+    // Rate limits and in-memory caches for Wikidata requests
+    pub wikidata_proxy: web::Data<WikidataProxy>,
+    pub nostr_reader: web::Data<NostrReader>,
+    // WebSockets of the open comparisons, across all workers
+    pub live_updates: web::Data<LiveUpdates>,
+    // Publishing of saved items to the relays of the comparisons that enabled it
+    pub comparison_publisher: web::Data<ComparisonPublisher>,
+    // Subscriptions of the comparisons mirroring another instance's comparison
+    pub comparison_mirrors: web::Data<ComparisonMirrors>,
+    // Scheduled uploads to S3-compatible storage, when a bucket is configured
+    pub storage_backups: web::Data<StorageBackups>,
+    // Settings admins change at runtime
+    pub instance: web::Data<InstanceConfig>,
+    // Errors reported by browsers and the configuration, for the diagnostics bundle
+    pub diagnostics: web::Data<Diagnostics>,
+    // Artificial latency and failures of the API, off outside development and tests
+    pub fault_injection: web::Data<FaultInjection>,
+    // Configuration the server was started with
+    pub config: web::Data<Config>,
+    // Requests per minute each IP may send to change something or look up Wikidata
+    pub request_limits: web::Data<RequestLimits>,
+    // Response times, sizes and Wikidata cache misses of each comparison's API
+    pub comparison_metrics: web::Data<ComparisonMetrics>,
+}
+
+impl AppState {
+    pub fn builder() -> AppStateBuilder {
+        AppStateBuilder::default()
+    }
+
+    // Start the background jobs, once for the whole process
+    pub fn spawn_jobs(&self) {
+        // Release locks of edit sessions that stopped sending heartbeats
+        actix_web::rt::spawn(expire_stale_sessions_periodically(self.db.get_ref().clone()));
+        // Resume the mirrors enabled before the restart
+        let mirrors = self.comparison_mirrors.clone();
+        actix_web::rt::spawn(async move { mirrors.start_enabled().await });
+        actix_web::rt::spawn(self.storage_backups.clone().into_inner().back_up_periodically(self.db.get_ref().clone()));
+    }
+
+    // Share the state with the handlers of a worker's App
+    pub fn configure(&self, config: &mut web::ServiceConfig) {
+        config
+            .app_data(self.leptos_options.clone())
+            .app_data(self.db.clone())
+            .app_data(self.wikidata_proxy.clone())
+            .app_data(self.nostr_reader.clone())
+            .app_data(self.live_updates.clone())
+            .app_data(self.comparison_publisher.clone())
+            .app_data(self.comparison_mirrors.clone())
+            .app_data(self.storage_backups.clone())
+            .app_data(self.instance.clone())
+            .app_data(self.diagnostics.clone())
+            .app_data(self.fault_injection.clone())
+            .app_data(self.config.clone())
+            .app_data(self.request_limits.clone())
+            .app_data(self.comparison_metrics.clone());
+    }
+}
+
+// Builder of the server state. Anything left unset comes from the Leptos
+// configuration and the defaults of Config
+#[derive(Default)]
+pub struct AppStateBuilder {
+    config: Option<Config>,
+    database_path: Option<String>,
+    leptos_options: Option<LeptosOptions>,
+    rate_limiter: Option<RateLimiter>,
+    nostr_relays: Option<Vec<String>>,
+}
+
+impl AppStateBuilder {
+    // Configuration loaded at startup, see Config::load
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    // SQLite database file, ":memory:" for a throwaway database
+    pub fn database_path(mut self, path: impl Into<String>) -> Self {
+        self.database_path = Some(path.into());
+        self
+    }
+
+    pub fn leptos_options(mut self, options: LeptosOptions) -> Self {
+        self.leptos_options = Some(options);
+        self
+    }
+
+    pub fn rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    // Relays read for profiles and the directory
+    pub fn nostr_relays(mut self, relays: Vec<String>) -> Self {
+        self.nostr_relays = Some(relays);
+        self
+    }
+
+    pub async fn build(self) -> io::Result<AppState> {
+        let config = self.config.unwrap_or_default();
+        let mut leptos_options = match self.leptos_options {
+            Some(options) => options,
+            None => leptos::get_configuration(None).await.map_err(io::Error::other)?.leptos_options,
+        };
+        if let Some(address) = config.listen_address {
+            leptos_options.site_addr = address;
+        }
+
+        let database_path = self.database_path.as_deref().unwrap_or(&config.database_path);
+        let db = Database::with_readers(database_path, config.database_readers).map_err(io::Error::other)?;
+        db.create_schema().await.map_err(io::Error::other)?;
+        server_log!("Schema created successfully!");
+        let limiter = self.rate_limiter.unwrap_or_else(|| {
+            RateLimiter::new(config.wikidata.client_requests_per_minute, config.wikidata.global_requests_per_minute)
+        });
+        let instance = InstanceConfig::load(&db, &limiter).await.map_err(io::Error::other)?;
+
+        let fault_injection = FaultInjection::new(config.fault_injection.clone());
+        if fault_injection.is_enabled() {
+            server_error!("Fault injection is on, API responses are delayed and fail on purpose: {:?}", config.fault_injection);
+        }
+
+        let request_limits = RequestLimits::new(config.rate_limits.clone());
+
+        let db = Arc::new(RwLock::new(db));
+        let live_updates = web::Data::new(LiveUpdates::default());
+        Ok(AppState {
+            leptos_options: web::Data::new(leptos_options),
+            routes: leptos_actix::generate_route_list(App),
+            db: web::Data::new(db.clone()),
+            wikidata_proxy: web::Data::new(WikidataProxy::new(limiter, config.wikidata.clone())),
+            nostr_reader: web::Data::new(NostrReader::new(self.nostr_relays.unwrap_or_else(|| config.nostr_relays.clone()))),
+            comparison_publisher: web::Data::new(ComparisonPublisher::from_secrets(&config.secrets)),
+            comparison_mirrors: web::Data::new(ComparisonMirrors::new(db, live_updates.clone())),
+            live_updates,
+            storage_backups: web::Data::new(StorageBackups::from_config(&config)),
+            instance: web::Data::new(instance),
+            diagnostics: web::Data::new(Diagnostics::new(config.clone())),
+            config: web::Data::new(config),
+            fault_injection: web::Data::new(fault_injection),
+            request_limits: web::Data::new(request_limits),
+            comparison_metrics: web::Data::new(ComparisonMetrics::default()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_builder_generates_routes_once() {
+        let state = AppState::builder()
+            .database_path(":memory:")
+            .leptos_options(LeptosOptions::builder().output_name("compareware").build())
+            .nostr_relays(vec!["wss://relay.example.com".into()])
+            .build()
+            .await
+            .unwrap();
+
+        let paths: Vec<&str> = state.routes.iter().map(|route| route.path()).collect();
+        assert!(paths.contains(&"/discover"));
+        assert!(paths.contains(&"/s/{snapshot_id}"));
+        assert_eq!(state.nostr_reader.relays(), ["wss://relay.example.com".to_string()]);
+
+        // Workers share the database instead of opening their own
+        let worker = state.clone();
+        assert!(Arc::ptr_eq(state.db.get_ref(), worker.db.get_ref()));
+        assert!(worker.db.read().await.get_urls().await.unwrap().is_empty());
+    }
+}
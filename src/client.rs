@@ -0,0 +1,735 @@
+// Typed client of the REST API described at /api/openapi.json, for tools and importers
+// talking to remote CompareWare instances. Each method is one operation of the spec, with
+// the request and response types the server uses. Edit tokens handed out by the server are
+// kept per comparison and sent with later edits, like the pages keep them in the browser
+use crate::models::access::{EditAccess, EditToken, VisibilitySetting, EDIT_TOKEN_HEADER};
+use crate::models::api_key::{ApiKey, ApiKeyRequest, CreatedApiKey};
+use crate::models::backup::{BackupSummary, ComparisonArchive, ComparisonExport, DatabaseRestoreSummary, RestoreRequest, RestoreSummary, StorageBackupSummary};
+use crate::models::card::SpecCard;
+use crate::models::diagnostics::{ClientErrorReport, ComparisonLoad};
+use crate::models::directory::{ComparisonAnnouncement, ComparisonIndex, ComparisonListing, DirectoryEntry};
+use crate::models::error::ErrorResponse;
+use crate::models::export::{SignedLink, SignedLinkRequest};
+use crate::models::history::Revision;
+use crate::models::instance::{InstanceInfo, InstanceSettings};
+use crate::models::item::{DeleteSummary, DeletedItem, Item, ItemQuery, PropertyValue, WikidataRefresh};
+use crate::models::metadata::{ComparisonMetadata, DecisionRequest};
+use crate::models::mirror::MirrorSettings;
+use crate::models::presence::{EditorMetrics, Heartbeat, PresenceState};
+use crate::models::property::{EntityClaims, PropertyColumn, PropertyDeletion, PropertyOverride, PropertyRename, PropertySelection, PropertySettings, PropertySuggestion, ReusableValues};
+use crate::models::publishing::PublishingSettings;
+use crate::models::review::{RatingSummary, ReviewRequest};
+use crate::models::search::SearchHit;
+use crate::models::setup::{SetupRequest, SetupResult, SetupStatus};
+use crate::models::snapshot::{Snapshot, SnapshotLink};
+use crate::models::zap::{TipInvoice, TipRequest};
+use crate::url_path::{comparison_api_path, encode_segment};
+use reqwest::{Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// Operations of the spec the client has no method for: streams a plain HTTP client
+// can't consume, and the Wikidata fixtures only the pages of test servers use
+pub const UNSUPPORTED_OPERATIONS: &[(&str, &str)] = &[
+    ("get", "/api/urls/{url}/events"),
+    ("get", "/ws/urls/{url}"),
+    ("get", "/api/wikidata/fixtures"),
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("Request to the CompareWare instance failed: {0}")]
+    Request(#[from] reqwest::Error),
+    // The instance answered with an error status, `error.code` is machine-readable
+    #[error("CompareWare answered {status}: {}", .error.message)]
+    Api { status: u16, error: ErrorResponse },
+}
+
+pub struct CompareWareClient {
+    // Origin of the instance without trailing slash, e.g. "https://compareware.org"
+    base_url: String,
+    http: reqwest::Client,
+    admin_token: Option<String>,
+    // Sent with the requests on comparisons, instead of their edit tokens
+    api_key: Option<String>,
+    // Edit tokens by comparison URL
+    edit_tokens: Mutex<HashMap<String, String>>,
+}
+
+impl CompareWareClient {
+    pub fn new(base_url: &str) -> Self {
+        CompareWareClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+            admin_token: None,
+            api_key: None,
+            edit_tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Token sent with the admin operations, COMPAREWARE_ADMIN_TOKEN or the one of the setup
+    pub fn with_admin_token(mut self, token: &str) -> Self {
+        self.admin_token = Some(token.to_string());
+        self
+    }
+
+    // API key created by an admin, editing the comparisons it allows without edit tokens
+    pub fn with_api_key(mut self, key: &str) -> Self {
+        self.api_key = Some(key.to_string());
+        self
+    }
+
+    // Edit token sent with the edits of a comparison, e.g. one a collaborator was given
+    pub fn set_edit_token(&self, url: &str, token: &str) {
+        self.edit_tokens.lock().unwrap().insert(url.to_string(), token.to_string());
+    }
+
+    // Edit token of a comparison, handed out on its first write or set before
+    pub fn edit_token(&self, url: &str) -> Option<String> {
+        self.edit_tokens.lock().unwrap().get(url).cloned()
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http.request(method, format!("{}{}", self.base_url, path))
+    }
+
+    // Request on a comparison, carrying its edit token when there is one, and the API key
+    fn comparison_request(&self, method: Method, url: &str, segments: &[&str]) -> RequestBuilder {
+        let mut request = self.request(method, &comparison_api_path(url, segments));
+        if let Some(token) = self.edit_token(url) {
+            request = request.header(EDIT_TOKEN_HEADER, token);
+        }
+        match &self.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+
+    fn admin_request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self.request(method, path);
+        match &self.admin_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    // Send a request and turn error statuses into ClientError::Api. A new edit token in
+    // the answer is kept for the comparison
+    async fn send(&self, request: RequestBuilder, url: Option<&str>) -> Result<Response, ClientError> {
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let error = serde_json::from_str(&body).unwrap_or(ErrorResponse { code: "http".into(), message: body, details: None });
+            return Err(ClientError::Api { status: status.as_u16(), error });
+        }
+        if let (Some(url), Some(token)) = (url, response.headers().get(EDIT_TOKEN_HEADER).and_then(|token| token.to_str().ok())) {
+            self.set_edit_token(url, token);
+        }
+        Ok(response)
+    }
+
+    async fn json<T: DeserializeOwned>(&self, request: RequestBuilder, url: Option<&str>) -> Result<T, ClientError> {
+        Ok(self.send(request, url).await?.json().await?)
+    }
+
+    async fn empty(&self, request: RequestBuilder, url: Option<&str>) -> Result<(), ClientError> {
+        self.send(request, url).await?;
+        Ok(())
+    }
+
+    async fn bytes(&self, request: RequestBuilder) -> Result<Vec<u8>, ClientError> {
+        Ok(self.send(request, None).await?.bytes().await?.to_vec())
+    }
+
+    async fn comparison_json<T: DeserializeOwned>(&self, method: Method, url: &str, segments: &[&str], body: Option<&impl Serialize>) -> Result<T, ClientError> {
+        let mut request = self.comparison_request(method, url, segments);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        self.json(request, Some(url)).await
+    }
+
+    // Items
+
+    pub async fn get_items(&self, url: &str) -> Result<Vec<Item>, ClientError> {
+        self.comparison_json(Method::GET, url, &["items"], None::<&()>).await
+    }
+
+    pub async fn save_item(&self, url: &str, item: &Item) -> Result<Item, ClientError> {
+        self.comparison_json(Method::POST, url, &["items"], Some(item)).await
+    }
+
+    pub async fn save_items(&self, url: &str, items: &[Item]) -> Result<Vec<Item>, ClientError> {
+        self.comparison_json(Method::POST, url, &["items", "batch"], Some(&items)).await
+    }
+
+    pub async fn import_items(&self, url: &str, query: &ItemQuery) -> Result<Vec<Item>, ClientError> {
+        self.comparison_json(Method::POST, url, &["items", "import"], Some(query)).await
+    }
+
+    // Replace the names, descriptions and values that came from Wikidata with the labels,
+    // descriptions and claims the server fetches from Wikidata anew
+    pub async fn refresh_from_wikidata(&self, url: &str) -> Result<WikidataRefresh, ClientError> {
+        self.comparison_json(Method::POST, url, &["items", "refresh"], None::<&()>).await
+    }
+
+    // Move an item to the trash, see restore_item
+    pub async fn delete_item(&self, url: &str, item_id: &str) -> Result<DeleteSummary, ClientError> {
+        self.comparison_json(Method::DELETE, url, &["items", item_id], None::<&()>).await
+    }
+
+    pub async fn restore_item(&self, url: &str, item_id: &str) -> Result<Item, ClientError> {
+        self.comparison_json(Method::POST, url, &["items", item_id, "restore"], None::<&()>).await
+    }
+
+    pub async fn deleted_items(&self, url: &str) -> Result<Vec<DeletedItem>, ClientError> {
+        self.comparison_json(Method::GET, url, &["trash"], None::<&()>).await
+    }
+
+    // Page of the history, of one item when `item_id` is set, older than revision `before` if set
+    pub async fn history(&self, url: &str, item_id: Option<&str>, before: Option<i64>) -> Result<Vec<Revision>, ClientError> {
+        let mut query = Vec::new();
+        if let Some(item_id) = item_id {
+            query.push(("item_id", item_id.to_string()));
+        }
+        if let Some(before) = before {
+            query.push(("before", before.to_string()));
+        }
+        self.json(self.comparison_request(Method::GET, url, &["history"]).query(&query), Some(url)).await
+    }
+
+    // Atom feed of the newest changes to the items and properties
+    pub async fn history_feed(&self, url: &str) -> Result<String, ClientError> {
+        Ok(self.send(self.comparison_request(Method::GET, url, &["feed.atom"]), None).await?.text().await?)
+    }
+
+    pub async fn property_value(&self, url: &str, item_id: &str, property: &str) -> Result<PropertyValue, ClientError> {
+        self.comparison_json(Method::GET, url, &["items", item_id, "values", property], None::<&()>).await
+    }
+
+    pub async fn reusable_values(&self, url: &str, wikidata_id: &str) -> Result<ReusableValues, ClientError> {
+        self.comparison_json(Method::GET, url, &["reusable", wikidata_id], None::<&()>).await
+    }
+
+    pub async fn tip_item(&self, url: &str, item_id: &str, tip: &TipRequest) -> Result<TipInvoice, ClientError> {
+        self.comparison_json(Method::POST, url, &["items", item_id, "tip"], Some(tip)).await
+    }
+
+    pub async fn review_item(&self, url: &str, item_id: &str, review: &ReviewRequest) -> Result<RatingSummary, ClientError> {
+        self.comparison_json(Method::PUT, url, &["items", item_id, "review"], Some(review)).await
+    }
+
+    // Rating summaries by item ID, with the ratings of `reviewer_id` as `mine`
+    pub async fn ratings(&self, url: &str, reviewer_id: Option<&str>) -> Result<HashMap<String, RatingSummary>, ClientError> {
+        let query: Vec<(&str, &str)> = reviewer_id.map(|reviewer_id| ("reviewer_id", reviewer_id)).into_iter().collect();
+        self.json(self.comparison_request(Method::GET, url, &["ratings"]).query(&query), Some(url)).await
+    }
+
+    // Atom feed of the newest reviews across the comparison's items
+    pub async fn reviews_feed(&self, url: &str) -> Result<String, ClientError> {
+        Ok(self.send(self.comparison_request(Method::GET, url, &["reviews.atom"]), None).await?.text().await?)
+    }
+
+    // Properties
+
+    pub async fn selected_properties(&self, url: &str) -> Result<Vec<String>, ClientError> {
+        self.comparison_json(Method::GET, url, &["properties"], None::<&()>).await
+    }
+
+    pub async fn add_property(&self, url: &str, selection: &PropertySelection) -> Result<PropertyColumn, ClientError> {
+        self.comparison_json(Method::POST, url, &["properties"], Some(selection)).await
+    }
+
+    pub async fn reorder_properties(&self, url: &str, properties: &[String]) -> Result<(), ClientError> {
+        self.empty(self.comparison_request(Method::PUT, url, &["properties", "order"]).json(properties), Some(url)).await
+    }
+
+    pub async fn delete_property(&self, url: &str, property: &str) -> Result<PropertyDeletion, ClientError> {
+        self.comparison_json(Method::DELETE, url, &["properties", property], None::<&()>).await
+    }
+
+    pub async fn rename_property(&self, url: &str, property: &str, rename_to: &str) -> Result<PropertyColumn, ClientError> {
+        let rename = PropertyRename { rename_to: rename_to.to_string() };
+        self.comparison_json(Method::PATCH, url, &["properties", property], Some(&rename)).await
+    }
+
+    pub async fn undo_property_deletion(&self, url: &str, deletion_id: i64) -> Result<PropertyColumn, ClientError> {
+        self.comparison_json(Method::POST, url, &["properties", "deletions", &deletion_id.to_string(), "undo"], None::<&()>).await
+    }
+
+    pub async fn property_settings(&self, url: &str) -> Result<HashMap<String, PropertySettings>, ClientError> {
+        self.comparison_json(Method::GET, url, &["properties", "settings"], None::<&()>).await
+    }
+
+    pub async fn set_property_settings(&self, url: &str, property: &str, settings: &PropertySettings) -> Result<PropertySettings, ClientError> {
+        self.comparison_json(Method::PUT, url, &["properties", property, "settings"], Some(settings)).await
+    }
+
+    pub async fn property_overrides(&self, url: &str) -> Result<HashMap<String, PropertyOverride>, ClientError> {
+        self.comparison_json(Method::GET, url, &["properties", "overrides"], None::<&()>).await
+    }
+
+    pub async fn set_property_override(&self, url: &str, property: &str, overrides: &PropertyOverride) -> Result<PropertyOverride, ClientError> {
+        self.comparison_json(Method::PUT, url, &["properties", property, "override"], Some(overrides)).await
+    }
+
+    pub async fn property_suggestions(&self, url: &str, wikidata_ids: &[&str]) -> Result<Vec<PropertySuggestion>, ClientError> {
+        let request = self.comparison_request(Method::GET, url, &["properties", "suggestions"]).query(&[("wikidata_ids", wikidata_ids.join(","))]);
+        self.json(request, Some(url)).await
+    }
+
+    // Comparisons
+
+    pub async fn metadata(&self, url: &str) -> Result<ComparisonMetadata, ClientError> {
+        self.comparison_json(Method::GET, url, &["metadata"], None::<&()>).await
+    }
+
+    pub async fn set_metadata(&self, url: &str, metadata: &ComparisonMetadata) -> Result<ComparisonMetadata, ClientError> {
+        self.comparison_json(Method::PUT, url, &["metadata"], Some(metadata)).await
+    }
+
+    pub async fn set_decision(&self, url: &str, decision: &DecisionRequest) -> Result<ComparisonMetadata, ClientError> {
+        self.comparison_json(Method::PUT, url, &["decision"], Some(decision)).await
+    }
+
+    pub async fn clear_decision(&self, url: &str) -> Result<ComparisonMetadata, ClientError> {
+        self.comparison_json(Method::DELETE, url, &["decision"], None::<&()>).await
+    }
+
+    // Page of the comparisons stored on the instance, starting at 1
+    pub async fn list_comparisons(&self, search: &str, decided_only: bool, page: usize) -> Result<ComparisonIndex, ClientError> {
+        let query = [("search", search.to_string()), ("decided", decided_only.to_string()), ("page", page.to_string())];
+        self.json(self.request(Method::GET, "/api/urls").query(&query), None).await
+    }
+
+    // Items of all comparisons on the instance with values matching the words of `search`
+    pub async fn search(&self, search: &str) -> Result<Vec<SearchHit>, ClientError> {
+        self.json(self.request(Method::GET, "/api/search").query(&[("q", search)]), None).await
+    }
+
+    // Access
+
+    pub async fn edit_access(&self, url: &str) -> Result<EditAccess, ClientError> {
+        self.comparison_json(Method::GET, url, &["access"], None::<&()>).await
+    }
+
+    // Protect a comparison created before edit keys, the owner's token is kept for later edits
+    pub async fn protect_comparison(&self, url: &str) -> Result<EditToken, ClientError> {
+        let token: EditToken = self.comparison_json(Method::POST, url, &["access"], None::<&()>).await?;
+        self.set_edit_token(url, &token.token);
+        Ok(token)
+    }
+
+    pub async fn invite_collaborator(&self, url: &str) -> Result<EditToken, ClientError> {
+        self.comparison_json(Method::POST, url, &["collaborators"], None::<&()>).await
+    }
+
+    pub async fn set_visibility(&self, url: &str, setting: &VisibilitySetting) -> Result<VisibilitySetting, ClientError> {
+        self.comparison_json(Method::PUT, url, &["visibility"], Some(setting)).await
+    }
+
+    // Snapshots
+
+    pub async fn create_snapshot(&self, url: &str) -> Result<SnapshotLink, ClientError> {
+        self.comparison_json(Method::POST, url, &["snapshots"], None::<&()>).await
+    }
+
+    pub async fn snapshot(&self, snapshot_id: &str) -> Result<Snapshot, ClientError> {
+        self.json(self.request(Method::GET, &format!("/api/snapshots/{}", encode_segment(snapshot_id))), None).await
+    }
+
+    // Export and import
+
+    pub async fn export_comparison(&self, url: &str) -> Result<ComparisonExport, ClientError> {
+        self.comparison_json(Method::GET, url, &["export.json"], None::<&()>).await
+    }
+
+    // Recreate an exported comparison at a URL nobody used yet, its owner token is kept
+    pub async fn import_comparison(&self, url: &str, export: &ComparisonExport) -> Result<ComparisonArchive, ClientError> {
+        self.comparison_json(Method::POST, url, &["import.json"], Some(export)).await
+    }
+
+    // A comparison in an export format named like its extension, such as csv, xlsx, md or
+    // html. With `original`, tables keep the values quantities were entered as next to the
+    // ones converted to the comparison's unit system
+    pub async fn export(&self, url: &str, format: &str, original: bool) -> Result<Vec<u8>, ClientError> {
+        let file = format!("export.{}", format);
+        self.bytes(self.comparison_request(Method::GET, url, &[&file]).query(&[("original", original)])).await
+    }
+
+    // A comparison in the export format of the first media type of `accept` the server has
+    pub async fn export_as(&self, url: &str, accept: &str, original: bool) -> Result<Vec<u8>, ClientError> {
+        let request = self.comparison_request(Method::GET, url, &["export"]).header(reqwest::header::ACCEPT, accept);
+        self.bytes(request.query(&[("original", original)])).await
+    }
+
+    // Expiring link to an export, opening it for anyone who has the link
+    pub async fn sign_link(&self, url: &str, request: &SignedLinkRequest) -> Result<SignedLink, ClientError> {
+        self.comparison_json(Method::POST, url, &["signed-links"], Some(request)).await
+    }
+
+    // The export a signed link opens, without any token
+    pub async fn signed_export(&self, link: &SignedLink) -> Result<Vec<u8>, ClientError> {
+        self.bytes(self.request(Method::GET, &link.path)).await
+    }
+
+    // Copy a comparison to a new URL, returned with the copy, whose owner token is kept
+    pub async fn clone_comparison(&self, url: &str) -> Result<ComparisonArchive, ClientError> {
+        let request = self.comparison_request(Method::POST, url, &["clone"]);
+        let response = self.send(request, None).await?;
+        let token = response.headers().get(EDIT_TOKEN_HEADER).and_then(|token| token.to_str().ok()).map(str::to_string);
+        let copy: ComparisonArchive = response.json().await?;
+        if let Some(token) = token {
+            self.set_edit_token(&copy.url, &token);
+        }
+        Ok(copy)
+    }
+
+    // Directory and publishing
+
+    pub async fn directory(&self) -> Result<Vec<DirectoryEntry>, ClientError> {
+        self.json(self.request(Method::GET, "/api/directory"), None).await
+    }
+
+    pub async fn listing(&self, url: &str) -> Result<ComparisonListing, ClientError> {
+        self.comparison_json(Method::GET, url, &["listing"], None::<&()>).await
+    }
+
+    pub async fn publish_listing(&self, url: &str, listing: &ComparisonListing) -> Result<ComparisonAnnouncement, ClientError> {
+        self.comparison_json(Method::PUT, url, &["listing"], Some(listing)).await
+    }
+
+    pub async fn remove_listing(&self, url: &str) -> Result<(), ClientError> {
+        self.empty(self.comparison_request(Method::DELETE, url, &["listing"]), Some(url)).await
+    }
+
+    pub async fn publishing_settings(&self, url: &str) -> Result<PublishingSettings, ClientError> {
+        self.comparison_json(Method::GET, url, &["publishing"], None::<&()>).await
+    }
+
+    pub async fn set_publishing_settings(&self, url: &str, settings: &PublishingSettings) -> Result<PublishingSettings, ClientError> {
+        self.comparison_json(Method::PUT, url, &["publishing"], Some(settings)).await
+    }
+
+    pub async fn mirror_settings(&self, url: &str) -> Result<MirrorSettings, ClientError> {
+        self.comparison_json(Method::GET, url, &["mirror"], None::<&()>).await
+    }
+
+    pub async fn set_mirror_settings(&self, url: &str, settings: &MirrorSettings) -> Result<MirrorSettings, ClientError> {
+        self.comparison_json(Method::PUT, url, &["mirror"], Some(settings)).await
+    }
+
+    // Presence
+
+    pub async fn presence_heartbeat(&self, url: &str, heartbeat: &Heartbeat) -> Result<PresenceState, ClientError> {
+        self.comparison_json(Method::POST, url, &["presence"], Some(heartbeat)).await
+    }
+
+    pub async fn editor_metrics(&self) -> Result<EditorMetrics, ClientError> {
+        self.json(self.admin_request(Method::GET, "/api/presence/metrics"), None).await
+    }
+
+    // Public
+
+    pub async fn instance_info(&self) -> Result<InstanceInfo, ClientError> {
+        self.json(self.request(Method::GET, "/api/instance"), None).await
+    }
+
+    pub async fn item_card(&self, wikidata_id: &str) -> Result<SpecCard, ClientError> {
+        self.json(self.request(Method::GET, &format!("/api/public/items/{}/card", encode_segment(wikidata_id))), None).await
+    }
+
+    pub async fn report_client_error(&self, report: &ClientErrorReport) -> Result<(), ClientError> {
+        self.empty(self.request(Method::POST, "/api/diagnostics/errors").json(report), None).await
+    }
+
+    // Wikidata cache
+
+    pub async fn cached_labels(&self, ids: &[&str]) -> Result<HashMap<String, String>, ClientError> {
+        self.json(self.request(Method::GET, "/api/wikidata/labels").query(&[("ids", ids.join(","))]), None).await
+    }
+
+    pub async fn cached_claims(&self, ids: &[&str]) -> Result<HashMap<String, EntityClaims>, ClientError> {
+        self.json(self.request(Method::GET, "/api/wikidata/claims").query(&[("ids", ids.join(","))]), None).await
+    }
+
+    // Setup
+
+    pub async fn setup_status(&self) -> Result<SetupStatus, ClientError> {
+        self.json(self.request(Method::GET, "/api/setup"), None).await
+    }
+
+    pub async fn complete_setup(&self, setup: &SetupRequest) -> Result<SetupResult, ClientError> {
+        self.json(self.request(Method::POST, "/api/setup").json(setup), None).await
+    }
+
+    // Admin, with the admin token
+
+    pub async fn instance_settings(&self) -> Result<InstanceSettings, ClientError> {
+        self.json(self.admin_request(Method::GET, "/api/admin/settings"), None).await
+    }
+
+    pub async fn set_instance_settings(&self, settings: &InstanceSettings) -> Result<InstanceSettings, ClientError> {
+        self.json(self.admin_request(Method::PUT, "/api/admin/settings").json(settings), None).await
+    }
+
+    pub async fn backup_workspace(&self) -> Result<BackupSummary, ClientError> {
+        self.json(self.admin_request(Method::POST, "/api/admin/backup"), None).await
+    }
+
+    pub async fn backup_to_storage(&self) -> Result<StorageBackupSummary, ClientError> {
+        self.json(self.admin_request(Method::POST, "/api/admin/backup/storage"), None).await
+    }
+
+    pub async fn restore_workspace(&self, request: &RestoreRequest) -> Result<RestoreSummary, ClientError> {
+        self.json(self.admin_request(Method::POST, "/api/admin/restore").json(request), None).await
+    }
+
+    // Consistent copy of the SQLite database
+    pub async fn download_database(&self) -> Result<Vec<u8>, ClientError> {
+        self.bytes(self.admin_request(Method::GET, "/api/admin/database")).await
+    }
+
+    pub async fn upload_database(&self, database: Vec<u8>) -> Result<DatabaseRestoreSummary, ClientError> {
+        let request = self.admin_request(Method::PUT, "/api/admin/database").header(reqwest::header::CONTENT_TYPE, "application/vnd.sqlite3").body(database);
+        self.json(request, None).await
+    }
+
+    // Zip of the server log, audit tail and configuration, for bug reports
+    pub async fn download_diagnostics(&self) -> Result<Vec<u8>, ClientError> {
+        self.bytes(self.admin_request(Method::GET, "/api/admin/diagnostics")).await
+    }
+
+    // Comparisons slowest to answer since the server started, with what would make them lighter
+    pub async fn heaviest_comparisons(&self, limit: usize) -> Result<Vec<ComparisonLoad>, ClientError> {
+        self.json(self.admin_request(Method::GET, "/api/admin/reports/heaviest-comparisons").query(&[("limit", limit)]), None).await
+    }
+
+    pub async fn api_keys(&self) -> Result<Vec<ApiKey>, ClientError> {
+        self.json(self.admin_request(Method::GET, "/api/admin/api-keys"), None).await
+    }
+
+    // New API key, the only time the key itself is returned
+    pub async fn create_api_key(&self, request: &ApiKeyRequest) -> Result<CreatedApiKey, ClientError> {
+        self.json(self.admin_request(Method::POST, "/api/admin/api-keys").json(request), None).await
+    }
+
+    pub async fn revoke_api_key(&self, id: i64) -> Result<(), ClientError> {
+        self.send(self.admin_request(Method::DELETE, &format!("/api/admin/api-keys/{}", id)), None).await?;
+        Ok(())
+    }
+}
+
+// Every client method against a running server, recording the routes they reach, which
+// must be the operations of the OpenAPI spec apart from the unsupported ones
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use super::*;
+    use crate::api::ApiDoc;
+    use crate::app_state::AppState;
+    use crate::config::{Config, WikidataConfig};
+    use crate::models::access::Visibility;
+    use crate::models::presence::Heartbeat;
+    use actix_web::dev::Service;
+    use actix_web::{App, HttpServer};
+    use leptos::logging::log;
+    use leptos::LeptosOptions;
+    use std::collections::BTreeSet;
+    use std::sync::Arc;
+    use utoipa::OpenApi;
+
+    const URL: &str = "https://example.com/laptops";
+
+    type Operations = Arc<Mutex<BTreeSet<(String, String)>>>;
+
+    // Server on a free port, recording the method and route pattern of every request
+    async fn start_server(operations: Operations) -> String {
+        let config = Config {
+            wikidata: WikidataConfig {
+                endpoint: "http://127.0.0.1:9/sparql".into(),
+                api_endpoint: "http://127.0.0.1:9/w/api.php".into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let state = AppState::builder()
+            .config(config)
+            .database_path(":memory:")
+            .leptos_options(LeptosOptions::builder().output_name("compareware").build())
+            .nostr_relays(Vec::new())
+            .build()
+            .await
+            .unwrap();
+        let server = HttpServer::new(move || {
+            let operations = operations.clone();
+            App::new()
+                .wrap_fn(move |req, srv| {
+                    let operations = operations.clone();
+                    let response = srv.call(req);
+                    async move {
+                        let response = response.await?;
+                        if let Some(pattern) = response.request().match_pattern() {
+                            operations.lock().unwrap().insert((response.request().method().as_str().to_lowercase(), pattern));
+                        }
+                        Ok(response)
+                    }
+                })
+                .configure(|config| state.configure(config))
+                .configure(crate::routes::configure)
+        })
+        .workers(1)
+        .disable_signals()
+        .bind("127.0.0.1:0")
+        .unwrap();
+        let address = server.addrs()[0];
+        actix_web::rt::spawn(server.run());
+        format!("http://{}", address)
+    }
+
+    fn test_item(id: &str) -> Item {
+        Item {
+            id: id.into(),
+            name: format!("Laptop {}", id),
+            description: "Light".into(),
+            wikidata_id: Some("Q42".into()),
+            custom_properties: HashMap::from([("weight".to_string(), "1.2 kg".to_string())]),
+            quantities: HashMap::new(),
+            group: None,
+            tip_recipient: None,
+            status: None,
+            sources: HashMap::new(),
+            updated_at: None,
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_client_covers_the_spec() {
+        log!("[TEST] Starting test_client_covers_the_spec");
+        let operations = Operations::default();
+        let base_url = start_server(operations.clone()).await;
+
+        // Setup, then the admin token of the setup for the admin operations
+        let setup_client = CompareWareClient::new(&base_url);
+        assert!(setup_client.setup_status().await.unwrap().needed);
+        let setup = SetupRequest { private: false, wikidata_language: "en".into(), demo_data: false };
+        let admin_token = setup_client.complete_setup(&setup).await.unwrap().admin_token;
+        let client = CompareWareClient::new(&base_url).with_admin_token(&admin_token);
+
+        // Test the first write hands out the owner's token, kept for later edits
+        client.save_item(URL, &test_item("item1")).await.unwrap();
+        assert!(client.edit_token(URL).is_some());
+        client.save_items(URL, &[test_item("item2"), test_item("item3")]).await.unwrap();
+        assert_eq!(client.get_items(URL).await.unwrap().len(), 3);
+        let stranger = CompareWareClient::new(&base_url);
+        let refused = stranger.delete_item(URL, "item1").await;
+        assert!(matches!(refused, Err(ClientError::Api { status: 403, .. })), "{:?}", refused);
+
+        assert_eq!(client.delete_item(URL, "item3").await.unwrap().rows_affected, 1);
+        assert_eq!(client.deleted_items(URL).await.unwrap()[0].id, "item3");
+        client.restore_item(URL, "item3").await.unwrap();
+        assert!(!client.history(URL, Some("item3"), None).await.unwrap().is_empty());
+        assert_eq!(client.property_value(URL, "item1", "weight").await.unwrap().value, "1.2 kg");
+        let _ = client.reusable_values(URL, "Q42").await;
+        let _ = client.tip_item(URL, "item1", &TipRequest { amount_sats: 21, comment: None }).await;
+        client.review_item(URL, "item1", &ReviewRequest { reviewer_id: "reviewer1".into(), rating: 4 }).await.unwrap();
+        assert_eq!(client.ratings(URL, Some("reviewer1")).await.unwrap()["item1"].mine, Some(4));
+        assert!(client.reviews_feed(URL).await.unwrap().contains("<feed"));
+        assert!(client.history_feed(URL).await.unwrap().contains("<feed"));
+        let _ = client.import_items(URL, &ItemQuery { query: "instances of smartphone".into() }).await;
+        let _ = client.refresh_from_wikidata(URL).await;
+
+        client.add_property(URL, &PropertySelection { property: "weight".into(), custom: true }).await.unwrap();
+        let properties = client.selected_properties(URL).await.unwrap();
+        client.reorder_properties(URL, &properties).await.unwrap();
+        client.set_property_settings(URL, "weight", &PropertySettings::default()).await.unwrap();
+        client.property_settings(URL).await.unwrap();
+        let overrides = PropertyOverride { label: Some("Weight".into()), description: None };
+        client.set_property_override(URL, "weight", &overrides).await.unwrap();
+        assert_eq!(client.property_overrides(URL).await.unwrap()["weight"], overrides);
+        let _ = client.property_suggestions(URL, &["Q42"]).await;
+        let deletion = client.delete_property(URL, "weight").await.unwrap();
+        client.undo_property_deletion(URL, deletion.deletion_id).await.unwrap();
+        assert_eq!(client.rename_property(URL, "weight", "mass").await.unwrap().values["item1"], "1.2 kg");
+        client.rename_property(URL, "mass", "weight").await.unwrap();
+
+        let metadata = ComparisonMetadata { title: "Laptops".into(), description: String::new(), ..Default::default() };
+        assert_eq!(client.set_metadata(URL, &metadata).await.unwrap().title, "Laptops");
+        client.metadata(URL).await.unwrap();
+        client.set_decision(URL, &DecisionRequest { item_id: "item1".into(), rationale: String::new() }).await.unwrap();
+        client.clear_decision(URL).await.unwrap();
+        assert_eq!(client.list_comparisons("laptops", false, 1).await.unwrap().total, 1);
+        let hits = client.search("lapt").await.unwrap();
+        assert!(!hits.is_empty() && hits.iter().all(|hit| hit.url == URL));
+
+        assert!(client.edit_access(URL).await.unwrap().can_edit());
+        let _ = client.protect_comparison(URL).await;
+        client.invite_collaborator(URL).await.unwrap();
+        let private = VisibilitySetting { visibility: Visibility::Private };
+        assert_eq!(client.set_visibility(URL, &private).await.unwrap(), private);
+        let hidden = stranger.get_items(URL).await;
+        assert!(matches!(hidden, Err(ClientError::Api { status: 403, .. })), "{:?}", hidden);
+        client.set_visibility(URL, &VisibilitySetting { visibility: Visibility::Public }).await.unwrap();
+        let link = client.create_snapshot(URL).await.unwrap();
+        assert_eq!(client.snapshot(&link.id).await.unwrap().items.len(), 3);
+        let export = client.export_comparison(URL).await.unwrap();
+        assert_eq!(client.import_comparison("https://imported.com", &export).await.unwrap().items.len(), 3);
+        let copy = client.clone_comparison(URL).await.unwrap();
+        assert!(client.export(URL, "csv", true).await.unwrap().starts_with(b"Name,Description"));
+        assert!(client.export(URL, "xlsx", false).await.unwrap().starts_with(b"PK"));
+        assert!(client.export(URL, "md", false).await.unwrap().starts_with(b"# "));
+        assert!(client.export_as(URL, "text/html", false).await.unwrap().starts_with(b"<!DOCTYPE html>"));
+        let link = client.sign_link(URL, &SignedLinkRequest { format: "md".into(), embed: false, expires_in_secs: None }).await.unwrap();
+        assert!(stranger.signed_export(&link).await.unwrap().starts_with(b"# "));
+        assert!(client.edit_access(&copy.url).await.unwrap().role.is_some());
+
+        let _ = client.directory().await;
+        let _ = client.listing(URL).await;
+        let _ = client.publish_listing(URL, &ComparisonListing { title: "Laptops".into(), category: "Computers".into() }).await;
+        let _ = client.remove_listing(URL).await;
+        client.set_publishing_settings(URL, &client.publishing_settings(URL).await.unwrap()).await.unwrap();
+        client.set_mirror_settings(URL, &client.mirror_settings(URL).await.unwrap()).await.unwrap();
+        client.presence_heartbeat(URL, &Heartbeat { session_id: "session1".into(), cell: None }).await.unwrap();
+        client.editor_metrics().await.unwrap();
+
+        client.instance_info().await.unwrap();
+        let _ = client.item_card("Q42").await;
+        client.report_client_error(&ClientErrorReport { message: "Failed".into(), page: "/laptops".into() }).await.unwrap();
+        let _ = client.cached_labels(&["Q42"]).await;
+        let _ = client.cached_claims(&["Q42"]).await;
+
+        let settings = client.instance_settings().await.unwrap();
+        client.set_instance_settings(&settings).await.unwrap();
+        let _ = client.backup_workspace().await;
+        let _ = client.backup_to_storage().await;
+        let _ = client.restore_workspace(&RestoreRequest { backup_id: None }).await;
+        client.download_diagnostics().await.unwrap();
+        assert_eq!(client.heaviest_comparisons(1).await.unwrap().len(), 1);
+        let created = client.create_api_key(&ApiKeyRequest { name: "Importer".into(), url: None }).await.unwrap();
+        assert_eq!(client.api_keys().await.unwrap(), [created.api_key.clone()]);
+        let script = CompareWareClient::new(&base_url).with_api_key(&created.key);
+        script.set_metadata(URL, &metadata).await.unwrap();
+        client.revoke_api_key(created.api_key.id).await.unwrap();
+        let revoked = script.metadata(URL).await;
+        assert!(matches!(revoked, Err(ClientError::Api { status: 401, .. })), "{:?}", revoked);
+        let database = client.download_database().await.unwrap();
+        assert_eq!(client.upload_database(database).await.unwrap().comparisons, 3);
+        let unauthorized = stranger.instance_settings().await;
+        assert!(matches!(unauthorized, Err(ClientError::Api { status: 401, .. })), "{:?}", unauthorized);
+
+        // Test the requests reached every operation of the spec
+        let spec: BTreeSet<(String, String)> = ApiDoc::openapi()
+            .paths
+            .paths
+            .iter()
+            .flat_map(|(path, item)| item.operations.keys().map(move |method| (serde_json::to_value(method).unwrap().as_str().unwrap().to_string(), path.clone())))
+            .filter(|(method, path)| !UNSUPPORTED_OPERATIONS.contains(&(method.as_str(), path.as_str())))
+            .collect();
+        let reached = operations.lock().unwrap().clone();
+        let missing: Vec<_> = spec.difference(&reached).collect();
+        assert!(missing.is_empty(), "Operations without a client method: {:?}", missing);
+        log!("[TEST] test_client_covers_the_spec completed successfully");
+    }
+}
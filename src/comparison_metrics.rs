@@ -0,0 +1,113 @@
+// Time and size of the answers of each comparison's API and the Wikidata cache misses of
+// its requests, kept in memory since the server started. Together with the rows the
+// comparisons hold, they make the heaviest comparisons report of the admin page
+use crate::models::diagnostics::ComparisonRequests;
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::web;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Comparisons measured at most, so requests to made-up URLs can't fill the memory
+const MAX_MEASURED_COMPARISONS: usize = 10_000;
+
+#[derive(Default)]
+pub struct ComparisonMetrics {
+    comparisons: Mutex<HashMap<String, ComparisonRequests>>,
+}
+
+impl ComparisonMetrics {
+    fn update(&self, url: &str, change: impl FnOnce(&mut ComparisonRequests)) {
+        let mut comparisons = self.comparisons.lock().unwrap();
+        if let Some(requests) = comparisons.get_mut(url) {
+            change(requests);
+        } else if comparisons.len() < MAX_MEASURED_COMPARISONS {
+            change(comparisons.entry(url.to_string()).or_default());
+        }
+    }
+
+    pub fn record_response(&self, url: &str, duration: Duration, bytes: u64) {
+        let ms = duration.as_millis() as u64;
+        self.update(url, |requests| {
+            requests.requests += 1;
+            requests.total_ms += ms;
+            requests.slowest_ms = requests.slowest_ms.max(ms);
+            requests.total_bytes += bytes;
+        });
+    }
+
+    pub fn record_cache_misses(&self, url: &str, misses: usize) {
+        if misses > 0 {
+            self.update(url, |requests| requests.wikidata_cache_misses += misses as u64);
+        }
+    }
+
+    // Requests of every comparison measured so far
+    pub fn requests(&self) -> HashMap<String, ComparisonRequests> {
+        self.comparisons.lock().unwrap().clone()
+    }
+}
+
+// Comparison URL of a request to the /api/urls/{url} scope
+#[derive(Deserialize)]
+pub(crate) struct ComparisonPath {
+    pub url: String,
+}
+
+// Middleware of the /api/urls/{url} scope, measuring the answers of a known size. Streams
+// such as the live updates stay open as long as the page and would only blur the times
+pub async fn measure_requests(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let metrics = req.app_data::<web::Data<ComparisonMetrics>>().cloned();
+    let url = req.match_info().load::<ComparisonPath>().ok().map(|path| path.url);
+    let started = Instant::now();
+    let response = next.call(req).await?;
+    if let (Some(metrics), Some(url), BodySize::Sized(bytes)) = (metrics, url, response.response().body().size()) {
+        metrics.record_response(&url, started.elapsed(), bytes);
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::url_path::comparison_api_path;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::{middleware, App, HttpResponse};
+    use leptos::logging::log;
+
+    #[actix_web::test]
+    async fn test_measure_requests() {
+        log!("[TEST] Starting test_measure_requests");
+        let metrics = web::Data::new(ComparisonMetrics::default());
+        let app = test::init_service(
+            App::new().app_data(metrics.clone()).service(
+                web::scope("/api/urls/{url}")
+                    .wrap(middleware::from_fn(measure_requests))
+                    .route("/items", web::get().to(|| async { HttpResponse::Ok().body("[1, 2]") }))
+                    .route("/items/{item_id}", web::get().to(|| async { HttpResponse::Ok().body("{}") })),
+            ),
+        )
+        .await;
+        let url = "https://example.com/laptops?sort=name";
+        for _ in 0..2 {
+            test::call_service(&app, TestRequest::get().uri(&comparison_api_path(url, &["items"])).to_request()).await;
+        }
+        test::call_service(&app, TestRequest::get().uri(&comparison_api_path(url, &["items", "item-1"])).to_request()).await;
+        // Test requests are counted under the decoded URL with the size of their body
+        let requests = metrics.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[url].requests, 3);
+        assert_eq!(requests[url].total_bytes, 14);
+
+        metrics.record_cache_misses(url, 3);
+        metrics.record_cache_misses(url, 0);
+        assert_eq!(metrics.requests()[url].wikidata_cache_misses, 3);
+        log!("[TEST] test_measure_requests completed successfully");
+    }
+}
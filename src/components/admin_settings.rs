@@ -0,0 +1,350 @@
+use crate::api_client::{api_keys, create_api_key, download_diagnostics, heaviest_comparisons, instance_settings, revoke_api_key, set_instance_settings};
+use crate::components::snapshot::format_timestamp;
+use crate::models::api_key::{ApiKey, ApiKeyRequest};
+use crate::models::diagnostics::ComparisonLoad;
+use crate::models::instance::{InstanceFeatures, InstanceSettings, DEFAULT_WIKIDATA_LANGUAGE, MAX_REQUESTS_PER_MINUTE};
+use crate::models::property::MAX_LANG_LENGTH;
+use leptos::*;
+use wasm_bindgen::JsCast;
+
+// Settings page of the instance at /admin. The admin token is only kept while the
+// page is open and sent as a bearer token with each request
+#[component]
+pub fn AdminSettings() -> impl IntoView {
+    let (token, set_token) = create_signal(String::new());
+    // Settings as loaded with the token, None until they are
+    let (settings, set_settings) = create_signal(None::<InstanceSettings>);
+    let (status, set_status) = create_signal(None::<String>);
+    let (busy, set_busy) = create_signal(false);
+    // Heaviest comparisons report, None until it's loaded
+    let (heaviest, set_heaviest) = create_signal(None::<Vec<ComparisonLoad>>);
+    let (show_api_keys, set_show_api_keys) = create_signal(false);
+
+    let load = move |_| {
+        set_busy.set(true);
+        set_status.set(None);
+        spawn_local(async move {
+            match instance_settings(&token.get_untracked()).await {
+                Ok(loaded) => set_settings.set(Some(loaded)),
+                Err(err) => set_status.set(Some(format!("Failed to load the settings: {}", err))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let save = move |_| {
+        let Some(current) = settings.get_untracked() else {
+            return;
+        };
+        set_busy.set(true);
+        set_status.set(None);
+        spawn_local(async move {
+            match set_instance_settings(&token.get_untracked(), &current).await {
+                Ok(saved) => {
+                    set_settings.set(Some(saved));
+                    set_status.set(Some("Settings saved, they apply right away.".to_string()));
+                }
+                Err(err) => set_status.set(Some(format!("Failed to save the settings: {}", err))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let save_diagnostics = move |_| {
+        set_busy.set(true);
+        set_status.set(None);
+        spawn_local(async move {
+            match download_diagnostics(&token.get_untracked()).await {
+                Ok(bundle) => {
+                    let file_name = bundle.file_name.unwrap_or_else(|| "compareware-diagnostics.zip".to_string());
+                    if let Err(err) = save_file(&bundle.bytes, &file_name) {
+                        set_status.set(Some(format!("Failed to save the diagnostics: {:?}", err)));
+                    }
+                }
+                Err(err) => set_status.set(Some(format!("Failed to download the diagnostics: {}", err))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let load_heaviest = move |_| {
+        set_busy.set(true);
+        set_status.set(None);
+        spawn_local(async move {
+            match heaviest_comparisons(&token.get_untracked()).await {
+                Ok(loads) => set_heaviest.set(Some(loads)),
+                Err(err) => set_status.set(Some(format!("Failed to load the report: {}", err))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    // Read and edit the loaded settings
+    let read = move |field: fn(&InstanceSettings) -> String| settings.with(|settings| settings.as_ref().map(field).unwrap_or_default());
+    let checked = move |field: fn(&InstanceSettings) -> bool| settings.with(|settings| settings.as_ref().is_some_and(field));
+    let update = move |change: &dyn Fn(&mut InstanceSettings)| {
+        set_settings.update(|settings| {
+            if let Some(settings) = settings {
+                change(settings);
+            }
+        });
+    };
+    let feature = move |label: &'static str, get: fn(&InstanceFeatures) -> bool, set: fn(&mut InstanceFeatures, bool)| {
+        view! {
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.with(|settings| settings.as_ref().is_some_and(|settings| get(&settings.features)))
+                    on:change=move |event| update(&|settings| set(&mut settings.features, event_target_checked(&event)))
+                />
+                { label }
+            </label>
+        }
+    };
+    let rate_limit = |value: String| value.trim().parse().unwrap_or_default();
+
+    view! {
+        <div class="admin-settings">
+            <h2>{ "Instance settings" }</h2>
+            <label>
+                { "Admin token" }
+                <input
+                    type="password"
+                    prop:value=move || token.get()
+                    on:input=move |event| set_token.set(event_target_value(&event))
+                />
+            </label>
+            <button disabled=move || busy.get() on:click=load>{ "Load settings" }</button>
+            <button
+                disabled=move || busy.get()
+                title="Recent server log, admin actions, schema version, configuration without secrets and errors visitors saw, to attach to bug reports"
+                on:click=save_diagnostics
+            >
+                { "Download diagnostics" }
+            </button>
+            <button
+                disabled=move || busy.get()
+                title="Comparisons slowest to answer since the server started, with what would make them lighter"
+                on:click=load_heaviest
+            >
+                { "Heaviest comparisons" }
+            </button>
+            <button
+                disabled=move || busy.get()
+                title="Keys letting scripts and integrations edit comparisons through the API"
+                on:click=move |_| set_show_api_keys.update(|shown| *shown = !*shown)
+            >
+                { "API keys" }
+            </button>
+            {move || status.get().map(|status| view! { <p class="admin-status">{status}</p> })}
+            {move || heaviest.get().map(|loads| view! { <HeaviestComparisons loads /> })}
+            {move || show_api_keys.get().then(|| view! { <ApiKeys token /> })}
+            <Show when=move || settings.with(Option::is_some)>
+                <fieldset>
+                    <legend>{ "Wikidata" }</legend>
+                    <label>
+                        { "Requests per minute of one visitor" }
+                        <input
+                            type="number"
+                            min=1
+                            max=MAX_REQUESTS_PER_MINUTE
+                            prop:value=move || read(|settings| settings.wikidata_client_rpm.to_string())
+                            on:input=move |event| update(&|settings| settings.wikidata_client_rpm = rate_limit(event_target_value(&event)))
+                        />
+                    </label>
+                    <label>
+                        { "Requests per minute of the whole instance" }
+                        <input
+                            type="number"
+                            min=1
+                            max=MAX_REQUESTS_PER_MINUTE
+                            prop:value=move || read(|settings| settings.wikidata_global_rpm.to_string())
+                            on:input=move |event| update(&|settings| settings.wikidata_global_rpm = rate_limit(event_target_value(&event)))
+                        />
+                    </label>
+                    <label>
+                        { "Language of searches and imports" }
+                        <input
+                            placeholder=DEFAULT_WIKIDATA_LANGUAGE
+                            maxlength=MAX_LANG_LENGTH
+                            prop:value=move || read(|settings| settings.wikidata_language.clone())
+                            on:input=move |event| update(&|settings| settings.wikidata_language = event_target_value(&event))
+                        />
+                    </label>
+                </fieldset>
+                <fieldset>
+                    <legend>{ "Visibility" }</legend>
+                    <label>
+                        <input
+                            type="checkbox"
+                            prop:checked=move || checked(|settings| settings.private)
+                            on:change=move |event| update(&|settings| settings.private = event_target_checked(&event))
+                        />
+                        { "Private instance: hide the comparisons index and don't list comparisons in the Nostr directory" }
+                    </label>
+                </fieldset>
+                <fieldset>
+                    <legend>{ "Features" }</legend>
+                    {feature("Imports from Wikidata queries", |features| features.imports, |features, on| features.imports = on)}
+                    {feature("Snapshots", |features| features.snapshots, |features, on| features.snapshots = on)}
+                    {feature("Lightning tips", |features| features.tips, |features, on| features.tips = on)}
+                    {feature("Ratings and reviews", |features| features.reviews, |features, on| features.reviews = on)}
+                </fieldset>
+                <button class="save-button" disabled=move || busy.get() on:click=save>{ "Save" }</button>
+            </Show>
+        </div>
+    }
+}
+
+// Table of the heaviest comparisons report, one row per comparison with its remediations
+#[component]
+fn HeaviestComparisons(loads: Vec<ComparisonLoad>) -> impl IntoView {
+    if loads.is_empty() {
+        return view! { <p class="admin-status">{ "No comparisons yet." }</p> }.into_view();
+    }
+    view! {
+        <table class="heaviest-comparisons">
+            <thead>
+                <tr>
+                    <th>{ "Comparison" }</th>
+                    <th>{ "Items (inactive, in the trash)" }</th>
+                    <th>{ "Values" }</th>
+                    <th>{ "Requests" }</th>
+                    <th>{ "Average time" }</th>
+                    <th>{ "Average size" }</th>
+                    <th>{ "Wikidata cache misses" }</th>
+                    <th>{ "Suggestions" }</th>
+                </tr>
+            </thead>
+            <tbody>
+                {loads.into_iter().map(|load| view! {
+                    <tr>
+                        <td><a href=load.rows.url.clone() target="_blank" rel="noopener">{load.rows.url.clone()}</a></td>
+                        <td>{ format!("{} ({}, {})", load.rows.items, load.rows.inactive_items, load.rows.trashed_items) }</td>
+                        <td>{load.rows.values}</td>
+                        <td>{load.requests.requests}</td>
+                        <td>{ format!("{:.0} ms", load.average_ms) }</td>
+                        <td>{ format!("{:.1} KB", load.average_bytes as f64 / 1024.0) }</td>
+                        <td>{load.requests.wikidata_cache_misses}</td>
+                        <td>
+                            <ul>
+                                {load.remediations.into_iter().map(|remediation| view! { <li>{remediation}</li> }).collect_view()}
+                            </ul>
+                        </td>
+                    </tr>
+                }).collect_view()}
+            </tbody>
+        </table>
+    }
+    .into_view()
+}
+
+// API keys of the instance, loaded with the admin token when shown. A created key is
+// shown once, since only its hash is stored
+#[component]
+fn ApiKeys(token: ReadSignal<String>) -> impl IntoView {
+    let (keys, set_keys) = create_signal(Vec::<ApiKey>::new());
+    let (name, set_name) = create_signal(String::new());
+    let (url, set_url) = create_signal(String::new());
+    let (created, set_created) = create_signal(None::<String>);
+    let (status, set_status) = create_signal(None::<String>);
+
+    let load = move || {
+        spawn_local(async move {
+            match api_keys(&token.get_untracked()).await {
+                Ok(loaded) => set_keys.set(loaded),
+                Err(err) => set_status.set(Some(format!("Failed to load the API keys: {}", err))),
+            }
+        });
+    };
+    load();
+
+    let create = move |_| {
+        let url = url.get_untracked().trim().to_string();
+        let request = ApiKeyRequest { name: name.get_untracked().trim().to_string(), url: (!url.is_empty()).then_some(url) };
+        if let Some(problem) = request.problem() {
+            set_status.set(Some(problem));
+            return;
+        }
+        set_status.set(None);
+        spawn_local(async move {
+            match create_api_key(&token.get_untracked(), &request).await {
+                Ok(new_key) => {
+                    set_created.set(Some(new_key.key));
+                    set_keys.update(|keys| keys.insert(0, new_key.api_key));
+                    set_name.set(String::new());
+                    set_url.set(String::new());
+                }
+                Err(err) => set_status.set(Some(format!("Failed to create the API key: {}", err))),
+            }
+        });
+    };
+
+    let revoke = move |id: i64| {
+        set_status.set(None);
+        spawn_local(async move {
+            match revoke_api_key(&token.get_untracked(), id).await {
+                Ok(()) => set_keys.update(|keys| keys.retain(|key| key.id != id)),
+                Err(err) => set_status.set(Some(format!("Failed to revoke the API key: {}", err))),
+            }
+        });
+    };
+
+    view! {
+        <fieldset class="api-keys">
+            <legend>{ "API keys" }</legend>
+            {move || status.get().map(|status| view! { <p class="admin-status">{status}</p> })}
+            {move || created.get().map(|key| view! {
+                <p>{ "Copy the new key now, it won't be shown again:" }</p>
+                <input class="setup-token" readonly prop:value=key />
+            })}
+            <ul>
+                {move || keys.get().into_iter().map(|key| {
+                    let id = key.id;
+                    let last_used = key.last_used_at.map_or_else(|| "never used".to_string(), |at| format!("last used {}", format_timestamp(at)));
+                    view! {
+                        <li>
+                            <strong>{key.name}</strong>
+                            { format!(
+                                " - {}, created {}, {} ",
+                                key.url.unwrap_or_else(|| "all comparisons".to_string()),
+                                format_timestamp(key.created_at),
+                                last_used,
+                            ) }
+                            <button on:click=move |_| revoke(id)>{ "Revoke" }</button>
+                        </li>
+                    }
+                }).collect_view()}
+            </ul>
+            <label>
+                { "Name" }
+                <input
+                    placeholder="Price sync"
+                    prop:value=move || name.get()
+                    on:input=move |event| set_name.set(event_target_value(&event))
+                />
+            </label>
+            <label>
+                { "Comparison it may edit, all when empty" }
+                <input
+                    placeholder="/phones"
+                    prop:value=move || url.get()
+                    on:input=move |event| set_url.set(event_target_value(&event))
+                />
+            </label>
+            <button on:click=create>{ "Create API key" }</button>
+        </fieldset>
+    }
+}
+
+// Offer bytes as a file download, through a temporary object URL
+fn save_file(bytes: &[u8], file_name: &str) -> Result<(), wasm_bindgen::JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let blob = web_sys::Blob::new_with_u8_array_sequence(&parts)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+    let anchor: web_sys::HtmlAnchorElement = document().create_element("a")?.dyn_into()?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+    web_sys::Url::revoke_object_url(&url)
+}
@@ -0,0 +1,160 @@
+use crate::components::perf_overlay::record_render;
+use crate::models::chart::{chart_data, numeric_properties, radar_point, ChartData};
+use crate::models::item::Item;
+use leptos::*;
+
+// Size in pixels of the charts
+const CHART_WIDTH: f64 = 640.0;
+const BAR_HEIGHT: f64 = 24.0;
+const BAR_GAP: f64 = 8.0;
+// Room for the item names left of the bars and the values right of them
+const BAR_LABEL_WIDTH: f64 = 160.0;
+const BAR_VALUE_WIDTH: f64 = 120.0;
+const RADAR_SIZE: f64 = 480.0;
+// Room for the item names around the radar
+const RADAR_MARGIN: f64 = 80.0;
+// Rings drawn at each quarter of the largest value
+const RADAR_RINGS: [f64; 4] = [0.25, 0.5, 0.75, 1.0];
+
+#[derive(Clone, Copy, PartialEq)]
+enum ChartKind {
+    Bar,
+    Radar,
+}
+
+fn bar_chart(data: ChartData) -> View {
+    let height = data.values.len() as f64 * (BAR_HEIGHT + BAR_GAP);
+    let bar_width = CHART_WIDTH - BAR_LABEL_WIDTH - BAR_VALUE_WIDTH;
+    let bars = data
+        .values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let y = index as f64 * (BAR_HEIGHT + BAR_GAP);
+            let width = data.fraction(value.value) * bar_width;
+            view! {
+                <g class="chart-bar">
+                    <title>{ format!("{}: {}", value.label, value.text) }</title>
+                    <text x=BAR_LABEL_WIDTH - 8.0 y=y + BAR_HEIGHT / 2.0 text-anchor="end" dominant-baseline="middle">{ value.label.clone() }</text>
+                    <rect x=BAR_LABEL_WIDTH y=y width=width height=BAR_HEIGHT />
+                    <text x=BAR_LABEL_WIDTH + width + 6.0 y=y + BAR_HEIGHT / 2.0 dominant-baseline="middle">{ value.text.clone() }</text>
+                </g>
+            }
+        })
+        .collect_view();
+    view! {
+        <svg class="comparison-chart" width=CHART_WIDTH height=height viewBox=format!("0 0 {} {}", CHART_WIDTH, height) role="img">
+            {bars}
+        </svg>
+    }
+    .into_view()
+}
+
+fn radar_chart(data: ChartData) -> View {
+    let count = data.values.len();
+    let radius = RADAR_SIZE / 2.0 - RADAR_MARGIN;
+    let center = RADAR_SIZE / 2.0;
+    let polygon = |fractions: Vec<f64>| {
+        fractions
+            .iter()
+            .enumerate()
+            .map(|(index, fraction)| {
+                let (x, y) = radar_point(index, count, *fraction, radius);
+                format!("{:.1},{:.1}", center + x, center + y)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let rings = RADAR_RINGS.iter().map(|ring| view! { <polygon class="chart-radar-ring" points=polygon(vec![*ring; count]) /> }).collect_view();
+    let spokes = data
+        .values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let (x, y) = radar_point(index, count, 1.0, radius);
+            let (label_x, label_y) = radar_point(index, count, 1.0, radius + 12.0);
+            let anchor = if label_x.abs() < 1.0 { "middle" } else if label_x > 0.0 { "start" } else { "end" };
+            view! {
+                <g class="chart-radar-spoke">
+                    <title>{ format!("{}: {}", value.label, value.text) }</title>
+                    <line x1=center y1=center x2=center + x y2=center + y />
+                    <text x=center + label_x y=center + label_y text-anchor=anchor dominant-baseline="middle">{ value.label.clone() }</text>
+                </g>
+            }
+        })
+        .collect_view();
+    let values = polygon(data.values.iter().map(|value| data.fraction(value.value)).collect());
+    view! {
+        <svg class="comparison-chart" width=RADAR_SIZE height=RADAR_SIZE viewBox=format!("0 0 {} {}", RADAR_SIZE, RADAR_SIZE) role="img">
+            {rings}
+            {spokes}
+            <polygon class="chart-radar-values" points=values />
+        </svg>
+    }
+    .into_view()
+}
+
+// Chart of a numeric property across the items, shown instead of the table. Properties
+// come with their labels; only those with numbers are offered
+#[component]
+pub fn ChartPanel(#[prop(into)] items: Signal<Vec<Item>>, #[prop(into)] properties: Signal<Vec<(String, String)>>) -> impl IntoView {
+    let (kind, set_kind) = create_signal(ChartKind::Bar);
+    let (chosen, set_chosen) = create_signal(None::<String>);
+    let numeric = create_memo(move |_| {
+        let properties = properties.get();
+        let ids: Vec<String> = properties.iter().map(|(property, _)| property.clone()).collect();
+        let numeric = items.with(|items| numeric_properties(items, &ids));
+        properties.into_iter().filter(|(property, _)| numeric.contains(property)).collect::<Vec<_>>()
+    });
+    // The chosen property while it still has numbers, the first one with numbers otherwise
+    let property = create_memo(move |_| {
+        numeric.with(|numeric| {
+            chosen
+                .get()
+                .filter(|chosen| numeric.iter().any(|(property, _)| property == chosen))
+                .or_else(|| numeric.first().map(|(property, _)| property.clone()))
+        })
+    });
+    let data = create_memo(move |_| property.get().map(|property| items.with(|items| chart_data(items, &property))));
+
+    view! {
+        <div class="chart-panel">
+            {move || if numeric.with(Vec::is_empty) {
+                view! { <p class="chart-empty">{ "No property has numbers to chart yet" }</p> }.into_view()
+            } else {
+                view! {
+                    <div class="chart-controls">
+                        <label>
+                            { "Property " }
+                            <select on:change=move |event| set_chosen.set(Some(event_target_value(&event)))>
+                                {numeric.get().into_iter().map(|(id, label)| {
+                                    let selected = property.get().as_ref() == Some(&id);
+                                    view! { <option value=id prop:selected=selected>{label}</option> }
+                                }).collect_view()}
+                            </select>
+                        </label>
+                        <button class="view-toggle" class:active=move || kind.get() == ChartKind::Bar on:click=move |_| set_kind.set(ChartKind::Bar)>
+                            { "Bars" }
+                        </button>
+                        <button class="view-toggle" class:active=move || kind.get() == ChartKind::Radar on:click=move |_| set_kind.set(ChartKind::Radar)>
+                            { "Radar" }
+                        </button>
+                        {move || data.get().and_then(|data| data.unit).map(|unit| view! { <span class="chart-unit">{ format!("in {}", unit) }</span> })}
+                    </div>
+                }.into_view()
+            }}
+            {move || {
+                record_render("ChartPanel");
+                let data = data.get()?;
+                Some(match kind.get() {
+                    ChartKind::Bar => bar_chart(data),
+                    // Fewer than three spokes don't make a shape
+                    ChartKind::Radar if data.values.len() < 3 => {
+                        view! { <p class="chart-empty">{ "A radar chart needs numbers for at least three items" }</p> }.into_view()
+                    }
+                    ChartKind::Radar => radar_chart(data),
+                })
+            }}
+        </div>
+    }
+}
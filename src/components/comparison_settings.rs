@@ -0,0 +1,172 @@
+use crate::api_client::set_metadata;
+use crate::components::edit_access::remember_edit_key;
+use crate::components::image_cell::{image_source, IMAGE_PROPERTIES};
+use crate::models::access::EditAccess;
+use crate::models::item::Item;
+use crate::models::metadata::{ComparisonMetadata, MAX_DESCRIPTION_LENGTH, MAX_TITLE_LENGTH};
+use crate::models::quantity::UnitSystem;
+use crate::url_path::comparison_api_path;
+use leptos::*;
+use leptos_meta::{Link, Meta, Title};
+
+// Title of pages whose comparison has none
+const DEFAULT_PAGE_TITLE: &str = "CompareWare";
+
+// Width in pixels requested for the link preview image, as large cards expect
+const PREVIEW_IMAGE_WIDTH: u32 = 1200;
+
+// Picture of the first item that has one, for large link preview cards
+fn preview_image(items: &[Item]) -> Option<String> {
+    items.iter().find_map(|item| {
+        IMAGE_PROPERTIES
+            .iter()
+            .filter_map(|property| item.custom_properties.get(*property))
+            .find_map(|value| image_source(value, true))
+            .map(|source| source.url(PREVIEW_IMAGE_WIDTH))
+    })
+}
+
+// Page title and meta tags of a comparison, for search engines and link previews
+// in chat apps and social media (Open Graph and Twitter cards)
+#[component]
+pub fn ComparisonMeta(
+    metadata: Signal<ComparisonMetadata>,
+    // Address of the comparison page
+    url: String,
+    // Items once loaded, summed up when the comparison has no description
+    items: Signal<Option<Vec<Item>>>,
+) -> impl IntoView {
+    let title = move || {
+        let title = metadata.with(|metadata| metadata.title.clone());
+        if title.is_empty() {
+            DEFAULT_PAGE_TITLE.to_string()
+        } else {
+            format!("{} · {}", title, DEFAULT_PAGE_TITLE)
+        }
+    };
+
+    // Meta tags are only rendered once the comparison is loaded, so the server
+    // doesn't also send the ones of its loading state
+    let meta_tags = move |items: Vec<Item>| {
+        let metadata = metadata.get();
+        let preview_title = if metadata.title.is_empty() { DEFAULT_PAGE_TITLE.to_string() } else { metadata.title.clone() };
+        let names: Vec<String> = items.iter().map(|item| item.name.clone()).collect();
+        let description = metadata.link_description(&names);
+        let image = preview_image(&items);
+        view! {
+            <Meta property="og:type" content="website"/>
+            <Meta property="og:site_name" content=DEFAULT_PAGE_TITLE/>
+            <Meta property="og:url" content=url.clone()/>
+            <Meta property="og:title" content=preview_title.clone()/>
+            <Meta name="twitter:title" content=preview_title/>
+            <Meta name="twitter:card" content=if image.is_some() { "summary_large_image" } else { "summary" }/>
+            // Feed readers find the reviews of and changes to the comparison from its page
+            <Link rel="alternate" type_="application/atom+xml" title="Reviews" href=comparison_api_path(&url, &["reviews.atom"])/>
+            <Link rel="alternate" type_="application/atom+xml" title="Changes" href=comparison_api_path(&url, &["feed.atom"])/>
+            {(!description.is_empty()).then(|| view! {
+                <Meta name="description" content=description.clone()/>
+                <Meta property="og:description" content=description.clone()/>
+                <Meta name="twitter:description" content=description/>
+            })}
+            {image.map(|image| view! {
+                <Meta property="og:image" content=image.clone()/>
+                <Meta name="twitter:image" content=image/>
+            })}
+            {(!items.is_empty()).then(|| view! {
+                <Meta name="twitter:label1" content="Items"/>
+                <Meta name="twitter:data1" content=items.len().to_string()/>
+            })}
+        }
+    };
+
+    view! {
+        <Title text=title/>
+        {move || items.get().map(meta_tags.clone())}
+    }
+}
+
+// "Settings" button and panel, editing the comparison's title, description and the unit
+// system of its exports
+#[component]
+pub fn ComparisonSettings(
+    current_url: String,
+    metadata: Signal<ComparisonMetadata>,
+    set_saved_metadata: WriteSignal<Option<ComparisonMetadata>>,
+    set_access: WriteSignal<EditAccess>,
+    show_error: Callback<String>,
+) -> impl IntoView {
+    let (show_settings, set_show_settings) = create_signal(false);
+    let (draft, set_draft) = create_signal(ComparisonMetadata::default());
+    let (saving, set_saving) = create_signal(false);
+
+    let open_settings = move |_| {
+        set_draft.set(metadata.get_untracked());
+        set_show_settings.set(true);
+    };
+
+    let save = Callback::new(move |_: ()| {
+        set_saving.set(true);
+        let current_url = current_url.clone();
+        spawn_local(async move {
+            match set_metadata(&current_url, &draft.get_untracked()).await {
+                Ok(stored) => {
+                    remember_edit_key(stored.edit_key, &current_url, set_access);
+                    set_saved_metadata.set(Some(stored.value));
+                    set_show_settings.set(false);
+                }
+                Err(err) => show_error.call(format!("Failed to save the settings: {}", err)),
+            }
+            set_saving.set(false);
+        });
+    });
+
+    view! {
+        <button class="import-button" on:click=open_settings>{ "Settings" }</button>
+        <Show when=move || show_settings.get()>
+            <div class="import-dialog-backdrop">
+                <div class="import-dialog" role="dialog" aria-label="Comparison settings">
+                    <h2>{ "Comparison settings" }</h2>
+                    <p>{ "The title and description are shown above the comparison, in search results and in link previews." }</p>
+                    <label>
+                        { "Title" }
+                        <input
+                            maxlength=MAX_TITLE_LENGTH
+                            prop:value=move || draft.with(|draft| draft.title.clone())
+                            on:input=move |event| set_draft.update(|draft| draft.title = event_target_value(&event))
+                        />
+                    </label>
+                    <label>
+                        { "Description" }
+                        <textarea
+                            rows=4
+                            maxlength=MAX_DESCRIPTION_LENGTH
+                            prop:value=move || draft.with(|draft| draft.description.clone())
+                            on:input=move |event| set_draft.update(|draft| draft.description = event_target_value(&event))
+                        ></textarea>
+                    </label>
+                    <label>
+                        { "Units in CSV, Excel and Markdown exports" }
+                        <select on:change=move |event| set_draft.update(|draft| draft.unit_system = UnitSystem::from_name(&event_target_value(&event)))>
+                            {[(UnitSystem::AsEntered, "As entered"), (UnitSystem::Metric, "Metric"), (UnitSystem::Imperial, "Imperial")]
+                                .into_iter()
+                                .map(|(system, label)| view! {
+                                    <option value=system.as_str() selected=move || draft.with(|draft| draft.unit_system == system)>{label}</option>
+                                })
+                                .collect::<Vec<_>>()}
+                        </select>
+                    </label>
+                    <div class="import-dialog-actions">
+                        <button on:click=move |_| set_show_settings.set(false)>{ "Cancel" }</button>
+                        <button
+                            class="save-button"
+                            disabled=move || saving.get()
+                            on:click=move |_| save.call(())
+                        >
+                            { "Save" }
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
@@ -0,0 +1,194 @@
+use crate::api_client::{clone_comparison, import_comparison, sign_link};
+use crate::components::edit_access::{remember_edit_key, store_edit_token};
+use crate::components::snapshot::format_timestamp;
+use crate::models::access::EditAccess;
+use crate::models::backup::ComparisonExport;
+use crate::models::export::{ExportFormat, SignedLink, SignedLinkRequest, EMBED_FORMAT};
+use crate::url_path::comparison_api_path;
+use leptos::*;
+use wasm_bindgen::JsCast;
+
+// Text of the file picked in a file input
+async fn picked_file_text(event: &ev::Event) -> Option<String> {
+    let input: web_sys::HtmlInputElement = event.target()?.dyn_into().ok()?;
+    let file = input.files()?.get(0)?;
+    wasm_bindgen_futures::JsFuture::from(file.text()).await.ok()?.as_string()
+}
+
+// Server function listing the formats of the exporters registered on the server
+#[server(LoadExportFormats, "/api")]
+pub async fn load_export_formats() -> Result<Vec<ExportFormat>, ServerFnError> {
+    Ok(crate::export::export_formats())
+}
+
+// "Export" downloads of the comparison, as tables or as JSON to move it to another instance,
+// "Import JSON" recreating an exported comparison at this page's URL while nothing is saved
+// here yet, and "Duplicate this comparison" copying it to a new URL to use as a template
+#[component]
+pub fn ComparisonTransfer(
+    current_url: String,
+    // Whether the comparison has no items, the only time an export can be imported into it
+    empty: Signal<bool>,
+    set_access: WriteSignal<EditAccess>,
+    show_error: Callback<String>,
+) -> impl IntoView {
+    let (show_export, set_show_export) = create_signal(false);
+    // Whether the table exports keep the values quantities were entered as
+    let (keep_original, set_keep_original) = create_signal(false);
+    // Whether the JSON export carries the ratings, to move them to another instance
+    let (include_reviews, set_include_reviews) = create_signal(false);
+    let formats = create_resource(|| (), |_| load_export_formats());
+    // Copy, for the closures of the export menu
+    let page_url = store_value(current_url.clone());
+    let export_url = move |format: &ExportFormat| {
+        let export_url = page_url.with_value(|page_url| comparison_api_path(page_url, &[&format!("export.{}", format.name)]));
+        let table = format.table;
+        move || {
+            if table {
+                format!("{}?original={}", export_url, keep_original.get())
+            } else {
+                format!("{}?reviews={}", export_url, include_reviews.get())
+            }
+        }
+    };
+    // Last link signed from the menu, absolute, with whether it's an embed
+    let (signed_link, set_signed_link) = create_signal(None::<(String, SignedLink, bool)>);
+    let create_signed_link = move |format: String, embed: bool| {
+        let page_url = page_url.get_value();
+        spawn_local(async move {
+            let request = SignedLinkRequest { format, embed, expires_in_secs: None };
+            match sign_link(&page_url, &request).await {
+                Ok(link) => {
+                    let origin = web_sys::window().and_then(|window| window.location().origin().ok()).unwrap_or_default();
+                    set_signed_link.set(Some((format!("{}{}", origin, link.path), link, embed)));
+                }
+                Err(err) => show_error.call(format!("Failed to create the link: {}", err)),
+            }
+        });
+    };
+
+    let duplicate = {
+        let current_url = current_url.clone();
+        move |_| {
+            let current_url = current_url.clone();
+            spawn_local(async move {
+                match clone_comparison(&current_url).await {
+                    Ok(copy) => {
+                        // The copy belongs to whoever made it
+                        if let Some(key) = copy.edit_key {
+                            store_edit_token(&copy.value.url, &key);
+                        }
+                        if let Some(window) = web_sys::window() {
+                            let _ = window.location().set_href(&copy.value.url);
+                        }
+                    }
+                    Err(err) => show_error.call(format!("Failed to duplicate the comparison: {}", err)),
+                }
+            });
+        }
+    };
+
+    let import = move |event: ev::Event| {
+        let current_url = current_url.clone();
+        spawn_local(async move {
+            let Some(text) = picked_file_text(&event).await else {
+                return;
+            };
+            let export = match serde_json::from_str::<ComparisonExport>(&text) {
+                Ok(export) => export,
+                Err(err) => {
+                    show_error.call(format!("The file is not a CompareWare export: {}", err));
+                    return;
+                }
+            };
+            match import_comparison(&current_url, &export).await {
+                Ok(imported) => {
+                    remember_edit_key(imported.edit_key, &current_url, set_access);
+                    // Load the imported comparison like any other
+                    if let Some(window) = web_sys::window() {
+                        let _ = window.location().reload();
+                    }
+                }
+                Err(err) => show_error.call(format!("Failed to import the comparison: {}", err)),
+            }
+        });
+    };
+
+    view! {
+        <button class="import-button" on:click=duplicate>{ "Duplicate this comparison" }</button>
+        <button class="import-button" on:click=move |_| set_show_export.set(true)>{ "Export" }</button>
+        <Show when=move || show_export.get()>
+            <div class="import-dialog-backdrop">
+                <div class="import-dialog" role="dialog" aria-label="Export">
+                    <h2>{ "Export" }</h2>
+                    <p>{ "Tables have a row per item, with quantities in the units chosen in the comparison's settings." }</p>
+                    <Suspense fallback=|| view! { <p>{ "Loading the formats..." }</p> }>
+                        <ul class="export-formats">
+                            {move || formats.get().map(|formats| match formats {
+                                Ok(formats) => formats.into_iter().map(|format| {
+                                    let (name, embed_name) = (format.name.clone(), format.name.clone());
+                                    let embeddable = format.name == EMBED_FORMAT;
+                                    view! {
+                                        <li>
+                                            <a href=export_url(&format) download=format.file_name()>{format.label}</a>
+                                            <button class="share-link-button" title="Link opening this export for a week, even without access to the comparison" on:click=move |_| create_signed_link(name.clone(), false)>
+                                                { "Share link" }
+                                            </button>
+                                            <Show when=move || embeddable>
+                                                <button class="share-link-button" title="Link to show this table in a frame on another site for a week" on:click={
+                                                    let embed_name = embed_name.clone();
+                                                    move |_| create_signed_link(embed_name.clone(), true)
+                                                }>
+                                                    { "Embed link" }
+                                                </button>
+                                            </Show>
+                                        </li>
+                                    }
+                                }).collect_view(),
+                                Err(err) => view! { <li>{ format!("Failed to load the formats: {}", err) }</li> }.into_view(),
+                            })}
+                        </ul>
+                    </Suspense>
+                    {move || signed_link.get().map(|(link, signed, embed)| {
+                        let shown = if embed { format!("<iframe src=\"{}\" width=\"100%\" height=\"400\"></iframe>", link) } else { link };
+                        view! {
+                            <p class="signed-link">
+                                <input type="text" readonly prop:value=shown on:focus=|event| {
+                                    if let Some(input) = event.target().and_then(|target| target.dyn_into::<web_sys::HtmlInputElement>().ok()) {
+                                        input.select();
+                                    }
+                                } />
+                                <small>{ format!("Works until {}", format_timestamp(signed.expires_at)) }</small>
+                            </p>
+                        }
+                    })}
+                    <label>
+                        <input
+                            type="checkbox"
+                            prop:checked=move || keep_original.get()
+                            on:change=move |event| set_keep_original.set(event_target_checked(&event))
+                        />
+                        { " Keep the values as entered next to converted ones" }
+                    </label>
+                    <label>
+                        <input
+                            type="checkbox"
+                            prop:checked=move || include_reviews.get()
+                            on:change=move |event| set_include_reviews.set(event_target_checked(&event))
+                        />
+                        { " Include the ratings in the JSON export" }
+                    </label>
+                    <div class="import-dialog-actions">
+                        <button on:click=move |_| set_show_export.set(false)>{ "Close" }</button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+        <Show when=move || empty.get()>
+            <label class="import-button">
+                { "Import JSON" }
+                <input type="file" accept="application/json,.json" hidden on:change=import.clone() />
+            </label>
+        </Show>
+    }
+}
@@ -0,0 +1,191 @@
+use crate::components::snapshot::format_timestamp;
+use crate::models::directory::ComparisonIndex;
+use crate::models::search::SearchHit;
+use leptos::logging::log;
+use leptos::*;
+
+// Server function to load a page of the index, so the landing page is complete when rendered on the server.
+// None on private instances, which don't list their comparisons
+#[server(LoadComparisonIndex, "/api")]
+pub async fn load_comparison_index(search: String, decided_only: bool, page: usize) -> Result<Option<ComparisonIndex>, ServerFnError> {
+    use crate::db::Database;
+    use crate::instance::InstanceConfig;
+    use crate::models::directory::COMPARISONS_PER_PAGE;
+    use actix_web::web;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    let instance: web::Data<InstanceConfig> = leptos_actix::extract().await?;
+    if instance.settings().private {
+        return Ok(None);
+    }
+    let db: web::Data<Arc<RwLock<Database>>> = leptos_actix::extract().await?;
+    let index = db
+        .read()
+        .await
+        .get_comparison_index(&search, decided_only, page.max(1), COMPARISONS_PER_PAGE)
+        .await
+        .map_err(|e| ServerFnError::new(format!("Failed to fetch comparisons: {}", e)))?;
+    Ok(Some(index))
+}
+
+// Landing page listing the comparisons stored on this instance, with search and pagination
+#[component]
+pub fn ComparisonsIndex() -> impl IntoView {
+    let (search, set_search) = create_signal(String::new());
+    let (decided_only, set_decided_only) = create_signal(false);
+    let (page, set_page) = create_signal(1usize);
+    let index = create_resource(
+        move || (search.get(), decided_only.get(), page.get()),
+        |(search, decided_only, page)| load_comparison_index(search, decided_only, page),
+    );
+
+    let pagination = move |index: &ComparisonIndex| {
+        let (current, page_count) = (index.page, index.page_count());
+        view! {
+            <div class="index-pagination">
+                <button disabled=current <= 1 on:click=move |_| set_page.set(current - 1)>{ "Previous" }</button>
+                <span>{ format!("Page {} of {}", current, page_count) }</span>
+                <button disabled=current >= page_count on:click=move |_| set_page.set(current + 1)>{ "Next" }</button>
+            </div>
+        }
+    };
+
+    view! {
+        <div class="comparisons-index">
+            <h2>{ "Comparisons" }</h2>
+            <div class="discover-filters">
+                <input
+                    placeholder="Search by title or URL"
+                    prop:value=move || search.get()
+                    on:input=move |event| {
+                        set_search.set(event_target_value(&event));
+                        set_page.set(1);
+                    }
+                />
+                <label>
+                    <input
+                        type="checkbox"
+                        prop:checked=move || decided_only.get()
+                        on:change=move |event| {
+                            set_decided_only.set(event_target_checked(&event));
+                            set_page.set(1);
+                        }
+                    />
+                    { "Past decisions only" }
+                </label>
+            </div>
+            <Transition fallback=move || view! { <p>{ "Loading comparisons..." }</p> }>
+                {move || index.get().map(|result| match result {
+                    Ok(None) => {
+                        view! { <p>{ "This instance is private, its comparisons are not listed." }</p> }.into_view()
+                    }
+                    Ok(Some(index)) if index.comparisons.is_empty() => {
+                        view! { <p>{ "No comparisons found." }</p> }.into_view()
+                    }
+                    Ok(Some(index)) => view! {
+                        <ul class="directory">
+                            {index.comparisons.iter().cloned().map(|comparison| view! {
+                                <li class="directory-entry">
+                                    <a href=comparison.url.clone()>{comparison.title}</a>
+                                    <span class="directory-meta">
+                                        { format!("{} items · updated {}", comparison.item_count, format_timestamp(comparison.updated_at)) }
+                                        {comparison.decided_at.map(|decided_at| format!(" · decided {}", format_timestamp(decided_at)))}
+                                    </span>
+                                </li>
+                            }).collect::<Vec<_>>()}
+                        </ul>
+                        {pagination(&index)}
+                    }.into_view(),
+                    Err(err) => {
+                        log!("Error loading comparisons: {}", err);
+                        view! { <p class="discover-error">{ "Failed to load the comparisons." }</p> }.into_view()
+                    }
+                })}
+            </Transition>
+        </div>
+    }
+}
+
+// Server function searching the items of all comparisons. None on private instances
+#[server(SearchItems, "/api")]
+pub async fn search_items(search: String) -> Result<Option<Vec<SearchHit>>, ServerFnError> {
+    use crate::db::Database;
+    use crate::instance::InstanceConfig;
+    use crate::models::search::{MAX_SEARCH_LENGTH, SEARCH_RESULTS_LIMIT};
+    use actix_web::web;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    let instance: web::Data<InstanceConfig> = leptos_actix::extract().await?;
+    if instance.settings().private {
+        return Ok(None);
+    }
+    let search: String = search.chars().take(MAX_SEARCH_LENGTH).collect();
+    let db: web::Data<Arc<RwLock<Database>>> = leptos_actix::extract().await?;
+    let hits = db
+        .read()
+        .await
+        .search_items(&search, SEARCH_RESULTS_LIMIT)
+        .await
+        .map_err(|e| ServerFnError::new(format!("Failed to search items: {}", e)))?;
+    Ok(Some(hits))
+}
+
+// Search box finding items in all comparisons by their names, descriptions and values,
+// linking to the comparison with the matching item highlighted
+#[component]
+pub fn ItemSearch() -> impl IntoView {
+    let (search, set_search) = create_signal(String::new());
+    let hits = create_resource(move || search.get(), |search| async move {
+        if search.trim().is_empty() {
+            Ok(Some(Vec::new()))
+        } else {
+            search_items(search).await
+        }
+    });
+
+    view! {
+        <div class="item-search">
+            <input
+                type="search"
+                placeholder="Search items in all comparisons"
+                prop:value=move || search.get()
+                on:input=move |event| set_search.set(event_target_value(&event))
+            />
+            <Transition fallback=move || ()>
+                {move || hits.get().map(|result| match result {
+                    Ok(Some(hits)) if hits.is_empty() => {
+                        (!search.get_untracked().trim().is_empty()).then(|| view! { <p>{ "No items found." }</p> }).into_view()
+                    }
+                    Ok(Some(hits)) => view! {
+                        <ul class="directory">
+                            {hits.into_iter().map(|hit| view! {
+                                <li class="directory-entry">
+                                    // The fragment scrolls to the item's column, which is highlighted
+                                    <a href=format!("{}#item-{}", hit.url, hit.item_id)>
+                                        {if hit.item_name.is_empty() { "Unnamed item".to_string() } else { hit.item_name.clone() }}
+                                    </a>
+                                    <span class="directory-meta">{ format!("in {}", hit.title) }</span>
+                                    <span class="search-snippet">
+                                        {hit.snippet.into_iter().map(|part| if part.matched {
+                                            view! { <mark>{part.text}</mark> }.into_view()
+                                        } else {
+                                            part.text.into_view()
+                                        }).collect::<Vec<_>>()}
+                                    </span>
+                                </li>
+                            }).collect::<Vec<_>>()}
+                        </ul>
+                    }.into_view(),
+                    // Private instances don't search their comparisons
+                    Ok(None) => ().into_view(),
+                    Err(err) => {
+                        log!("Error searching items: {}", err);
+                        view! { <p class="discover-error">{ "Failed to search the items." }</p> }.into_view()
+                    }
+                })}
+            </Transition>
+        </div>
+    }
+}
@@ -0,0 +1,67 @@
+use crate::models::item::Item;
+use leptos::*;
+use std::collections::{BTreeSet, HashMap};
+
+// Fields the two copies of an item differ in, with the value of each copy
+fn differences(mine: &Item, theirs: &Item, property_labels: &HashMap<String, String>) -> Vec<(String, String, String)> {
+    let mut fields = Vec::new();
+    if mine.name != theirs.name {
+        fields.push(("Name".to_string(), mine.name.clone(), theirs.name.clone()));
+    }
+    if mine.description != theirs.description {
+        fields.push(("Description".to_string(), mine.description.clone(), theirs.description.clone()));
+    }
+    let properties: BTreeSet<&String> = mine.custom_properties.keys().chain(theirs.custom_properties.keys()).collect();
+    for property in properties {
+        let value = |item: &Item| item.custom_properties.get(property).cloned().unwrap_or_default();
+        if value(mine) != value(theirs) {
+            let label = property_labels.get(property).unwrap_or(property).clone();
+            fields.push((label, value(mine), value(theirs)));
+        }
+    }
+    fields
+}
+
+// Dialog shown when the save of an item was refused because someone saved it after this
+// page loaded it. `mine` is the copy edited here, `theirs` the one stored on the server
+#[component]
+pub fn ConflictDialog(
+    mine: Item,
+    theirs: Item,
+    property_labels: ReadSignal<HashMap<String, String>>,
+    on_keep_mine: Callback<()>,
+    on_use_theirs: Callback<()>,
+) -> impl IntoView {
+    let fields = differences(&mine, &theirs, &property_labels.get_untracked());
+    let name = if theirs.name.is_empty() { mine.name.clone() } else { theirs.name.clone() };
+    view! {
+        <div class="import-dialog-backdrop">
+            <div class="import-dialog conflict-dialog" role="alertdialog" aria-label="Edit conflict">
+                <h2>{ format!("{} was changed elsewhere", name) }</h2>
+                <p>{ "Someone saved this item after it was loaded here. Keep your version, or use theirs and drop your changes." }</p>
+                <table>
+                    <thead>
+                        <tr>
+                            <th></th>
+                            <th>{ "Yours" }</th>
+                            <th>{ "Theirs" }</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {fields.into_iter().map(|(field, mine, theirs)| view! {
+                            <tr>
+                                <th>{field}</th>
+                                <td>{mine}</td>
+                                <td>{theirs}</td>
+                            </tr>
+                        }).collect_view()}
+                    </tbody>
+                </table>
+                <div class="import-dialog-actions">
+                    <button on:click=move |_| on_use_theirs.call(())>{ "Use theirs" }</button>
+                    <button class="save-button" on:click=move |_| on_keep_mine.call(())>{ "Keep mine" }</button>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,122 @@
+use crate::api_client::{clear_decision, set_decision};
+use crate::components::snapshot::format_timestamp;
+use crate::models::item::Item;
+use crate::models::metadata::{ComparisonMetadata, DecisionRequest, MAX_RATIONALE_LENGTH};
+use leptos::*;
+
+// Banner above the table of a decided comparison, with the chosen item and the rationale
+#[component]
+pub fn DecisionBanner(
+    metadata: Signal<ComparisonMetadata>,
+    // Name of the chosen item, `None` once it's no longer part of the comparison
+    item_name: Signal<Option<String>>,
+) -> impl IntoView {
+    move || {
+        metadata.with(|metadata| metadata.decision.clone()).map(|decision| {
+            let chosen = item_name.get().unwrap_or_else(|| "an item no longer in the comparison".to_string());
+            view! {
+                <div class="decision-banner" role="status">
+                    <strong>{ format!("Decided: {}", chosen) }</strong>
+                    <span class="decision-date">{ format!(" on {}", format_timestamp(decision.decided_at)) }</span>
+                    {(!decision.rationale.is_empty()).then(|| view! {
+                        <p class="decision-rationale">{decision.rationale.clone()}</p>
+                    })}
+                </div>
+            }
+        })
+    }
+}
+
+// "Mark as decided" button and panel, recording which item was chosen and why
+#[component]
+pub fn DecisionDialog(
+    current_url: String,
+    metadata: Signal<ComparisonMetadata>,
+    items: ReadSignal<Vec<Item>>,
+    set_saved_metadata: WriteSignal<Option<ComparisonMetadata>>,
+    show_error: Callback<String>,
+) -> impl IntoView {
+    let (show_dialog, set_show_dialog) = create_signal(false);
+    let (draft, set_draft) = create_signal(DecisionRequest { item_id: String::new(), rationale: String::new() });
+    let (saving, set_saving) = create_signal(false);
+    let decided = move || metadata.with(|metadata| metadata.decision.is_some());
+
+    let open_dialog = move |_| {
+        let draft = match metadata.get_untracked().decision {
+            Some(decision) => DecisionRequest { item_id: decision.item_id, rationale: decision.rationale },
+            None => DecisionRequest {
+                item_id: items.with_untracked(|items| items.first().map(|item| item.id.clone()).unwrap_or_default()),
+                rationale: String::new(),
+            },
+        };
+        set_draft.set(draft);
+        set_show_dialog.set(true);
+    };
+
+    // Store the decision, or reopen the comparison when `reopen` is set
+    let send = Callback::new(move |reopen: bool| {
+        set_saving.set(true);
+        let current_url = current_url.clone();
+        spawn_local(async move {
+            let stored = if reopen {
+                clear_decision(&current_url).await
+            } else {
+                set_decision(&current_url, &draft.get_untracked()).await
+            };
+            match stored {
+                Ok(stored) => {
+                    set_saved_metadata.set(Some(stored));
+                    set_show_dialog.set(false);
+                }
+                Err(err) => show_error.call(format!("Failed to save the decision: {}", err)),
+            }
+            set_saving.set(false);
+        });
+    });
+
+    view! {
+        <button class="import-button" on:click=open_dialog>
+            {move || if decided() { "Change decision" } else { "Mark as decided" }}
+        </button>
+        <Show when=move || show_dialog.get()>
+            <div class="import-dialog-backdrop">
+                <div class="import-dialog" role="dialog" aria-label="Decision">
+                    <h2>{ "Decision" }</h2>
+                    <p>{ "Record which item was chosen and why. It's shown above the comparison and listed with past decisions." }</p>
+                    <label>
+                        { "Chosen item" }
+                        <select on:change=move |event| set_draft.update(|draft| draft.item_id = event_target_value(&event))>
+                            {move || items.get().into_iter().map(|item| {
+                                let selected = draft.with(|draft| draft.item_id == item.id);
+                                let name = if item.name.is_empty() { "(unnamed item)".to_string() } else { item.name };
+                                view! { <option value=item.id selected=selected>{name}</option> }
+                            }).collect::<Vec<_>>()}
+                        </select>
+                    </label>
+                    <label>
+                        { "Rationale" }
+                        <textarea
+                            rows=4
+                            maxlength=MAX_RATIONALE_LENGTH
+                            prop:value=move || draft.with(|draft| draft.rationale.clone())
+                            on:input=move |event| set_draft.update(|draft| draft.rationale = event_target_value(&event))
+                        ></textarea>
+                    </label>
+                    <div class="import-dialog-actions">
+                        <Show when=decided>
+                            <button disabled=move || saving.get() on:click=move |_| send.call(true)>{ "Reopen" }</button>
+                        </Show>
+                        <button on:click=move |_| set_show_dialog.set(false)>{ "Cancel" }</button>
+                        <button
+                            class="save-button"
+                            disabled=move || saving.get() || draft.with(|draft| draft.item_id.is_empty())
+                            on:click=move |_| send.call(false)
+                        >
+                            { "Save" }
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
@@ -0,0 +1,86 @@
+use crate::api_client::directory;
+use leptos::*;
+use leptos::logging::log;
+
+// Host of an instance, shown next to the comparisons it announced
+fn instance_host(url: &str) -> String {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    without_scheme.split('/').next().unwrap_or_default().to_string()
+}
+
+#[component]
+pub fn Discover() -> impl IntoView {
+    // Comparisons announced by CompareWare instances on the configured relays. Relays are
+    // queried by the server, only once the page runs in the browser
+    let directory = create_local_resource(|| (), |_| directory());
+    let (search, set_search) = create_signal(String::new());
+    let (category, set_category) = create_signal(String::new());
+
+    let entries = move || directory.get().and_then(|result| result.ok()).unwrap_or_default();
+    let categories = move || {
+        let mut categories: Vec<String> = entries()
+            .into_iter()
+            .map(|entry| entry.announcement.category)
+            .filter(|category| !category.is_empty())
+            .collect();
+        categories.sort();
+        categories.dedup();
+        categories
+    };
+    let visible_entries = move || {
+        let search = search.get().to_lowercase();
+        let category = category.get();
+        entries()
+            .into_iter()
+            .filter(|entry| category.is_empty() || entry.announcement.category == category)
+            .filter(|entry| search.is_empty() || entry.announcement.title.to_lowercase().contains(&search))
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <div class="discover">
+            <h2>{ "Discover comparisons" }</h2>
+            <div class="discover-filters">
+                <input
+                    placeholder="Search by title"
+                    prop:value=move || search.get()
+                    on:input=move |event| set_search.set(event_target_value(&event))
+                />
+                <select on:change=move |event| set_category.set(event_target_value(&event))>
+                    <option value="">{ "All categories" }</option>
+                    {move || categories().into_iter().map(|category| view! {
+                        <option value=category.clone()>{category.clone()}</option>
+                    }).collect::<Vec<_>>()}
+                </select>
+            </div>
+            {move || match directory.get() {
+                None => view! { <p>{ "Loading the directory..." }</p> }.into_view(),
+                Some(Err(err)) => {
+                    log!("Error loading the directory: {}", err);
+                    view! { <p class="discover-error">{err.to_string()}</p> }.into_view()
+                }
+                Some(Ok(_)) if visible_entries().is_empty() => {
+                    view! { <p>{ "No comparisons found." }</p> }.into_view()
+                }
+                Some(Ok(_)) => view! {
+                    <ul class="directory">
+                        {visible_entries().into_iter().map(|entry| {
+                            let announcement = entry.announcement;
+                            view! {
+                                <li class="directory-entry">
+                                    <a href=announcement.url.clone()>{announcement.title}</a>
+                                    {(!announcement.category.is_empty()).then(|| view! {
+                                        <span class="directory-category">{announcement.category.clone()}</span>
+                                    })}
+                                    <span class="directory-meta">
+                                        { format!("{} items · {}{}", announcement.item_count, instance_host(&announcement.url), announcement.slug) }
+                                    </span>
+                                </li>
+                            }
+                        }).collect::<Vec<_>>()}
+                    </ul>
+                }.into_view(),
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,191 @@
+use crate::api_client::{edit_access, invite_collaborator, protect_comparison, set_visibility};
+use crate::components::live_updates::with_live_session;
+use crate::models::access::{EditAccess, EditorRole, Visibility, VisibilitySetting, EDIT_TOKEN_HEADER};
+use gloo_net::http::RequestBuilder;
+use leptos::*;
+
+// Fragment of invitation links carrying a collaborator's edit token,
+// e.g. https://compareware.org/laptops#edit-token=...
+const TOKEN_FRAGMENT_PREFIX: &str = "#edit-token=";
+
+fn edit_token_key(url: &str) -> String {
+    format!("compareware-edit-token:{}", url)
+}
+
+// Pages rendered on the server have no browser storage, so no edit tokens either
+#[cfg(feature = "ssr")]
+fn storage() -> Option<web_sys::Storage> {
+    None
+}
+
+#[cfg(not(feature = "ssr"))]
+fn storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+// Edit token of a comparison remembered by this browser
+pub fn edit_token(url: &str) -> Option<String> {
+    storage()?.get_item(&edit_token_key(url)).ok()?
+}
+
+pub fn store_edit_token(url: &str, token: &str) {
+    if let Some(storage) = storage() {
+        let _ = storage.set_item(&edit_token_key(url), token);
+    }
+}
+
+// Send the comparison's edit token along with a request changing it, and the tab's
+// live session so the change isn't pushed back to this tab
+pub fn with_edit_token(request: RequestBuilder, url: &str) -> RequestBuilder {
+    let request = with_live_session(request);
+    match edit_token(url) {
+        Some(token) => request.header(EDIT_TOKEN_HEADER, &token),
+        None => request,
+    }
+}
+
+// Keep the owner edit key the server hands out with the first write to a comparison
+pub fn remember_edit_key(edit_key: Option<String>, url: &str, set_access: WriteSignal<EditAccess>) {
+    if let Some(key) = edit_key {
+        store_edit_token(url, &key);
+        set_access.update(|access| {
+            access.protected = true;
+            access.role = Some(EditorRole::Owner);
+        });
+    }
+}
+
+// Remember the token of an invitation link and remove it from the address bar
+fn accept_invitation(url: &str) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let location = window.location();
+    let Some(token) = location
+        .hash()
+        .ok()
+        .and_then(|hash| hash.strip_prefix(TOKEN_FRAGMENT_PREFIX).map(str::to_string))
+    else {
+        return;
+    };
+    store_edit_token(url, &token);
+    if let (Ok(history), Ok(path), Ok(query)) = (window.history(), location.pathname(), location.search()) {
+        let _ = history.replace_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(&format!("{}{}", path, query)));
+    }
+}
+
+// Load whether the comparison is protected and what this browser may do with it
+pub fn load_edit_access(url: String, set_access: WriteSignal<EditAccess>, show_error: Callback<String>) {
+    accept_invitation(&url);
+    spawn_local(async move {
+        match edit_access(&url).await {
+            Ok(access) => set_access.set(access),
+            Err(err) => show_error.call(format!("Failed to load edit access: {}", err)),
+        }
+    });
+}
+
+// Protect the comparison, or choose its visibility and invite collaborators, depending on
+// the visitor's access
+#[component]
+pub fn EditAccessControls(
+    current_url: String,
+    access: ReadSignal<EditAccess>,
+    set_access: WriteSignal<EditAccess>,
+    show_error: Callback<String>,
+) -> impl IntoView {
+    let (invitation, set_invitation) = create_signal(None::<String>);
+
+    let protect = {
+        let current_url = current_url.clone();
+        Callback::new(move |_: ()| {
+            let current_url = current_url.clone();
+            spawn_local(async move {
+                match protect_comparison(&current_url).await {
+                    Ok(owner) => {
+                        store_edit_token(&current_url, &owner.token);
+                        set_access.update(|access| {
+                            access.protected = true;
+                            access.role = Some(owner.role);
+                        });
+                    }
+                    Err(err) => show_error.call(format!("Failed to protect comparison: {}", err)),
+                }
+            });
+        })
+    };
+
+    let change_visibility = {
+        let current_url = current_url.clone();
+        Callback::new(move |visibility: Visibility| {
+            let current_url = current_url.clone();
+            spawn_local(async move {
+                match set_visibility(&current_url, &VisibilitySetting { visibility }).await {
+                    Ok(setting) => set_access.update(|access| access.visibility = setting.visibility),
+                    Err(err) => show_error.call(format!("Failed to change visibility: {}", err)),
+                }
+            });
+        })
+    };
+
+    let invite = Callback::new(move |_: ()| {
+        let current_url = current_url.clone();
+        spawn_local(async move {
+            match invite_collaborator(&current_url).await {
+                Ok(collaborator) => {
+                    let page = web_sys::window().and_then(|window| window.location().href().ok()).unwrap_or_default();
+                    let page = page.split('#').next().unwrap_or_default();
+                    set_invitation.set(Some(format!("{}{}{}", page, TOKEN_FRAGMENT_PREFIX, collaborator.token)));
+                }
+                Err(err) => show_error.call(format!("Failed to invite collaborator: {}", err)),
+            }
+        });
+    });
+
+    view! {
+        <span class="edit-access">
+            {move || match access.get() {
+                EditAccess { protected: false, .. } => view! {
+                    <button
+                        class="import-button"
+                        title="Only you and the collaborators you invite will be able to edit"
+                        on:click=move |_| protect.call(())
+                    >
+                        { "Protect editing" }
+                    </button>
+                }.into_view(),
+                EditAccess { role: Some(EditorRole::Owner), visibility, .. } => view! {
+                    <select
+                        class="visibility-select"
+                        title="Public comparisons are listed and searched, unlisted ones only read through their link, private ones only by you and your collaborators"
+                        prop:value=visibility.as_str()
+                        on:change=move |event| {
+                            if let Some(visibility) = Visibility::parse(&event_target_value(&event)) {
+                                change_visibility.call(visibility);
+                            }
+                        }
+                    >
+                        {Visibility::ALL.into_iter().map(|choice| view! {
+                            <option value=choice.as_str() selected=choice == visibility>{ choice.label() }</option>
+                        }).collect_view()}
+                    </select>
+                    <button class="import-button" on:click=move |_| invite.call(())>{ "Invite collaborator" }</button>
+                    {move || invitation.get().map(|link| view! {
+                        <input
+                            class="invitation-link"
+                            readonly=true
+                            title="Anyone opening this link can edit the comparison"
+                            prop:value=link
+                        />
+                    })}
+                }.into_view(),
+                EditAccess { role: Some(EditorRole::Collaborator), .. } => view! {
+                    <span class="access-indicator">{ "Editing as collaborator" }</span>
+                }.into_view(),
+                EditAccess { role: None, .. } => view! {
+                    <span class="access-indicator">{ "Read-only: ask the owner for an edit link" }</span>
+                }.into_view(),
+            }}
+        </span>
+    }
+}
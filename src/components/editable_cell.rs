@@ -1,6 +1,60 @@
 use leptos::*;
+use std::rc::Rc;
 use std::sync::Arc;
 use leptos::logging::log;
+use crate::components::perf_overlay::record_render;
+use crate::models::markdown::{parse_markdown, MarkdownBlock, MarkdownInline};
+
+fn inline_views(inlines: Vec<MarkdownInline>) -> View {
+    inlines
+        .into_iter()
+        .map(|inline| match inline {
+            MarkdownInline::Text(text) => text.into_view(),
+            MarkdownInline::Strong(children) => view! { <strong>{inline_views(children)}</strong> }.into_view(),
+            MarkdownInline::Emphasis(children) => view! { <em>{inline_views(children)}</em> }.into_view(),
+            MarkdownInline::Code(code) => view! { <code>{code}</code> }.into_view(),
+            // Clicking a link follows it instead of starting to edit the cell
+            MarkdownInline::Link { text, url } => view! {
+                <a href=url target="_blank" rel="noopener noreferrer nofollow" on:mousedown=|event| event.stop_propagation()>
+                    {inline_views(text)}
+                </a>
+            }
+            .into_view(),
+            MarkdownInline::LineBreak => view! { <br/> }.into_view(),
+        })
+        .collect_view()
+}
+
+// Elements of a Markdown text. Its markup only ever becomes these elements, and text
+// is never parsed as HTML
+fn markdown_view(text: &str) -> View {
+    parse_markdown(text)
+        .into_iter()
+        .map(|block| match block {
+            MarkdownBlock::Heading(level, inlines) => {
+                let inlines = inline_views(inlines);
+                match level {
+                    1 => view! { <h1>{inlines}</h1> }.into_view(),
+                    2 => view! { <h2>{inlines}</h2> }.into_view(),
+                    3 => view! { <h3>{inlines}</h3> }.into_view(),
+                    4 => view! { <h4>{inlines}</h4> }.into_view(),
+                    5 => view! { <h5>{inlines}</h5> }.into_view(),
+                    _ => view! { <h6>{inlines}</h6> }.into_view(),
+                }
+            }
+            MarkdownBlock::Paragraph(inlines) => view! { <p>{inline_views(inlines)}</p> }.into_view(),
+            MarkdownBlock::List { ordered, items } => {
+                let items = items.into_iter().map(|item| view! { <li>{inline_views(item)}</li> }).collect_view();
+                if ordered {
+                    view! { <ol>{items}</ol> }.into_view()
+                } else {
+                    view! { <ul>{items}</ul> }.into_view()
+                }
+            }
+            MarkdownBlock::Code(code) => view! { <pre><code>{code}</code></pre> }.into_view(),
+        })
+        .collect_view()
+}
 
 #[component]
 pub fn EditableCell(
@@ -12,22 +66,39 @@ pub fn EditableCell(
     on_focus: Option<Callback<()>>,
     on_blur: Option<Callback<()>>,
     input_type: InputType,
+    // Read-only while another editor holds the lock on this cell
+    #[prop(optional, into)]
+    locked: MaybeSignal<bool>,
+    // Longest value accepted, in characters. Longer pastes are cut
+    #[prop(optional)]
+    max_length: Option<usize>,
+    // Language of the value, e.g. "de", and whether the browser spell-checks it.
+    // Unset, the page's language and the browser's default apply
+    #[prop(optional, into)]
+    lang: MaybeSignal<Option<String>>,
+    #[prop(optional, into)]
+    spellcheck: MaybeSignal<Option<bool>>,
 ) -> impl IntoView {
+    record_render("EditableCell");
     let input_ref = create_node_ref::<html::Input>();
     let textarea_ref = create_node_ref::<html::Textarea>();
     let (local_value, set_local_value) = create_signal(value.clone());
     let input_type_clone = input_type.clone();
     // Handle input event
     let handle_input = move |e: web_sys::Event| {
-        let new_value = match input_type_clone {
+        let mut new_value = match input_type_clone {
             InputType::Text => event_target_value(&e),
-            InputType::TextArea => event_target_value(&e),
+            InputType::TextArea | InputType::Markdown => event_target_value(&e),
         };
+        if let Some((end, _)) = max_length.and_then(|max_length| new_value.char_indices().nth(max_length)) {
+            new_value.truncate(end);
+        }
         log!("Input event: {}", new_value);
         set_local_value.set(new_value);
     };
 
     // Commit the input value on blur or enter
+    let on_input = Rc::new(on_input);
     let commit_input = move || {
         let value = local_value.get();
         log!("Committing input: {}", value);
@@ -55,6 +126,17 @@ pub fn EditableCell(
         }
     };
 
+    let spellcheck = move || spellcheck.get().map(|enabled| enabled.to_string());
+    // Markdown cells show the raw text only while focused
+    let editing = {
+        let key = Arc::clone(&key);
+        move || focused_cell.get().as_deref() == Some(key.as_str())
+    };
+    let start_editing = {
+        let key = Arc::clone(&key);
+        move || set_focused_cell.set(Some(key.to_string()))
+    };
+
     // Update input field value when focused cell changes
     create_effect(move |_| {
         if focused_cell.get().as_deref() == Some(key.as_str()) {
@@ -62,6 +144,9 @@ pub fn EditableCell(
             if let Some(input) = input_ref.get() {
                 let _ = input.focus();
             }
+            if let Some(textarea) = textarea_ref.get() {
+                let _ = textarea.focus();
+            }
         }
     });
 
@@ -75,6 +160,10 @@ pub fn EditableCell(
                         on:input=handle_input
                         on:focus=handle_focus
                         on:blur=handle_blur
+                        readonly=move || locked.get()
+                        maxlength=max_length
+                        lang=lang
+                        spellcheck=spellcheck
                         node_ref=input_ref
                         class="editable-cell-input"
                     />
@@ -85,10 +174,54 @@ pub fn EditableCell(
                         on:input=handle_input
                         on:focus=handle_focus
                         on:blur=handle_blur
+                        readonly=move || locked.get()
+                        maxlength=max_length
+                        lang=lang
+                        spellcheck=spellcheck
                         node_ref=textarea_ref
                         class="editable-cell-input"
                     />
-                }.into_view()
+                }.into_view(),
+                InputType::Markdown => {
+                    let start_editing_on_key = start_editing.clone();
+                    (move || if editing() {
+                        view! {
+                            <textarea
+                                prop:value=move || local_value.get()
+                                on:input=handle_input.clone()
+                                on:focus=handle_focus.clone()
+                                on:blur=handle_blur.clone()
+                                readonly=move || locked.get()
+                                maxlength=max_length
+                                lang=lang.clone()
+                                spellcheck=spellcheck
+                                node_ref=textarea_ref
+                                class="editable-cell-input"
+                            />
+                        }.into_view()
+                    } else {
+                        let start_editing = start_editing.clone();
+                        let start_editing_on_key = start_editing_on_key.clone();
+                        view! {
+                            <div
+                                class="markdown-cell"
+                                tabindex="0"
+                                lang=lang.clone()
+                                on:mousedown=move |event| {
+                                    // Keeps the focus on the text area replacing the rendering
+                                    event.prevent_default();
+                                    start_editing();
+                                }
+                                on:keydown=move |event: web_sys::KeyboardEvent| if event.key() == "Enter" {
+                                    event.prevent_default();
+                                    start_editing_on_key();
+                                }
+                            >
+                                {markdown_view(&local_value.get())}
+                            </div>
+                        }.into_view()
+                    }).into_view()
+                }
             }}
         </div>
     }
@@ -98,4 +231,6 @@ pub fn EditableCell(
 pub enum InputType {
     Text,
     TextArea,
+    // Text area shown as rendered Markdown while not focused
+    Markdown,
 }
\ No newline at end of file
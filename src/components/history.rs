@@ -0,0 +1,114 @@
+use crate::api_client::history;
+use crate::components::snapshot::format_timestamp;
+use crate::models::history::{HistoryChange, Revision, HISTORY_PAGE_SIZE};
+use crate::models::item::Item;
+use leptos::*;
+use std::collections::HashMap;
+
+// What a revision changed, naming items after the latest version listed
+fn describe_change(change: &HistoryChange, names: &HashMap<String, String>) -> String {
+    let item_name = |item_id: &str| match names.get(item_id) {
+        Some(name) if !name.is_empty() => name.clone(),
+        _ => "an unnamed item".to_string(),
+    };
+    match change {
+        HistoryChange::ItemSaved { item } => format!("Saved {}", item_name(&item.id)),
+        HistoryChange::ItemDeleted { item_id } => format!("Deleted {}", item_name(item_id)),
+        HistoryChange::ItemRestored { item } => format!("Restored {}", item_name(&item.id)),
+        HistoryChange::PropertyAdded { property } => format!("Added property {}", property),
+        HistoryChange::PropertyDeleted { property } => format!("Deleted property {}", property),
+        HistoryChange::PropertyRenamed { property, rename_to } => format!("Renamed property {} to {}", property, rename_to),
+        HistoryChange::PropertyRestored { property } => format!("Restored property {}", property),
+    }
+}
+
+// "History" button and panel listing the changes to the comparison's items and properties,
+// newest first, with earlier versions of items that can be saved again
+#[component]
+pub fn ItemHistory(
+    current_url: String,
+    // Called with an earlier version of an item, to save it as the current one
+    on_restore: Callback<Item>,
+    show_error: Callback<String>,
+) -> impl IntoView {
+    let (show_panel, set_show_panel) = create_signal(false);
+    let (revisions, set_revisions) = create_signal(Vec::<Revision>::new());
+    // Whether a full page came back last, so older revisions may follow
+    let (has_more, set_has_more) = create_signal(false);
+
+    // Fetch a page of the history, the newest or the one before a revision
+    let load_page = Callback::new(move |before: Option<i64>| {
+        let current_url = current_url.clone();
+        spawn_local(async move {
+            let page = match history(&current_url, before).await {
+                Ok(page) => page,
+                // Comparisons nobody saved anything to yet have no history
+                Err(err) if err.status() == Some(404) => Vec::new(),
+                Err(err) => {
+                    show_error.call(format!("Failed to load the history: {}", err));
+                    return;
+                }
+            };
+            set_has_more.set(page.len() == HISTORY_PAGE_SIZE);
+            if before.is_some() {
+                set_revisions.update(|revisions| revisions.extend(page));
+            } else {
+                set_revisions.set(page);
+            }
+        });
+    });
+
+    let names = move || {
+        let mut names = HashMap::new();
+        revisions.with(|revisions| {
+            for item in revisions.iter().filter_map(|revision| revision.change.item()) {
+                names.entry(item.id.clone()).or_insert_with(|| item.name.clone());
+            }
+        });
+        names
+    };
+
+    view! {
+        <button class="import-button" on:click=move |_| {
+            set_show_panel.set(true);
+            load_page.call(None);
+        }>{ "History" }</button>
+        <Show when=move || show_panel.get()>
+            <div class="import-dialog-backdrop">
+                <div class="import-dialog" role="dialog" aria-label="History">
+                    <h2>{ "History" }</h2>
+                    {move || revisions.with(Vec::is_empty).then(|| view! { <p>{ "No changes were recorded for this comparison yet." }</p> })}
+                    <ul class="history-list">
+                        {move || {
+                            let names = names();
+                            revisions.get().into_iter().map(|revision| {
+                                let description = describe_change(&revision.change, &names);
+                                let version = revision.change.item().cloned();
+                                view! {
+                                    <li>
+                                        <strong>{description}</strong>
+                                        <span class="history-meta">{ format!(" by {}, {}", revision.actor, format_timestamp(revision.created_at)) }</span>
+                                        {version.map(|item| view! {
+                                            <button on:click=move |_| {
+                                                on_restore.call(item.clone());
+                                                set_show_panel.set(false);
+                                            }>{ "Restore this version" }</button>
+                                        })}
+                                    </li>
+                                }
+                            }).collect::<Vec<_>>()
+                        }}
+                    </ul>
+                    <div class="import-dialog-actions">
+                        <Show when=move || has_more.get()>
+                            <button on:click=move |_| {
+                                load_page.call(revisions.with_untracked(|revisions| revisions.last().map(|revision| revision.id)));
+                            }>{ "Load older changes" }</button>
+                        </Show>
+                        <button on:click=move |_| set_show_panel.set(false)>{ "Close" }</button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
@@ -0,0 +1,112 @@
+use crate::components::perf_overlay::record_render;
+use leptos::*;
+use urlencoding::encode;
+
+// Properties with the Commons media datatype that hold pictures, rendered as
+// images even before their datatype is known from a Wikidata fetch
+pub const IMAGE_PROPERTIES: &[&str] = &[
+    "P18",   // image
+    "P14",   // traffic sign
+    "P15",   // route map
+    "P41",   // flag image
+    "P94",   // coat of arms image
+    "P109",  // signature
+    "P117",  // chemical structure
+    "P154",  // logo image
+    "P158",  // seal image
+    "P242",  // locator map image
+    "P1442", // image of grave
+    "P1766", // place name sign
+    "P1943", // location map
+    "P2910", // icon
+    "P3451", // nighttime view
+    "P4291", // panoramic view
+    "P5775", // image of interior
+    "P8517", // view
+];
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "svg", "webp", "tif", "tiff", "bmp"];
+
+// Width in pixels of the thumbnails shown in table cells
+const THUMBNAIL_WIDTH: u32 = 120;
+
+// Width in pixels of the image shown in the lightbox
+const LIGHTBOX_WIDTH: u32 = 1280;
+
+fn has_image_extension(path: &str) -> bool {
+    path.rsplit_once('.')
+        .is_some_and(|(_, extension)| IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+// Image shown for a cell value, if any: a direct link to an image file, or a
+// Commons filename ("Foo.jpg" or "File:Foo.jpg") of a Commons media property
+pub fn image_source(value: &str, commons_media: bool) -> Option<ImageSource> {
+    let value = value.trim();
+    if value.starts_with("http://") || value.starts_with("https://") {
+        let path = value.split(['?', '#']).next().unwrap_or(value);
+        return has_image_extension(path).then(|| ImageSource::Url(value.to_string()));
+    }
+    let filename = value.strip_prefix("File:").unwrap_or(value);
+    (commons_media && !filename.is_empty() && !filename.contains('/') && has_image_extension(filename))
+        .then(|| ImageSource::Commons(filename.to_string()))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImageSource {
+    Url(String),
+    Commons(String),
+}
+
+impl ImageSource {
+    // Image URL scaled to the given width. Commons scales files on request,
+    // other images are shown as they are
+    pub fn url(&self, width: u32) -> String {
+        match self {
+            ImageSource::Url(url) => url.clone(),
+            ImageSource::Commons(filename) => format!(
+                "https://commons.wikimedia.org/wiki/Special:FilePath/{}?width={}",
+                encode(&filename.replace(' ', "_")),
+                width
+            ),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            ImageSource::Url(url) => url.rsplit('/').next().unwrap_or(url).to_string(),
+            ImageSource::Commons(filename) => filename.clone(),
+        }
+    }
+}
+
+// Thumbnail of an image value, opening the full image in a lightbox on click
+#[component]
+pub fn ImageCell(source: ImageSource) -> impl IntoView {
+    record_render("ImageCell");
+    let (open, set_open) = create_signal(false);
+    let name = source.name();
+    let thumbnail = source.url(THUMBNAIL_WIDTH);
+    let full = source.url(LIGHTBOX_WIDTH);
+    let lightbox_name = name.clone();
+
+    view! {
+        <img
+            class="image-cell-thumbnail"
+            src=thumbnail
+            alt=name.clone()
+            title=name
+            loading="lazy"
+            on:click=move |_| set_open.set(true)
+        />
+        <Show when=move || open.get()>
+            <div
+                class="lightbox"
+                role="dialog"
+                aria-label=lightbox_name.clone()
+                on:click=move |_| set_open.set(false)
+            >
+                <img src=full.clone() alt=lightbox_name.clone() />
+            </div>
+        </Show>
+    }
+}
@@ -0,0 +1,91 @@
+use crate::api_client::import_items;
+use crate::components::edit_access::remember_edit_key;
+use crate::models::access::EditAccess;
+use crate::models::item::{Item, ItemQuery};
+use leptos::logging::log;
+use leptos::*;
+
+// Whether an item is the empty row kept at the end of the table
+fn is_blank_item(item: &Item) -> bool {
+    item.name.is_empty() && item.description.is_empty() && item.custom_properties.is_empty()
+}
+
+// "Import from Wikidata" button and dialog, creating items from a SPARQL query
+// or a Wikidata class. Only part of the bundle with the `importer` feature
+#[component]
+pub fn ImportDialog(
+    current_url: String,
+    set_items: WriteSignal<Vec<Item>>,
+    set_access: WriteSignal<EditAccess>,
+    show_error: Callback<String>,
+) -> impl IntoView {
+    let (show_import_dialog, set_show_import_dialog) = create_signal(false);
+    let (import_query, set_import_query) = create_signal(String::new());
+    let (importing, set_importing) = create_signal(false);
+
+    // Create items from a SPARQL query or Wikidata class, run by the server
+    let run_import = move || {
+        let query = import_query.get_untracked();
+        if query.trim().is_empty() {
+            return;
+        }
+        set_importing.set(true);
+        let current_url = current_url.clone();
+        spawn_local(async move {
+            match import_items(&current_url, &ItemQuery { query }).await {
+                Ok(imported) => {
+                    remember_edit_key(imported.edit_key, &current_url, set_access);
+                    let imported = imported.value;
+                    log!("Imported {} items", imported.len());
+                    set_items.update(|items| {
+                        // Keep the trailing empty row at the end
+                        let insert_at = match items.last() {
+                            Some(last) if is_blank_item(last) => items.len() - 1,
+                            _ => items.len(),
+                        };
+                        items.splice(insert_at..insert_at, imported);
+                    });
+                    set_show_import_dialog.set(false);
+                    set_import_query.set(String::new());
+                }
+                Err(err) => show_error.call(format!("Failed to import items: {}", err)),
+            }
+            set_importing.set(false);
+        });
+    };
+
+    view! {
+        <button class="import-button" on:click=move |_| set_show_import_dialog.set(true)>
+            { "Import from Wikidata" }
+        </button>
+        <Show when=move || show_import_dialog.get()>
+            <div class="import-dialog-backdrop">
+                <div class="import-dialog" role="dialog" aria-label="Import from Wikidata">
+                    <h2>{ "Import from Wikidata" }</h2>
+                    <p>
+                        { "Enter a class such as \"instances of smartphone\" or \"Q22645\", or a SPARQL query selecting ?item, ?itemLabel and ?itemDescription." }
+                    </p>
+                    <textarea
+                        rows="8"
+                        placeholder="instances of smartphone"
+                        prop:value=move || import_query.get()
+                        on:input=move |event| set_import_query.set(event_target_value(&event))
+                    />
+                    <div class="import-dialog-actions">
+                        <button on:click=move |_| set_show_import_dialog.set(false)>{ "Cancel" }</button>
+                        <button
+                            class="save-button"
+                            disabled=move || importing.get() || import_query.get().trim().is_empty()
+                            on:click={
+                                let run_import = run_import.clone();
+                                move |_| run_import()
+                            }
+                        >
+                            {move || if importing.get() { "Importing..." } else { "Import" }}
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
@@ -1,128 +1,343 @@
+use crate::api_client::{self, ApiError};
+use crate::components::edit_access::{edit_token, load_edit_access, remember_edit_key, EditAccessControls};
 use crate::components::editable_cell::EditableCell;
 use crate::components::editable_cell::InputType;
+use crate::components::chart_panel::ChartPanel;
+use crate::components::image_cell::{image_source, ImageCell, IMAGE_PROPERTIES};
+use crate::components::map_cell::MapCell;
+use crate::components::lazy_mount::LazyMount;
+use crate::components::loading::{LoadError, TableSkeleton};
+use crate::components::live_updates::{live_session_id, subscribe_to_changes};
+use crate::components::snapshot::ShareSnapshot;
+use crate::components::tab_sync::TabSync;
+use crate::components::trash::RecentlyDeleted;
+use crate::components::history::ItemHistory;
+use crate::components::refresh_report::RefreshReport;
+use crate::components::relink_dialog::RelinkDialog;
+use crate::components::conflict_dialog::ConflictDialog;
+use crate::components::comparison_transfer::ComparisonTransfer;
+use crate::components::comparison_settings::{ComparisonMeta, ComparisonSettings};
+use crate::components::property_settings::PropertySettingsButton;
+use crate::components::property_override::PropertyOverrideButton;
+use crate::components::decision::{DecisionBanner, DecisionDialog};
+use crate::components::notifications::{use_notifier, Notifier};
+use crate::components::perf_overlay::{record_render, record_save_queue, track_signal};
 use leptos::*;
-use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use leptos::logging::log;
-use crate::models::item::Item;
-use std::collections::HashMap;
+use crate::models::access::{EditAccess, Visibility};
+use crate::models::chart::{chart_data, ChartData};
+use crate::models::coordinate::{Coordinate, MapPoint, COORDINATE_PROPERTIES};
+use crate::models::instance::InstanceInfo;
+use crate::models::knowledge::{fact_for, KnowledgeSourceKind};
+use crate::models::item::{filter_selected_properties, Item, ItemStatus, LoadedItems, PropertyValue, TruncatedValues, ValueSource, WikidataRefresh, MAX_VALUE_LENGTH};
+use crate::models::live::{ComparisonChange, TabMessage};
+use crate::models::metadata::ComparisonMetadata;
+use crate::models::presence::Heartbeat;
+use crate::models::property::{canonical_property, is_property_id, EntityClaims, PropertyOverride, PropertySelection, PropertySettings, PropertySuggestion, ReusableValues, MAX_CUSTOM_PROPERTY_LENGTH};
+use crate::models::quantity::common_unit;
+use crate::models::review::{RatingSummary, ReviewRequest, MAX_RATING, MIN_RATING};
+use crate::models::score::item_scores;
+use crate::models::wikidata::{
+    claim_values, entities_url, entity_claims, entity_labels, entity_terms, entity_terms_url, parse_property_labels, pasted_entity_id, property_labels_url,
+    referenced_entity_ids, WikidataSuggestion,
+};
+use crate::models::zap::{TipInvoice, TipRequest};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use wasm_bindgen::JsCast;
 use std::rc::Rc;
-use urlencoding::encode;
-#[derive(Deserialize, Clone, Debug)]
-struct WikidataSuggestion {
-    id: String,
-    label: String,
-    description: Option<String>,
-}
+use crate::url_path::encode_segment;
 
 //function to load items from database
-pub async fn load_items_from_db(current_url: &str) -> Result<Vec<Item>, String> {
-    //logging for the raw URL
+pub async fn load_items_from_db(current_url: &str) -> Result<Vec<Item>, ApiError> {
     log!("[DEBUG] Loading items for URL: {}", current_url);
+    let items = match api_client::get_items(current_url).await {
+        Ok(items) => items,
+        // Nothing has been stored for this URL yet
+        Err(err) if err.status() == Some(404) => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    log!("[DEBUG] Successfully parsed {} items", items.len());
+    // Filter the items to only include the selected properties
+    let selected_properties = api_client::selected_properties(current_url).await?;
+    Ok(filter_selected_properties(items, &selected_properties))
+}
+
+// Why a save failed: items someone else saved since they were loaded here, with the copies
+// stored on the server, or any other error to show
+enum SaveFailure {
+    Stale(Vec<Item>),
+    Error(String),
+}
+
+fn save_failure(err: ApiError, action: &str) -> SaveFailure {
+    match err.stale_items() {
+        Some(stored) => SaveFailure::Stale(stored),
+        None => SaveFailure::Error(format!("{}: {}", action, err)),
+    }
+}
 
-    let encoded_url = encode(&current_url);
-    let api_url = format!("/api/urls/{}/items", encoded_url);
+// Server function to load items for a URL, used for server-side rendering and hydration.
+// Private comparisons are loaded with one of their edit tokens only
+#[server(LoadItems, "/api")]
+pub async fn load_items(url: String, edit_token: Option<String>) -> Result<LoadedItems, ServerFnError> {
+    use crate::api::hash_edit_token;
+    use crate::db::Database;
+    use actix_web::web;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
 
-    // Log the constructed API URL
-    log!("[DEBUG] Making request to API endpoint: {}", api_url);
+    log!("[SERVER] Loading items for URL: {}", url);
+    let db: web::Data<Arc<RwLock<Database>>> = leptos_actix::extract().await?;
+    let db = db.read().await;
 
-    let response = gloo_net::http::Request::get(&api_url)
-        .send()
+    let access = db
+        .get_edit_access(&url, edit_token.as_deref().map(hash_edit_token).as_deref())
         .await
-        .map_err(|err| {
-            log!("[ERROR] Network error: {:?}", err);
-            format!("Failed to fetch items: {:?}", err)
-        })?;
-    // Log response metadata
-    log!("[DEBUG] Received response - Status: {}", response.status());
-    if response.status() == 200 {
-        log!("[DEBUG] Successfully received items");
-        let items = response
-            .json::<Vec<Item>>()
-            .await
-            .map_err(|err| {
-                log!("[ERROR] JSON parsing error: {:?}", err);
-                format!("Failed to parse items: {:?}", err)
-            })?;
-            log!("[DEBUG] Successfully parsed {} items", items.len());
-
-        // Get the selected properties for the current URL
-        let selected_properties_response = gloo_net::http::Request::get(
-            &format!("/api/urls/{}/properties", encoded_url)
-        )
-        .send()
+        .map_err(|e| ServerFnError::new(format!("Failed to fetch edit access: {}", e)))?;
+    if !access.can_read() {
+        return Err(ServerFnError::new("This comparison is private, open it with an edit link"));
+    }
+
+    let (items, truncated_values) = db
+        .get_item_previews_by_url(&url)
         .await
-        .map_err(|err| {
-            log!("[ERROR] Network error: {:?}", err);
-            format!("Failed to fetch selected properties: {:?}", err)
-        })?;
-        if selected_properties_response.status() == 200 {
-            let selected_properties: Vec<String> = selected_properties_response
-                .json()
-                .await
-                .map_err(|err| {
-                    log!("[ERROR] JSON parsing error: {:?}", err);
-                    format!("Failed to parse selected properties: {:?}", err)
-                })?;
-            log!("[DEBUG] Successfully received selected properties");
-
-            // Filter the items to only include the selected properties
-            let filtered_items = items
-                .into_iter()
-                .map(|item| {
-                    let filtered_custom_properties = item
-                        .custom_properties
-                        .into_iter()
-                        .filter(|(key, _)| selected_properties.contains(key))
-                        .collect();
-                    Item {
-                        id: item.id,
-                        name: item.name,
-                        description: item.description,
-                        wikidata_id: item.wikidata_id,
-                        custom_properties: filtered_custom_properties,
-                    }
-                })
-                .collect();
-            Ok(filtered_items)
-        } else {
-            let body = selected_properties_response.text().await.unwrap_or_default();
-            log!("[ERROR] Server error details:
-                Status: {}
-                URL: {}
-                Response Body: {}
-                Request URL: {}", 
-                selected_properties_response.status(),
-                api_url,
-                body,
-                current_url
-            );
-            Err(format!("Server error ({}): {}", selected_properties_response.status(), body))
+        .map_err(|e| ServerFnError::new(format!("Failed to fetch items: {}", e)))?;
+    let selected_properties = db
+        .get_selected_properties(&url)
+        .await
+        .map_err(|e| ServerFnError::new(format!("Failed to fetch selected properties: {}", e)))?;
+    let metadata = db
+        .get_metadata(&url)
+        .await
+        .map_err(|e| ServerFnError::new(format!("Failed to fetch title and description: {}", e)))?;
+    let property_settings = db
+        .get_property_settings(&url)
+        .await
+        .map_err(|e| ServerFnError::new(format!("Failed to fetch property settings: {}", e)))?;
+    let property_overrides = db
+        .get_property_overrides(&url)
+        .await
+        .map_err(|e| ServerFnError::new(format!("Failed to fetch property labels: {}", e)))?;
+
+    log!("[SERVER] Returning {} items for URL: {}", items.len(), url);
+    Ok(LoadedItems {
+        items: filter_selected_properties(items, &selected_properties),
+        selected_properties,
+        metadata,
+        truncated_values,
+        property_settings,
+        property_overrides,
+    })
+}
+
+// Collect the distinct custom property IDs used by a list of items, following the
+// order of the selected properties and then the order of appearance
+fn collect_property_ids(items: &[Item], selected_properties: &[String]) -> Vec<String> {
+    let mut property_ids: Vec<String> = selected_properties
+        .iter()
+        .filter(|property| items.iter().any(|item| item.custom_properties.contains_key(*property)))
+        .cloned()
+        .collect();
+    for item in items {
+        for property in item.custom_properties.keys() {
+            if !property_ids.contains(property) {
+                property_ids.push(property.clone());
+            }
+        }
+    }
+    property_ids
+}
+
+// Maximum number of IDs accepted by one wbgetentities request
+const WBGETENTITIES_MAX_IDS: usize = 50;
+// Maximum number of entities per request to the server's Wikidata cache
+const WIKIDATA_CACHE_MAX_IDS: usize = 500;
+
+// Look up Wikidata "labels" or "claims" through the server, which answers from its cache
+// and fetches and caches the entities it doesn't have yet
+async fn fetch_cached_wikidata<T: serde::de::DeserializeOwned>(kind: &str, ids: &[String]) -> HashMap<String, T> {
+    let mut cached = HashMap::new();
+    for ids in ids.chunks(WIKIDATA_CACHE_MAX_IDS) {
+        match api_client::cached_wikidata::<T>(kind, ids).await {
+            Ok(entries) => cached.extend(entries),
+            Err(err) => log!("Failed to fetch cached Wikidata {}: {}", kind, err),
         }
+    }
+    cached
+}
+
+// Set when the server replays recorded Wikidata responses, see wikidata_request_url
+static WIKIDATA_FIXTURES: AtomicBool = AtomicBool::new(false);
+
+// URL to request a wikidata.org URL from. Servers in the fixture mode of tests answer
+// these requests themselves, from the responses they recorded
+pub fn wikidata_request_url(url: &str) -> String {
+    if WIKIDATA_FIXTURES.load(Ordering::Relaxed) {
+        format!("/api/wikidata/fixtures?url={}", encode_segment(url))
     } else {
-        let body = response.text().await.unwrap_or_default();
-        log!("[ERROR] Server error details:
-            Status: {}
-            URL: {}
-            Response Body: {}
-            Request URL: {}", 
-            response.status(),
-            api_url,
-            body,
-            current_url
-        );
-        Err(format!("Server error ({}): {}", response.status(), body))
+        url.to_string()
+    }
+}
+
+// URL to request a knowledge base URL from, replayed from fixtures like wikidata.org
+// requests when it is Wikidata
+fn knowledge_request_url(source: KnowledgeSourceKind, url: &str) -> String {
+    match source {
+        KnowledgeSourceKind::Wikidata => wikidata_request_url(url),
+        _ => url.to_string(),
     }
 }
 
+// Wikidata language and features of the instance, the defaults when they can't be loaded
+async fn load_instance_info() -> InstanceInfo {
+    match api_client::instance_info().await {
+        Ok(info) => {
+            WIKIDATA_FIXTURES.store(info.wikidata_fixtures, Ordering::Relaxed);
+            info
+        }
+        Err(err) => {
+            log!("Failed to load the instance settings: {}", err);
+            InstanceInfo::default()
+        }
+    }
+}
+
+// Values other comparisons on this instance hold for an entity, None when no other
+// comparison has it
+async fn fetch_reusable_values(current_url: &str, wikidata_id: &str) -> Option<ReusableValues> {
+    match api_client::reusable_values(current_url, wikidata_id).await {
+        Ok(values) => Some(values),
+        Err(err) if err.status() == Some(404) => None,
+        Err(err) => {
+            log!("Failed to look up values of {}: {}", wikidata_id, err);
+            None
+        }
+    }
+}
+
+// Display text of each item's property value. With unit normalization, quantities of
+// a property are shown in the unit most items use, when their units can be converted
+fn display_values(items: &[Item], property: &str, normalize_units: bool) -> Vec<String> {
+    let target_unit = normalize_units
+        .then(|| common_unit(items.iter().filter_map(|item| item.quantities.get(property))))
+        .flatten();
+    items
+        .iter()
+        .map(|item| {
+            let converted = target_unit.as_deref().and_then(|unit_id| {
+                item.quantities.get(property).and_then(|quantity| quantity.convert_to(unit_id))
+            });
+            match converted {
+                Some(quantity) => quantity.to_text(),
+                None => item.custom_properties.get(property).cloned().unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+// Whether a property holds Commons media files, known for common image properties
+// and from the datatype of the claims fetched for the compared items
+fn is_image_property(property: &str, property_cache: &HashMap<String, EntityClaims>) -> bool {
+    IMAGE_PROPERTIES.contains(&property) || property_cache.values().any(|claims| claims.images.contains(property))
+}
+
+// Local storage key of the unit normalization preference
+const NORMALIZE_UNITS_KEY: &str = "compareware-normalize-units";
+
+// Local storage key of the number of item columns shown per page, 0 showing all of them
+const ITEMS_PER_PAGE_KEY: &str = "compareware-items-per-page";
+// Choices of items per page, for comparisons too wide for the screen
+const ITEMS_PER_PAGE_OPTIONS: [usize; 5] = [0, 5, 10, 20, 50];
+
+// Indices of the item columns shown on a page, pages past the end showing the last one
+fn page_range(item_count: usize, per_page: usize, page: usize) -> std::ops::Range<usize> {
+    if per_page == 0 {
+        return 0..item_count;
+    }
+    let start = page.min(page_count(item_count, per_page) - 1) * per_page;
+    start..(start + per_page).min(item_count)
+}
+
+fn page_count(item_count: usize, per_page: usize) -> usize {
+    if per_page == 0 {
+        1
+    } else {
+        item_count.div_ceil(per_page).max(1)
+    }
+}
+
+// Whether a name or label contains the text of the table filter, already lowercased
+fn filter_matches(text: &str, filter: &str) -> bool {
+    filter.is_empty() || text.to_lowercase().contains(filter)
+}
+
+// Whether an item passes the status filter: "" keeps all items, "none" the ones without a
+// status, other values the items with the status of that name
+fn status_filter_matches(item: &Item, filter: &str) -> bool {
+    match filter {
+        "" => true,
+        "none" => item.status.is_none(),
+        name => item.status.is_some_and(|status| status.as_str() == name),
+    }
+}
+
+// Local storage key of the random ID this browser rates items with
+const REVIEWER_ID_KEY: &str = "compareware-reviewer-id";
+
+// Seconds between edit presence heartbeats, well below the server's session TTL
+const HEARTBEAT_INTERVAL_SECS: u64 = 10;
+
+// Map a focused cell key ("name-0", "custom-P18-2", ...) to the lock key
+// shared with other editors, which uses the item ID instead of its column index
+fn lock_cell(focused_key: &str, items: &[Item]) -> Option<String> {
+    let (field, index) = focused_key.rsplit_once('-')?;
+    let field = field.strip_prefix("custom-").unwrap_or(field);
+    let item = items.get(index.parse::<usize>().ok()?)?;
+    Some(lock_key(&item.id, field))
+}
+
+// Lock of a cell as sent in heartbeats and matched against the locks of other editors,
+// the property lowercased the same way for the core rows and the custom ones
+fn lock_key(item_id: &str, property: &str) -> String {
+    format!("{}:{}", item_id, property.to_lowercase())
+}
+
+// Split the item columns into runs of adjacent items sharing a group, as
+// (group, number of columns) pairs for the two-level table header
+fn group_spans(groups: &[Option<String>]) -> Vec<(Option<String>, usize)> {
+    let mut spans: Vec<(Option<String>, usize)> = Vec::new();
+    for item_group in groups {
+        match spans.last_mut() {
+            Some((group, span)) if group.is_some() && group == item_group => *span += 1,
+            _ => spans.push((item_group.clone(), 1)),
+        }
+    }
+    spans
+}
+
+// One field of the item at an index, notifying only when that field changed, so a cell
+// is rendered again when its own value changes instead of with every edit of the table
+fn item_field<T: PartialEq + 'static>(items: ReadSignal<Vec<Item>>, index: usize, field: impl Fn(&Item) -> T + 'static) -> Memo<Option<T>> {
+    create_memo(move |_| items.with(|items| items.get(index).map(&field)))
+}
+
+// Item nothing was entered for yet, like the empty row at the end of the table
+fn is_blank(item: &Item) -> bool {
+    item.name.is_empty()
+        && item.description.is_empty()
+        && item.wikidata_id.is_none()
+        && item.custom_properties.values().all(String::is_empty)
+}
+
 #[component]
 pub fn ItemsList(
-    url: String, 
     items: ReadSignal<Vec<Item>>,
     set_items: WriteSignal<Vec<Item>>,
 ) -> impl IntoView {
+    record_render("ItemsList");
     // State to track selected properties
     let (selected_properties, set_selected_properties) = create_signal(HashMap::<String, bool>::new());
     
@@ -135,389 +350,1565 @@ pub fn ItemsList(
     // State to manage suggestions visibility
     let (show_suggestions, set_show_suggestions) = create_signal(HashMap::<String, bool>::new());
     
-    // cache to store fetched properties
-    let (fetched_properties, set_fetched_properties) = create_signal(HashMap::<String, HashMap<String, String>>::new());
-   
     // Signal to store the fetched property labels
     let (property_labels, set_property_labels) = create_signal(HashMap::<String, String>::new());
     
     // State to manage property cache
-    let (property_cache, set_property_cache) = create_signal(HashMap::<String, HashMap<String, String>>::new());
+    let (property_cache, set_property_cache) = create_signal(HashMap::<String, EntityClaims>::new());
+
+    // Toasts for the outcome of API operations, see Notifications
+    let notifier = use_notifier();
+    let show_error = Callback::new(move |message: String| notifier.error(message));
     #[cfg(feature = "ssr")]
     fn get_current_url() -> String {
         use leptos::use_context;
         use actix_web::HttpRequest;
 
+        // Rebuild the full URL so the server uses the same key as the browser
         use_context::<HttpRequest>()
-            .map(|req| req.uri().to_string())
+            .map(|req| {
+                let conn = req.connection_info();
+                format!("{}://{}{}", conn.scheme(), conn.host(), req.uri())
+            })
             .unwrap_or_default()
     }
 
     #[cfg(not(feature = "ssr"))] 
     fn get_current_url() -> String {
+        // The fragment never reaches the server, so it isn't part of the key either
         web_sys::window()
             .and_then(|win| win.location().href().ok())
+            .map(|href| href.split('#').next().unwrap_or_default().to_string())
             .unwrap_or_else(|| "".to_string())
     }
 
     let current_url = Rc::new(get_current_url());
 
-    spawn_local({
+    // Edit protection of the comparison, read-only visitors don't get the edit controls
+    let (access, set_access) = create_signal(EditAccess::default());
+    let read_only = Signal::derive(move || !access.get().can_edit());
+    create_effect({
         let current_url = Rc::clone(&current_url);
-        async move {
-        match load_items_from_db(&current_url).await {
-            Ok(loaded_items) => {
-                // Set the loaded items
-                if loaded_items.is_empty() {
-                    // Initialize with one empty item if the database is empty
-                    set_items.set(vec![Item {
-                        id: Uuid::new_v4().to_string(),
-                        name: String::new(),
-                        description: String::new(),
-                        wikidata_id: None,
-                        custom_properties: HashMap::new(),
-                    }]);
+        move |_| load_edit_access(current_url.to_string(), set_access, show_error)
+    });
+
+    // Load the items through a server function so that the table is
+    // already filled in when the page is rendered on the server
+    let loaded_items = create_resource(
+        {
+            let current_url = Rc::clone(&current_url);
+            move || current_url.to_string()
+        },
+        |url| async move {
+            let token = edit_token(&url);
+            load_items(url, token).await
+        },
+    );
+
+    // Title and description of the comparison, as loaded or as last saved from this page
+    let (saved_metadata, set_saved_metadata) = create_signal(None::<ComparisonMetadata>);
+    let metadata = Signal::derive(move || {
+        saved_metadata
+            .get()
+            .or_else(|| loaded_items.get().and_then(|result| result.ok()).map(|loaded| loaded.metadata))
+            .unwrap_or_default()
+    });
+
+    // Why the items couldn't be loaded, the table is replaced by the error and a retry button
+    let load_error = Signal::derive(move || loaded_items.get().and_then(|result| result.err()).map(|err| err.to_string()));
+    let retry_load = Callback::new(move |_| loaded_items.refetch());
+    // The server renders pages without the edit tokens this browser keeps, so a private
+    // comparison is loaded again once its editor is known
+    create_effect(move |reloaded: Option<bool>| {
+        let editor = access.with(|access| access.visibility == Visibility::Private && access.role.is_some());
+        let reload = !reloaded.unwrap_or_default() && editor && load_error.get_untracked().is_some();
+        if reload {
+            loaded_items.refetch();
+        }
+        reloaded.unwrap_or_default() || reload
+    });
+
+    // Items as loaded, for the link preview tags rendered with the page
+    let loaded_item_list =
+        Signal::derive(move || loaded_items.get().and_then(|result| result.ok()).map(|loaded| loaded.items));
+
+    // Name of the item the comparison was decided for, from the table or else as loaded
+    let decided_item_name = Signal::derive(move || {
+        let item_id = metadata.with(|metadata| metadata.decision.as_ref().map(|decision| decision.item_id.clone()))?;
+        let find = |items: &Vec<Item>| items.iter().find(|item| item.id == item_id).map(|item| item.name.clone());
+        items.with(find).or_else(|| loaded_item_list.with(|loaded| loaded.as_ref().and_then(find)))
+    });
+
+    // Cells loaded as a preview of a long value, per item ID, until they are expanded
+    let (truncated_values, set_truncated_values) = create_signal(TruncatedValues::new());
+
+    // Language and spell-checking of the cells of a property, "name" and "description" included
+    let (property_settings, set_property_settings) = create_signal(HashMap::<String, PropertySettings>::new());
+    let settings_of = move |property: String| {
+        Signal::derive(move || property_settings.with(|all| all.get(&property).cloned().unwrap_or_default()))
+    };
+    let save_property_settings = move |property: String| {
+        Callback::new(move |settings: PropertySettings| {
+            set_property_settings.update(|all| {
+                if settings.is_default() {
+                    all.remove(&property);
                 } else {
-                    set_items.set(loaded_items.clone());
-                }
-    
-                // Derive selected properties from the loaded items
-                let mut selected_props = HashMap::new();
-                let loaded_items_clone = loaded_items.clone();
-                for item in loaded_items {
-                    for (property, _) in item.custom_properties {
-                        selected_props.insert(property, true);
-                    }
+                    all.insert(property.clone(), settings);
                 }
-                set_selected_properties.set(selected_props);
-
-                // Update the custom_properties signal
-                let mut custom_props = Vec::new();
-                for item in loaded_items_clone {
-                    for (property, _) in &item.custom_properties {
-                        if !custom_props.iter().any(|p| p == property) {
-                            custom_props.push(property.clone());
-                        }
-                    }
+            });
+        })
+    };
+    // Labels and tooltips the comparison gives its properties instead of the defaults
+    let (property_overrides, set_property_overrides) = create_signal(HashMap::<String, PropertyOverride>::new());
+    let overrides_of = move |property: String| {
+        Signal::derive(move || property_overrides.with(|all| all.get(&property).cloned().unwrap_or_default()))
+    };
+    let save_property_override = move |property: String| {
+        Callback::new(move |overrides: PropertyOverride| {
+            set_property_overrides.update(|all| {
+                if overrides.is_default() {
+                    all.remove(&property);
+                } else {
+                    all.insert(property.clone(), overrides);
                 }
+            });
+        })
+    };
+    let cell_lang = move |property: &str| {
+        let settings = settings_of(property.to_string());
+        Signal::derive(move || settings.with(|settings| settings.lang.clone()))
+    };
+    let cell_spellcheck = move |property: &str| {
+        let settings = settings_of(property.to_string());
+        Signal::derive(move || settings.with(|settings| settings.spellcheck))
+    };
 
-                let custom_props_clone = custom_props.clone();
-                set_custom_properties.set(custom_props);
-
-                // Fetch labels for the custom properties
-                let property_ids = custom_props_clone;
-                let labels = fetch_property_labels(property_ids).await;
-                set_property_labels.update(|labels_map| {
-                    for (key, value) in labels {
-                        labels_map.insert(key, value);
-                    }
-                });
+    // Apply the loaded items to the table state
+    let apply_loaded_items = move |loaded: LoadedItems| {
+        let loaded_items = loaded.items;
+        set_truncated_values.set(loaded.truncated_values);
+        set_property_settings.set(loaded.property_settings);
+        set_property_overrides.set(loaded.property_overrides);
+        if loaded_items.is_empty() {
+            // Initialize with one empty item if the database is empty
+            set_items.set(vec![Item {
+                id: Uuid::new_v4().to_string(),
+                name: String::new(),
+                description: String::new(),
+                wikidata_id: None,
+                custom_properties: HashMap::new(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            }]);
+        } else {
+            set_items.set(loaded_items.clone());
+        }
 
-                // log!("Items after loading: {:?}", items.get());
+        // Derive selected properties from the loaded items
+        let mut selected_props = HashMap::new();
+        for item in &loaded_items {
+            for property in item.custom_properties.keys() {
+                selected_props.insert(property.clone(), true);
             }
-            Err(err) => {
-                log!("Error loading items: {}", err);
+        }
+        set_selected_properties.set(selected_props);
+
+        // Update the custom_properties signal
+        set_custom_properties.set(collect_property_ids(&loaded_items, &loaded.selected_properties));
+    };
+    // Apply the items whenever they are (re)loaded. The effect also runs on the server, so
+    // that the rendered table is filled in, and before the table is hydrated in the browser
+    create_isomorphic_effect(move |_| match loaded_items.get() {
+        Some(Ok(loaded)) => apply_loaded_items(loaded),
+        Some(Err(err)) => log!("Error loading items: {}", err),
+        None => {}
+    });
+
+    // Fetch labels for the loaded custom properties (runs in the browser only)
+    create_effect(move |_| {
+        if let Some(Ok(loaded)) = loaded_items.get() {
+            let property_ids = collect_property_ids(&loaded.items, &loaded.selected_properties);
+            if !property_ids.is_empty() {
+                spawn_local(async move {
+                    let labels = fetch_property_labels(property_ids).await;
+                    set_property_labels.update(|labels_map| {
+                        labels_map.extend(labels);
+                    });
+                });
             }
         }
-    }});
+    });
+
+    // Edit presence: this tab's session, the number of other editors and the
+    // cells they have locked, refreshed through periodic heartbeats
+    let session_id = store_value(live_session_id());
+    let (other_editors, set_other_editors) = create_signal(0usize);
+    let (locked_cells, set_locked_cells) = create_signal(HashSet::<String>::new());
+    let send_heartbeat = {
+        let current_url = Rc::clone(&current_url);
+        Callback::new(move |_: ()| {
+            let heartbeat = Heartbeat {
+                session_id: session_id.get_value(),
+                cell: focused_cell
+                    .get_untracked()
+                    .and_then(|key| lock_cell(&key, &items.get_untracked())),
+            };
+            let current_url = Rc::clone(&current_url);
+            spawn_local(async move {
+                match api_client::presence_heartbeat(&current_url, &heartbeat).await {
+                    Ok(state) => {
+                        set_other_editors.set(state.editors.saturating_sub(1));
+                        set_locked_cells.set(
+                            state
+                                .locks
+                                .into_iter()
+                                .filter(|lock| lock.session_id != heartbeat.session_id)
+                                .map(|lock| lock.cell)
+                                .collect(),
+                        );
+                    }
+                    Err(err) => log!("Heartbeat rejected: {}", err),
+                }
+            });
+        })
+    };
 
+    // Announce focus changes right away, so cells are locked while being edited
+    create_effect(move |_| {
+        focused_cell.track();
+        send_heartbeat.call(());
+    });
+
+    // Keep the session alive while the page is open, stopping when it's left
+    create_effect(move |_| {
+        let (stop, stopped) = futures::channel::oneshot::channel::<()>();
+        on_cleanup(move || {
+            let _ = stop.send(());
+        });
+        let beat = async move {
+            loop {
+                gloo_timers::future::sleep(std::time::Duration::from_secs(HEARTBEAT_INTERVAL_SECS)).await;
+                send_heartbeat.call(());
+            }
+        };
+        spawn_local(async move {
+            futures::future::select(Box::pin(beat), stopped).await;
+        });
+    });
 
     // Ensure there's an initial empty row
-    if items.get().is_empty() {
+    if items.get_untracked().is_empty() {
         set_items.set(vec![Item {
             id: Uuid::new_v4().to_string(),
             name: String::new(),
             description: String::new(),
             wikidata_id: None,
             custom_properties: HashMap::new(),
+            quantities: HashMap::new(),
+            group: None,
+            tip_recipient: None,
+            status: None,
+            sources: HashMap::new(),
+            updated_at: None,
         }]);
     }
     
-    // Function to send an item to the backend API
-    async fn save_item_to_db(item: Item, selected_properties: ReadSignal<HashMap<String, bool>>, current_url: String) {
-        
-        let custom_props = item.custom_properties.clone();
-        // Use a reactive closure to access `selected_properties`
-        let custom_properties: HashMap<String, String> = (move || {
-            let selected_props = selected_properties.get(); // Access the signal inside a reactive closure
-            custom_props
-                .into_iter()
-                .filter(|(key, _)| selected_props.contains_key(key)) // Use the extracted value
-                .collect()
-        })(); 
-    
-        // Create a new struct to send to the backend
-        #[derive(Serialize, Debug)]
-        struct ItemRequest {
-            url: String,
-            item: Item,
+    // Only persist the properties that are selected for this URL
+    fn strip_unselected_properties(item: Item, selected_props: &HashMap<String, bool>) -> Item {
+        let custom_properties = item
+            .custom_properties
+            .into_iter()
+            .filter(|(key, _)| selected_props.contains_key(key))
+            .collect();
+        Item {
+            custom_properties,
+            ..item
         }
-        
+    }
+
+    // Signals the saves of items use, Copy so every save and its retry can take them along
+    #[derive(Clone, Copy)]
+    struct ItemSaves {
+        items: ReadSignal<Vec<Item>>,
+        set_items: WriteSignal<Vec<Item>>,
+        selected_properties: ReadSignal<HashMap<String, bool>>,
+        set_access: WriteSignal<EditAccess>,
+        dirty_items: ReadSignal<HashSet<String>>,
+        set_dirty_items: WriteSignal<HashSet<String>>,
+        set_saves_in_flight: WriteSignal<usize>,
+        // Items with an autosave sent, and whether they were edited again since
+        autosaving: StoredValue<HashMap<String, bool>>,
+        set_conflicts: WriteSignal<Vec<Item>>,
+        notifier: Notifier,
+    }
+
+    impl ItemSaves {
+        // Take the versions of saved items, their next save is based on them
+        fn saved(&self, saved: &[Item]) {
+            self.set_items.update(|items| {
+                for item in items.iter_mut() {
+                    if let Some(saved) = saved.iter().find(|saved| saved.id == item.id) {
+                        item.updated_at = saved.updated_at;
+                    }
+                }
+            });
+        }
+
+        // Ask what to keep of items someone else saved meanwhile, see ConflictDialog
+        fn conflict(&self, stored: Vec<Item>) {
+            self.set_conflicts.update(|conflicts| {
+                conflicts.retain(|conflict| !stored.iter().any(|item| item.id == conflict.id));
+                conflicts.extend(stored);
+            });
+        }
+
+        fn latest(&self, item_id: &str) -> Option<Item> {
+            self.items.with_untracked(|items| items.iter().find(|item| item.id == item_id).cloned())
+        }
+    }
+
+    // Function to send an item to the backend API, answered with its new version
+    async fn save_item_to_db(item: Item, current_url: String, saves: ItemSaves) -> Result<Item, SaveFailure> {
+
         log!("[FRONTEND] Saving item - ID: {}, Name: '{}', Properties: {:?}", 
         item.id, item.name, item.custom_properties);
     
-        let encoded_url = encode(&current_url);
-        let api_url = format!("/api/urls/{}/items", encoded_url);
+        let item_to_send = strip_unselected_properties(item, &saves.selected_properties.get_untracked());
 
-        let response = gloo_net::http::Request::post(&api_url)
-            .json(&item)
-            .unwrap()
-            .send()
-            .await;
+        match api_client::save_item(&current_url, &item_to_send).await {
+            Ok(saved) => {
+                remember_edit_key(saved.edit_key, &current_url, saves.set_access);
+                Ok(saved.value)
+            }
+            Err(err) => Err(save_failure(err, "Failed to save item")),
+        }
+    }
 
-            log!("[FRONTEND] Save response status: {:?}", response.as_ref().map(|r| r.status()));
-    
-        match response {
-            Ok(resp) => {
-                if resp.status() == 200 {
-                    // log!("Item saved to database: {:?}", item_to_send);
-                } else {
-                    log!("Failed to save item: {}", resp.status_text());
+    // Autosave an item, offering to retry with its latest version when the save fails.
+    // Edits made while the item's save is sent are saved once it's answered, based on the
+    // version it created
+    fn autosave_item(item: Item, current_url: String, saves: ItemSaves) {
+        let item_id = item.id.clone();
+        let sending = saves.autosaving.try_update_value(|autosaving| match autosaving.get_mut(&item_id) {
+            Some(edited) => {
+                *edited = true;
+                true
+            }
+            None => {
+                autosaving.insert(item_id.clone(), false);
+                false
+            }
+        });
+        if sending != Some(false) {
+            return;
+        }
+        saves.set_saves_in_flight.update(|count| *count += 1);
+        spawn_local(async move {
+            let result = save_item_to_db(item, current_url.clone(), saves).await;
+            saves.set_saves_in_flight.update(|count| *count -= 1);
+            let edited = saves.autosaving.try_update_value(|autosaving| autosaving.remove(&item_id)).flatten() == Some(true);
+            match result {
+                Ok(saved) => {
+                    saves.saved(&[saved]);
+                    if let Some(item) = saves.latest(&item_id).filter(|_| edited) {
+                        autosave_item(item, current_url, saves);
+                    }
+                }
+                Err(SaveFailure::Stale(stored)) => saves.conflict(stored),
+                Err(SaveFailure::Error(message)) => {
+                    let retry = Callback::new(move |_| {
+                        if let Some(item) = saves.latest(&item_id) {
+                            autosave_item(item, current_url.clone(), saves);
+                        }
+                    });
+                    saves.notifier.error_with_retry(message, retry);
+                }
+            }
+        });
+    }
+
+    // Function to send several items to the backend API in one request, answered with
+    // their new versions
+    async fn save_items_batch_to_db(items: Vec<Item>, current_url: String, saves: ItemSaves) -> Result<Vec<Item>, SaveFailure> {
+        let selected_props = saves.selected_properties.get_untracked();
+        let items_to_send: Vec<Item> = items
+            .into_iter()
+            .map(|item| strip_unselected_properties(item, &selected_props))
+            .collect();
+
+        log!("[FRONTEND] Saving {} items in batch", items_to_send.len());
+
+        match api_client::save_items(&current_url, &items_to_send).await {
+            Ok(saved) => {
+                remember_edit_key(saved.edit_key, &current_url, saves.set_access);
+                Ok(saved.value)
+            }
+            Err(err) => Err(save_failure(err, "Failed to save items")),
+        }
+    }
+
+    // Save the unsaved items through the batch endpoint, offering to retry when it fails
+    fn save_dirty_items(current_url: String, saves: ItemSaves) {
+        let dirty = saves.dirty_items.get_untracked();
+        if dirty.is_empty() {
+            return;
+        }
+        let items_to_save: Vec<Item> = saves
+            .items
+            .get_untracked()
+            .into_iter()
+            .filter(|item| dirty.contains(&item.id))
+            .collect();
+        spawn_local(async move {
+            match save_items_batch_to_db(items_to_save, current_url.clone(), saves).await {
+                Ok(saved) => {
+                    saves.saved(&saved);
+                    saves.set_dirty_items.update(|pending| {
+                        pending.retain(|id| !dirty.contains(id));
+                    });
+                    log!("Saved {} items", dirty.len());
+                    saves.notifier.success(if dirty.len() == 1 { "Saved 1 item".to_string() } else { format!("Saved {} items", dirty.len()) });
+                }
+                // Nothing was saved, the items stay unsaved until the conflicts are resolved
+                Err(SaveFailure::Stale(stored)) => saves.conflict(stored),
+                Err(SaveFailure::Error(err)) => {
+                    // Items edited since stay unsaved, the retry sends them too
+                    let retry = Callback::new(move |_| save_dirty_items(current_url.clone(), saves));
+                    saves.notifier.error_with_retry(err, retry);
+                }
+            }
+        });
+    }
+
+    // Save mode for this URL: autosave persists every change right away,
+    // manual mode collects changed items until "Save" is pressed
+    let (autosave, set_autosave) = create_signal(true);
+    let (dirty_items, set_dirty_items) = create_signal(HashSet::<String>::new());
+    // Autosaves sent to the server and not answered yet
+    let (saves_in_flight, set_saves_in_flight) = create_signal(0usize);
+    // Stored copies of the items whose save was refused because someone saved them first
+    let (conflicts, set_conflicts) = create_signal(Vec::<Item>::new());
+    let saves = ItemSaves {
+        items,
+        set_items,
+        selected_properties,
+        set_access,
+        dirty_items,
+        set_dirty_items,
+        set_saves_in_flight,
+        autosaving: store_value(HashMap::new()),
+        set_conflicts,
+        notifier,
+    };
+    let save_mode_key = format!("compareware-save-mode:{}", current_url);
+    // Other tabs of this browser showing the comparison, and how many items each hasn't saved yet
+    let tab_sync = TabSync::new();
+    let (other_tabs_unsaved, set_other_tabs_unsaved) = create_signal(HashMap::<String, usize>::new());
+
+    // Restore the remembered save mode (runs in the browser only)
+    create_effect({
+        let save_mode_key = save_mode_key.clone();
+        move |_| {
+            if let Some(storage) = local_storage() {
+                if let Ok(Some(mode)) = storage.get_item(&save_mode_key) {
+                    set_autosave.set(mode != "manual");
+                }
+            }
+        }
+    });
+
+    // Show quantities of a property in a common unit, remembered for all comparisons
+    let (normalize_units, set_normalize_units) = create_signal(false);
+    create_effect(move |_| {
+        if let Some(storage) = local_storage() {
+            if let Ok(Some(mode)) = storage.get_item(NORMALIZE_UNITS_KEY) {
+                set_normalize_units.set(mode == "true");
+            }
+        }
+    });
+    // Horizontal paging of the item columns, the page size remembered for all comparisons
+    let (items_per_page, set_items_per_page) = create_signal(0usize);
+    let (item_page, set_item_page) = create_signal(0usize);
+    create_effect(move |_| {
+        if let Some(storage) = local_storage() {
+            if let Ok(Some(per_page)) = storage.get_item(ITEMS_PER_PAGE_KEY) {
+                set_items_per_page.set(per_page.parse().unwrap_or(0));
+            }
+        }
+    });
+    // Filter box of the table. It narrows the item columns to the names containing its text
+    // and the property rows to the labels containing it, each only when something matches,
+    // so the same box finds items and properties
+    let (table_filter, set_table_filter) = create_signal(String::new());
+    let filter_text = create_memo(move |_| table_filter.get().trim().to_lowercase());
+    let matching_items = create_memo(move |_| {
+        let filter = filter_text.get();
+        items.with(|items| items.iter().filter(|item| filter_matches(&item.name, &filter)).count())
+    });
+    let property_label = move |property: &str| {
+        let property = canonical_property(property);
+        property_overrides
+            .with(|all| all.get(&property).and_then(|overrides| overrides.label.clone()))
+            .or_else(|| property_labels.with(|labels| labels.get(&property).cloned()))
+            .unwrap_or(property)
+    };
+    let matching_properties = create_memo(move |_| {
+        let filter = filter_text.get();
+        custom_properties.with(|properties| {
+            properties.iter().filter(|property| filter_matches(property, &filter) || filter_matches(&property_label(property), &filter)).count()
+        })
+    });
+    let row_filtered_out = move |property: &str| {
+        let filter = filter_text.get();
+        matching_properties.get() > 0 && !filter_matches(property, &filter) && !filter_matches(&property_label(property), &filter)
+    };
+
+    // Indices of the items the status and table filters keep, which the pages are made of
+    let (status_filter, set_status_filter) = create_signal(String::new());
+    let shown_items = create_memo(move |_| {
+        let filter = status_filter.get();
+        let (name_filter, names_match) = (filter_text.get(), matching_items.get() > 0);
+        items.with(|items| {
+            items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| status_filter_matches(item, &filter) && (!names_match || filter_matches(&item.name, &name_filter)))
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>()
+        })
+    });
+    let visible_items = create_memo(move |_| shown_items.with(|shown| page_range(shown.len(), items_per_page.get(), item_page.get())));
+    let item_pages = create_memo(move |_| shown_items.with(|shown| page_count(shown.len(), items_per_page.get())));
+    // Indices of the items on the current page
+    let page_items = create_memo(move |_| shown_items.with(|shown| shown[visible_items.get()].to_vec()));
+    // Index and ID of the item columns on the page, keying the columns so they are only
+    // rebuilt when items are added, removed or paged through, not when a value changes
+    let page_columns = create_memo(move |_| {
+        items.with(|items| page_items.get().into_iter().filter_map(|index| Some((index, items.get(index)?.id.clone()))).collect::<Vec<_>>())
+    });
+    let page_groups = create_memo(move |_| {
+        items.with(|items| page_items.get().into_iter().filter_map(|index| Some(items.get(index)?.group.clone())).collect::<Vec<_>>())
+    });
+    // Removing items can leave the page past the end
+    create_effect(move |_| {
+        let pages = item_pages.get();
+        if item_page.get_untracked() >= pages {
+            set_item_page.set(pages - 1);
+        }
+    });
+    let change_items_per_page = move |per_page: usize| {
+        // Stay around the first item shown
+        let first = visible_items.get_untracked().start;
+        set_items_per_page.set(per_page);
+        set_item_page.set(if per_page == 0 { 0 } else { first / per_page });
+        if let Some(storage) = local_storage() {
+            let _ = storage.set_item(ITEMS_PER_PAGE_KEY, &per_page.to_string());
+        }
+    };
+    // Chart of a numeric property instead of the table, of the items the filters keep
+    let (chart_view, set_chart_view) = create_signal(false);
+    let chart_items = Signal::derive(move || {
+        items.with(|items| shown_items.with(|shown| shown.iter().filter_map(|index| items.get(*index).cloned()).collect::<Vec<_>>()))
+    });
+    let chart_properties = Signal::derive(move || {
+        custom_properties.with(|properties| properties.iter().map(|property| (property.clone(), property_label(property))).collect::<Vec<_>>())
+    });
+    // Weighted scores of the items the filters keep, for the Score row
+    let scores = create_memo(move |_| {
+        chart_items.with(|items| {
+            custom_properties.with(|properties| property_settings.with(|settings| item_scores(items, properties, settings)))
+        })
+    });
+    let toggle_normalize_units = move |enabled: bool| {
+        set_normalize_units.set(enabled);
+        if let Some(storage) = local_storage() {
+            let _ = storage.set_item(NORMALIZE_UNITS_KEY, if enabled { "true" } else { "false" });
+        }
+    };
+
+    // Persist an item right away or mark it as unsaved, depending on the save mode
+    let persist_item = {
+        let current_url = Rc::clone(&current_url);
+        Rc::new(move |item: Item| {
+            let mut truncated: Vec<String> = truncated_values
+                .with_untracked(|truncated| truncated.get(&item.id).map(|properties| properties.iter().cloned().collect()))
+                .unwrap_or_default();
+            truncated.sort();
+            tab_sync.send(&TabMessage::ItemEdited { item: Box::new(item.clone()), truncated });
+            if autosave.get_untracked() {
+                autosave_item(item, current_url.to_string(), saves);
+            } else {
+                set_dirty_items.update(|dirty| {
+                    dirty.insert(item.id);
+                });
+            }
+        })
+    };
+
+    // Flush all unsaved items through the batch endpoint
+    let save_changes = {
+        let current_url = Rc::clone(&current_url);
+        Rc::new(move || {
+            save_dirty_items(current_url.to_string(), saves)
+        })
+    };
+
+    // Switch between autosave and manual save, flushing pending changes when autosave is enabled
+    let set_save_mode = {
+        let save_changes = Rc::clone(&save_changes);
+        move |enabled: bool| {
+            set_autosave.set(enabled);
+            if let Some(storage) = local_storage() {
+                let _ = storage.set_item(&save_mode_key, if enabled { "autosave" } else { "manual" });
+            }
+            if enabled {
+                save_changes();
+            }
+        }
+    };
+
+    // Show an item saved or edited elsewhere
+    let apply_saved_item = move |item: Item, truncated: Vec<String>| {
+        let known = items.with_untracked(|items| items.iter().any(|existing| existing.id == item.id));
+        // Other tabs save their empty last row too, it only shows up here once filled in
+        if !known && is_blank(&item) {
+            return;
+        }
+        set_truncated_values.update(|truncated_values| {
+            if truncated.is_empty() {
+                truncated_values.remove(&item.id);
+            } else {
+                truncated_values.insert(item.id.clone(), truncated.into_iter().collect());
+            }
+        });
+        set_items.update(|items| match items.iter().position(|existing| existing.id == item.id) {
+            Some(index) => items[index] = item,
+            // New items go before the empty row kept at the end of the table
+            None => {
+                let index = if items.last().is_some_and(is_blank) { items.len() - 1 } else { items.len() };
+                items.insert(index, item);
+            }
+        });
+    };
+
+    // Show a property under its new name, keeping its row, values and settings
+    let apply_property_rename = move |property: String, rename_to: String| {
+        set_custom_properties.update(|props| {
+            for p in props.iter_mut().filter(|p| **p == property) {
+                *p = rename_to.clone();
+            }
+        });
+        set_selected_properties.update(|selected| {
+            if let Some(enabled) = selected.remove(&property) {
+                selected.insert(rename_to.clone(), enabled);
+            }
+        });
+        set_items.update(|items| {
+            for item in items {
+                if let Some(value) = item.custom_properties.remove(&property) {
+                    item.custom_properties.insert(rename_to.clone(), value);
+                }
+                if let Some(quantity) = item.quantities.remove(&property) {
+                    item.quantities.insert(rename_to.clone(), quantity);
+                }
+                if let Some(source) = item.sources.remove(&property) {
+                    item.sources.insert(rename_to.clone(), source);
+                }
+            }
+        });
+        set_truncated_values.update(|truncated_values| {
+            for truncated in truncated_values.values_mut() {
+                if truncated.remove(&property) {
+                    truncated.insert(rename_to.clone());
+                }
+            }
+        });
+        set_property_settings.update(|all| {
+            if let Some(settings) = all.remove(&property) {
+                all.insert(rename_to.clone(), settings);
+            }
+        });
+        set_property_overrides.update(|all| {
+            if let Some(overrides) = all.remove(&property) {
+                all.insert(rename_to.clone(), overrides);
+            }
+        });
+    };
+
+    // Live collaboration: apply the changes other tabs save to the comparison
+    let apply_change = Callback::new(move |change: ComparisonChange| match change {
+        ComparisonChange::ItemSaved { item, truncated } => {
+            // Unsaved changes made here win, they overwrite the item once saved
+            if dirty_items.with_untracked(|dirty| dirty.contains(&item.id)) {
+                return;
+            }
+            apply_saved_item(item, truncated);
+        }
+        ComparisonChange::ItemDeleted { item_id } => {
+            set_items.update(|items| items.retain(|item| item.id != item_id));
+            set_dirty_items.update(|dirty| {
+                dirty.remove(&item_id);
+            });
+        }
+        ComparisonChange::PropertyAdded { column } => {
+            let property = column.property.clone();
+            set_selected_properties.update(|selected| {
+                selected.insert(property.clone(), true);
+            });
+            set_custom_properties.update(|props| {
+                if !props.contains(&property) {
+                    props.push(property.clone());
+                }
+            });
+            set_items.update(|items| {
+                for item in items {
+                    if let Some(value) = column.values.get(&item.id) {
+                        item.custom_properties.insert(property.clone(), value.clone());
+                    }
+                    if let Some(quantity) = column.quantities.get(&item.id) {
+                        item.quantities.insert(property.clone(), quantity.clone());
+                    }
+                    if let Some(source) = column.sources.get(&item.id) {
+                        item.sources.insert(property.clone(), *source);
+                    }
+                }
+            });
+            if !property_labels.with_untracked(|labels| labels.contains_key(&property)) {
+                spawn_local(async move {
+                    let labels = fetch_property_labels(vec![property]).await;
+                    set_property_labels.update(|labels_map| labels_map.extend(labels));
+                });
+            }
+        }
+        ComparisonChange::PropertyDeleted { property } => {
+            set_custom_properties.update(|props| props.retain(|p| p != &property));
+            set_selected_properties.update(|selected| {
+                selected.remove(&property);
+            });
+            set_items.update(|items| {
+                for item in items {
+                    item.custom_properties.remove(&property);
+                    item.sources.remove(&property);
+                }
+            });
+        }
+        ComparisonChange::PropertyRenamed { property, rename_to } => apply_property_rename(property, rename_to),
+        ComparisonChange::PropertiesReordered { properties } => {
+            // Properties the order doesn't know about yet keep their place at the end
+            set_custom_properties.update(|props| {
+                let mut order: Vec<String> = properties.into_iter().filter(|p| props.contains(p)).collect();
+                order.extend(props.iter().filter(|p| !order.contains(p)).cloned().collect::<Vec<_>>());
+                *props = order;
+            });
+        }
+        ComparisonChange::PropertySettingsChanged { property, settings } => save_property_settings(property).call(settings),
+        ComparisonChange::PropertyOverrideChanged { property, overrides } => save_property_override(property).call(overrides),
+        ComparisonChange::Reload => loaded_items.refetch(),
+    });
+    // Items taken out of the trash come back like items saved in another tab
+    let restore_item = Callback::new(move |item: Item| apply_change.call(ComparisonChange::item_saved(item)));
+    // An earlier version from the history is shown with its values in full and saved
+    // like any edit, which records it as the newest version
+    let restore_version = {
+        let persist_item = Rc::clone(&persist_item);
+        Callback::new(move |item: Item| {
+            set_dirty_items.update(|dirty| {
+                dirty.remove(&item.id);
+            });
+            apply_saved_item(item.clone(), Vec::new());
+            persist_item(item);
+        })
+    };
+    // Edit conflicts: keep the copy edited here, saved again on top of the stored version,
+    // or take the stored one and drop the edits
+    let resolve_conflict = move |item_id: &str| {
+        let stored = conflicts.with_untracked(|conflicts| conflicts.iter().find(|item| item.id == item_id).cloned());
+        set_conflicts.update(|conflicts| conflicts.retain(|item| item.id != item_id));
+        stored
+    };
+    let keep_mine = {
+        let persist_item = Rc::clone(&persist_item);
+        Callback::new(move |item_id: String| {
+            let Some(stored) = resolve_conflict(&item_id) else {
+                return;
+            };
+            set_items.update(|items| {
+                if let Some(item) = items.iter_mut().find(|item| item.id == item_id) {
+                    item.updated_at = stored.updated_at;
+                }
+            });
+            if let Some(item) = saves.latest(&item_id) {
+                persist_item(item);
+            }
+        })
+    };
+    let use_theirs = Callback::new(move |item_id: String| {
+        if let Some(stored) = resolve_conflict(&item_id) {
+            set_dirty_items.update(|dirty| {
+                dirty.remove(&item_id);
+            });
+            apply_saved_item(stored, Vec::new());
+        }
+    });
+    // Subscribe once the page runs in the browser
+    create_effect({
+        let current_url = Rc::clone(&current_url);
+        move |_| subscribe_to_changes(&current_url, apply_change)
+    });
+
+    // Tabs of this browser: edits show up in the other tabs before they reach the server
+    let apply_tab_message = Callback::new(move |message: TabMessage| match message {
+        TabMessage::ItemEdited { item, truncated } => {
+            // The latest edit wins, and the tab that made it saves it
+            set_dirty_items.update(|dirty| {
+                dirty.remove(&item.id);
+            });
+            apply_saved_item(*item, truncated);
+        }
+        TabMessage::SaveStatus { tab, unsaved } => set_other_tabs_unsaved.update(|tabs| {
+            if unsaved == 0 {
+                tabs.remove(&tab);
+            } else {
+                tabs.insert(tab, unsaved);
+            }
+        }),
+        TabMessage::Opened => {
+            let unsaved = dirty_items.with_untracked(HashSet::len) + saves_in_flight.get_untracked();
+            tab_sync.send(&TabMessage::SaveStatus { tab: live_session_id(), unsaved });
+        }
+    });
+    create_effect({
+        let current_url = Rc::clone(&current_url);
+        move |_| {
+            tab_sync.connect(&current_url, apply_tab_message);
+            tab_sync.send(&TabMessage::Opened);
+            // Closed tabs have nothing left to save
+            let closed = window_event_listener_untyped("pagehide", move |_| {
+                tab_sync.send(&TabMessage::SaveStatus { tab: live_session_id(), unsaved: 0 });
+            });
+            on_cleanup(move || closed.remove());
+        }
+    });
+    create_effect(move |previous: Option<usize>| {
+        let unsaved = dirty_items.with(HashSet::len) + saves_in_flight.get();
+        if previous.is_some_and(|previous| previous != unsaved) {
+            tab_sync.send(&TabMessage::SaveStatus { tab: live_session_id(), unsaved });
+        }
+        unsaved
+    });
+
+    // Instrumentation for the performance overlay
+    track_signal("items", items);
+    track_signal("focused_cell", focused_cell);
+    track_signal("custom_properties", custom_properties);
+    track_signal("property_cache", property_cache);
+    track_signal("dirty_items", dirty_items);
+    create_effect(move |_| record_save_queue(dirty_items.with(HashSet::len) + saves_in_flight.get()));
+
+    // Wikidata language and the features an admin left on, loaded once the page runs in the browser
+    let instance = create_local_resource(|| (), |_| load_instance_info());
+    let instance_info = move || instance.get().unwrap_or_default();
+
+    // Toolbar features left out of the WASM bundle unless their cargo feature is enabled
+    let optional_tools: Vec<View> = vec![
+        #[cfg(feature = "importer")]
+        view! {
+            <span class:turned-off=move || !instance_info().features.imports>
+                <crate::components::import_dialog::ImportDialog current_url=current_url.to_string() set_items set_access show_error/>
+            </span>
+        }
+        .into_view(),
+        #[cfg(feature = "publishing")]
+        view! {
+            <crate::components::listing_dialog::ListingDialog current_url=current_url.to_string() show_error/>
+        }
+        .into_view(),
+        #[cfg(feature = "publishing")]
+        view! {
+            <crate::components::publishing_dialog::PublishingDialog current_url=current_url.to_string() show_error/>
+        }
+        .into_view(),
+        #[cfg(feature = "publishing")]
+        view! {
+            <crate::components::mirror_dialog::MirrorDialog current_url=current_url.to_string() show_error/>
+        }
+        .into_view(),
+    ];
+
+    // State of the tip dialog: the item being tipped, and the invoice once requested
+    let (tip_target, set_tip_target) = create_signal(None::<(String, String)>);
+    let (tip_amount, set_tip_amount) = create_signal(String::from("1000"));
+    let (tip_comment, set_tip_comment) = create_signal(String::new());
+    let (tip_invoice, set_tip_invoice) = create_signal(None::<TipInvoice>);
+    let (requesting_tip, set_requesting_tip) = create_signal(false);
+
+    // Ask the server for an invoice paying the item's creator, with a zap request when they are on Nostr
+    let request_tip = {
+        let current_url = Rc::clone(&current_url);
+        Callback::new(move |_: ()| {
+            let Some((item_id, _)) = tip_target.get_untracked() else {
+                return;
+            };
+            let Ok(amount_sats) = tip_amount.get_untracked().trim().parse::<u64>() else {
+                show_error.call("Tip amount must be a whole number of sats".to_string());
+                return;
+            };
+            let comment = tip_comment.get_untracked();
+            let request = TipRequest {
+                amount_sats,
+                comment: (!comment.trim().is_empty()).then_some(comment),
+            };
+            set_requesting_tip.set(true);
+            let current_url = Rc::clone(&current_url);
+            spawn_local(async move {
+                match api_client::tip_item(&current_url, &item_id, &request).await {
+                    Ok(invoice) => set_tip_invoice.set(Some(invoice)),
+                    Err(err) => show_error.call(format!("Failed to create tip: {}", err)),
+                }
+                set_requesting_tip.set(false);
+            });
+        })
+    };
+    let close_tip_dialog = move || {
+        set_tip_target.set(None);
+        set_tip_invoice.set(None);
+        set_tip_comment.set(String::new());
+    };
+
+    // Star ratings of the items, with the ratings given from this browser
+    let reviewer_id = store_value(String::new());
+    let (ratings, set_ratings) = create_signal(HashMap::<String, RatingSummary>::new());
+    create_effect({
+        let current_url = Rc::clone(&current_url);
+        move |_| {
+            let Some(storage) = local_storage() else {
+                return;
+            };
+            let id = match storage.get_item(REVIEWER_ID_KEY) {
+                Ok(Some(id)) => id,
+                _ => {
+                    let id = Uuid::new_v4().to_string();
+                    let _ = storage.set_item(REVIEWER_ID_KEY, &id);
+                    id
+                }
+            };
+            reviewer_id.set_value(id.clone());
+            let current_url = Rc::clone(&current_url);
+            spawn_local(async move {
+                match api_client::ratings(&current_url, &id).await {
+                    Ok(summaries) => set_ratings.set(summaries),
+                    Err(err) => show_error.call(format!("Failed to load ratings: {}", err)),
+                }
+            });
+        }
+    });
+    let rate_item = {
+        let current_url = Rc::clone(&current_url);
+        Callback::new(move |(item_id, rating): (String, u8)| {
+            let review = ReviewRequest {
+                reviewer_id: reviewer_id.get_value(),
+                rating,
+            };
+            let current_url = Rc::clone(&current_url);
+            spawn_local(async move {
+                match api_client::review_item(&current_url, &item_id, &review).await {
+                    Ok(summary) => set_ratings.update(|ratings| {
+                        ratings.insert(item_id, summary);
+                    }),
+                    Err(err) => show_error.call(format!("Failed to rate item: {}", err)),
+                }
+            });
+        })
+    };
+
+    // Replace the preview of a long value with its full text, so it can be read and edited
+    let expand_value = {
+        let current_url = Rc::clone(&current_url);
+        Callback::new(move |(item_id, property): (String, String)| {
+            let current_url = Rc::clone(&current_url);
+            spawn_local(async move {
+                match api_client::property_value(&current_url, &item_id, &property).await {
+                    Ok(PropertyValue { item_id, property, value }) => {
+                        set_items.update(|items| {
+                            if let Some(item) = items.iter_mut().find(|item| item.id == item_id) {
+                                item.custom_properties.insert(property.clone(), value);
+                            }
+                        });
+                        set_truncated_values.update(|truncated| {
+                            if let Some(properties) = truncated.get_mut(&item_id) {
+                                properties.remove(&property);
+                            }
+                        });
+                    }
+                    Err(err) => show_error.call(format!("Failed to load the full value: {}", err)),
+                }
+            });
+        })
+    };
+
+    let current_url_for_remove_item = Rc::clone(&current_url);
+    // Function to remove an item
+    let remove_item = {
+        move |index: usize| {
+            let item_id = items.get()[index].id.clone();
+            let current_url = Rc::clone(&current_url_for_remove_item);
+            spawn_local(async move {
+                let trashed = match api_client::delete_item(&current_url, &item_id).await {
+                    Ok(_) => true,
+                    // A 404 means the item was never saved, so it only needs removing locally
+                    Err(err) if err.status() == Some(404) => false,
+                    Err(err) => return show_error.call(format!("Failed to delete item: {}", err)),
+                };
+                let removed = set_items.try_update(|items| items.remove(index));
+                if let Some(item) = removed.filter(|_| trashed) {
+                    let name = if item.name.trim().is_empty() { "the item".to_string() } else { item.name };
+                    notifier.success(format!("Moved {} to the trash", name));
+                }
+                set_dirty_items.update(|dirty| {
+                    dirty.remove(&item_id);
+                });
+                log!("Item deleted: {}", item_id);
+            });
+        }
+    };
+
+    // Restore a deleted property row at its former position, with the values the server kept
+    let undo_property_deletion = move |current_url: String, property: String, position: usize, deletion_id: i64| {
+        Callback::new(move |_: ()| {
+            let (current_url, property) = (current_url.clone(), property.clone());
+            spawn_local(async move {
+                match api_client::undo_property_deletion(&current_url, deletion_id).await {
+                    Ok(column) => {
+                        set_custom_properties.update(|props| {
+                            if !props.contains(&property) {
+                                props.insert(position.min(props.len()), property.clone());
+                            }
+                        });
+                        set_selected_properties.update(|selected| {
+                            selected.insert(property.clone(), true);
+                        });
+                        set_items.update(|items| {
+                            for item in items {
+                                if let Some(value) = column.values.get(&item.id) {
+                                    item.custom_properties.insert(property.clone(), value.clone());
+                                }
+                                if let Some(quantity) = column.quantities.get(&item.id) {
+                                    item.quantities.insert(property.clone(), quantity.clone());
+                                }
+                                if let Some(source) = column.sources.get(&item.id) {
+                                    item.sources.insert(property.clone(), *source);
+                                }
+                            }
+                        });
+                    }
+                    Err(err) => show_error.call(format!("Failed to undo deleting the property: {}", err)),
+                }
+            });
+        })
+    };
+
+    let current_url_for_remove_property = Rc::clone(&current_url);
+    // Function to remove a property
+    let remove_property = {
+        move |property: String, position: usize| {
+            let current_url = Rc::clone(&current_url_for_remove_property);
+            spawn_local(async move {
+                let deletion = match api_client::delete_property(&current_url, &property).await {
+                    Ok(deletion) => Some(deletion),
+                    // A 404 means the property was never saved, so it only needs removing locally
+                    Err(err) if err.status() == Some(404) => None,
+                    Err(err) => return show_error.call(format!("Failed to delete property: {}", err)),
+                };
+                set_custom_properties.update(|props| {
+                    props.retain(|p| p != &property);
+                });
+                set_selected_properties.update(|selected| {
+                    selected.remove(&property);
+                });
+                set_items.update(|items| {
+                    for item in items {
+                        item.custom_properties.remove(&property);
+                        item.sources.remove(&property);
+                    }
+                });
+                log!("Property deleted: {}", property);
+                if let Some(deletion) = deletion {
+                    let label = property_labels.get_untracked().get(&property).cloned().unwrap_or_else(|| property.clone());
+                    let undo = undo_property_deletion(current_url.to_string(), property, position, deletion.deletion_id);
+                    notifier.success_with_action(format!("Deleted property {}", label), "Undo delete property", undo);
+                }
+            });
+        }
+    };
+
+    let current_url_for_move_property = Rc::clone(&current_url);
+    // Function to move a property row up (-1) or down (+1) and persist the new order
+    let move_property = move |property: String, offset: isize| {
+        let mut order = custom_properties.get_untracked();
+        let Some(index) = order.iter().position(|p| p == &property) else {
+            return;
+        };
+        let target = index as isize + offset;
+        if target < 0 || target as usize >= order.len() {
+            return;
+        }
+        order.swap(index, target as usize);
+        set_custom_properties.set(order.clone());
+
+        // Keep keyboard focus on the moved row once it has been re-rendered
+        request_animation_frame(move || {
+            if let Some(row_header) = web_sys::window()
+                .and_then(|win| win.document())
+                .and_then(|doc| doc.get_element_by_id(&format!("property-row-{}", property)))
+                .and_then(|el| el.dyn_into::<web_sys::HtmlElement>().ok())
+            {
+                let _ = row_header.focus();
+            }
+        });
+
+        let current_url = Rc::clone(&current_url_for_move_property);
+        spawn_local(async move {
+            match api_client::reorder_properties(&current_url, &order).await {
+                Ok(()) => log!("Property order saved: {:?}", order),
+                Err(err) => show_error.call(format!("Failed to save property order: {}", err)),
+            }
+        });
+    };
+
+    let current_url_for_rename_property = Rc::clone(&current_url);
+    // Function to rename a custom property, shown under its new name once the server has it
+    let rename_property = move |property: String, rename_to: String| {
+        let rename_to = rename_to.trim().to_string();
+        if rename_to.is_empty() || rename_to == property {
+            return;
+        }
+        let current_url = Rc::clone(&current_url_for_rename_property);
+        spawn_local(async move {
+            match api_client::rename_property(&current_url, &property, &rename_to).await {
+                Ok(_) => {
+                    log!("Property renamed: {} -> {}", property, rename_to);
+                    apply_property_rename(property, rename_to);
+                }
+                Err(err) => show_error.call(format!("Failed to rename property: {}", err)),
+            }
+        });
+    };
+
+    // State to store Wikidata suggestions
+    let (wikidata_suggestions, set_wikidata_suggestions) = create_signal(HashMap::<String, Vec<WikidataSuggestion>>::new());
+
+    // Function to fetch Wikidata suggestions
+    let fetch_wikidata_suggestions = move |key: String, query: String| {
+        log!("Fetching suggestions for key: {}, query: {}", key, query);
+        let InstanceInfo { wikidata_language: language, knowledge_source, .. } = instance_info();
+        spawn_local(async move {
+            if query.is_empty() {
+                set_wikidata_suggestions.update(|suggestions| {
+                    suggestions.remove(&key);
+                });
+                return;
+            }
+
+            let source = knowledge_source.source();
+            match gloo_net::http::Request::get(&knowledge_request_url(knowledge_source, &source.search_url(&query, &language))).send().await {
+                Ok(response) => {
+                    if let Ok(data) = response.json::<serde_json::Value>().await {
+                        set_wikidata_suggestions.update(|suggestions| {
+                            suggestions.insert(key, source.suggestions(&data));
+                        });
+                    }
+                }
+                Err(_) => log!("Failed to fetch Wikidata suggestions"),
+            }
+        });
+    };
+
+    // Fill the empty cells of an item picked from the suggestions in every Wikidata property
+    // the comparison already shows, from one fetch of its entity's claims, and save it
+    let save_filled_item = {
+        let persist_item = Rc::clone(&persist_item);
+        Callback::new(move |item: Item| persist_item(item))
+    };
+    let fill_from_wikidata = move |item_id: String, wikidata_id: String| {
+        spawn_local(async move {
+            let claims = fetch_item_properties(&wikidata_id, set_property_labels, property_cache, set_property_cache, property_labels).await;
+            let shown: Vec<String> = selected_properties.with_untracked(|selected| {
+                selected.iter().filter(|(property, shown)| **shown && is_property_id(property)).map(|(property, _)| property.clone()).collect()
+            });
+            let mut filled = None;
+            set_items.update(|items| {
+                let Some(item) = items.iter_mut().find(|item| item.id == item_id) else {
+                    return;
+                };
+                for property in &shown {
+                    let Some(value) = claims.values.get(property) else {
+                        continue;
+                    };
+                    if item.custom_properties.get(property).map_or(true, String::is_empty) {
+                        item.custom_properties.insert(property.clone(), value.clone());
+                        item.sources.insert(property.clone(), ValueSource::Wikidata);
+                        if let Some(quantity) = claims.quantities.get(property) {
+                            item.quantities.insert(property.clone(), quantity.clone());
+                        }
+                    }
+                }
+                filled = Some(item.clone());
+            });
+            if let Some(item) = filled {
+                log!("Filled picked item {} from Wikidata", wikidata_id);
+                save_filled_item.call(item);
+            }
+        });
+    };
+
+    // Values other comparisons curated for a picked entity, offered instead of its raw Wikidata claims
+    let (reuse_offer, set_reuse_offer) = create_signal(None::<(String, ReusableValues)>);
+    let reuse_lookup_url = store_value(current_url.to_string());
+    let fill_picked_item = move |item_id: String, wikidata_id: String| {
+        let current_url = reuse_lookup_url.get_value();
+        spawn_local(async move {
+            match fetch_reusable_values(&current_url, &wikidata_id).await {
+                Some(reusable) => set_reuse_offer.set(Some((item_id, reusable))),
+                None => fill_from_wikidata(item_id, wikidata_id),
+            }
+        });
+    };
+
+    // Look up an item pasted into a name cell as a Wikidata link or ID, taking its label and
+    // description without a search, then fill it in like a picked suggestion
+    let resolve_pasted_entity = move |index: usize, item_id: String, entity_id: String| {
+        let language = instance_info().wikidata_language;
+        set_show_suggestions.update(|suggestions| {
+            suggestions.insert(format!("name-{}", index), false);
+        });
+        spawn_local(async move {
+            let url = wikidata_request_url(&entity_terms_url(&[entity_id.clone()], &language));
+            let data = match gloo_net::http::Request::get(&url).send().await {
+                Ok(response) => response.json::<serde_json::Value>().await.unwrap_or_default(),
+                Err(err) => {
+                    show_error.call(format!("Failed to look up {} on Wikidata: {:?}", entity_id, err));
+                    return;
+                }
+            };
+            let entity = &data["entities"][&entity_id];
+            if entity.is_null() || entity.get("missing").is_some() {
+                show_error.call(format!("{} isn't an item on Wikidata", entity_id));
+                return;
+            }
+            let terms = entity_terms(&data, &language).remove(&entity_id).unwrap_or_default();
+            set_items.update(|items| {
+                if let Some(item) = items.iter_mut().find(|item| item.id == item_id) {
+                    item.name = terms.label.unwrap_or_else(|| entity_id.clone());
+                    item.description = terms.description.unwrap_or_default();
+                    item.wikidata_id = Some(entity_id.clone());
+                    item.sources.insert("name".into(), ValueSource::Wikidata);
+                    item.sources.insert("description".into(), ValueSource::Wikidata);
+                }
+            });
+            log!("Resolved pasted Wikidata item {}", entity_id);
+            fill_picked_item(item_id, entity_id);
+        });
+    };
+
+    // Fill the empty custom properties of an item picked from another knowledge base than
+    // Wikidata with the facts it has of them, and link the item to the Wikidata item it
+    // names so its Wikidata properties are filled in too
+    let fill_from_knowledge_source = move |item_id: String, entity_id: String| {
+        let InstanceInfo { wikidata_language: language, knowledge_source, .. } = instance_info();
+        let source = knowledge_source.source();
+        spawn_local(async move {
+            let url = knowledge_request_url(knowledge_source, &source.facts_url(&entity_id, &language));
+            let data = match gloo_net::http::Request::get(&url).send().await {
+                Ok(response) => response.json::<serde_json::Value>().await.unwrap_or_default(),
+                Err(err) => {
+                    show_error.call(format!("Failed to look up {} on {}: {:?}", entity_id, source.name(), err));
+                    return;
+                }
+            };
+            let facts = source.facts(&entity_id, &data, &language);
+            let wikidata_id = source.wikidata_id(&entity_id, &data);
+            let shown: Vec<String> = selected_properties.with_untracked(|selected| {
+                selected.iter().filter(|(property, shown)| **shown && !is_property_id(property)).map(|(property, _)| property.clone()).collect()
+            });
+            let mut filled = None;
+            set_items.update(|items| {
+                let Some(item) = items.iter_mut().find(|item| item.id == item_id) else {
+                    return;
+                };
+                for property in &shown {
+                    if let Some(value) = fact_for(&facts, property) {
+                        if item.custom_properties.get(property).map_or(true, String::is_empty) {
+                            item.custom_properties.insert(property.clone(), value.clone());
+                        }
+                    }
+                }
+                if wikidata_id.is_some() {
+                    item.wikidata_id = wikidata_id.clone();
+                }
+                filled = Some(item.clone());
+            });
+            log!("Filled picked item {} from {}", entity_id, source.name());
+            match (filled, wikidata_id) {
+                (Some(_), Some(wikidata_id)) => fill_picked_item(item_id, wikidata_id),
+                (Some(item), None) => save_filled_item.call(item),
+                (None, _) => {}
+            }
+        });
+    };
+
+    // Link items typed in by hand to the Wikidata entities accepted in the relink dialog,
+    // filling in the properties they have no value for when asked to
+    let link_to_wikidata = {
+        let persist_item = Rc::clone(&persist_item);
+        Callback::new(move |(links, fill): (Vec<(String, WikidataSuggestion)>, bool)| {
+            let mut linked = Vec::new();
+            set_items.update(|items| {
+                for (item_id, entity) in &links {
+                    if let Some(item) = items.iter_mut().find(|item| item.id == *item_id) {
+                        item.wikidata_id = Some(entity.id.clone());
+                        if item.description.is_empty() {
+                            item.description = entity.description.clone().unwrap_or_default();
+                            item.sources.insert("description".into(), ValueSource::Wikidata);
+                        }
+                        linked.push(item.clone());
+                    }
                 }
+            });
+            if !fill {
+                linked.into_iter().for_each(|item| persist_item(item));
+                return;
             }
-            Err(err) => log!("Failed to save item: {:?}", err),
-        }
-    }
-
-    let current_url_for_remove_item = Rc::clone(&current_url);
-    // Function to remove an item
-    let remove_item = {
-        let set_items = set_items.clone();
-        move |index: usize| {
-            let item_id = items.get()[index].id.clone();
-            let current_url = Rc::clone(&current_url_for_remove_item);
+            let persist_item = Rc::clone(&persist_item);
             spawn_local(async move {
-                let response = gloo_net::http::Request::delete(
-                    &format!("/api/urls/{}/items/{}", encode(&current_url), item_id)
-                )
-                .send()
-                .await;
-                
-                match response {
-                    Ok(resp) => {
-                        if resp.status() == 200 {
-                            set_items.update(|items| {
-                                items.remove(index);
-                            });
-                            log!("Item deleted: {}", item_id);
-                        } else {
-                            log!("Failed to delete item: {}", resp.status_text());
+                let ids = links.into_iter().map(|(_, entity)| entity.id).collect();
+                let claims = fetch_entities_properties(ids, set_property_labels, property_cache, set_property_cache, property_labels).await;
+                let mut filled = Vec::new();
+                set_items.update(|items| {
+                    for item in items.iter_mut().filter(|item| linked.iter().any(|linked| linked.id == item.id)) {
+                        let Some(claims) = item.wikidata_id.as_ref().and_then(|id| claims.get(id)) else {
+                            continue;
+                        };
+                        // Values typed in stay, Wikidata only fills the empty cells
+                        for (property, value) in &claims.values {
+                            if item.custom_properties.get(property).map_or(true, String::is_empty) {
+                                item.custom_properties.insert(property.clone(), value.clone());
+                                item.sources.insert(property.clone(), ValueSource::Wikidata);
+                                if let Some(quantity) = claims.quantities.get(property) {
+                                    item.quantities.insert(property.clone(), quantity.clone());
+                                }
+                            }
                         }
+                        filled.push(item.clone());
                     }
-                    Err(err) => log!("Failed to delete item: {:?}", err),
-                }
+                });
+                filled.into_iter().for_each(|item| persist_item(item));
             });
-        }
+        })
     };
 
-    let current_url_for_remove_property = Rc::clone(&current_url);
-    // Function to remove a property
-    let remove_property = {
-        let set_custom_properties = set_custom_properties.clone();
-        let set_selected_properties = set_selected_properties.clone();
-        let set_items = set_items.clone();
-        move |property: String| {
-            let current_url = Rc::clone(&current_url_for_remove_property);
+    // Let the server fetch the labels, descriptions and claims of the items' entities anew
+    // and replace the names, descriptions and values that came from Wikidata with them.
+    // Values typed in or imported stay, the values that changed are listed afterwards
+    let (refreshing, set_refreshing) = create_signal(false);
+    let (refresh_report, set_refresh_report) = create_signal(None::<WikidataRefresh>);
+    let refresh_from_wikidata = {
+        let current_url = Rc::clone(&current_url);
+        move |_| {
+            let current_url = current_url.to_string();
+            set_refreshing.set(true);
             spawn_local(async move {
-                let response = gloo_net::http::Request::delete(
-                    &format!("/api/urls/{}/properties/{}", encode(&current_url), property)
-                )
-                .send()
-                .await;
-    
-                match response {
-                    Ok(resp) => {
-                        if resp.status() == 200 {
-                            set_custom_properties.update(|props| {
-                                props.retain(|p| p != &property);
-                            });
-                            set_selected_properties.update(|selected| {
-                                selected.remove(&property);
-                            });
-                            set_items.update(|items| {
-                                for item in items {
-                                    item.custom_properties.remove(&property);
-                                }
-                            });
-                            log!("Property deleted: {}", property);
+                match api_client::refresh_from_wikidata(&current_url).await {
+                    Ok(refresh) => {
+                        // The server cached the claims it fetched, columns added later read them from there
+                        set_property_cache.set(HashMap::new());
+                        for item in refresh.items.clone() {
+                            apply_change.call(ComparisonChange::item_saved(item));
+                        }
+                        if refresh.changes.is_empty() {
+                            notifier.success("Everything is up to date with Wikidata");
                         } else {
-                            log!("Failed to delete property: {}", resp.status_text());
+                            set_refresh_report.set(Some(refresh));
                         }
                     }
-                    Err(err) => log!("Failed to delete property: {:?}", err),
+                    Err(err) => show_error.call(format!("Failed to refresh from Wikidata: {}", err)),
                 }
+                set_refreshing.set(false);
             });
         }
     };
 
-    // State to store Wikidata suggestions
-    let (wikidata_suggestions, set_wikidata_suggestions) = create_signal(HashMap::<String, Vec<WikidataSuggestion>>::new());
+    //function to fetch properties
+    async fn fetch_item_properties(
+        wikidata_id: &str,
+        set_property_labels: WriteSignal<HashMap<String, String>>,
+        property_cache: ReadSignal<HashMap<String, EntityClaims>>,
+        set_property_cache: WriteSignal<HashMap<String, EntityClaims>>,
+        property_labels: ReadSignal<HashMap<String, String>>,
+    ) -> EntityClaims {
+        fetch_entities_properties(
+            vec![wikidata_id.to_string()],
+            set_property_labels,
+            property_cache,
+            set_property_cache,
+            property_labels,
+        )
+        .await
+        .remove(wikidata_id)
+        .unwrap_or_default()
+    }
 
-    // Function to fetch Wikidata suggestions
-    let fetch_wikidata_suggestions = move |key: String, query: String| {
-        log!("Fetching suggestions for key: {}, query: {}", key, query);
-        spawn_local(async move {
-            if query.is_empty() {
-                set_wikidata_suggestions.update(|suggestions| {
-                    suggestions.remove(&key);
-                });
-                return;
+    // Fetch the claims of several items at once through wbgetentities, which accepts
+    // up to 50 IDs per request, and fill the property cache for all of them
+    async fn fetch_entities_properties(
+        wikidata_ids: Vec<String>,
+        set_property_labels: WriteSignal<HashMap<String, String>>,
+        property_cache: ReadSignal<HashMap<String, EntityClaims>>,
+        set_property_cache: WriteSignal<HashMap<String, EntityClaims>>,
+        property_labels: ReadSignal<HashMap<String, String>>,
+    ) -> HashMap<String, EntityClaims> {
+        // Check cache first
+        let cache = property_cache.get_untracked();
+        let mut result: HashMap<String, EntityClaims> = HashMap::new();
+        let mut missing: Vec<String> = Vec::new();
+        for wikidata_id in wikidata_ids {
+            if let Some(cached) = cache.get(&wikidata_id) {
+                result.insert(wikidata_id, cached.clone());
+            } else if !missing.contains(&wikidata_id) {
+                missing.push(wikidata_id);
             }
+        }
+        if missing.is_empty() {
+            return result;
+        }
 
-            let url = format!(
-                "https://www.wikidata.org/w/api.php?action=wbsearchentities&search={}&language=en&limit=5&format=json&origin=*",
-                query
-            );
-
-            match gloo_net::http::Request::get(&url).send().await {
-                Ok(response) => {
-                    if let Ok(data) = response.json::<WikidataResponse>().await {
-                        set_wikidata_suggestions.update(|suggestions| {
-                            suggestions.insert(key, data.search);
-                        });
-                    }
-                }
-                Err(_) => log!("Failed to fetch Wikidata suggestions"),
-            }
+        // Then the server, which fetches the entities it hasn't cached. Those it can't get,
+        // e.g. with its Wikidata budget used up, are fetched from Wikidata directly
+        let cached: HashMap<String, EntityClaims> = fetch_cached_wikidata("claims", &missing).await;
+        missing.retain(|wikidata_id| !cached.contains_key(wikidata_id));
+        let cached_properties: Vec<String> = cached.values().flat_map(|claims| claims.values.keys()).cloned().collect();
+        set_property_cache.update(|cache| {
+            cache.extend(cached.clone());
         });
-    };
+        result.extend(cached);
+        let fetched = fetch_wikidata_claims(missing, &cached_properties, set_property_labels, set_property_cache, property_labels).await;
+        result.extend(fetched);
+        result
+    }
 
-    //function to fetch properties
-    async fn fetch_item_properties(
-        wikidata_id: &str,
+    // Fetch the claims of entities from Wikidata itself, storing them in the property cache.
+    // `known_properties` also get their labels fetched when missing
+    async fn fetch_wikidata_claims(
+        missing: Vec<String>,
+        known_properties: &[String],
         set_property_labels: WriteSignal<HashMap<String, String>>,
-        property_cache: ReadSignal<HashMap<String, HashMap<String, String>>>,
-        set_property_cache: WriteSignal<HashMap<String, HashMap<String, String>>>,
+        set_property_cache: WriteSignal<HashMap<String, EntityClaims>>,
         property_labels: ReadSignal<HashMap<String, String>>,
-    ) -> HashMap<String, String> {
+    ) -> HashMap<String, EntityClaims> {
+        let mut claims_by_entity: HashMap<String, serde_json::Value> = HashMap::new();
+        for ids in missing.chunks(WBGETENTITIES_MAX_IDS) {
+            let data = fetch_wbgetentities(ids, "claims").await;
+            if let Some(entities) = data["entities"].as_object() {
+                for (id, entity) in entities {
+                    claims_by_entity.insert(id.clone(), entity["claims"].clone());
+                }
+            }
+        }
 
-        // Check cache first
-        if let Some(cached) = property_cache.get().get(wikidata_id) {
-            return cached.clone();
+        // Pick one value per property, preferring statements with the preferred rank
+        let mut values_by_entity: HashMap<String, HashMap<String, serde_json::Value>> = HashMap::new();
+        let mut images_by_entity: HashMap<String, HashSet<String>> = HashMap::new();
+        let mut referenced_ids: Vec<String> = Vec::new();
+        for (wikidata_id, claims) in &claims_by_entity {
+            let (values, images) = claim_values(claims);
+            referenced_ids.extend(values.values().flat_map(referenced_entity_ids));
+            if !images.is_empty() {
+                images_by_entity.insert(wikidata_id.clone(), images);
+            }
+            values_by_entity.insert(wikidata_id.clone(), values);
         }
 
-        let sparql_query = format!(
-            r#"
-            SELECT ?prop ?propLabel ?value ?valueLabel WHERE {{
-              wd:{} ?prop ?statement.
-              ?statement ?ps ?value.
-              ?property wikibase:claim ?prop.
-              ?property wikibase:statementProperty ?ps.
-              SERVICE wikibase:label {{ 
-                bd:serviceParam wikibase:language "en". 
-                ?prop rdfs:label ?propLabel.
-                ?value rdfs:label ?valueLabel.
-              }}
-            }}
-            "#,
-            wikidata_id
-        );
-    
-        let url = format!(
-            "https://query.wikidata.org/sparql?query={}&format=json",
-            urlencoding::encode(&sparql_query)
-        );
-    
-        match gloo_net::http::Request::get(&url)
-            .header("Accept", "application/json")
-            .send()
-            .await
-        {
+        // Resolve the labels of entities used as values and units in one batched lookup
+        referenced_ids.sort();
+        referenced_ids.dedup();
+        let mut value_labels: HashMap<String, String> = fetch_cached_wikidata("labels", &referenced_ids).await;
+        referenced_ids.retain(|id| !value_labels.contains_key(id));
+        let mut new_value_labels: HashMap<String, String> = HashMap::new();
+        for ids in referenced_ids.chunks(WBGETENTITIES_MAX_IDS) {
+            new_value_labels.extend(entity_labels(&fetch_wbgetentities(ids, "labels").await));
+        }
+        value_labels.extend(new_value_labels);
+
+        // Batch fetch missing property labels
+        let existing_labels = property_labels.get_untracked();
+        let mut missing_labels: Vec<String> = values_by_entity
+            .values()
+            .flat_map(|values| values.keys())
+            .chain(known_properties.iter())
+            .filter(|id| !existing_labels.contains_key(*id))
+            .cloned()
+            .collect();
+        missing_labels.sort();
+        missing_labels.dedup();
+        if !missing_labels.is_empty() {
+            let new_labels = fetch_property_labels(missing_labels).await;
+            set_property_labels.update(|labels| {
+                labels.extend(new_labels);
+            });
+        }
+
+        // Build results and update cache
+        let fetched: HashMap<String, EntityClaims> = values_by_entity
+            .into_iter()
+            .map(|(wikidata_id, values)| {
+                let images = images_by_entity.remove(&wikidata_id).unwrap_or_default();
+                let claims = entity_claims(values, images, &value_labels);
+                (wikidata_id, claims)
+            })
+            .collect();
+        set_property_cache.update(|cache| {
+            cache.extend(fetched.clone());
+        });
+        fetched
+    }
+
+    // Request entity data from the Wikidata API, returning null on failure
+    async fn fetch_wbgetentities(ids: &[String], props: &str) -> serde_json::Value {
+        match gloo_net::http::Request::get(&wikidata_request_url(&entities_url(ids, props))).send().await {
+            Ok(response) if response.status() == 200 => response.json().await.unwrap_or_else(|e| {
+                log!("Error parsing wbgetentities response: {:?}", e);
+                serde_json::Value::Null
+            }),
             Ok(response) => {
-                if let Ok(data) = response.json::<serde_json::Value>().await {
-                    let mut result = HashMap::new();
-                    let mut prop_ids = Vec::new();
-    
-                    // First pass: collect unique property IDs
-                    if let Some(bindings) = data["results"]["bindings"].as_array() {
-                        for binding in bindings {
-                            if let Some(prop) = binding["propLabel"]["value"].as_str() {
-                                let prop_id = prop.replace("http://www.wikidata.org/prop/", "");
-                                if !prop_ids.contains(&prop_id) {
-                                    prop_ids.push(prop_id.clone());
-                                }
-                            }
-                        }
-                    }
-    
-                    // Batch fetch missing labels
-                    let existing_labels = property_labels.get();
-                    let missing_ids: Vec<String> = prop_ids
-                        .iter()
-                        .filter(|id| !existing_labels.contains_key(*id))
-                        .cloned()
-                        .collect();
-    
-                    if !missing_ids.is_empty() {
-                        let new_labels = fetch_property_labels(missing_ids).await;
-                        set_property_labels.update(|labels| {
-                            labels.extend(new_labels.clone());
-                        });
-                    }
-    
-                    // Second pass: build results
-                    if let Some(bindings) = data["results"]["bindings"].as_array() {
-                        for binding in bindings {
-                            let prop_label = binding["propLabel"]["value"].as_str().unwrap_or_default();
-                            let value = binding["valueLabel"]["value"]
-                                .as_str()
-                                .or_else(|| binding["value"]["value"].as_str())
-                                .unwrap_or_default();
-                            
-                            if let Some(prop_uri) = binding["prop"]["value"].as_str() {
-                                let prop_id = prop_uri.split('/').last().unwrap_or_default().to_string();
-                                result.insert(
-                                    prop_id.clone(),
-                                    value.to_string()
-                                );
-                                
-                                // Update labels if missing
-                                set_property_labels.update(|labels| {
-                                    labels.entry(prop_id.clone())
-                                        .or_insert(prop_label.to_string());
-                                });
-                            }
-                        }
-                    }
-    
-                    // Update cache
-                    set_property_cache.update(|cache| {
-                        cache.insert(wikidata_id.to_string(), result.clone());
-                    });
-    
-                    result
-                } else {
-                    HashMap::new()
-                }
+                log!("Error: Unexpected status code {} from wbgetentities", response.status());
+                serde_json::Value::Null
+            }
+            Err(e) => {
+                log!("Error fetching entities from Wikidata: {:?}", e);
+                serde_json::Value::Null
             }
-            Err(_) => HashMap::new(),
         }
     }
     
+    // Fetch property labels through the server when possible
     async fn fetch_property_labels(property_ids: Vec<String>) -> HashMap<String, String> {
+        let mut property_ids: Vec<String> = property_ids.iter().map(|id| canonical_property(id)).collect();
+
+        let mut labels: HashMap<String, String> = fetch_cached_wikidata("labels", &property_ids).await;
+        property_ids.retain(|id| !labels.contains_key(id));
+        if !property_ids.is_empty() {
+            labels.extend(query_property_labels(property_ids).await);
+        }
+        labels
+    }
+
+    async fn query_property_labels(property_ids: Vec<String>) -> HashMap<String, String> {
         log!("Fetching property labels for properties: {:?}", property_ids);
         
-        // Remove the "http://www.wikidata.org/prop/" prefix from property IDs
-        let property_ids: Vec<String> = property_ids
-            .into_iter()
-            .map(|id| id.replace("http://www.wikidata.org/prop/", ""))
-            .collect();
-        
-        let property_ids_str = property_ids.join(" wd:");
-        let sparql_query = format!(
-            r#"
-            SELECT ?prop ?propLabel WHERE {{
-              VALUES ?prop {{ wd:{} }}
-              SERVICE wikibase:label {{ bd:serviceParam wikibase:language "en". }}
-            }}
-            "#,
-            property_ids_str
-        );
-    
-        let url = format!(
-            "https://query.wikidata.org/sparql?query={}&format=json",
-            urlencoding::encode(&sparql_query)
-        );
+        let url = wikidata_request_url(&property_labels_url(&property_ids));
         log!("Sending request to URL: {}", url);
     
         match gloo_net::http::Request::get(&url)
@@ -537,25 +1928,7 @@ pub fn ItemsList(
                         log!("Response body: {}", text);
                         match serde_json::from_str::<serde_json::Value>(&text) {
                             Ok(data) => {
-                                log!("Successfully parsed response from Wikidata");
-                                let mut result = HashMap::new();
-                                if let Some(bindings) = data["results"]["bindings"].as_array() {
-                                    log!("Found {} bindings in response", bindings.len());
-                                    for (i, binding) in bindings.iter().enumerate() {
-                                        if let (Some(prop), Some(label)) = (
-                                            binding["prop"]["value"].as_str(),
-                                            binding["propLabel"]["value"].as_str()
-                                        ) {
-                                            let prop_id = prop.split('/').last().unwrap_or("").to_string();
-                                            result.insert(prop_id.clone(), label.to_string());
-                                            log!("Processed binding {}: prop_id = {}, label = {}", i, prop_id, label);
-                                        } else {
-                                            log!("Warning: Binding {} is missing prop or propLabel", i);
-                                        }
-                                    }
-                                } else {
-                                    log!("Warning: No bindings found in the response");
-                                }
+                                let result = parse_property_labels(&data);
                                 log!("Fetched {} property labels", result.len());
                                 result
                             }
@@ -581,20 +1954,15 @@ pub fn ItemsList(
     // Add a new custom property
     let add_property = {
         let current_url = Rc::clone(&current_url);
-        let set_items = set_items.clone();
-        let set_property_labels = set_property_labels.clone();
-        let property_cache = property_cache.clone();
-        let set_property_cache = set_property_cache.clone();
-        Arc::new(move |property: String| {
-        // Normalize the property ID
-        let normalized_property = property.replace("http://www.wikidata.org/prop/", "");
+        let persist_item = Rc::clone(&persist_item);
+        Rc::new(move |property: String| {
+        let normalized_property = canonical_property(&property);
         let normalized_property_clone = normalized_property.clone();
 
         // Check if label already exists
         if !property_labels.get().contains_key(&normalized_property) {
             spawn_local({
                 let normalized_property = normalized_property.clone();
-                let set_property_labels = set_property_labels.clone();
                 async move {
                     let labels = fetch_property_labels(vec![normalized_property.clone()]).await;
                     set_property_labels.update(|map| {
@@ -611,30 +1979,93 @@ pub fn ItemsList(
                 selected.insert(normalized_property.clone(), true);
             });
 
-            // Save the selected property to the database
+            // Select the property on the server, which fills in the values it has cached
+            // for the items' Wikidata entities and returns the column
             spawn_local({
                 let current_url = Rc::clone(&current_url);
+                let persist_item = Rc::clone(&persist_item);
                 let normalized_property = normalized_property_clone.clone();
                 async move {
-                    let response = gloo_net::http::Request::post(
-                        &format!("/api/urls/{}/properties", encode(&current_url))
-                    )
-                    .json(&normalized_property)
-                    .unwrap()
-                    .send()
-                    .await;
-                    
-                    match response {
-                        Ok(resp) => {
-                            if resp.status() == 200 {
-                                log!("Property saved successfully");
-                            } else {
-                                log!("Error saving property: {}", resp.status_text());
-                            }
+                    // Anything that isn't a Wikidata property is registered as a custom one
+                    let selection = PropertySelection {
+                        custom: !is_property_id(&normalized_property),
+                        property: normalized_property.clone(),
+                    };
+                    let column = match api_client::add_property(&current_url, &selection).await {
+                        Ok(column) => {
+                            remember_edit_key(column.edit_key, &current_url, set_access);
+                            column.value
                         }
                         Err(err) => {
-                            log!("Error saving property: {:?}", err);
+                            show_error.call(format!("Error saving property: {}", err));
+                            return;
+                        }
+                    };
+                    log!("Property saved, {} values filled in by the server", column.backfilled);
+
+                    // Fill empty cells with the server's values, they are already stored
+                    set_items.update(|items| {
+                        for item in items.iter_mut() {
+                            let Some(value) = column.values.get(&item.id) else {
+                                continue;
+                            };
+                            let cell = item.custom_properties.entry(normalized_property.clone()).or_default();
+                            if cell.is_empty() {
+                                *cell = value.clone();
+                                if let Some(quantity) = column.quantities.get(&item.id) {
+                                    item.quantities.insert(normalized_property.clone(), quantity.clone());
+                                }
+                                if let Some(source) = column.sources.get(&item.id) {
+                                    item.sources.insert(normalized_property.clone(), *source);
+                                }
+                            }
+                        }
+                    });
+
+                    // Fetch the entities the server had no claims for, all in one batch
+                    if !is_property_id(&normalized_property) {
+                        return;
+                    }
+                    let wikidata_ids: Vec<String> = items
+                        .get_untracked()
+                        .iter()
+                        .filter(|item| !column.values.contains_key(&item.id))
+                        .filter_map(|item| item.wikidata_id.clone())
+                        .collect();
+                    if wikidata_ids.is_empty() {
+                        return;
+                    }
+                    let properties_by_entity = fetch_entities_properties(
+                        wikidata_ids,
+                        set_property_labels,
+                        property_cache,
+                        set_property_cache,
+                        property_labels
+                    ).await;
+
+                    // Update and save the items that got a value
+                    let mut filled = Vec::new();
+                    set_items.update(|items| {
+                        for item in items.iter_mut() {
+                            if column.values.contains_key(&item.id) {
+                                continue;
+                            }
+                            let Some(claims) = item.wikidata_id.as_ref().and_then(|id| properties_by_entity.get(id)) else {
+                                continue;
+                            };
+                            let Some(value) = claims.values.get(&normalized_property) else {
+                                continue;
+                            };
+                            item.custom_properties.insert(normalized_property.clone(), value.clone());
+                            item.sources.insert(normalized_property.clone(), ValueSource::Wikidata);
+                            if let Some(quantity) = claims.quantities.get(&normalized_property) {
+                                item.quantities.insert(normalized_property.clone(), quantity.clone());
+                            }
+                            filled.push(item.clone());
                         }
+                    });
+                    for item in filled {
+                        persist_item(item);
                     }
                 }
             });
@@ -649,115 +2080,88 @@ pub fn ItemsList(
                     selected.insert(normalized_property.clone(), true);
                 });
 
-                // Ensure the grid updates reactively
-                set_items.update(|items| {
-                    for item in items {
-                        item.custom_properties.entry(normalized_property.clone()).or_insert_with(|| "".to_string());
-                        
-                        // Save the updated item to the database
-                        let item_clone = item.clone();
-                        spawn_local({
-                            let current_url = Rc::clone(&current_url);
-                            async move {
-                                save_item_to_db(item_clone, selected_properties, current_url.to_string()).await;
-                            }
-                        });
-                    }
-                });
-
                 // Use the property label from the property_labels signal
                 let property_label = property_labels.get().get(&normalized_property).cloned().unwrap_or_else(|| normalized_property.clone());
                 log!("Added property with label: {}", property_label);
 
             }
         });
-        // Fetch the relevant value for each item and populate the corresponding cells
+        // Show the new column right away, with empty cells until values arrive
         set_items.update(|items| {
             for item in items {
-                // Initialize property with empty string if it doesn't exist
                 item.custom_properties.entry(normalized_property.clone())
                     .or_insert_with(|| "".to_string());
-
-                // Only fetch properties if Wikidata ID exists
-                if let Some(wikidata_id) = &item.wikidata_id {
-                    let wikidata_id = wikidata_id.clone();
-                    let set_items = set_items.clone();
-                    let set_fetched_properties = set_fetched_properties.clone();
-                    let property_clone = normalized_property.clone();
-                    
-                    spawn_local(async move {
-                        let properties = fetch_item_properties(
-                            &wikidata_id,
-                            set_property_labels.clone(),
-                            property_cache.clone(),
-                            set_property_cache.clone(),
-                            property_labels.clone()
-                        ).await;
-
-                        // Update the specific property for this item
-                        if let Some(value) = properties.get(&property_clone) {
-                            set_items.update(|items| {
-                                if let Some(item) = items.iter_mut()
-                                    .find(|i| i.wikidata_id.as_ref() == Some(&wikidata_id)) 
-                                {
-                                    item.custom_properties.insert(
-                                        property_clone.clone(), 
-                                        value.clone()
-                                    );
-                                }
-                            });
-                        }
-                    });
-                }
             }
         });
     })};
     
     // Update item fields
     let update_item = {
-        let set_items = set_items.clone();
-        let current_url = Rc::clone(&current_url);
-        Arc::new(move |index: usize, field: &str, value: String| {
-        let set_items = set_items.clone();
-        let current_url = Rc::clone(&current_url);
+        let persist_item = Rc::clone(&persist_item);
+        Rc::new(move |index: usize, field: &str, value: String| {
+        let persist_item = Rc::clone(&persist_item);
         set_items.update(move|items| {
             if let Some(item) = items.get_mut(index) {
                 match field {
                     "name" => {
+                        if item.name != value {
+                            item.sources.remove("name");
+                        }
                         item.name = value.clone();
-                        fetch_wikidata_suggestions(format!("name-{}", index), value.clone());
+                        match pasted_entity_id(&value) {
+                            Some(entity_id) => {
+                                item.name = entity_id.clone();
+                                resolve_pasted_entity(index, item.id.clone(), entity_id);
+                            }
+                            None => fetch_wikidata_suggestions(format!("name-{}", index), value.clone()),
+                        }
 
                         // Fetch Wikidata properties if the field is "name" and the item has a valid Wikidata ID
                         if !value.is_empty() {
                             if let Some(wikidata_id) = &item.wikidata_id {
                                 let wikidata_id = wikidata_id.clone();
                                 spawn_local(async move {
-                                    let properties = fetch_item_properties(&wikidata_id, set_property_labels.clone(), property_cache.clone(), set_property_cache.clone(), property_labels.clone()).await;
+                                    let properties = fetch_item_properties(&wikidata_id, set_property_labels, property_cache, set_property_cache, property_labels).await;
                                     log!("Fetched properties for index {}: {:?}", index, properties);
                                 });
                             }
                         }
                     }
                     "description" => {
+                        if item.description != value {
+                            item.sources.remove("description");
+                        }
                         item.description = value.clone();
                     }
+                    "group" => {
+                        let group = value.trim();
+                        item.group = (!group.is_empty()).then(|| group.to_string());
+                    }
+                    "tip_recipient" => {
+                        let recipient = value.trim();
+                        item.tip_recipient = (!recipient.is_empty()).then(|| recipient.to_string());
+                    }
+                    "status" => {
+                        item.status = ItemStatus::from_name(&value);
+                    }
                     _ => {
-                        // Update custom property
-                        item.custom_properties.insert(field.to_string(), value.clone());
+                        // Update custom property. Committing the text a quantity is displayed as,
+                        // possibly in a normalized unit, keeps the quantity; other text replaces it
+                        if !item.quantities.get(field).is_some_and(|quantity| quantity.is_shown_as(&value)) {
+                            if item.custom_properties.get(field) != Some(&value) {
+                                item.sources.remove(field);
+                            }
+                            item.custom_properties.insert(field.to_string(), value.clone());
+                            item.quantities.remove(field);
+                        }
                     }
                 }
 
                 // Save the updated item to the database
-                let item_clone = item.clone();
-                spawn_local({
-                    let current_url = Rc::clone(&current_url);
-                    async move {
-                        save_item_to_db(item_clone, selected_properties, current_url.to_string()).await;
-                    }
-                });
+                persist_item(item.clone());
             }
             // Automatically add a new row when editing the last row
-            if index == items.len() - 1 && !value.is_empty() {
+            if index == items.len() - 1 && !value.is_empty() && field != "group" && field != "tip_recipient" && field != "status" {
                 let new_item = Item {
                     id: Uuid::new_v4().to_string(),
                     name: String::new(),
@@ -765,249 +2169,965 @@ pub fn ItemsList(
                     // reviews: vec![],
                     wikidata_id: None,
                     custom_properties: HashMap::new(),
+                    quantities: HashMap::new(),
+                    group: None,
+                    tip_recipient: None,
+                    status: None,
+                    sources: HashMap::new(),
+                    updated_at: None,
                 };
                 items.push(new_item.clone());
 
                 // Save the new item to the database
-                spawn_local({
-                    let current_url = Rc::clone(&current_url);
-                    async move {
-                        save_item_to_db(new_item, selected_properties, current_url.to_string()).await;
-                    }
-                });
+                persist_item(new_item);
             }
             log!("Items updated: {:?}", items);
         });
     })};
 
+    // Properties commonly used by the classes (P31) of the items with a Wikidata ID
+    let (property_suggestions, set_property_suggestions) = create_signal(Vec::<PropertySuggestion>::new());
+    let wikidata_ids = create_memo(move |_| {
+        let mut ids: Vec<String> = items.get().iter().filter_map(|item| item.wikidata_id.clone()).collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    });
+    create_effect({
+        let current_url = Rc::clone(&current_url);
+        move |_| {
+            let ids = wikidata_ids.get();
+            if ids.is_empty() {
+                set_property_suggestions.set(Vec::new());
+                return;
+            }
+            let current_url = Rc::clone(&current_url);
+            spawn_local(async move {
+                match api_client::property_suggestions(&current_url, &ids).await {
+                    Ok(suggestions) => {
+                        set_property_labels.update(|labels| {
+                            for suggestion in &suggestions {
+                                labels
+                                    .entry(suggestion.property.clone())
+                                    .or_insert_with(|| suggestion.label.clone());
+                            }
+                        });
+                        set_property_suggestions.set(suggestions);
+                    }
+                    Err(err) => log!("Failed to fetch property suggestions: {}", err),
+                }
+            });
+        }
+    });
+    let add_suggested_property = Rc::clone(&add_property);
+
     // List of properties to display as rows
     let properties = vec!["Name", "Description"];
+    let page_url = current_url.to_string();
 
     view! {
         <div>
-            <h1>{ "Items List" }</h1>
-            <table>
-                <thead>
-                    <tr>
-                        <th>{ "Property" }</th>
-                        {move || items.get().iter().enumerate().map(|(index, item)| {
-                            let remove_item = remove_item.clone();
-                            view! {
-                                <th>
-                                    {item.name.clone()}
-                                    <button on:click=move |_| remove_item(index)>{ "Delete" }</button>
-                                </th>
+            // Waits for the loaded metadata, so the server renders the right head and heading
+            <Suspense fallback=move || view! { <h1>{ "Items List" }</h1> }>
+                <ComparisonMeta metadata url=page_url.clone() items=loaded_item_list/>
+                <h1>
+                    {move || metadata.with(|metadata| {
+                        if metadata.title.is_empty() { "Items List".to_string() } else { metadata.title.clone() }
+                    })}
+                </h1>
+                {move || metadata.with(|metadata| {
+                    (!metadata.description.is_empty()).then(|| view! {
+                        <p class="comparison-description">{metadata.description.clone()}</p>
+                    })
+                })}
+                <DecisionBanner metadata item_name=decided_item_name/>
+            </Suspense>
+            <div class="save-controls">
+                <label class:read-only-hidden=read_only>
+                    <input
+                        type="checkbox"
+                        prop:checked=move || autosave.get()
+                        on:change=move |event| set_save_mode(event_target_checked(&event))
+                    />
+                    { "Autosave" }
+                </label>
+                <span class="view-toggles" role="group" aria-label="View">
+                    <button class="view-toggle" class:active=move || !chart_view.get() on:click=move |_| set_chart_view.set(false)>{ "Table" }</button>
+                    <button class="view-toggle" class:active=move || chart_view.get() on:click=move |_| set_chart_view.set(true)>{ "Chart" }</button>
+                </span>
+                <label title="Show the quantities of each property in the unit most items use">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || normalize_units.get()
+                        on:change=move |event| toggle_normalize_units(event_target_checked(&event))
+                    />
+                    { "Normalize units" }
+                </label>
+                <button
+                    class="save-button"
+                    class:read-only-hidden=read_only
+                    style:display=move || if autosave.get() { "none" } else { "inline-block" }
+                    disabled=move || dirty_items.get().is_empty()
+                    on:click=move |_| save_changes()
+                >
+                    { "Save" }
+                </button>
+                {move || (!dirty_items.get().is_empty()).then(|| view! {
+                    <span class="unsaved-indicator">
+                        { format!("{} unsaved item(s)", dirty_items.get().len()) }
+                    </span>
+                })}
+                {move || {
+                    let unsaved: usize = other_tabs_unsaved.with(|tabs| tabs.values().sum());
+                    (unsaved > 0).then(|| view! {
+                        <span class="unsaved-indicator">
+                            { format!("{} unsaved item(s) in another tab", unsaved) }
+                        </span>
+                    })
+                }}
+                {move || (other_editors.get() > 0).then(|| view! {
+                    <span class="presence-indicator">
+                        { format!("{} other editor(s) on this page", other_editors.get()) }
+                    </span>
+                })}
+                <span class:read-only-hidden=read_only>
+                    {optional_tools}
+                    <RelinkDialog items language=Signal::derive(move || instance_info().wikidata_language) on_link=link_to_wikidata/>
+                    <button
+                        class="import-button"
+                        title="Update the names, descriptions and values that came from Wikidata, values typed in stay"
+                        disabled=move || refreshing.get()
+                        on:click=refresh_from_wikidata
+                    >
+                        { "Refresh all Wikidata data" }
+                    </button>
+                    <ComparisonSettings current_url=current_url.to_string() metadata set_saved_metadata set_access show_error/>
+                    <DecisionDialog current_url=current_url.to_string() metadata items set_saved_metadata show_error/>
+                    <RecentlyDeleted current_url=current_url.to_string() on_restore=restore_item show_error/>
+                    <ItemHistory current_url=current_url.to_string() on_restore=restore_version show_error/>
+                </span>
+                <EditAccessControls current_url=current_url.to_string() access set_access show_error/>
+                <span class:turned-off=move || !instance_info().features.snapshots class:read-only-hidden=read_only>
+                    <ShareSnapshot current_url=current_url.to_string() show_error/>
+                </span>
+                <ComparisonTransfer current_url=current_url.to_string() empty=Signal::derive(move || items.with(Vec::is_empty)) set_access show_error/>
+            </div>
+            {
+                let persist_item = Rc::clone(&persist_item);
+                move || reuse_offer.get().map(|(item_id, reusable)| {
+                    let persist_item = Rc::clone(&persist_item);
+                    let wikidata_id = reusable.wikidata_id.clone();
+                    let use_values = move |_| {
+                        let Some((item_id, reusable)) = reuse_offer.get_untracked() else {
+                            return;
+                        };
+                        set_reuse_offer.set(None);
+                        let mut saved = None;
+                        set_items.update(|items| {
+                            if let Some(item) = items.iter_mut().find(|item| item.id == item_id) {
+                                item.custom_properties.extend(reusable.values);
+                                item.quantities.extend(reusable.quantities);
+                                saved = Some(item.clone());
                             }
-                        }).collect::<Vec<_>>()} 
-                    </tr>
-                </thead>
-                <tbody>
-                    {properties.into_iter().map(|property| {
-                        let update_item_cloned = Arc::clone(&update_item);
-                        log!("Rendering property: {}", property);
-                        view! {
-                            <tr>
-                                <td>{ property }</td>
-                                {move || items.get().iter().enumerate().map(|(index, item)| {
-                                    let update_item_clone = Arc::clone(&update_item_cloned);
-                                        view! {
-                                            <td>
-                                            {match property {
-                                                "Name" => view! {
-                                                    <div class="editable-cell">
-                                                        <EditableCell
-                                                            value=item.name.clone()
-                                                            on_input=move |value| {
-                                                                update_item_clone(index, "name", value.clone());
-                                                                fetch_wikidata_suggestions(format!("name-{}", index), value);
+                        });
+                        if let Some(item) = saved {
+                            persist_item(item);
+                        }
+                    };
+                    let fetch_wikidata = {
+                        let item_id = item_id.clone();
+                        move |_| {
+                            set_reuse_offer.set(None);
+                            fill_from_wikidata(item_id.clone(), wikidata_id.clone());
+                        }
+                    };
+                    let name = items.with_untracked(|items| {
+                        items.iter().find(|item| item.id == item_id).map(|item| item.name.clone()).unwrap_or_default()
+                    });
+                    view! {
+                        <div class="import-dialog-backdrop">
+                            <div class="import-dialog" role="dialog" aria-label="Reuse values">
+                                <h2>{ format!("{} is already compared", name) }</h2>
+                                <p>
+                                    {format!(
+                                        "{} other {} on this instance {} values for {} of its properties, including corrections made by their editors.",
+                                        reusable.comparisons,
+                                        if reusable.comparisons == 1 { "comparison" } else { "comparisons" },
+                                        if reusable.comparisons == 1 { "has" } else { "have" },
+                                        reusable.values.len()
+                                    )}
+                                </p>
+                                <div class="import-dialog-actions">
+                                    <button on:click=fetch_wikidata>{ "Fetch from Wikidata" }</button>
+                                    <button class="save-button" on:click=use_values>{ "Use their values" }</button>
+                                </div>
+                            </div>
+                        </div>
+                    }
+                })
+            }
+            {move || refresh_report.get().map(|refresh| view! {
+                <RefreshReport
+                    refresh
+                    property_label=Callback::new(move |property: String| property_label(&property))
+                    on_close=Callback::new(move |_| set_refresh_report.set(None))
+                />
+            })}
+            {move || {
+                conflicts.get().into_iter().find_map(|theirs| {
+                    let mine = saves.latest(&theirs.id)?;
+                    let item_id = theirs.id.clone();
+                    let keep_id = item_id.clone();
+                    Some(view! {
+                        <ConflictDialog
+                            mine
+                            theirs
+                            property_labels
+                            on_keep_mine=Callback::new(move |_| keep_mine.call(keep_id.clone()))
+                            on_use_theirs=Callback::new(move |_| use_theirs.call(item_id.clone()))
+                        />
+                    })
+                })
+            }}
+            <Show when=move || tip_target.get().is_some()>
+                <div class="import-dialog-backdrop">
+                    <div class="import-dialog" role="dialog" aria-label="Tip the creator">
+                        <h2>{move || format!("Tip the creator of {}", tip_target.get().map(|(_, name)| name).unwrap_or_default())}</h2>
+                        {move || match tip_invoice.get() {
+                            Some(invoice) => view! {
+                                <div class="tip-invoice">
+                                    <p>
+                                        { format!("Invoice for {}", invoice.lightning_address) }
+                                        {invoice.zap.then_some(" (zap)")}
+                                    </p>
+                                    <a class="save-button" href=format!("lightning:{}", invoice.invoice)>{ "Open in wallet" }</a>
+                                    <textarea rows="5" readonly=true prop:value=invoice.invoice.clone() />
+                                </div>
+                            }.into_view(),
+                            None => view! {
+                                <label>
+                                    { "Amount (sats)" }
+                                    <input
+                                        type="number"
+                                        min="1"
+                                        prop:value=move || tip_amount.get()
+                                        on:input=move |event| set_tip_amount.set(event_target_value(&event))
+                                    />
+                                </label>
+                                <label>
+                                    { "Message" }
+                                    <input
+                                        placeholder="Thanks for the great product!"
+                                        prop:value=move || tip_comment.get()
+                                        on:input=move |event| set_tip_comment.set(event_target_value(&event))
+                                    />
+                                </label>
+                            }.into_view(),
+                        }}
+                        <div class="import-dialog-actions">
+                            <button on:click=move |_| close_tip_dialog()>{ "Close" }</button>
+                            <Show when=move || tip_invoice.get().is_none()>
+                                <button
+                                    class="save-button"
+                                    disabled=move || requesting_tip.get()
+                                    on:click=move |_| request_tip.call(())
+                                >
+                                    {move || if requesting_tip.get() { "Requesting..." } else { "Create invoice" }}
+                                </button>
+                            </Show>
+                        </div>
+                    </div>
+                </div>
+            </Show>
+            <div class="item-pager">
+                <input
+                    type="search"
+                    class="table-filter"
+                    placeholder="Filter items and properties"
+                    prop:value=move || table_filter.get()
+                    on:input=move |event| {
+                        set_table_filter.set(event_target_value(&event));
+                        set_item_page.set(0);
+                    }
+                />
+                {move || (!filter_text.get().is_empty()).then(|| {
+                    let (items_matched, properties_matched) = (matching_items.get(), matching_properties.get());
+                    let counts = format!(
+                        "{} of {} items, {} of {} properties match",
+                        items_matched,
+                        items.with(Vec::len),
+                        properties_matched,
+                        custom_properties.with(Vec::len)
+                    );
+                    view! { <span class="filter-count" class:no-matches=items_matched == 0 && properties_matched == 0>{counts}</span> }
+                })}
+                <Show when=move || { items_per_page.get() > 0 }>
+                    <button disabled=move || item_page.get() == 0 on:click=move |_| set_item_page.update(|page| *page = page.saturating_sub(1))>
+                        { "‹ Previous" }
+                    </button>
+                    <span>
+                        {move || {
+                            let visible = visible_items.get();
+                            let total = shown_items.with(Vec::len);
+                            if visible.is_empty() {
+                                "No items".to_string()
+                            } else {
+                                format!("Showing items {}–{} of {}", visible.start + 1, visible.end, total)
+                            }
+                        }}
+                    </span>
+                    <button disabled=move || item_page.get() + 1 >= item_pages.get() on:click=move |_| set_item_page.update(|page| *page += 1)>
+                        { "Next ›" }
+                    </button>
+                </Show>
+                <label>
+                    { "Items per page " }
+                    <select on:change=move |event| change_items_per_page(event_target_value(&event).parse().unwrap_or(0))>
+                        {ITEMS_PER_PAGE_OPTIONS.into_iter().map(|per_page| view! {
+                            <option value=per_page.to_string() prop:selected=move || items_per_page.get() == per_page>
+                                {if per_page == 0 { "All".to_string() } else { per_page.to_string() }}
+                            </option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label>
+                    { "Status " }
+                    <select on:change=move |event| {
+                        set_status_filter.set(event_target_value(&event));
+                        set_item_page.set(0);
+                    }>
+                        <option value="">{ "All items" }</option>
+                        <option value="none">{ "No status" }</option>
+                        {ItemStatus::ALL.into_iter().map(|status| view! {
+                            <option value=status.as_str()>{status.label()}</option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                </label>
+            </div>
+            // A skeleton of the table until the items are loaded, instead of an empty table
+            <Suspense fallback=move || view! { <TableSkeleton /> }>
+                {
+                    let remove_item = remove_item.clone();
+                    let remove_property = remove_property.clone();
+                    let move_property = move_property.clone();
+                    let rename_property = rename_property.clone();
+                    let update_item = Rc::clone(&update_item);
+                    let properties = properties.clone();
+                    view! {
+                        {move || load_error.get().map(|err| view! {
+                            <LoadError message=format!("Failed to load the items: {}", err) on_retry=retry_load/>
+                        })}
+                        <Show when=move || chart_view.get()>
+                            <ChartPanel items=chart_items properties=chart_properties />
+                        </Show>
+                        <table class:load-error-hidden=move || load_error.with(Option::is_some) class:chart-view-hidden=move || chart_view.get()>
+                            <thead>
+                                // Shared headers for grouped variant columns
+                                {move || {
+                                    record_render("ItemsList group header");
+                                    let groups = page_groups.get();
+                                    groups.iter().any(Option::is_some).then(|| view! {
+                                        <tr class="group-header">
+                                            <th></th>
+                                            {group_spans(&groups).into_iter().map(|(group, span)| view! {
+                                                <th colspan=span class:item-group=group.is_some()>
+                                                    {group.unwrap_or_default()}
+                                                </th>
+                                            }).collect::<Vec<_>>()}
+                                        </tr>
+                                    })
+                                }}
+                                <tr>
+                                    <th>{ "Property" }</th>
+                                    <For
+                                        each=move || page_columns.get()
+                                        key=|column| column.clone()
+                                        children={
+                                            let update_item = Rc::clone(&update_item);
+                                            let remove_item = remove_item.clone();
+                                            move |(index, item_id): (usize, String)| {
+                                                record_render("ItemsList item header");
+                                                let remove_item = remove_item.clone();
+                                                let update_group = Rc::clone(&update_item);
+                                                let update_recipient = Rc::clone(&update_item);
+                                                let update_status = Rc::clone(&update_item);
+                                                let name = item_field(items, index, |item| item.name.clone());
+                                                let status = item_field(items, index, |item| item.status);
+                                                let status = move || status.get().flatten();
+                                                let group = item_field(items, index, |item| item.group.clone());
+                                                let tip_recipient = item_field(items, index, |item| item.tip_recipient.clone());
+                                                let rated_id = item_id.clone();
+                                                let tipped_id = item_id.clone();
+                                                view! {
+                                                    <th id=format!("item-{}", item_id) class=move || status().map(|status| format!("item-status-{}", status.as_str()))>
+                                                        {move || name.get().unwrap_or_default()}
+                                                        <button class:read-only-hidden=read_only on:click=move |_| remove_item(index)>{ "Delete" }</button>
+                                                        <select
+                                                            class="item-status-select"
+                                                            title="Where this item stands in the decision"
+                                                            disabled=read_only
+                                                            on:change=move |event| update_status(index, "status", event_target_value(&event))
+                                                        >
+                                                            <option value="" prop:selected=move || status().is_none()>{ "No status" }</option>
+                                                            {ItemStatus::ALL.into_iter().map(|choice| view! {
+                                                                <option value=choice.as_str() prop:selected=move || status() == Some(choice)>{choice.label()}</option>
+                                                            }).collect::<Vec<_>>()}
+                                                        </select>
+                                                        <div class="item-rating" class:turned-off=move || !instance_info().features.reviews>
+                                                            {
+                                                                let item_id = item_id.clone();
+                                                                move || ratings.with(|ratings| match ratings.get(&item_id) {
+                                                                    Some(summary) if summary.count > 0 => {
+                                                                        format!("★ {:.1} ({})", summary.average, summary.count)
+                                                                    }
+                                                                    _ => "No ratings".to_string(),
+                                                                })
                                                             }
-                                                            key=Arc::new(format!("name-{}", index))
-                                                            focused_cell=focused_cell
-                                                            set_focused_cell=set_focused_cell.clone()
-                                                            on_focus=Some(Callback::new(move |_| {
-                                                                log!("Input focused, showing suggestions");
-                                                                set_show_suggestions.update(|suggestions| {
-                                                                    suggestions.insert(format!("name-{}", index), true);
-                                                                });
-                                                            }))
-                                                            on_blur=Some(Callback::new(move |_| {
-                                                                log!("Input blurred, delaying hiding suggestions");
-                                                                spawn_local(async move {
-                                                                    gloo_timers::future::sleep(std::time::Duration::from_millis(500)).await;
-                                                                    log!("Hiding suggestions after delay");
-                                                                    set_show_suggestions.update(|suggestions| {
-                                                                        suggestions.insert(format!("name-{}", index), false);
+                                                            <span class="rating-stars">
+                                                                {(MIN_RATING..=MAX_RATING).map(|stars| {
+                                                                    let item_id = rated_id.clone();
+                                                                    let rated_id = rated_id.clone();
+                                                                    let mine = move || ratings.with(|ratings| {
+                                                                        ratings.get(&rated_id).and_then(|summary| summary.mine)
                                                                     });
-                                                                });
-                                                            }))
-                                                            input_type=InputType::Text
+                                                                    view! {
+                                                                        <button
+                                                                            class="rating-star"
+                                                                            class:rated=move || mine().is_some_and(|rating| rating >= stars)
+                                                                            title=format!("Rate {} of {} stars", stars, MAX_RATING)
+                                                                            on:click=move |_| rate_item.call((item_id.clone(), stars))
+                                                                        >
+                                                                            { "★" }
+                                                                        </button>
+                                                                    }
+                                                                }).collect::<Vec<_>>()}
+                                                            </span>
+                                                        </div>
+                                                        // The server looks up the recipient, so unsaved changes can't be tipped yet
+                                                        {move || tip_recipient.get().flatten().is_some().then(|| {
+                                                            let (item_id, tipped_id) = (tipped_id.clone(), tipped_id.clone());
+                                                            view! {
+                                                                <button
+                                                                    class="tip-button"
+                                                                    class:turned-off=move || !instance_info().features.tips
+                                                                    disabled=move || dirty_items.get().contains(&item_id)
+                                                                    title="Tip the creator with Lightning"
+                                                                    on:click=move |_| set_tip_target.set(Some((tipped_id.clone(), name.get_untracked().unwrap_or_default())))
+                                                                >
+                                                                    { "⚡ Tip" }
+                                                                </button>
+                                                            }
+                                                        })}
+                                                        <input
+                                                            class="group-input"
+                                                            class:read-only-hidden=read_only
+                                                            placeholder="Group"
+                                                            title="Columns with the same group share a header"
+                                                            prop:value=move || group.get().flatten().unwrap_or_default()
+                                                            on:change=move |event| update_group(index, "group", event_target_value(&event))
                                                         />
-                                                        <button class="search-icon" on:click=move |_| {
-                                                            log!("Search icon clicked, showing suggestions");
-                                                            set_show_suggestions.update(|suggestions| {
-                                                                suggestions.insert(format!("name-{}", index), true);
-                                                            });
-                                                        }> 
-                                                            <i class="fas fa-search"></i> Search Wiki
-                                                        </button>
-                                                        {move || {
-                                                            if *show_suggestions.get().get(&format!("name-{}", index)).unwrap_or(&false) {
-                                                                log!("Rendering suggestions list");
+                                                        <input
+                                                            class="tip-recipient-input"
+                                                            class:read-only-hidden=read_only
+                                                            placeholder="Lightning address or npub"
+                                                            title="Creator or vendor to tip, as a lightning address or Nostr public key"
+                                                            prop:value=move || tip_recipient.get().flatten().unwrap_or_default()
+                                                            on:change=move |event| update_recipient(index, "tip_recipient", event_target_value(&event))
+                                                        />
+                                                    </th>
+                                                }
+                                            }
+                                        }
+                                    />
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {properties.into_iter().map(|property| {
+                                    let update_item_cloned = Rc::clone(&update_item);
+                                    log!("Rendering property: {}", property);
+                                    let settings_key = property.to_lowercase();
+                                    view! {
+                                        <tr>
+                                            <td>
+                                                { property }
+                                                <PropertySettingsButton
+                                                    current_url=current_url.to_string()
+                                                    property=settings_key.clone()
+                                                    label=property.to_string()
+                                                    settings=settings_of(settings_key.clone())
+                                                    on_saved=save_property_settings(settings_key)
+                                                    read_only
+                                                    show_error
+                                                />
+                                            </td>
+                                            <For
+                                                each=move || page_columns.get()
+                                                key=|column| column.clone()
+                                                children=move |(index, item_id): (usize, String)| {
+                                                record_render("ItemsList core cell");
+                                                let update_item_clone = Rc::clone(&update_item_cloned);
+                                                let lock_key = lock_key(&item_id, property);
+                                                let is_locked = Signal::derive(move || locked_cells.get().contains(&lock_key));
+                                                    view! {
+                                                        <td class:locked-cell=is_locked title=move || is_locked.get().then_some("Being edited by someone else")>
+                                                        {match property {
+                                                            // The typeahead is only mounted once the cell scrolls into view or is focused
+                                                            "Name" => {
+                                                                let name = item_field(items, index, |item| item.name.clone());
                                                                 view! {
-                                                                        <ul class="editable-cell-suggestions">
-                                                                                {move || {
-                                                                                    let suggestions = wikidata_suggestions.get()
-                                                                                        .get(&format!("name-{}", index))
-                                                                                        .cloned()
-                                                                                        .unwrap_or_default();
-                                                                                    log!("Suggestions for cell {}: {:?}", index, suggestions);
-                                                                                    suggestions.into_iter().map(|suggestion| {
-                                                                                        let label_for_click = suggestion.label.clone();
-                                                                                        let label_for_display = suggestion.label.clone();
-                                                                                        let description_for_click = suggestion.description.clone().unwrap_or_default();
-                                                                                        let description_for_display = suggestion.description.clone().unwrap_or_default();
-                                                                                        let id = suggestion.id.clone();                                                                                    
-                                                                                view! {
-                                                                                    <li class="editable-cell-suggestions-li" on:click=move |_| {
-                                                                                        // Update item with basic suggestion details
-                                                                                        set_items.update(|items| {
-                                                                                            if let Some(item) = items.get_mut(index) {
-                                                                                                item.description = description_for_click.clone();
-                                                                                                item.wikidata_id = Some(id.clone());
-                                                                                                item.name = label_for_click.clone();
-                                                                                            }
-                                                                                        });
-
-                                                                                        // Fetch additional properties from Wikidata
-                                                                                        let wikidata_id = id.clone();
-                                                                                        spawn_local(async move {
-                                                                                            let properties = fetch_item_properties(&wikidata_id, set_property_labels.clone(), property_cache.clone(), set_property_cache.clone(), property_labels.clone()).await;
-                                                                                            // log!("Fetched properties for Wikidata ID {}: {:?}", wikidata_id, properties);
-                                                                                            
-                                                                                            // Populate the custom properties for the new item
-                                                                                            set_items.update(|items| {
-                                                                                                if let Some(item) = items.iter_mut().find(|item| item.wikidata_id.as_ref() == Some(&wikidata_id)) {
-                                                                                                    for (property, value) in properties {
-                                                                                                        item.custom_properties.insert(property, value);
-                                                                                                    }
-                                                                                                }
+                                                                    <LazyMount
+                                                                        placeholder=move || view! {
+                                                                            <div class="editable-cell">
+                                                                                <input
+                                                                                    type="text"
+                                                                                    class="editable-cell-input"
+                                                                                    prop:value=move || name.get().unwrap_or_default()
+                                                                                    readonly=move || is_locked.get() || read_only.get()
+                                                                                />
+                                                                            </div>
+                                                                        }
+                                                                        on_activate=Callback::new(move |_| set_focused_cell.set(Some(format!("name-{}", index))))
+                                                                    >
+                                                                        {
+                                                                            let update_item_clone = Rc::clone(&update_item_clone);
+                                                                            view! {
+                                                                                <div class="editable-cell">
+                                                                                    {move || {
+                                                                                    let update_item_clone = Rc::clone(&update_item_clone);
+                                                                                    view! {
+                                                                                    <EditableCell
+                                                                                        value=name.get().unwrap_or_default()
+                                                                                        on_input=move |value| {
+                                                                                            update_item_clone(index, "name", value.clone());
+                                                                                            fetch_wikidata_suggestions(format!("name-{}", index), value);
+                                                                                        }
+                                                                                        key=Arc::new(format!("name-{}", index))
+                                                                                        lang=cell_lang("name")
+                                                                                        spellcheck=cell_spellcheck("name")
+                                                                                        focused_cell=focused_cell
+                                                                                        set_focused_cell=set_focused_cell
+                                                                                        on_focus=Some(Callback::new(move |_| {
+                                                                                            log!("Input focused, showing suggestions");
+                                                                                            set_show_suggestions.update(|suggestions| {
+                                                                                                suggestions.insert(format!("name-{}", index), true);
                                                                                             });
-                                                                                        });
-
-                                                                                        // Hide the suggestion list
+                                                                                        }))
+                                                                                        on_blur=Some(Callback::new(move |_| {
+                                                                                            log!("Input blurred, delaying hiding suggestions");
+                                                                                            spawn_local(async move {
+                                                                                                gloo_timers::future::sleep(std::time::Duration::from_millis(500)).await;
+                                                                                                log!("Hiding suggestions after delay");
+                                                                                                set_show_suggestions.update(|suggestions| {
+                                                                                                    suggestions.insert(format!("name-{}", index), false);
+                                                                                                });
+                                                                                            });
+                                                                                        }))
+                                                                                        input_type=InputType::Text
+                                                                                        locked=Signal::derive(move || is_locked.get() || read_only.get())
+                                                                                    />
+                                                                                    }
+                                                                                    }}
+                                                                                    <button class="search-icon" on:click=move |_| {
+                                                                                        log!("Search icon clicked, showing suggestions");
                                                                                         set_show_suggestions.update(|suggestions| {
-                                                                                            suggestions.insert(format!("name-{}", index), false);
-                                                                                            log!("Updated show_suggestions: {:?}", suggestions);
+                                                                                            suggestions.insert(format!("name-{}", index), true);
                                                                                         });
-                                                                                    }>
-                                                                                        { format!("{} - {}", label_for_display, description_for_display) }
-                                                                                    </li>
-                                                                                }
-                                                                                    }).collect::<Vec<_>>()
-                                                                                }}
-                                                                        </ul>
-                                                                }
-                                                            } else {
-                                                                log!("Suggestions list hidden");
-                                                                view! {
-                                                                    <ul></ul>
-                                                                }
+                                                                                    }> 
+                                                                                        <i class="fas fa-search"></i> { move || format!(" Search {}", instance_info().knowledge_source.source().name()) }
+                                                                                    </button>
+                                                                                    {move || {
+                                                                                        if *show_suggestions.get().get(&format!("name-{}", index)).unwrap_or(&false) {
+                                                                                            log!("Rendering suggestions list");
+                                                                                            view! {
+                                                                                                    <ul class="editable-cell-suggestions">
+                                                                                                            {move || {
+                                                                                                                let suggestions = wikidata_suggestions.get()
+                                                                                                                    .get(&format!("name-{}", index))
+                                                                                                                    .cloned()
+                                                                                                                    .unwrap_or_default();
+                                                                                                                log!("Suggestions for cell {}: {:?}", index, suggestions);
+                                                                                                                suggestions.into_iter().map(|suggestion| {
+                                                                                                                    let label_for_click = suggestion.label.clone();
+                                                                                                                    let label_for_display = suggestion.label.clone();
+                                                                                                                    let description_for_click = suggestion.description.clone().unwrap_or_default();
+                                                                                                                    let description_for_display = suggestion.description.clone().unwrap_or_default();
+                                                                                                                    let id = suggestion.id.clone();                                                                                    
+                                                                                                            view! {
+                                                                                                                <li class="editable-cell-suggestions-li" on:click=move |_| {
+                                                                                                                    // Update item with basic suggestion details
+                                                                                                                    let from_wikidata = instance_info().knowledge_source == KnowledgeSourceKind::Wikidata;
+                                                                                                                    set_items.update(|items| {
+                                                                                                                        if let Some(item) = items.get_mut(index) {
+                                                                                                                            item.description = description_for_click.clone();
+                                                                                                                            item.name = label_for_click.clone();
+                                                                                                                            if from_wikidata {
+                                                                                                                                item.wikidata_id = Some(id.clone());
+                                                                                                                                item.sources.insert("name".into(), ValueSource::Wikidata);
+                                                                                                                                item.sources.insert("description".into(), ValueSource::Wikidata);
+                                                                                                                            } else {
+                                                                                                                                item.sources.remove("name");
+                                                                                                                                item.sources.remove("description");
+                                                                                                                            }
+                                                                                                                        }
+                                                                                                                    });
+
+                                                                                                                    // Fill in its properties, from other comparisons or from the knowledge base
+                                                                                                                    if let Some(item_id) = items.with_untracked(|items| items.get(index).map(|item| item.id.clone())) {
+                                                                                                                        if from_wikidata {
+                                                                                                                            fill_picked_item(item_id, id.clone());
+                                                                                                                        } else {
+                                                                                                                            fill_from_knowledge_source(item_id, id.clone());
+                                                                                                                        }
+                                                                                                                    }
+
+                                                                                                                    // Hide the suggestion list
+                                                                                                                    set_show_suggestions.update(|suggestions| {
+                                                                                                                        suggestions.insert(format!("name-{}", index), false);
+                                                                                                                        log!("Updated show_suggestions: {:?}", suggestions);
+                                                                                                                    });
+                                                                                                                }>
+                                                                                                                    { format!("{} - {}", label_for_display, description_for_display) }
+                                                                                                                </li>
+                                                                                                            }
+                                                                                                                }).collect::<Vec<_>>()
+                                                                                                            }}
+                                                                                                    </ul>
+                                                                                            }
+                                                                                        } else {
+                                                                                            log!("Suggestions list hidden");
+                                                                                            view! {
+                                                                                                <ul></ul>
+                                                                                            }
+                                                                                        }
+                                                                                    }}
+                                                                                </div>
+                                                                            }
+                                                                        }
+                                                                    </LazyMount>
+                                                                }.into_view()
+                                                            },
+                                                            "Description" => {
+                                                            let description = item_field(items, index, |item| item.description.clone());
+                                                            (move || {
+                                                            let update_item_clone = Rc::clone(&update_item_clone);
+                                                            view! {
+                                                            <EditableCell
+                                                                value=description.get().unwrap_or_default()
+                                                                on_input=move |value| update_item_clone(index, "description", value)
+                                                                key=Arc::new(format!("description-{}", index))
+                                                                lang=cell_lang("description")
+                                                                spellcheck=cell_spellcheck("description")
+                                                                focused_cell=focused_cell
+                                                                set_focused_cell=set_focused_cell
+                                                                on_focus=Some(Callback::new(move |_| {
+                                                                    log!("Description input focused");
+                                                                }))
+                                                                on_blur=Some(Callback::new(move |_| {
+                                                                    log!("Description input blurred");
+                                                                }))
+                                                                input_type=InputType::Markdown
+                                                                locked=Signal::derive(move || is_locked.get() || read_only.get())
+                                                                max_length=MAX_VALUE_LENGTH
+                                                            />
                                                             }
+                                                            }).into_view()
+                                                            },
+                                                            _ => view! {
+                                                                { "" }
+                                                            }.into_view(),
                                                         }}
-                                                    </div>
-                                                }.into_view(),
-                                                "Description" => view! {
-                                                <EditableCell
-                                                    value=item.description.clone()
-                                                    on_input=move |value| update_item_clone(index, "description", value)
-                                                    key=Arc::new(format!("description-{}", index))
-                                                    focused_cell=focused_cell
-                                                    set_focused_cell=set_focused_cell.clone()
-                                                    on_focus=Some(Callback::new(move |_| {
-                                                        log!("Description input focused");
-                                                    }))
-                                                    on_blur=Some(Callback::new(move |_| {
-                                                        log!("Description input blurred");
-                                                    }))
-                                                    input_type=InputType::TextArea
-                                                />
-                                                }.into_view(),
-                                                _ => view! {
-                                                    { "" }
-                                                }.into_view(),
-                                            }}
-                                            </td>
-                                        }
-                                }).collect::<Vec<_>>()}                                
-                            </tr>
-                        }
-                    }).collect::<Vec<_>>()}
-                    // Dynamically adding custom properties as columns
-                    {{
-                        let update_item_outer = Arc::clone(&update_item);
-
-                        move || {
-                        let update_item = Arc::clone(&update_item_outer);
-                        let custom_props = custom_properties.get().clone();
-                        let remove_property = remove_property.clone();
-                        custom_props.into_iter().map(move |property| {
-                            let remove_property_clone = remove_property.clone();
-                            let update_item_inner = Arc::clone(&update_item);
-                            let normalized_property = property.replace("http://www.wikidata.org/prop/", "");
-                            let property_label = property_labels.get().get(&normalized_property).cloned().unwrap_or_else(|| normalized_property.clone());
-                            log!("Rendering property: {} -> {}", normalized_property, property_label);
-                            let property_clone_for_button = normalized_property.clone();
-                            view! {
-                                <tr>
-                                    <td>
-                                        { property_label }
-                                        <button class="delete-property" on:click=move |_| {
-                                            log!("Deleting property: {}", property_clone_for_button);
-                                            remove_property_clone(property_clone_for_button.clone());
-                                            set_custom_properties.update(|props| {
-                                                props.retain(|p| p != &property_clone_for_button);
-                                            });
-                                            set_selected_properties.update(|selected| {
-                                                selected.remove(&property_clone_for_button);
-                                            });
-                                            set_items.update(|items| {
-                                                for item in items {
-                                                    item.custom_properties.remove(&property_clone_for_button);
+                                                        </td>
+                                                    }
                                                 }
-                                            });
-                                        }>{ "Delete" }</button>
-                                    </td>
-                                    {move || {
-                                        let update_item_cell = Arc::clone(&update_item_inner);
-                                        let property_clone_for_cells = normalized_property.clone();
-                                        items.get().iter().enumerate().map(move |(index, item)| {
-                                            let update_item_cell = Arc::clone(&update_item_cell);
-                                            let property_clone_for_closure = property_clone_for_cells.clone();
+                                            />
+                                        </tr>
+                                    }
+                                }).collect::<Vec<_>>()}
+                                // Dynamically adding custom properties as columns
+                                {{
+                                    let update_item_outer = Rc::clone(&update_item);
+                                    let current_url = current_url.to_string();
+
+                                    move || {
+                                    record_render("ItemsList property rows");
+                                    let update_item = Rc::clone(&update_item_outer);
+                                    let current_url = current_url.clone();
+                                    let custom_props = custom_properties.get().clone();
+                                    let remove_property = remove_property.clone();
+                                    let move_property = move_property.clone();
+                                    let rename_property = rename_property.clone();
+                                    let property_count = custom_props.len();
+                                    custom_props.into_iter().enumerate().map(move |(position, property)| {
+                                        let remove_property_clone = remove_property.clone();
+                                        let rename_property = rename_property.clone();
+                                        let move_property_up = move_property.clone();
+                                        let move_property_down = move_property.clone();
+                                        let move_property_key = move_property.clone();
+                                        let update_item_inner = Rc::clone(&update_item);
+                                        let normalized_property = canonical_property(&property);
+                                        let default_label = property_labels.get().get(&normalized_property).cloned().unwrap_or_else(|| normalized_property.clone());
+                                        let overrides = property_overrides.with(|all| all.get(&normalized_property).cloned().unwrap_or_default());
+                                        let property_label = overrides.label.clone().unwrap_or_else(|| default_label.clone());
+                                        log!("Rendering property: {} -> {}", normalized_property, property_label);
+                                        let property_clone_for_button = normalized_property.clone();
+                                        let property_for_up = normalized_property.clone();
+                                        let property_for_down = normalized_property.clone();
+                                        let property_for_keys = normalized_property.clone();
+                                        let property_for_filter = normalized_property.clone();
+                                        // Minimum, maximum and average of numeric properties, and the items
+                                        // with the best value when the property says which end is best
+                                        let row_numbers = {
+                                            let property = normalized_property.clone();
+                                            create_memo(move |_| items.with(|items| chart_data(items, &property)))
+                                        };
+                                        let row_summary = create_memo(move |_| row_numbers.with(ChartData::summary));
+                                        let row_best = {
+                                            let settings = settings_of(normalized_property.clone());
+                                            create_memo(move |_| {
+                                                settings.with(|settings| settings.better).map(|better| row_numbers.with(|numbers| numbers.best(better))).unwrap_or_default()
+                                            })
+                                        };
                                         view! {
-                                            <td>
-                                                <EditableCell
-                                                    value=item.custom_properties.get(&property_clone_for_closure).cloned().unwrap_or_default()
-                                                    on_input=move |value| update_item_cell(index, &property_clone_for_closure, value)
-                                                    key=Arc::new(format!("custom-{}-{}", property_clone_for_cells, index))
-                                                    focused_cell=focused_cell
-                                                    set_focused_cell=set_focused_cell.clone()
-                                                    on_focus=Some(Callback::new(move |_| {
-                                                    }))
-                                                    on_blur=Some(Callback::new(move |_| {
-                                                    }))
-                                                    input_type=InputType::TextArea
-                                                />
-                                            </td>
+                                            <tr class:filtered-out=move || row_filtered_out(&property_for_filter) on:keydown=move |event: web_sys::KeyboardEvent| {
+                                                // Alt+ArrowUp / Alt+ArrowDown reorder the row without a pointer
+                                                if event.alt_key() {
+                                                    let offset = match event.key().as_str() {
+                                                        "ArrowUp" => -1,
+                                                        "ArrowDown" => 1,
+                                                        _ => return,
+                                                    };
+                                                    event.prevent_default();
+                                                    move_property_key(property_for_keys.clone(), offset);
+                                                }
+                                            }>
+                                                <td id=format!("property-row-{}", normalized_property) tabindex="0">
+                                                    {
+                                                        // Custom properties are renamed in place by double-clicking their label,
+                                                        // Wikidata properties keep the label Wikidata gives them
+                                                        let (renaming, set_renaming) = create_signal(false);
+                                                        let renamable = !is_property_id(&normalized_property);
+                                                        let property = normalized_property.clone();
+                                                        let label = property_label.clone();
+                                                        let tooltip = overrides.description.clone().or_else(|| renamable.then(|| "Double-click to rename".to_string()));
+                                                        let described = overrides.description.is_some();
+                                                        move || if renaming.get() {
+                                                            let rename_on_enter = rename_property.clone();
+                                                            let rename_on_blur = rename_property.clone();
+                                                            let (property_on_enter, property_on_blur) = (property.clone(), property.clone());
+                                                            view! {
+                                                                <input
+                                                                    class="rename-property"
+                                                                    aria-label="New property name"
+                                                                    value=property.clone()
+                                                                    maxlength=MAX_CUSTOM_PROPERTY_LENGTH
+                                                                    autofocus
+                                                                    on:keydown=move |event: web_sys::KeyboardEvent| match event.key().as_str() {
+                                                                        "Enter" => {
+                                                                            set_renaming.set(false);
+                                                                            rename_on_enter(property_on_enter.clone(), event_target_value(&event));
+                                                                        }
+                                                                        "Escape" => set_renaming.set(false),
+                                                                        _ => {}
+                                                                    }
+                                                                    on:blur=move |event| {
+                                                                        if renaming.get_untracked() {
+                                                                            set_renaming.set(false);
+                                                                            rename_on_blur(property_on_blur.clone(), event_target_value(&event));
+                                                                        }
+                                                                    }
+                                                                />
+                                                            }.into_view()
+                                                        } else {
+                                                            view! {
+                                                                <span
+                                                                    class:renamable=renamable
+                                                                    class:described=described
+                                                                    title=tooltip.clone()
+                                                                    on:dblclick=move |_| set_renaming.set(renamable && !read_only.get_untracked())
+                                                                >
+                                                                    { label.clone() }
+                                                                </span>
+                                                            }.into_view()
+                                                        }
+                                                    }
+                                                    <button
+                                                        class="move-property"
+                                                        class:read-only-hidden=read_only
+                                                        title="Move up (Alt+ArrowUp)"
+                                                        aria-label="Move property up"
+                                                        disabled=position == 0
+                                                        on:click=move |_| move_property_up(property_for_up.clone(), -1)
+                                                    >{ "▲" }</button>
+                                                    <button
+                                                        class="move-property"
+                                                        class:read-only-hidden=read_only
+                                                        title="Move down (Alt+ArrowDown)"
+                                                        aria-label="Move property down"
+                                                        disabled=position + 1 == property_count
+                                                        on:click=move |_| move_property_down(property_for_down.clone(), 1)
+                                                    >{ "▼" }</button>
+                                                    <PropertyOverrideButton
+                                                        current_url=current_url.clone()
+                                                        property=normalized_property.clone()
+                                                        default_label=default_label.clone()
+                                                        overrides=overrides_of(normalized_property.clone())
+                                                        on_saved=save_property_override(normalized_property.clone())
+                                                        read_only
+                                                        show_error
+                                                    />
+                                                    <PropertySettingsButton
+                                                        current_url=current_url.clone()
+                                                        property=normalized_property.clone()
+                                                        label=property_label.clone()
+                                                        settings=settings_of(normalized_property.clone())
+                                                        on_saved=save_property_settings(normalized_property.clone())
+                                                        read_only
+                                                        show_error
+                                                    />
+                                                    <button class="delete-property" class:read-only-hidden=read_only on:click=move |_| {
+                                                        log!("Deleting property: {}", property_clone_for_button);
+                                                        remove_property_clone(property_clone_for_button.clone(), position);
+                                                        set_custom_properties.update(|props| {
+                                                            props.retain(|p| p != &property_clone_for_button);
+                                                        });
+                                                        set_selected_properties.update(|selected| {
+                                                            selected.remove(&property_clone_for_button);
+                                                        });
+                                                        set_items.update(|items| {
+                                                            for item in items {
+                                                                item.custom_properties.remove(&property_clone_for_button);
+                                                                item.quantities.remove(&property_clone_for_button);
+                                                                item.sources.remove(&property_clone_for_button);
+                                                            }
+                                                        });
+                                                    }>{ "Delete" }</button>
+                                                    {move || row_summary.get().map(|summary| view! {
+                                                        <div class="property-summary">
+                                                            { format!("min {} · max {} · avg {}", summary.min, summary.max, summary.average) }
+                                                        </div>
+                                                    })}
+                                                </td>
+                                                {
+                                                    let property = normalized_property.clone();
+                                                    let values = create_memo(move |_| items.with(|items| display_values(items, &property, normalize_units.get())));
+                                                    let property = normalized_property.clone();
+                                                    let commons_media = create_memo(move |_| property_cache.with(|cache| is_image_property(&property, cache)));
+                                                    let coordinate_property = COORDINATE_PROPERTIES.contains(&normalized_property.as_str());
+                                                    // Items with a coordinate in this property, for the map of all of them
+                                                    let map_points = create_memo(move |_| items.with(|items| values.with(|values| {
+                                                        items.iter().zip(values).filter_map(|(item, value)| {
+                                                            let coordinate = Coordinate::parse(value, coordinate_property)?;
+                                                            Some(MapPoint { label: item.name.clone(), coordinate })
+                                                        }).collect::<Vec<_>>()
+                                                    })));
+                                                    let property = normalized_property.clone();
+                                                    view! {
+                                                        <For
+                                                            each=move || page_columns.get()
+                                                            key=|column| column.clone()
+                                                            children=move |(index, item_id): (usize, String)| {
+                                                                let value = create_memo(move |_| values.with(|values| values.get(index).cloned().unwrap_or_default()));
+                                                                let truncated = {
+                                                                    let (item_id, property) = (item_id.clone(), property.clone());
+                                                                    create_memo(move |_| truncated_values.with(|truncated| {
+                                                                        truncated.get(&item_id).is_some_and(|properties| properties.contains(&property))
+                                                                    }))
+                                                                };
+                                                                let source = {
+                                                                    let (item_id, property) = (item_id.clone(), property.clone());
+                                                                    create_memo(move |_| items.with(|items| {
+                                                                        items.iter()
+                                                                            .find(|item| item.id == item_id)
+                                                                            .and_then(|item| item.sources.get(&property).copied())
+                                                                            .filter(|source| *source != ValueSource::Manual)
+                                                                    }))
+                                                                };
+                                                                let lock_key = lock_key(&item_id, &property);
+                                                                let is_locked = Signal::derive(move || locked_cells.get().contains(&lock_key));
+                                                                let is_best = {
+                                                                    let item_id = item_id.clone();
+                                                                    Signal::derive(move || row_best.with(|best| best.contains(&item_id)))
+                                                                };
+                                                                let update_item_cell = Rc::clone(&update_item_inner);
+                                                                let property = property.clone();
+                                                                move || {
+                                                                    record_render("ItemsList property cells");
+                                                                    let value = value.get();
+                                                                    if truncated.get() {
+                                                                        let expand = (item_id.clone(), property.clone());
+                                                                        return view! {
+                                                                            <td>
+                                                                                <div class="long-value">
+                                                                                    <div class="long-value-preview">{value}</div>
+                                                                                    <button class="show-all" on:click=move |_| expand_value.call(expand.clone())>{ "Show all" }</button>
+                                                                                </div>
+                                                                            </td>
+                                                                        };
+                                                                    }
+                                                                    let image = image_source(&value, commons_media.get());
+                                                                    let coordinate = image.is_none().then(|| Coordinate::parse(&value, coordinate_property)).flatten();
+                                                                    let item_name = items.with_untracked(|items| {
+                                                                        items.iter().find(|item| item.id == item_id).map(|item| item.name.clone()).unwrap_or_default()
+                                                                    });
+                                                                    let update_item_cell = Rc::clone(&update_item_cell);
+                                                                    let property_for_input = property.clone();
+                                                                    view! {
+                                                                        <td class:locked-cell=is_locked class:best-value=is_best title=move || is_locked.get().then_some("Being edited by someone else")>
+                                                                            {image.map(|source| view! { <ImageCell source=source /> })}
+                                                                            {coordinate.map(|coordinate| view! { <MapCell coordinate label=item_name points=map_points /> })}
+                                                                            {move || source.get().map(|source| view! {
+                                                                                <span class=format!("value-source value-source-{}", source.as_str()) title=source.label()></span>
+                                                                            })}
+                                                                            <EditableCell
+                                                                                value=value
+                                                                                on_input=move |value| update_item_cell(index, &property_for_input, value)
+                                                                                key=Arc::new(format!("custom-{}-{}", property, index))
+                                                                                lang=cell_lang(&property)
+                                                                                spellcheck=cell_spellcheck(&property)
+                                                                                focused_cell=focused_cell
+                                                                                set_focused_cell=set_focused_cell
+                                                                                on_focus=Some(Callback::new(move |_| {
+                                                                                }))
+                                                                                on_blur=Some(Callback::new(move |_| {
+                                                                                }))
+                                                                                input_type=InputType::Markdown
+                                                                                locked=Signal::derive(move || is_locked.get() || read_only.get())
+                                                                                max_length=MAX_VALUE_LENGTH
+                                                                            />
+                                                                        </td>
+                                                                    }
+                                                                }
+                                                            }
+                                                        />
+                                                    }
+                                                }
+                                            </tr>
                                         }
                                     }).collect::<Vec<_>>()}
-                                    }
-                                </tr>
-                            }
-                        }).collect::<Vec<_>>()}
-                    }}
-                </tbody>
-            </table>
-            <div style="margin-bottom: 20px;">
+                                }}
+                                <Show when=move || scores.with(|scores| !scores.is_empty())>
+                                    <tr class="score-row">
+                                        <td title="Weighted score out of 100 over the properties with a weight and a best value">{ "Score" }</td>
+                                        <For
+                                            each=move || page_columns.get()
+                                            key=|column| column.clone()
+                                            children=move |(_, item_id): (usize, String)| {
+                                                let score = create_memo(move |_| scores.with(|scores| scores.iter().find(|score| score.item_id == item_id).cloned()));
+                                                view! {
+                                                    <td class:best-value=move || score.with(|score| score.as_ref().is_some_and(|score| score.rank == 1))>
+                                                        {move || score.get().map(|score| format!("{:.0} · #{}", score.score, score.rank))}
+                                                    </td>
+                                                }
+                                            }
+                                        />
+                                    </tr>
+                                </Show>
+                            </tbody>
+                        </table>
+                    }
+                }
+            </Suspense>
+            <div style="margin-bottom: 20px;" class:read-only-hidden=read_only>
                 <input type="text" id="new-property" placeholder="Add New Property" list="properties" on:keydown=move |event| {
                     if event.key() == "Enter" {
                         let input_element = event.target().unwrap().dyn_into::<web_sys::HtmlInputElement>().unwrap();
@@ -1040,12 +3160,39 @@ pub fn ItemsList(
                         }).collect::<Vec<_>>()
                     }}
                 </datalist>
+                {move || {
+                    let selected = selected_properties.get();
+                    let suggestions: Vec<PropertySuggestion> = property_suggestions
+                        .get()
+                        .into_iter()
+                        .filter(|suggestion| !selected.contains_key(&suggestion.property))
+                        .collect();
+                    let add_suggested_property = Rc::clone(&add_suggested_property);
+                    (!suggestions.is_empty()).then(|| view! {
+                        <div class="property-suggestions">
+                            <span>{ "Suggested for these items:" }</span>
+                            {suggestions.into_iter().map(|suggestion| {
+                                let add_suggested_property = Rc::clone(&add_suggested_property);
+                                let property = suggestion.property.clone();
+                                view! {
+                                    <button
+                                        class="property-suggestion"
+                                        title=format!("Used by {} sampled items of the same class", suggestion.uses)
+                                        on:click=move |_| add_suggested_property(property.clone())
+                                    >
+                                        { format!("{} ({})", suggestion.label, suggestion.property) }
+                                    </button>
+                                }
+                            }).collect::<Vec<_>>()}
+                        </div>
+                    })
+                }}
             </div>
         </div>
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
-struct WikidataResponse {
-    search: Vec<WikidataSuggestion>,
-}
\ No newline at end of file
+// Browser local storage, used for per-URL client preferences
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
@@ -0,0 +1,73 @@
+use leptos::*;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+
+// Content is mounted a bit before it scrolls into view, so it's ready when it appears
+const ROOT_MARGIN: &str = "200px";
+
+// Renders a cheap placeholder, on the server and while hydrating, and mounts the
+// children once the placeholder scrolls into view or receives focus. Keeps the
+// initial WASM work of large comparisons down to what's actually on screen
+#[component]
+pub fn LazyMount(
+    // Stand-in shown until the children are mounted, e.g. a plain input
+    #[prop(into)]
+    placeholder: ViewFn,
+    // Called when the placeholder receives focus, before the children are mounted
+    #[prop(optional)]
+    on_activate: Option<Callback<()>>,
+    children: ChildrenFn,
+) -> impl IntoView {
+    let (mounted, set_mounted) = create_signal(false);
+    let container = create_node_ref::<html::Div>();
+
+    // Watch the placeholder until it becomes visible (runs in the browser only)
+    create_effect(move |_| {
+        let Some(container) = container.get() else {
+            return;
+        };
+        if mounted.get_untracked() {
+            return;
+        }
+        let callback = Closure::<dyn FnMut(js_sys::Array, web_sys::IntersectionObserver)>::new(
+            move |entries: js_sys::Array, observer: web_sys::IntersectionObserver| {
+                let visible = entries
+                    .iter()
+                    .any(|entry| entry.unchecked_into::<web_sys::IntersectionObserverEntry>().is_intersecting());
+                if visible {
+                    observer.disconnect();
+                    set_mounted.set(true);
+                }
+            },
+        );
+        let options = web_sys::IntersectionObserverInit::new();
+        options.set_root_margin(ROOT_MARGIN);
+        let Ok(observer) = web_sys::IntersectionObserver::new_with_options(callback.as_ref().unchecked_ref(), &options) else {
+            // Without the observer the children are mounted right away
+            set_mounted.set(true);
+            return;
+        };
+        observer.observe(&container);
+        on_cleanup(move || {
+            observer.disconnect();
+            drop(callback);
+        });
+    });
+
+    let activate = move |_| {
+        if !mounted.get_untracked() {
+            if let Some(on_activate) = on_activate {
+                on_activate.call(());
+            }
+            set_mounted.set(true);
+        }
+    };
+
+    view! {
+        <div class="lazy-mount" node_ref=container on:focusin=activate>
+            <Show when=move || mounted.get() fallback=move || placeholder.run()>
+                {children()}
+            </Show>
+        </div>
+    }
+}
@@ -0,0 +1,121 @@
+use crate::api_client::{listing, publish_listing, remove_listing};
+use crate::models::directory::ComparisonListing;
+use leptos::logging::log;
+use leptos::*;
+
+// "List in directory" button and dialog, announcing the comparison on Nostr.
+// Only part of the bundle with the `publishing` feature
+#[component]
+pub fn ListingDialog(current_url: String, show_error: Callback<String>) -> impl IntoView {
+    let (show_listing_dialog, set_show_listing_dialog) = create_signal(false);
+    let (listing_title, set_listing_title) = create_signal(String::new());
+    let (listing_category, set_listing_category) = create_signal(String::new());
+    let (listed, set_listed) = create_signal(false);
+    let (publishing_listing, set_publishing_listing) = create_signal(false);
+
+    // Open the dialog with the current listing, if the comparison is already listed
+    let open_listing_dialog = {
+        let current_url = current_url.clone();
+        Callback::new(move |_: ()| {
+            set_show_listing_dialog.set(true);
+            let current_url = current_url.clone();
+            spawn_local(async move {
+                match listing(&current_url).await {
+                    Ok(listing) => {
+                        set_listing_title.set(listing.title);
+                        set_listing_category.set(listing.category);
+                        set_listed.set(true);
+                    }
+                    Err(err) if err.status().is_some() => set_listed.set(false),
+                    Err(err) => show_error.call(format!("Failed to load listing: {}", err)),
+                }
+            });
+        })
+    };
+
+    // Announce the comparison in the public directory, or update its announcement
+    let publish = {
+        let current_url = current_url.clone();
+        Callback::new(move |_: ()| {
+            let listing = ComparisonListing {
+                title: listing_title.get_untracked(),
+                category: listing_category.get_untracked(),
+            };
+            set_publishing_listing.set(true);
+            let current_url = current_url.clone();
+            spawn_local(async move {
+                match publish_listing(&current_url, &listing).await {
+                    Ok(announcement) => {
+                        log!("Announced {} with {} items", announcement.url, announcement.item_count);
+                        set_listed.set(true);
+                        set_show_listing_dialog.set(false);
+                    }
+                    Err(err) => show_error.call(format!("Failed to list comparison: {}", err)),
+                }
+                set_publishing_listing.set(false);
+            });
+        })
+    };
+
+    let unlist = Callback::new(move |_: ()| {
+        set_publishing_listing.set(true);
+        let current_url = current_url.clone();
+        spawn_local(async move {
+            match remove_listing(&current_url).await {
+                Ok(()) => {
+                    set_listed.set(false);
+                    set_show_listing_dialog.set(false);
+                }
+                Err(err) => show_error.call(format!("Failed to unlist comparison: {}", err)),
+            }
+            set_publishing_listing.set(false);
+        });
+    });
+
+    view! {
+        <button class="import-button" on:click=move |_| open_listing_dialog.call(())>
+            { "List in directory" }
+        </button>
+        <Show when=move || show_listing_dialog.get()>
+            <div class="import-dialog-backdrop">
+                <div class="import-dialog" role="dialog" aria-label="List in directory">
+                    <h2>{ "List in the public directory" }</h2>
+                    <p>{ "Announce this comparison on Nostr so it shows up on the Discover page of CompareWare instances." }</p>
+                    <label>
+                        { "Title" }
+                        <input
+                            prop:value=move || listing_title.get()
+                            on:input=move |event| set_listing_title.set(event_target_value(&event))
+                        />
+                    </label>
+                    <label>
+                        { "Category" }
+                        <input
+                            placeholder="Hardware"
+                            prop:value=move || listing_category.get()
+                            on:input=move |event| set_listing_category.set(event_target_value(&event))
+                        />
+                    </label>
+                    <div class="import-dialog-actions">
+                        <button on:click=move |_| set_show_listing_dialog.set(false)>{ "Cancel" }</button>
+                        <Show when=move || listed.get()>
+                            <button
+                                disabled=move || publishing_listing.get()
+                                on:click=move |_| unlist.call(())
+                            >
+                                { "Unlist" }
+                            </button>
+                        </Show>
+                        <button
+                            class="save-button"
+                            disabled=move || publishing_listing.get() || listing_title.get().trim().is_empty()
+                            on:click=move |_| publish.call(())
+                        >
+                            {move || if listed.get() { "Update listing" } else { "Publish" }}
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
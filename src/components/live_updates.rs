@@ -0,0 +1,106 @@
+use crate::components::edit_access::edit_token;
+use crate::models::access::EDIT_TOKEN_PARAM;
+use crate::models::live::{ComparisonChange, LIVE_SESSION_HEADER, LIVE_SESSION_PARAM};
+use crate::url_path::{comparison_ws_path, encode_segment};
+use futures::StreamExt;
+use gloo_net::http::RequestBuilder;
+use gloo_net::websocket::{futures::WebSocket, Message, State};
+use leptos::logging::log;
+use leptos::*;
+use std::time::Duration;
+
+// Seconds before a closed socket is opened again
+const RECONNECT_SECS: u64 = 5;
+
+// Milliseconds between checks of a socket that is still connecting
+const CONNECTING_POLL_MS: u64 = 100;
+
+thread_local! {
+    // Live session of this tab, also used for its edit presence
+    static LIVE_SESSION_ID: String = uuid::Uuid::new_v4().to_string();
+}
+
+pub fn live_session_id() -> String {
+    LIVE_SESSION_ID.with(String::clone)
+}
+
+// Tell the server which tab sends a change, so it isn't pushed back to the same tab
+pub fn with_live_session(request: RequestBuilder) -> RequestBuilder {
+    request.header(LIVE_SESSION_HEADER, &live_session_id())
+}
+
+// Address of the comparison's WebSocket on the server the page came from, with the edit
+// token since private comparisons only send their changes to editors
+fn socket_url(url: &str) -> Option<String> {
+    let location = web_sys::window()?.location();
+    let scheme = if location.protocol().ok()? == "https:" { "wss" } else { "ws" };
+    let token = edit_token(url)
+        .map(|token| format!("&{}={}", EDIT_TOKEN_PARAM, encode_segment(&token)))
+        .unwrap_or_default();
+    Some(format!(
+        "{}://{}{}?{}={}{}",
+        scheme,
+        location.host().ok()?,
+        comparison_ws_path(url),
+        LIVE_SESSION_PARAM,
+        encode_segment(&live_session_id()),
+        token
+    ))
+}
+
+// Wait for the handshake, false when the socket couldn't be opened
+async fn opened(socket: &WebSocket) -> bool {
+    while matches!(socket.state(), State::Connecting) {
+        gloo_timers::future::sleep(Duration::from_millis(CONNECTING_POLL_MS)).await;
+    }
+    matches!(socket.state(), State::Open)
+}
+
+// Receive the changes other tabs save to the comparison, until the owner is disposed.
+// The socket is opened again when it's closed, and since changes may have been
+// missed in between, the comparison is reloaded once it's back
+pub fn subscribe_to_changes(url: &str, on_change: Callback<ComparisonChange>) {
+    let Some(socket_url) = socket_url(url) else {
+        return;
+    };
+    let (stop, stopped) = futures::channel::oneshot::channel::<()>();
+    on_cleanup(move || {
+        let _ = stop.send(());
+    });
+
+    let listen = async move {
+        let mut reconnecting = false;
+        loop {
+            if reconnecting {
+                gloo_timers::future::sleep(Duration::from_secs(RECONNECT_SECS)).await;
+            }
+            let Ok(mut socket) = WebSocket::open(&socket_url) else {
+                return;
+            };
+            if !opened(&socket).await {
+                reconnecting = true;
+                continue;
+            }
+            if reconnecting {
+                on_change.call(ComparisonChange::Reload);
+            }
+            reconnecting = true;
+            while let Some(message) = socket.next().await {
+                match message {
+                    Ok(Message::Text(text)) => match serde_json::from_str::<ComparisonChange>(&text) {
+                        Ok(change) => on_change.call(change),
+                        Err(err) => log!("Failed to parse live update: {:?}", err),
+                    },
+                    Ok(Message::Bytes(_)) => {}
+                    Err(err) => {
+                        log!("Live updates socket closed: {:?}", err);
+                        break;
+                    }
+                }
+            }
+        }
+    };
+    spawn_local(async move {
+        futures::future::select(Box::pin(listen), stopped).await;
+    });
+}
@@ -0,0 +1,34 @@
+use leptos::*;
+
+// Grey placeholder of a table, shown while its data is loading so the page doesn't jump
+// from empty to filled
+#[component]
+pub fn TableSkeleton(
+    // Columns after the header column, rows after the header row
+    #[prop(default = 3)] columns: usize,
+    #[prop(default = 4)] rows: usize,
+) -> impl IntoView {
+    let header = move || (0..=columns).map(|_| view! { <th><span class="skeleton-bar"></span></th> }).collect_view();
+    let row = move || (0..=columns).map(|_| view! { <td><span class="skeleton-bar"></span></td> }).collect_view();
+    view! {
+        <table class="table-skeleton" aria-busy="true" aria-label="Loading">
+            <thead>
+                <tr>{header()}</tr>
+            </thead>
+            <tbody>
+                {(0..rows).map(|_| view! { <tr>{row()}</tr> }).collect_view()}
+            </tbody>
+        </table>
+    }
+}
+
+// Failed load, in place of what couldn't be loaded, with a button to try again
+#[component]
+pub fn LoadError(#[prop(into)] message: String, on_retry: Callback<()>) -> impl IntoView {
+    view! {
+        <div class="load-error" role="alert">
+            <span>{message}</span>
+            <button on:click=move |_| on_retry.call(())>{ "Retry" }</button>
+        </div>
+    }
+}
@@ -0,0 +1,73 @@
+use crate::components::perf_overlay::record_render;
+use crate::models::coordinate::{map_layout, Coordinate, MapPoint};
+use leptos::*;
+
+// Size in pixels and zoom of the map previews shown in table cells
+const PREVIEW_WIDTH: f64 = 120.0;
+const PREVIEW_HEIGHT: f64 = 80.0;
+const PREVIEW_ZOOM: u8 = 12;
+
+// Size in pixels and closest zoom of the map of all items
+const MAP_WIDTH: f64 = 640.0;
+const MAP_HEIGHT: f64 = 420.0;
+const MAP_ZOOM: u8 = 16;
+
+// OpenStreetMap tiles with a marker for each point, the highlighted one on top
+#[component]
+fn MapView(points: Vec<MapPoint>, width: f64, height: f64, max_zoom: u8, #[prop(optional)] highlighted: Option<Coordinate>) -> impl IntoView {
+    let coordinates: Vec<Coordinate> = points.iter().map(|point| point.coordinate).collect();
+    let layout = map_layout(&coordinates, width, height, max_zoom);
+    let mut markers: Vec<(MapPoint, (f64, f64))> = points.into_iter().zip(layout.markers).collect();
+    markers.sort_by_key(|(point, _)| Some(point.coordinate) == highlighted);
+    view! {
+        <div class="map-view" style=format!("width: {}px; height: {}px", width, height)>
+            {layout.tiles.into_iter().map(|tile| view! {
+                <img
+                    class="map-tile"
+                    src=tile.url()
+                    alt=""
+                    loading="lazy"
+                    style=format!("left: {}px; top: {}px", tile.left, tile.top)
+                />
+            }).collect_view()}
+            {markers.into_iter().map(|(point, (left, top))| view! {
+                <span
+                    class="map-marker"
+                    class:highlighted=Some(point.coordinate) == highlighted
+                    style=format!("left: {}px; top: {}px", left, top)
+                    title=point.label
+                ></span>
+            }).collect_view()}
+            <a class="map-attribution" href="https://www.openstreetmap.org/copyright" target="_blank" rel="noopener">
+                { "© OpenStreetMap" }
+            </a>
+        </div>
+    }
+}
+
+// Map preview of a coordinate value, opening a map of the coordinates of all items
+// in the property on click
+#[component]
+pub fn MapCell(coordinate: Coordinate, label: String, #[prop(into)] points: Signal<Vec<MapPoint>>) -> impl IntoView {
+    record_render("MapCell");
+    let (open, set_open) = create_signal(false);
+    let preview = vec![MapPoint { label: label.clone(), coordinate }];
+    let title = format!("{}, view on map", label);
+
+    view! {
+        <button class="map-cell-preview" title=title.clone() aria-label=title on:click=move |_| set_open.set(true)>
+            <MapView points=preview width=PREVIEW_WIDTH height=PREVIEW_HEIGHT max_zoom=PREVIEW_ZOOM />
+        </button>
+        <Show when=move || open.get()>
+            <div class="import-dialog-backdrop" on:click=move |_| set_open.set(false)>
+                <div class="import-dialog map-dialog" role="dialog" aria-label="Map" on:click=|event| event.stop_propagation()>
+                    <MapView points=points.get() width=MAP_WIDTH height=MAP_HEIGHT max_zoom=MAP_ZOOM highlighted=coordinate />
+                    <div class="import-dialog-actions">
+                        <a href=coordinate.osm_url() target="_blank" rel="noopener">{ "Open in OpenStreetMap" }</a>
+                        <button on:click=move |_| set_open.set(false)>{ "Close" }</button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
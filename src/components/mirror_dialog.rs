@@ -0,0 +1,104 @@
+use crate::api_client::{mirror_settings, set_mirror_settings};
+use crate::models::mirror::MirrorSettings;
+use leptos::*;
+
+// "Mirror from Nostr" button and dialog, choosing the comparison of another instance
+// whose published items are merged into this one. Only part of the bundle with the
+// `publishing` feature
+#[component]
+pub fn MirrorDialog(current_url: String, show_error: Callback<String>) -> impl IntoView {
+    let (show_dialog, set_show_dialog) = create_signal(false);
+    let (settings, set_settings) = create_signal(MirrorSettings::default());
+    // Relay URLs, one per line
+    let (relays, set_relays) = create_signal(String::new());
+    let (saving, set_saving) = create_signal(false);
+
+    let open_dialog = {
+        let current_url = current_url.clone();
+        Callback::new(move |_: ()| {
+            set_show_dialog.set(true);
+            let current_url = current_url.clone();
+            spawn_local(async move {
+                match mirror_settings(&current_url).await {
+                    Ok(loaded) => {
+                        set_relays.set(loaded.relays.join("\n"));
+                        set_settings.set(loaded);
+                    }
+                    Err(err) => show_error.call(format!("Failed to load mirror settings: {}", err)),
+                }
+            });
+        })
+    };
+
+    let save = Callback::new(move |_: ()| {
+        let settings = MirrorSettings {
+            relays: relays.get_untracked().lines().map(String::from).collect(),
+            ..settings.get_untracked()
+        };
+        set_saving.set(true);
+        let current_url = current_url.clone();
+        spawn_local(async move {
+            match set_mirror_settings(&current_url, &settings).await {
+                Ok(_) => set_show_dialog.set(false),
+                Err(err) => show_error.call(format!("Failed to save mirror settings: {}", err)),
+            }
+            set_saving.set(false);
+        });
+    });
+
+    view! {
+        <button class="import-button" on:click=move |_| open_dialog.call(())>
+            { "Mirror from Nostr" }
+        </button>
+        <Show when=move || show_dialog.get()>
+            <div class="import-dialog-backdrop">
+                <div class="import-dialog" role="dialog" aria-label="Mirror from Nostr">
+                    <h2>{ "Mirror a comparison from Nostr" }</h2>
+                    <p>{ "Items another instance publishes for its comparison are merged into this one as they arrive. Items changed here keep their local version." }</p>
+                    <label>
+                        <input
+                            type="checkbox"
+                            prop:checked=move || settings.with(|settings| settings.enabled)
+                            on:change=move |event| set_settings.update(|settings| settings.enabled = event_target_checked(&event))
+                        />
+                        { "Mirror the comparison" }
+                    </label>
+                    <label>
+                        { "Comparison URL on the other instance" }
+                        <input
+                            placeholder="/laptops"
+                            prop:value=move || settings.with(|settings| settings.source_url.clone())
+                            on:input=move |event| set_settings.update(|settings| settings.source_url = event_target_value(&event))
+                        />
+                    </label>
+                    <label>
+                        { "Public key of the other instance, in hex" }
+                        <input
+                            prop:value=move || settings.with(|settings| settings.author.clone())
+                            on:input=move |event| set_settings.update(|settings| settings.author = event_target_value(&event))
+                        />
+                    </label>
+                    <label>
+                        { "Relays, one per line" }
+                        <textarea
+                            rows=4
+                            placeholder="wss://relay.damus.io"
+                            prop:value=move || relays.get()
+                            on:input=move |event| set_relays.set(event_target_value(&event))
+                        ></textarea>
+                    </label>
+                    <div class="import-dialog-actions">
+                        <button on:click=move |_| set_show_dialog.set(false)>{ "Cancel" }</button>
+                        <button
+                            class="save-button"
+                            disabled=move || saving.get()
+                            on:click=move |_| save.call(())
+                        >
+                            { "Save" }
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
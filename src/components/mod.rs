@@ -1,2 +1,35 @@
 pub mod items_list;
-pub mod editable_cell;
\ No newline at end of file
+pub mod editable_cell;
+pub mod discover;
+pub mod chart_panel;
+pub mod image_cell;
+pub mod map_cell;
+pub mod lazy_mount;
+pub mod loading;
+pub mod live_updates;
+pub mod tab_sync;
+pub mod perf_overlay;
+pub mod notifications;
+pub mod edit_access;
+pub mod snapshot;
+pub mod comparison_settings;
+pub mod property_settings;
+pub mod property_override;
+pub mod comparisons_index;
+pub mod decision;
+pub mod trash;
+pub mod history;
+pub mod relink_dialog;
+pub mod conflict_dialog;
+pub mod refresh_report;
+pub mod comparison_transfer;
+pub mod admin_settings;
+pub mod setup_wizard;
+#[cfg(feature = "importer")]
+pub mod import_dialog;
+#[cfg(feature = "publishing")]
+pub mod listing_dialog;
+#[cfg(feature = "publishing")]
+pub mod publishing_dialog;
+#[cfg(feature = "publishing")]
+pub mod mirror_dialog;
@@ -0,0 +1,140 @@
+use crate::api_client;
+use crate::models::diagnostics::ClientErrorReport;
+use leptos::logging::log;
+use leptos::*;
+
+// Seconds a toast stays up, longer when it offers an action such as undo or retry
+const TOAST_SECS: u64 = 5;
+const ACTION_TOAST_SECS: u64 = 10;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum NotificationKind {
+    Success,
+    Error,
+}
+
+// Message shown in a toast, with the label of its action button and what it runs
+#[derive(Clone)]
+struct Notification {
+    id: usize,
+    kind: NotificationKind,
+    message: String,
+    action: Option<(&'static str, Callback<()>)>,
+}
+
+// Toasts of the page, shared through the context so any component can report how its
+// requests went. Shown by the Notifications component
+#[derive(Clone, Copy)]
+pub struct Notifier {
+    toasts: RwSignal<Vec<Notification>>,
+    next_id: StoredValue<usize>,
+}
+
+impl Notifier {
+    // Show a toast, dismissed automatically after a few seconds. Pages rendered on the
+    // server show none, the visitor never sees them
+    pub fn notify(&self, kind: NotificationKind, message: String, action: Option<(&'static str, Callback<()>)>) {
+        if leptos::leptos_dom::is_server() {
+            return;
+        }
+        let id = self.next_id.get_value();
+        self.next_id.set_value(id + 1);
+        let seconds = if action.is_some() { ACTION_TOAST_SECS } else { TOAST_SECS };
+        self.toasts.update(|toasts| toasts.push(Notification { id, kind, message, action }));
+        let notifier = *self;
+        spawn_local(async move {
+            gloo_timers::future::sleep(std::time::Duration::from_secs(seconds)).await;
+            notifier.dismiss(id);
+        });
+    }
+
+    pub fn success(&self, message: impl Into<String>) {
+        self.notify(NotificationKind::Success, message.into(), None);
+    }
+
+    // Success offering a follow-up, such as undoing the change
+    pub fn success_with_action(&self, message: impl Into<String>, label: &'static str, action: Callback<()>) {
+        self.notify(NotificationKind::Success, message.into(), Some((label, action)));
+    }
+
+    // Errors are logged and reported to the server too, for the diagnostics bundle
+    pub fn error(&self, message: impl Into<String>) {
+        let message = message.into();
+        log!("[ERROR] {}", message);
+        report_client_error(message.clone());
+        self.notify(NotificationKind::Error, message, None);
+    }
+
+    // Error of a request worth sending again, such as one that failed on the network
+    pub fn error_with_retry(&self, message: impl Into<String>, retry: Callback<()>) {
+        let message = message.into();
+        log!("[ERROR] {}", message);
+        report_client_error(message.clone());
+        self.notify(NotificationKind::Error, message, Some(("Retry", retry)));
+    }
+
+    fn dismiss(&self, id: usize) {
+        self.toasts.update(|toasts| toasts.retain(|toast| toast.id != id));
+    }
+}
+
+pub fn provide_notifier() {
+    provide_context(Notifier { toasts: create_rw_signal(Vec::new()), next_id: store_value(0) });
+}
+
+// Notifier of the page, see provide_notifier
+pub fn use_notifier() -> Notifier {
+    use_context::<Notifier>().expect("provide_notifier is called by App")
+}
+
+// Send an error shown to the visitor to the server, which keeps the latest ones for the
+// diagnostics bundle admins download. Failures to report are ignored
+fn report_client_error(message: String) {
+    let Some(page) = web_sys::window().and_then(|win| win.location().pathname().ok()) else {
+        return;
+    };
+    spawn_local(async move {
+        let _ = api_client::report_client_error(&ClientErrorReport { message, page }).await;
+    });
+}
+
+// Toasts of the Notifier, in the top right corner of the page
+#[component]
+pub fn Notifications() -> impl IntoView {
+    let notifier = use_notifier();
+    view! {
+        <div class="toasts">
+            <For
+                each=move || notifier.toasts.get()
+                key=|toast| toast.id
+                children=move |Notification { id, kind, message, action }| {
+                    view! {
+                        <div
+                            class="toast"
+                            class:toast-error=kind == NotificationKind::Error
+                            class:toast-notice=kind == NotificationKind::Success
+                            role=if kind == NotificationKind::Error { "alert" } else { "status" }
+                        >
+                            <span>{ message }</span>
+                            {action.map(|(label, action)| view! {
+                                <button class="toast-action" on:click=move |_| {
+                                    notifier.dismiss(id);
+                                    action.call(());
+                                }>
+                                    { label }
+                                </button>
+                            })}
+                            <button
+                                class="toast-dismiss"
+                                title="Dismiss"
+                                on:click=move |_| notifier.dismiss(id)
+                            >
+                                { "×" }
+                            </button>
+                        </div>
+                    }
+                }
+            />
+        </div>
+    }
+}
@@ -0,0 +1,152 @@
+use leptos::*;
+use std::collections::BTreeMap;
+
+// Local storage key enabling the overlay, set from the browser console with
+// localStorage.setItem("compareware-perf-overlay", "true")
+const PERF_OVERLAY_KEY: &str = "compareware-perf-overlay";
+
+// Seconds between two samples of the counters shown in the overlay
+const SAMPLE_INTERVAL_SECS: u64 = 1;
+
+#[derive(Default, Clone, PartialEq)]
+struct Counters {
+    renders: BTreeMap<&'static str, u64>,
+    signal_updates: BTreeMap<&'static str, u64>,
+    save_queue: usize,
+}
+
+// Render and signal update counters shared through the context. Counting only
+// starts once the overlay is enabled in the browser, so server renders and
+// regular sessions don't pay for it
+#[derive(Clone, Copy)]
+pub struct PerfMonitor {
+    enabled: RwSignal<bool>,
+    counters: StoredValue<Counters>,
+}
+
+pub fn provide_perf_monitor() {
+    provide_context(PerfMonitor {
+        enabled: create_rw_signal(false),
+        counters: store_value(Counters::default()),
+    });
+}
+
+fn active_monitor() -> Option<PerfMonitor> {
+    use_context::<PerfMonitor>().filter(|monitor| monitor.enabled.get_untracked())
+}
+
+// Performance mark showing up in the browser's performance timeline
+fn mark(name: &str) {
+    if let Some(performance) = web_sys::window().and_then(|window| window.performance()) {
+        let _ = performance.mark(name);
+    }
+}
+
+// Count a run of a component or of a reactive part of a view
+pub fn record_render(component: &'static str) {
+    if let Some(monitor) = active_monitor() {
+        monitor.counters.update_value(|counters| *counters.renders.entry(component).or_default() += 1);
+        mark(&format!("compareware:render:{}", component));
+    }
+}
+
+// Count every update of a signal
+pub fn track_signal(name: &'static str, signal: impl SignalWith + 'static) {
+    create_effect(move |previous: Option<()>| {
+        signal.track();
+        if previous.is_none() {
+            return;
+        }
+        if let Some(monitor) = active_monitor() {
+            monitor.counters.update_value(|counters| *counters.signal_updates.entry(name).or_default() += 1);
+            mark(&format!("compareware:signal:{}", name));
+        }
+    });
+}
+
+// Items waiting to be saved, unsaved in manual mode or being sent to the server
+pub fn record_save_queue(depth: usize) {
+    if let Some(monitor) = active_monitor() {
+        monitor.counters.update_value(|counters| counters.save_queue = depth);
+        mark(&format!("compareware:save-queue:{}", depth));
+    }
+}
+
+// Counter totals with their increase over the last sample
+fn rows(current: &BTreeMap<&'static str, u64>, previous: &BTreeMap<&'static str, u64>) -> Vec<(&'static str, u64, u64)> {
+    current
+        .iter()
+        .map(|(name, total)| (*name, *total, total - previous.get(name).copied().unwrap_or_default()))
+        .collect()
+}
+
+fn counter_table(title: &'static str, rows: Vec<(&'static str, u64, u64)>) -> impl IntoView {
+    view! {
+        <table>
+            <thead>
+                <tr><th>{ title }</th><th>{ "total" }</th><th>{ "/s" }</th></tr>
+            </thead>
+            <tbody>
+                {rows.into_iter().map(|(name, total, rate)| view! {
+                    <tr><td>{ name }</td><td>{ total }</td><td>{ rate }</td></tr>
+                }).collect::<Vec<_>>()}
+            </tbody>
+        </table>
+    }
+}
+
+// Debug overlay with render counts per component, signal update frequency and
+// save queue depth, refreshed every second
+#[component]
+pub fn PerfOverlay() -> impl IntoView {
+    let Some(monitor) = use_context::<PerfMonitor>() else {
+        return ().into_view();
+    };
+    let (samples, set_samples) = create_signal((Counters::default(), Counters::default()));
+
+    // Opt in from local storage and sample the counters (runs in the browser only)
+    create_effect(move |_| {
+        let enabled = web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .and_then(|storage| storage.get_item(PERF_OVERLAY_KEY).ok().flatten())
+            .is_some_and(|value| value == "true");
+        monitor.enabled.set(enabled);
+        if !enabled {
+            return;
+        }
+        spawn_local(async move {
+            while monitor.enabled.get_untracked() {
+                gloo_timers::future::sleep(std::time::Duration::from_secs(SAMPLE_INTERVAL_SECS)).await;
+                let current = monitor.counters.get_value();
+                set_samples.update(|(previous, latest)| *previous = std::mem::replace(latest, current));
+                if let Some(performance) = web_sys::window().and_then(|window| window.performance()) {
+                    performance.clear_marks();
+                }
+            }
+        });
+    });
+
+    let disable = move |_| {
+        monitor.enabled.set(false);
+        if let Some(storage) = web_sys::window().and_then(|window| window.local_storage().ok().flatten()) {
+            let _ = storage.remove_item(PERF_OVERLAY_KEY);
+        }
+    };
+
+    view! {
+        <Show when=move || monitor.enabled.get()>
+            <div class="perf-overlay">
+                <div class="perf-overlay-header">
+                    <strong>{ "Performance" }</strong>
+                    <button on:click=disable title="Disable the overlay">{ "×" }</button>
+                </div>
+                {move || samples.with(|(previous, latest)| view! {
+                    {counter_table("Renders", rows(&latest.renders, &previous.renders))}
+                    {counter_table("Signal updates", rows(&latest.signal_updates, &previous.signal_updates))}
+                    <div>{ format!("Save queue: {}", latest.save_queue) }</div>
+                })}
+            </div>
+        </Show>
+    }
+    .into_view()
+}
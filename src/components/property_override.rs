@@ -0,0 +1,93 @@
+use crate::api_client::set_property_override;
+use crate::models::property::{PropertyOverride, MAX_CUSTOM_PROPERTY_LENGTH, MAX_PROPERTY_DESCRIPTION_LENGTH};
+use leptos::*;
+
+// "✎" button of a property row and its dialog, giving the property a label and a
+// tooltip of this comparison's own instead of its Wikidata label or raw name
+#[component]
+pub fn PropertyOverrideButton(
+    current_url: String,
+    property: String,
+    // Label shown when the comparison doesn't override it
+    default_label: String,
+    #[prop(into)] overrides: Signal<PropertyOverride>,
+    on_saved: Callback<PropertyOverride>,
+    #[prop(into)] read_only: Signal<bool>,
+    show_error: Callback<String>,
+) -> impl IntoView {
+    let (show_dialog, set_show_dialog) = create_signal(false);
+    let (label, set_label) = create_signal(String::new());
+    let (description, set_description) = create_signal(String::new());
+    let (saving, set_saving) = create_signal(false);
+
+    let open_dialog = move |_| {
+        let current = overrides.get_untracked();
+        set_label.set(current.label.unwrap_or_default());
+        set_description.set(current.description.unwrap_or_default());
+        set_show_dialog.set(true);
+    };
+
+    let save = Callback::new(move |_: ()| {
+        let overrides = PropertyOverride { label: Some(label.get_untracked()), description: Some(description.get_untracked()) }.trimmed();
+        set_saving.set(true);
+        let (current_url, property) = (current_url.clone(), property.clone());
+        spawn_local(async move {
+            match set_property_override(&current_url, &property, &overrides).await {
+                Ok(_) => {
+                    on_saved.call(overrides);
+                    set_show_dialog.set(false);
+                }
+                Err(err) => show_error.call(format!("Failed to save the property label: {}", err)),
+            }
+            set_saving.set(false);
+        });
+    });
+
+    let title = format!("Label and description of {}", default_label);
+    view! {
+        <button
+            class="property-settings"
+            class:read-only-hidden=read_only
+            class:customized=move || !overrides.with(PropertyOverride::is_default)
+            title=title.clone()
+            aria-label=title
+            on:click=open_dialog
+        >
+            { "✎" }
+        </button>
+        <Show when=move || show_dialog.get()>
+            <div class="import-dialog-backdrop">
+                <div class="import-dialog" role="dialog" aria-label="Property label">
+                    <h2>{ format!("Label of {}", default_label) }</h2>
+                    <label>
+                        { "Label" }
+                        <input
+                            placeholder=default_label.clone()
+                            maxlength=MAX_CUSTOM_PROPERTY_LENGTH
+                            prop:value=move || label.get()
+                            on:input=move |event| set_label.set(event_target_value(&event))
+                        />
+                    </label>
+                    <label>
+                        { "Description, shown when pointing at the label" }
+                        <textarea
+                            maxlength=MAX_PROPERTY_DESCRIPTION_LENGTH
+                            prop:value=move || description.get()
+                            on:input=move |event| set_description.set(event_target_value(&event))
+                        />
+                    </label>
+                    <div class="import-dialog-actions">
+                        <button on:click=move |_| set_show_dialog.set(false)>{ "Cancel" }</button>
+                        <button
+                            class="save-button"
+                            disabled=move || saving.get()
+                            on:click=move |_| save.call(())
+                        >
+                            { "Save" }
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
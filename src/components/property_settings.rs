@@ -0,0 +1,147 @@
+use crate::api_client::set_property_settings;
+use crate::models::property::{BetterValue, PropertySettings, MAX_LANG_LENGTH, MAX_WEIGHT};
+use leptos::*;
+
+// "Aa" button of a property row and its dialog, setting the language of the
+// property's cells, whether the browser spell-checks them, which of its numbers
+// are highlighted as best and its weight in the score of the items
+#[component]
+pub fn PropertySettingsButton(
+    current_url: String,
+    // Property ID, or "name" / "description" for the core rows
+    property: String,
+    label: String,
+    #[prop(into)] settings: Signal<PropertySettings>,
+    on_saved: Callback<PropertySettings>,
+    #[prop(into)] read_only: Signal<bool>,
+    show_error: Callback<String>,
+) -> impl IntoView {
+    let (show_dialog, set_show_dialog) = create_signal(false);
+    let (lang, set_lang) = create_signal(String::new());
+    let (spellcheck, set_spellcheck) = create_signal(None::<bool>);
+    let (better, set_better) = create_signal(None::<BetterValue>);
+    // Typed text, empty for no weight
+    let (weight, set_weight) = create_signal(String::new());
+    let (saving, set_saving) = create_signal(false);
+
+    let open_dialog = move |_| {
+        let current = settings.get_untracked();
+        set_lang.set(current.lang.unwrap_or_default());
+        set_spellcheck.set(current.spellcheck);
+        set_better.set(current.better);
+        set_weight.set(current.weight.map(|weight| weight.to_string()).unwrap_or_default());
+        set_show_dialog.set(true);
+    };
+
+    let save = Callback::new(move |_: ()| {
+        let weight = weight.get_untracked();
+        let weight = match weight.trim() {
+            "" => None,
+            text => match text.parse::<f64>() {
+                Ok(weight) => Some(weight),
+                Err(_) => return show_error.call(format!("Weight {} is not a number", text)),
+            },
+        };
+        let settings = PropertySettings {
+            lang: Some(lang.get_untracked()),
+            spellcheck: spellcheck.get_untracked(),
+            better: better.get_untracked(),
+            weight,
+        }
+        .trimmed();
+        set_saving.set(true);
+        let (current_url, property) = (current_url.clone(), property.clone());
+        spawn_local(async move {
+            match set_property_settings(&current_url, &property, &settings).await {
+                Ok(_) => {
+                    on_saved.call(settings);
+                    set_show_dialog.set(false);
+                }
+                Err(err) => show_error.call(format!("Failed to save cell settings: {}", err)),
+            }
+            set_saving.set(false);
+        });
+    });
+
+    let title = format!("Language, spell-checking, best values and weight of {}", label);
+    view! {
+        <button
+            class="property-settings"
+            class:read-only-hidden=read_only
+            class:customized=move || !settings.with(PropertySettings::is_default)
+            title=title.clone()
+            aria-label=title
+            on:click=open_dialog
+        >
+            { "Aa" }
+        </button>
+        <Show when=move || show_dialog.get()>
+            <div class="import-dialog-backdrop">
+                <div class="import-dialog" role="dialog" aria-label="Cell settings">
+                    <h2>{ format!("Cells of {}", label) }</h2>
+                    <label>
+                        { "Language" }
+                        <input
+                            placeholder="e.g. de or pt-BR, empty for the page language"
+                            maxlength=MAX_LANG_LENGTH
+                            prop:value=move || lang.get()
+                            on:input=move |event| set_lang.set(event_target_value(&event))
+                        />
+                    </label>
+                    <label>
+                        { "Spell-checking" }
+                        <select
+                            prop:value=move || match spellcheck.get() {
+                                None => "default",
+                                Some(true) => "on",
+                                Some(false) => "off",
+                            }
+                            on:change=move |event| set_spellcheck.set(match event_target_value(&event).as_str() {
+                                "on" => Some(true),
+                                "off" => Some(false),
+                                _ => None,
+                            })
+                        >
+                            <option value="default">{ "Browser default" }</option>
+                            <option value="on">{ "On" }</option>
+                            <option value="off">{ "Off" }</option>
+                        </select>
+                    </label>
+                    <label>
+                        { "Best value, highlighted among numbers" }
+                        <select
+                            prop:value=move || better.get().map_or("", |better| better.as_str())
+                            on:change=move |event| set_better.set(BetterValue::from_name(&event_target_value(&event)))
+                        >
+                            <option value="">{ "None" }</option>
+                            <option value="higher">{ "Higher is better" }</option>
+                            <option value="lower">{ "Lower is better" }</option>
+                        </select>
+                    </label>
+                    <label>
+                        { "Weight in the score, counted with a best value" }
+                        <input
+                            type="number"
+                            min="0"
+                            max=MAX_WEIGHT
+                            step="any"
+                            placeholder="Not scored"
+                            prop:value=move || weight.get()
+                            on:input=move |event| set_weight.set(event_target_value(&event))
+                        />
+                    </label>
+                    <div class="import-dialog-actions">
+                        <button on:click=move |_| set_show_dialog.set(false)>{ "Cancel" }</button>
+                        <button
+                            class="save-button"
+                            disabled=move || saving.get()
+                            on:click=move |_| save.call(())
+                        >
+                            { "Save" }
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
@@ -0,0 +1,88 @@
+use crate::api_client::{publishing_settings, set_publishing_settings};
+use crate::models::publishing::PublishingSettings;
+use leptos::*;
+
+// "Publish to Nostr" button and dialog, choosing whether saved items are published
+// and to which relays. Only part of the bundle with the `publishing` feature
+#[component]
+pub fn PublishingDialog(current_url: String, show_error: Callback<String>) -> impl IntoView {
+    let (show_dialog, set_show_dialog) = create_signal(false);
+    let (enabled, set_enabled) = create_signal(false);
+    // Relay URLs, one per line
+    let (relays, set_relays) = create_signal(String::new());
+    let (saving, set_saving) = create_signal(false);
+
+    let open_dialog = {
+        let current_url = current_url.clone();
+        Callback::new(move |_: ()| {
+            set_show_dialog.set(true);
+            let current_url = current_url.clone();
+            spawn_local(async move {
+                match publishing_settings(&current_url).await {
+                    Ok(settings) => {
+                        set_enabled.set(settings.enabled);
+                        set_relays.set(settings.relays.join("\n"));
+                    }
+                    Err(err) => show_error.call(format!("Failed to load publishing settings: {}", err)),
+                }
+            });
+        })
+    };
+
+    let save = Callback::new(move |_: ()| {
+        let settings = PublishingSettings {
+            enabled: enabled.get_untracked(),
+            relays: relays.get_untracked().lines().map(String::from).collect(),
+        };
+        set_saving.set(true);
+        let current_url = current_url.clone();
+        spawn_local(async move {
+            match set_publishing_settings(&current_url, &settings).await {
+                Ok(_) => set_show_dialog.set(false),
+                Err(err) => show_error.call(format!("Failed to save publishing settings: {}", err)),
+            }
+            set_saving.set(false);
+        });
+    });
+
+    view! {
+        <button class="import-button" on:click=move |_| open_dialog.call(())>
+            { "Publish to Nostr" }
+        </button>
+        <Show when=move || show_dialog.get()>
+            <div class="import-dialog-backdrop">
+                <div class="import-dialog" role="dialog" aria-label="Publish to Nostr">
+                    <h2>{ "Publish items to Nostr" }</h2>
+                    <p>{ "Every item saved to this comparison is published to the relays below, signed with this instance's key." }</p>
+                    <label>
+                        <input
+                            type="checkbox"
+                            prop:checked=move || enabled.get()
+                            on:change=move |event| set_enabled.set(event_target_checked(&event))
+                        />
+                        { "Publish saved items" }
+                    </label>
+                    <label>
+                        { "Relays, one per line" }
+                        <textarea
+                            rows=4
+                            placeholder="wss://relay.damus.io"
+                            prop:value=move || relays.get()
+                            on:input=move |event| set_relays.set(event_target_value(&event))
+                        ></textarea>
+                    </label>
+                    <div class="import-dialog-actions">
+                        <button on:click=move |_| set_show_dialog.set(false)>{ "Cancel" }</button>
+                        <button
+                            class="save-button"
+                            disabled=move || saving.get()
+                            on:click=move |_| save.call(())
+                        >
+                            { "Save" }
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
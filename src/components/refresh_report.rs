@@ -0,0 +1,65 @@
+use crate::models::item::WikidataRefresh;
+use leptos::*;
+
+// Dialog listing the names, descriptions and values a refresh from Wikidata replaced, with
+// what the cells held before
+#[component]
+pub fn RefreshReport(
+    refresh: WikidataRefresh,
+    // Label shown for a property of the table
+    property_label: Callback<String, String>,
+    on_close: Callback<()>,
+) -> impl IntoView {
+    let item_name = |item_id: &str| {
+        refresh.items.iter().find(|item| item.id == item_id).map(|item| item.name.clone()).unwrap_or_default()
+    };
+    let rows: Vec<(String, String, String, String)> = refresh
+        .changes
+        .iter()
+        .map(|change| {
+            let field = match change.property.as_str() {
+                "name" => "Name".to_string(),
+                "description" => "Description".to_string(),
+                property => property_label.call(property.to_string()),
+            };
+            (item_name(&change.item_id), field, change.previous.clone(), change.value.clone())
+        })
+        .collect();
+    let title = format!(
+        "Refreshed {} {} of {} {}",
+        rows.len(),
+        if rows.len() == 1 { "value" } else { "values" },
+        refresh.items.len(),
+        if refresh.items.len() == 1 { "item" } else { "items" }
+    );
+    view! {
+        <div class="import-dialog-backdrop">
+            <div class="import-dialog refresh-report" role="dialog" aria-label="Wikidata refresh">
+                <h2>{title}</h2>
+                <table>
+                    <thead>
+                        <tr>
+                            <th>{ "Item" }</th>
+                            <th>{ "Property" }</th>
+                            <th>{ "Before" }</th>
+                            <th>{ "Now" }</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {rows.into_iter().map(|(item, field, previous, value)| view! {
+                            <tr>
+                                <td>{item}</td>
+                                <th>{field}</th>
+                                <td>{previous}</td>
+                                <td>{value}</td>
+                            </tr>
+                        }).collect_view()}
+                    </tbody>
+                </table>
+                <div class="import-dialog-actions">
+                    <button class="save-button" on:click=move |_| on_close.call(())>{ "Close" }</button>
+                </div>
+            </div>
+        </div>
+    }
+}
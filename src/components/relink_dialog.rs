@@ -0,0 +1,160 @@
+use crate::components::items_list::wikidata_request_url;
+use crate::models::item::Item;
+use crate::models::wikidata::{best_match, search_url, WikidataSearchResponse, WikidataSuggestion, LIKELY_MATCH};
+use leptos::*;
+use std::collections::HashSet;
+
+// Match proposed for an item typed in by hand
+#[derive(Clone, Debug, PartialEq)]
+struct ProposedMatch {
+    item_id: String,
+    item_name: String,
+    entity: WikidataSuggestion,
+    confidence: f64,
+}
+
+// "Link to Wikidata" button and dialog, searching Wikidata for the names of the items
+// without an entity and proposing the best match of each. The accepted matches are
+// handed to `on_link`, with whether to fill in their empty properties from Wikidata
+#[component]
+pub fn RelinkDialog(
+    items: ReadSignal<Vec<Item>>,
+    // Wikidata language of the instance
+    #[prop(into)] language: Signal<String>,
+    on_link: Callback<(Vec<(String, WikidataSuggestion)>, bool)>,
+) -> impl IntoView {
+    let (show_dialog, set_show_dialog) = create_signal(false);
+    let (matches, set_matches) = create_signal(Vec::<ProposedMatch>::new());
+    let (accepted, set_accepted) = create_signal(HashSet::<String>::new());
+    let (fill_properties, set_fill_properties) = create_signal(true);
+    // Items searched so far, out of how many
+    let (progress, set_progress) = create_signal(None::<(usize, usize)>);
+
+    // Search the items one after the other, which keeps within Wikidata's rate limits
+    let find_matches = move || {
+        let unlinked: Vec<(String, String)> = items.with_untracked(|items| {
+            items
+                .iter()
+                .filter(|item| item.wikidata_id.is_none() && !item.name.trim().is_empty())
+                .map(|item| (item.id.clone(), item.name.trim().to_string()))
+                .collect()
+        });
+        set_matches.set(Vec::new());
+        set_accepted.set(HashSet::new());
+        set_progress.set(Some((0, unlinked.len())));
+        let language = language.get_untracked();
+        spawn_local(async move {
+            for (searched, (item_id, item_name)) in unlinked.iter().enumerate() {
+                let request = gloo_net::http::Request::get(&wikidata_request_url(&search_url(item_name, &language)));
+                if let Ok(response) = request.send().await {
+                    if let Ok(data) = response.json::<WikidataSearchResponse>().await {
+                        if let Some((entity, confidence)) = best_match(item_name, &data.search) {
+                            if confidence >= LIKELY_MATCH {
+                                set_accepted.update(|accepted| {
+                                    accepted.insert(item_id.clone());
+                                });
+                            }
+                            let proposed = ProposedMatch { item_id: item_id.clone(), item_name: item_name.clone(), entity, confidence };
+                            set_matches.update(|matches| matches.push(proposed));
+                        }
+                    }
+                }
+                set_progress.set(Some((searched + 1, unlinked.len())));
+            }
+        });
+    };
+
+    let link = move |_| {
+        let accepted = accepted.get_untracked();
+        let links: Vec<(String, WikidataSuggestion)> = matches
+            .get_untracked()
+            .into_iter()
+            .filter(|proposed| accepted.contains(&proposed.item_id))
+            .map(|proposed| (proposed.item_id, proposed.entity))
+            .collect();
+        on_link.call((links, fill_properties.get_untracked()));
+        set_show_dialog.set(false);
+    };
+    let searching = move || progress.get().is_some_and(|(searched, total)| searched < total);
+
+    view! {
+        <button class="import-button" on:click=move |_| {
+            set_show_dialog.set(true);
+            // Searches still running go on, their matches show up in the reopened dialog
+            if !searching() {
+                find_matches();
+            }
+        }>{ "Link to Wikidata" }</button>
+        <Show when=move || show_dialog.get()>
+            <div class="import-dialog-backdrop">
+                <div class="import-dialog" role="dialog" aria-label="Link to Wikidata">
+                    <h2>{ "Link to Wikidata" }</h2>
+                    <p>
+                        { "Items typed in by hand are looked up on Wikidata by their name. Linked items get the Wikidata ID of the match, likely matches are ticked already." }
+                    </p>
+                    <p>
+                        {move || match progress.get() {
+                            Some((_, 0)) => "All named items are linked to Wikidata already.".to_string(),
+                            Some((searched, total)) if searched < total => format!("Searching {} of {} items...", searched + 1, total),
+                            Some((_, total)) => format!("Found matches for {} of {} items.", matches.with(Vec::len), total),
+                            None => String::new(),
+                        }}
+                    </p>
+                    <ul class="relink-matches">
+                        {move || matches.get().into_iter().map(|proposed| {
+                            let item_id = proposed.item_id.clone();
+                            let checked_id = proposed.item_id.clone();
+                            let entity_url = format!("https://www.wikidata.org/wiki/{}", proposed.entity.id);
+                            let likely = proposed.confidence >= LIKELY_MATCH;
+                            view! {
+                                <li class:likely-match=likely>
+                                    <label>
+                                        <input
+                                            type="checkbox"
+                                            prop:checked=move || accepted.with(|accepted| accepted.contains(&checked_id))
+                                            on:change=move |event| {
+                                                let checked = event_target_checked(&event);
+                                                set_accepted.update(|accepted| {
+                                                    if checked {
+                                                        accepted.insert(item_id.clone());
+                                                    } else {
+                                                        accepted.remove(&item_id);
+                                                    }
+                                                });
+                                            }
+                                        />
+                                        <strong>{proposed.item_name}</strong>
+                                        { " → " }
+                                        <a href=entity_url target="_blank" rel="noopener">
+                                            { format!("{} ({})", proposed.entity.label, proposed.entity.id) }
+                                        </a>
+                                        <span class="relink-confidence">{ format!(" {:.0}% match", proposed.confidence * 100.0) }</span>
+                                    </label>
+                                    {proposed.entity.description.map(|description| view! { <div class="relink-description">{description}</div> })}
+                                </li>
+                            }
+                        }).collect::<Vec<_>>()}
+                    </ul>
+                    <label>
+                        <input
+                            type="checkbox"
+                            prop:checked=move || fill_properties.get()
+                            on:change=move |event| set_fill_properties.set(event_target_checked(&event))
+                        />
+                        { "Fill in empty properties from Wikidata" }
+                    </label>
+                    <div class="import-dialog-actions">
+                        <button on:click=move |_| set_show_dialog.set(false)>{ "Cancel" }</button>
+                        <button
+                            class="save-button"
+                            disabled=move || searching() || accepted.with(HashSet::is_empty)
+                            on:click=link
+                        >
+                            {move || format!("Link {} items", accepted.with(HashSet::len))}
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
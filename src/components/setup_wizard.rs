@@ -0,0 +1,129 @@
+use crate::api_client::complete_setup;
+use crate::models::instance::DEFAULT_WIKIDATA_LANGUAGE;
+use crate::models::property::MAX_LANG_LENGTH;
+use crate::models::setup::{SetupRequest, SetupResult};
+use leptos::*;
+use leptos_router::Redirect;
+
+// Server function telling whether the instance still waits for its first run setup, so the
+// landing page can send the first visitor to /setup while it is rendered on the server
+#[server(LoadSetupNeeded, "/api")]
+pub async fn load_setup_needed() -> Result<bool, ServerFnError> {
+    use crate::config::Config;
+    use crate::db::Database;
+    use actix_web::web;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    let db: web::Data<Arc<RwLock<Database>>> = leptos_actix::extract().await?;
+    let config: web::Data<Config> = leptos_actix::extract().await?;
+    let db = db.read().await;
+    crate::api::setup_needed(&config, &db)
+        .await
+        .map_err(|e| ServerFnError::new(format!("Failed to check the setup: {}", e)))
+}
+
+// Sends visitors of a brand new instance to the setup wizard, renders nothing otherwise
+#[component]
+pub fn SetupRedirect() -> impl IntoView {
+    let needed = create_resource(|| (), |_| load_setup_needed());
+    view! {
+        <Suspense>
+            {move || matches!(needed.get(), Some(Ok(true))).then(|| view! { <Redirect path="/setup" /> })}
+        </Suspense>
+    }
+}
+
+// First run wizard at /setup: admin token, public or private mode, Wikidata language and
+// demo comparisons. The token is shown once, the server only keeps its hash
+#[component]
+pub fn SetupWizard() -> impl IntoView {
+    let needed = create_resource(|| (), |_| load_setup_needed());
+    let (private, set_private) = create_signal(false);
+    let (language, set_language) = create_signal(DEFAULT_WIKIDATA_LANGUAGE.to_string());
+    let (demo_data, set_demo_data) = create_signal(true);
+    let (result, set_result) = create_signal(None::<SetupResult>);
+    let (status, set_status) = create_signal(None::<String>);
+    let (busy, set_busy) = create_signal(false);
+
+    let finish = move |_| {
+        let request = SetupRequest {
+            private: private.get_untracked(),
+            wikidata_language: language.get_untracked(),
+            demo_data: demo_data.get_untracked(),
+        };
+        set_busy.set(true);
+        set_status.set(None);
+        spawn_local(async move {
+            match complete_setup(&request).await {
+                Ok(done) => set_result.set(Some(done)),
+                Err(err) => set_status.set(Some(format!("Failed to complete the setup: {}", err))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let form = move || {
+        view! {
+            <p>{ "Welcome! Choose how this instance works before the first comparison is created." }</p>
+            <fieldset>
+                <legend>{ "Visibility" }</legend>
+                <label>
+                    <input type="radio" name="visibility" prop:checked=move || !private.get() on:change=move |_| set_private.set(false) />
+                    { "Public: list the comparisons on the landing page and announce them in the Nostr directory" }
+                </label>
+                <label>
+                    <input type="radio" name="visibility" prop:checked=move || private.get() on:change=move |_| set_private.set(true) />
+                    { "Private: hide the comparisons index and keep comparisons out of the Nostr directory" }
+                </label>
+            </fieldset>
+            <fieldset>
+                <legend>{ "Wikidata" }</legend>
+                <label>
+                    { "Language of searches and imports" }
+                    <input
+                        placeholder=DEFAULT_WIKIDATA_LANGUAGE
+                        maxlength=MAX_LANG_LENGTH
+                        prop:value=move || language.get()
+                        on:input=move |event| set_language.set(event_target_value(&event))
+                    />
+                </label>
+            </fieldset>
+            <label>
+                <input type="checkbox" prop:checked=move || demo_data.get() on:change=move |event| set_demo_data.set(event_target_checked(&event)) />
+                { "Add a few demo comparisons to try things out" }
+            </label>
+            <button class="save-button" disabled=move || busy.get() on:click=finish>{ "Finish setup" }</button>
+            {move || status.get().map(|status| view! { <p class="admin-status">{status}</p> })}
+        }
+    };
+
+    let done = move |done: SetupResult| {
+        view! {
+            <p>{ "The instance is ready. This is the admin token of the settings page, copy it now: it won't be shown again." }</p>
+            <input class="setup-token" readonly prop:value=done.admin_token />
+            <ul>
+                <li><a href="/admin">{ "Instance settings" }</a></li>
+                {done.demo_comparisons.into_iter().map(|url| view! { <li><a href=url.clone()>{url.clone()}</a></li> }).collect::<Vec<_>>()}
+                <li><a href="/">{ "Landing page" }</a></li>
+            </ul>
+        }
+    };
+
+    view! {
+        <div class="admin-settings">
+            <h2>{ "Set up CompareWare" }</h2>
+            <Suspense fallback=|| view! { <p>{ "Loading..." }</p> }>
+                {move || match (result.get(), needed.get()) {
+                    (Some(result), _) => done(result).into_view(),
+                    (None, Some(Ok(true))) => form().into_view(),
+                    (None, Some(Ok(false))) => view! {
+                        <p>{ "This instance is already set up. Admins change its settings on " }<a href="/admin">{ "the settings page" }</a>{ "." }</p>
+                    }.into_view(),
+                    (None, Some(Err(err))) => view! { <p class="admin-status">{ err.to_string() }</p> }.into_view(),
+                    (None, None) => ().into_view(),
+                }}
+            </Suspense>
+        </div>
+    }
+}
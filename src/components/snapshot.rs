@@ -0,0 +1,128 @@
+use crate::api_client::create_snapshot;
+use crate::components::loading::{LoadError, TableSkeleton};
+use crate::models::snapshot::Snapshot;
+use leptos::logging::log;
+use leptos::*;
+use leptos_router::use_params_map;
+
+// Server function to load a snapshot, so its page is complete when rendered on the server
+#[server(LoadSnapshot, "/api")]
+pub async fn load_snapshot(id: String) -> Result<Option<Snapshot>, ServerFnError> {
+    use crate::db::Database;
+    use actix_web::web;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    let db: web::Data<Arc<RwLock<Database>>> = leptos_actix::extract().await?;
+    let snapshot = db
+        .read()
+        .await
+        .get_snapshot(&id)
+        .await
+        .map_err(|e| ServerFnError::new(format!("Failed to fetch snapshot: {}", e)))?;
+    Ok(snapshot)
+}
+
+pub fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default()
+}
+
+// Read-only table of a snapshot, laid out like the comparison grid
+fn snapshot_table(snapshot: Snapshot) -> impl IntoView {
+    let Snapshot { url, created_at, items, selected_properties, property_labels, .. } = snapshot;
+    let row = |label: String, values: Vec<String>| view! {
+        <tr>
+            <th>{label}</th>
+            {values.into_iter().map(|value| view! { <td>{value}</td> }).collect::<Vec<_>>()}
+        </tr>
+    };
+    let descriptions = items.iter().map(|item| item.description.clone()).collect();
+    let property_rows = selected_properties
+        .iter()
+        .map(|property| {
+            let label = property_labels.get(property).cloned().unwrap_or_else(|| property.clone());
+            let values = items
+                .iter()
+                .map(|item| item.custom_properties.get(property).cloned().unwrap_or_default())
+                .collect();
+            row(label, values)
+        })
+        .collect::<Vec<_>>();
+
+    view! {
+        <p class="snapshot-meta">
+            { "Snapshot of " }<a href=url.clone()>{url.clone()}</a>{ format!(", taken {}", format_timestamp(created_at)) }
+        </p>
+        <table class="snapshot">
+            <thead>
+                <tr>
+                    <th>{ "Property" }</th>
+                    {items.iter().map(|item| view! { <th>{item.name.clone()}</th> }).collect::<Vec<_>>()}
+                </tr>
+            </thead>
+            <tbody>
+                {row("Description".to_string(), descriptions)}
+                {property_rows}
+            </tbody>
+        </table>
+    }
+}
+
+// Page of a snapshot at /s/{snapshot_id}
+#[component]
+pub fn SnapshotView() -> impl IntoView {
+    let params = use_params_map();
+    let snapshot = create_resource(
+        move || params.with(|params| params.get("snapshot_id").cloned().unwrap_or_default()),
+        load_snapshot,
+    );
+
+    view! {
+        <Suspense fallback=move || view! { <TableSkeleton /> }>
+            {move || snapshot.get().map(|result| match result {
+                Ok(Some(snapshot)) => snapshot_table(snapshot).into_view(),
+                Ok(None) => view! { <p>{ "This snapshot doesn't exist." }</p> }.into_view(),
+                Err(err) => {
+                    log!("Error loading snapshot: {}", err);
+                    view! { <LoadError message="Failed to load the snapshot." on_retry=Callback::new(move |_| snapshot.refetch())/> }.into_view()
+                }
+            })}
+        </Suspense>
+    }
+}
+
+// "Share snapshot" button, freezing the comparison and showing a link to the copy
+#[component]
+pub fn ShareSnapshot(current_url: String, show_error: Callback<String>) -> impl IntoView {
+    let (link, set_link) = create_signal(None::<String>);
+
+    let share = move |_| {
+        let current_url = current_url.clone();
+        spawn_local(async move {
+            match create_snapshot(&current_url).await {
+                Ok(snapshot) => {
+                    let origin = web_sys::window().and_then(|window| window.location().origin().ok()).unwrap_or_default();
+                    set_link.set(Some(format!("{}{}", origin, snapshot.path)));
+                }
+                Err(err) => show_error.call(format!("Failed to take snapshot: {}", err)),
+            }
+        });
+    };
+
+    view! {
+        <span class="share-snapshot">
+            <button
+                class="import-button"
+                title="Share a copy of the comparison as it is now, later changes won't show up in it"
+                on:click=share
+            >
+                { "Share snapshot" }
+            </button>
+            {move || link.get().map(|link| view! {
+                <input class="invitation-link" readonly=true prop:value=link/>
+            })}
+        </span>
+    }
+}
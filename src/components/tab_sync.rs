@@ -0,0 +1,72 @@
+use crate::models::live::TabMessage;
+use leptos::logging::log;
+use leptos::*;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+
+// BroadcastChannel of the tabs of this browser that have a comparison open
+fn channel_name(url: &str) -> String {
+    format!("compareware-tabs:{}", url)
+}
+
+// Edits and save status shared with the other tabs of this browser showing the same
+// comparison. They see an edit as soon as it's made, before the server saved it, so
+// two tabs of one person don't overwrite each other. Sending does nothing until
+// connected, and on the server
+#[derive(Clone, Copy)]
+pub struct TabSync {
+    channel: StoredValue<Option<web_sys::BroadcastChannel>>,
+}
+
+impl TabSync {
+    pub fn new() -> Self {
+        TabSync { channel: store_value(None) }
+    }
+
+    // Join the comparison's channel and receive the messages of the other tabs until
+    // the owner is disposed (runs in the browser only)
+    pub fn connect(&self, url: &str, on_message: Callback<TabMessage>) {
+        let Ok(channel) = web_sys::BroadcastChannel::new(&channel_name(url)) else {
+            return;
+        };
+        let listener = Closure::<dyn Fn(web_sys::MessageEvent)>::new(move |event: web_sys::MessageEvent| {
+            let Some(text) = event.data().as_string() else {
+                return;
+            };
+            match serde_json::from_str::<TabMessage>(&text) {
+                Ok(message) => on_message.call(message),
+                Err(err) => log!("Failed to parse message of another tab: {:?}", err),
+            }
+        });
+        channel.set_onmessage(Some(listener.as_ref().unchecked_ref()));
+        self.channel.set_value(Some(channel.clone()));
+        on_cleanup(move || {
+            channel.set_onmessage(None);
+            channel.close();
+            drop(listener);
+        });
+    }
+
+    // Send a message to the other tabs, the sending tab doesn't receive it
+    pub fn send(&self, message: &TabMessage) {
+        self.channel.with_value(|channel| {
+            let Some(channel) = channel else {
+                return;
+            };
+            match serde_json::to_string(message) {
+                Ok(text) => {
+                    if let Err(err) = channel.post_message(&JsValue::from_str(&text)) {
+                        log!("Failed to message the other tabs: {:?}", err);
+                    }
+                }
+                Err(err) => log!("Failed to serialize message to the other tabs: {:?}", err),
+            }
+        });
+    }
+}
+
+impl Default for TabSync {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,82 @@
+use crate::api_client::{deleted_items, restore_item};
+use crate::components::snapshot::format_timestamp;
+use crate::models::item::{DeletedItem, Item};
+use leptos::*;
+
+// "Recently deleted" button and panel listing the items in the comparison's trash,
+// each of which can be put back where it was
+#[component]
+pub fn RecentlyDeleted(
+    current_url: String,
+    // Called with a restored item, its values in full
+    on_restore: Callback<Item>,
+    show_error: Callback<String>,
+) -> impl IntoView {
+    let (show_panel, set_show_panel) = create_signal(false);
+    let (deleted, set_deleted) = create_signal(Vec::<DeletedItem>::new());
+    let (restoring, set_restoring) = create_signal(None::<String>);
+
+    let open_panel = {
+        let current_url = current_url.clone();
+        move |_| {
+            set_show_panel.set(true);
+            let current_url = current_url.clone();
+            spawn_local(async move {
+                match deleted_items(&current_url).await {
+                    Ok(deleted) => set_deleted.set(deleted),
+                    // Comparisons nobody saved anything to yet have an empty trash
+                    Err(err) if err.status() == Some(404) => set_deleted.set(Vec::new()),
+                    Err(err) => show_error.call(format!("Failed to load deleted items: {}", err)),
+                }
+            });
+        }
+    };
+
+    let restore = Callback::new(move |item_id: String| {
+        set_restoring.set(Some(item_id.clone()));
+        let current_url = current_url.clone();
+        spawn_local(async move {
+            match restore_item(&current_url, &item_id).await {
+                Ok(item) => {
+                    set_deleted.update(|deleted| deleted.retain(|deleted| deleted.id != item_id));
+                    on_restore.call(item);
+                }
+                Err(err) => show_error.call(format!("Failed to restore item: {}", err)),
+            }
+            set_restoring.set(None);
+        });
+    });
+
+    view! {
+        <button class="import-button" on:click=open_panel>{ "Recently deleted" }</button>
+        <Show when=move || show_panel.get()>
+            <div class="import-dialog-backdrop">
+                <div class="import-dialog" role="dialog" aria-label="Recently deleted">
+                    <h2>{ "Recently deleted" }</h2>
+                    {move || deleted.with(Vec::is_empty).then(|| view! { <p>{ "Nothing was deleted from this comparison." }</p> })}
+                    <ul class="trash-list">
+                        {move || deleted.get().into_iter().map(|item| {
+                            let name = if item.name.is_empty() { "(unnamed item)".to_string() } else { item.name };
+                            let item_id = item.id.clone();
+                            let busy = {
+                                let item_id = item.id.clone();
+                                move || restoring.with(|restoring| restoring.as_ref() == Some(&item_id))
+                            };
+                            view! {
+                                <li>
+                                    <strong>{name}</strong>
+                                    {(!item.description.is_empty()).then(|| view! { <span>{ format!(" – {}", item.description) }</span> })}
+                                    <span class="trash-date">{ format!(" deleted {}", format_timestamp(item.deleted_at)) }</span>
+                                    <button disabled=busy on:click=move |_| restore.call(item_id.clone())>{ "Restore" }</button>
+                                </li>
+                            }
+                        }).collect::<Vec<_>>()}
+                    </ul>
+                    <div class="import-dialog-actions">
+                        <button on:click=move |_| set_show_panel.set(false)>{ "Close" }</button>
+                    </div>
+                </div>
+            </div>
+        </Show>
+    }
+}
@@ -0,0 +1,471 @@
+// Server configuration, read once at startup from an optional TOML file and the
+// environment. COMPAREWARE_* variables override the file, which overrides the defaults.
+// Secrets such as the workspace key and the admin token are only read from the environment,
+// into Config::secrets, which the file can't set and the diagnostics bundle leaves out
+use crate::models::knowledge::KnowledgeSourceKind;
+use crate::wikidata_fixtures::FixtureMode;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+
+// File read when COMPAREWARE_CONFIG doesn't name another one, if it exists
+const DEFAULT_CONFIG_PATH: &str = "compareware.toml";
+const DEFAULT_DATABASE_PATH: &str = "compareware.db";
+// Read-only connections serving the queries of GET requests next to the writer
+const DEFAULT_DATABASE_READERS: usize = 4;
+const DEFAULT_NOSTR_RELAYS: [&str; 2] = ["wss://relay.damus.io", "wss://nos.lol"];
+const DEFAULT_WIKIDATA_ENDPOINT: &str = "https://query.wikidata.org/sparql";
+const DEFAULT_WIKIDATA_API_ENDPOINT: &str = "https://www.wikidata.org/w/api.php";
+// Upstream requests per minute for one client and for the whole instance.
+// The query service allows about a minute of query time per minute and IP
+const DEFAULT_CLIENT_REQUESTS_PER_MINUTE: u32 = 10;
+const DEFAULT_GLOBAL_REQUESTS_PER_MINUTE: u32 = 60;
+// Wikidata labels and claims rarely change, cached entries are kept for a week
+const DEFAULT_CACHE_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+// Suggestions are fresh for a day, then served stale for up to a week while
+// they are refreshed in the background
+const DEFAULT_SUGGESTION_TTL_SECS: i64 = 24 * 60 * 60;
+const DEFAULT_SUGGESTION_STALE_SECS: i64 = 7 * 24 * 60 * 60;
+const DEFAULT_FIXTURE_DIR: &str = "fixtures/wikidata";
+// API requests per minute and IP: changes, and lookups through the Wikidata proxy, which
+// pages send a few of for every item
+const DEFAULT_WRITES_PER_MINUTE: u32 = 120;
+const DEFAULT_SNAPSHOTS_PER_MINUTE: u32 = 6;
+const DEFAULT_WIKIDATA_LOOKUPS_PER_MINUTE: u32 = 300;
+const DEFAULT_STORAGE_REGION: &str = "us-east-1";
+const DEFAULT_STORAGE_PREFIX: &str = "compareware/";
+const DEFAULT_STORAGE_INTERVAL_HOURS: u64 = 24;
+// Uploads kept in the bucket, older ones are removed after each upload
+const DEFAULT_STORAGE_RETENTION: usize = 7;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to read {path}: {source}")]
+    Read { path: String, source: std::io::Error },
+    #[error("Invalid configuration in {path}: {source}")]
+    Parse { path: String, source: toml::de::Error },
+    #[error("Invalid value of {name}: {value:?}")]
+    Variable { name: &'static str, value: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // SQLite database file
+    pub database_path: String,
+    // Read-only connections to it, 0 to read through the writer
+    pub database_readers: usize,
+    // Address the server listens on, Leptos' site address when unset
+    pub listen_address: Option<SocketAddr>,
+    // Relays read for profiles, zap receipts and the directory, and written to by announcements
+    pub nostr_relays: Vec<String>,
+    // Announce the comparisons editors list in the directory, signed with the workspace key
+    pub publish_directory: bool,
+    // Relays the workspace backups are published to, Nostr backups are off without any
+    pub backup_relays: Vec<String>,
+    pub wikidata: WikidataConfig,
+    pub rate_limits: RateLimitConfig,
+    pub storage_backup: StorageBackupConfig,
+    pub fault_injection: FaultInjectionConfig,
+    #[serde(skip)]
+    pub secrets: Secrets,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct WikidataConfig {
+    // SPARQL endpoint of imports, property suggestions and property checks
+    pub endpoint: String,
+    // Wikidata API the labels and claims of entities are fetched from
+    pub api_endpoint: String,
+    // Until an admin changes them in the instance settings
+    pub client_requests_per_minute: u32,
+    pub global_requests_per_minute: u32,
+    // Lifetime of the labels and claims cached in the database
+    pub cache_ttl_secs: i64,
+    pub suggestion_ttl_secs: i64,
+    pub suggestion_stale_secs: i64,
+    // Knowledge base the name typeahead searches and custom properties are filled from
+    pub knowledge_source: KnowledgeSourceKind,
+    // Record wikidata.org responses into fixture_dir or replay them from it, for tests
+    pub fixture_mode: Option<FixtureMode>,
+    pub fixture_dir: String,
+}
+
+// Requests per minute each IP may send to the API, 0 for no limit
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    // Requests that change something: POST, PUT, PATCH and DELETE
+    pub writes_per_minute: u32,
+    // Snapshots taken (POST /api/urls/{url}/snapshots), instead of the writes
+    pub snapshots_per_minute: u32,
+    // Requests to the Wikidata proxy (/api/wikidata/*) and the property suggestions
+    pub wikidata_per_minute: u32,
+    // Reverse proxies whose X-Forwarded-For names the client, requests of any other
+    // peer are counted against the peer's own address
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+// Uploads to S3-compatible storage, off unless the bucket and both of its keys are set
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageBackupConfig {
+    pub bucket: Option<String>,
+    // AWS S3 in the region when unset
+    pub endpoint: Option<String>,
+    pub region: String,
+    // Folder of the uploads in the bucket
+    pub prefix: String,
+    pub interval_hours: u64,
+    // Uploads kept, the oldest beyond them are removed
+    pub retention: usize,
+}
+
+// Keys and tokens, set by the environment only
+#[derive(Clone, PartialEq, Default)]
+pub struct Secrets {
+    // Admin token next to the one of the setup (COMPAREWARE_ADMIN_TOKEN)
+    pub admin_token: Option<String>,
+    // Workspace key, hex or nsec, signing announcements, publications and backups
+    // (COMPAREWARE_NOSTR_SECRET_KEY)
+    pub nostr_secret_key: Option<String>,
+    // Keys of the storage bucket (COMPAREWARE_S3_ACCESS_KEY, COMPAREWARE_S3_SECRET_KEY)
+    pub storage_access_key: Option<String>,
+    pub storage_secret_key: Option<String>,
+}
+
+// Tells which secrets are set, never their values
+impl std::fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Secrets")
+            .field("admin_token", &self.admin_token.is_some())
+            .field("nostr_secret_key", &self.nostr_secret_key.is_some())
+            .field("storage_access_key", &self.storage_access_key.is_some())
+            .field("storage_secret_key", &self.storage_secret_key.is_some())
+            .finish()
+    }
+}
+
+// Artificial latency and failures of API responses, for development and end-to-end
+// tests only. All zero, the default, turns them off
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct FaultInjectionConfig {
+    // Delay added to every API response, plus a random part of up to latency_jitter_ms
+    pub latency_ms: u64,
+    pub latency_jitter_ms: u64,
+    // Share of API requests answered with a 503 instead of reaching their handler, from 0 to 1
+    pub failure_rate: f64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            database_path: DEFAULT_DATABASE_PATH.to_string(),
+            database_readers: DEFAULT_DATABASE_READERS,
+            listen_address: None,
+            nostr_relays: DEFAULT_NOSTR_RELAYS.map(String::from).to_vec(),
+            publish_directory: false,
+            backup_relays: Vec::new(),
+            wikidata: WikidataConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            storage_backup: StorageBackupConfig::default(),
+            fault_injection: FaultInjectionConfig::default(),
+            secrets: Secrets::default(),
+        }
+    }
+}
+
+impl Default for WikidataConfig {
+    fn default() -> Self {
+        WikidataConfig {
+            endpoint: DEFAULT_WIKIDATA_ENDPOINT.to_string(),
+            api_endpoint: DEFAULT_WIKIDATA_API_ENDPOINT.to_string(),
+            client_requests_per_minute: DEFAULT_CLIENT_REQUESTS_PER_MINUTE,
+            global_requests_per_minute: DEFAULT_GLOBAL_REQUESTS_PER_MINUTE,
+            cache_ttl_secs: DEFAULT_CACHE_TTL_SECS,
+            suggestion_ttl_secs: DEFAULT_SUGGESTION_TTL_SECS,
+            suggestion_stale_secs: DEFAULT_SUGGESTION_STALE_SECS,
+            knowledge_source: KnowledgeSourceKind::default(),
+            fixture_mode: None,
+            fixture_dir: DEFAULT_FIXTURE_DIR.to_string(),
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            writes_per_minute: DEFAULT_WRITES_PER_MINUTE,
+            snapshots_per_minute: DEFAULT_SNAPSHOTS_PER_MINUTE,
+            wikidata_per_minute: DEFAULT_WIKIDATA_LOOKUPS_PER_MINUTE,
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+impl Default for StorageBackupConfig {
+    fn default() -> Self {
+        StorageBackupConfig {
+            bucket: None,
+            endpoint: None,
+            region: DEFAULT_STORAGE_REGION.to_string(),
+            prefix: DEFAULT_STORAGE_PREFIX.to_string(),
+            interval_hours: DEFAULT_STORAGE_INTERVAL_HOURS,
+            retention: DEFAULT_STORAGE_RETENTION,
+        }
+    }
+}
+
+impl Config {
+    // Configuration of the server: the file named by COMPAREWARE_CONFIG, which must
+    // exist, or compareware.toml if there is one, overridden by the environment
+    pub fn load() -> Result<Self, ConfigError> {
+        let (path, required) = match std::env::var("COMPAREWARE_CONFIG") {
+            Ok(path) => (path, true),
+            Err(_) => (DEFAULT_CONFIG_PATH.to_string(), false),
+        };
+        let mut config = match std::fs::read_to_string(&path) {
+            Ok(text) => Config::from_toml(&text).map_err(|source| ConfigError::Parse { path: path.clone(), source })?,
+            Err(e) if !required && e.kind() == std::io::ErrorKind::NotFound => Config::default(),
+            Err(source) => return Err(ConfigError::Read { path, source }),
+        };
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+
+    // Settings of a configuration file, the defaults for anything it leaves out
+    pub fn from_toml(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    // Override settings with the variables `var` finds. Blank variables are ignored
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        let var = |name: &str| var(name).map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+
+        if let Some(path) = var("COMPAREWARE_DATABASE_PATH") {
+            self.database_path = path;
+        }
+        if let Some(readers) = parse_variable(&var, "COMPAREWARE_DATABASE_READERS")? {
+            self.database_readers = readers;
+        }
+        if let Some(address) = parse_variable(&var, "COMPAREWARE_LISTEN_ADDRESS")? {
+            self.listen_address = Some(address);
+        }
+        if let Some(relays) = var("COMPAREWARE_NOSTR_RELAYS") {
+            let relays = comma_separated(&relays);
+            if !relays.is_empty() {
+                self.nostr_relays = relays;
+            }
+        }
+        if let Some(publish) = parse_variable(&var, "COMPAREWARE_PUBLISH_DIRECTORY")? {
+            self.publish_directory = publish;
+        }
+        if let Some(relays) = var("COMPAREWARE_BACKUP_RELAYS") {
+            self.backup_relays = comma_separated(&relays);
+        }
+        let wikidata = &mut self.wikidata;
+        if let Some(endpoint) = var("COMPAREWARE_WIKIDATA_ENDPOINT") {
+            wikidata.endpoint = endpoint;
+        }
+        if let Some(endpoint) = var("COMPAREWARE_WIKIDATA_API_ENDPOINT") {
+            wikidata.api_endpoint = endpoint;
+        }
+        if let Some(limit) = parse_variable(&var, "COMPAREWARE_WIKIDATA_CLIENT_RPM")? {
+            wikidata.client_requests_per_minute = limit;
+        }
+        if let Some(limit) = parse_variable(&var, "COMPAREWARE_WIKIDATA_GLOBAL_RPM")? {
+            wikidata.global_requests_per_minute = limit;
+        }
+        if let Some(ttl) = parse_variable(&var, "COMPAREWARE_WIKIDATA_CACHE_TTL_SECS")? {
+            wikidata.cache_ttl_secs = ttl;
+        }
+        if let Some(ttl) = parse_variable(&var, "COMPAREWARE_WIKIDATA_SUGGESTION_TTL_SECS")? {
+            wikidata.suggestion_ttl_secs = ttl;
+        }
+        if let Some(ttl) = parse_variable(&var, "COMPAREWARE_WIKIDATA_SUGGESTION_STALE_SECS")? {
+            wikidata.suggestion_stale_secs = ttl;
+        }
+        if let Some(source) = parse_variable(&var, "COMPAREWARE_KNOWLEDGE_SOURCE")? {
+            wikidata.knowledge_source = source;
+        }
+        if let Some(mode) = parse_variable(&var, "COMPAREWARE_WIKIDATA_FIXTURES")? {
+            wikidata.fixture_mode = Some(mode);
+        }
+        if let Some(dir) = var("COMPAREWARE_WIKIDATA_FIXTURE_DIR") {
+            wikidata.fixture_dir = dir;
+        }
+        if let Some(limit) = parse_variable(&var, "COMPAREWARE_RATE_LIMIT_WRITES_RPM")? {
+            self.rate_limits.writes_per_minute = limit;
+        }
+        if let Some(limit) = parse_variable(&var, "COMPAREWARE_RATE_LIMIT_SNAPSHOTS_RPM")? {
+            self.rate_limits.snapshots_per_minute = limit;
+        }
+        if let Some(limit) = parse_variable(&var, "COMPAREWARE_RATE_LIMIT_WIKIDATA_RPM")? {
+            self.rate_limits.wikidata_per_minute = limit;
+        }
+        if let Some(proxies) = var("COMPAREWARE_TRUSTED_PROXIES") {
+            // Comma-separated
+            self.rate_limits.trusted_proxies = proxies
+                .split(',')
+                .map(str::trim)
+                .filter(|proxy| !proxy.is_empty())
+                .map(|proxy| proxy.parse().map_err(|_| ConfigError::Variable { name: "COMPAREWARE_TRUSTED_PROXIES", value: proxies.clone() }))
+                .collect::<Result<_, _>>()?;
+        }
+        let storage = &mut self.storage_backup;
+        if let Some(bucket) = var("COMPAREWARE_S3_BUCKET") {
+            storage.bucket = Some(bucket);
+        }
+        if let Some(endpoint) = var("COMPAREWARE_S3_ENDPOINT") {
+            storage.endpoint = Some(endpoint);
+        }
+        if let Some(region) = var("COMPAREWARE_S3_REGION") {
+            storage.region = region;
+        }
+        if let Some(prefix) = var("COMPAREWARE_S3_PREFIX") {
+            storage.prefix = prefix;
+        }
+        if let Some(hours) = parse_variable(&var, "COMPAREWARE_S3_BACKUP_INTERVAL_HOURS")? {
+            storage.interval_hours = hours;
+        }
+        if let Some(retention) = parse_variable(&var, "COMPAREWARE_S3_BACKUP_RETENTION")? {
+            storage.retention = retention;
+        }
+        let secrets = &mut self.secrets;
+        secrets.admin_token = var("COMPAREWARE_ADMIN_TOKEN");
+        secrets.nostr_secret_key = var("COMPAREWARE_NOSTR_SECRET_KEY");
+        secrets.storage_access_key = var("COMPAREWARE_S3_ACCESS_KEY");
+        secrets.storage_secret_key = var("COMPAREWARE_S3_SECRET_KEY");
+        let faults = &mut self.fault_injection;
+        if let Some(latency) = parse_variable(&var, "COMPAREWARE_FAULT_LATENCY_MS")? {
+            faults.latency_ms = latency;
+        }
+        if let Some(jitter) = parse_variable(&var, "COMPAREWARE_FAULT_LATENCY_JITTER_MS")? {
+            faults.latency_jitter_ms = jitter;
+        }
+        if let Some(rate) = parse_variable(&var, "COMPAREWARE_FAULT_FAILURE_RATE")? {
+            faults.failure_rate = rate;
+        }
+        if !(0.0..=1.0).contains(&faults.failure_rate) {
+            return Err(ConfigError::Variable { name: "fault_injection.failure_rate", value: faults.failure_rate.to_string() });
+        }
+        Ok(())
+    }
+}
+
+// Entries of a comma-separated list, without blank ones
+fn comma_separated(list: &str) -> Vec<String> {
+    list.split(',').map(str::trim).filter(|entry| !entry.is_empty()).map(String::from).collect()
+}
+
+// Value of a variable parsed as T, None when it's unset
+fn parse_variable<T: FromStr>(var: &impl Fn(&str) -> Option<String>, name: &'static str) -> Result<Option<T>, ConfigError> {
+    var(name)
+        .map(|value| value.parse().map_err(|_| ConfigError::Variable { name, value }))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leptos::logging::log;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_config_file() {
+        log!("[TEST] Starting test_config_file");
+        let config = Config::from_toml(
+            r#"
+            database_path = "/var/lib/compareware/data.db"
+            listen_address = "0.0.0.0:8080"
+
+            [wikidata]
+            endpoint = "https://sparql.example.com/sparql"
+            cache_ttl_secs = 3600
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.database_path, "/var/lib/compareware/data.db");
+        assert_eq!(config.listen_address, Some("0.0.0.0:8080".parse().unwrap()));
+        assert_eq!(config.wikidata.endpoint, "https://sparql.example.com/sparql");
+        assert_eq!(config.wikidata.cache_ttl_secs, 3600);
+        // Settings the file leaves out keep their defaults
+        assert_eq!(config.nostr_relays, Config::default().nostr_relays);
+        assert_eq!(config.wikidata.client_requests_per_minute, DEFAULT_CLIENT_REQUESTS_PER_MINUTE);
+        assert_eq!(Config::from_toml("").unwrap(), Config::default());
+        // Test the example file documents the defaults
+        assert_eq!(Config::from_toml(include_str!("../compareware.example.toml")).unwrap(), Config::default());
+
+        // Test secrets can't be set by the file nor end up in a written one
+        assert!(Config::from_toml("[secrets]\nadmin_token = \"x\"").is_err());
+        let mut config = Config::default();
+        config.secrets.admin_token = Some("hunter2".into());
+        assert!(!toml::to_string(&config).unwrap().contains("hunter2"));
+        assert!(!format!("{:?}", config).contains("hunter2"));
+
+        // Test typos are reported instead of silently ignored
+        assert!(Config::from_toml("databse_path = \"x.db\"").is_err());
+        assert!(Config::from_toml("[wikidata]\ncache_ttl = 5").is_err());
+        log!("[TEST] test_config_file completed successfully");
+    }
+
+    #[test]
+    fn test_config_environment() {
+        log!("[TEST] Starting test_config_environment");
+        let variables = HashMap::from([
+            ("COMPAREWARE_DATABASE_PATH", " /tmp/test.db "),
+            ("COMPAREWARE_DATABASE_READERS", "8"),
+            ("COMPAREWARE_LISTEN_ADDRESS", "127.0.0.1:4000"),
+            ("COMPAREWARE_NOSTR_RELAYS", "wss://relay.example.com, ,wss://nos.lol"),
+            ("COMPAREWARE_WIKIDATA_CLIENT_RPM", "5"),
+            ("COMPAREWARE_WIKIDATA_ENDPOINT", ""),
+            ("COMPAREWARE_WIKIDATA_FIXTURES", "replay"),
+            ("COMPAREWARE_KNOWLEDGE_SOURCE", "dbpedia"),
+            ("COMPAREWARE_RATE_LIMIT_WRITES_RPM", "0"),
+            ("COMPAREWARE_TRUSTED_PROXIES", "127.0.0.1, ::1"),
+            ("COMPAREWARE_PUBLISH_DIRECTORY", "true"),
+            ("COMPAREWARE_BACKUP_RELAYS", "wss://backup.example.com,"),
+            ("COMPAREWARE_S3_BUCKET", "backups"),
+            ("COMPAREWARE_S3_BACKUP_RETENTION", "3"),
+            ("COMPAREWARE_ADMIN_TOKEN", " hunter2 "),
+            ("COMPAREWARE_NOSTR_SECRET_KEY", ""),
+        ]);
+        let mut config = Config::from_toml("database_path = \"file.db\"").unwrap();
+        config.apply_env(|name| variables.get(name).map(|value| value.to_string())).unwrap();
+        assert_eq!(config.database_path, "/tmp/test.db");
+        assert_eq!(config.database_readers, 8);
+        assert_eq!(config.listen_address, Some("127.0.0.1:4000".parse().unwrap()));
+        assert_eq!(config.nostr_relays, ["wss://relay.example.com", "wss://nos.lol"]);
+        assert_eq!(config.wikidata.client_requests_per_minute, 5);
+        assert_eq!(config.wikidata.fixture_mode, Some(FixtureMode::Replay));
+        assert_eq!(config.wikidata.knowledge_source, KnowledgeSourceKind::Dbpedia);
+        let trusted_proxies = vec!["127.0.0.1".parse().unwrap(), "::1".parse().unwrap()];
+        assert_eq!(config.rate_limits, RateLimitConfig { writes_per_minute: 0, trusted_proxies, ..Default::default() });
+        assert!(config.publish_directory);
+        assert_eq!(config.backup_relays, ["wss://backup.example.com"]);
+        assert_eq!(config.storage_backup.bucket.as_deref(), Some("backups"));
+        assert_eq!(config.storage_backup.retention, 3);
+        assert_eq!(config.storage_backup.region, DEFAULT_STORAGE_REGION);
+        assert_eq!(config.secrets.admin_token.as_deref(), Some("hunter2"));
+        // Blank variables leave the setting alone
+        assert_eq!(config.wikidata.endpoint, DEFAULT_WIKIDATA_ENDPOINT);
+        assert_eq!(config.secrets.nostr_secret_key, None);
+
+        // Test malformed values name the variable
+        let error = Config::default()
+            .apply_env(|name| (name == "COMPAREWARE_WIKIDATA_GLOBAL_RPM").then(|| "many".to_string()))
+            .unwrap_err();
+        assert!(error.to_string().contains("COMPAREWARE_WIKIDATA_GLOBAL_RPM"));
+
+        // Test failure rates outside 0 to 1 are refused
+        let mut config = Config::default();
+        config.apply_env(|name| (name == "COMPAREWARE_FAULT_FAILURE_RATE").then(|| "0.25".to_string())).unwrap();
+        assert_eq!(config.fault_injection.failure_rate, 0.25);
+        assert!(Config::default()
+            .apply_env(|name| (name == "COMPAREWARE_FAULT_FAILURE_RATE").then(|| "25".to_string()))
+            .is_err());
+        log!("[TEST] test_config_environment completed successfully");
+    }
+}
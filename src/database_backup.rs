@@ -0,0 +1,158 @@
+// Whole-database backups for operators: a consistent copy of the SQLite file, and the
+// restore of such a copy over the database. Admins download and upload copies at
+// /api/admin/database, operators without an HTTP client run the server binary with
+// `--backup <file>` or `--restore <file>` instead of starting the server
+use crate::db::{database_copy_problem, Database};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DatabaseBackupError {
+    #[error("Database copy failed: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("Failed to access the database copy: {0}")]
+    File(#[from] std::io::Error),
+    // The file can't be restored, it's damaged, isn't a CompareWare database or is too new
+    #[error("{0}")]
+    Invalid(String),
+    #[error("No database at {0}")]
+    Missing(String),
+}
+
+pub const USAGE: &str = "Usage: compareware [--backup <file> | --restore <file>]";
+
+// Commands run instead of the server
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    // Write a consistent copy of the database to a new file
+    Backup(String),
+    // Replace the database with a copy, meant for a stopped server
+    Restore(String),
+}
+
+// Command given by the arguments after the program name, None to start the server
+pub fn parse_command(args: impl IntoIterator<Item = String>) -> Result<Option<Command>, String> {
+    let mut args = args.into_iter();
+    let Some(flag) = args.next() else {
+        return Ok(None);
+    };
+    let command: fn(String) -> Command = match flag.as_str() {
+        "--backup" => Command::Backup,
+        "--restore" => Command::Restore,
+        _ => return Err(format!("Unknown argument {}\n{}", flag, USAGE)),
+    };
+    match (args.next(), args.next()) {
+        (Some(file), None) if !file.is_empty() => Ok(Some(command(file))),
+        _ => Err(format!("{} takes a single file\n{}", flag, USAGE)),
+    }
+}
+
+// Run a command on the database file of the configuration, returning what it did
+pub async fn run_command(command: &Command, database_path: &str) -> Result<String, DatabaseBackupError> {
+    // Both commands need the database file, opening a missing one would create it
+    if !Path::new(database_path).exists() {
+        return Err(DatabaseBackupError::Missing(database_path.to_string()));
+    }
+    let db = Database::new(database_path)?;
+    match command {
+        Command::Backup(file) => {
+            db.copy_database_to(file).await?;
+            Ok(format!("Copied {} to {}", database_path, file))
+        }
+        Command::Restore(file) => {
+            restore_from_file(&db, file).await?;
+            // Leave a database file that is complete without its write-ahead log
+            db.checkpoint().await?;
+            Ok(format!("Restored {} from {}", database_path, file))
+        }
+    }
+}
+
+// Temporary file of a copy on its way in or out of the database
+fn temp_copy_path() -> PathBuf {
+    std::env::temp_dir().join(format!("compareware-database-{}.db", uuid::Uuid::new_v4()))
+}
+
+// Consistent copy of the database as the bytes of an SQLite file
+pub async fn database_copy(db: &Database) -> Result<Vec<u8>, DatabaseBackupError> {
+    let path = temp_copy_path();
+    let copied = db.copy_database_to(&path.to_string_lossy()).await;
+    let bytes = copied.map_err(DatabaseBackupError::from).and_then(|_| Ok(std::fs::read(&path)?));
+    let _ = std::fs::remove_file(&path);
+    bytes
+}
+
+// Replace the database with an uploaded SQLite file
+pub async fn restore_database(db: &Database, bytes: &[u8]) -> Result<(), DatabaseBackupError> {
+    let path = temp_copy_path();
+    std::fs::write(&path, bytes)?;
+    let restored = restore_from_file(db, &path.to_string_lossy()).await;
+    let _ = std::fs::remove_file(&path);
+    restored
+}
+
+async fn restore_from_file(db: &Database, file: &str) -> Result<(), DatabaseBackupError> {
+    if let Some(problem) = database_copy_problem(file) {
+        return Err(DatabaseBackupError::Invalid(problem));
+    }
+    db.restore_database_from(file).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leptos::logging::log;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_command() {
+        log!("[TEST] Starting test_parse_command");
+        assert_eq!(parse_command(args(&[])), Ok(None));
+        assert_eq!(parse_command(args(&["--backup", "copy.db"])), Ok(Some(Command::Backup("copy.db".into()))));
+        assert_eq!(parse_command(args(&["--restore", "copy.db"])), Ok(Some(Command::Restore("copy.db".into()))));
+        assert!(parse_command(args(&["--backup"])).unwrap_err().contains("takes a single file"));
+        assert!(parse_command(args(&["--restore", "a.db", "b.db"])).is_err());
+        assert!(parse_command(args(&["--serve"])).unwrap_err().starts_with("Unknown argument --serve"));
+        log!("[TEST] test_parse_command completed successfully");
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_restore() {
+        log!("[TEST] Starting test_backup_and_restore");
+        let dir = std::env::temp_dir().join(format!("compareware-backup-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let database_path = dir.join("data.db").to_string_lossy().to_string();
+        let copy_path = dir.join("copy.db").to_string_lossy().to_string();
+        let missing = run_command(&Command::Backup(copy_path.clone()), &database_path).await;
+        assert!(matches!(missing, Err(DatabaseBackupError::Missing(_))));
+
+        let db = Database::new(&database_path).unwrap();
+        db.create_schema().await.unwrap();
+        db.insert_url("https://kept.com").await.unwrap();
+        run_command(&Command::Backup(copy_path.clone()), &database_path).await.unwrap();
+
+        // Test an uploaded copy replaces the database, and a bad upload leaves it alone
+        let copy = std::fs::read(&copy_path).unwrap();
+        db.insert_url("https://later.com").await.unwrap();
+        restore_database(&db, &copy).await.unwrap();
+        assert_eq!(db.get_urls().await.unwrap(), ["https://kept.com"]);
+        let invalid = restore_database(&db, b"not a database").await;
+        assert!(matches!(invalid, Err(DatabaseBackupError::Invalid(_))));
+        assert_eq!(db.get_urls().await.unwrap(), ["https://kept.com"]);
+        let downloaded = database_copy(&db).await.unwrap();
+        assert!(downloaded.starts_with(b"SQLite format 3\0"));
+        drop(db);
+
+        // Test the restore command on a database file
+        let db = Database::new(&database_path).unwrap();
+        db.insert_url("https://later.com").await.unwrap();
+        drop(db);
+        run_command(&Command::Restore(copy_path), &database_path).await.unwrap();
+        assert_eq!(Database::new(&database_path).unwrap().get_urls().await.unwrap(), ["https://kept.com"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+        log!("[TEST] test_backup_and_restore completed successfully");
+    }
+}
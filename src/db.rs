@@ -1,18 +1,38 @@
 #[cfg(feature = "ssr")]
 mod db_impl {
-    use crate::models::item::Item;
+    use crate::models::access::{EditAccess, EditorRole, Visibility};
+    use crate::models::api_key::{ApiKey, ApiKeyRequest};
+    use crate::models::backup::{ComparisonArchive, WorkspaceArchive};
+    use crate::models::card::{Spec, SpecCard, MAX_CARD_SPECS};
+    use crate::models::diagnostics::{AuditEntry, ComparisonRows};
+    use crate::models::directory::{default_title, ComparisonIndex, ComparisonListing, ComparisonSummary};
+    use crate::models::history::{HistoryChange, Revision, HISTORY_PAGE_SIZE};
+    use crate::models::instance::InstanceSettings;
+    use crate::models::item::{value_preview, DeletedItem, Item, ItemStatus, RefreshedValue, TruncatedValues, ValueSource, WikidataRefresh, MAX_DELETED_ITEMS, PREVIEW_LENGTH};
+    use crate::models::metadata::{ComparisonMetadata, Decision};
+    use crate::models::mirror::{MirrorSettings, MirrorUpdate};
+    use crate::models::quantity::{Quantity, UnitSystem};
+    use crate::models::property::{is_property_id, BetterValue, EntityClaims, EntityTerms, PropertyColumn, PropertyDeletion, PropertyOverride, PropertySettings, ReusableValues};
+    use crate::models::publishing::PublishingSettings;
+    use crate::models::presence::{CellLock, EditorMetrics, Heartbeat, PresenceState, UrlEditors};
+    use crate::models::remote::{RemoteComparison, RemoteUpdate};
+    use crate::models::review::{ExportedReview, RatingSummary, ReviewEntry};
+    use crate::models::search::{search_terms, snippet_parts, SearchHit, MATCH_END, MATCH_START};
+    use crate::models::snapshot::Snapshot;
+    use crate::server_error;
     use leptos::logging;
     use leptos::logging::log;
-    use rusqlite::{Connection, Error};
+    use rusqlite::backup::Backup;
+    use rusqlite::{Connection, Error, OpenFlags, OptionalExtension};
     use serde::{Deserialize, Serialize};
     use std::collections::{HashMap, HashSet};
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
-    use tokio::sync::Mutex;
+    use tokio::sync::{Mutex, MutexGuard};
     use uuid::Uuid;
     #[cfg(test)]
     mod tests {
         use super::*;
-        use tokio::runtime::Runtime;
         use uuid::Uuid;
 
         // Helper function to create test database
@@ -65,6 +85,12 @@ mod db_impl {
                 ]
                 .into_iter()
                 .collect(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
             };
 
             // Test insertion
@@ -96,12 +122,23 @@ mod db_impl {
 
             // Test deletion
             log!("[TEST] Testing item deletion");
-            db.delete_item_by_url(test_url, &test_item.id)
-                .await
-                .unwrap();
+            assert_eq!(db.delete_item_by_url(test_url, &test_item.id).await.unwrap(), 1);
             let items = db.get_items_by_url(test_url).await.unwrap();
             assert!(items.is_empty());
+            assert_eq!(db.delete_item_by_url(test_url, &test_item.id).await.unwrap(), 0);
             log!("[TEST] Item deletion - PASSED");
+
+            // Test deleted items wait in the trash until restored
+            log!("[TEST] Testing item restoration");
+            let trash = db.get_deleted_items(test_url).await.unwrap();
+            assert_eq!(trash.len(), 1);
+            assert_eq!((trash[0].id.as_str(), trash[0].name.as_str()), (test_item.id.as_str(), "Updated Name"));
+            let restored = db.restore_item(test_url, &test_item.id).await.unwrap().unwrap();
+            assert_eq!(restored.custom_properties, test_item.custom_properties);
+            assert_eq!(db.get_items_by_url(test_url).await.unwrap().len(), 1);
+            assert!(db.get_deleted_items(test_url).await.unwrap().is_empty());
+            assert!(db.restore_item(test_url, &test_item.id).await.unwrap().is_none());
+            log!("[TEST] Item restoration - PASSED");
             log!("[TEST] test_full_item_lifecycle completed successfully");
         }
 
@@ -118,6 +155,10 @@ mod db_impl {
             assert!(url_id > 0);
             log!("[TEST] URL creation - PASSED");
 
+            // Test URL existence check
+            assert!(db.url_exists(test_url).await.unwrap());
+            assert!(!db.url_exists("https://unknown.com").await.unwrap());
+
             // Test duplicate URL handling
             log!("[TEST] Testing duplicate URL handling");
             let duplicate_id = db.insert_url(test_url).await.unwrap();
@@ -155,6 +196,12 @@ mod db_impl {
                 ]
                 .into_iter()
                 .collect(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
             };
             // Test property creation
             log!("[TEST] Testing property creation");
@@ -167,10 +214,15 @@ mod db_impl {
 
             // Test property deletion
             log!("[TEST] Testing property deletion");
-            db.delete_property_by_url(test_url, "price").await.unwrap();
+            assert_eq!(db.delete_property_by_url(test_url, "price").await.unwrap().unwrap().rows_affected, 1);
             let items = db.get_items_by_url(test_url).await.unwrap();
             assert_eq!(items[0].custom_properties.len(), 1);
             assert!(!items[0].custom_properties.contains_key("price"));
+            assert!(db.delete_property_by_url(test_url, "price").await.unwrap().is_none());
+            assert!(matches!(
+                db.delete_property_by_url(test_url, "never-stored").await,
+                Err(Error::QueryReturnedNoRows)
+            ));
             log!("[TEST] Property deletion - PASSED");
 
             log!("[TEST] test_property_operations completed successfully");
@@ -203,538 +255,5224 @@ mod db_impl {
             assert_eq!(props.len(), 2); // No duplicate added
             log!("[TEST] Duplicate prevention - PASSED");
 
+            // Test selected properties count as stored
+            log!("[TEST] Testing property existence");
+            assert!(db.property_exists("price").await.unwrap());
+            assert!(db.property_exists("name").await.unwrap());
+            assert!(!db.property_exists("never-stored").await.unwrap());
+            log!("[TEST] Property existence - PASSED");
+
             log!("[TEST] test_selected_properties completed successfully");
         }
-    }
 
-    // Define a struct to represent a database connection
-    #[derive(Debug)]
-    pub struct Database {
-        conn: Arc<Mutex<Connection>>,
-    }
+        // Property column backfill test
+        #[tokio::test]
+        async fn test_property_column_backfill() {
+            log!("[TEST] Starting test_property_column_backfill");
+            let db = create_test_db().await;
+            let test_url = "https://backfill.com";
+            let item = |name: &str, wikidata_id: Option<&str>, mass: Option<&str>| Item {
+                id: Uuid::new_v4().to_string(),
+                name: name.into(),
+                description: String::new(),
+                wikidata_id: wikidata_id.map(String::from),
+                custom_properties: mass.map(|mass| ("P2067".to_string(), mass.to_string())).into_iter().collect(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            let cached = item("Cached", Some("Q1"), None);
+            let edited = item("Edited", Some("Q2"), Some("2 kg (measured)"));
+            let uncached = item("Uncached", Some("Q3"), None);
+            let custom = item("Custom", None, None);
+            db.insert_items_by_url(test_url, &[cached.clone(), edited.clone(), uncached.clone(), custom.clone()])
+                .await
+                .unwrap();
+            let mass = Quantity { amount: 1.5, unit_id: Some("Q11570".into()), unit: Some("kilogram".into()), precision: None };
+            let claims = HashMap::from([
+                ("Q1".to_string(), EntityClaims {
+                    values: HashMap::from([("P2067".to_string(), "1.5 kg".to_string())]),
+                    quantities: HashMap::from([("P2067".to_string(), mass.clone())]),
+                    ..Default::default()
+                }),
+                ("Q2".to_string(), EntityClaims {
+                    values: HashMap::from([("P2067".to_string(), "2 kg".to_string())]),
+                    ..Default::default()
+                }),
+            ]);
 
-    impl Database {
-        // Create a new database connection
-        pub fn new(db_path: &str) -> Result<Self, Error> {
-            let conn = Connection::open(db_path)?;
-            logging::log!("Database connection established at: {}", db_path);
-            Ok(Database {
-                conn: Arc::new(Mutex::new(conn)),
-            })
+            // Test cached values fill empty cells only
+            log!("[TEST] Testing backfill");
+            let column = db.add_property_column(test_url, "P2067", &claims).await.unwrap();
+            assert_eq!(column.backfilled, 1);
+            assert_eq!(column.values.len(), 2);
+            assert_eq!(column.values[&cached.id], "1.5 kg");
+            assert_eq!(column.quantities[&cached.id], mass);
+            assert_eq!(column.values[&edited.id], "2 kg (measured)");
+            assert_eq!(db.get_selected_properties(test_url).await.unwrap(), vec!["P2067"]);
+            let stored = db.get_items_by_url(test_url).await.unwrap();
+            let stored_cached = stored.iter().find(|item| item.id == cached.id).unwrap();
+            assert_eq!(stored_cached.custom_properties["P2067"], "1.5 kg");
+            assert_eq!(stored_cached.quantities["P2067"], mass);
+            log!("[TEST] Backfill - PASSED");
+
+            // Test a deleted property shows its values again once re-added
+            log!("[TEST] Testing re-adding a deleted property");
+            db.delete_property_by_url(test_url, "P2067").await.unwrap();
+            let column = db.add_property_column(test_url, "P2067", &HashMap::new()).await.unwrap();
+            assert_eq!(column.backfilled, 0);
+            assert_eq!(column.values.len(), 2);
+            let stored = db.get_items_by_url(test_url).await.unwrap();
+            assert!(stored.iter().any(|item| item.custom_properties.get("P2067").is_some_and(|value| value == "1.5 kg")));
+            log!("[TEST] Re-adding a deleted property - PASSED");
+
+            log!("[TEST] test_property_column_backfill completed successfully");
         }
 
-        // Create the database schema
-        pub async fn create_schema(&self) -> Result<(), Error> {
-            let conn = self.conn.lock().await;
+        // Value sources and refreshing from Wikidata test
+        #[tokio::test]
+        async fn test_refresh_from_wikidata() {
+            log!("[TEST] Starting test_refresh_from_wikidata");
+            let db = create_test_db().await;
+            let test_url = "https://refresh.com";
+            let item = |name: &str, wikidata_id: &str, mass: Option<&str>| Item {
+                id: Uuid::new_v4().to_string(),
+                name: name.into(),
+                description: String::new(),
+                wikidata_id: Some(wikidata_id.into()),
+                custom_properties: mass.map(|mass| ("P2067".to_string(), mass.to_string())).into_iter().collect(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            // Picked from the Wikidata suggestions
+            let filled = Item { sources: HashMap::from([("name".to_string(), ValueSource::Wikidata)]), ..item("Filled", "Q1", None) };
+            let typed = item("Typed", "Q2", Some("2 kg (measured)"));
+            db.insert_items_by_url(test_url, &[filled.clone(), typed.clone()]).await.unwrap();
+            let claims = |mass: &str| {
+                HashMap::from([("Q1", mass), ("Q2", mass)].map(|(id, mass)| {
+                    (id.to_string(), EntityClaims {
+                        values: HashMap::from([("P2067".to_string(), mass.to_string())]),
+                        ..Default::default()
+                    })
+                }))
+            };
 
-            // 1. Properties table
-            conn.execute_batch(
-                "CREATE TABLE IF NOT EXISTS properties (
-                    id INTEGER PRIMARY KEY,
-                    name TEXT NOT NULL UNIQUE, 
-                    global_usage_count INTEGER DEFAULT 0
-                );",
-            )
-            .map_err(|e| {
-                eprintln!("Failed creating properties table: {}", e);
-                e
-            })?;
+            // Test backfilled values are marked as coming from Wikidata, typed ones aren't
+            log!("[TEST] Testing sources");
+            let column = db.add_property_column(test_url, "P2067", &claims("1 kg")).await.unwrap();
+            assert_eq!(column.sources, HashMap::from([(filled.id.clone(), ValueSource::Wikidata)]));
+            let stored = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(stored[0].sources, HashMap::from([
+                ("name".to_string(), ValueSource::Wikidata),
+                ("P2067".to_string(), ValueSource::Wikidata),
+            ]));
+            assert!(stored[1].sources.is_empty());
 
-            // 2. URLs table
-            conn.execute_batch(
-                "CREATE TABLE IF NOT EXISTS urls (
-                    id INTEGER PRIMARY KEY,
-                    url TEXT NOT NULL UNIQUE,
-                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-                );",
-            )
-            .map_err(|e| {
-                eprintln!("Failed creating urls table: {}", e);
-                e
-            })?;
+            // Test saving an unchanged value keeps its source, editing it makes it typed in
+            let mut resaved = stored[0].clone();
+            resaved.sources.clear();
+            db.insert_item_by_url(test_url, &resaved).await.unwrap();
+            assert_eq!(db.get_items_by_url(test_url).await.unwrap()[0].sources["P2067"], ValueSource::Wikidata);
+            log!("[TEST] Sources - PASSED");
 
-            // 3. Items table
-            conn.execute_batch(
-                "CREATE TABLE IF NOT EXISTS items (
-                    id TEXT PRIMARY KEY,
-                    url_id INTEGER NOT NULL,
-                    wikidata_id TEXT,
-                    item_order INTEGER NOT NULL DEFAULT 0,
-                    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE
-                );
-                INSERT OR IGNORE INTO properties (name) VALUES 
-                ('name'), 
-                ('description');",
-            )
-            .map_err(|e| {
-                eprintln!("Failed creating items table: {}", e);
-                e
-            })?;
+            // Test refreshing replaces the names and values from Wikidata and keeps the typed ones
+            log!("[TEST] Testing refresh");
+            let label = EntityTerms { label: Some("Laptop".into()), description: None };
+            let terms = HashMap::from([("Q1".to_string(), label.clone()), ("Q2".to_string(), label)]);
+            let refresh = db.refresh_from_wikidata(test_url, &claims("1.2 kg"), &terms).await.unwrap();
+            assert_eq!(refresh.items.len(), 1);
+            assert_eq!((refresh.items[0].name.as_str(), refresh.items[0].custom_properties["P2067"].as_str()), ("Laptop", "1.2 kg"));
+            assert!(refresh.items[0].updated_at > stored[0].updated_at);
+            let mut changes: Vec<(&str, &str, &str)> = refresh
+                .changes
+                .iter()
+                .map(|change| (change.property.as_str(), change.previous.as_str(), change.value.as_str()))
+                .collect();
+            changes.sort();
+            assert_eq!(changes, [("P2067", "1 kg", "1.2 kg"), ("name", "Filled", "Laptop")]);
+            assert!(refresh.changes.iter().all(|change| change.item_id == filled.id));
+            let stored = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!((stored[1].name.as_str(), stored[1].custom_properties["P2067"].as_str()), ("Typed", "2 kg (measured)"));
+            assert!(db.refresh_from_wikidata(test_url, &claims("1.2 kg"), &terms).await.unwrap().changes.is_empty());
 
-            // Check if the global_item_id column exists
-            let mut stmt = conn.prepare("PRAGMA table_info(items);")?;
-            let columns: Vec<String> = stmt
-                .query_map([], |row| row.get(1))? // Column 1 contains the column names
-                .collect::<Result<_, _>>()?;
+            // Test an edited value, saved without its source like the grid does, is no longer refreshed
+            let mut edited = stored[0].clone();
+            edited.custom_properties.insert("P2067".into(), "1.1 kg".into());
+            edited.sources.remove("P2067");
+            db.insert_item_by_url(test_url, &edited).await.unwrap();
+            assert!(db.refresh_from_wikidata(test_url, &claims("1.3 kg"), &HashMap::new()).await.unwrap().items.is_empty());
+            assert_eq!(db.get_items_by_url(test_url).await.unwrap()[0].custom_properties["P2067"], "1.1 kg");
+            log!("[TEST] Refresh - PASSED");
 
-            if !columns.contains(&"global_item_id".to_string()) {
-                conn.execute_batch(
-                    "ALTER TABLE items ADD COLUMN global_item_id TEXT;"
-                )
-                .map_err(|e| {
-                    eprintln!("Failed adding global_item_id to items table: {}", e);
-                    e
-                })?;
-            }
+            log!("[TEST] test_refresh_from_wikidata completed successfully");
+        }
 
-            // 4. Table for selected properties
-            conn.execute_batch(
-                "CREATE TABLE IF NOT EXISTS selected_properties (
-                    url_id INTEGER NOT NULL,
-                    property_id INTEGER NOT NULL,
-                    PRIMARY KEY (url_id, property_id),
-                    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE,
-                    FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
-                );",
-            )
-            .map_err(|e| {
-                eprintln!("Failed creating properties table: {}", e);
-                e
-            })?;
+        //property reordering test
+        #[tokio::test]
+        async fn test_property_reordering() {
+            log!("[TEST] Starting test_property_reordering");
+            let db = create_test_db().await;
+            let test_url = "https://reorder.com";
 
-            // 5. Junction table for custom properties
-            conn.execute_batch(
-                "CREATE TABLE IF NOT EXISTS item_properties (
-                    global_item_id TEXT NOT NULL,
-                    property_id INTEGER NOT NULL,
-                    value TEXT NOT NULL,
-                    PRIMARY KEY (global_item_id, property_id),
-                    FOREIGN KEY (global_item_id) REFERENCES items(global_item_id) ON DELETE CASCADE,
-                    FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
-                );",
-            )
-            .map_err(|e| {
-                eprintln!("Failed creating item_properties table: {}", e);
-                e
-            })?;
+            db.add_selected_property(test_url, "P1").await.unwrap();
+            db.add_selected_property(test_url, "P2").await.unwrap();
+            db.add_selected_property(test_url, "P3").await.unwrap();
 
-            // 6. Junction table for deleted properties
-            conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS deleted_properties (
-                url_id INTEGER NOT NULL,
-                global_item_id TEXT NOT NULL,
-                property_id INTEGER NOT NULL,
-                PRIMARY KEY (url_id, global_item_id, property_id),
-                FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE,
-                FOREIGN KEY (global_item_id) REFERENCES items(global_item_id) ON DELETE CASCADE,
-                FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
-                );",
-            ).map_err(|e| {
-                eprintln!("Failed creating item_properties table: {}", e);
-                e
-            })?;
+            // Test insertion order is preserved
+            log!("[TEST] Testing default order");
+            let props = db.get_selected_properties(test_url).await.unwrap();
+            assert_eq!(props, vec!["P1", "P2", "P3"]);
+            log!("[TEST] Default order - PASSED");
 
-            Ok(())
+            // Test reordering
+            log!("[TEST] Testing reordering");
+            let new_order = vec!["P3".to_string(), "P1".to_string(), "P2".to_string()];
+            db.reorder_selected_properties(test_url, &new_order).await.unwrap();
+            let props = db.get_selected_properties(test_url).await.unwrap();
+            assert_eq!(props, new_order);
+            log!("[TEST] Reordering - PASSED");
+
+            log!("[TEST] test_property_reordering completed successfully");
         }
 
-        // Insert a new URL into the database
-        pub async fn insert_url(&self, url: &str) -> Result<i64, Error> {
-            let mut conn = self.conn.lock().await;
-            let tx = conn.transaction()?;
+        // Undoing property deletions test
+        #[tokio::test]
+        async fn test_undo_property_deletion() {
+            log!("[TEST] Starting test_undo_property_deletion");
+            let db = create_test_db().await;
+            let test_url = "https://undo.com";
+            let item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Laptop".into(),
+                description: String::new(),
+                wikidata_id: None,
+                custom_properties: HashMap::from([
+                    ("price".to_string(), "1000".to_string()),
+                    ("color".to_string(), "grey".to_string()),
+                    ("weight".to_string(), "1.5 kg".to_string()),
+                ]),
+                quantities: HashMap::from([(
+                    "weight".to_string(),
+                    Quantity { amount: 1.5, unit_id: Some("Q11570".into()), unit: Some("kg".into()), precision: None },
+                )]),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            for property in ["price", "weight", "color"] {
+                db.add_selected_property(test_url, property).await.unwrap();
+            }
 
-            // Use INSERT OR IGNORE to handle duplicates
-            tx.execute("INSERT OR IGNORE INTO urls (url) VALUES (?)", [url])?;
+            // Test the column comes back at its position with its values
+            log!("[TEST] Testing undo");
+            let deletion = db.delete_property_by_url(test_url, "weight").await.unwrap().unwrap();
+            assert_eq!(db.get_selected_properties(test_url).await.unwrap(), vec!["price", "color"]);
+            let column = db.undo_property_deletion(test_url, deletion.deletion_id).await.unwrap().unwrap();
+            assert_eq!(column.property, "weight");
+            assert_eq!(column.values[&item.id], "1.5 kg");
+            assert_eq!(column.quantities[&item.id].unit_id.as_deref(), Some("Q11570"));
+            assert_eq!(db.get_selected_properties(test_url).await.unwrap(), vec!["price", "weight", "color"]);
+            let items = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(items[0].custom_properties["weight"], "1.5 kg");
+            assert_eq!(items[0].quantities["weight"].amount, 1.5);
+            log!("[TEST] Undo - PASSED");
 
-            // Get the URL ID whether it was inserted or already existed
-            let url_id =
-                tx.query_row("SELECT id FROM urls WHERE url = ?", [url], |row| row.get(0))?;
+            // Test a deletion is undone only once, and only for its URL
+            log!("[TEST] Testing spent and foreign deletions");
+            assert!(db.undo_property_deletion(test_url, deletion.deletion_id).await.unwrap().is_none());
+            let deletion = db.delete_property_by_url(test_url, "price").await.unwrap().unwrap();
+            assert!(db.undo_property_deletion("https://other.com", deletion.deletion_id).await.unwrap().is_none());
+            assert!(!db.get_items_by_url(test_url).await.unwrap()[0].custom_properties.contains_key("price"));
+            log!("[TEST] Spent and foreign deletions - PASSED");
 
-            tx.commit()?;
-            logging::log!("URL inserted: {}", url);
-            Ok(url_id)
+            log!("[TEST] test_undo_property_deletion completed successfully");
         }
 
-        pub async fn delete_item(&self, item_id: &str) -> Result<(), Error> {
-            let conn = self.conn.lock().await;
-            conn.execute("DELETE FROM items WHERE id = ?", &[item_id])?;
-            logging::log!("Item deleted: {}", item_id);
-            Ok(())
-        }
+        #[tokio::test]
+        async fn test_rename_property() {
+            log!("[TEST] Starting test_rename_property");
+            let db = create_test_db().await;
+            let test_url = "https://rename.com";
+            let item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Laptop".into(),
+                description: String::new(),
+                wikidata_id: None,
+                custom_properties: HashMap::from([
+                    ("price".to_string(), "1000".to_string()),
+                    ("weight".to_string(), "1.5 kg".to_string()),
+                ]),
+                quantities: HashMap::from([(
+                    "weight".to_string(),
+                    Quantity { amount: 1.5, unit_id: Some("Q11570".into()), unit: Some("kg".into()), precision: None },
+                )]),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            for property in ["price", "weight"] {
+                db.add_selected_property(test_url, property).await.unwrap();
+            }
+            let settings = PropertySettings { lang: Some("de".into()), spellcheck: None, better: None, weight: None };
+            db.set_property_settings(test_url, "weight", &settings).await.unwrap();
+            let version = db.get_items_by_url(test_url).await.unwrap()[0].updated_at;
 
-        pub async fn delete_property(&self, property: &str) -> Result<(), Error> {
-            let conn = self.conn.lock().await;
-            let query = format!(
-                "UPDATE items SET custom_properties = json_remove(custom_properties, '$.{}')",
-                property
-            );
-            conn.execute(&query, []).map_err(|e| Error::from(e))?;
-            logging::log!("Property deleted: {}", property);
-            Ok(())
+            // Test the column keeps its position, values, quantities and settings
+            log!("[TEST] Testing rename");
+            let PropertyRenaming::Renamed(column) = db.rename_property(test_url, "weight", "mass").await.unwrap() else {
+                panic!("Rename was refused");
+            };
+            assert_eq!((column.property.as_str(), column.values[&item.id].as_str()), ("mass", "1.5 kg"));
+            assert_eq!(db.get_selected_properties(test_url).await.unwrap(), vec!["price", "mass"]);
+            let items = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(items[0].custom_properties.get("mass").map(String::as_str), Some("1.5 kg"));
+            assert!(!items[0].custom_properties.contains_key("weight"));
+            assert_eq!(items[0].quantities["mass"].unit_id.as_deref(), Some("Q11570"));
+            assert!(items[0].updated_at > version);
+            assert_eq!(db.get_property_settings(test_url).await.unwrap()["mass"], settings);
+            log!("[TEST] Rename - PASSED");
+
+            // Test names already in use and unknown properties are refused
+            log!("[TEST] Testing refused renames");
+            assert_eq!(db.rename_property(test_url, "mass", "price").await.unwrap(), PropertyRenaming::Taken);
+            assert_eq!(db.rename_property(test_url, "weight", "size").await.unwrap(), PropertyRenaming::Unknown);
+            assert_eq!(db.get_selected_properties(test_url).await.unwrap(), vec!["price", "mass"]);
+            log!("[TEST] Refused renames - PASSED");
+
+            log!("[TEST] test_rename_property completed successfully");
         }
 
-        // Retrieve all items from the database
-        pub async fn get_items(&self) -> Result<Vec<DbItem>, Error> {
-            let conn = self.conn.lock().await;
-            let mut stmt = conn.prepare("SELECT * FROM items;")?;
-            let items = stmt.query_map([], |row| {
-                Ok(DbItem {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    wikidata_id: row.get(3)?,
+        //batch save test
+        #[tokio::test]
+        async fn test_batch_item_insert() {
+            log!("[TEST] Starting test_batch_item_insert");
+            let db = create_test_db().await;
+            let test_url = "https://batch.com";
+            let items: Vec<Item> = (0..3)
+                .map(|i| Item {
+                    id: Uuid::new_v4().to_string(),
+                    name: format!("Batch Item {}", i),
+                    description: "Batch Description".into(),
+                    wikidata_id: None,
+                    custom_properties: vec![("price".into(), format!("{}", i * 10))]
+                        .into_iter()
+                        .collect(),
+                    quantities: HashMap::new(),
+                    group: None,
+                    tip_recipient: None,
+                    status: None,
+                    sources: HashMap::new(),
+                    updated_at: None,
                 })
-            })?;
-            let mut result = Vec::new();
-            for item in items {
-                result.push(item?);
-            }
-            logging::log!("Fetched {} items from the database", result.len()); // Log with Leptos
-            Ok(result)
-        }
+                .collect();
 
-        // Retrieve all items from the database for a specific URL
-        pub async fn get_items_by_url(&self, url: &str) -> Result<Vec<Item>, Error> {
-            let conn = self.conn.lock().await;
-            let url_id: Option<i64> =
-                match conn.query_row("SELECT id FROM urls WHERE url = ?", &[url], |row| {
-                    row.get(0)
-                }) {
-                    Ok(id) => Some(id),
-                    Err(rusqlite::Error::QueryReturnedNoRows) => None,
-                    Err(e) => return Err(e),
-                };
+            // Test batch insertion
+            log!("[TEST] Testing batch insertion");
+            db.insert_items_by_url(test_url, &items).await.unwrap();
+            let stored = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(stored.len(), 3);
+            assert_eq!(stored[2].name, "Batch Item 2");
+            assert_eq!(stored[2].custom_properties.get("price"), Some(&"20".to_string()));
+            log!("[TEST] Batch insertion - PASSED");
 
-            let url_id = match url_id {
-                Some(id) => id,
-                None => return Ok(Vec::new()), // Return empty list if URL not found
+            // Test batch update of existing items
+            log!("[TEST] Testing batch update");
+            let mut updated = items.clone();
+            updated[0].description = "Updated Description".into();
+            db.insert_items_by_url(test_url, &updated[..1]).await.unwrap();
+            let stored = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(stored.len(), 3);
+            assert!(stored.iter().any(|item| item.description == "Updated Description"));
+            log!("[TEST] Batch update - PASSED");
+
+            log!("[TEST] test_batch_item_insert completed successfully");
+        }
+
+        // Column grouping test
+        #[tokio::test]
+        async fn test_item_grouping() {
+            log!("[TEST] Starting test_item_grouping");
+            let db = create_test_db().await;
+            let test_url = "https://groups.com";
+            let mut item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "iPhone 15 128GB".into(),
+                description: "Base storage".into(),
+                wikidata_id: None,
+                custom_properties: HashMap::new(),
+                quantities: HashMap::new(),
+                group: Some("iPhone 15".into()),
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
             };
 
-            log!("Fetching items for URL '{}' (ID: {})", url, url_id);
+            // Test group persistence
+            log!("[TEST] Testing group persistence");
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            let stored = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(stored[0].group.as_deref(), Some("iPhone 15"));
+            log!("[TEST] Group persistence - PASSED");
 
-            let mut stmt = conn.prepare(
-                "WITH ordered_items AS (
-                    SELECT 
-                        i.id,
-                        i.wikidata_id,
-                        i.item_order,
-                        i.global_item_id 
-                    FROM items i
-                    WHERE i.url_id = ?
-                    ORDER BY i.item_order ASC
-                )
-                SELECT
-                    oi.id,
-                    oi.wikidata_id,
-                    name_ip.value AS name,
-                    desc_ip.value AS description,
-                    json_group_object(p.name, ip.value) as custom_properties
-                FROM ordered_items oi
-                LEFT JOIN item_properties ip 
-                    ON oi.global_item_id = ip.global_item_id
-                    AND ip.property_id NOT IN (
-                        SELECT property_id
-                        FROM deleted_properties
-                        WHERE url_id = ? AND global_item_id = oi.global_item_id
-                    )
-                LEFT JOIN properties p 
-                    ON ip.property_id = p.id
-                LEFT JOIN item_properties name_ip 
-                    ON oi.global_item_id = name_ip.global_item_id
-                    AND name_ip.property_id = (SELECT id FROM properties WHERE name = 'name')
-                LEFT JOIN item_properties desc_ip 
-                    ON oi.global_item_id = desc_ip.global_item_id
-                    AND desc_ip.property_id = (SELECT id FROM properties WHERE name = 'description')
-                GROUP BY oi.id
-                ORDER BY oi.item_order ASC"
-            )?;
-        
-            // Change from HashMap to Vec to preserve order
-            let rows = stmt.query_map([url_id, url_id], |row| {
-                  let custom_props_json: String = row.get(4)?;
-                  let custom_properties: HashMap<String, String> = serde_json::from_str(&custom_props_json)
-                      .unwrap_or_default();
-    
-                  Ok(Item {
-                      id: row.get(0)?,
-                      name: row.get::<_, Option<String>>(2)?.unwrap_or_default(), // Handle NULL values for name
-                      description: row.get::<_, Option<String>>(3)?.unwrap_or_default(), // Handle NULL values for description
-                      wikidata_id: row.get(1)?,
-                      custom_properties,
-                  })
-            })?;
-        
-            let mut items = Vec::new();
-            for row in rows {
-                items.push(row?);
-            }
-        
-            Ok(items)
-        }
+            // Test clearing the group
+            log!("[TEST] Testing group removal");
+            item.group = None;
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            let stored = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(stored[0].group, None);
+            log!("[TEST] Group removal - PASSED");
 
-        async fn get_or_create_property(
-            &self,
-            tx: &mut rusqlite::Transaction<'_>,
-            prop: &str,
-        ) -> Result<i64, Error> {
-            match tx.query_row("SELECT id FROM properties WHERE name = ?", [prop], |row| {
-                row.get::<_, i64>(0)
-            }) {
-                Ok(id) => Ok(id),
-                Err(rusqlite::Error::QueryReturnedNoRows) => {
-                    tx.execute("INSERT INTO properties (name) VALUES (?)", [prop])?;
-                    Ok(tx.last_insert_rowid())
-                }
-                Err(e) => Err(e.into()),
-            }
+            log!("[TEST] test_item_grouping completed successfully");
         }
 
-        // Insert a new item into the database for a specific URL
-        pub async fn insert_item_by_url(&self, url: &str, item: &Item) -> Result<(), Error> {
-            log!("[DB] Starting insert for URL: {}, Item: {}", url, item.id);
+        // Tip recipient tests
+        #[tokio::test]
+        async fn test_item_tip_recipient() {
+            log!("[TEST] Starting test_item_tip_recipient");
+            let db = create_test_db().await;
+            let test_url = "https://tips.com";
+            let item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Open hardware board".into(),
+                description: "Made by an independent vendor".into(),
+                wikidata_id: None,
+                custom_properties: HashMap::new(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: Some("vendor@getalby.com".into()),
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
 
-            // 1. Check database lock acquisition
-            let lock_start = std::time::Instant::now();
-            let mut conn = self.conn.lock().await;
-            log!("[DB] Lock acquired in {:?}", lock_start.elapsed());
+            // Test recipient persistence
+            log!("[TEST] Testing tip recipient persistence");
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            let stored = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(stored[0].tip_recipient.as_deref(), Some("vendor@getalby.com"));
+            log!("[TEST] Tip recipient persistence - PASSED");
 
-            // 2. Transaction handling
-            log!("[DB] Starting transaction");
-            let mut tx = conn.transaction().map_err(|e| {
-                log!("[DB] Transaction start failed: {:?}", e);
-                e
-            })?;
+            log!("[TEST] test_item_tip_recipient completed successfully");
+        }
 
-            // 3. URL handling
-            log!("[DB] Checking URL existence: {}", url);
-            let url_id = match tx.query_row("SELECT id FROM urls WHERE url = ?", [url], |row| {
-                row.get::<_, i64>(0)
-            }) {
-                Ok(id) => {
-                    log!("[DB] Found existing URL ID: {}", id);
-                    id
-                }
-                Err(rusqlite::Error::QueryReturnedNoRows) => {
-                    log!("[DB] Inserting new URL");
-                    tx.execute("INSERT INTO urls (url) VALUES (?)", [url])?;
-                    let id = tx.last_insert_rowid();
-                    log!("[DB] Created URL ID: {}", id);
-                    id
-                }
-                Err(e) => return Err(e.into()),
+        // Item status tests
+        #[tokio::test]
+        async fn test_item_status() {
+            log!("[TEST] Starting test_item_status");
+            let db = create_test_db().await;
+            let test_url = "https://status.com";
+            let mut item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Espresso machine".into(),
+                description: "Dual boiler".into(),
+                wikidata_id: None,
+                custom_properties: HashMap::new(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: Some(ItemStatus::Shortlisted),
+                sources: HashMap::new(),
+                updated_at: None,
             };
 
-            // 4. Item insertion
-            let max_order: i32 = tx.query_row(
-                "SELECT COALESCE(MAX(item_order), 0) FROM items WHERE url_id = ?",
-                [url_id],
-                |row| row.get(0),
-            )?;
+            // Test status persistence
+            log!("[TEST] Testing status persistence");
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            let stored = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(stored[0].status, Some(ItemStatus::Shortlisted));
 
-            let global_item_id = match tx.query_row(
-                "SELECT ip.global_item_id
-                 FROM item_properties ip
-                 JOIN properties p ON ip.property_id = p.id
-                 WHERE p.name = 'name' AND ip.value = ? LIMIT 1",
-                [&item.name],
-                |row| row.get::<_, String>(0),
-            ) {
-                Ok(id) => id, // Reuse existing global_item_id
-                Err(rusqlite::Error::QueryReturnedNoRows) => {
-                    let new_id = Uuid::new_v4().to_string(); // Generate a new global_item_id
-                    new_id
-                }
-                Err(e) => return Err(e.into()),
+            // Test changing and clearing the status
+            log!("[TEST] Testing status updates");
+            item.status = Some(ItemStatus::Purchased);
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            assert_eq!(db.get_items_by_url(test_url).await.unwrap()[0].status, Some(ItemStatus::Purchased));
+            item.status = None;
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            assert_eq!(db.get_items_by_url(test_url).await.unwrap()[0].status, None);
+            log!("[TEST] Status updates - PASSED");
+
+            log!("[TEST] test_item_status completed successfully");
+        }
+
+        // Quantity tests
+        #[tokio::test]
+        async fn test_item_quantities() {
+            log!("[TEST] Starting test_item_quantities");
+            let db = create_test_db().await;
+            let test_url = "https://quantities.com";
+            let mass = Quantity {
+                amount: 1.2,
+                unit_id: Some("Q11570".into()),
+                unit: Some("kilogram".into()),
+                precision: Some(0.05),
+            };
+            let mut item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Laptop".into(),
+                description: "Light laptop".into(),
+                wikidata_id: None,
+                custom_properties: vec![
+                    ("P2067".into(), "1.2 kg".into()),
+                    ("color".into(), "silver".into()),
+                ]
+                .into_iter()
+                .collect(),
+                quantities: HashMap::from([("P2067".to_string(), mass.clone())]),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
             };
 
-            log!("[DB] Upserting item");
-            tx.execute(
-                "INSERT INTO items (id, url_id, wikidata_id, item_order, global_item_id)
-                VALUES (?, ?, ?, ?, ?)
-                ON CONFLICT(id) DO UPDATE SET
-                    url_id = excluded.url_id,
-                    wikidata_id = excluded.wikidata_id,
-                    global_item_id = excluded.global_item_id",
-                rusqlite::params![
-                    &item.id,
-                    url_id,
-                    &item.wikidata_id,
-                    max_order + 1,
-                    &global_item_id
-                ],
-            )?;
-            log!("[DB] Item upserted successfully");
+            // Test quantity persistence
+            log!("[TEST] Testing quantity persistence");
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            let stored = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(stored[0].quantities, item.quantities);
+            assert_eq!(stored[0].custom_properties.get("P2067"), Some(&"1.2 kg".to_string()));
+            log!("[TEST] Quantity persistence - PASSED");
 
-            // property handling
-            let core_properties = vec![
-                ("name", &item.name),
-                ("description", &item.description)
-            ];
+            // Test replacing a quantity with plain text
+            log!("[TEST] Testing quantity removal");
+            item.custom_properties.insert("P2067".into(), "about a kilo".into());
+            item.quantities.clear();
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            let stored = db.get_items_by_url(test_url).await.unwrap();
+            assert!(stored[0].quantities.is_empty());
+            log!("[TEST] Quantity removal - PASSED");
 
-            for (prop, value) in core_properties.into_iter().chain(
-                item.custom_properties.iter().map(|(k, v)| (k.as_str(), v))
-            ) {
-                let prop_id = self.get_or_create_property(&mut tx, prop).await?;
-                
-                tx.execute(
-                    "INSERT INTO item_properties (global_item_id, property_id, value)
-                    VALUES (?, ?, ?)
-                    ON CONFLICT(global_item_id, property_id) DO UPDATE SET
-                        value = excluded.value",
-                    rusqlite::params![&global_item_id, prop_id, value],
-                )?;
-            }
+            // Test unit conversion
+            log!("[TEST] Testing unit conversion");
+            assert_eq!(mass.to_text(), "1.2 kg");
+            assert_eq!(mass.convert_to("Q41803").unwrap().to_text(), "1200 g");
+            assert!(mass.convert_to("Q11573").is_none());
+            assert!(mass.is_shown_as("1200 g"));
+            let imperial = mass.in_system(UnitSystem::Imperial).unwrap();
+            assert_eq!(imperial.to_text(), "2.6 lb");
+            assert_eq!(imperial.in_system(UnitSystem::Metric).unwrap().to_text(), "1.2 kg");
+            assert!(mass.in_system(UnitSystem::Metric).is_none());
+            assert!(mass.in_system(UnitSystem::AsEntered).is_none());
+            log!("[TEST] Unit conversion - PASSED");
 
-            // Property synchronization
-            log!("[DB] Synchronizing properties for item {}", item.id);
-            let existing_props = {
-                let mut stmt = tx.prepare(
+            log!("[TEST] test_item_quantities completed successfully");
+        }
+
+        // Edit presence tests
+        #[tokio::test]
+        async fn test_presence_heartbeat_and_expiry() {
+            log!("[TEST] Starting test_presence_heartbeat_and_expiry");
+            let db = create_test_db().await;
+            let test_url = "https://presence.com";
+            let heartbeat = |session: &str, cell: Option<&str>| Heartbeat {
+                session_id: session.into(),
+                cell: cell.map(String::from),
+            };
+
+            // Test lock acquisition
+            log!("[TEST] Testing cell locking");
+            db.record_heartbeat(test_url, &heartbeat("a", Some("item1:name")), 100).await.unwrap();
+            let state = db.record_heartbeat(test_url, &heartbeat("b", Some("item1:name")), 110).await.unwrap();
+            assert_eq!(state.editors, 2);
+            assert_eq!(state.locks, vec![CellLock { cell: "item1:name".into(), session_id: "a".into() }]);
+            log!("[TEST] Cell locking - PASSED");
+
+            // Test metrics
+            log!("[TEST] Testing editor metrics");
+            let metrics = db.editor_metrics().await.unwrap();
+            assert_eq!(metrics.active_editors, 2);
+            assert_eq!(metrics.active_locks, 1);
+            log!("[TEST] Editor metrics - PASSED");
+
+            // Test expiry of the session that stopped sending heartbeats
+            log!("[TEST] Testing stale session expiry");
+            assert_eq!(db.expire_stale_sessions(105).await.unwrap(), 1);
+            let state = db.record_heartbeat(test_url, &heartbeat("b", Some("item1:name")), 120).await.unwrap();
+            assert_eq!(state.editors, 1);
+            assert_eq!(state.locks, vec![CellLock { cell: "item1:name".into(), session_id: "b".into() }]);
+            log!("[TEST] Stale session expiry - PASSED");
+
+            // Test releasing the lock
+            log!("[TEST] Testing lock release");
+            let state = db.record_heartbeat(test_url, &heartbeat("b", None), 130).await.unwrap();
+            assert!(state.locks.is_empty());
+            log!("[TEST] Lock release - PASSED");
+
+            log!("[TEST] test_presence_heartbeat_and_expiry completed successfully");
+        }
+
+        // Workspace backup test
+        #[tokio::test]
+        async fn test_workspace_export_and_restore() {
+            log!("[TEST] Starting test_workspace_export_and_restore");
+            let db = create_test_db().await;
+            let test_url = "https://backup.com";
+            let test_item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Backup Item".into(),
+                description: "Backup Description".into(),
+                wikidata_id: Some("Q42".into()),
+                custom_properties: vec![("P1".into(), "one".into()), ("P2".into(), "two".into())]
+                    .into_iter()
+                    .collect(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url(test_url, &test_item).await.unwrap();
+            db.add_selected_property(test_url, "P2").await.unwrap();
+            db.add_selected_property(test_url, "P1").await.unwrap();
+
+            // Test export
+            log!("[TEST] Testing workspace export");
+            let archive = db.export_workspace(1000).await.unwrap();
+            assert_eq!(archive.comparisons.len(), 1);
+            assert_eq!(archive.comparisons[0].items.len(), 1);
+            assert_eq!(archive.comparisons[0].selected_properties, vec!["P2", "P1"]);
+            log!("[TEST] Workspace export - PASSED");
+
+            // Test restore into an empty database
+            log!("[TEST] Testing workspace restore");
+            let restored_db = create_test_db().await;
+            assert_eq!(restored_db.restore_workspace(&archive).await.unwrap(), 1);
+            let items = restored_db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0].id, test_item.id);
+            assert_eq!(items[0].custom_properties.get("P1"), Some(&"one".to_string()));
+            assert_eq!(restored_db.get_selected_properties(test_url).await.unwrap(), vec!["P2", "P1"]);
+            log!("[TEST] Workspace restore - PASSED");
+
+            log!("[TEST] test_workspace_export_and_restore completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_copy_database() {
+            log!("[TEST] Starting test_copy_database");
+            let db = create_test_db().await;
+            let test_url = "https://backup.com";
+            db.insert_url(test_url).await.unwrap();
+
+            let path = std::env::temp_dir().join(format!("compareware-test-{}.db", Uuid::new_v4()));
+            let path = path.to_str().unwrap();
+            db.copy_database_to(path).await.unwrap();
+            let copy = Database::new(path).unwrap();
+            assert_eq!(copy.get_urls().await.unwrap(), vec![test_url]);
+
+            // Test an existing file is not overwritten
+            assert!(db.copy_database_to(path).await.is_err());
+            drop(copy);
+
+            // Test restoring the copy replaces what changed since
+            db.insert_url("https://later.com").await.unwrap();
+            assert_eq!(database_copy_problem(path), None);
+            db.restore_database_from(path).await.unwrap();
+            assert_eq!(db.get_urls().await.unwrap(), vec![test_url]);
+            assert_eq!(db.schema_version().await.unwrap(), SCHEMA_VERSION);
+
+            // Test files that aren't CompareWare databases are refused, leaving the database as is
+            std::fs::write(path, "not a database").unwrap();
+            assert!(database_copy_problem(path).unwrap().starts_with("Not a readable SQLite database"));
+            assert!(db.restore_database_from(path).await.is_err());
+            std::fs::remove_file(path).unwrap();
+            Connection::open(path).unwrap().execute_batch("CREATE TABLE notes (text TEXT)").unwrap();
+            assert!(database_copy_problem(path).unwrap().starts_with("Not a CompareWare database"));
+            std::fs::remove_file(path).unwrap();
+            Connection::open(path)
+                .unwrap()
+                .execute_batch(&format!("CREATE TABLE urls (id INTEGER); CREATE TABLE schema_version (version INTEGER); INSERT INTO schema_version VALUES ({});", SCHEMA_VERSION + 1))
+                .unwrap();
+            assert!(database_copy_problem(path).unwrap().contains("newer than"));
+            assert_eq!(db.get_urls().await.unwrap(), vec![test_url]);
+            std::fs::remove_file(path).unwrap();
+            log!("[TEST] test_copy_database completed successfully");
+        }
+
+        // Remote comparison reconciliation tests
+        fn remote_comparison(event_id: &str, created_at: i64, relay: &str) -> RemoteComparison {
+            RemoteComparison {
+                address: "30078:author:compareware:laptops".into(),
+                event_id: event_id.into(),
+                author: "author".into(),
+                created_at,
+                content: format!("version {}", event_id),
+                relays: vec![relay.into()],
+            }
+        }
+
+        #[tokio::test]
+        async fn test_remote_comparison_relay_duplicates() {
+            log!("[TEST] Starting test_remote_comparison_relay_duplicates");
+            let db = create_test_db().await;
+
+            // Test first arrival
+            log!("[TEST] Testing first arrival");
+            let update = db.store_remote_comparison(&remote_comparison("e1", 100, "wss://a")).await.unwrap();
+            assert_eq!(update, RemoteUpdate::Inserted);
+            log!("[TEST] First arrival - PASSED");
+
+            // Test the same event from other relays
+            log!("[TEST] Testing duplicate arrivals");
+            for relay in ["wss://b", "wss://a"] {
+                let update = db.store_remote_comparison(&remote_comparison("e1", 100, relay)).await.unwrap();
+                assert_eq!(update, RemoteUpdate::Duplicate);
+            }
+            let stored = db.get_remote_comparisons().await.unwrap();
+            assert_eq!(stored.len(), 1);
+            assert_eq!(stored[0].relays, vec!["wss://a", "wss://b"]);
+            log!("[TEST] Duplicate arrivals - PASSED");
+
+            log!("[TEST] test_remote_comparison_relay_duplicates completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_remote_comparison_versions() {
+            log!("[TEST] Starting test_remote_comparison_versions");
+            let db = create_test_db().await;
+            db.store_remote_comparison(&remote_comparison("e2", 200, "wss://a")).await.unwrap();
+
+            // Test that an older version arriving late is ignored
+            log!("[TEST] Testing stale version");
+            let update = db.store_remote_comparison(&remote_comparison("e1", 100, "wss://b")).await.unwrap();
+            assert_eq!(update, RemoteUpdate::Stale);
+            assert_eq!(db.get_remote_comparisons().await.unwrap()[0].event_id, "e2");
+            log!("[TEST] Stale version - PASSED");
+
+            // Test that a newer version replaces the stored one, relays included
+            log!("[TEST] Testing newer version");
+            let update = db.store_remote_comparison(&remote_comparison("e3", 300, "wss://b")).await.unwrap();
+            assert_eq!(update, RemoteUpdate::Replaced);
+            let stored = db.get_remote_comparisons().await.unwrap();
+            assert_eq!(stored.len(), 1);
+            assert_eq!(stored[0].event_id, "e3");
+            assert_eq!(stored[0].content, "version e3");
+            assert_eq!(stored[0].relays, vec!["wss://b"]);
+            log!("[TEST] Newer version - PASSED");
+
+            // Test the tie-break on equal timestamps: the lowest event ID wins
+            log!("[TEST] Testing timestamp tie-break");
+            let update = db.store_remote_comparison(&remote_comparison("e4", 300, "wss://a")).await.unwrap();
+            assert_eq!(update, RemoteUpdate::Stale);
+            let update = db.store_remote_comparison(&remote_comparison("e0", 300, "wss://a")).await.unwrap();
+            assert_eq!(update, RemoteUpdate::Replaced);
+            assert_eq!(db.get_remote_comparisons().await.unwrap()[0].event_id, "e0");
+            log!("[TEST] Timestamp tie-break - PASSED");
+
+            log!("[TEST] test_remote_comparison_versions completed successfully");
+        }
+
+        // Wikidata cache tests
+        #[tokio::test]
+        async fn test_wikidata_cache_expiry() {
+            log!("[TEST] Starting test_wikidata_cache_expiry");
+            let db = create_test_db().await;
+            let ids = vec!["P2067".to_string(), "Q2".to_string(), "Q11570".to_string()];
+
+            // Test cache hits
+            log!("[TEST] Testing cached labels and claims");
+            let labels = HashMap::from([("P2067".to_string(), "mass".to_string())]);
+            db.cache_labels(&labels, 200, 100).await.unwrap();
+            let claims = HashMap::from([(
+                "Q2".to_string(),
+                EntityClaims {
+                    values: HashMap::from([("P2067".to_string(), "5.97 Yg".to_string())]),
+                    quantities: HashMap::from([(
+                        "P2067".to_string(),
+                        Quantity {
+                            amount: 5.97,
+                            unit_id: Some("Q613726".into()),
+                            unit: Some("yottagram".into()),
+                            precision: None,
+                        },
+                    )]),
+                    images: HashSet::from(["P18".to_string()]),
+                },
+            )]);
+            db.cache_claims(&claims, 200, 100).await.unwrap();
+            assert_eq!(db.get_cached_labels(&ids, 150).await.unwrap(), labels);
+            assert_eq!(db.get_cached_claims(&ids, 150).await.unwrap(), claims);
+            log!("[TEST] Cache hits - PASSED");
+
+            // Test expiry
+            log!("[TEST] Testing expired entries");
+            assert!(db.get_cached_labels(&ids, 200).await.unwrap().is_empty());
+            assert!(db.get_cached_claims(&ids, 250).await.unwrap().is_empty());
+            db.cache_labels(&HashMap::from([("Q11570".to_string(), "kilogram".to_string())]), 400, 300)
+                .await
+                .unwrap();
+            let stored: i64 = db
+                .conn
+                .lock()
+                .await
+                .query_row("SELECT COUNT(*) FROM wikidata_labels", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(stored, 1);
+            log!("[TEST] Expired entries - PASSED");
+
+            log!("[TEST] test_wikidata_cache_expiry completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_wikidata_proxy_memory_cache() {
+            use crate::wikidata_proxy::{LruCache, RateLimiter, WikidataProxy};
+            log!("[TEST] Starting test_wikidata_proxy_memory_cache");
+            let db = create_test_db().await;
+            let proxy = WikidataProxy::new(RateLimiter::new(2, 3), Default::default());
+            let ids = vec!["P2067".to_string()];
+
+            // Test the in-memory cache in front of SQLite
+            log!("[TEST] Testing memory cache");
+            let labels = HashMap::from([("P2067".to_string(), "mass".to_string())]);
+            proxy.cache_labels(&db, &labels, 10_000, 100).await.unwrap();
+            db.conn.lock().await.execute("DELETE FROM wikidata_labels", []).unwrap();
+            assert_eq!(proxy.cached_labels(&db, &ids, 150).await.unwrap(), labels);
+            assert!(proxy.cached_labels(&db, &ids, 100 + 3_600).await.unwrap().is_empty());
+            log!("[TEST] Memory cache - PASSED");
+
+            // Test LRU eviction
+            log!("[TEST] Testing LRU eviction");
+            let mut lru = LruCache::new(2);
+            lru.insert("a", 1);
+            lru.insert("b", 2);
+            assert_eq!(lru.get(&"a"), Some(&1));
+            lru.insert("c", 3);
+            assert_eq!(lru.len(), 2);
+            assert_eq!(lru.get(&"b"), None);
+            assert_eq!(lru.get(&"a"), Some(&1));
+            log!("[TEST] LRU eviction - PASSED");
+
+            // Test per-client and global rate limits
+            log!("[TEST] Testing rate limits");
+            assert!(proxy.limiter.check("10.0.0.1").await.is_ok());
+            assert!(proxy.limiter.check("10.0.0.1").await.is_ok());
+            assert!(proxy.limiter.check("10.0.0.1").await.is_err());
+            assert!(proxy.limiter.check("10.0.0.2").await.is_ok());
+            let wait = proxy.limiter.check("10.0.0.3").await.unwrap_err();
+            assert!(wait.as_secs() <= 20);
+            log!("[TEST] Rate limits - PASSED");
+
+            log!("[TEST] test_wikidata_proxy_memory_cache completed successfully");
+        }
+
+        // Directory listing tests
+        #[tokio::test]
+        async fn test_comparison_listing() {
+            log!("[TEST] Starting test_comparison_listing");
+            let db = create_test_db().await;
+            let test_url = "https://listed.com/laptops";
+            db.insert_url(test_url).await.unwrap();
+
+            // Test listing and relisting
+            log!("[TEST] Testing listing updates");
+            assert_eq!(db.get_listing(test_url).await.unwrap(), None);
+            let mut listing = ComparisonListing {
+                title: "Laptops".into(),
+                category: "Hardware".into(),
+            };
+            db.set_listing(test_url, &listing, 100).await.unwrap();
+            listing.title = "Ultrabooks".into();
+            db.set_listing(test_url, &listing, 200).await.unwrap();
+            assert_eq!(db.get_listing(test_url).await.unwrap(), Some(listing));
+            log!("[TEST] Listing updates - PASSED");
+
+            // Test unlisting
+            log!("[TEST] Testing unlisting");
+            assert!(db.remove_listing(test_url).await.unwrap());
+            assert!(!db.remove_listing(test_url).await.unwrap());
+            assert_eq!(db.get_listing(test_url).await.unwrap(), None);
+            log!("[TEST] Unlisting - PASSED");
+
+            // Test listing an unknown URL
+            log!("[TEST] Testing unknown URL");
+            assert!(db.set_listing("https://unknown.com", &ComparisonListing {
+                title: "Unknown".into(),
+                category: String::new(),
+            }, 100).await.is_err());
+            log!("[TEST] Unknown URL - PASSED");
+
+            log!("[TEST] test_comparison_listing completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_reusable_values() {
+            log!("[TEST] Starting test_reusable_values");
+            let db = create_test_db().await;
+            let (laptops, ultrabooks, travel) = ("https://example.com/laptops", "https://example.com/ultrabooks", "https://example.com/travel");
+            let laptop = |id: &str, mass: &str| Item {
+                id: id.into(),
+                name: format!("Laptop {}", id),
+                description: String::new(),
+                wikidata_id: Some("Q3962".into()),
+                custom_properties: HashMap::from([("P2067".into(), mass.into()), ("notes".into(), "Loud fan".into())]),
+                quantities: HashMap::from([("P2067".into(), Quantity { amount: 1.4, unit_id: Some("Q11570".into()), unit: None, precision: None })]),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url(laptops, &laptop("a", "1.4 kg")).await.unwrap();
+            db.insert_item_by_url(ultrabooks, &laptop("b", "1.4 kg")).await.unwrap();
+            for url in [laptops, ultrabooks] {
+                db.add_selected_property(url, "P2067").await.unwrap();
+            }
+            db.add_selected_property(laptops, "notes").await.unwrap();
+
+            // Test the comparison asking is left out, values hidden by a comparison too
+            let reusable = db.get_reusable_values(travel, "Q3962").await.unwrap().unwrap();
+            assert_eq!(reusable.comparisons, 2);
+            assert_eq!(reusable.values.get("P2067").map(String::as_str), Some("1.4 kg"));
+            assert_eq!(reusable.values.get("notes").map(String::as_str), Some("Loud fan"));
+            assert_eq!(reusable.quantities["P2067"].unit_id.as_deref(), Some("Q11570"));
+            assert!(!reusable.values.contains_key("name"));
+            let reusable = db.get_reusable_values(laptops, "Q3962").await.unwrap().unwrap();
+            assert_eq!(reusable.comparisons, 1);
+            assert!(!reusable.values.contains_key("notes"));
+
+            // Test an entity no comparison has
+            assert!(db.get_reusable_values(travel, "Q42").await.unwrap().is_none());
+            log!("[TEST] test_reusable_values completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_property_settings() {
+            log!("[TEST] Starting test_property_settings");
+            let db = create_test_db().await;
+            let test_url = "https://example.com/laptops";
+            let item = Item {
+                id: "item1".into(),
+                name: "Laptop".into(),
+                description: String::new(),
+                wikidata_id: None,
+                custom_properties: HashMap::from([("P1324".into(), "XPS-13".into())]),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            assert!(db.get_property_settings(test_url).await.unwrap().is_empty());
+
+            // Test settings of a custom and a core property
+            let model_numbers = PropertySettings { lang: None, spellcheck: Some(false), better: Some(BetterValue::Lower), weight: Some(2.5) };
+            let descriptions = PropertySettings { lang: Some("de".into()), spellcheck: Some(true), better: None, weight: None };
+            assert!(db.set_property_settings(test_url, "P1324", &model_numbers).await.unwrap());
+            assert!(db.set_property_settings(test_url, "description", &descriptions).await.unwrap());
+            let settings = db.get_property_settings(test_url).await.unwrap();
+            assert_eq!(settings.len(), 2);
+            assert_eq!(settings["P1324"], model_numbers);
+            assert_eq!(settings["description"], descriptions);
+
+            // Test default settings are not kept
+            assert!(db.set_property_settings(test_url, "P1324", &PropertySettings::default()).await.unwrap());
+            assert!(!db.get_property_settings(test_url).await.unwrap().contains_key("P1324"));
+
+            // Test unknown URLs and properties
+            assert!(!db.set_property_settings("https://unknown.com", "P1324", &model_numbers).await.unwrap());
+            assert!(!db.set_property_settings(test_url, "P9999", &model_numbers).await.unwrap());
+            log!("[TEST] test_property_settings completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_property_overrides() {
+            log!("[TEST] Starting test_property_overrides");
+            let db = create_test_db().await;
+            let test_url = "https://example.com/scales";
+            db.add_selected_property(test_url, "P2067").await.unwrap();
+            db.add_selected_property(test_url, "capacity").await.unwrap();
+            assert!(db.get_property_overrides(test_url).await.unwrap().is_empty());
+
+            // Test labels and tooltips are kept per URL
+            let weight = PropertyOverride { label: Some("Weight (g)".into()), description: Some("Without batteries".into()) };
+            assert!(db.set_property_override(test_url, "P2067", &weight).await.unwrap());
+            assert!(db.set_property_override(test_url, "capacity", &PropertyOverride { label: Some("Max load".into()), description: None }).await.unwrap());
+            let overrides = db.get_property_overrides(test_url).await.unwrap();
+            assert_eq!(overrides.len(), 2);
+            assert_eq!(overrides["P2067"], weight);
+            db.add_selected_property("https://example.com/other", "P2067").await.unwrap();
+            assert!(db.get_property_overrides("https://example.com/other").await.unwrap().is_empty());
+
+            // Test they follow renamed properties, and empty overrides are not kept
+            db.rename_property(test_url, "capacity", "load").await.unwrap();
+            assert_eq!(db.get_property_overrides(test_url).await.unwrap()["load"].label.as_deref(), Some("Max load"));
+            assert!(db.set_property_override(test_url, "P2067", &PropertyOverride::default()).await.unwrap());
+            assert!(!db.get_property_overrides(test_url).await.unwrap().contains_key("P2067"));
+
+            // Test unknown URLs and properties
+            assert!(!db.set_property_override("https://unknown.com", "P2067", &weight).await.unwrap());
+            assert!(!db.set_property_override(test_url, "P9999", &weight).await.unwrap());
+            log!("[TEST] test_property_overrides completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_publishing_settings() {
+            log!("[TEST] Starting test_publishing_settings");
+            let db = create_test_db().await;
+            let test_url = "https://example.com/laptops";
+            db.insert_url(test_url).await.unwrap();
+
+            // Test comparisons don't publish until told to
+            assert_eq!(db.get_publishing_settings(test_url).await.unwrap(), PublishingSettings::default());
+
+            // Test enabling and disabling, the relays are kept
+            let mut settings = PublishingSettings { enabled: true, relays: vec!["wss://relay.damus.io".into(), "wss://nos.lol".into()] };
+            db.set_publishing_settings(test_url, &settings).await.unwrap();
+            assert_eq!(db.get_publishing_settings(test_url).await.unwrap(), settings);
+            settings.enabled = false;
+            db.set_publishing_settings(test_url, &settings).await.unwrap();
+            assert_eq!(db.get_publishing_settings(test_url).await.unwrap(), settings);
+
+            // Test an unknown URL
+            assert!(db.set_publishing_settings("https://unknown.com", &settings).await.is_err());
+            log!("[TEST] test_publishing_settings completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_mirror_settings() {
+            log!("[TEST] Starting test_mirror_settings");
+            let db = create_test_db().await;
+            let test_url = "https://example.com/laptops";
+            db.insert_url(test_url).await.unwrap();
+            assert_eq!(db.get_mirror_settings(test_url).await.unwrap(), MirrorSettings::default());
+
+            let settings = MirrorSettings {
+                enabled: true,
+                source_url: "/laptops".into(),
+                author: "a".repeat(64),
+                relays: vec!["wss://relay.damus.io".into()],
+            };
+            db.set_mirror_settings(test_url, &settings).await.unwrap();
+            assert_eq!(db.get_mirror_settings(test_url).await.unwrap(), settings);
+            assert_eq!(db.get_enabled_mirrors().await.unwrap(), vec![(test_url.to_string(), settings.clone())]);
+
+            // Test disabled mirrors are not started
+            db.set_mirror_settings(test_url, &MirrorSettings { enabled: false, ..settings }).await.unwrap();
+            assert!(db.get_enabled_mirrors().await.unwrap().is_empty());
+            log!("[TEST] test_mirror_settings completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_merge_mirrored_items() {
+            log!("[TEST] Starting test_merge_mirrored_items");
+            let db = create_test_db().await;
+            let test_url = "https://example.com/laptops";
+            db.insert_url(test_url).await.unwrap();
+            let settings = MirrorSettings {
+                enabled: true,
+                source_url: "/laptops".into(),
+                author: "a".repeat(64),
+                relays: vec!["wss://relay.damus.io".into()],
+            };
+            db.set_mirror_settings(test_url, &settings).await.unwrap();
+            let mut received = Item {
+                id: "remote1".into(),
+                name: "Mirrored laptop".into(),
+                description: "From another instance".into(),
+                wikidata_id: None,
+                custom_properties: HashMap::from([("P1324".into(), "XPS-13".into())]),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+
+            // Test a new item gets a local ID and selects its properties
+            let MirrorUpdate::Saved { item, added_properties } = db.merge_mirrored_item(test_url, &received, 100).await.unwrap() else {
+                panic!("New item was not saved");
+            };
+            assert_ne!(item.id, "remote1");
+            assert_eq!(added_properties, ["P1324"]);
+            let local_id = item.id.clone();
+
+            // Test older or repeated versions are ignored
+            assert!(matches!(db.merge_mirrored_item(test_url, &received, 100).await.unwrap(), MirrorUpdate::Stale));
+            assert!(matches!(db.merge_mirrored_item(test_url, &received, 50).await.unwrap(), MirrorUpdate::Stale));
+            assert!(matches!(db.merge_mirrored_item(test_url, &received, 150).await.unwrap(), MirrorUpdate::Stale));
+
+            // Test a newer version updates the same local item
+            received.custom_properties.insert("P1324".into(), "XPS-15".into());
+            let MirrorUpdate::Saved { item, added_properties } = db.merge_mirrored_item(test_url, &received, 200).await.unwrap() else {
+                panic!("Newer version was not saved");
+            };
+            assert_eq!(item.id, local_id);
+            assert!(added_properties.is_empty());
+            let items = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(items.len(), 1);
+            assert_eq!(items[0].custom_properties["P1324"], "XPS-15");
+
+            // Test local changes win over later versions
+            let mut edited = items[0].clone();
+            edited.description = "Edited here".into();
+            db.insert_item_by_url(test_url, &edited).await.unwrap();
+            received.description = "Edited there".into();
+            assert!(matches!(db.merge_mirrored_item(test_url, &received, 300).await.unwrap(), MirrorUpdate::Conflict));
+            assert_eq!(db.get_items_by_url(test_url).await.unwrap()[0].description, "Edited here");
+
+            // Test mirroring another comparison forgets the merged items
+            db.set_mirror_settings(test_url, &MirrorSettings { source_url: "/phones".into(), ..settings }).await.unwrap();
+            assert!(matches!(db.merge_mirrored_item(test_url, &received, 300).await.unwrap(), MirrorUpdate::Saved { .. }));
+            assert_eq!(db.get_items_by_url(test_url).await.unwrap().len(), 2);
+            log!("[TEST] test_merge_mirrored_items completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_instance_settings() {
+            log!("[TEST] Starting test_instance_settings");
+            let db = create_test_db().await;
+            assert!(db.get_instance_settings().await.unwrap().is_none());
+
+            let mut settings = InstanceSettings {
+                wikidata_client_rpm: 5,
+                wikidata_global_rpm: 30,
+                wikidata_language: "de".into(),
+                private: true,
+                features: Default::default(),
+            };
+            settings.features.tips = false;
+            db.set_instance_settings(&settings).await.unwrap();
+            assert_eq!(db.get_instance_settings().await.unwrap(), Some(settings.clone()));
+
+            // Test saving again replaces the single row
+            settings.private = false;
+            db.set_instance_settings(&settings).await.unwrap();
+            assert_eq!(db.get_instance_settings().await.unwrap(), Some(settings));
+            log!("[TEST] test_instance_settings completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_complete_setup() {
+            log!("[TEST] Starting test_complete_setup");
+            let db = create_test_db().await;
+            assert!(db.needs_setup().await.unwrap());
+            assert!(db.get_admin_token_hash().await.unwrap().is_none());
+
+            let settings = InstanceSettings {
+                wikidata_client_rpm: 5,
+                wikidata_global_rpm: 30,
+                wikidata_language: "fr".into(),
+                private: true,
+                features: Default::default(),
+            };
+            assert!(db.complete_setup("hash", &settings, 100).await.unwrap());
+            assert_eq!(db.get_admin_token_hash().await.unwrap().as_deref(), Some("hash"));
+            assert_eq!(db.get_instance_settings().await.unwrap(), Some(settings.clone()));
+            assert!(!db.needs_setup().await.unwrap());
+
+            // Test a second setup changes nothing
+            assert!(!db.complete_setup("other", &settings, 200).await.unwrap());
+            assert_eq!(db.get_admin_token_hash().await.unwrap().as_deref(), Some("hash"));
+
+            // Test instances already holding comparisons are never offered the setup
+            let db = create_test_db().await;
+            db.insert_url("https://existing.com").await.unwrap();
+            assert!(!db.needs_setup().await.unwrap());
+            assert!(!db.complete_setup("hash", &settings, 100).await.unwrap());
+            log!("[TEST] test_complete_setup completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_link_signing_key() {
+            log!("[TEST] Starting test_link_signing_key");
+            let db = create_test_db().await;
+            assert!(db.get_link_signing_key().await.unwrap().is_none());
+            assert_eq!(db.link_signing_key("first", 100).await.unwrap(), "first");
+            // Test the key is kept, so links signed earlier stay valid
+            assert_eq!(db.link_signing_key("second", 200).await.unwrap(), "first");
+            assert_eq!(db.get_link_signing_key().await.unwrap().as_deref(), Some("first"));
+            log!("[TEST] test_link_signing_key completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_api_keys() {
+            log!("[TEST] Starting test_api_keys");
+            let db = create_test_db().await;
+            let request = ApiKeyRequest { name: " Importer ".into(), url: Some("https://example.com/laptops".into()) };
+            let key = db.create_api_key(&request, "hash1", 100).await.unwrap();
+            assert_eq!(key.name, "Importer");
+            db.create_api_key(&ApiKeyRequest { name: "Sync".into(), url: None }, "hash2", 200).await.unwrap();
+            let names: Vec<String> = db.list_api_keys().await.unwrap().into_iter().map(|key| key.name).collect();
+            assert_eq!(names, ["Sync", "Importer"]);
+
+            // Test using a key records when, at most once a minute
+            assert_eq!(db.use_api_key("hash1", 1000).await.unwrap().unwrap().last_used_at, None);
+            db.use_api_key("hash1", 1030).await.unwrap();
+            assert_eq!(db.use_api_key("hash1", 1040).await.unwrap().unwrap().last_used_at, Some(1000));
+            assert!(db.use_api_key("unknown", 1000).await.unwrap().is_none());
+
+            // Test revoked keys stop working
+            assert!(db.delete_api_key(key.id).await.unwrap());
+            assert!(!db.delete_api_key(key.id).await.unwrap());
+            assert!(db.use_api_key("hash1", 2000).await.unwrap().is_none());
+            log!("[TEST] test_api_keys completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_audit_log() {
+            log!("[TEST] Starting test_audit_log");
+            let db = create_test_db().await;
+            assert_eq!(db.schema_version().await.unwrap(), SCHEMA_VERSION);
+            assert!(db.get_audit_tail(10).await.unwrap().is_empty());
+
+            for (now, action) in [(100, "backup"), (200, "settings"), (300, "restore")] {
+                db.record_audit(action, "detail", now).await.unwrap();
+            }
+            // Test the tail keeps the latest entries in the order they happened
+            let tail = db.get_audit_tail(2).await.unwrap();
+            let actions: Vec<&str> = tail.iter().map(|entry| entry.action.as_str()).collect();
+            assert_eq!(actions, ["settings", "restore"]);
+            assert_eq!(tail[1].created_at, 300);
+            log!("[TEST] test_audit_log completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_item_history() {
+            log!("[TEST] Starting test_item_history");
+            let db = create_test_db().await;
+            let test_url = "https://history.com";
+            let mut item = Item {
+                id: "laptop".into(),
+                name: "Laptop".into(),
+                description: String::new(),
+                wikidata_id: None,
+                custom_properties: HashMap::new(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            db.record_revisions(test_url, "owner", &[HistoryChange::ItemSaved { item: item.clone() }], 100).await.unwrap();
+            item.name = "Gaming laptop".into();
+            let changes = [
+                HistoryChange::ItemSaved { item: item.clone() },
+                HistoryChange::PropertyAdded { property: "P2067".into() },
+            ];
+            db.record_revisions(test_url, "anonymous", &changes, 200).await.unwrap();
+            db.record_revisions(test_url, "owner", &[HistoryChange::ItemDeleted { item_id: item.id.clone() }], 300).await.unwrap();
+
+            // Test the history lists the newest changes first, with their versions of the item
+            let history = db.get_history(test_url, None, None).await.unwrap();
+            assert_eq!(history.len(), 4);
+            assert!(matches!(&history[0].change, HistoryChange::ItemDeleted { item_id } if item_id == "laptop"));
+            assert!(matches!(&history[1].change, HistoryChange::PropertyAdded { property } if property == "P2067"));
+            assert_eq!((history[2].actor.as_str(), history[2].created_at), ("anonymous", 200));
+            assert_eq!(history[3].change.item().unwrap().name, "Laptop");
+
+            // Test filtering by item and paging with `before`
+            let of_item = db.get_history(test_url, Some("laptop"), None).await.unwrap();
+            assert_eq!(of_item.len(), 3);
+            let older = db.get_history(test_url, Some("laptop"), Some(history[1].id)).await.unwrap();
+            let names: Vec<&str> = older.iter().filter_map(|revision| revision.change.item()).map(|item| item.name.as_str()).collect();
+            assert_eq!(names, ["Gaming laptop", "Laptop"]);
+            assert!(db.get_history("https://other.com", None, None).await.unwrap().is_empty());
+            log!("[TEST] test_item_history completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_database_pragmas() {
+            log!("[TEST] Starting test_database_pragmas");
+            let dir = std::env::temp_dir().join(format!("compareware-{}", Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("pragmas.db");
+
+            // A database created before foreign keys were enforced
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE long_values (
+                    global_item_id TEXT NOT NULL,
+                    property_id INTEGER NOT NULL,
+                    value TEXT NOT NULL,
+                    PRIMARY KEY (global_item_id, property_id),
+                    FOREIGN KEY (global_item_id) REFERENCES items(global_item_id) ON DELETE CASCADE,
+                    FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
+                );
+                INSERT INTO long_values VALUES ('global1', 1, 'kept');",
+            )
+            .unwrap();
+            drop(conn);
+
+            let db = Database::new(path.to_str().unwrap()).unwrap();
+            db.create_schema().await.unwrap();
+            {
+                let conn = db.conn.lock().await;
+                let journal_mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+                assert_eq!(journal_mode, "wal");
+                let foreign_keys: bool = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0)).unwrap();
+                assert!(foreign_keys);
+
+                // Test the rebuilt table kept its rows and only references properties
+                let value: String = conn.query_row("SELECT value FROM long_values", [], |row| row.get(0)).unwrap();
+                assert_eq!(value, "kept");
+                let referenced: Vec<String> = conn
+                    .prepare("SELECT \"table\" FROM pragma_foreign_key_list('long_values')")
+                    .unwrap()
+                    .query_map([], |row| row.get(0))
+                    .unwrap()
+                    .collect::<Result<_, _>>()
+                    .unwrap();
+                assert_eq!(referenced, ["properties"]);
+            }
+
+            // Test values can be saved with foreign keys enforced
+            let item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Long".into(),
+                description: String::new(),
+                wikidata_id: None,
+                custom_properties: HashMap::from([("notes".to_string(), "x".repeat(PREVIEW_LENGTH * 2))]),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url("https://pragmas.com", &item).await.unwrap();
+            db.checkpoint().await.unwrap();
+            assert_eq!(std::fs::metadata(dir.join("pragmas.db-wal")).unwrap().len(), 0);
+            std::fs::remove_dir_all(&dir).unwrap();
+            log!("[TEST] test_database_pragmas completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_database_readers() {
+            log!("[TEST] Starting test_database_readers");
+            let dir = std::env::temp_dir().join(format!("compareware-{}", Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("readers.db");
+            let db = Database::with_readers(path.to_str().unwrap(), 2).unwrap();
+            db.create_schema().await.unwrap();
+            assert_eq!(db.readers.len(), 2);
+
+            // Test the readers see what the writer committed, even while all of them are busy
+            let item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Read".into(),
+                description: String::new(),
+                wikidata_id: None,
+                custom_properties: HashMap::new(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url("https://readers.com", &item).await.unwrap();
+            let busy = db.readers[0].lock().await;
+            assert_eq!(db.get_items_by_url("https://readers.com").await.unwrap()[0].name, "Read");
+            drop(busy);
+
+            // Test readers can't write
+            let reader = db.reader().await;
+            assert!(reader.execute("DELETE FROM items", []).is_err());
+            drop(reader);
+
+            // Test in-memory databases read through the writer
+            assert!(Database::with_readers(":memory:", 2).unwrap().readers.is_empty());
+            std::fs::remove_dir_all(&dir).unwrap();
+            log!("[TEST] test_database_readers completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_migrations() {
+            log!("[TEST] Starting test_migrations");
+            let db = create_test_db().await;
+            let test_url = "https://migrations.com";
+            let item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Migrated item".into(),
+                description: String::new(),
+                wikidata_id: None,
+                custom_properties: HashMap::from([("weight".to_string(), "1 kg".to_string())]),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            // Test starting again applies nothing
+            db.create_schema().await.unwrap();
+            assert_eq!(db.schema_version().await.unwrap(), SCHEMA_VERSION);
+
+            // Test a migration after the current ones rebuilds a table and keeps its rows
+            log!("[TEST] Testing an upgrade from the current schema");
+            let upgrade = Migration {
+                version: SCHEMA_VERSION + 1,
+                name: "item_notes",
+                sql: "CREATE TABLE items_rebuilt (
+                        id TEXT PRIMARY KEY,
+                        url_id INTEGER NOT NULL,
+                        wikidata_id TEXT,
+                        item_order INTEGER NOT NULL DEFAULT 0,
+                        global_item_id TEXT,
+                        item_group TEXT,
+                        tip_recipient TEXT,
+                        deleted_at INTEGER,
+                        status TEXT,
+                        updated_at INTEGER,
+                        notes TEXT NOT NULL DEFAULT '',
+                        FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE
+                    );
+                    INSERT INTO items_rebuilt (id, url_id, wikidata_id, item_order, global_item_id, item_group, tip_recipient, deleted_at, status, updated_at)
+                        SELECT id, url_id, wikidata_id, item_order, global_item_id, item_group, tip_recipient, deleted_at, status, updated_at FROM items;
+                    DROP TABLE items;
+                    ALTER TABLE items_rebuilt RENAME TO items;",
+            };
+            let upgraded: Vec<Migration> = MIGRATIONS.iter().copied().chain([upgrade]).collect();
+            {
+                let mut conn = db.conn.lock().await;
+                assert_eq!(migrate(&mut conn, &upgraded).unwrap(), [SCHEMA_VERSION + 1]);
+                assert!(migrate(&mut conn, &upgraded).unwrap().is_empty());
+                let foreign_keys: bool = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0)).unwrap();
+                assert!(foreign_keys);
+            }
+            assert_eq!(db.schema_version().await.unwrap(), SCHEMA_VERSION + 1);
+            let items = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(items[0].custom_properties.get("weight"), Some(&"1 kg".to_string()));
+            // Reviews still reference the rebuilt items table
+            db.set_review(test_url, &item.id, "alice", 4, 100).await.unwrap();
+
+            // Test a database migrated by a newer build is refused
+            {
+                let mut conn = db.conn.lock().await;
+                assert!(migrate(&mut conn, MIGRATIONS).is_err());
+            }
+
+            // Test failing migrations leave no trace
+            log!("[TEST] Testing failing migrations");
+            let failing = [
+                Migration { version: SCHEMA_VERSION + 2, name: "broken", sql: "CREATE TABLE half_done (id INTEGER); SELECT nonsense FROM nowhere;" },
+                Migration { version: SCHEMA_VERSION + 2, name: "orphans", sql: "INSERT INTO items (id, url_id) VALUES ('orphan', 999999);" },
+            ];
+            for migration in failing {
+                let migrations: Vec<Migration> = upgraded.iter().copied().chain([migration]).collect();
+                let mut conn = db.conn.lock().await;
+                assert!(migrate(&mut conn, &migrations).is_err());
+                let half_done: bool = conn
+                    .query_row("SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'half_done')", [], |row| row.get(0))
+                    .unwrap();
+                assert!(!half_done);
+                let orphans: i64 = conn.query_row("SELECT COUNT(*) FROM items WHERE id = 'orphan'", [], |row| row.get(0)).unwrap();
+                assert_eq!(orphans, 0);
+            }
+            assert_eq!(db.schema_version().await.unwrap(), SCHEMA_VERSION + 1);
+            log!("[TEST] test_migrations completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_item_values_per_comparison() {
+            log!("[TEST] Starting test_item_values_per_comparison");
+            let db = create_test_db().await;
+            let item = |id: &str, weight: &str| Item {
+                id: id.into(),
+                name: "Shared laptop".into(),
+                description: String::new(),
+                wikidata_id: None,
+                custom_properties: HashMap::from([("weight".to_string(), weight.to_string())]),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url("https://first.com", &item("first1", "1 kg")).await.unwrap();
+            db.insert_item_by_url("https://second.com", &item("second1", "2 kg")).await.unwrap();
+
+            // Items of the same name in two comparisons, sharing their values as they did before
+            {
+                let conn = db.conn.lock().await;
+                conn.execute_batch(
+                    "DELETE FROM item_properties WHERE global_item_id = (SELECT global_item_id FROM items WHERE id = 'second1');
+                    UPDATE items SET global_item_id = (SELECT global_item_id FROM items WHERE id = 'first1') WHERE id = 'second1';",
+                )
+                .unwrap();
+                conn.execute_batch(MIGRATIONS[MIGRATIONS.len() - 1].sql).unwrap();
+            }
+
+            // Test each comparison has a copy of the values and edits it alone
+            assert_eq!(db.get_items_by_url("https://second.com").await.unwrap()[0].custom_properties["weight"], "1 kg");
+            db.insert_item_by_url("https://second.com", &item("second1", "3 kg")).await.unwrap();
+            assert_eq!(db.get_items_by_url("https://first.com").await.unwrap()[0].custom_properties["weight"], "1 kg");
+            assert_eq!(db.get_items_by_url("https://second.com").await.unwrap()[0].custom_properties["weight"], "3 kg");
+            log!("[TEST] test_item_values_per_comparison completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_legacy_schema_adoption() {
+            log!("[TEST] Starting test_legacy_schema_adoption");
+            let db = Database::new(":memory:").unwrap();
+            {
+                // A database from before migrations, created by early create_schema steps
+                let conn = db.conn.lock().await;
+                conn.execute_batch(
+                    "CREATE TABLE properties (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE, global_usage_count INTEGER DEFAULT 0);
+                    CREATE TABLE urls (id INTEGER PRIMARY KEY, url TEXT NOT NULL UNIQUE, created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP);
+                    CREATE TABLE items (
+                        id TEXT PRIMARY KEY,
+                        url_id INTEGER NOT NULL,
+                        wikidata_id TEXT,
+                        item_order INTEGER NOT NULL DEFAULT 0,
+                        FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE
+                    );
+                    INSERT INTO urls (id, url) VALUES (1, 'https://legacy.com');
+                    INSERT INTO items (id, url_id, wikidata_id) VALUES ('legacy1', 1, 'Q42');
+                    PRAGMA user_version = 3;",
+                )
+                .unwrap();
+            }
+            db.create_schema().await.unwrap();
+            assert_eq!(db.schema_version().await.unwrap(), SCHEMA_VERSION);
+
+            // Test the later columns were added and the rows kept
+            {
+                let conn = db.conn.lock().await;
+                let title: String = conn.query_row("SELECT title FROM urls WHERE id = 1", [], |row| row.get(0)).unwrap();
+                assert_eq!(title, "");
+                let (wikidata_id, group): (String, Option<String>) = conn
+                    .query_row("SELECT wikidata_id, item_group FROM items WHERE id = 'legacy1'", [], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .unwrap();
+                assert_eq!((wikidata_id.as_str(), group), ("Q42", None));
+            }
+            let metadata = ComparisonMetadata { title: "Legacy".into(), description: String::new(), ..Default::default() };
+            db.set_metadata("https://legacy.com", &metadata).await.unwrap();
+            assert_eq!(db.get_metadata("https://legacy.com").await.unwrap().title, "Legacy");
+            log!("[TEST] test_legacy_schema_adoption completed successfully");
+        }
+
+        // Review tests
+        #[tokio::test]
+        async fn test_item_reviews() {
+            log!("[TEST] Starting test_item_reviews");
+            let db = create_test_db().await;
+            let test_url = "https://reviews.com";
+            let item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Reviewed item".into(),
+                description: String::new(),
+                wikidata_id: None,
+                custom_properties: HashMap::new(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+
+            // Test aggregation, rating again replaces the earlier rating
+            log!("[TEST] Testing rating aggregation");
+            db.set_review(test_url, &item.id, "alice", 2, 100).await.unwrap();
+            db.set_review(test_url, &item.id, "alice", 4, 200).await.unwrap();
+            db.set_review(test_url, &item.id, "bob", 5, 300).await.unwrap();
+            let summaries = db.get_rating_summaries(test_url, Some("alice")).await.unwrap();
+            assert_eq!(summaries[&item.id], RatingSummary { average: 4.5, count: 2, mine: Some(4) });
+            let summaries = db.get_rating_summaries(test_url, None).await.unwrap();
+            assert_eq!(summaries[&item.id].mine, None);
+            let reviews = db.get_recent_reviews(test_url, 10).await.unwrap();
+            let listed: Vec<_> = reviews.iter().map(|review| (review.reviewer.as_str(), review.rating)).collect();
+            assert_eq!(listed, [("bob", 5), ("alice", 4)]);
+            assert_eq!(reviews[0].item_name, "Reviewed item");
+            assert_eq!(db.get_recent_reviews(test_url, 1).await.unwrap().len(), 1);
+            let exported: Vec<_> = db.get_reviews(test_url).await.unwrap().into_iter().map(|review| (review.reviewer, review.created_at)).collect();
+            assert_eq!(exported, [("alice".to_string(), 200), ("bob".to_string(), 300)]);
+            log!("[TEST] Rating aggregation - PASSED");
+
+            // Test ratings of unknown items and out-of-range ratings
+            log!("[TEST] Testing invalid reviews");
+            assert!(matches!(
+                db.set_review("https://other.com", &item.id, "alice", 3, 100).await,
+                Err(Error::QueryReturnedNoRows)
+            ));
+            assert!(db.set_review(test_url, &item.id, "carol", 6, 100).await.is_err());
+            log!("[TEST] Invalid reviews - PASSED");
+
+            // Test reviews are hidden with their item and come back when it's restored
+            log!("[TEST] Testing review removal");
+            db.delete_item_by_url(test_url, &item.id).await.unwrap();
+            assert!(db.get_rating_summaries(test_url, None).await.unwrap().is_empty());
+            assert!(db.get_recent_reviews(test_url, 10).await.unwrap().is_empty());
+            assert!(db.get_reviews(test_url).await.unwrap().is_empty());
+            assert!(matches!(db.set_review(test_url, &item.id, "alice", 3, 100).await, Err(Error::QueryReturnedNoRows)));
+            db.restore_item(test_url, &item.id).await.unwrap();
+            assert_eq!(db.get_rating_summaries(test_url, None).await.unwrap()[&item.id].count, 2);
+            log!("[TEST] Review removal - PASSED");
+
+            log!("[TEST] test_item_reviews completed successfully");
+        }
+
+        // Edit protection tests
+        #[tokio::test]
+        async fn test_edit_access() {
+            log!("[TEST] Starting test_edit_access");
+            let db = create_test_db().await;
+            let test_url = "https://protected.com";
+
+            // Test open comparisons
+            log!("[TEST] Testing unprotected comparison");
+            let access = db.get_edit_access(test_url, None).await.unwrap();
+            assert_eq!(access, EditAccess { protected: false, role: None, visibility: Visibility::Public });
+            assert!(access.can_edit());
+            log!("[TEST] Unprotected comparison - PASSED");
+
+            // Test protecting, which only the first claim may do
+            log!("[TEST] Testing protection");
+            assert!(db.protect_comparison(test_url, "owner-hash").await.unwrap());
+            assert!(!db.protect_comparison(test_url, "other-hash").await.unwrap());
+            let owner = db.get_edit_access(test_url, Some("owner-hash")).await.unwrap();
+            assert_eq!(owner.role, Some(EditorRole::Owner));
+            let visitor = db.get_edit_access(test_url, Some("other-hash")).await.unwrap();
+            assert_eq!(visitor, EditAccess { protected: true, role: None, visibility: Visibility::Public });
+            assert!(!visitor.can_edit());
+            log!("[TEST] Protection - PASSED");
+
+            // Test collaborators
+            log!("[TEST] Testing collaborators");
+            db.add_editor(test_url, "collaborator-hash", EditorRole::Collaborator, 300).await.unwrap();
+            let collaborator = db.get_edit_access(test_url, Some("collaborator-hash")).await.unwrap();
+            assert_eq!(collaborator.role, Some(EditorRole::Collaborator));
+            assert!(collaborator.can_edit());
+            assert!(!db.protect_comparison(test_url, "collaborator-hash").await.unwrap());
+            log!("[TEST] Collaborators - PASSED");
+
+            // Test claiming new URLs on their first write
+            log!("[TEST] Testing claims");
+            let claimed_url = "https://claimed.com";
+            assert!(db.claim_url(claimed_url, "creator-hash").await.unwrap());
+            assert!(!db.claim_url(claimed_url, "late-hash").await.unwrap());
+            assert!(!db.claim_url(test_url, "late-hash").await.unwrap());
+            let creator = db.get_edit_access(claimed_url, Some("creator-hash")).await.unwrap();
+            assert_eq!(creator, EditAccess { protected: true, role: Some(EditorRole::Owner), visibility: Visibility::Public });
+            assert!(!db.get_edit_access(claimed_url, Some("late-hash")).await.unwrap().can_edit());
+            log!("[TEST] Claims - PASSED");
+
+            // Test private comparisons are only read by their editors
+            log!("[TEST] Testing visibility");
+            assert!(db.set_visibility(test_url, Visibility::Private).await.unwrap());
+            assert!(!db.set_visibility("https://unknown.com", Visibility::Private).await.unwrap());
+            let visitor = db.get_edit_access(test_url, None).await.unwrap();
+            assert_eq!(visitor.visibility, Visibility::Private);
+            assert!(!visitor.can_read());
+            assert!(db.get_edit_access(test_url, Some("collaborator-hash")).await.unwrap().can_read());
+            db.set_visibility(test_url, Visibility::Unlisted).await.unwrap();
+            assert!(db.get_edit_access(test_url, None).await.unwrap().can_read());
+            log!("[TEST] Visibility - PASSED");
+
+            log!("[TEST] test_edit_access completed successfully");
+        }
+
+        // Spec card tests
+        #[tokio::test]
+        async fn test_spec_card() {
+            log!("[TEST] Starting test_spec_card");
+            let db = create_test_db().await;
+            let laptop = |name: &str, properties: &[(&str, &str)]| Item {
+                id: Uuid::new_v4().to_string(),
+                name: name.into(),
+                description: String::new(),
+                wikidata_id: Some("Q1".into()),
+                custom_properties: properties.iter().map(|(property, value)| (property.to_string(), value.to_string())).collect(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url("https://a.com", &laptop("MacBook Air", &[("price", "1000"), ("weight", "1.2 kg")]))
+                .await
+                .unwrap();
+            db.insert_item_by_url("https://b.com", &laptop("MacBook Air M2", &[("price", "1100")])).await.unwrap();
+            db.insert_item_by_url("https://c.com", &laptop("MacBook Air M2", &[("price", "1100")])).await.unwrap();
+            for (url, property) in [("https://a.com", "price"), ("https://a.com", "weight"), ("https://b.com", "price"), ("https://c.com", "price")] {
+                db.add_selected_property(url, property).await.unwrap();
+            }
+
+            // Test the values most comparisons agree on win
+            log!("[TEST] Testing aggregation");
+            let card = db.get_spec_card("Q1").await.unwrap().unwrap();
+            assert_eq!(card.name, "MacBook Air M2");
+            assert_eq!(card.comparisons, 3);
+            assert_eq!(card.specs.len(), 2);
+            assert_eq!((card.specs[0].property.as_str(), card.specs[0].value.as_str()), ("price", "1100"));
+            assert_eq!(card.specs[0].comparisons, 2);
+            assert_eq!((card.specs[1].property.as_str(), card.specs[1].value.as_str()), ("weight", "1.2 kg"));
+            log!("[TEST] Aggregation - PASSED");
+
+            // Test deleted properties are left out
+            log!("[TEST] Testing deleted properties");
+            db.delete_property_by_url("https://a.com", "weight").await.unwrap();
+            let card = db.get_spec_card("Q1").await.unwrap().unwrap();
+            assert!(card.specs.iter().all(|spec| spec.property != "weight"));
+            assert!(db.get_spec_card("Q2").await.unwrap().is_none());
+            log!("[TEST] Deleted properties - PASSED");
+            log!("[TEST] test_spec_card completed successfully");
+        }
+
+        // Long value tests
+        #[tokio::test]
+        async fn test_long_values() {
+            log!("[TEST] Starting test_long_values");
+            let db = create_test_db().await;
+            let test_url = "https://long-values.com";
+            let long_value = "Lorem ipsum ".repeat(100);
+            let mut item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Laptop".into(),
+                description: "Short".into(),
+                wikidata_id: None,
+                custom_properties: HashMap::from([
+                    ("notes".to_string(), long_value.clone()),
+                    ("price".to_string(), "1000".to_string()),
+                ]),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+
+            // Test the grid gets a preview and the API the full text
+            log!("[TEST] Testing previews");
+            let preview = value_preview(&long_value).unwrap();
+            let (previews, truncated) = db.get_item_previews_by_url(test_url).await.unwrap();
+            assert_eq!(previews[0].custom_properties["notes"], preview);
+            assert_eq!(previews[0].custom_properties["price"], "1000");
+            assert_eq!(truncated[&item.id], HashSet::from(["notes".to_string()]));
+            let items = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(items[0].custom_properties["notes"], long_value);
+            assert_eq!(
+                db.get_property_value(test_url, &item.id, "notes").await.unwrap(),
+                Some(long_value.clone())
+            );
+            assert_eq!(db.get_property_value(test_url, &item.id, "weight").await.unwrap(), None);
+            log!("[TEST] Previews - PASSED");
+
+            // Test saving the preview back keeps the full text
+            log!("[TEST] Testing unchanged previews");
+            db.insert_items_by_url(test_url, &previews).await.unwrap();
+            let items = db.get_items_by_url(test_url).await.unwrap();
+            assert_eq!(items[0].custom_properties["notes"], long_value);
+            log!("[TEST] Unchanged previews - PASSED");
+
+            // Test a shorter value replaces the long one
+            log!("[TEST] Testing shortened values");
+            item.custom_properties.insert("notes".into(), "Fast".into());
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            let (previews, truncated) = db.get_item_previews_by_url(test_url).await.unwrap();
+            assert_eq!(previews[0].custom_properties["notes"], "Fast");
+            assert!(truncated.is_empty());
+            assert_eq!(db.get_items_by_url(test_url).await.unwrap()[0].custom_properties["notes"], "Fast");
+            log!("[TEST] Shortened values - PASSED");
+
+            log!("[TEST] test_long_values completed successfully");
+        }
+
+        // Snapshot tests
+        #[tokio::test]
+        async fn test_snapshots() {
+            log!("[TEST] Starting test_snapshots");
+            let db = create_test_db().await;
+            let test_url = "https://snapshots.com";
+            let mut item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Laptop".into(),
+                description: "Before".into(),
+                wikidata_id: None,
+                custom_properties: HashMap::from([("price".to_string(), "1000".to_string())]),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+
+            // Test storing and reading back a snapshot
+            log!("[TEST] Testing snapshot round trip");
+            let snapshot = Snapshot {
+                id: "snapshot-1".into(),
+                url: test_url.into(),
+                created_at: 100,
+                items: db.get_items_by_url(test_url).await.unwrap(),
+                selected_properties: vec!["price".into()],
+                property_labels: HashMap::from([("P18".to_string(), "image".to_string())]),
+            };
+            db.insert_snapshot(&snapshot).await.unwrap();
+            let stored = db.get_snapshot("snapshot-1").await.unwrap().unwrap();
+            assert_eq!(stored.url, test_url);
+            assert_eq!(stored.created_at, 100);
+            assert_eq!(stored.items.len(), 1);
+            assert_eq!(stored.items[0].custom_properties["price"], "1000");
+            assert_eq!(stored.selected_properties, vec!["price".to_string()]);
+            assert_eq!(stored.property_labels["P18"], "image");
+            log!("[TEST] Snapshot round trip - PASSED");
+
+            // Test snapshots don't follow later edits
+            log!("[TEST] Testing snapshot immutability");
+            item.description = "After".into();
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            db.delete_item_by_url(test_url, &item.id).await.unwrap();
+            let stored = db.get_snapshot("snapshot-1").await.unwrap().unwrap();
+            assert_eq!(stored.items[0].description, "Before");
+            assert!(db.insert_snapshot(&snapshot).await.is_err());
+            log!("[TEST] Snapshot immutability - PASSED");
+
+            assert!(db.get_snapshot("unknown").await.unwrap().is_none());
+            log!("[TEST] test_snapshots completed successfully");
+        }
+
+        // Comparison metadata tests
+        #[tokio::test]
+        async fn test_comparison_metadata() {
+            log!("[TEST] Starting test_comparison_metadata");
+            let db = create_test_db().await;
+            let test_url = "https://example.com/laptops";
+            assert_eq!(db.get_metadata(test_url).await.unwrap(), ComparisonMetadata::default());
+
+            // Test storing registers the URL
+            log!("[TEST] Testing metadata round trip");
+            let metadata = ComparisonMetadata { title: "Laptops".into(), description: "Light laptops".into(), ..Default::default() };
+            db.set_metadata(test_url, &metadata).await.unwrap();
+            assert!(db.url_exists(test_url).await.unwrap());
+            assert_eq!(db.get_metadata(test_url).await.unwrap(), metadata);
+            log!("[TEST] Metadata round trip - PASSED");
+
+            // Test the title takes precedence over the directory listing on the index
+            log!("[TEST] Testing index titles");
+            db.set_listing(test_url, &ComparisonListing { title: "Listed laptops".into(), category: String::new() }, 1)
+                .await
+                .unwrap();
+            let index = db.get_comparison_index("light", false, 1, 20).await.unwrap();
+            assert_eq!(index.total, 0);
+            let index = db.get_comparison_index("listed", false, 1, 20).await.unwrap();
+            assert_eq!(index.comparisons[0].title, "Laptops");
+            log!("[TEST] Index titles - PASSED");
+            log!("[TEST] test_comparison_metadata completed successfully");
+        }
+
+        // Comparison handle tests
+        #[tokio::test]
+        async fn test_comparison_handle() {
+            log!("[TEST] Starting test_comparison_handle");
+            let db = create_test_db().await;
+            let laptops = "https://example.com/laptops";
+            let phones = "https://example.com/phones";
+            assert!(db.comparison(laptops).await.unwrap().is_none());
+
+            let item = |name: &str| Item {
+                id: Uuid::new_v4().to_string(),
+                name: name.into(),
+                description: String::new(),
+                wikidata_id: None,
+                custom_properties: HashMap::from([("price".into(), "100".into())]),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_url(laptops).await.unwrap();
+            db.insert_item_by_url(phones, &item("Phone")).await.unwrap();
+            db.add_selected_property(laptops, "price").await.unwrap();
+
+            // Test the handle only sees the items of its URL
+            log!("[TEST] Testing scoped items");
+            let comparison = db.comparison(laptops).await.unwrap().unwrap();
+            assert_eq!(comparison.url(), laptops);
+            assert!(comparison.items().await.unwrap().is_empty());
+            let laptop = item("Laptop");
+            comparison.insert_items(&[laptop.clone(), item("Tablet")]).await.unwrap();
+            let items = comparison.items().await.unwrap();
+            assert_eq!(items.iter().map(|item| item.name.as_str()).collect::<Vec<_>>(), ["Laptop", "Tablet"]);
+            assert_eq!(db.get_items_by_url(laptops).await.unwrap(), items);
+            assert_eq!(comparison.properties().await.unwrap(), ["price"]);
+            assert_eq!(comparison.metadata().await.unwrap(), ComparisonMetadata::default());
+            log!("[TEST] Scoped items - PASSED");
+
+            // Test items of another URL are out of reach
+            log!("[TEST] Testing deletion scope");
+            let phone_id = db.get_items_by_url(phones).await.unwrap()[0].id.clone();
+            assert_eq!(comparison.delete_item(&phone_id).await.unwrap(), 0);
+            assert_eq!(db.get_items_by_url(phones).await.unwrap().len(), 1);
+            assert_eq!(comparison.delete_item(&laptop.id).await.unwrap(), 1);
+            assert_eq!(comparison.items().await.unwrap().len(), 1);
+            log!("[TEST] Deletion scope - PASSED");
+            log!("[TEST] test_comparison_handle completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_comparison_rows() {
+            log!("[TEST] Starting test_comparison_rows");
+            let db = create_test_db().await;
+            let laptops = "https://example.com/laptops";
+            let item = |name: &str, status: Option<ItemStatus>| Item {
+                id: Uuid::new_v4().to_string(),
+                name: name.into(),
+                description: "Laptop".into(),
+                wikidata_id: None,
+                custom_properties: HashMap::from([("price".into(), "100".into())]),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            let trashed = item("Old laptop", None);
+            for item in [item("ThinkPad", None), item("Latitude", Some(ItemStatus::Rejected)), trashed.clone()] {
+                db.insert_item_by_url(laptops, &item).await.unwrap();
+            }
+            db.add_selected_property(laptops, "price").await.unwrap();
+            db.delete_item_by_url(laptops, &trashed.id).await.unwrap();
+            db.insert_url("https://example.com/empty").await.unwrap();
+
+            // Test items in the trash and their values are counted apart
+            let rows = db.comparison_rows().await.unwrap();
+            assert_eq!(rows.len(), 2);
+            assert_eq!(
+                rows[0],
+                ComparisonRows {
+                    url: laptops.into(),
+                    items: 2,
+                    inactive_items: 1,
+                    trashed_items: 1,
+                    properties: 1,
+                    values: 6,
+                    revisions: 0,
+                }
+            );
+            assert_eq!(rows[1], ComparisonRows { url: "https://example.com/empty".into(), ..Default::default() });
+            log!("[TEST] test_comparison_rows completed successfully");
+        }
+
+        #[tokio::test]
+        async fn test_save_items_by_url() {
+            log!("[TEST] Starting test_save_items_by_url");
+            let db = create_test_db().await;
+            let url = "https://example.com/phones";
+            let item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Pixel".into(),
+                description: "Phone".into(),
+                wikidata_id: None,
+                custom_properties: HashMap::new(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            let ItemsSave::Saved(saved) = db.save_items_by_url(url, &[item.clone()], None).await.unwrap() else {
+                panic!("New items are never stale");
+            };
+            let first = saved[0].updated_at.expect("Saved items have a version");
+
+            // Test saving the current version bumps it, even within the same millisecond
+            let mut mine = saved[0].clone();
+            mine.description = "Android phone".into();
+            let ItemsSave::Saved(saved) = db.save_items_by_url(url, &[mine.clone()], None).await.unwrap() else {
+                panic!("Save of the current version was refused");
+            };
+            assert!(saved[0].updated_at.unwrap() > first);
+
+            // Test a save based on the older version is refused and returns the stored copy
+            mine.description = "Old phone".into();
+            let ItemsSave::Stale(stored) = db.save_items_by_url(url, &[mine], None).await.unwrap() else {
+                panic!("Stale save was accepted");
+            };
+            assert_eq!(stored, saved);
+            assert_eq!(db.get_items_by_url(url).await.unwrap()[0].description, "Android phone");
+
+            // Test the time limit applies to items without a version
+            let since = saved[0].updated_at.unwrap() - 1;
+            assert!(matches!(db.save_items_by_url(url, &[item.clone()], Some(since)).await.unwrap(), ItemsSave::Stale(_)));
+            assert!(matches!(db.save_items_by_url(url, &[item.clone()], Some(since + 1)).await.unwrap(), ItemsSave::Saved(_)));
+
+            // Test the item's ID is refused under another URL, where saving it would move it
+            let other = "https://example.com/other-phones";
+            let ItemsSave::Taken(taken) = db.save_items_by_url(other, &[item.clone()], None).await.unwrap() else {
+                panic!("Item of another URL was saved");
+            };
+            assert_eq!(taken, [item.id.clone()]);
+            assert!(db.insert_item_by_url(other, &item).await.is_err());
+            assert_eq!(db.get_items_by_url(url).await.unwrap().len(), 1);
+
+            // Test an item of the same name in another URL has values of its own
+            let namesake = Item { id: Uuid::new_v4().to_string(), description: "Someone else's phone".into(), ..item };
+            db.save_items_by_url(other, &[namesake], None).await.unwrap();
+            assert_eq!(db.get_items_by_url(url).await.unwrap()[0].description, "Phone");
+            log!("[TEST] test_save_items_by_url completed successfully");
+        }
+
+        // Decision tests
+        #[tokio::test]
+        async fn test_comparison_decision() {
+            log!("[TEST] Starting test_comparison_decision");
+            let db = create_test_db().await;
+            let test_url = "https://decisions.com/laptops";
+            let item = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "Laptop".into(),
+                description: String::new(),
+                wikidata_id: None,
+                custom_properties: HashMap::new(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url(test_url, &item).await.unwrap();
+            db.insert_item_by_url("https://decisions.com/open", &Item { id: Uuid::new_v4().to_string(), ..item.clone() })
+                .await
+                .unwrap();
+
+            // Test deciding for an item of the comparison
+            log!("[TEST] Testing decisions");
+            let decision = Decision { item_id: item.id.clone(), decided_at: 1718500000, rationale: "Lightest".into() };
+            assert!(!db.set_decision(test_url, &Decision { item_id: "unknown".into(), ..decision.clone() }).await.unwrap());
+            assert!(db.set_decision(test_url, &decision).await.unwrap());
+            let metadata = db.get_metadata(test_url).await.unwrap();
+            assert_eq!(metadata.decision, Some(decision.clone()));
+
+            // Saving the title and description keeps the decision
+            db.set_metadata(test_url, &ComparisonMetadata { title: "Laptops".into(), ..Default::default() }).await.unwrap();
+            assert_eq!(db.get_metadata(test_url).await.unwrap().decision, Some(decision.clone()));
+            log!("[TEST] Decisions - PASSED");
+
+            // Test the index lists decided comparisons apart
+            log!("[TEST] Testing decided comparisons on the index");
+            let index = db.get_comparison_index("", true, 1, 20).await.unwrap();
+            assert_eq!(index.total, 1);
+            assert_eq!(index.comparisons[0].decided_at, Some(1718500000));
+            assert_eq!(db.get_comparison_index("", false, 1, 20).await.unwrap().total, 2);
+            log!("[TEST] Decided comparisons - PASSED");
+
+            // Test archives carry the decision
+            log!("[TEST] Testing decisions in archives");
+            let archive = db.export_workspace(1000).await.unwrap();
+            let restored = create_test_db().await;
+            restored.restore_workspace(&archive).await.unwrap();
+            assert_eq!(restored.get_metadata(test_url).await.unwrap().decision, Some(decision));
+            log!("[TEST] Decisions in archives - PASSED");
+
+            // Test reopening
+            assert!(db.clear_decision(test_url).await.unwrap());
+            assert!(!db.clear_decision(test_url).await.unwrap());
+            assert_eq!(db.get_metadata(test_url).await.unwrap().decision, None);
+            log!("[TEST] test_comparison_decision completed successfully");
+        }
+
+        // Comparison index tests
+        #[tokio::test]
+        async fn test_comparison_index() {
+            log!("[TEST] Starting test_comparison_index");
+            let db = create_test_db().await;
+            let item = |name: &str| Item {
+                id: Uuid::new_v4().to_string(),
+                name: name.into(),
+                description: String::new(),
+                wikidata_id: None,
+                custom_properties: HashMap::new(),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            let laptops = "https://example.com/laptops";
+            let phones = "https://example.com/phones_100%";
+            db.insert_items_by_url(laptops, &[item("Laptop A"), item("Laptop B")]).await.unwrap();
+            db.insert_item_by_url(phones, &item("Phone")).await.unwrap();
+            db.insert_url("https://example.com/").await.unwrap();
+            db.set_listing(laptops, &ComparisonListing { title: "Best laptops".into(), category: String::new() }, 1)
+                .await
+                .unwrap();
+
+            // Test writes record when a comparison was last updated
+            log!("[TEST] Testing last updated times");
+            {
+                let conn = db.conn.lock().await;
+                conn.execute("UPDATE urls SET updated_at = 10000 WHERE url = ?", [laptops]).unwrap();
+                conn.execute("UPDATE urls SET updated_at = 20000 WHERE url = ?", [phones]).unwrap();
+                conn.execute("UPDATE urls SET created_at = '2024-01-01 00:00:00' WHERE updated_at IS NULL", []).unwrap();
+            }
+            db.add_selected_property(laptops, "price").await.unwrap();
+            let index = db.get_comparison_index("", false, 1, 20).await.unwrap();
+            assert_eq!(index.total, 3);
+            assert_eq!(index.comparisons[0].url, laptops);
+            assert!(index.comparisons[0].updated_at > 20);
+            assert_eq!(index.comparisons[0].title, "Best laptops");
+            assert_eq!(index.comparisons[0].item_count, 2);
+            assert!(index.comparisons.iter().any(|comparison| comparison.title == "/phones_100%"));
+            assert!(index.comparisons.iter().any(|comparison| comparison.title == "example.com"));
+            // Versions of the URLs grow with every change, even within a millisecond
+            let version = db.get_url_version(laptops).await.unwrap().unwrap();
+            db.add_selected_property(laptops, "weight").await.unwrap();
+            db.add_selected_property(laptops, "color").await.unwrap();
+            assert!(db.get_url_version(laptops).await.unwrap().unwrap() >= version + 2);
+            assert_eq!(db.get_url_version("https://example.com/unknown").await.unwrap(), None);
+            log!("[TEST] Last updated times - PASSED");
+
+            // Test search by title and URL, with LIKE wildcards taken literally
+            log!("[TEST] Testing search");
+            let titles = |index: ComparisonIndex| index.comparisons.into_iter().map(|c| c.url).collect::<Vec<_>>();
+            assert_eq!(titles(db.get_comparison_index("best", false, 1, 20).await.unwrap()), vec![laptops.to_string()]);
+            assert_eq!(titles(db.get_comparison_index("phones", false, 1, 20).await.unwrap()), vec![phones.to_string()]);
+            assert_eq!(titles(db.get_comparison_index("0%", false, 1, 20).await.unwrap()), vec![phones.to_string()]);
+            assert_eq!(db.get_comparison_index("s_1", false, 1, 20).await.unwrap().total, 1);
+            assert_eq!(db.get_comparison_index("%", false, 1, 20).await.unwrap().total, 1);
+            log!("[TEST] Search - PASSED");
+
+            // Test pagination
+            log!("[TEST] Testing pagination");
+            let second_page = db.get_comparison_index("", false, 2, 2).await.unwrap();
+            assert_eq!(second_page.total, 3);
+            assert_eq!(second_page.page_count(), 2);
+            assert_eq!(second_page.comparisons.len(), 1);
+            assert!(db.get_comparison_index("", false, 3, 2).await.unwrap().comparisons.is_empty());
+            log!("[TEST] Pagination - PASSED");
+
+            // Test only public comparisons are listed
+            log!("[TEST] Testing visibility");
+            db.set_visibility(phones, Visibility::Unlisted).await.unwrap();
+            db.set_visibility(laptops, Visibility::Private).await.unwrap();
+            assert_eq!(titles(db.get_comparison_index("", false, 1, 20).await.unwrap()), vec!["https://example.com/".to_string()]);
+            log!("[TEST] Visibility - PASSED");
+            log!("[TEST] test_comparison_index completed successfully");
+        }
+
+        // Full-text search tests
+        #[tokio::test]
+        async fn test_search_items() {
+            log!("[TEST] Starting test_search_items");
+            let db = create_test_db().await;
+            let laptops = "https://example.com/laptops";
+            let mut laptop = Item {
+                id: Uuid::new_v4().to_string(),
+                name: "ThinkPad X1".into(),
+                description: "Light business laptop".into(),
+                wikidata_id: None,
+                custom_properties: HashMap::from([
+                    ("notes".to_string(), format!("{} with a matte display", "Long review. ".repeat(30))),
+                    ("hidden".to_string(), "unselected keyboard".to_string()),
+                ]),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            };
+            db.insert_item_by_url(laptops, &laptop).await.unwrap();
+            db.add_selected_property(laptops, "notes").await.unwrap();
+
+            // Test names, descriptions and the full text of long values are found by word prefixes
+            log!("[TEST] Testing matches");
+            let hits = db.search_items("thinkp", 10).await.unwrap();
+            assert_eq!(hits.len(), 1);
+            assert_eq!((hits[0].url.as_str(), hits[0].item_id.as_str()), (laptops, laptop.id.as_str()));
+            assert_eq!((hits[0].item_name.as_str(), hits[0].property.as_str(), hits[0].title.as_str()), ("ThinkPad X1", "name", "/laptops"));
+            assert!(hits[0].snippet.iter().any(|part| part.matched && part.text == "ThinkPad"));
+            assert_eq!(db.search_items("business LAPTOP", 10).await.unwrap()[0].property, "description");
+            assert_eq!(db.search_items("matte", 10).await.unwrap()[0].property, "notes");
+            log!("[TEST] Matches - PASSED");
+
+            // Test properties the comparison doesn't show aren't searched, and query syntax is taken as words
+            log!("[TEST] Testing what isn't found");
+            assert!(db.search_items("keyboard", 10).await.unwrap().is_empty());
+            assert_eq!(db.search_items("\"ThinkPad: (x1)* -", 10).await.unwrap().len(), 1);
+            assert!(db.search_items(" *-\" ", 10).await.unwrap().is_empty());
+            log!("[TEST] What isn't found - PASSED");
+
+            // Test the index follows edits and deletions
+            log!("[TEST] Testing index updates");
+            laptop.name = "Latitude 7440".into();
+            laptop.custom_properties.insert("notes".into(), "Short".into());
+            db.insert_item_by_url(laptops, &laptop).await.unwrap();
+            assert!(db.search_items("thinkpad", 10).await.unwrap().is_empty());
+            assert!(db.search_items("matte", 10).await.unwrap().is_empty());
+            assert_eq!(db.search_items("latitude", 10).await.unwrap().len(), 1);
+            db.delete_item_by_url(laptops, &laptop.id).await.unwrap();
+            assert!(db.search_items("latitude", 10).await.unwrap().is_empty());
+            log!("[TEST] Index updates - PASSED");
+            log!("[TEST] test_search_items completed successfully");
+        }
+    }
+
+    // Schema change applied once on startup, recorded in the schema_version table
+    #[derive(Clone, Copy)]
+    struct Migration {
+        version: i64,
+        name: &'static str,
+        sql: &'static str,
+    }
+
+    // Migrations in the order they apply. Released migrations never change, schema
+    // changes are new migrations at the end. Versions continue the numbered steps
+    // create_schema took before migrations existed
+    const MIGRATIONS: &[Migration] = &[
+        Migration { version: 21, name: "baseline", sql: include_str!("migrations/0021_baseline.sql") },
+        Migration { version: 22, name: "item_trash", sql: include_str!("migrations/0022_item_trash.sql") },
+        Migration { version: 23, name: "item_revisions", sql: include_str!("migrations/0023_item_revisions.sql") },
+        Migration { version: 24, name: "admin_token", sql: include_str!("migrations/0024_admin_token.sql") },
+        Migration { version: 25, name: "unit_system", sql: include_str!("migrations/0025_unit_system.sql") },
+        Migration { version: 26, name: "item_status", sql: include_str!("migrations/0026_item_status.sql") },
+        Migration { version: 27, name: "item_search", sql: include_str!("migrations/0027_item_search.sql") },
+        Migration { version: 28, name: "link_signing_key", sql: include_str!("migrations/0028_link_signing_key.sql") },
+        Migration { version: 29, name: "item_versions", sql: include_str!("migrations/0029_item_versions.sql") },
+        Migration { version: 30, name: "api_keys", sql: include_str!("migrations/0030_api_keys.sql") },
+        Migration { version: 31, name: "property_overrides", sql: include_str!("migrations/0031_property_overrides.sql") },
+        Migration { version: 32, name: "value_sources", sql: include_str!("migrations/0032_value_sources.sql") },
+        Migration { version: 33, name: "property_better_values", sql: include_str!("migrations/0033_property_better_values.sql") },
+        Migration { version: 34, name: "property_weights", sql: include_str!("migrations/0034_property_weights.sql") },
+        Migration { version: 35, name: "comparison_visibility", sql: include_str!("migrations/0035_comparison_visibility.sql") },
+        Migration { version: 36, name: "url_versions", sql: include_str!("migrations/0036_url_versions.sql") },
+        Migration { version: 37, name: "item_values_per_comparison", sql: include_str!("migrations/0037_item_values_per_comparison.sql") },
+    ];
+    // Version of a database once create_schema ran, the last migration
+    pub const SCHEMA_VERSION: i64 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+    // Pages copied at a time when a database is restored from a copy
+    const RESTORE_PAGES_PER_STEP: std::os::raw::c_int = 1024;
+    // How long a statement waits for another connection's lock before failing
+    const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+    // Current time in milliseconds since the Unix epoch, in SQL, for item and URL versions
+    const NOW_MILLIS: &str = "CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)";
+
+    // Why a file can't replace the database, None for an intact CompareWare database that
+    // this build can migrate. Databases from before migrations are adopted like on startup
+    pub fn database_copy_problem(path: &str) -> Option<String> {
+        let check = || -> Result<Option<String>, Error> {
+            let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            let integrity: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+            if integrity != "ok" {
+                return Ok(Some(format!("The database is damaged: {}", integrity)));
+            }
+            let has_table = |table: &str| {
+                conn.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?)",
+                    [table],
+                    |row| row.get::<_, bool>(0),
+                )
+            };
+            if !has_table("urls")? {
+                return Ok(Some("Not a CompareWare database, it has no comparisons table".into()));
+            }
+            if has_table("schema_version")? {
+                let version: i64 = conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))?;
+                if version > SCHEMA_VERSION {
+                    return Ok(Some(format!(
+                        "Database schema version {} is newer than {}, the version of this build",
+                        version, SCHEMA_VERSION
+                    )));
+                }
+            }
+            Ok(None)
+        };
+        check().unwrap_or_else(|e| Some(format!("Not a readable SQLite database: {}", e)))
+    }
+
+    // Apply the migrations a database hasn't had yet, returning the versions applied.
+    // Databases from before migrations are first brought up to the baseline and marked
+    // as having it. Databases migrated by a newer build are refused
+    fn migrate(conn: &mut Connection, migrations: &[Migration]) -> Result<Vec<i64>, Error> {
+        let tracked: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version')",
+            [],
+            |row| row.get(0),
+        )?;
+        let empty: bool = conn.query_row(
+            "SELECT NOT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%')",
+            [],
+            |row| row.get(0),
+        )?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS schema_version (
+                version INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                applied_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            );",
+        )?;
+        if !tracked && !empty {
+            adopt_legacy_schema(conn, &migrations[0])?;
+        }
+
+        let current: i64 = conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))?;
+        let latest = migrations.last().map_or(0, |migration| migration.version);
+        if current > latest {
+            return Err(Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_ERROR),
+                Some(format!("Database schema version {} is newer than {}, the version of this build", current, latest)),
+            ));
+        }
+        let mut applied = Vec::new();
+        for migration in migrations.iter().filter(|migration| migration.version > current) {
+            apply_migration(conn, migration).map_err(|e| {
+                server_error!("Failed applying migration {} ({}): {}", migration.version, migration.name, e);
+                e
+            })?;
+            log!("Applied database migration {} ({})", migration.version, migration.name);
+            applied.push(migration.version);
+        }
+        Ok(applied)
+    }
+
+    // Run a migration and record it in one transaction. Foreign keys are off while it
+    // runs so tables can be rebuilt without cascading, and checked before committing
+    fn apply_migration(conn: &mut Connection, migration: &Migration) -> Result<(), Error> {
+        conn.pragma_update(None, "foreign_keys", false)?;
+        let result = (|| {
+            let tx = conn.transaction()?;
+            tx.execute_batch(migration.sql)?;
+            let violations: i64 = tx.query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| row.get(0))?;
+            if violations > 0 {
+                return Err(Error::SqliteFailure(
+                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+                    Some(format!("{} rows violate foreign keys", violations)),
+                ));
+            }
+            tx.execute(
+                "INSERT INTO schema_version (version, name) VALUES (?, ?)",
+                rusqlite::params![migration.version, migration.name],
+            )?;
+            tx.commit()
+        })();
+        conn.pragma_update(None, "foreign_keys", true)?;
+        result
+    }
+
+    // Bring a database created before migrations up to the baseline: create the tables
+    // it lacks, add the columns later create_schema steps added and rebuild the tables
+    // step 21 rebuilt, then record the baseline as applied
+    fn adopt_legacy_schema(conn: &mut Connection, baseline: &Migration) -> Result<(), Error> {
+        let added_columns: [(&str, &[(&str, &str)]); 4] = [
+            (
+                "urls",
+                &[
+                    ("edit_key_hash", "TEXT"),
+                    ("updated_at", "INTEGER"),
+                    ("title", "TEXT NOT NULL DEFAULT ''"),
+                    ("description", "TEXT NOT NULL DEFAULT ''"),
+                    ("decision_item_id", "TEXT"),
+                    ("decided_at", "INTEGER"),
+                    ("decision_rationale", "TEXT NOT NULL DEFAULT ''"),
+                ],
+            ),
+            ("items", &[("global_item_id", "TEXT"), ("item_group", "TEXT"), ("tip_recipient", "TEXT")]),
+            ("selected_properties", &[("property_order", "INTEGER NOT NULL DEFAULT 0")]),
+            (
+                "item_properties",
+                &[
+                    ("quantity_amount", "REAL"),
+                    ("quantity_unit_id", "TEXT"),
+                    ("quantity_unit", "TEXT"),
+                    ("quantity_precision", "REAL"),
+                ],
+            ),
+        ];
+
+        log!("Bringing a database from before migrations up to version {}", baseline.version);
+        conn.execute_batch(baseline.sql)?;
+        for (table, columns) in added_columns {
+            let existing: Vec<String> = conn
+                .prepare(&format!("PRAGMA table_info({});", table))?
+                .query_map([], |row| row.get(1))?
+                .collect::<Result<_, _>>()?;
+            for (column, definition) in columns {
+                if !existing.iter().any(|name| name == column) {
+                    conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {};", table, column, definition))
+                        .map_err(|e| {
+                            server_error!("Failed adding {} to {} table: {}", column, table, e);
+                            e
+                        })?;
+                }
+            }
+        }
+        for table in ["item_properties", "deleted_properties", "long_values"] {
+            drop_global_item_reference(conn, table).map_err(|e| {
+                server_error!("Failed rebuilding {} table: {}", table, e);
+                e
+            })?;
+        }
+        conn.execute(
+            "INSERT INTO schema_version (version, name) VALUES (?, ?)",
+            rusqlite::params![baseline.version, baseline.name],
+        )?;
+        Ok(())
+    }
+
+    // Rebuild a table without its foreign key on items(global_item_id), keeping its rows
+    fn drop_global_item_reference(conn: &Connection, table: &str) -> Result<(), Error> {
+        let references_items: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM pragma_foreign_key_list(?) WHERE \"table\" = 'items')",
+            [table],
+            |row| row.get(0),
+        )?;
+        if !references_items {
+            return Ok(());
+        }
+        let sql: String = conn.query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?",
+            [table],
+            |row| row.get(0),
+        )?;
+        let rebuilt: Vec<&str> = sql.lines().filter(|line| !line.contains("REFERENCES items(global_item_id)")).collect();
+        let rebuilt = rebuilt.join("\n").replacen(table, &format!("{}_rebuilt", table), 1);
+        // Dropping the old table must not cascade to anything
+        conn.pragma_update(None, "foreign_keys", false)?;
+        let result = conn.execute_batch(&format!(
+            "BEGIN;
+            {rebuilt};
+            INSERT INTO {table}_rebuilt SELECT * FROM {table};
+            DROP TABLE {table};
+            ALTER TABLE {table}_rebuilt RENAME TO {table};
+            COMMIT;"
+        ));
+        if result.is_err() {
+            let _ = conn.execute_batch("ROLLBACK;");
+        }
+        conn.pragma_update(None, "foreign_keys", true)?;
+        result
+    }
+
+    // Define a struct to represent a database connection
+    #[derive(Debug)]
+    pub struct Database {
+        conn: Arc<Mutex<Connection>>,
+        // Read-only connections running the queries that only read, alongside the writer
+        readers: Vec<Mutex<Connection>>,
+        next_reader: AtomicUsize,
+    }
+
+    // JSON columns, failing like any other conversion between Rust and SQLite values
+    fn to_json<T: serde::Serialize>(value: &T) -> Result<String, Error> {
+        serde_json::to_string(value).map_err(|e| Error::ToSqlConversionFailure(Box::new(e)))
+    }
+
+    fn from_json<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, Error> {
+        serde_json::from_str(json)
+            .map_err(|e| Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))
+    }
+
+    // Record a change to the items or properties of a URL, for the comparison index and
+    // the ETag of its items. Versions only grow, even within a millisecond
+    fn touch_url(conn: &Connection, url_id: i64) -> Result<(), Error> {
+        conn.execute(
+            &format!("UPDATE urls SET updated_at = MAX({}, COALESCE(updated_at, 0) + 1) WHERE id = ?", NOW_MILLIS),
+            [url_id],
+        )?;
+        Ok(())
+    }
+
+    fn needs_setup(conn: &Connection) -> Result<bool, Error> {
+        conn.query_row(
+            "SELECT NOT EXISTS (SELECT 1 FROM admin_token)
+                AND NOT EXISTS (SELECT 1 FROM instance_settings)
+                AND NOT EXISTS (SELECT 1 FROM urls)",
+            [],
+            |row| row.get(0),
+        )
+    }
+
+    // ID of a URL. Fails with QueryReturnedNoRows for URLs that were never stored
+    fn url_id(conn: &Connection, url: &str) -> Result<i64, Error> {
+        conn.query_row("SELECT id FROM urls WHERE url = ?", [url], |row| row.get(0))
+    }
+
+    // ID of a URL, registering the URL when it is new
+    fn register_url(conn: &Connection, url: &str) -> Result<i64, Error> {
+        conn.execute("INSERT OR IGNORE INTO urls (url) VALUES (?)", [url])?;
+        url_id(conn, url)
+    }
+
+    // Select a property for a URL, registering both when they are new, and return their IDs.
+    // New selections are appended after the existing properties
+    fn select_property(conn: &Connection, url: &str, property: &str) -> Result<(i64, i64), Error> {
+        let url_id = register_url(conn, url)?;
+
+        conn.execute("INSERT OR IGNORE INTO properties (name) VALUES (?)", [property])?;
+        let property_id =
+            conn.query_row("SELECT id FROM properties WHERE name = ?", [property], |row| row.get::<_, i64>(0))?;
+
+        conn.execute(
+            "INSERT OR IGNORE INTO selected_properties (url_id, property_id, property_order)
+            VALUES (?1, ?2, (SELECT COALESCE(MAX(property_order), 0) + 1 FROM selected_properties WHERE url_id = ?1))",
+            [url_id, property_id],
+        )?;
+        touch_url(conn, url_id)?;
+        Ok((url_id, property_id))
+    }
+
+    // Metadata of a URL from its row of `urls`: title, description, decision item, decision
+    // time, decision rationale and unit system
+    fn metadata_from_row(row: &rusqlite::Row) -> Result<ComparisonMetadata, Error> {
+        let decision = match (row.get::<_, Option<String>>(2)?, row.get::<_, Option<i64>>(3)?) {
+            (Some(item_id), Some(decided_at)) => Some(Decision { item_id, decided_at, rationale: row.get(4)? }),
+            _ => None,
+        };
+        let unit_system = UnitSystem::from_name(&row.get::<_, String>(5)?);
+        Ok(ComparisonMetadata { title: row.get(0)?, description: row.get(1)?, decision, unit_system })
+    }
+
+    fn mirror_settings_from_row(row: &rusqlite::Row) -> Result<MirrorSettings, Error> {
+        Ok(MirrorSettings {
+            enabled: row.get(0)?,
+            source_url: row.get(1)?,
+            author: row.get(2)?,
+            relays: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+        })
+    }
+
+    // Values of a property across the items of a URL, keyed by item ID
+    fn property_column(conn: &Connection, url_id: i64, property_id: i64, property: &str) -> Result<PropertyColumn, Error> {
+        let mut column = PropertyColumn { property: property.to_string(), ..Default::default() };
+        let mut stmt = conn.prepare(
+            "SELECT i.id, ip.value, ip.quantity_amount, ip.quantity_unit_id, ip.quantity_unit, ip.quantity_precision,
+                ip.source
+            FROM items i
+            JOIN item_properties ip
+                ON ip.global_item_id = i.global_item_id AND ip.property_id = ?2
+            WHERE i.url_id = ?1 AND i.deleted_at IS NULL AND ip.value != ''",
+        )?;
+        let mut rows = stmt.query([url_id, property_id])?;
+        while let Some(row) = rows.next()? {
+            let item_id: String = row.get(0)?;
+            if let Some(amount) = row.get::<_, Option<f64>>(2)? {
+                column.quantities.insert(
+                    item_id.clone(),
+                    Quantity { amount, unit_id: row.get(3)?, unit: row.get(4)?, precision: row.get(5)? },
+                );
+            }
+            let source = ValueSource::from_name(&row.get::<_, String>(6)?);
+            if source != ValueSource::Manual {
+                column.sources.insert(item_id.clone(), source);
+            }
+            column.values.insert(item_id, row.get(1)?);
+        }
+        Ok(column)
+    }
+
+    // Store the value of a Wikidata claim as the value of an item's property, marked as
+    // coming from Wikidata
+    fn store_claim(
+        conn: &Connection,
+        global_item_id: &str,
+        property_id: i64,
+        value: &str,
+        quantity: Option<&Quantity>,
+    ) -> Result<(), Error> {
+        conn.execute(
+            "DELETE FROM long_values WHERE global_item_id = ? AND property_id = ?",
+            rusqlite::params![global_item_id, property_id],
+        )?;
+        conn.execute(
+            "INSERT INTO item_properties (global_item_id, property_id, value,
+                quantity_amount, quantity_unit_id, quantity_unit, quantity_precision, source)
+            VALUES (?, ?, ?, ?, ?, ?, ?, 'wikidata')
+            ON CONFLICT(global_item_id, property_id) DO UPDATE SET
+                value = excluded.value,
+                quantity_amount = excluded.quantity_amount,
+                quantity_unit_id = excluded.quantity_unit_id,
+                quantity_unit = excluded.quantity_unit,
+                quantity_precision = excluded.quantity_precision,
+                source = excluded.source",
+            rusqlite::params![
+                global_item_id,
+                property_id,
+                value,
+                quantity.map(|quantity| quantity.amount),
+                quantity.and_then(|quantity| quantity.unit_id.as_ref()),
+                quantity.and_then(|quantity| quantity.unit.as_ref()),
+                quantity.and_then(|quantity| quantity.precision)
+            ],
+        )?;
+        Ok(())
+    }
+
+    // LIKE pattern matching `text` anywhere, with its wildcards taken literally
+    fn contains_pattern(text: &str) -> String {
+        let escaped = text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        format!("%{}%", escaped)
+    }
+
+    // FTS5 query finding the values with words starting with each term of a search, None
+    // when it has no words. Terms are quoted, so none of them is read as query syntax
+    fn full_text_query(search: &str) -> Option<String> {
+        let terms = search_terms(search);
+        (!terms.is_empty()).then(|| terms.iter().map(|term| format!("\"{}\"*", term)).collect::<Vec<_>>().join(" "))
+    }
+
+    // SQL condition telling whether the `long_values` row `lv` holds the full text of the
+    // preview in `ip`. Rows left behind by a value overwritten outside `upsert_item` don't match
+    fn long_value_matches() -> String {
+        format!("lv.value IS NOT NULL AND substr(lv.value, 1, {}) || '…' = ip.value", PREVIEW_LENGTH)
+    }
+
+    // Row of `SELECT id, name, url, created_at, last_used_at FROM api_keys`
+    fn api_key_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ApiKey> {
+        Ok(ApiKey {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            url: row.get(2)?,
+            created_at: row.get(3)?,
+            last_used_at: row.get(4)?,
+        })
+    }
+
+    // Items of a URL in display order. Long values are read in full or kept as their preview,
+    // in which case the properties cut are returned per item ID
+    fn query_items(
+        conn: &Connection,
+        url_id: i64,
+        full_values: bool,
+    ) -> Result<(Vec<Item>, TruncatedValues), Error> {
+        log!("Fetching items for URL ID {}", url_id);
+
+        let value = if full_values {
+            format!("CASE WHEN {} THEN lv.value ELSE ip.value END", long_value_matches())
+        } else {
+            "ip.value".to_string()
+        };
+        let mut stmt = conn.prepare(&format!(
+            "WITH ordered_items AS (
+                SELECT 
+                    i.id,
+                    i.wikidata_id,
+                    i.item_order,
+                    i.global_item_id,
+                    i.item_group,
+                    i.tip_recipient,
+                    i.status,
+                    i.updated_at
+                FROM items i
+                WHERE i.url_id = ? AND i.deleted_at IS NULL
+                ORDER BY i.item_order ASC
+            )
+            SELECT
+                oi.id,
+                oi.wikidata_id,
+                name_ip.value AS name,
+                desc_ip.value AS description,
+                json_group_object(p.name, {value}) as custom_properties,
+                oi.item_group,
+                oi.tip_recipient,
+                json_group_object(p.name, CASE WHEN ip.quantity_amount IS NOT NULL THEN json_object(
+                    'amount', ip.quantity_amount,
+                    'unit_id', ip.quantity_unit_id,
+                    'unit', ip.quantity_unit,
+                    'precision', ip.quantity_precision
+                ) END) as quantities,
+                json_group_array(CASE WHEN {truncated} THEN p.name END) as truncated,
+                oi.status,
+                oi.updated_at,
+                json_group_object(p.name, CASE WHEN ip.source != 'manual' THEN ip.source END) as sources,
+                name_ip.source,
+                desc_ip.source
+            FROM ordered_items oi
+            LEFT JOIN item_properties ip
+                ON oi.global_item_id = ip.global_item_id
+                AND ip.property_id NOT IN (
+                    SELECT id FROM properties WHERE name IN ('name', 'description')
+                )
+                AND ip.property_id NOT IN (
+                    SELECT property_id
+                    FROM deleted_properties
+                    WHERE url_id = ? AND global_item_id = oi.global_item_id
+                )
+            LEFT JOIN long_values lv
+                ON lv.global_item_id = ip.global_item_id
+                AND lv.property_id = ip.property_id
+            LEFT JOIN properties p 
+                ON ip.property_id = p.id
+            LEFT JOIN item_properties name_ip 
+                ON oi.global_item_id = name_ip.global_item_id
+                AND name_ip.property_id = (SELECT id FROM properties WHERE name = 'name')
+            LEFT JOIN item_properties desc_ip 
+                ON oi.global_item_id = desc_ip.global_item_id
+                AND desc_ip.property_id = (SELECT id FROM properties WHERE name = 'description')
+            GROUP BY oi.id
+            ORDER BY oi.item_order ASC",
+            value = value,
+            truncated = long_value_matches(),
+        ))?;
+    
+        // Change from HashMap to Vec to preserve order
+        let rows = stmt.query_map([url_id, url_id], |row| {
+              let custom_props_json: String = row.get(4)?;
+              let custom_properties: HashMap<String, String> = serde_json::from_str(&custom_props_json)
+                  .unwrap_or_default();
+              let quantities: HashMap<String, Option<Quantity>> =
+                  serde_json::from_str(&row.get::<_, String>(7)?).unwrap_or_default();
+              let truncated: Vec<Option<String>> =
+                  serde_json::from_str(&row.get::<_, String>(8)?).unwrap_or_default();
+              let mut sources: HashMap<String, Option<String>> =
+                  serde_json::from_str(&row.get::<_, String>(11)?).unwrap_or_default();
+              sources.insert("name".into(), row.get::<_, Option<String>>(12)?.filter(|source| source != "manual"));
+              sources.insert("description".into(), row.get::<_, Option<String>>(13)?.filter(|source| source != "manual"));
+
+              Ok((Item {
+                  id: row.get(0)?,
+                  name: row.get::<_, Option<String>>(2)?.unwrap_or_default(), // Handle NULL values for name
+                  description: row.get::<_, Option<String>>(3)?.unwrap_or_default(), // Handle NULL values for description
+                  wikidata_id: row.get(1)?,
+                  custom_properties,
+                  group: row.get(5)?,
+                  tip_recipient: row.get(6)?,
+                  status: row.get::<_, Option<String>>(9)?.as_deref().and_then(ItemStatus::from_name),
+                  updated_at: row.get(10)?,
+                  sources: sources
+                      .into_iter()
+                      .filter_map(|(property, source)| source.map(|source| (property, ValueSource::from_name(&source))))
+                      .collect(),
+                  quantities: quantities
+                      .into_iter()
+                      .filter_map(|(property, quantity)| quantity.map(|quantity| (property, quantity)))
+                      .collect(),
+              }, truncated.into_iter().flatten().collect::<HashSet<String>>()))
+        })?;
+    
+        let mut items = Vec::new();
+        let mut truncated_values = HashMap::new();
+        for row in rows {
+            let (item, truncated) = row?;
+            if !full_values && !truncated.is_empty() {
+                truncated_values.insert(item.id.clone(), truncated);
+            }
+            items.push(item);
+        }
+    
+        Ok((items, truncated_values))
+    }
+
+    // Fetch the unexpired `column` values of a Wikidata cache table for the given entities
+    fn query_cache(
+        conn: &Connection,
+        table: &str,
+        column: &str,
+        entity_ids: &[String],
+        now: i64,
+    ) -> Result<Vec<(String, String)>, Error> {
+        if entity_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders = vec!["?"; entity_ids.len()].join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT entity_id, {} FROM {} WHERE expires_at > ? AND entity_id IN ({})",
+            column, table, placeholders
+        ))?;
+        let params = std::iter::once(rusqlite::types::Value::from(now))
+            .chain(entity_ids.iter().map(|id| rusqlite::types::Value::from(id.clone())));
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    impl Database {
+        // Create a new database connection
+        pub fn new(db_path: &str) -> Result<Self, Error> {
+            Self::with_readers(db_path, 0)
+        }
+
+        // Database with a connection for writes and `readers` read-only connections for the
+        // queries that only read, so GET requests don't wait for each other. In-memory
+        // databases only get the writer, other connections would open other databases
+        pub fn with_readers(db_path: &str, readers: usize) -> Result<Self, Error> {
+            let conn = Connection::open(db_path)?;
+            // The write-ahead log keeps the file consistent when the process is killed mid-write
+            // and lets readers such as backups run alongside a writer. Other connections to the
+            // file, like sqlite3 or a backup tool, wait for locks instead of failing right away
+            conn.pragma_update(None, "journal_mode", "WAL")?;
+            conn.pragma_update(None, "synchronous", "NORMAL")?;
+            conn.busy_timeout(BUSY_TIMEOUT)?;
+            conn.pragma_update(None, "foreign_keys", true)?;
+            let readers = if db_path.is_empty() || db_path == ":memory:" { 0 } else { readers };
+            let readers = (0..readers)
+                .map(|_| {
+                    let reader = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+                    reader.busy_timeout(BUSY_TIMEOUT)?;
+                    Ok(Mutex::new(reader))
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            logging::log!("Database connection established at: {} with {} readers", db_path, readers.len());
+            Ok(Database {
+                conn: Arc::new(Mutex::new(conn)),
+                readers,
+                next_reader: AtomicUsize::new(0),
+            })
+        }
+
+        // Connection for queries that only read: a free reader, taking turns, or the writer
+        // when there are no readers. Writes are committed when their call returns, so
+        // readers see them
+        async fn reader(&self) -> MutexGuard<'_, Connection> {
+            if self.readers.is_empty() {
+                return self.conn.lock().await;
+            }
+            let first = self.next_reader.fetch_add(1, Ordering::Relaxed);
+            for turn in 0..self.readers.len() {
+                if let Ok(reader) = self.readers[(first + turn) % self.readers.len()].try_lock() {
+                    return reader;
+                }
+            }
+            self.readers[first % self.readers.len()].lock().await
+        }
+
+        // Create the database schema or bring it up to date, applying the pending migrations
+        pub async fn create_schema(&self) -> Result<(), Error> {
+            let mut conn = self.conn.lock().await;
+            migrate(&mut conn, MIGRATIONS)?;
+            Ok(())
+        }
+
+        // Copy the pages of the write-ahead log into the database file and empty the log,
+        // so the file is complete on its own once the server stopped
+        pub async fn checkpoint(&self) -> Result<(), Error> {
+            let conn = self.conn.lock().await;
+            conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+        }
+
+        // Last migration applied to the database, SCHEMA_VERSION once create_schema ran
+        pub async fn schema_version(&self) -> Result<i64, Error> {
+            let conn = self.conn.lock().await;
+            conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))
+        }
+
+        // Insert a new URL into the database
+        pub async fn insert_url(&self, url: &str) -> Result<i64, Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+
+            // Get the URL ID whether it was inserted or already existed
+            let url_id = register_url(&tx, url)?;
+
+            tx.commit()?;
+            logging::log!("URL inserted: {}", url);
+            Ok(url_id)
+        }
+
+        // Handle on the comparison of a URL, `None` when the URL was never stored
+        pub async fn comparison(&self, url: &str) -> Result<Option<ComparisonHandle<'_>>, Error> {
+            let conn = self.reader().await;
+            match url_id(&conn, url) {
+                Ok(url_id) => Ok(Some(ComparisonHandle { db: self, url: url.to_string(), url_id })),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+
+        // Check whether a URL has been registered
+        pub async fn url_exists(&self, url: &str) -> Result<bool, Error> {
+            let conn = self.reader().await;
+            conn.query_row("SELECT EXISTS(SELECT 1 FROM urls WHERE url = ?)", [url], |row| {
+                row.get(0)
+            })
+        }
+
+        pub async fn delete_item(&self, item_id: &str) -> Result<(), Error> {
+            let conn = self.conn.lock().await;
+            conn.execute("DELETE FROM items WHERE id = ?", [item_id])?;
+            logging::log!("Item deleted: {}", item_id);
+            Ok(())
+        }
+
+        pub async fn delete_property(&self, property: &str) -> Result<(), Error> {
+            let conn = self.conn.lock().await;
+            let query = format!(
+                "UPDATE items SET custom_properties = json_remove(custom_properties, '$.{}')",
+                property
+            );
+            conn.execute(&query, [])?;
+            logging::log!("Property deleted: {}", property);
+            Ok(())
+        }
+
+        // Retrieve all items from the database
+        pub async fn get_items(&self) -> Result<Vec<DbItem>, Error> {
+            let conn = self.conn.lock().await;
+            let mut stmt = conn.prepare("SELECT * FROM items;")?;
+            let items = stmt.query_map([], |row| {
+                Ok(DbItem {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    description: row.get(2)?,
+                    wikidata_id: row.get(3)?,
+                })
+            })?;
+            let mut result = Vec::new();
+            for item in items {
+                result.push(item?);
+            }
+            logging::log!("Fetched {} items from the database", result.len()); // Log with Leptos
+            Ok(result)
+        }
+
+        // Retrieve all items from the database for a specific URL, with long values in full
+        pub async fn get_items_by_url(&self, url: &str) -> Result<Vec<Item>, Error> {
+            match self.comparison(url).await? {
+                Some(comparison) => comparison.items().await,
+                None => Ok(Vec::new()),
+            }
+        }
+
+        // Retrieve the items of a URL for the grid, with long values as a preview, together
+        // with the properties per item ID whose value was cut
+        pub async fn get_item_previews_by_url(
+            &self,
+            url: &str,
+        ) -> Result<(Vec<Item>, TruncatedValues), Error> {
+            match self.comparison(url).await? {
+                Some(comparison) => comparison.item_previews().await,
+                None => Ok((Vec::new(), HashMap::new())),
+            }
+        }
+
+        // Full value of a property of an item, `None` when the item has no such value
+        pub async fn get_property_value(&self, url: &str, item_id: &str, property: &str) -> Result<Option<String>, Error> {
+            let conn = self.conn.lock().await;
+            let value = conn.query_row(
+                &format!(
+                    "SELECT CASE WHEN {} THEN lv.value ELSE ip.value END
+                    FROM items i
+                    JOIN urls u ON i.url_id = u.id
+                    JOIN properties p ON p.name = ?3
+                    JOIN item_properties ip ON ip.global_item_id = i.global_item_id AND ip.property_id = p.id
+                    LEFT JOIN long_values lv ON lv.global_item_id = ip.global_item_id AND lv.property_id = ip.property_id
+                    WHERE u.url = ?1 AND i.id = ?2 AND i.deleted_at IS NULL",
+                    long_value_matches()
+                ),
+                [url, item_id, property],
+                |row| row.get(0),
+            );
+            match value {
+                Ok(value) => Ok(Some(value)),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+
+        async fn get_or_create_property(
+            &self,
+            tx: &mut rusqlite::Transaction<'_>,
+            prop: &str,
+        ) -> Result<i64, Error> {
+            match tx.query_row("SELECT id FROM properties WHERE name = ?", [prop], |row| {
+                row.get::<_, i64>(0)
+            }) {
+                Ok(id) => Ok(id),
+                Err(rusqlite::Error::QueryReturnedNoRows) => {
+                    tx.execute("INSERT INTO properties (name) VALUES (?)", [prop])?;
+                    Ok(tx.last_insert_rowid())
+                }
+                Err(e) => Err(e),
+            }
+        }
+
+        // Insert a new item into the database for a specific URL
+        pub async fn insert_item_by_url(&self, url: &str, item: &Item) -> Result<(), Error> {
+            log!("[DB] Starting insert for URL: {}, Item: {}", url, item.id);
+
+            // 1. Check database lock acquisition
+            let lock_start = std::time::Instant::now();
+            let mut conn = self.conn.lock().await;
+            log!("[DB] Lock acquired in {:?}", lock_start.elapsed());
+
+            // 2. Transaction handling
+            log!("[DB] Starting transaction");
+            let mut tx = conn.transaction().map_err(|e| {
+                log!("[DB] Transaction start failed: {:?}", e);
+                e
+            })?;
+
+            let url_id = register_url(&tx, url)?;
+            self.upsert_item(&mut tx, url_id, item).await?;
+
+            tx.commit()?;
+            log!("[DB] Transaction committed successfully");
+            Ok(())
+        }
+
+        // Insert or update several items for a specific URL in a single transaction
+        pub async fn insert_items_by_url(&self, url: &str, items: &[Item]) -> Result<(), Error> {
+            log!("[DB] Starting batch insert for URL: {}, {} items", url, items.len());
+            let mut conn = self.conn.lock().await;
+            let mut tx = conn.transaction()?;
+
+            let url_id = register_url(&tx, url)?;
+            for item in items {
+                self.upsert_item(&mut tx, url_id, item).await?;
+            }
+
+            tx.commit()?;
+            log!("[DB] Batch transaction committed successfully");
+            Ok(())
+        }
+
+        // Save items unless one was changed since the version the client based its copy on:
+        // its `updated_at` when given, or the time `unmodified_since` (milliseconds) for
+        // all of them. Stale saves write nothing and return the stored copies instead
+        pub async fn save_items_by_url(&self, url: &str, items: &[Item], unmodified_since: Option<i64>) -> Result<ItemsSave, Error> {
+            let mut conn = self.conn.lock().await;
+            let mut tx = conn.transaction()?;
+            let url_id = register_url(&tx, url)?;
+
+            // IDs of items of other comparisons are refused, saving them here would move them
+            let mut taken = Vec::new();
+            for item in items {
+                let elsewhere: bool = tx.query_row(
+                    "SELECT EXISTS (SELECT 1 FROM items WHERE id = ? AND url_id != ?)",
+                    rusqlite::params![&item.id, url_id],
+                    |row| row.get(0),
+                )?;
+                if elsewhere {
+                    taken.push(item.id.clone());
+                }
+            }
+            if !taken.is_empty() {
+                log!("[DB] Refused to save {} items of other URLs under URL: {}", taken.len(), url);
+                return Ok(ItemsSave::Taken(taken));
+            }
+
+            let mut stale = HashSet::new();
+            for item in items {
+                let stored: Option<Option<i64>> = tx
+                    .query_row(
+                        "SELECT updated_at FROM items WHERE id = ? AND url_id = ? AND deleted_at IS NULL",
+                        rusqlite::params![&item.id, url_id],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                let Some(stored) = stored else {
+                    continue;
+                };
+                let version = stored.unwrap_or_default();
+                let changed = item.updated_at.is_some_and(|base| base != version)
+                    || unmodified_since.is_some_and(|since| version > since);
+                if changed {
+                    stale.insert(item.id.clone());
+                }
+            }
+            if !stale.is_empty() {
+                let (stored, _) = query_items(&tx, url_id, true)?;
+                log!("[DB] Refused stale save of {} items for URL: {}", stale.len(), url);
+                return Ok(ItemsSave::Stale(stored.into_iter().filter(|item| stale.contains(&item.id)).collect()));
+            }
+
+            let mut saved = items.to_vec();
+            for item in &mut saved {
+                self.upsert_item(&mut tx, url_id, item).await?;
+                item.updated_at = tx.query_row(
+                    "SELECT updated_at FROM items WHERE id = ? AND url_id = ?",
+                    rusqlite::params![&item.id, url_id],
+                    |row| row.get(0),
+                )?;
+            }
+            tx.commit()?;
+            log!("[DB] Saved {} items for URL: {}", saved.len(), url);
+            Ok(ItemsSave::Saved(saved))
+        }
+
+        // Upsert an item and its properties inside an open transaction
+        async fn upsert_item(
+            &self,
+            tx: &mut rusqlite::Transaction<'_>,
+            url_id: i64,
+            item: &Item,
+        ) -> Result<(), Error> {
+            touch_url(tx, url_id)?;
+
+            // 4. Item insertion
+            let max_order: i32 = tx.query_row(
+                "SELECT COALESCE(MAX(item_order), 0) FROM items WHERE url_id = ?",
+                [url_id],
+                |row| row.get(0),
+            )?;
+
+            // Items of the same name share their values within a comparison, never across
+            let global_item_id = match tx.query_row(
+                "SELECT ip.global_item_id
+                 FROM item_properties ip
+                 JOIN properties p ON ip.property_id = p.id
+                 JOIN items i ON i.global_item_id = ip.global_item_id
+                 WHERE p.name = 'name' AND ip.value = ? AND i.url_id = ? LIMIT 1",
+                rusqlite::params![&item.name, url_id],
+                |row| row.get::<_, String>(0),
+            ) {
+                Ok(id) => id, // Reuse existing global_item_id
+                Err(rusqlite::Error::QueryReturnedNoRows) => {
+                    Uuid::new_v4().to_string() // Generate a new global_item_id
+                }
+                Err(e) => return Err(e),
+            };
+
+            log!("[DB] Upserting item");
+            tx.execute(
+                &format!(
+                    "INSERT INTO items (id, url_id, wikidata_id, item_order, global_item_id, item_group, tip_recipient, status, updated_at)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, {})
+                    ON CONFLICT(id, url_id) DO UPDATE SET
+                        wikidata_id = excluded.wikidata_id,
+                        global_item_id = excluded.global_item_id,
+                        item_group = excluded.item_group,
+                        tip_recipient = excluded.tip_recipient,
+                        status = excluded.status,
+                        deleted_at = NULL,
+                        updated_at = MAX(excluded.updated_at, COALESCE(items.updated_at, 0) + 1)",
+                    NOW_MILLIS
+                ),
+                rusqlite::params![
+                    &item.id,
+                    url_id,
+                    &item.wikidata_id,
+                    max_order + 1,
+                    &global_item_id,
+                    &item.group,
+                    &item.tip_recipient,
+                    item.status.map(|status| status.as_str())
+                ],
+            )?;
+            log!("[DB] Item upserted successfully");
+
+            // property handling
+            let core_properties = vec![
+                ("name", &item.name),
+                ("description", &item.description)
+            ];
+
+            for (prop, value) in core_properties.into_iter().chain(
+                item.custom_properties.iter().map(|(k, v)| (k.as_str(), v))
+            ) {
+                let prop_id = self.get_or_create_property(tx, prop).await?;
+                let quantity = item.quantities.get(prop);
+
+                // Long custom values are stored in full apart, with a preview in their cell
+                let stored_value = match value_preview(value) {
+                    Some(preview) if !["name", "description"].contains(&prop) => {
+                        // A preview saved back unchanged keeps the full value it stands for
+                        let unchanged_preview = preview == *value
+                            && tx.query_row(
+                                &format!(
+                                    "SELECT COUNT(*) FROM item_properties ip
+                                    JOIN long_values lv
+                                        ON lv.global_item_id = ip.global_item_id AND lv.property_id = ip.property_id
+                                    WHERE ip.global_item_id = ? AND ip.property_id = ? AND ip.value = ? AND {}",
+                                    long_value_matches()
+                                ),
+                                rusqlite::params![&global_item_id, prop_id, value],
+                                |row| row.get::<_, i64>(0),
+                            )? > 0;
+                        if unchanged_preview {
+                            continue;
+                        }
+                        tx.execute(
+                            "INSERT INTO long_values (global_item_id, property_id, value) VALUES (?, ?, ?)
+                            ON CONFLICT(global_item_id, property_id) DO UPDATE SET value = excluded.value",
+                            rusqlite::params![&global_item_id, prop_id, value],
+                        )?;
+                        preview
+                    }
+                    _ => {
+                        tx.execute(
+                            "DELETE FROM long_values WHERE global_item_id = ? AND property_id = ?",
+                            rusqlite::params![&global_item_id, prop_id],
+                        )?;
+                        value.clone()
+                    }
+                };
+
+                // Values saved without a source keep theirs while unchanged, edited ones are typed in
+                tx.execute(
+                    "INSERT INTO item_properties (global_item_id, property_id, value,
+                        quantity_amount, quantity_unit_id, quantity_unit, quantity_precision, source)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, COALESCE(?8, 'manual'))
+                    ON CONFLICT(global_item_id, property_id) DO UPDATE SET
+                        source = CASE
+                            WHEN ?8 IS NOT NULL THEN ?8
+                            WHEN item_properties.value = excluded.value THEN item_properties.source
+                            ELSE 'manual'
+                        END,
+                        value = excluded.value,
+                        quantity_amount = excluded.quantity_amount,
+                        quantity_unit_id = excluded.quantity_unit_id,
+                        quantity_unit = excluded.quantity_unit,
+                        quantity_precision = excluded.quantity_precision",
+                    rusqlite::params![
+                        &global_item_id,
+                        prop_id,
+                        stored_value,
+                        quantity.map(|quantity| quantity.amount),
+                        quantity.and_then(|quantity| quantity.unit_id.as_ref()),
+                        quantity.and_then(|quantity| quantity.unit.as_ref()),
+                        quantity.and_then(|quantity| quantity.precision),
+                        item.sources.get(prop).map(ValueSource::as_str)
+                    ],
+                )?;
+            }
+
+            // Property synchronization
+            log!("[DB] Synchronizing properties for item {}", item.id);
+            let existing_props = {
+                let mut stmt = tx.prepare(
                     "SELECT p.name, ip.value 
                     FROM item_properties ip
                     JOIN properties p ON ip.property_id = p.id
                     WHERE ip.global_item_id = ?",
                 )?;
-            
-                let mapped_rows = stmt.query_map([&item.id], |row| {
-                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            
+                let mapped_rows = stmt.query_map([&item.id], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?;
+            
+                mapped_rows.collect::<Result<HashMap<String, String>, _>>()?
+            };
+
+            // Include core properties in current_props check
+            let mut current_props: HashSet<&str> = item.custom_properties.keys()
+                .map(|s| s.as_str())
+                .collect();
+            current_props.insert("name");
+            current_props.insert("description");
+            
+            // Cleanup with core property protection
+            for (existing_prop, _) in existing_props {
+                if !current_props.contains(existing_prop.as_str()) 
+                    && !["name", "description"].contains(&existing_prop.as_str())
+                {
+                    log!("[DB] Removing deleted property {}", existing_prop);
+                    tx.execute(
+                        "DELETE FROM item_properties 
+                        WHERE item_id = ? 
+                        AND property_id = (SELECT id FROM properties WHERE name = ?)",
+                        rusqlite::params![&item.id, existing_prop],
+                    )?;
+                }
+            }
+            Ok(())
+        }
+
+        // Move an item of a URL to the trash, returning the number of items removed (0 or 1).
+        // Its values and reviews are kept, so restore_item can bring it back
+        pub async fn delete_item_by_url(&self, url: &str, item_id: &str) -> Result<usize, Error> {
+            match self.comparison(url).await? {
+                Some(comparison) => comparison.delete_item(item_id).await,
+                None => Err(rusqlite::Error::QueryReturnedNoRows),
+            }
+        }
+
+        // Items of a URL in the trash, most recently deleted first
+        pub async fn get_deleted_items(&self, url: &str) -> Result<Vec<DeletedItem>, Error> {
+            let conn = self.reader().await;
+            let mut stmt = conn.prepare(
+                "SELECT i.id, COALESCE(name_ip.value, ''), COALESCE(desc_ip.value, ''), i.deleted_at
+                FROM items i
+                JOIN urls u ON i.url_id = u.id
+                LEFT JOIN item_properties name_ip
+                    ON name_ip.global_item_id = i.global_item_id
+                    AND name_ip.property_id = (SELECT id FROM properties WHERE name = 'name')
+                LEFT JOIN item_properties desc_ip
+                    ON desc_ip.global_item_id = i.global_item_id
+                    AND desc_ip.property_id = (SELECT id FROM properties WHERE name = 'description')
+                WHERE u.url = ? AND i.deleted_at IS NOT NULL
+                ORDER BY i.deleted_at DESC, i.item_order DESC
+                LIMIT ?",
+            )?;
+            let items = stmt.query_map(rusqlite::params![url, MAX_DELETED_ITEMS], |row| {
+                Ok(DeletedItem { id: row.get(0)?, name: row.get(1)?, description: row.get(2)?, deleted_at: row.get(3)? })
+            })?;
+            items.collect()
+        }
+
+        // Take an item of a URL out of the trash, back at its former position. Returns the
+        // item with its values in full, or `None` when the URL has no such deleted item
+        pub async fn restore_item(&self, url: &str, item_id: &str) -> Result<Option<Item>, Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+            let url_id = url_id(&tx, url)?;
+            let restored = tx.execute(
+                "UPDATE items SET deleted_at = NULL WHERE id = ? AND url_id = ? AND deleted_at IS NOT NULL",
+                rusqlite::params![item_id, url_id],
+            )?;
+            if restored == 0 {
+                return Ok(None);
+            }
+            touch_url(&tx, url_id)?;
+            let (items, _) = query_items(&tx, url_id, true)?;
+            tx.commit()?;
+            log!("[DB] Restored item {} of URL: {}", item_id, url);
+            Ok(items.into_iter().find(|item| item.id == item_id))
+        }
+
+        // Add changes to the edit history of a URL, in the order they were made
+        pub async fn record_revisions(&self, url: &str, actor: &str, changes: &[HistoryChange], now: i64) -> Result<(), Error> {
+            if changes.is_empty() {
+                return Ok(());
+            }
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+            let url_id = url_id(&tx, url)?;
+            {
+                let mut stmt = tx.prepare(
+                    "INSERT INTO item_revisions (url_id, item_id, property, change, actor, created_at)
+                    VALUES (?, ?, ?, ?, ?, ?)",
+                )?;
+                for change in changes {
+                    stmt.execute(rusqlite::params![url_id, change.item_id(), change.property(), to_json(change)?, actor, now])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        }
+
+        // Edit history of a URL, newest first: a page of the revisions older than `before`,
+        // only those of one item when `item_id` is given
+        pub async fn get_history(&self, url: &str, item_id: Option<&str>, before: Option<i64>) -> Result<Vec<Revision>, Error> {
+            let conn = self.reader().await;
+            let mut stmt = conn.prepare(
+                "SELECT r.id, r.actor, r.created_at, r.change
+                FROM item_revisions r
+                JOIN urls u ON r.url_id = u.id
+                WHERE u.url = ?1 AND (?2 IS NULL OR r.item_id = ?2) AND (?3 IS NULL OR r.id < ?3)
+                ORDER BY r.id DESC
+                LIMIT ?4",
+            )?;
+            let revisions = stmt.query_map(rusqlite::params![url, item_id, before, HISTORY_PAGE_SIZE], |row| {
+                Ok(Revision {
+                    id: row.get(0)?,
+                    actor: row.get(1)?,
+                    created_at: row.get(2)?,
+                    change: from_json(&row.get::<_, String>(3)?)?,
+                })
+            })?;
+            revisions.collect()
+        }
+
+        // Delete a property from the database for a specific URL, returning the number of
+        // rows affected: its selection plus the item values it is hidden from.
+        // Fails with QueryReturnedNoRows for properties that were never stored
+        pub async fn delete_property_by_url(&self, url: &str, property: &str) -> Result<Option<PropertyDeletion>, Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+        
+            // Get URL ID
+            let url_id = url_id(&tx, url)?;
+        
+            // Get property ID
+            let property_id: i64 = tx.query_row(
+                "SELECT id FROM properties WHERE name = ?",
+                [property],
+                |row| row.get(0),
+            )?;
+
+            // Keep the position and the values of the column, to undo the deletion
+            let property_order: Option<i64> = match tx.query_row(
+                "SELECT property_order FROM selected_properties WHERE url_id = ? AND property_id = ?",
+                [url_id, property_id],
+                |row| row.get(0),
+            ) {
+                Ok(order) => order,
+                Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                Err(e) => return Err(e),
+            };
+            tx.execute(
+                "INSERT INTO property_deletions (url_id, property_id, property_order) VALUES (?, ?, ?)",
+                rusqlite::params![url_id, property_id, property_order],
+            )?;
+            let deletion_id = tx.last_insert_rowid();
+            tx.execute(
+                "INSERT INTO property_deletion_items (deletion_id, global_item_id, value,
+                    quantity_amount, quantity_unit_id, quantity_unit, quantity_precision, source)
+                SELECT ?1, i.global_item_id, ip.value,
+                    ip.quantity_amount, ip.quantity_unit_id, ip.quantity_unit, ip.quantity_precision, ip.source
+                FROM items i
+                LEFT JOIN item_properties ip
+                    ON ip.global_item_id = i.global_item_id AND ip.property_id = ?3
+                WHERE i.url_id = ?2 AND i.global_item_id IS NOT NULL
+                    AND NOT EXISTS (
+                        SELECT 1 FROM deleted_properties dp
+                        WHERE dp.url_id = ?2 AND dp.global_item_id = i.global_item_id AND dp.property_id = ?3
+                    )",
+                rusqlite::params![deletion_id, url_id, property_id],
+            )?;
+        
+            let unselected = tx.execute(
+                "DELETE FROM selected_properties WHERE url_id = ? AND property_id = ?",
+                [url_id, property_id],
+            )?;
+
+            // Hide the property from every item of this URL
+            let hidden = tx.execute(
+                "INSERT OR IGNORE INTO deleted_properties (url_id, global_item_id, property_id)
+                SELECT url_id, global_item_id, ? FROM items WHERE url_id = ?",
+                [property_id, url_id],
+            )?;
+            if unselected + hidden == 0 {
+                // Nothing was deleted, so there is nothing to undo either
+                return Ok(None);
+            }
+            touch_url(&tx, url_id)?;
+        
+            tx.commit()?;
+            Ok(Some(PropertyDeletion { rows_affected: unselected + hidden, deletion_id }))
+        }
+
+        // Undo a deletion of a property of a URL: select the property again at its former
+        // position and show the values the items had. Returns the restored column, or
+        // `None` when the URL has no such deletion
+        pub async fn undo_property_deletion(&self, url: &str, deletion_id: i64) -> Result<Option<PropertyColumn>, Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+            let deletion = tx.query_row(
+                "SELECT d.url_id, d.property_id, d.property_order, p.name
+                FROM property_deletions d
+                JOIN urls u ON d.url_id = u.id
+                JOIN properties p ON d.property_id = p.id
+                WHERE d.id = ? AND u.url = ?",
+                rusqlite::params![deletion_id, url],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, Option<i64>>(2)?, row.get::<_, String>(3)?)),
+            );
+            let (url_id, property_id, property_order, property) = match deletion {
+                Ok(deletion) => deletion,
+                Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+                Err(e) => return Err(e),
+            };
+
+            // Put the property back where it was, unless it was selected again meanwhile
+            let selected: bool = tx.query_row(
+                "SELECT EXISTS (SELECT 1 FROM selected_properties WHERE url_id = ? AND property_id = ?)",
+                [url_id, property_id],
+                |row| row.get(0),
+            )?;
+            if let (false, Some(order)) = (selected, property_order) {
+                tx.execute(
+                    "UPDATE selected_properties SET property_order = property_order + 1
+                    WHERE url_id = ? AND property_order >= ?",
+                    [url_id, order],
+                )?;
+                tx.execute(
+                    "INSERT INTO selected_properties (url_id, property_id, property_order) VALUES (?, ?, ?)",
+                    [url_id, property_id, order],
+                )?;
+            }
+
+            // Show the property again on the items the deletion hid, with their former values
+            tx.execute(
+                "DELETE FROM deleted_properties
+                WHERE url_id = ?1 AND property_id = ?2 AND global_item_id IN (
+                    SELECT global_item_id FROM property_deletion_items WHERE deletion_id = ?3
+                )",
+                [url_id, property_id, deletion_id],
+            )?;
+            tx.execute(
+                "INSERT INTO item_properties (global_item_id, property_id, value,
+                    quantity_amount, quantity_unit_id, quantity_unit, quantity_precision, source)
+                SELECT di.global_item_id, ?2, di.value,
+                    di.quantity_amount, di.quantity_unit_id, di.quantity_unit, di.quantity_precision,
+                    COALESCE(di.source, 'manual')
+                FROM property_deletion_items di
+                WHERE di.deletion_id = ?3 AND di.value IS NOT NULL
+                    AND di.global_item_id IN (SELECT global_item_id FROM items WHERE url_id = ?1)
+                ON CONFLICT(global_item_id, property_id) DO UPDATE SET
+                    value = excluded.value,
+                    quantity_amount = excluded.quantity_amount,
+                    quantity_unit_id = excluded.quantity_unit_id,
+                    quantity_unit = excluded.quantity_unit,
+                    quantity_precision = excluded.quantity_precision,
+                    source = excluded.source",
+                [url_id, property_id, deletion_id],
+            )?;
+            tx.execute("DELETE FROM property_deletion_items WHERE deletion_id = ?", [deletion_id])?;
+            tx.execute("DELETE FROM property_deletions WHERE id = ?", [deletion_id])?;
+            touch_url(&tx, url_id)?;
+
+            let column = property_column(&tx, url_id, property_id, &property)?;
+            tx.commit()?;
+            log!("[DB] Undid deletion {} of {} for URL: {}", deletion_id, property, url);
+            Ok(Some(column))
+        }
+
+        // Wikidata entities of the items of a URL
+        pub async fn get_wikidata_ids(&self, url: &str) -> Result<Vec<String>, Error> {
+            let conn = self.reader().await;
+            let mut stmt = conn.prepare(
+                "SELECT DISTINCT i.wikidata_id FROM items i
+                JOIN urls u ON i.url_id = u.id
+                WHERE u.url = ? AND i.wikidata_id IS NOT NULL AND i.deleted_at IS NULL",
+            )?;
+            let ids = stmt.query_map([url], |row| row.get(0))?;
+            ids.collect()
+        }
+
+        // Card of a Wikidata entity from the items of all comparisons having it, private ones
+        // left out: the most
+        // common name and description, and per selected property the value most comparisons
+        // hold. Property labels are left to the caller. None when no comparison has the entity
+        pub async fn get_spec_card(&self, wikidata_id: &str) -> Result<Option<SpecCard>, Error> {
+            let conn = self.reader().await;
+            let comparisons: usize = conn.query_row(
+                "SELECT COUNT(DISTINCT i.url_id) FROM items i
+                JOIN urls u ON u.id = i.url_id
+                WHERE i.wikidata_id = ? AND i.deleted_at IS NULL AND u.visibility != 'private'",
+                [wikidata_id],
+                |row| row.get(0),
+            )?;
+            if comparisons == 0 {
+                return Ok(None);
+            }
+
+            let most_common = |property: &str| -> Result<String, Error> {
+                match conn.query_row(
+                    "SELECT ip.value FROM items i
+                    JOIN urls u ON u.id = i.url_id
+                    JOIN item_properties ip ON ip.global_item_id = i.global_item_id
+                    JOIN properties p ON p.id = ip.property_id AND p.name = ?2
+                    WHERE i.wikidata_id = ?1 AND i.deleted_at IS NULL AND ip.value != '' AND u.visibility != 'private'
+                    GROUP BY ip.value
+                    ORDER BY COUNT(DISTINCT i.url_id) DESC, ip.value
+                    LIMIT 1",
+                    [wikidata_id, property],
+                    |row| row.get(0),
+                ) {
+                    Ok(value) => Ok(value),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(String::new()),
+                    Err(e) => Err(e),
+                }
+            };
+            let name = most_common("name")?;
+            let description = most_common("description")?;
+
+            // Rows come most agreed on first, so the first value of a property is its most common one
+            let mut stmt = conn.prepare(&format!(
+                "SELECT p.name, CASE WHEN {} THEN lv.value ELSE ip.value END AS full_value, COUNT(DISTINCT i.url_id) AS uses
+                FROM items i
+                JOIN urls u ON u.id = i.url_id
+                JOIN selected_properties sp ON sp.url_id = i.url_id
+                JOIN properties p ON p.id = sp.property_id
+                JOIN item_properties ip ON ip.global_item_id = i.global_item_id AND ip.property_id = sp.property_id
+                LEFT JOIN long_values lv ON lv.global_item_id = ip.global_item_id AND lv.property_id = ip.property_id
+                WHERE i.wikidata_id = ? AND i.deleted_at IS NULL AND ip.value != '' AND u.visibility != 'private'
+                GROUP BY p.name, full_value
+                ORDER BY uses DESC, p.name",
+                long_value_matches()
+            ))?;
+            let rows = stmt.query_map([wikidata_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, usize>(2)?))
+            })?;
+            let mut specs: Vec<Spec> = Vec::new();
+            for row in rows {
+                let (property, value, uses) = row?;
+                if specs.len() == MAX_CARD_SPECS {
+                    break;
+                }
+                if specs.iter().any(|spec| spec.property == property) {
+                    continue;
+                }
+                specs.push(Spec {
+                    label: property.clone(),
+                    property,
+                    value: value_preview(&value).unwrap_or(value),
+                    comparisons: uses,
+                });
+            }
+
+            Ok(Some(SpecCard { wikidata_id: wikidata_id.to_string(), name, description, comparisons, specs }))
+        }
+
+        // Values the comparisons other than `url` hold for an entity, for the properties
+        // they show. Private comparisons keep theirs. None when no other comparison has the entity
+        pub async fn get_reusable_values(&self, url: &str, wikidata_id: &str) -> Result<Option<ReusableValues>, Error> {
+            let conn = self.reader().await;
+            let comparisons: usize = conn.query_row(
+                "SELECT COUNT(DISTINCT i.url_id) FROM items i
+                JOIN urls u ON u.id = i.url_id
+                WHERE i.wikidata_id = ?1 AND u.url != ?2 AND i.deleted_at IS NULL AND u.visibility != 'private'",
+                [wikidata_id, url],
+                |row| row.get(0),
+            )?;
+            if comparisons == 0 {
+                return Ok(None);
+            }
+
+            // Rows come most agreed on first, so the first value of a property is its most common one
+            let mut stmt = conn.prepare(&format!(
+                "SELECT p.name, CASE WHEN {} THEN lv.value ELSE ip.value END AS full_value,
+                    ip.quantity_amount, ip.quantity_unit_id, ip.quantity_unit, ip.quantity_precision,
+                    COUNT(DISTINCT i.url_id) AS uses
+                FROM items i
+                JOIN urls u ON u.id = i.url_id
+                JOIN selected_properties sp ON sp.url_id = i.url_id
+                JOIN properties p ON p.id = sp.property_id
+                JOIN item_properties ip ON ip.global_item_id = i.global_item_id AND ip.property_id = sp.property_id
+                LEFT JOIN long_values lv ON lv.global_item_id = ip.global_item_id AND lv.property_id = ip.property_id
+                WHERE i.wikidata_id = ?1 AND u.url != ?2 AND i.deleted_at IS NULL AND ip.value != ''
+                    AND u.visibility != 'private'
+                GROUP BY p.name, full_value
+                ORDER BY uses DESC, p.name, full_value",
+                long_value_matches()
+            ))?;
+            let rows = stmt.query_map([wikidata_id, url], |row| {
+                let quantity = row.get::<_, Option<f64>>(2)?.map(|amount| -> Result<Quantity, Error> {
+                    Ok(Quantity { amount, unit_id: row.get(3)?, unit: row.get(4)?, precision: row.get(5)? })
+                });
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, quantity.transpose()?))
+            })?;
+            let mut reusable = ReusableValues { wikidata_id: wikidata_id.to_string(), comparisons, ..ReusableValues::default() };
+            for row in rows {
+                let (property, value, quantity) = row?;
+                if reusable.values.contains_key(&property) {
+                    continue;
+                }
+                if let Some(quantity) = quantity {
+                    reusable.quantities.insert(property.clone(), quantity);
+                }
+                reusable.values.insert(property, value);
+            }
+            Ok(Some(reusable))
+        }
+
+        // Title and description of a URL, empty for URLs without any
+        pub async fn get_metadata(&self, url: &str) -> Result<ComparisonMetadata, Error> {
+            match self.comparison(url).await? {
+                Some(comparison) => comparison.metadata().await,
+                None => Ok(ComparisonMetadata::default()),
+            }
+        }
+
+        // Store the title, description and unit system of a URL, registering the URL if needed
+        pub async fn set_metadata(&self, url: &str, metadata: &ComparisonMetadata) -> Result<(), Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+            tx.execute("INSERT OR IGNORE INTO urls (url) VALUES (?)", [url])?;
+            tx.execute(
+                "UPDATE urls SET title = ?, description = ?, unit_system = ? WHERE url = ?",
+                [&metadata.title, &metadata.description, metadata.unit_system.as_str(), url],
+            )?;
+            touch_url(&tx, url_id(&tx, url)?)?;
+            tx.commit()?;
+            log!("[DB] Updated title, description and unit system of URL: {}", url);
+            Ok(())
+        }
+
+        // Mark a URL as decided for one of its items. Returns false, storing nothing,
+        // when the item isn't part of the URL
+        pub async fn set_decision(&self, url: &str, decision: &Decision) -> Result<bool, Error> {
+            let conn = self.conn.lock().await;
+            let updated = conn.execute(
+                &format!("UPDATE urls SET decision_item_id = ?1, decided_at = ?2, decision_rationale = ?3,
+                    updated_at = MAX({}, COALESCE(updated_at, 0) + 1)
+                WHERE url = ?4 AND EXISTS (SELECT 1 FROM items WHERE url_id = urls.id AND id = ?1 AND deleted_at IS NULL)", NOW_MILLIS),
+                rusqlite::params![&decision.item_id, decision.decided_at, &decision.rationale, url],
+            )?;
+            log!("[DB] Decided URL {} for item {}: {}", url, decision.item_id, updated > 0);
+            Ok(updated > 0)
+        }
+
+        // Reopen a decided URL, returning whether it was decided
+        pub async fn clear_decision(&self, url: &str) -> Result<bool, Error> {
+            let conn = self.conn.lock().await;
+            let updated = conn.execute(
+                &format!("UPDATE urls SET decision_item_id = NULL, decided_at = NULL, decision_rationale = '',
+                    updated_at = MAX({}, COALESCE(updated_at, 0) + 1)
+                WHERE url = ? AND decided_at IS NOT NULL", NOW_MILLIS),
+                [url],
+            )?;
+            Ok(updated > 0)
+        }
+
+        // Whether a property name has been stored before, by an item or a selection
+        pub async fn property_exists(&self, property: &str) -> Result<bool, Error> {
+            let conn = self.reader().await;
+            conn.query_row("SELECT EXISTS(SELECT 1 FROM properties WHERE name = ?)", [property], |row| {
+                row.get(0)
+            })
+        }
+
+        pub async fn add_selected_property(&self, url: &str, property: &str) -> Result<(), Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+            select_property(&tx, url, property)?;
+            tx.commit()?;
+            Ok(())
+        }
+
+        // Select a property for a URL and, in the same transaction, fill it in for the items
+        // without a value from the claims of their Wikidata entities. A property deleted from
+        // the URL before shows its values again. Returns the property's column
+        pub async fn add_property_column(
+            &self,
+            url: &str,
+            property: &str,
+            claims: &HashMap<String, EntityClaims>,
+        ) -> Result<PropertyColumn, Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+            let (url_id, property_id) = select_property(&tx, url, property)?;
+            tx.execute(
+                "DELETE FROM deleted_properties WHERE url_id = ? AND property_id = ?",
+                [url_id, property_id],
+            )?;
+
+            // Items of the URL that have no value yet, with their entities
+            let missing: Vec<(String, String)> = {
+                let mut stmt = tx.prepare(
+                    "SELECT i.global_item_id, i.wikidata_id
+                    FROM items i
+                    LEFT JOIN item_properties ip
+                        ON ip.global_item_id = i.global_item_id AND ip.property_id = ?2
+                    WHERE i.url_id = ?1 AND i.global_item_id IS NOT NULL
+                        AND i.wikidata_id IS NOT NULL AND COALESCE(ip.value, '') = ''",
+                )?;
+                let rows = stmt.query_map([url_id, property_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+                rows.collect::<Result<_, _>>()?
+            };
+
+            let mut backfilled = 0;
+            for (global_item_id, wikidata_id) in missing {
+                let Some(entity) = claims.get(&wikidata_id) else {
+                    continue;
+                };
+                let Some(value) = entity.values.get(property) else {
+                    continue;
+                };
+                store_claim(&tx, &global_item_id, property_id, value, entity.quantities.get(property))?;
+                backfilled += 1;
+            }
+
+            let column = PropertyColumn { backfilled, ..property_column(&tx, url_id, property_id, property)? };
+
+            tx.commit()?;
+            log!("[DB] Selected {} for URL: {}, backfilled {} items", property, url, backfilled);
+            Ok(column)
+        }
+
+        // Replace the names, descriptions and values of the selected Wikidata properties of a
+        // URL's items that came from Wikidata, or are still empty, with the labels, descriptions
+        // and claims of their entities. Values typed in or imported are kept. Returns the items
+        // that changed, with their values in full, and the values that changed
+        pub async fn refresh_from_wikidata(
+            &self,
+            url: &str,
+            claims: &HashMap<String, EntityClaims>,
+            terms: &HashMap<String, EntityTerms>,
+        ) -> Result<WikidataRefresh, Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+            let url_id = url_id(&tx, url)?;
+            let properties: Vec<(i64, String)> = {
+                let mut stmt = tx.prepare(
+                    "SELECT id, name FROM properties WHERE name IN ('name', 'description')
+                    UNION ALL
+                    SELECT p.id, p.name FROM selected_properties sp
+                    JOIN properties p ON p.id = sp.property_id
+                    WHERE sp.url_id = ?",
+                )?;
+                let rows = stmt.query_map([url_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+                rows.collect::<Result<Vec<(i64, String)>, _>>()?
+                    .into_iter()
+                    .filter(|(_, name)| ["name", "description"].contains(&name.as_str()) || is_property_id(name))
+                    .collect()
+            };
+            let items: Vec<(String, String, String)> = {
+                let mut stmt = tx.prepare(
+                    "SELECT id, global_item_id, wikidata_id FROM items
+                    WHERE url_id = ? AND deleted_at IS NULL
+                        AND global_item_id IS NOT NULL AND wikidata_id IS NOT NULL",
+                )?;
+                let rows = stmt.query_map([url_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+                rows.collect::<Result<_, _>>()?
+            };
+
+            let mut changes = Vec::new();
+            for (item_id, global_item_id, wikidata_id) in &items {
+                let entity = claims.get(wikidata_id);
+                let entity_terms = terms.get(wikidata_id);
+                for (property_id, property) in &properties {
+                    let value = match property.as_str() {
+                        "name" => entity_terms.and_then(|terms| terms.label.as_ref()),
+                        "description" => entity_terms.and_then(|terms| terms.description.as_ref()),
+                        _ => entity.and_then(|entity| entity.values.get(property)),
+                    };
+                    let Some(value) = value.filter(|value| !value.is_empty()) else {
+                        continue;
+                    };
+                    let hidden: bool = tx.query_row(
+                        "SELECT EXISTS(SELECT 1 FROM deleted_properties
+                            WHERE url_id = ? AND global_item_id = ? AND property_id = ?)",
+                        rusqlite::params![url_id, global_item_id, property_id],
+                        |row| row.get(0),
+                    )?;
+                    let stored: Option<(String, String)> = tx
+                        .query_row(
+                            "SELECT value, source FROM item_properties WHERE global_item_id = ? AND property_id = ?",
+                            rusqlite::params![global_item_id, property_id],
+                            |row| Ok((row.get(0)?, row.get(1)?)),
+                        )
+                        .optional()?;
+                    let previous = match stored {
+                        None => String::new(),
+                        Some((stored, _)) if stored.is_empty() => stored,
+                        Some((stored, source)) if source == ValueSource::Wikidata.as_str() && stored != *value => stored,
+                        Some(_) => continue,
+                    };
+                    if hidden {
+                        continue;
+                    }
+                    let quantity = entity.and_then(|entity| entity.quantities.get(property));
+                    store_claim(&tx, global_item_id, *property_id, value, quantity)?;
+                    changes.push(RefreshedValue {
+                        item_id: item_id.clone(),
+                        property: property.clone(),
+                        previous,
+                        value: value.clone(),
+                    });
+                }
+            }
+
+            let refreshed: HashSet<&String> = changes.iter().map(|change| &change.item_id).collect();
+            for item_id in &refreshed {
+                tx.execute(
+                    &format!("UPDATE items SET updated_at = MAX({}, COALESCE(updated_at, 0) + 1) WHERE id = ?", NOW_MILLIS),
+                    [item_id],
+                )?;
+            }
+            if !refreshed.is_empty() {
+                touch_url(&tx, url_id)?;
+            }
+            let (items, _) = query_items(&tx, url_id, true)?;
+            tx.commit()?;
+            log!("[DB] Refreshed {} values of {} items of URL {} from Wikidata", changes.len(), refreshed.len(), url);
+            let items = items.into_iter().filter(|item| refreshed.contains(&item.id)).collect();
+            Ok(WikidataRefresh { items, changes })
+        }
+
+        pub async fn get_selected_properties(&self, url: &str) -> Result<Vec<String>, Error> {
+            match self.comparison(url).await? {
+                Some(comparison) => comparison.properties().await,
+                None => Ok(Vec::new()),
+            }
+        }
+
+        // Store a new display order for the selected properties of a URL
+        pub async fn reorder_selected_properties(&self, url: &str, properties: &[String]) -> Result<(), Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+
+            let url_id = url_id(&tx, url)?;
+
+            for (position, property) in properties.iter().enumerate() {
+                tx.execute(
+                    "UPDATE selected_properties SET property_order = ?
+                    WHERE url_id = ? AND property_id = (SELECT id FROM properties WHERE name = ?)",
+                    rusqlite::params![position as i64, url_id, property],
+                )?;
+            }
+            touch_url(&tx, url_id)?;
+
+            tx.commit()?;
+            log!("[DB] Reordered {} properties for URL: {}", properties.len(), url);
+            Ok(())
+        }
+
+        // Rename a property selected for a URL, in one transaction: the selection keeps its
+        // position and settings, and the values of the URL's items move to the new name.
+        // Items sharing values with other URLs keep the old name there
+        pub async fn rename_property(&self, url: &str, property: &str, rename_to: &str) -> Result<PropertyRenaming, Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+            let url_id = url_id(&tx, url)?;
+            let selected: Option<i64> = tx
+                .query_row(
+                    "SELECT sp.property_id FROM selected_properties sp
+                    JOIN properties p ON p.id = sp.property_id
+                    WHERE sp.url_id = ? AND p.name = ?",
+                    rusqlite::params![url_id, property],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let Some(property_id) = selected else {
+                return Ok(PropertyRenaming::Unknown);
+            };
+
+            tx.execute("INSERT OR IGNORE INTO properties (name) VALUES (?)", [rename_to])?;
+            let renamed_id: i64 = tx.query_row("SELECT id FROM properties WHERE name = ?", [rename_to], |row| row.get(0))?;
+            // The new name must not be a column of the URL already, selected or with values shown
+            let taken: bool = tx.query_row(
+                "SELECT EXISTS (SELECT 1 FROM selected_properties WHERE url_id = ?1 AND property_id = ?2)
+                    OR EXISTS (
+                        SELECT 1 FROM items i
+                        JOIN item_properties ip ON ip.global_item_id = i.global_item_id AND ip.property_id = ?2
+                        WHERE i.url_id = ?1 AND ip.value != ''
+                            AND NOT EXISTS (
+                                SELECT 1 FROM deleted_properties dp
+                                WHERE dp.url_id = ?1 AND dp.global_item_id = i.global_item_id AND dp.property_id = ?2
+                            )
+                    )",
+                [url_id, renamed_id],
+                |row| row.get(0),
+            )?;
+            if taken {
+                return Ok(PropertyRenaming::Taken);
+            }
+
+            // Values the items of the URL show, copied to the new name and hidden under the old one
+            let shown = "SELECT i.global_item_id FROM items i
+                WHERE i.url_id = ?1 AND i.global_item_id IS NOT NULL
+                    AND NOT EXISTS (
+                        SELECT 1 FROM deleted_properties dp
+                        WHERE dp.url_id = ?1 AND dp.global_item_id = i.global_item_id AND dp.property_id = ?2
+                    )";
+            tx.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO item_properties (global_item_id, property_id, value,
+                        quantity_amount, quantity_unit_id, quantity_unit, quantity_precision, source)
+                    SELECT global_item_id, ?3, value, quantity_amount, quantity_unit_id, quantity_unit, quantity_precision, source
+                    FROM item_properties WHERE property_id = ?2 AND global_item_id IN ({shown})"
+                ),
+                [url_id, property_id, renamed_id],
+            )?;
+            tx.execute(
+                &format!(
+                    "INSERT OR REPLACE INTO long_values (global_item_id, property_id, value)
+                    SELECT global_item_id, ?3, value FROM long_values WHERE property_id = ?2 AND global_item_id IN ({shown})"
+                ),
+                [url_id, property_id, renamed_id],
+            )?;
+            let renamed_items = tx.execute(
+                &format!(
+                    "UPDATE items SET updated_at = MAX({now}, COALESCE(updated_at, 0) + 1)
+                    WHERE url_id = ?1 AND deleted_at IS NULL AND global_item_id IN (
+                        SELECT global_item_id FROM item_properties WHERE property_id = ?2 AND global_item_id IN ({shown})
+                    )",
+                    now = NOW_MILLIS
+                ),
+                [url_id, property_id],
+            )?;
+            tx.execute(
+                "INSERT OR IGNORE INTO deleted_properties (url_id, global_item_id, property_id)
+                SELECT url_id, global_item_id, ? FROM items WHERE url_id = ? AND global_item_id IS NOT NULL",
+                [property_id, url_id],
+            )?;
+            tx.execute("DELETE FROM deleted_properties WHERE url_id = ? AND property_id = ?", [url_id, renamed_id])?;
+
+            tx.execute(
+                "UPDATE selected_properties SET property_id = ? WHERE url_id = ? AND property_id = ?",
+                [renamed_id, url_id, property_id],
+            )?;
+            for table in ["property_settings", "property_overrides"] {
+                tx.execute(
+                    &format!("UPDATE OR REPLACE {} SET property_id = ? WHERE url_id = ? AND property_id = ?", table),
+                    [renamed_id, url_id, property_id],
+                )?;
+            }
+            let column = property_column(&tx, url_id, renamed_id, rename_to)?;
+            touch_url(&tx, url_id)?;
+
+            tx.commit()?;
+            log!("[DB] Renamed {} to {} for URL: {}, {} items changed", property, rename_to, url, renamed_items);
+            Ok(PropertyRenaming::Renamed(column))
+        }
+
+        // Record a heartbeat from an edit session and update the cell it holds locked.
+        // A session holds at most one lock, and a cell locked by another session is not taken over
+        pub async fn record_heartbeat(&self, url: &str, heartbeat: &Heartbeat, now: i64) -> Result<PresenceState, Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "INSERT INTO edit_sessions (session_id, url, last_seen) VALUES (?, ?, ?)
+                ON CONFLICT(session_id) DO UPDATE SET
+                    url = excluded.url,
+                    last_seen = excluded.last_seen",
+                rusqlite::params![&heartbeat.session_id, url, now],
+            )?;
+
+            match &heartbeat.cell {
+                Some(cell) => {
+                    tx.execute(
+                        "DELETE FROM cell_locks WHERE session_id = ? AND (url != ? OR cell != ?)",
+                        rusqlite::params![&heartbeat.session_id, url, cell],
+                    )?;
+                    tx.execute(
+                        "INSERT OR IGNORE INTO cell_locks (url, cell, session_id, acquired_at) VALUES (?, ?, ?, ?)",
+                        rusqlite::params![url, cell, &heartbeat.session_id, now],
+                    )?;
+                }
+                None => {
+                    tx.execute("DELETE FROM cell_locks WHERE session_id = ?", [&heartbeat.session_id])?;
+                }
+            }
+
+            let editors: i64 = tx.query_row(
+                "SELECT COUNT(*) FROM edit_sessions WHERE url = ?",
+                [url],
+                |row| row.get(0),
+            )?;
+            let locks = {
+                let mut stmt = tx.prepare("SELECT cell, session_id FROM cell_locks WHERE url = ? ORDER BY cell")?;
+                let rows = stmt.query_map([url], |row| {
+                    Ok(CellLock {
+                        cell: row.get(0)?,
+                        session_id: row.get(1)?,
+                    })
                 })?;
-            
-                mapped_rows.collect::<Result<HashMap<String, String>, _>>()?
+                rows.collect::<Result<Vec<_>, _>>()?
+            };
+
+            tx.commit()?;
+            Ok(PresenceState {
+                editors: editors as usize,
+                locks,
+            })
+        }
+
+        // Remove sessions that haven't sent a heartbeat since `cutoff` together with their locks
+        pub async fn expire_stale_sessions(&self, cutoff: i64) -> Result<usize, Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+
+            tx.execute(
+                "DELETE FROM cell_locks WHERE session_id NOT IN (
+                    SELECT session_id FROM edit_sessions WHERE last_seen >= ?
+                )",
+                [cutoff],
+            )?;
+            let expired = tx.execute("DELETE FROM edit_sessions WHERE last_seen < ?", [cutoff])?;
+
+            tx.commit()?;
+            if expired > 0 {
+                log!("[DB] Expired {} stale edit sessions", expired);
+            }
+            Ok(expired)
+        }
+
+        // Count the active edit sessions and locks per URL
+        pub async fn editor_metrics(&self) -> Result<EditorMetrics, Error> {
+            let conn = self.reader().await;
+            let mut stmt = conn.prepare(
+                "SELECT url, COUNT(*) FROM edit_sessions GROUP BY url ORDER BY COUNT(*) DESC, url",
+            )?;
+            let urls = stmt
+                .query_map([], |row| {
+                    Ok(UrlEditors {
+                        url: row.get(0)?,
+                        editors: row.get::<_, i64>(1)? as usize,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            let active_locks: i64 = conn.query_row("SELECT COUNT(*) FROM cell_locks", [], |row| row.get(0))?;
+
+            Ok(EditorMetrics {
+                active_editors: urls.iter().map(|url| url.editors).sum(),
+                active_locks: active_locks as usize,
+                urls,
+            })
+        }
+
+        // List every registered comparison URL
+        pub async fn get_urls(&self) -> Result<Vec<String>, Error> {
+            let conn = self.reader().await;
+            let mut stmt = conn.prepare("SELECT url FROM urls ORDER BY id")?;
+            let urls = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+            Ok(urls)
+        }
+
+        // Page through the public comparisons of this instance, most recently updated first.
+        // `search` matches titles and URLs, `page` starts at 1
+        pub async fn get_comparison_index(
+            &self,
+            search: &str,
+            decided_only: bool,
+            page: usize,
+            per_page: usize,
+        ) -> Result<ComparisonIndex, Error> {
+            let conn = self.reader().await;
+            let pattern = contains_pattern(search.trim());
+            let filter = "FROM urls u
+                LEFT JOIN comparison_listings l ON l.url_id = u.id
+                WHERE (u.url LIKE ?1 ESCAPE '\\' OR u.title LIKE ?1 ESCAPE '\\' OR l.title LIKE ?1 ESCAPE '\\')
+                    AND (?2 = 0 OR u.decided_at IS NOT NULL)
+                    AND u.visibility = 'public'";
+
+            let total: i64 = conn.query_row(
+                &format!("SELECT COUNT(*) {}", filter),
+                rusqlite::params![pattern, decided_only],
+                |row| row.get(0),
+            )?;
+            let mut stmt = conn.prepare(&format!(
+                "SELECT u.url, COALESCE(NULLIF(u.title, ''), l.title),
+                    (SELECT COUNT(*) FROM items i WHERE i.url_id = u.id AND i.deleted_at IS NULL),
+                    COALESCE(u.updated_at / 1000, CAST(strftime('%s', u.created_at) AS INTEGER), 0) AS updated_at,
+                    u.decided_at
+                {}
+                ORDER BY updated_at DESC, u.id DESC
+                LIMIT ?3 OFFSET ?4",
+                filter
+            ))?;
+            let offset = page.saturating_sub(1) * per_page;
+            let comparisons = stmt
+                .query_map(rusqlite::params![pattern, decided_only, per_page as i64, offset as i64], |row| {
+                    let url: String = row.get(0)?;
+                    let title = row.get::<_, Option<String>>(1)?.unwrap_or_else(|| default_title(&url));
+                    Ok(ComparisonSummary {
+                        url,
+                        title,
+                        item_count: row.get::<_, i64>(2)? as usize,
+                        updated_at: row.get(3)?,
+                        decided_at: row.get(4)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(ComparisonIndex { comparisons, total: total as usize, page: page.max(1), per_page })
+        }
+
+        // Rows each comparison holds, for the heaviest comparisons report
+        pub async fn comparison_rows(&self) -> Result<Vec<ComparisonRows>, Error> {
+            let conn = self.reader().await;
+            let mut stmt = conn.prepare(
+                "SELECT u.url,
+                    (SELECT COUNT(*) FROM items i WHERE i.url_id = u.id AND i.deleted_at IS NULL),
+                    (SELECT COUNT(*) FROM items i
+                        WHERE i.url_id = u.id AND i.deleted_at IS NULL AND i.status IN (?1, ?2)),
+                    (SELECT COUNT(*) FROM items i WHERE i.url_id = u.id AND i.deleted_at IS NOT NULL),
+                    (SELECT COUNT(*) FROM selected_properties sp WHERE sp.url_id = u.id),
+                    (SELECT COUNT(*) FROM item_properties ip WHERE ip.global_item_id IN
+                        (SELECT i.global_item_id FROM items i WHERE i.url_id = u.id AND i.deleted_at IS NULL)),
+                    (SELECT COUNT(*) FROM item_revisions r WHERE r.url_id = u.id)
+                FROM urls u
+                ORDER BY u.id",
+            )?;
+            let rows = stmt
+                .query_map([ItemStatus::Rejected.as_str(), ItemStatus::Discontinued.as_str()], |row| {
+                    Ok(ComparisonRows {
+                        url: row.get(0)?,
+                        items: row.get::<_, i64>(1)? as usize,
+                        inactive_items: row.get::<_, i64>(2)? as usize,
+                        trashed_items: row.get::<_, i64>(3)? as usize,
+                        properties: row.get::<_, i64>(4)? as usize,
+                        values: row.get::<_, i64>(5)? as usize,
+                        revisions: row.get::<_, i64>(6)? as usize,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        }
+
+        // Items of all public comparisons with values containing words that start with the words
+        // of `search`, best matches first, one hit per item. Values of properties a comparison
+        // doesn't show and items in the trash are left out
+        pub async fn search_items(&self, search: &str, limit: usize) -> Result<Vec<SearchHit>, Error> {
+            let Some(query) = full_text_query(search) else {
+                return Ok(Vec::new());
+            };
+            let conn = self.reader().await;
+            let snippet = |table: &str| format!("snippet({}, 0, '{}', '{}', '…', 12)", table, MATCH_START, MATCH_END);
+            let mut stmt = conn.prepare(&format!(
+                "WITH matches AS (
+                    SELECT global_item_id, property_id, {item_snippet} AS snippet, rank
+                    FROM item_search WHERE item_search MATCH ?1
+                    UNION ALL
+                    SELECT lv.global_item_id, lv.property_id, {long_snippet}, long_value_search.rank
+                    FROM long_value_search
+                    JOIN long_values lv
+                        ON lv.global_item_id = long_value_search.global_item_id AND lv.property_id = long_value_search.property_id
+                    JOIN item_properties ip ON ip.global_item_id = lv.global_item_id AND ip.property_id = lv.property_id
+                    WHERE long_value_search MATCH ?1 AND {long_value}
+                )
+                SELECT u.url, COALESCE(NULLIF(u.title, ''), l.title), i.id, name_ip.value, p.name, m.snippet, MIN(m.rank)
+                FROM matches m
+                JOIN items i ON i.global_item_id = m.global_item_id AND i.deleted_at IS NULL
+                JOIN urls u ON u.id = i.url_id
+                JOIN properties p ON p.id = m.property_id
+                LEFT JOIN comparison_listings l ON l.url_id = u.id
+                LEFT JOIN item_properties name_ip
+                    ON name_ip.global_item_id = i.global_item_id
+                    AND name_ip.property_id = (SELECT id FROM properties WHERE name = 'name')
+                WHERE u.visibility = 'public'
+                    AND (p.name IN ('name', 'description')
+                        OR EXISTS (SELECT 1 FROM selected_properties sp WHERE sp.url_id = u.id AND sp.property_id = m.property_id))
+                    AND NOT EXISTS (
+                        SELECT 1 FROM deleted_properties dp
+                        WHERE dp.url_id = u.id AND dp.global_item_id = m.global_item_id AND dp.property_id = m.property_id
+                    )
+                GROUP BY i.id
+                ORDER BY MIN(m.rank), u.id, i.item_order
+                LIMIT ?2",
+                item_snippet = snippet("item_search"),
+                long_snippet = snippet("long_value_search"),
+                long_value = long_value_matches(),
+            ))?;
+            let hits = stmt
+                .query_map(rusqlite::params![query, limit as i64], |row| {
+                    let url: String = row.get(0)?;
+                    let title = row.get::<_, Option<String>>(1)?.unwrap_or_else(|| default_title(&url));
+                    Ok(SearchHit {
+                        url,
+                        title,
+                        item_id: row.get(2)?,
+                        item_name: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                        property: row.get(4)?,
+                        snippet: snippet_parts(&row.get::<_, String>(5)?),
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(hits)
+        }
+
+        // Write a consistent copy of the whole database to a new file, for off-machine backups.
+        // Fails when the file already exists
+        pub async fn copy_database_to(&self, path: &str) -> Result<(), Error> {
+            let conn = self.conn.lock().await;
+            conn.execute("VACUUM INTO ?", [path])?;
+            log!("[DB] Copied the database to {}", path);
+            Ok(())
+        }
+
+        // Replace the whole database with a copy like the ones copy_database_to writes, and bring
+        // it up to the schema of this build. Callers check the copy with database_copy_problem
+        // first, this only fails on copies SQLite can't read
+        pub async fn restore_database_from(&self, path: &str) -> Result<(), Error> {
+            let copy = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            let mut conn = self.conn.lock().await;
+            Backup::new(&copy, &mut conn)?.run_to_completion(RESTORE_PAGES_PER_STEP, std::time::Duration::ZERO, None)?;
+            migrate(&mut conn, MIGRATIONS)?;
+            log!("[DB] Restored the database from {}", path);
+            Ok(())
+        }
+
+        // A comparison with its items, selected properties and metadata
+        pub async fn export_comparison(&self, url: &str) -> Result<ComparisonArchive, Error> {
+            Ok(ComparisonArchive {
+                url: url.to_string(),
+                items: self.get_items_by_url(url).await?,
+                selected_properties: self.get_selected_properties(url).await?,
+                metadata: self.get_metadata(url).await?,
+            })
+        }
+
+        // Collect all comparisons with their items and selected properties
+        pub async fn export_workspace(&self, created_at: i64) -> Result<WorkspaceArchive, Error> {
+            let mut comparisons = Vec::new();
+            for url in self.get_urls().await? {
+                comparisons.push(self.export_comparison(&url).await?);
+            }
+            log!("[DB] Exported {} comparisons", comparisons.len());
+            Ok(WorkspaceArchive {
+                version: 1,
+                created_at,
+                comparisons,
+            })
+        }
+
+        // Write the comparisons of an archive back, returning the number of restored items
+        pub async fn restore_workspace(&self, archive: &WorkspaceArchive) -> Result<usize, Error> {
+            let mut restored_items = 0;
+            for comparison in &archive.comparisons {
+                restored_items += self.restore_comparison(comparison).await?;
+            }
+            log!("[DB] Restored {} comparisons with {} items", archive.comparisons.len(), restored_items);
+            Ok(restored_items)
+        }
+
+        // Write one archived comparison back, returning the number of restored items
+        pub async fn restore_comparison(&self, comparison: &ComparisonArchive) -> Result<usize, Error> {
+            self.insert_url(&comparison.url).await?;
+            self.insert_items_by_url(&comparison.url, &comparison.items).await?;
+            for property in &comparison.selected_properties {
+                self.add_selected_property(&comparison.url, property).await?;
+            }
+            self.reorder_selected_properties(&comparison.url, &comparison.selected_properties).await?;
+            if comparison.metadata != ComparisonMetadata::default() {
+                self.set_metadata(&comparison.url, &comparison.metadata).await?;
+            }
+            if let Some(decision) = &comparison.metadata.decision {
+                self.set_decision(&comparison.url, decision).await?;
+            }
+            Ok(comparison.items.len())
+        }
+
+        // Store a received comparison event, keeping only the newest version per address.
+        // Ties on created_at are broken by the lowest event ID, as NIP-01 does for replaceable events
+        pub async fn store_remote_comparison(&self, comparison: &RemoteComparison) -> Result<RemoteUpdate, Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+
+            let stored = match tx.query_row(
+                "SELECT event_id, created_at, relays FROM remote_comparisons WHERE address = ?",
+                [&comparison.address],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?)),
+            ) {
+                Ok(stored) => Some(stored),
+                Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                Err(e) => return Err(e),
+            };
+
+            let update = match stored {
+                None => RemoteUpdate::Inserted,
+                Some((event_id, _, relays)) if event_id == comparison.event_id => {
+                    // Same version from another relay, only remember where it was seen
+                    let mut relays: Vec<String> = serde_json::from_str(&relays).unwrap_or_default();
+                    for relay in &comparison.relays {
+                        if !relays.contains(relay) {
+                            relays.push(relay.clone());
+                        }
+                    }
+                    tx.execute(
+                        "UPDATE remote_comparisons SET relays = ? WHERE address = ?",
+                        [&serde_json::to_string(&relays).unwrap_or_default(), &comparison.address],
+                    )?;
+                    RemoteUpdate::Duplicate
+                }
+                Some((event_id, created_at, _))
+                    if (comparison.created_at, std::cmp::Reverse(&comparison.event_id))
+                        > (created_at, std::cmp::Reverse(&event_id)) =>
+                {
+                    RemoteUpdate::Replaced
+                }
+                Some(_) => RemoteUpdate::Stale,
+            };
+
+            if matches!(update, RemoteUpdate::Inserted | RemoteUpdate::Replaced) {
+                tx.execute(
+                    "INSERT INTO remote_comparisons (address, event_id, author, created_at, content, relays)
+                    VALUES (?, ?, ?, ?, ?, ?)
+                    ON CONFLICT(address) DO UPDATE SET
+                        event_id = excluded.event_id,
+                        author = excluded.author,
+                        created_at = excluded.created_at,
+                        content = excluded.content,
+                        relays = excluded.relays",
+                    rusqlite::params![
+                        &comparison.address,
+                        &comparison.event_id,
+                        &comparison.author,
+                        comparison.created_at,
+                        &comparison.content,
+                        serde_json::to_string(&comparison.relays).unwrap_or_default()
+                    ],
+                )?;
+            }
+
+            tx.commit()?;
+            log!("[DB] Remote comparison {}: {:?}", comparison.address, update);
+            Ok(update)
+        }
+
+        // Retrieve the stored remote comparisons, most recent first
+        pub async fn get_remote_comparisons(&self) -> Result<Vec<RemoteComparison>, Error> {
+            let conn = self.conn.lock().await;
+            let mut stmt = conn.prepare(
+                "SELECT address, event_id, author, created_at, content, relays
+                FROM remote_comparisons
+                ORDER BY created_at DESC",
+            )?;
+            let comparisons = stmt
+                .query_map([], |row| {
+                    Ok(RemoteComparison {
+                        address: row.get(0)?,
+                        event_id: row.get(1)?,
+                        author: row.get(2)?,
+                        created_at: row.get(3)?,
+                        content: row.get(4)?,
+                        relays: serde_json::from_str(&row.get::<_, String>(5)?).unwrap_or_default(),
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(comparisons)
+        }
+
+        // List a comparison in the public directory, or update its listing
+        pub async fn set_listing(&self, url: &str, listing: &ComparisonListing, listed_at: i64) -> Result<(), Error> {
+            let conn = self.conn.lock().await;
+            let url_id = url_id(&conn, url)?;
+            conn.execute(
+                "INSERT INTO comparison_listings (url_id, title, category, listed_at)
+                VALUES (?, ?, ?, ?)
+                ON CONFLICT(url_id) DO UPDATE SET
+                    title = excluded.title,
+                    category = excluded.category,
+                    listed_at = excluded.listed_at",
+                rusqlite::params![url_id, &listing.title, &listing.category, listed_at],
+            )?;
+            log!("[DB] Listed {} as \"{}\"", url, listing.title);
+            Ok(())
+        }
+
+        pub async fn get_listing(&self, url: &str) -> Result<Option<ComparisonListing>, Error> {
+            let conn = self.reader().await;
+            match conn.query_row(
+                "SELECT l.title, l.category
+                FROM comparison_listings l
+                JOIN urls u ON l.url_id = u.id
+                WHERE u.url = ?",
+                [url],
+                |row| Ok(ComparisonListing { title: row.get(0)?, category: row.get(1)? }),
+            ) {
+                Ok(listing) => Ok(Some(listing)),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+
+        // Remove a comparison from the directory, returning whether it was listed
+        pub async fn remove_listing(&self, url: &str) -> Result<bool, Error> {
+            let conn = self.conn.lock().await;
+            let removed = conn.execute(
+                "DELETE FROM comparison_listings WHERE url_id = (SELECT id FROM urls WHERE url = ?)",
+                [url],
+            )?;
+            Ok(removed > 0)
+        }
+
+        // Settings of the properties of a URL that changed them, by property name
+        pub async fn get_property_settings(&self, url: &str) -> Result<HashMap<String, PropertySettings>, Error> {
+            let conn = self.reader().await;
+            let mut stmt = conn.prepare(
+                "SELECT p.name, ps.lang, ps.spellcheck, ps.better, ps.weight
+                FROM property_settings ps
+                JOIN urls u ON ps.url_id = u.id
+                JOIN properties p ON ps.property_id = p.id
+                WHERE u.url = ?",
+            )?;
+            let settings = stmt
+                .query_map([url], |row| {
+                    let better: Option<String> = row.get(3)?;
+                    Ok((row.get(0)?, PropertySettings {
+                        lang: row.get(1)?,
+                        spellcheck: row.get(2)?,
+                        better: better.as_deref().and_then(BetterValue::from_name),
+                        weight: row.get(4)?,
+                    }))
+                })?
+                .collect::<Result<HashMap<_, _>, _>>()?;
+            Ok(settings)
+        }
+
+        // Store the settings of a property, default settings remove the stored ones.
+        // Returns false when the URL or the property is unknown
+        pub async fn set_property_settings(&self, url: &str, property: &str, settings: &PropertySettings) -> Result<bool, Error> {
+            let conn = self.conn.lock().await;
+            let ids = conn.query_row(
+                "SELECT u.id, p.id FROM urls u, properties p WHERE u.url = ? AND p.name = ?",
+                [url, property],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+            );
+            let (url_id, property_id) = match ids {
+                Ok(ids) => ids,
+                Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(false),
+                Err(e) => return Err(e),
+            };
+            if settings.is_default() {
+                conn.execute(
+                    "DELETE FROM property_settings WHERE url_id = ? AND property_id = ?",
+                    [url_id, property_id],
+                )?;
+            } else {
+                conn.execute(
+                    "INSERT INTO property_settings (url_id, property_id, lang, spellcheck, better, weight)
+                    VALUES (?, ?, ?, ?, ?, ?)
+                    ON CONFLICT(url_id, property_id) DO UPDATE SET
+                        lang = excluded.lang,
+                        spellcheck = excluded.spellcheck,
+                        better = excluded.better,
+                        weight = excluded.weight",
+                    rusqlite::params![
+                        url_id,
+                        property_id,
+                        &settings.lang,
+                        settings.spellcheck,
+                        settings.better.map(|better| better.as_str()),
+                        settings.weight
+                    ],
+                )?;
+            }
+            Ok(true)
+        }
+
+        // Labels and tooltips of the properties of a URL that override their defaults
+        pub async fn get_property_overrides(&self, url: &str) -> Result<HashMap<String, PropertyOverride>, Error> {
+            let conn = self.reader().await;
+            let mut stmt = conn.prepare(
+                "SELECT p.name, po.label, po.description
+                FROM property_overrides po
+                JOIN urls u ON po.url_id = u.id
+                JOIN properties p ON po.property_id = p.id
+                WHERE u.url = ?",
+            )?;
+            let overrides = stmt
+                .query_map([url], |row| {
+                    Ok((row.get(0)?, PropertyOverride { label: row.get(1)?, description: row.get(2)? }))
+                })?
+                .collect::<Result<HashMap<_, _>, _>>()?;
+            Ok(overrides)
+        }
+
+        // Store the label and tooltip of a property, an empty override removes the stored one.
+        // Returns false when the URL or the property is unknown
+        pub async fn set_property_override(&self, url: &str, property: &str, property_override: &PropertyOverride) -> Result<bool, Error> {
+            let conn = self.conn.lock().await;
+            let ids = conn.query_row(
+                "SELECT u.id, p.id FROM urls u, properties p WHERE u.url = ? AND p.name = ?",
+                [url, property],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+            );
+            let (url_id, property_id) = match ids {
+                Ok(ids) => ids,
+                Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(false),
+                Err(e) => return Err(e),
+            };
+            if property_override.is_default() {
+                conn.execute(
+                    "DELETE FROM property_overrides WHERE url_id = ? AND property_id = ?",
+                    [url_id, property_id],
+                )?;
+            } else {
+                conn.execute(
+                    "INSERT INTO property_overrides (url_id, property_id, label, description)
+                    VALUES (?, ?, ?, ?)
+                    ON CONFLICT(url_id, property_id) DO UPDATE SET
+                        label = excluded.label,
+                        description = excluded.description",
+                    rusqlite::params![url_id, property_id, &property_override.label, &property_override.description],
+                )?;
+            }
+            touch_url(&conn, url_id)?;
+            Ok(true)
+        }
+
+        // Nostr publishing of a comparison, disabled without relays when never set
+        pub async fn get_publishing_settings(&self, url: &str) -> Result<PublishingSettings, Error> {
+            let conn = self.reader().await;
+            match conn.query_row(
+                "SELECT p.enabled, p.relays
+                FROM nostr_publishing p
+                JOIN urls u ON p.url_id = u.id
+                WHERE u.url = ?",
+                [url],
+                |row| {
+                    Ok(PublishingSettings {
+                        enabled: row.get(0)?,
+                        relays: serde_json::from_str(&row.get::<_, String>(1)?).unwrap_or_default(),
+                    })
+                },
+            ) {
+                Ok(settings) => Ok(settings),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(PublishingSettings::default()),
+                Err(e) => Err(e),
+            }
+        }
+
+        pub async fn set_publishing_settings(&self, url: &str, settings: &PublishingSettings) -> Result<(), Error> {
+            let conn = self.conn.lock().await;
+            let url_id = url_id(&conn, url)?;
+            conn.execute(
+                "INSERT INTO nostr_publishing (url_id, enabled, relays)
+                VALUES (?, ?, ?)
+                ON CONFLICT(url_id) DO UPDATE SET
+                    enabled = excluded.enabled,
+                    relays = excluded.relays",
+                rusqlite::params![url_id, settings.enabled, serde_json::to_string(&settings.relays).unwrap_or_default()],
+            )?;
+            log!("[DB] Publishing of {} {}", url, if settings.enabled { "enabled" } else { "disabled" });
+            Ok(())
+        }
+
+        // Mirroring of a comparison, disabled without a source when never set
+        pub async fn get_mirror_settings(&self, url: &str) -> Result<MirrorSettings, Error> {
+            let conn = self.reader().await;
+            match conn.query_row(
+                "SELECT m.enabled, m.source_url, m.author, m.relays
+                FROM comparison_mirrors m
+                JOIN urls u ON m.url_id = u.id
+                WHERE u.url = ?",
+                [url],
+                mirror_settings_from_row,
+            ) {
+                Ok(settings) => Ok(settings),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(MirrorSettings::default()),
+                Err(e) => Err(e),
+            }
+        }
+
+        // Store the mirroring of a comparison. Mirroring another source forgets the
+        // items merged from the previous one, they stay in the comparison as local items
+        pub async fn set_mirror_settings(&self, url: &str, settings: &MirrorSettings) -> Result<(), Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+            let url_id = url_id(&tx, url)?;
+            let previous = match tx.query_row(
+                "SELECT enabled, source_url, author, relays FROM comparison_mirrors WHERE url_id = ?",
+                [url_id],
+                mirror_settings_from_row,
+            ) {
+                Ok(previous) => Some(previous),
+                Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                Err(e) => return Err(e),
             };
+            if previous.is_some_and(|previous| !previous.same_source(settings)) {
+                tx.execute("DELETE FROM mirrored_items WHERE url_id = ?", [url_id])?;
+            }
+            tx.execute(
+                "INSERT INTO comparison_mirrors (url_id, enabled, source_url, author, relays)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(url_id) DO UPDATE SET
+                    enabled = excluded.enabled,
+                    source_url = excluded.source_url,
+                    author = excluded.author,
+                    relays = excluded.relays",
+                rusqlite::params![
+                    url_id,
+                    settings.enabled,
+                    &settings.source_url,
+                    &settings.author,
+                    serde_json::to_string(&settings.relays).unwrap_or_default()
+                ],
+            )?;
+            tx.commit()?;
+            log!("[DB] Mirroring of {} {}", url, if settings.enabled { "enabled" } else { "disabled" });
+            Ok(())
+        }
 
-            // Include core properties in current_props check
-            let mut current_props: HashSet<&str> = item.custom_properties.keys()
-                .map(|s| s.as_str())
-                .collect();
-            current_props.insert("name");
-            current_props.insert("description");
-            
-            // Cleanup with core property protection
-            for (existing_prop, _) in existing_props {
-                if !current_props.contains(existing_prop.as_str()) 
-                    && !["name", "description"].contains(&existing_prop.as_str())
-                {
-                    log!("[DB] Removing deleted property {}", existing_prop);
-                    tx.execute(
-                        "DELETE FROM item_properties 
-                        WHERE item_id = ? 
-                        AND property_id = (SELECT id FROM properties WHERE name = ?)",
-                        rusqlite::params![&item.id, existing_prop],
-                    )?;
+        // Comparisons with mirroring enabled, as (URL, settings)
+        pub async fn get_enabled_mirrors(&self) -> Result<Vec<(String, MirrorSettings)>, Error> {
+            let conn = self.conn.lock().await;
+            let mut stmt = conn.prepare(
+                "SELECT m.enabled, m.source_url, m.author, m.relays, u.url
+                FROM comparison_mirrors m
+                JOIN urls u ON m.url_id = u.id
+                WHERE m.enabled = 1
+                ORDER BY u.url",
+            )?;
+            let mirrors = stmt.query_map([], |row| Ok((row.get::<_, String>(4)?, mirror_settings_from_row(row)?)))?;
+            mirrors.collect()
+        }
+
+        // Merge an item received from the mirrored comparison. The item gets an ID of its
+        // own here, and local changes made since the last merge win over the received version
+        pub async fn merge_mirrored_item(&self, url: &str, item: &Item, created_at: i64) -> Result<MirrorUpdate, Error> {
+            let mut conn = self.conn.lock().await;
+            let mut tx = conn.transaction()?;
+            let url_id = url_id(&tx, url)?;
+            let synced = match tx.query_row(
+                "SELECT item_id, created_at, synced_item FROM mirrored_items WHERE url_id = ? AND source_item_id = ?",
+                rusqlite::params![url_id, &item.id],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?)),
+            ) {
+                Ok(synced) => Some(synced),
+                Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                Err(e) => return Err(e),
+            };
+            // Every state of the source comparison carries all of its items, most unchanged.
+            // Versions are those of each instance and left out
+            let unchanged_at_source = |local_id: &str, synced_item: &str| {
+                serde_json::from_str::<Item>(synced_item)
+                    .is_ok_and(|synced| Item { updated_at: None, ..synced } == Item { id: local_id.to_string(), updated_at: None, ..item.clone() })
+            };
+            let local_id = match synced {
+                Some((_, synced_at, _)) if synced_at >= created_at => return Ok(MirrorUpdate::Stale),
+                Some((local_id, _, synced_item)) if unchanged_at_source(&local_id, &synced_item) => {
+                    return Ok(MirrorUpdate::Stale)
+                }
+                Some((local_id, _, synced_item)) => {
+                    let (items, _) = query_items(&tx, url_id, true)?;
+                    let local = items.iter().find(|local| local.id == local_id);
+                    let unchanged = local.is_some_and(|local| {
+                        serde_json::to_value(local).ok() == serde_json::from_str::<serde_json::Value>(&synced_item).ok()
+                    });
+                    if !unchanged {
+                        log!("[DB] Kept local changes of item {} in {}", local_id, url);
+                        return Ok(MirrorUpdate::Conflict);
+                    }
+                    local_id
+                }
+                None => Uuid::new_v4().to_string(),
+            };
+
+            let mut added_properties = Vec::new();
+            for property in item.custom_properties.keys() {
+                let selected: bool = tx.query_row(
+                    "SELECT EXISTS(
+                        SELECT 1 FROM selected_properties sp
+                        JOIN properties p ON sp.property_id = p.id
+                        WHERE sp.url_id = ? AND p.name = ?
+                    )",
+                    rusqlite::params![url_id, property],
+                    |row| row.get(0),
+                )?;
+                if !selected {
+                    select_property(&tx, url, property)?;
+                    added_properties.push(property.clone());
                 }
             }
+            added_properties.sort();
+
+            let local_item = Item { id: local_id.clone(), ..item.clone() };
+            self.upsert_item(&mut tx, url_id, &local_item).await?;
+            // Later versions are compared against the item as stored
+            let (items, _) = query_items(&tx, url_id, true)?;
+            let stored = items.into_iter().find(|stored| stored.id == local_id).unwrap_or(local_item);
+            tx.execute(
+                "INSERT INTO mirrored_items (url_id, source_item_id, item_id, created_at, synced_item)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(url_id, source_item_id) DO UPDATE SET
+                    item_id = excluded.item_id,
+                    created_at = excluded.created_at,
+                    synced_item = excluded.synced_item",
+                rusqlite::params![url_id, &item.id, &local_id, created_at, serde_json::to_string(&stored).unwrap_or_default()],
+            )?;
             tx.commit()?;
-            log!("[DB] Transaction committed successfully");
+            log!("[DB] Merged mirrored item {} into {} as {}", item.id, url, local_id);
+            Ok(MirrorUpdate::Saved { item: Box::new(stored), added_properties })
+        }
+
+        // Settings saved by an admin, None until they first are
+        pub async fn get_instance_settings(&self) -> Result<Option<InstanceSettings>, Error> {
+            let conn = self.conn.lock().await;
+            match conn.query_row(
+                "SELECT wikidata_client_rpm, wikidata_global_rpm, wikidata_language, private, features
+                FROM instance_settings WHERE id = 1",
+                [],
+                |row| {
+                    Ok(InstanceSettings {
+                        wikidata_client_rpm: row.get(0)?,
+                        wikidata_global_rpm: row.get(1)?,
+                        wikidata_language: row.get(2)?,
+                        private: row.get(3)?,
+                        features: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+                    })
+                },
+            ) {
+                Ok(settings) => Ok(Some(settings)),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+
+        pub async fn set_instance_settings(&self, settings: &InstanceSettings) -> Result<(), Error> {
+            let conn = self.conn.lock().await;
+            conn.execute(
+                "INSERT INTO instance_settings (id, wikidata_client_rpm, wikidata_global_rpm, wikidata_language, private, features)
+                VALUES (1, ?, ?, ?, ?, ?)
+                ON CONFLICT(id) DO UPDATE SET
+                    wikidata_client_rpm = excluded.wikidata_client_rpm,
+                    wikidata_global_rpm = excluded.wikidata_global_rpm,
+                    wikidata_language = excluded.wikidata_language,
+                    private = excluded.private,
+                    features = excluded.features",
+                rusqlite::params![
+                    settings.wikidata_client_rpm,
+                    settings.wikidata_global_rpm,
+                    &settings.wikidata_language,
+                    settings.private,
+                    serde_json::to_string(&settings.features).unwrap_or_default(),
+                ],
+            )?;
+            log!("[DB] Saved the instance settings");
             Ok(())
         }
 
-        // Delete an item from the database for a specific URL
-        pub async fn delete_item_by_url(&self, url: &str, item_id: &str) -> Result<(), Error> {
-            let mut conn = self.conn.lock().await;
-            let tx = conn.transaction()?;
+        // Hash of the admin token stored by the setup wizard, None before the setup
+        pub async fn get_admin_token_hash(&self) -> Result<Option<String>, Error> {
+            let conn = self.conn.lock().await;
+            match conn.query_row("SELECT token_hash FROM admin_token WHERE id = 1", [], |row| row.get(0)) {
+                Ok(hash) => Ok(Some(hash)),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
 
-            // Get URL ID
-            let url_id: i64 =
-                tx.query_row("SELECT id FROM urls WHERE url = ?", [url], |row| row.get(0))?;
+        // Key signing shared links, None until the first link was signed
+        pub async fn get_link_signing_key(&self) -> Result<Option<String>, Error> {
+            let conn = self.reader().await;
+            match conn.query_row("SELECT key FROM link_signing_key WHERE id = 1", [], |row| row.get(0)) {
+                Ok(key) => Ok(Some(key)),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+
+        // Key signing shared links, storing `new_key` when there is none yet
+        pub async fn link_signing_key(&self, new_key: &str, now: i64) -> Result<String, Error> {
+            let conn = self.conn.lock().await;
+            conn.execute(
+                "INSERT OR IGNORE INTO link_signing_key (id, key, created_at) VALUES (1, ?, ?)",
+                rusqlite::params![new_key, now],
+            )?;
+            conn.query_row("SELECT key FROM link_signing_key WHERE id = 1", [], |row| row.get(0))
+        }
+
+        pub async fn create_api_key(&self, request: &ApiKeyRequest, key_hash: &str, now: i64) -> Result<ApiKey, Error> {
+            let conn = self.conn.lock().await;
+            conn.execute(
+                "INSERT INTO api_keys (name, key_hash, url, created_at) VALUES (?, ?, ?, ?)",
+                rusqlite::params![request.name.trim(), key_hash, request.url, now],
+            )?;
+            Ok(ApiKey {
+                id: conn.last_insert_rowid(),
+                name: request.name.trim().to_string(),
+                url: request.url.clone(),
+                created_at: now,
+                last_used_at: None,
+            })
+        }
+
+        // API keys, newest first
+        pub async fn list_api_keys(&self) -> Result<Vec<ApiKey>, Error> {
+            let conn = self.reader().await;
+            let mut stmt = conn.prepare("SELECT id, name, url, created_at, last_used_at FROM api_keys ORDER BY id DESC")?;
+            let keys = stmt.query_map([], api_key_from_row)?.collect::<Result<Vec<_>, _>>()?;
+            Ok(keys)
+        }
+
+        // Revoke an API key, false when there is none with this ID
+        pub async fn delete_api_key(&self, id: i64) -> Result<bool, Error> {
+            let conn = self.conn.lock().await;
+            Ok(conn.execute("DELETE FROM api_keys WHERE id = ?", [id])? > 0)
+        }
+
+        // The API key with this hash, recording that it was used. The time is written at
+        // most once a minute, not on every request of a busy script
+        pub async fn use_api_key(&self, key_hash: &str, now: i64) -> Result<Option<ApiKey>, Error> {
+            let conn = self.conn.lock().await;
+            let key = conn
+                .query_row(
+                    "SELECT id, name, url, created_at, last_used_at FROM api_keys WHERE key_hash = ?",
+                    [key_hash],
+                    api_key_from_row,
+                )
+                .optional()?;
+            if let Some(key) = &key {
+                conn.execute(
+                    "UPDATE api_keys SET last_used_at = ?1 WHERE id = ?2 AND COALESCE(last_used_at, 0) <= ?1 - 60",
+                    rusqlite::params![now, key.id],
+                )?;
+            }
+            Ok(key)
+        }
+
+        // Whether the instance is brand new: no admin token, no saved settings and no comparisons
+        pub async fn needs_setup(&self) -> Result<bool, Error> {
+            let conn = self.conn.lock().await;
+            needs_setup(&conn)
+        }
 
-            // Delete item and properties
+        // Store the admin token and the first settings of a new instance, in one transaction
+        // so two visitors can't both finish the setup. False when the instance was already set up
+        pub async fn complete_setup(&self, token_hash: &str, settings: &InstanceSettings, now: i64) -> Result<bool, Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+            if !needs_setup(&tx)? {
+                return Ok(false);
+            }
             tx.execute(
-                "DELETE FROM items WHERE id = ? AND url_id = ?",
-                [item_id, &url_id.to_string()],
+                "INSERT INTO admin_token (id, token_hash, created_at) VALUES (1, ?, ?)",
+                rusqlite::params![token_hash, now],
             )?;
-        
             tx.execute(
-                "DELETE FROM item_properties WHERE global_item_id = ?",
-                [item_id],
+                "INSERT INTO instance_settings (id, wikidata_client_rpm, wikidata_global_rpm, wikidata_language, private, features)
+                VALUES (1, ?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    settings.wikidata_client_rpm,
+                    settings.wikidata_global_rpm,
+                    &settings.wikidata_language,
+                    settings.private,
+                    serde_json::to_string(&settings.features).unwrap_or_default(),
+                ],
             )?;
-
             tx.commit()?;
+            log!("[DB] Completed the setup of the instance");
+            Ok(true)
+        }
+
+        pub async fn record_audit(&self, action: &str, detail: &str, now: i64) -> Result<(), Error> {
+            let conn = self.conn.lock().await;
+            conn.execute(
+                "INSERT INTO audit_log (created_at, action, detail) VALUES (?, ?, ?)",
+                rusqlite::params![now, action, detail],
+            )?;
             Ok(())
         }
 
-        // Delete a property from the database for a specific URL
-        pub async fn delete_property_by_url(&self, url: &str, property: &str) -> Result<(), Error> {
+        // Latest admin actions, oldest first
+        pub async fn get_audit_tail(&self, limit: usize) -> Result<Vec<AuditEntry>, Error> {
+            let conn = self.reader().await;
+            let mut stmt = conn.prepare(
+                "SELECT created_at, action, detail FROM (
+                    SELECT id, created_at, action, detail FROM audit_log ORDER BY id DESC LIMIT ?
+                ) ORDER BY id",
+            )?;
+            let entries = stmt
+                .query_map([limit as i64], |row| {
+                    Ok(AuditEntry { created_at: row.get(0)?, action: row.get(1)?, detail: row.get(2)? })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(entries)
+        }
+
+        // Protect a comparison with the owner's edit key, registering the URL if needed.
+        // Returns false when the comparison is already protected
+        pub async fn protect_comparison(&self, url: &str, key_hash: &str) -> Result<bool, Error> {
             let mut conn = self.conn.lock().await;
             let tx = conn.transaction()?;
-        
-            // Get URL ID
-            let url_id: i64 =
-                tx.query_row("SELECT id FROM urls WHERE url = ?", [url], |row| row.get(0))?;
-        
-            // Get property ID
-            let property_id: i64 = tx.query_row(
-                "SELECT id FROM properties WHERE name = ?",
-                [property],
-                |row| row.get(0),
+            tx.execute("INSERT OR IGNORE INTO urls (url) VALUES (?)", [url])?;
+            let protected = tx.execute(
+                "UPDATE urls SET edit_key_hash = ?
+                WHERE url = ? AND edit_key_hash IS NULL
+                AND NOT EXISTS (SELECT 1 FROM comparison_editors e WHERE e.url_id = urls.id)",
+                [key_hash, url],
             )?;
-        
-            // Get all global_item_ids for this URL
-            {
-                let mut stmt = tx.prepare("SELECT global_item_id FROM items WHERE url_id = ?")?;
-                let global_item_ids: Vec<String> = stmt
-                    .query_map([url_id], |row| row.get(0))?
-                    .collect::<Result<_, _>>()?;
-        
-                // Insert into deleted_properties for each global_item_id
-                for global_item_id in global_item_ids {
-                    tx.execute(
-                        "INSERT OR IGNORE INTO deleted_properties (url_id, global_item_id, property_id)
-                        VALUES (?, ?, ?)",
-                        rusqlite::params![url_id, global_item_id, property_id],
-                    )?;
+            tx.commit()?;
+            if protected > 0 {
+                log!("[DB] Protected {}", url);
+            }
+            Ok(protected > 0)
+        }
+
+        // Change who finds and reads a comparison. Returns false when the URL is unknown
+        pub async fn set_visibility(&self, url: &str, visibility: Visibility) -> Result<bool, Error> {
+            let conn = self.conn.lock().await;
+            let updated = conn.execute("UPDATE urls SET visibility = ? WHERE url = ?", [visibility.as_str(), url])?;
+            Ok(updated > 0)
+        }
+
+        // IDs among `item_ids` of items stored under other URLs than `url`
+        pub async fn items_of_other_urls(&self, url: &str, item_ids: &[&str]) -> Result<Vec<String>, Error> {
+            let conn = self.reader().await;
+            let mut stmt = conn.prepare(
+                "SELECT EXISTS (SELECT 1 FROM items i JOIN urls u ON i.url_id = u.id WHERE i.id = ? AND u.url != ?)",
+            )?;
+            let mut taken = Vec::new();
+            for item_id in item_ids {
+                if stmt.query_row(rusqlite::params![item_id, url], |row| row.get(0))? {
+                    taken.push(item_id.to_string());
                 }
             }
-        
-            tx.commit()?;
-            Ok(())
+            Ok(taken)
         }
 
-        pub async fn add_selected_property(&self, url: &str, property: &str) -> Result<(), Error> {
-            let mut conn = self.conn.lock().await;
-            let tx = conn.transaction()?;
+        // Register a URL that has never been written to, protected by the creator's edit key.
+        // Returns false when the URL already exists
+        pub async fn claim_url(&self, url: &str, key_hash: &str) -> Result<bool, Error> {
+            let conn = self.conn.lock().await;
+            let claimed = conn.execute(
+                "INSERT OR IGNORE INTO urls (url, edit_key_hash) VALUES (?, ?)",
+                [url, key_hash],
+            )?;
+            if claimed > 0 {
+                log!("[DB] Claimed {}", url);
+            }
+            Ok(claimed > 0)
+        }
 
-            // Insert URL if it does not exists
-            tx.execute("INSERT OR IGNORE INTO urls (url) VALUES (?)", [url])?;
+        // Grant another token edit access to a protected comparison
+        pub async fn add_editor(&self, url: &str, token_hash: &str, role: EditorRole, now: i64) -> Result<(), Error> {
+            let conn = self.conn.lock().await;
+            let url_id = url_id(&conn, url)?;
+            conn.execute(
+                "INSERT INTO comparison_editors (url_id, token_hash, role, created_at) VALUES (?, ?, ?, ?)",
+                rusqlite::params![url_id, token_hash, role.as_str(), now],
+            )?;
+            log!("[DB] Added {} of {}", role.as_str(), url);
+            Ok(())
+        }
 
-            // Get URL ID
-            let url_id = tx.query_row("SELECT id FROM urls WHERE url = ?", [url], |row| {
-                row.get::<_, i64>(0)
-            })?;
+        // Version of a URL: when its items, properties or metadata last changed, in milliseconds
+        // since the Unix epoch. None for unknown URLs and URLs unchanged since versions exist
+        pub async fn get_url_version(&self, url: &str) -> Result<Option<i64>, Error> {
+            let conn = self.reader().await;
+            match conn.query_row("SELECT updated_at FROM urls WHERE url = ?", [url], |row| row.get(0)) {
+                Err(Error::QueryReturnedNoRows) => Ok(None),
+                version => version,
+            }
+        }
 
-            // Get/Create property
-            let prop_id = match tx.query_row(
-                "SELECT id FROM properties WHERE name = ?",
-                [property],
-                |row| row.get::<_, i64>(0),
-            ) {
-                Ok(id) => id,
-                Err(_) => {
-                    tx.execute("INSERT INTO properties (name) VALUES (?)", [property])?;
-                    tx.last_insert_rowid()
-                }
-            };
+        // Whether the comparison is protected, and the role of the given token
+        pub async fn get_edit_access(&self, url: &str, token_hash: Option<&str>) -> Result<EditAccess, Error> {
+            let conn = self.reader().await;
+            let access = conn.query_row(
+                "SELECT
+                    u.edit_key_hash IS NOT NULL
+                        OR EXISTS(SELECT 1 FROM comparison_editors e WHERE e.url_id = u.id),
+                    CASE WHEN u.edit_key_hash = ?1 THEN 'owner' ELSE (
+                        SELECT MAX(e.role) FROM comparison_editors e WHERE e.url_id = u.id AND e.token_hash = ?1
+                    ) END,
+                    u.visibility
+                FROM urls u
+                WHERE u.url = ?2",
+                rusqlite::params![token_hash, url],
+                |row| Ok((row.get::<_, bool>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, String>(2)?)),
+            );
+            match access {
+                Ok((protected, role, visibility)) => Ok(EditAccess {
+                    protected,
+                    role: role.as_deref().and_then(EditorRole::parse),
+                    visibility: Visibility::parse(&visibility).unwrap_or_default(),
+                }),
+                // URLs that have never been written to are open
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(EditAccess::default()),
+                Err(e) => Err(e),
+            }
+        }
 
-            // Insert into selected_properties
+        // Store a snapshot of a comparison
+        pub async fn insert_snapshot(&self, snapshot: &Snapshot) -> Result<(), Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
             tx.execute(
-                "INSERT OR IGNORE INTO selected_properties (url_id, property_id) VALUES (?, ?)",
-                [url_id, prop_id],
+                "INSERT INTO snapshots (id, url, selected_properties, property_labels, created_at)
+                VALUES (?, ?, ?, ?, ?)",
+                rusqlite::params![
+                    snapshot.id,
+                    snapshot.url,
+                    to_json(&snapshot.selected_properties)?,
+                    to_json(&snapshot.property_labels)?,
+                    snapshot.created_at
+                ],
             )?;
-
+            for (position, item) in snapshot.items.iter().enumerate() {
+                tx.execute(
+                    "INSERT INTO snapshot_items (snapshot_id, position, item) VALUES (?, ?, ?)",
+                    rusqlite::params![snapshot.id, position as i64, to_json(item)?],
+                )?;
+            }
             tx.commit()?;
+            log!("[DB] Stored snapshot {} of {} with {} items", snapshot.id, snapshot.url, snapshot.items.len());
             Ok(())
         }
 
-        pub async fn get_selected_properties(&self, url: &str) -> Result<Vec<String>, Error> {
+        pub async fn get_snapshot(&self, id: &str) -> Result<Option<Snapshot>, Error> {
+            let conn = self.reader().await;
+            let snapshot = conn.query_row(
+                "SELECT url, selected_properties, property_labels, created_at FROM snapshots WHERE id = ?",
+                [id],
+                |row| {
+                    Ok(Snapshot {
+                        id: id.to_string(),
+                        url: row.get(0)?,
+                        created_at: row.get(3)?,
+                        items: Vec::new(),
+                        selected_properties: from_json(&row.get::<_, String>(1)?)?,
+                        property_labels: from_json(&row.get::<_, String>(2)?)?,
+                    })
+                },
+            );
+            let mut snapshot = match snapshot {
+                Ok(snapshot) => snapshot,
+                Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+                Err(e) => return Err(e),
+            };
+            let mut stmt = conn.prepare("SELECT item FROM snapshot_items WHERE snapshot_id = ? ORDER BY position")?;
+            snapshot.items = stmt
+                .query_map([id], |row| from_json(&row.get::<_, String>(0)?))?
+                .collect::<Result<_, _>>()?;
+            Ok(Some(snapshot))
+        }
+
+        // Store a reviewer's rating of an item of the URL, replacing their earlier rating.
+        // Fails with QueryReturnedNoRows when the item isn't part of the URL
+        pub async fn set_review(
+            &self,
+            url: &str,
+            item_id: &str,
+            reviewer_id: &str,
+            rating: u8,
+            now: i64,
+        ) -> Result<(), Error> {
             let conn = self.conn.lock().await;
+            conn.query_row(
+                "SELECT i.id FROM items i JOIN urls u ON i.url_id = u.id WHERE i.id = ? AND u.url = ? AND i.deleted_at IS NULL",
+                [item_id, url],
+                |row| row.get::<_, String>(0),
+            )?;
+            conn.execute(
+                "INSERT INTO reviews (item_id, reviewer_id, rating, created_at)
+                VALUES (?, ?, ?, ?)
+                ON CONFLICT(item_id, reviewer_id) DO UPDATE SET
+                    rating = excluded.rating,
+                    created_at = excluded.created_at",
+                rusqlite::params![item_id, reviewer_id, rating, now],
+            )?;
+            log!("[DB] Stored {} star rating of item {}", rating, item_id);
+            Ok(())
+        }
+
+        // Average rating and review count of each reviewed item of the URL,
+        // with the ratings of `reviewer_id` when given
+        pub async fn get_rating_summaries(
+            &self,
+            url: &str,
+            reviewer_id: Option<&str>,
+        ) -> Result<HashMap<String, RatingSummary>, Error> {
+            let conn = self.reader().await;
             let mut stmt = conn.prepare(
-                "SELECT p.name 
-                 FROM selected_properties sp
-                 JOIN properties p ON sp.property_id = p.id
-                 JOIN urls u ON sp.url_id = u.id
-                 WHERE u.url = ?",
+                "SELECT r.item_id, AVG(r.rating), COUNT(*), MAX(CASE WHEN r.reviewer_id = ? THEN r.rating END)
+                FROM reviews r
+                JOIN items i ON r.item_id = i.id
+                JOIN urls u ON i.url_id = u.id
+                WHERE u.url = ? AND i.deleted_at IS NULL
+                GROUP BY r.item_id",
             )?;
+            let summaries = stmt
+                .query_map(rusqlite::params![reviewer_id, url], |row| {
+                    Ok((
+                        row.get(0)?,
+                        RatingSummary {
+                            average: row.get(1)?,
+                            count: row.get(2)?,
+                            mine: row.get(3)?,
+                        },
+                    ))
+                })?
+                .collect::<Result<HashMap<_, _>, _>>()?;
+            Ok(summaries)
+        }
 
-            let properties = stmt.query_map([url], |row| row.get(0))?;
-            properties.collect()
+        // Newest reviews of the URL's items in use, with the reviewers' IDs
+        pub async fn get_recent_reviews(&self, url: &str, limit: usize) -> Result<Vec<ReviewEntry>, Error> {
+            let conn = self.reader().await;
+            let mut stmt = conn.prepare(
+                "SELECT r.item_id, COALESCE(name_ip.value, ''), r.reviewer_id, r.rating, r.created_at
+                FROM reviews r
+                JOIN items i ON r.item_id = i.id
+                JOIN urls u ON i.url_id = u.id
+                LEFT JOIN item_properties name_ip
+                    ON name_ip.global_item_id = i.global_item_id
+                    AND name_ip.property_id = (SELECT id FROM properties WHERE name = 'name')
+                WHERE u.url = ? AND i.deleted_at IS NULL
+                ORDER BY r.created_at DESC, r.item_id, r.reviewer_id
+                LIMIT ?",
+            )?;
+            let reviews = stmt
+                .query_map(rusqlite::params![url, limit], |row| {
+                    Ok(ReviewEntry {
+                        item_id: row.get(0)?,
+                        item_name: row.get(1)?,
+                        reviewer: row.get(2)?,
+                        rating: row.get(3)?,
+                        created_at: row.get(4)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(reviews)
+        }
+
+        // Every review of the URL's items in use, oldest first, with the reviewers' IDs
+        pub async fn get_reviews(&self, url: &str) -> Result<Vec<ExportedReview>, Error> {
+            let conn = self.reader().await;
+            let mut stmt = conn.prepare(
+                "SELECT r.item_id, r.reviewer_id, r.rating, r.created_at
+                FROM reviews r
+                JOIN items i ON r.item_id = i.id
+                JOIN urls u ON i.url_id = u.id
+                WHERE u.url = ? AND i.deleted_at IS NULL
+                ORDER BY r.created_at, r.item_id, r.reviewer_id",
+            )?;
+            let reviews = stmt
+                .query_map([url], |row| {
+                    Ok(ExportedReview {
+                        item_id: row.get(0)?,
+                        reviewer: row.get(1)?,
+                        rating: row.get(2)?,
+                        created_at: row.get(3)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(reviews)
+        }
+
+        // Retrieve the cached labels of the given entities that haven't expired
+        pub async fn get_cached_labels(&self, entity_ids: &[String], now: i64) -> Result<HashMap<String, String>, Error> {
+            let conn = self.reader().await;
+            let rows = query_cache(&conn, "wikidata_labels", "label", entity_ids, now)?;
+            Ok(rows.into_iter().collect())
+        }
+
+        // Cache labels until `expires_at`, replacing older entries
+        pub async fn cache_labels(&self, labels: &HashMap<String, String>, expires_at: i64, now: i64) -> Result<(), Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM wikidata_labels WHERE expires_at <= ?", [now])?;
+            for (entity_id, label) in labels {
+                tx.execute(
+                    "INSERT OR REPLACE INTO wikidata_labels (entity_id, label, expires_at) VALUES (?, ?, ?)",
+                    rusqlite::params![entity_id, label, expires_at],
+                )?;
+            }
+            tx.commit()?;
+            log!("[DB] Cached {} Wikidata labels", labels.len());
+            Ok(())
+        }
+
+        // Retrieve the cached claims of the given entities. Entries in an older format are treated as missing
+        pub async fn get_cached_claims(
+            &self,
+            entity_ids: &[String],
+            now: i64,
+        ) -> Result<HashMap<String, EntityClaims>, Error> {
+            let conn = self.reader().await;
+            let rows = query_cache(&conn, "wikidata_claims", "claims", entity_ids, now)?;
+            Ok(rows
+                .into_iter()
+                .filter_map(|(entity_id, claims)| {
+                    serde_json::from_str(&claims).ok().map(|claims| (entity_id, claims))
+                })
+                .collect())
+        }
+
+        // Cache entity claims until `expires_at`, replacing older entries
+        pub async fn cache_claims(
+            &self,
+            claims: &HashMap<String, EntityClaims>,
+            expires_at: i64,
+            now: i64,
+        ) -> Result<(), Error> {
+            let mut conn = self.conn.lock().await;
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM wikidata_claims WHERE expires_at <= ?", [now])?;
+            for (entity_id, entity_claims) in claims {
+                tx.execute(
+                    "INSERT OR REPLACE INTO wikidata_claims (entity_id, claims, expires_at) VALUES (?, ?, ?)",
+                    rusqlite::params![entity_id, serde_json::to_string(entity_claims).unwrap_or_default(), expires_at],
+                )?;
+            }
+            tx.commit()?;
+            log!("[DB] Cached claims of {} Wikidata entities", claims.len());
+            Ok(())
         }
 
         // function to log database state
@@ -770,6 +5508,113 @@ mod db_impl {
         }
     }
 
+    // Outcome of Database::save_items_by_url
+    #[derive(Debug, PartialEq)]
+    pub enum ItemsSave {
+        // The items as saved, with their new versions
+        Saved(Vec<Item>),
+        // Stored copies of the items changed since the client's version, nothing was saved
+        Stale(Vec<Item>),
+        // IDs of items belonging to other comparisons, nothing was saved
+        Taken(Vec<String>),
+    }
+
+    // Outcome of Database::rename_property
+    #[derive(Debug, PartialEq)]
+    pub enum PropertyRenaming {
+        // Column of the property under its new name
+        Renamed(PropertyColumn),
+        // The URL has no such property selected
+        Unknown,
+        // The URL already has a property with the new name
+        Taken,
+    }
+
+    // Comparison of a stored URL, from Database::comparison. Its ID is looked up once and
+    // every query of the handle is scoped to it, so none can reach the rows of another URL
+    pub struct ComparisonHandle<'a> {
+        db: &'a Database,
+        url: String,
+        url_id: i64,
+    }
+
+    impl ComparisonHandle<'_> {
+        pub fn url(&self) -> &str {
+            &self.url
+        }
+
+        // Items in display order, with long values in full
+        pub async fn items(&self) -> Result<Vec<Item>, Error> {
+            let conn = self.db.reader().await;
+            let (items, _) = query_items(&conn, self.url_id, true)?;
+            Ok(items)
+        }
+
+        // Items for the grid, with long values as a preview and the properties cut per item ID
+        pub async fn item_previews(&self) -> Result<(Vec<Item>, TruncatedValues), Error> {
+            let conn = self.db.reader().await;
+            query_items(&conn, self.url_id, false)
+        }
+
+        // Insert an item or update it, in place when it already belongs to the comparison
+        pub async fn insert_item(&self, item: &Item) -> Result<(), Error> {
+            self.insert_items(std::slice::from_ref(item)).await
+        }
+
+        // Insert or update several items in a single transaction
+        pub async fn insert_items(&self, items: &[Item]) -> Result<(), Error> {
+            let mut conn = self.db.conn.lock().await;
+            let mut tx = conn.transaction()?;
+            for item in items {
+                self.db.upsert_item(&mut tx, self.url_id, item).await?;
+            }
+            tx.commit()?;
+            log!("[DB] Stored {} items for URL: {}", items.len(), self.url);
+            Ok(())
+        }
+
+        // Move an item to the trash, returning the number of items removed (0 or 1)
+        pub async fn delete_item(&self, item_id: &str) -> Result<usize, Error> {
+            let mut conn = self.db.conn.lock().await;
+            let tx = conn.transaction()?;
+            // Leave other URLs' items and items already in the trash alone
+            let deleted = tx.execute(
+                "UPDATE items SET deleted_at = strftime('%s', 'now')
+                WHERE id = ? AND url_id = ? AND deleted_at IS NULL",
+                rusqlite::params![item_id, self.url_id],
+            )?;
+            if deleted == 0 {
+                return Ok(0);
+            }
+            touch_url(&tx, self.url_id)?;
+            tx.commit()?;
+            Ok(deleted)
+        }
+
+        // Selected properties in display order
+        pub async fn properties(&self) -> Result<Vec<String>, Error> {
+            let conn = self.db.reader().await;
+            let mut stmt = conn.prepare(
+                "SELECT p.name
+                FROM selected_properties sp
+                JOIN properties p ON sp.property_id = p.id
+                WHERE sp.url_id = ?
+                ORDER BY sp.property_order ASC, sp.rowid ASC",
+            )?;
+            let properties = stmt.query_map([self.url_id], |row| row.get(0))?;
+            properties.collect()
+        }
+
+        pub async fn metadata(&self) -> Result<ComparisonMetadata, Error> {
+            let conn = self.db.reader().await;
+            conn.query_row(
+                "SELECT title, description, decision_item_id, decided_at, decision_rationale, unit_system FROM urls WHERE id = ?",
+                [self.url_id],
+                metadata_from_row,
+            )
+        }
+    }
+
     // Define a struct to represent an item in the database
     #[derive(Debug, Deserialize, Serialize, Clone)]
     pub struct DbItem {
@@ -781,4 +5626,4 @@ mod db_impl {
 }
 
 #[cfg(feature = "ssr")]
-pub use db_impl::{Database, DbItem};
+pub use db_impl::{database_copy_problem, ComparisonHandle, Database, DbItem, ItemsSave, PropertyRenaming, SCHEMA_VERSION};
@@ -0,0 +1,279 @@
+// Diagnostics bundle admins download from /api/admin/diagnostics and attach to bug
+// reports. Nothing leaves the server on its own: the bundle is a zip of the recent
+// server log, the audit tail, the schema version, the configuration with secrets
+// redacted and the errors browsers reported
+use crate::config::Config;
+use crate::db::SCHEMA_VERSION;
+use crate::models::diagnostics::{AuditEntry, ClientErrorReport};
+use chrono::{DateTime, Datelike, SecondsFormat, Timelike, Utc};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+// Lines of the server log kept for the bundle
+const SERVER_LOG_CAPACITY: usize = 1000;
+// Errors reported by browsers kept for the bundle
+const CLIENT_ERRORS_CAPACITY: usize = 200;
+// Of those, errors kept from one IP. Its newer reports replace its older ones, so a
+// single client can't push the reports of everyone else out
+const CLIENT_ERRORS_PER_CLIENT: usize = 10;
+// Admin actions of the audit trail that go into the bundle
+pub const AUDIT_TAIL_LENGTH: usize = 200;
+// Parts of environment variable names whose values are never written to the bundle
+const SECRET_NAME_PARTS: [&str; 4] = ["KEY", "SECRET", "TOKEN", "PASSWORD"];
+
+static SERVER_LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+// Print a line to stdout and keep it for the diagnostics bundle
+#[macro_export]
+macro_rules! server_log {
+    ($($arg:tt)*) => {
+        $crate::diagnostics::record("INFO", format!($($arg)*))
+    };
+}
+
+// Print a line to stderr and keep it for the diagnostics bundle
+#[macro_export]
+macro_rules! server_error {
+    ($($arg:tt)*) => {
+        $crate::diagnostics::record("ERROR", format!($($arg)*))
+    };
+}
+
+// Used by server_log! and server_error!
+pub fn record(level: &str, message: String) {
+    if level == "ERROR" {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+    let mut log = SERVER_LOG.lock().unwrap();
+    if log.len() == SERVER_LOG_CAPACITY {
+        log.pop_front();
+    }
+    log.push_back(format!("{} {} {}", Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true), level, message));
+}
+
+pub struct Diagnostics {
+    // Configuration the server was started with
+    config: Config,
+    started_at: i64,
+    // Time, IP of the reporting client and report
+    client_errors: Mutex<VecDeque<(i64, String, ClientErrorReport)>>,
+}
+
+impl Diagnostics {
+    pub fn new(config: Config) -> Self {
+        Diagnostics { config, started_at: Utc::now().timestamp(), client_errors: Default::default() }
+    }
+
+    // Keep an error the browser at `client` reported, dropping the client's oldest one when
+    // it has reported its share, or else the oldest one when full
+    pub fn report_client_error(&self, client: &str, report: ClientErrorReport, now: i64) {
+        let mut errors = self.client_errors.lock().unwrap();
+        let reported = errors.iter().filter(|(_, reporter, _)| reporter == client).count();
+        if reported >= CLIENT_ERRORS_PER_CLIENT {
+            if let Some(oldest) = errors.iter().position(|(_, reporter, _)| reporter == client) {
+                errors.remove(oldest);
+            }
+        } else if errors.len() == CLIENT_ERRORS_CAPACITY {
+            errors.pop_front();
+        }
+        errors.push_back((now, client.to_string(), report));
+    }
+
+    // Zip archive of everything worth attaching to a bug report
+    pub fn bundle(&self, schema_version: i64, audit: &[AuditEntry], now: i64) -> Vec<u8> {
+        let summary = format!(
+            "CompareWare {}\nGenerated at: {}\nRunning since: {}\nSchema version: {} (this build creates {})\n",
+            env!("CARGO_PKG_VERSION"),
+            timestamp(now),
+            timestamp(self.started_at),
+            schema_version,
+            SCHEMA_VERSION,
+        );
+        let config = toml::to_string_pretty(&self.config).unwrap_or_else(|e| format!("# Failed to write the configuration: {}\n", e));
+        let server_log = lines(SERVER_LOG.lock().unwrap().iter().cloned());
+        let audit_log = lines(audit.iter().map(|entry| format!("{} {} {}", timestamp(entry.created_at), entry.action, entry.detail)));
+        let client_errors = lines(
+            self.client_errors
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(at, client, report)| format!("{} {} {} {}", timestamp(*at), client, report.page, report.message)),
+        );
+        zip(
+            &[
+                ("summary.txt", summary),
+                ("config.toml", config),
+                ("environment.txt", environment_report(std::env::vars())),
+                ("server.log", server_log),
+                ("audit.log", audit_log),
+                ("client-errors.log", client_errors),
+            ],
+            now,
+        )
+    }
+}
+
+fn timestamp(seconds: i64) -> String {
+    DateTime::from_timestamp(seconds, 0).unwrap_or_default().to_rfc3339()
+}
+
+fn lines(lines: impl Iterator<Item = String>) -> String {
+    lines.map(|line| line + "\n").collect()
+}
+
+// CompareWare and Leptos variables of the server's environment, with the values
+// of keys, tokens and passwords left out
+fn environment_report(variables: impl Iterator<Item = (String, String)>) -> String {
+    let mut variables: Vec<(String, String)> = variables
+        .filter(|(name, _)| name.starts_with("COMPAREWARE_") || name.starts_with("LEPTOS_"))
+        .map(|(name, value)| {
+            let secret = SECRET_NAME_PARTS.iter().any(|part| name.contains(part));
+            (name, if secret { "[redacted]".to_string() } else { value })
+        })
+        .collect();
+    variables.sort();
+    lines(variables.into_iter().map(|(name, value)| format!("{}={}", name, value)))
+}
+
+// Zip archive of text files, stored without compression, also the container of XLSX exports
+pub fn zip(files: &[(&str, String)], now: i64) -> Vec<u8> {
+    let modified = DateTime::from_timestamp(now, 0).unwrap_or_default();
+    let dos_time = ((modified.hour() << 11) | (modified.minute() << 5) | (modified.second() / 2)) as u16;
+    let dos_date = (((modified.year().max(1980) - 1980) << 9) as u32 | (modified.month() << 5) | modified.day()) as u16;
+
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for (name, contents) in files {
+        let offset = archive.len() as u32;
+        let crc = crc32fast::hash(contents.as_bytes());
+        // Fields shared by the local header and the central directory entry: version
+        // needed, UTF-8 names, no compression, time, date, CRC and both sizes
+        let mut fields = Vec::new();
+        for value in [20u16, 0x0800, 0, dos_time, dos_date] {
+            fields.extend_from_slice(&value.to_le_bytes());
+        }
+        for value in [crc, contents.len() as u32, contents.len() as u32] {
+            fields.extend_from_slice(&value.to_le_bytes());
+        }
+        fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+
+        archive.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        archive.extend_from_slice(&fields);
+        archive.extend_from_slice(&0u16.to_le_bytes());
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(contents.as_bytes());
+
+        directory.extend_from_slice(&0x02014b50u32.to_le_bytes());
+        directory.extend_from_slice(&20u16.to_le_bytes());
+        directory.extend_from_slice(&fields);
+        // No extra field, comment, disk number or attributes
+        directory.extend_from_slice(&[0; 12]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let directory_offset = archive.len() as u32;
+    archive.extend_from_slice(&directory);
+    archive.extend_from_slice(&0x06054b50u32.to_le_bytes());
+    archive.extend_from_slice(&[0; 4]);
+    archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&directory_offset.to_le_bytes());
+    archive.extend_from_slice(&0u16.to_le_bytes());
+    archive
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leptos::logging::log;
+
+    // Names and contents of the files of a stored zip archive, read through its central directory
+    fn unzip(archive: &[u8]) -> Vec<(String, String)> {
+        let u16_at = |at: usize| u16::from_le_bytes([archive[at], archive[at + 1]]) as usize;
+        let u32_at = |at: usize| u32::from_le_bytes(archive[at..at + 4].try_into().unwrap()) as usize;
+        let end = archive.len() - 22;
+        assert_eq!(u32_at(end), 0x06054b50);
+        let mut entry = u32_at(end + 16);
+        let mut files = Vec::new();
+        for _ in 0..u16_at(end + 10) {
+            assert_eq!(u32_at(entry), 0x02014b50);
+            let name_length = u16_at(entry + 28);
+            let name = String::from_utf8(archive[entry + 46..entry + 46 + name_length].to_vec()).unwrap();
+            let local = u32_at(entry + 42);
+            assert_eq!(u32_at(local), 0x04034b50);
+            let start = local + 30 + u16_at(local + 26);
+            let contents = &archive[start..start + u32_at(local + 18)];
+            assert_eq!(crc32fast::hash(contents), u32_at(entry + 16) as u32);
+            files.push((name, String::from_utf8(contents.to_vec()).unwrap()));
+            entry += 46 + name_length;
+        }
+        files
+    }
+
+    #[test]
+    fn test_zip() {
+        log!("[TEST] Starting test_zip");
+        let files = [("a.txt", "first\n".to_string()), ("logs/b.log", String::new())];
+        let archive = zip(&files, 1_700_000_000);
+        let unzipped = unzip(&archive);
+        assert_eq!(unzipped, files.map(|(name, contents)| (name.to_string(), contents)));
+        log!("[TEST] test_zip completed successfully");
+    }
+
+    #[test]
+    fn test_environment_report() {
+        log!("[TEST] Starting test_environment_report");
+        let variables = [
+            ("COMPAREWARE_ADMIN_TOKEN", "hunter2"),
+            ("COMPAREWARE_S3_ACCESS_KEY", "AKIA"),
+            ("COMPAREWARE_S3_BUCKET", "backups"),
+            ("LEPTOS_SITE_ADDR", "0.0.0.0:3000"),
+            ("HOME", "/root"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+        assert_eq!(
+            environment_report(variables.into_iter()),
+            "COMPAREWARE_ADMIN_TOKEN=[redacted]\nCOMPAREWARE_S3_ACCESS_KEY=[redacted]\nCOMPAREWARE_S3_BUCKET=backups\nLEPTOS_SITE_ADDR=0.0.0.0:3000\n"
+        );
+        log!("[TEST] test_environment_report completed successfully");
+    }
+
+    #[test]
+    fn test_bundle() {
+        log!("[TEST] Starting test_bundle");
+        let diagnostics = Diagnostics::new(Config::default());
+        crate::server_error!("Failed to reach the relays");
+        diagnostics.report_client_error("10.0.0.1", ClientErrorReport { message: "Failed to save item".into(), page: "/laptops".into() }, 100);
+        let audit = [AuditEntry { created_at: 50, action: "settings".into(), detail: "Instance settings changed".into() }];
+
+        let files = unzip(&diagnostics.bundle(SCHEMA_VERSION, &audit, 200));
+        let file = |name: &str| files.iter().find(|(file, _)| file == name).map(|(_, contents)| contents.as_str()).unwrap();
+        assert!(file("summary.txt").contains(&format!("Schema version: {}", SCHEMA_VERSION)));
+        assert!(file("config.toml").contains("database_path = \"compareware.db\""));
+        assert!(file("server.log").contains("ERROR Failed to reach the relays"));
+        assert_eq!(file("audit.log"), "1970-01-01T00:00:50+00:00 settings Instance settings changed\n");
+        assert_eq!(file("client-errors.log"), "1970-01-01T00:01:40+00:00 10.0.0.1 /laptops Failed to save item\n");
+        log!("[TEST] test_bundle completed successfully");
+    }
+
+    #[test]
+    fn test_client_error_share() {
+        log!("[TEST] Starting test_client_error_share");
+        let diagnostics = Diagnostics::new(Config::default());
+        let report = |message: &str| ClientErrorReport { message: message.into(), page: "/".into() };
+        diagnostics.report_client_error("10.0.0.1", report("Failed to load"), 0);
+        // Test a flood from one client only replaces its own reports
+        for at in 1..=CLIENT_ERRORS_CAPACITY as i64 {
+            diagnostics.report_client_error("10.0.0.2", report("Flood"), at);
+        }
+        let errors = diagnostics.client_errors.lock().unwrap();
+        assert_eq!(errors.len(), 1 + CLIENT_ERRORS_PER_CLIENT);
+        assert_eq!(errors[0].1, "10.0.0.1");
+        assert_eq!(errors.back().unwrap().0, CLIENT_ERRORS_CAPACITY as i64);
+        log!("[TEST] test_client_error_share completed successfully");
+    }
+}
@@ -0,0 +1,266 @@
+// Formats comparisons are exported as. Each one is an `Exporter`, and the ones in EXPORTERS
+// are served at /api/urls/{url}/export.{name}, picked by the Accept header at
+// /api/urls/{url}/export and offered in the export menu. Adding a format is adding an
+// exporter here
+use crate::models::backup::ComparisonExport;
+use crate::models::export::ExportFormat;
+use crate::table_export::{Table, CSV_CONTENT_TYPE, HTML_CONTENT_TYPE, MARKDOWN_CONTENT_TYPE, XLSX_CONTENT_TYPE};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+
+// Comparison being exported
+pub struct ExportSource {
+    // Self-contained document of the comparison
+    pub export: ComparisonExport,
+    // Title, or the comparison's path when it has none, for headings
+    pub title: String,
+    // Labels of the Wikidata properties the cache knows
+    pub labels: HashMap<String, String>,
+    // Whether tables keep the values quantities were entered as next to the converted ones
+    pub original: bool,
+}
+
+impl ExportSource {
+    pub fn table(&self) -> Table {
+        let comparison = &self.export.comparison;
+        Table::new(&comparison.items, &comparison.selected_properties, &self.labels, comparison.metadata.unit_system, self.original)
+    }
+}
+
+pub trait Exporter: Sync {
+    // Name in the export URL, also the extension of the file
+    fn name(&self) -> &'static str;
+    fn label(&self) -> &'static str;
+    fn content_type(&self) -> &'static str;
+
+    // Whether it's a table of the items, which can keep the values as entered
+    fn table(&self) -> bool {
+        true
+    }
+
+    fn export(&self, source: &ExportSource) -> Vec<u8>;
+
+    fn format(&self) -> ExportFormat {
+        ExportFormat {
+            name: self.name().to_string(),
+            label: self.label().to_string(),
+            content_type: self.content_type().to_string(),
+            table: self.table(),
+        }
+    }
+}
+
+struct Csv;
+
+impl Exporter for Csv {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    fn label(&self) -> &'static str {
+        "CSV"
+    }
+
+    fn content_type(&self) -> &'static str {
+        CSV_CONTENT_TYPE
+    }
+
+    fn export(&self, source: &ExportSource) -> Vec<u8> {
+        source.table().to_csv().into_bytes()
+    }
+}
+
+struct Xlsx;
+
+impl Exporter for Xlsx {
+    fn name(&self) -> &'static str {
+        "xlsx"
+    }
+
+    fn label(&self) -> &'static str {
+        "Excel (XLSX)"
+    }
+
+    fn content_type(&self) -> &'static str {
+        XLSX_CONTENT_TYPE
+    }
+
+    fn export(&self, source: &ExportSource) -> Vec<u8> {
+        source.table().to_xlsx(source.export.exported_at)
+    }
+}
+
+struct Markdown;
+
+impl Exporter for Markdown {
+    fn name(&self) -> &'static str {
+        "md"
+    }
+
+    fn label(&self) -> &'static str {
+        "Markdown"
+    }
+
+    fn content_type(&self) -> &'static str {
+        MARKDOWN_CONTENT_TYPE
+    }
+
+    fn export(&self, source: &ExportSource) -> Vec<u8> {
+        source.table().to_markdown(&source.title, &source.export.comparison.metadata.description).into_bytes()
+    }
+}
+
+struct Html;
+
+impl Exporter for Html {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn label(&self) -> &'static str {
+        "HTML page"
+    }
+
+    fn content_type(&self) -> &'static str {
+        HTML_CONTENT_TYPE
+    }
+
+    fn export(&self, source: &ExportSource) -> Vec<u8> {
+        source.table().to_html(&source.title, &source.export.comparison.metadata.description).into_bytes()
+    }
+}
+
+// The document POST /api/urls/{url}/import.json recreates the comparison from
+struct Json;
+
+impl Exporter for Json {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn label(&self) -> &'static str {
+        "JSON, to import on another instance"
+    }
+
+    fn content_type(&self) -> &'static str {
+        JSON_CONTENT_TYPE
+    }
+
+    fn table(&self) -> bool {
+        false
+    }
+
+    fn export(&self, source: &ExportSource) -> Vec<u8> {
+        serde_json::to_vec(&source.export).unwrap_or_default()
+    }
+}
+
+// Exporters in the order of the export menu. The first one answers requests accepting any type
+pub const EXPORTERS: &[&dyn Exporter] = &[&Json, &Csv, &Xlsx, &Markdown, &Html];
+
+pub fn exporter(name: &str) -> Option<&'static dyn Exporter> {
+    EXPORTERS.iter().copied().find(|exporter| exporter.name() == name)
+}
+
+pub fn export_formats() -> Vec<ExportFormat> {
+    EXPORTERS.iter().map(|exporter| exporter.format()).collect()
+}
+
+// Exporter of the type the Accept header prefers, by quality then by order, with
+// wildcards such as text/* matching the first exporter of the type. None when the
+// header accepts none of them
+pub fn negotiate(accept: &str) -> Option<&'static dyn Exporter> {
+    let mut ranges: Vec<(&str, f32)> = accept
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';').map(str::trim);
+            let media_type = parts.next().filter(|media_type| !media_type.is_empty())?;
+            let quality = parts
+                .filter_map(|parameter| parameter.strip_prefix("q="))
+                .find_map(|quality| quality.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            Some((media_type, quality))
+        })
+        .filter(|(_, quality)| *quality > 0.0)
+        .collect();
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranges.into_iter().find_map(|(media_type, _)| {
+        EXPORTERS.iter().copied().find(|exporter| {
+            let essence = exporter.content_type().split(';').next().unwrap_or_default();
+            match media_type.strip_suffix("/*") {
+                Some(prefix) => prefix == "*" || essence.split('/').next() == Some(prefix),
+                None => media_type.eq_ignore_ascii_case(essence),
+            }
+        })
+    })
+}
+
+// HMAC of what a signed export link opens, keyed with the instance's link signing key
+fn link_mac(key: &str, url: &str, format: &str, embed: bool, expires_at: i64) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}\n{}\n{}\n{}", url, format, embed, expires_at).as_bytes());
+    mac
+}
+
+// Hex signature of a link to the export of `url` in `format`, valid until `expires_at`
+pub fn link_signature(key: &str, url: &str, format: &str, embed: bool, expires_at: i64) -> String {
+    format!("{:x}", link_mac(key, url, format, embed, expires_at).finalize().into_bytes())
+}
+
+// Whether `signature` was made by link_signature with the same key and link, compared in
+// constant time. Expiry is up to the caller
+pub fn verify_link_signature(key: &str, url: &str, format: &str, embed: bool, expires_at: i64, signature: &str) -> bool {
+    let bytes: Option<Vec<u8>> = (0..signature.len())
+        .step_by(2)
+        .map(|start| signature.get(start..start + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+        .collect();
+    bytes.is_some_and(|bytes| link_mac(key, url, format, embed, expires_at).verify_slice(&bytes).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use leptos::logging::log;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_export_registry() {
+        log!("[TEST] Starting test_export_registry");
+        let names: HashSet<&str> = EXPORTERS.iter().map(|exporter| exporter.name()).collect();
+        assert_eq!(names.len(), EXPORTERS.len());
+        assert_eq!(exporter("html").map(|exporter| exporter.content_type()), Some(HTML_CONTENT_TYPE));
+        assert!(exporter("pdf").is_none());
+        assert_eq!(export_formats().iter().filter(|format| !format.table).count(), 1);
+
+        let negotiated = |accept: &str| negotiate(accept).map(|exporter| exporter.name());
+        assert_eq!(negotiated("text/csv"), Some("csv"));
+        assert_eq!(negotiated("text/markdown;q=0.5, text/html"), Some("html"));
+        assert_eq!(negotiated("application/pdf, text/*;q=0.8"), Some("csv"));
+        assert_eq!(negotiated("*/*"), Some("json"));
+        assert_eq!(negotiated("Application/JSON"), Some("json"));
+        assert_eq!(negotiated("text/csv;q=0, application/pdf"), None);
+        assert_eq!(negotiated(""), None);
+        log!("[TEST] test_export_registry completed successfully");
+    }
+
+    #[test]
+    fn test_link_signature() {
+        log!("[TEST] Starting test_link_signature");
+        let url = "https://example.com/laptops";
+        let signature = link_signature("key", url, "csv", false, 1000);
+        assert_eq!(signature.len(), 64);
+        assert!(verify_link_signature("key", url, "csv", false, 1000, &signature));
+        // Test every part of the link is signed
+        assert!(!verify_link_signature("other", url, "csv", false, 1000, &signature));
+        assert!(!verify_link_signature("key", "https://example.com/phones", "csv", false, 1000, &signature));
+        assert!(!verify_link_signature("key", url, "xlsx", false, 1000, &signature));
+        assert!(!verify_link_signature("key", url, "csv", true, 1000, &signature));
+        assert!(!verify_link_signature("key", url, "csv", false, 2000, &signature));
+        assert!(!verify_link_signature("key", url, "csv", false, 1000, &signature[..62]));
+        assert!(!verify_link_signature("key", url, "csv", false, 1000, "zz"));
+        log!("[TEST] test_link_signature completed successfully");
+    }
+}
@@ -0,0 +1,111 @@
+// Artificial latency and failures of the API, set up in the fault_injection section of
+// the configuration for development and end-to-end tests. Slow and failing answers let
+// the offline queue, the retries and the rollback of optimistic edits be tried by hand
+// without unplugging anything. Off unless a latency or a failure rate is configured
+use crate::api::ApiError;
+use crate::config::FaultInjectionConfig;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{web, ResponseError};
+use rand::Rng;
+use std::time::Duration;
+
+pub struct FaultInjection {
+    config: FaultInjectionConfig,
+}
+
+impl FaultInjection {
+    pub fn new(config: FaultInjectionConfig) -> Self {
+        FaultInjection { config }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.latency_ms > 0 || self.config.latency_jitter_ms > 0 || self.config.failure_rate > 0.0
+    }
+
+    // Delay of a response and whether it fails instead of reaching its handler
+    pub fn draw(&self, rng: &mut impl Rng) -> (Duration, bool) {
+        let jitter = if self.config.latency_jitter_ms > 0 { rng.gen_range(0..=self.config.latency_jitter_ms) } else { 0 };
+        let fails = self.config.failure_rate > 0.0 && rng.gen_bool(self.config.failure_rate.min(1.0));
+        (Duration::from_millis(self.config.latency_ms + jitter), fails)
+    }
+}
+
+// Middleware of the /api scope, delaying or failing requests as configured
+pub async fn inject_faults(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let faults = req.app_data::<web::Data<FaultInjection>>().filter(|faults| faults.is_enabled()).cloned();
+    let Some(faults) = faults else {
+        return next.call(req).await.map(ServiceResponse::map_into_left_body);
+    };
+    let (delay, fails) = faults.draw(&mut rand::thread_rng());
+    if !delay.is_zero() {
+        actix_web::rt::time::sleep(delay).await;
+    }
+    if fails {
+        let response = ApiError::InjectedFailure.error_response();
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+    next.call(req).await.map(ServiceResponse::map_into_left_body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::error::ErrorResponse;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::{middleware, App, HttpResponse};
+    use leptos::logging::log;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_draw() {
+        log!("[TEST] Starting test_draw");
+        let mut rng = StdRng::seed_from_u64(7);
+        let off = FaultInjection::new(FaultInjectionConfig::default());
+        assert!(!off.is_enabled());
+        assert_eq!(off.draw(&mut rng), (Duration::ZERO, false));
+
+        let faults = FaultInjection::new(FaultInjectionConfig { latency_ms: 100, latency_jitter_ms: 50, failure_rate: 0.5 });
+        assert!(faults.is_enabled());
+        let draws: Vec<(Duration, bool)> = (0..200).map(|_| faults.draw(&mut rng)).collect();
+        assert!(draws.iter().all(|(delay, _)| (100..=150).contains(&delay.as_millis())));
+        let failures = draws.iter().filter(|(_, fails)| *fails).count();
+        assert!((50..150).contains(&failures), "{} failures out of 200", failures);
+
+        // Test a rate of 1 fails every request
+        let failing = FaultInjection::new(FaultInjectionConfig { failure_rate: 1.0, ..Default::default() });
+        assert!((0..20).all(|_| failing.draw(&mut rng) == (Duration::ZERO, true)));
+        log!("[TEST] test_draw completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_inject_faults() {
+        log!("[TEST] Starting test_inject_faults");
+        let faulty_app = |config: FaultInjectionConfig| {
+            App::new()
+                .app_data(web::Data::new(FaultInjection::new(config)))
+                .wrap(middleware::from_fn(inject_faults))
+                .route("/api/ping", web::get().to(|| async { HttpResponse::Ok().body("pong") }))
+        };
+
+        let app = test::init_service(faulty_app(FaultInjectionConfig { latency_ms: 20, ..Default::default() })).await;
+        let started = std::time::Instant::now();
+        let response = test::call_service(&app, TestRequest::get().uri("/api/ping").to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        assert_eq!(test::read_body(response).await, "pong");
+
+        let app = test::init_service(faulty_app(FaultInjectionConfig { failure_rate: 1.0, ..Default::default() })).await;
+        let response = test::call_service(&app, TestRequest::get().uri("/api/ping").to_request()).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let error: ErrorResponse = test::read_body_json(response).await;
+        assert_eq!(error.code, "injected_failure");
+        log!("[TEST] test_inject_faults completed successfully");
+    }
+}
@@ -0,0 +1,74 @@
+// Instance settings changed by admins at /api/admin/settings, and the admin token chosen
+// in the setup wizard. Handlers read them on every request, so they are kept in memory
+// and written through to the database
+use crate::db::Database;
+use crate::models::instance::{InstanceFeatures, InstanceSettings, DEFAULT_WIKIDATA_LANGUAGE};
+use crate::wikidata_proxy::RateLimiter;
+use std::sync::RwLock;
+
+pub struct InstanceConfig {
+    settings: RwLock<InstanceSettings>,
+    // Hash of the admin token stored by the setup wizard
+    admin_token_hash: RwLock<Option<String>>,
+}
+
+impl InstanceConfig {
+    // Settings saved by an admin, applied to the rate limiter. Before any are saved,
+    // the limits the rate limiter was built with are kept
+    pub async fn load(db: &Database, limiter: &RateLimiter) -> Result<Self, rusqlite::Error> {
+        let settings = match db.get_instance_settings().await? {
+            Some(settings) => {
+                limiter.set_limits(settings.wikidata_client_rpm, settings.wikidata_global_rpm);
+                settings
+            }
+            None => {
+                let (wikidata_client_rpm, wikidata_global_rpm) = limiter.limits();
+                InstanceSettings {
+                    wikidata_client_rpm,
+                    wikidata_global_rpm,
+                    wikidata_language: DEFAULT_WIKIDATA_LANGUAGE.to_string(),
+                    private: false,
+                    features: InstanceFeatures::default(),
+                }
+            }
+        };
+        let admin_token_hash = db.get_admin_token_hash().await?;
+        Ok(InstanceConfig { settings: RwLock::new(settings), admin_token_hash: RwLock::new(admin_token_hash) })
+    }
+
+    // Read the settings again after the database was replaced
+    pub async fn reload(&self, db: &Database, limiter: &RateLimiter) -> Result<(), rusqlite::Error> {
+        let loaded = InstanceConfig::load(db, limiter).await?;
+        *self.settings.write().unwrap() = loaded.settings();
+        *self.admin_token_hash.write().unwrap() = loaded.admin_token_hash();
+        Ok(())
+    }
+
+    pub fn settings(&self) -> InstanceSettings {
+        self.settings.read().unwrap().clone()
+    }
+
+    pub fn admin_token_hash(&self) -> Option<String> {
+        self.admin_token_hash.read().unwrap().clone()
+    }
+
+    // Store new settings and apply them to the rate limiter
+    pub async fn update(&self, db: &Database, limiter: &RateLimiter, settings: InstanceSettings) -> Result<(), rusqlite::Error> {
+        db.set_instance_settings(&settings).await?;
+        limiter.set_limits(settings.wikidata_client_rpm, settings.wikidata_global_rpm);
+        *self.settings.write().unwrap() = settings;
+        Ok(())
+    }
+
+    // Store the admin token and the first settings of a new instance, false when it
+    // was already set up and nothing changed
+    pub async fn complete_setup(&self, db: &Database, limiter: &RateLimiter, token_hash: String, settings: InstanceSettings) -> Result<bool, rusqlite::Error> {
+        if !db.complete_setup(&token_hash, &settings, chrono::Utc::now().timestamp()).await? {
+            return Ok(false);
+        }
+        limiter.set_limits(settings.wikidata_client_rpm, settings.wikidata_global_rpm);
+        *self.settings.write().unwrap() = settings;
+        *self.admin_token_hash.write().unwrap() = Some(token_hash);
+        Ok(true)
+    }
+}
@@ -1,10 +1,59 @@
 pub mod app;
 pub mod components;
 pub mod models;
-pub mod nostr;
 pub mod api;
+pub mod url_path;
+pub mod api_client;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "ssr")]
+pub mod nostr;
 #[cfg(feature = "ssr")]
 pub mod db;
+#[cfg(feature = "ssr")]
+pub mod sparql;
+#[cfg(feature = "ssr")]
+pub mod wikidata_proxy;
+#[cfg(feature = "ssr")]
+pub mod wikidata_fixtures;
+#[cfg(feature = "ssr")]
+pub mod zap;
+#[cfg(feature = "ssr")]
+pub mod app_state;
+#[cfg(feature = "ssr")]
+pub mod live;
+#[cfg(feature = "ssr")]
+pub mod publisher;
+#[cfg(feature = "ssr")]
+pub mod mirror;
+#[cfg(feature = "ssr")]
+pub mod storage_backup;
+#[cfg(feature = "ssr")]
+pub mod database_backup;
+#[cfg(feature = "ssr")]
+pub mod instance;
+#[cfg(feature = "ssr")]
+pub mod setup;
+#[cfg(feature = "ssr")]
+pub mod table_export;
+#[cfg(feature = "ssr")]
+pub mod export;
+#[cfg(feature = "ssr")]
+pub mod config;
+#[cfg(feature = "ssr")]
+pub mod diagnostics;
+#[cfg(feature = "ssr")]
+pub mod fault_injection;
+#[cfg(feature = "ssr")]
+pub mod comparison_metrics;
+#[cfg(feature = "ssr")]
+pub mod request_limits;
+#[cfg(feature = "ssr")]
+pub mod api_keys;
+#[cfg(feature = "ssr")]
+pub mod visibility;
+#[cfg(feature = "ssr")]
+pub mod routes;
 
 
 #[cfg(feature = "hydrate")]
@@ -0,0 +1,308 @@
+// Live collaboration: changes saved through the API are broadcast to the WebSockets
+// open on the same comparison, /ws/urls/{url}, one JSON text message per change, and
+// to the Server-Sent Events streams of simpler clients, /api/urls/{url}/events
+use crate::models::live::ComparisonChange;
+use actix_codec::{Decoder, Encoder};
+use actix_http::ws::{hash_key, verify_handshake, CloseCode, Codec, Frame, Message};
+use actix_web::http::header;
+use actix_web::web::{self, Bytes, BytesMut};
+use actix_web::{HttpRequest, HttpResponse};
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc;
+
+// Changes queued for a socket before it falls behind and is told to reload
+const CHANNEL_CAPACITY: usize = 64;
+
+// Seconds between pings and keep-alive comments, keeping idle connections open behind proxies
+const PING_INTERVAL_SECS: u64 = 30;
+
+// A change together with the live session of the tab that made it
+#[derive(Clone)]
+struct LiveUpdate {
+    session_id: Option<String>,
+    change: ComparisonChange,
+}
+
+// Broadcast channels of the comparisons open in at least one browser
+#[derive(Default)]
+pub struct LiveUpdates {
+    channels: Mutex<HashMap<String, broadcast::Sender<LiveUpdate>>>,
+}
+
+// Receiver of a URL's changes, releasing the URL's channel once the last one is dropped
+struct Subscription {
+    live: web::Data<LiveUpdates>,
+    url: String,
+    updates: Option<broadcast::Receiver<LiveUpdate>>,
+    // Changes made by this session are left out
+    session_id: Option<String>,
+}
+
+impl Subscription {
+    fn new(live: web::Data<LiveUpdates>, url: String, session_id: Option<String>) -> Self {
+        let updates = Some(live.subscribe(&url));
+        Subscription { live, url, updates, session_id }
+    }
+
+    // Next change made by another session, None once the channel is closed
+    async fn recv(&mut self) -> Option<ComparisonChange> {
+        let updates = self.updates.as_mut()?;
+        loop {
+            match updates.recv().await {
+                Ok(update) if update.session_id.is_some() && update.session_id == self.session_id => continue,
+                Ok(update) => return Some(update.change),
+                // The client fell behind and missed some changes
+                Err(RecvError::Lagged(_)) => return Some(ComparisonChange::Reload),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.updates.take();
+        self.live.release(&self.url);
+    }
+}
+
+impl LiveUpdates {
+    fn subscribe(&self, url: &str) -> broadcast::Receiver<LiveUpdate> {
+        let mut channels = self.channels.lock().unwrap();
+        channels
+            .entry(url.to_string())
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    // Drop the channel of a URL once its last socket is closed
+    fn release(&self, url: &str) {
+        let mut channels = self.channels.lock().unwrap();
+        if channels.get(url).is_some_and(|sender| sender.receiver_count() == 0) {
+            channels.remove(url);
+        }
+    }
+
+    // Send a change to the sockets open on the URL, except the one of the tab that made it
+    pub fn publish(&self, url: &str, session_id: Option<&str>, change: ComparisonChange) {
+        if let Some(sender) = self.channels.lock().unwrap().get(url) {
+            let _ = sender.send(LiveUpdate { session_id: session_id.map(str::to_string), change });
+        }
+    }
+
+    // Number of sockets open on the URL
+    pub fn subscribers(&self, url: &str) -> usize {
+        self.channels.lock().unwrap().get(url).map_or(0, |sender| sender.receiver_count())
+    }
+}
+
+// Accept a WebSocket handshake and stream the URL's changes to it, leaving out
+// the ones made by `session_id`
+pub fn connect(
+    live: web::Data<LiveUpdates>,
+    url: String,
+    session_id: Option<String>,
+    req: &HttpRequest,
+    payload: web::Payload,
+) -> Result<HttpResponse, actix_web::Error> {
+    verify_handshake(req.head())?;
+    let accept = req
+        .headers()
+        .get(header::SEC_WEBSOCKET_KEY)
+        .map(|key| hash_key(key.as_bytes()))
+        .unwrap_or_default();
+
+    let (outgoing, outgoing_rx) = mpsc::unbounded_channel();
+    let subscription = Subscription::new(live, url, session_id);
+    actix_web::rt::spawn(run_socket(payload, subscription, outgoing));
+
+    // Frames sent to the browser, the socket closes once the sender is dropped
+    let frames = futures::stream::unfold((outgoing_rx, Codec::new()), |(mut messages, mut codec)| async move {
+        let message = messages.recv().await?;
+        let mut frame = BytesMut::new();
+        codec.encode(message, &mut frame).ok()?;
+        Some((Ok::<_, Infallible>(frame.freeze()), (messages, codec)))
+    });
+
+    Ok(HttpResponse::SwitchingProtocols()
+        .upgrade("websocket")
+        .insert_header((header::SEC_WEBSOCKET_ACCEPT, &accept[..]))
+        .streaming(frames))
+}
+
+async fn run_socket(mut payload: web::Payload, mut subscription: Subscription, outgoing: mpsc::UnboundedSender<Message>) {
+    let mut codec = Codec::new();
+    let mut buffer = BytesMut::new();
+    let period = Duration::from_secs(PING_INTERVAL_SECS);
+    let mut ping = actix_web::rt::time::interval_at(actix_web::rt::time::Instant::now() + period, period);
+
+    loop {
+        tokio::select! {
+            chunk = payload.next() => {
+                let Some(Ok(chunk)) = chunk else {
+                    break;
+                };
+                buffer.extend_from_slice(&chunk);
+                // Browsers only listen, apart from pings and closing the socket
+                loop {
+                    match codec.decode(&mut buffer) {
+                        Ok(Some(Frame::Ping(data))) => {
+                            let _ = outgoing.send(Message::Pong(data));
+                        }
+                        Ok(Some(Frame::Close(reason))) => {
+                            let _ = outgoing.send(Message::Close(reason));
+                            return;
+                        }
+                        Ok(Some(_)) => {}
+                        Ok(None) => break,
+                        Err(_) => {
+                            let _ = outgoing.send(Message::Close(Some(CloseCode::Protocol.into())));
+                            return;
+                        }
+                    }
+                }
+            }
+            change = subscription.recv() => {
+                let Some(change) = change else {
+                    break;
+                };
+                let Ok(text) = serde_json::to_string(&change) else {
+                    continue;
+                };
+                if outgoing.send(Message::Text(text.into())).is_err() {
+                    break;
+                }
+            }
+            _ = ping.tick() => {
+                if outgoing.send(Message::Ping(Bytes::new())).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// Stream the URL's changes as Server-Sent Events, named after the change type with
+// the change as JSON data, e.g. `event: item_deleted` and `data: {"type":"item_deleted",...}`
+pub fn event_stream(live: web::Data<LiveUpdates>, url: String, session_id: Option<String>) -> HttpResponse {
+    let subscription = Subscription::new(live, url, session_id);
+    let events = futures::stream::unfold(subscription, |mut subscription| async move {
+        let keep_alive = Duration::from_secs(PING_INTERVAL_SECS);
+        let event = match actix_web::rt::time::timeout(keep_alive, subscription.recv()).await {
+            Ok(Some(change)) => server_sent_event(&change),
+            Ok(None) => return None,
+            Err(_) => ": keep-alive\n\n".to_string(),
+        };
+        Some((Ok::<_, Infallible>(Bytes::from(event)), subscription))
+    });
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        // Compressed events would wait in the encoder's buffer instead of reaching the page
+        .insert_header(header::ContentEncoding::Identity)
+        .streaming(events)
+}
+
+fn server_sent_event(change: &ComparisonChange) -> String {
+    let data = serde_json::to_value(change).unwrap_or_default();
+    let name = data["type"].as_str().unwrap_or("change").to_string();
+    format!("event: {}\ndata: {}\n\n", name, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test as actix_test, App};
+    use leptos::logging::log;
+
+    #[tokio::test]
+    async fn test_publish_to_subscribers() {
+        log!("[TEST] Starting test_publish_to_subscribers");
+        let live = LiveUpdates::default();
+        let url = "https://example.com/laptops";
+        // Nobody listens yet, the change goes nowhere
+        live.publish(url, None, ComparisonChange::Reload);
+        assert_eq!(live.subscribers(url), 0);
+
+        let mut updates = live.subscribe(url);
+        live.publish(url, Some("tab1"), ComparisonChange::ItemDeleted { item_id: "item1".into() });
+        live.publish("https://example.com/phones", None, ComparisonChange::Reload);
+        let update = updates.recv().await.unwrap();
+        assert_eq!(update.session_id.as_deref(), Some("tab1"));
+        assert!(matches!(update.change, ComparisonChange::ItemDeleted { item_id } if item_id == "item1"));
+        assert!(updates.try_recv().is_err());
+
+        drop(updates);
+        live.release(url);
+        assert!(live.channels.lock().unwrap().is_empty());
+        log!("[TEST] test_publish_to_subscribers completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_websocket_handshake() {
+        log!("[TEST] Starting test_websocket_handshake");
+        let app = actix_test::init_service(
+            App::new().app_data(web::Data::new(LiveUpdates::default())).route(
+                "/ws/urls/{url}",
+                web::get().to(|req: HttpRequest, payload: web::Payload, live: web::Data<LiveUpdates>, url: web::Path<String>| async move {
+                    connect(live, url.into_inner(), None, &req, payload)
+                }),
+            ),
+        )
+        .await;
+
+        let request = actix_test::TestRequest::get()
+            .uri("/ws/urls/laptops")
+            .insert_header((header::UPGRADE, "websocket"))
+            .insert_header((header::CONNECTION, "upgrade"))
+            .insert_header((header::SEC_WEBSOCKET_VERSION, "13"))
+            .insert_header((header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ=="))
+            .to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.status(), 101);
+        // Accept key of the handshake example in RFC 6455
+        assert_eq!(response.headers().get(header::SEC_WEBSOCKET_ACCEPT).unwrap(), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+
+        let request = actix_test::TestRequest::get().uri("/ws/urls/laptops").to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.status(), 400);
+        log!("[TEST] test_websocket_handshake completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_event_stream() {
+        log!("[TEST] Starting test_event_stream");
+        let live = web::Data::new(LiveUpdates::default());
+        let app = actix_test::init_service(App::new().app_data(live.clone()).route(
+            "/events/{url}",
+            web::get().to(|live: web::Data<LiveUpdates>, url: web::Path<String>| async move {
+                event_stream(live, url.into_inner(), Some("tab1".into()))
+            }),
+        ))
+        .await;
+
+        let response = actix_test::call_service(&app, actix_test::TestRequest::get().uri("/events/laptops").to_request()).await;
+        assert_eq!(response.headers().get(header::CONTENT_TYPE).unwrap(), "text/event-stream");
+        assert_eq!(live.subscribers("laptops"), 1);
+
+        // The stream's own session is skipped
+        live.publish("laptops", Some("tab1"), ComparisonChange::Reload);
+        live.publish("laptops", Some("tab2"), ComparisonChange::ItemDeleted { item_id: "item1".into() });
+        let mut body = response.into_body();
+        let event = futures::future::poll_fn(|cx| actix_web::body::MessageBody::poll_next(std::pin::Pin::new(&mut body), cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event, "event: item_deleted\ndata: {\"type\":\"item_deleted\",\"item_id\":\"item1\"}\n\n");
+
+        // Disconnecting releases the channel
+        drop(body);
+        assert_eq!(live.subscribers("laptops"), 0);
+        log!("[TEST] test_event_stream completed successfully");
+    }
+}
@@ -0,0 +1,250 @@
+-- Schema built by the numbered steps of create_schema before migrations existed,
+-- which is why the first migration is version 21. Databases created back then are
+-- brought up to it and marked as migrated instead of running it.
+
+-- 1. Properties
+CREATE TABLE IF NOT EXISTS properties (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    global_usage_count INTEGER DEFAULT 0
+);
+INSERT OR IGNORE INTO properties (name) VALUES ('name'), ('description');
+
+-- 2. Comparison URLs, with the hash of the owner's edit key and the decision
+-- the comparison led to
+CREATE TABLE IF NOT EXISTS urls (
+    id INTEGER PRIMARY KEY,
+    url TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    edit_key_hash TEXT,
+    updated_at INTEGER,
+    title TEXT NOT NULL DEFAULT '',
+    description TEXT NOT NULL DEFAULT '',
+    decision_item_id TEXT,
+    decided_at INTEGER,
+    decision_rationale TEXT NOT NULL DEFAULT ''
+);
+
+-- 3. Items. Items with the same name share a global_item_id and their values
+CREATE TABLE IF NOT EXISTS items (
+    id TEXT PRIMARY KEY,
+    url_id INTEGER NOT NULL,
+    wikidata_id TEXT,
+    item_order INTEGER NOT NULL DEFAULT 0,
+    global_item_id TEXT,
+    item_group TEXT,
+    tip_recipient TEXT,
+    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE
+);
+
+-- 4. Properties selected for a comparison, in their order
+CREATE TABLE IF NOT EXISTS selected_properties (
+    url_id INTEGER NOT NULL,
+    property_id INTEGER NOT NULL,
+    property_order INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (url_id, property_id),
+    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE,
+    FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
+);
+
+-- 5. Property values of items, with the amount and unit of Wikidata quantities
+CREATE TABLE IF NOT EXISTS item_properties (
+    global_item_id TEXT NOT NULL,
+    property_id INTEGER NOT NULL,
+    value TEXT NOT NULL,
+    quantity_amount REAL,
+    quantity_unit_id TEXT,
+    quantity_unit TEXT,
+    quantity_precision REAL,
+    PRIMARY KEY (global_item_id, property_id),
+    FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
+);
+
+-- 6. Properties deleted from a comparison's items
+CREATE TABLE IF NOT EXISTS deleted_properties (
+    url_id INTEGER NOT NULL,
+    global_item_id TEXT NOT NULL,
+    property_id INTEGER NOT NULL,
+    PRIMARY KEY (url_id, global_item_id, property_id),
+    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE,
+    FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
+);
+
+-- 7. Edit sessions and the cells they hold locked. These are keyed by
+-- URL text so that viewing a URL doesn't register it in the urls table
+CREATE TABLE IF NOT EXISTS edit_sessions (
+    session_id TEXT PRIMARY KEY,
+    url TEXT NOT NULL,
+    last_seen INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS cell_locks (
+    url TEXT NOT NULL,
+    cell TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    acquired_at INTEGER NOT NULL,
+    PRIMARY KEY (url, cell),
+    FOREIGN KEY (session_id) REFERENCES edit_sessions(session_id) ON DELETE CASCADE
+);
+
+-- 8. Comparisons received from other instances over Nostr, one row per event address
+CREATE TABLE IF NOT EXISTS remote_comparisons (
+    address TEXT PRIMARY KEY,
+    event_id TEXT NOT NULL,
+    author TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    relays TEXT NOT NULL DEFAULT '[]'
+);
+
+-- 9. Cached Wikidata labels and entity claims, so reloading a comparison doesn't
+-- query wikidata.org again. Rows past `expires_at` are ignored and purged on write
+CREATE TABLE IF NOT EXISTS wikidata_labels (
+    entity_id TEXT PRIMARY KEY,
+    label TEXT NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS wikidata_claims (
+    entity_id TEXT PRIMARY KEY,
+    claims TEXT NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+
+-- 10. Comparisons listed in the public directory announced over Nostr
+CREATE TABLE IF NOT EXISTS comparison_listings (
+    url_id INTEGER PRIMARY KEY,
+    title TEXT NOT NULL,
+    category TEXT NOT NULL DEFAULT '',
+    listed_at INTEGER NOT NULL,
+    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE
+);
+
+-- 11. Item reviews, one per reviewer and item
+CREATE TABLE IF NOT EXISTS reviews (
+    item_id TEXT NOT NULL,
+    reviewer_id TEXT NOT NULL,
+    rating INTEGER NOT NULL CHECK (rating BETWEEN 1 AND 5),
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (item_id, reviewer_id),
+    FOREIGN KEY (item_id) REFERENCES items(id) ON DELETE CASCADE
+);
+
+-- 12. Collaborator tokens of protected comparisons, stored as SHA-256 hashes.
+-- Owner keys live in urls.edit_key_hash, older databases may still hold owner rows here
+CREATE TABLE IF NOT EXISTS comparison_editors (
+    url_id INTEGER NOT NULL,
+    token_hash TEXT NOT NULL,
+    role TEXT NOT NULL CHECK (role IN ('owner', 'collaborator')),
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (url_id, token_hash),
+    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE
+);
+
+-- 13. Snapshots, frozen copies of comparisons. Items are stored as JSON
+-- so later schema changes can't alter what a snapshot shows
+CREATE TABLE IF NOT EXISTS snapshots (
+    id TEXT PRIMARY KEY,
+    url TEXT NOT NULL,
+    selected_properties TEXT NOT NULL,
+    property_labels TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS snapshot_items (
+    snapshot_id TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    item TEXT NOT NULL,
+    PRIMARY KEY (snapshot_id, position),
+    FOREIGN KEY (snapshot_id) REFERENCES snapshots(id) ON DELETE CASCADE
+);
+
+-- 14. Full text of property values too long for the grid. `item_properties`
+-- keeps a preview of these, so loading a comparison stays light
+CREATE TABLE IF NOT EXISTS long_values (
+    global_item_id TEXT NOT NULL,
+    property_id INTEGER NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (global_item_id, property_id),
+    FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
+);
+
+-- 15. History of deleted properties, with the items each deletion hid and
+-- their values at the time, so a deletion can be undone
+CREATE TABLE IF NOT EXISTS property_deletions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    url_id INTEGER NOT NULL,
+    property_id INTEGER NOT NULL,
+    property_order INTEGER,
+    deleted_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE,
+    FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
+);
+CREATE TABLE IF NOT EXISTS property_deletion_items (
+    deletion_id INTEGER NOT NULL,
+    global_item_id TEXT NOT NULL,
+    value TEXT,
+    quantity_amount REAL,
+    quantity_unit_id TEXT,
+    quantity_unit TEXT,
+    quantity_precision REAL,
+    PRIMARY KEY (deletion_id, global_item_id),
+    FOREIGN KEY (deletion_id) REFERENCES property_deletions(id) ON DELETE CASCADE
+);
+
+-- 16. Comparisons publishing their saved items to Nostr relays
+CREATE TABLE IF NOT EXISTS nostr_publishing (
+    url_id INTEGER PRIMARY KEY,
+    enabled INTEGER NOT NULL DEFAULT 0,
+    relays TEXT NOT NULL DEFAULT '[]',
+    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE
+);
+
+-- 17. Language and spell-checking of a comparison's property cells
+CREATE TABLE IF NOT EXISTS property_settings (
+    url_id INTEGER NOT NULL,
+    property_id INTEGER NOT NULL,
+    lang TEXT,
+    spellcheck INTEGER,
+    PRIMARY KEY (url_id, property_id),
+    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE,
+    FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
+);
+
+-- 18. Comparisons mirroring the items another instance publishes to Nostr,
+-- with the version of each received item that was last merged
+CREATE TABLE IF NOT EXISTS comparison_mirrors (
+    url_id INTEGER PRIMARY KEY,
+    enabled INTEGER NOT NULL DEFAULT 0,
+    source_url TEXT NOT NULL,
+    author TEXT NOT NULL,
+    relays TEXT NOT NULL DEFAULT '[]',
+    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE
+);
+CREATE TABLE IF NOT EXISTS mirrored_items (
+    url_id INTEGER NOT NULL,
+    source_item_id TEXT NOT NULL,
+    item_id TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    synced_item TEXT NOT NULL,
+    PRIMARY KEY (url_id, source_item_id),
+    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE
+);
+
+-- 19. Instance settings changed by admins at runtime, a single row once saved
+CREATE TABLE IF NOT EXISTS instance_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    wikidata_client_rpm INTEGER NOT NULL,
+    wikidata_global_rpm INTEGER NOT NULL,
+    wikidata_language TEXT NOT NULL,
+    private INTEGER NOT NULL DEFAULT 0,
+    features TEXT NOT NULL DEFAULT '{}'
+);
+
+-- 20. Audit trail of admin actions, its tail is part of the diagnostics bundle
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_at INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    detail TEXT NOT NULL DEFAULT ''
+);
+
+-- 21. Older databases declared global_item_id a foreign key of items, where it isn't
+-- unique. The tables above no longer do, databases still doing it are rebuilt on adoption
@@ -0,0 +1,3 @@
+-- Deleted items go to the trash of their comparison instead of being dropped, with
+-- their values and reviews, until they are restored. NULL for the items in use
+ALTER TABLE items ADD COLUMN deleted_at INTEGER;
@@ -0,0 +1,12 @@
+-- Edit history of comparisons: one row per change of an item or a property, with
+-- who made it. Saved items are stored as JSON, the version that can be restored
+CREATE TABLE IF NOT EXISTS item_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    url_id INTEGER NOT NULL,
+    item_id TEXT,
+    property TEXT,
+    change TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE
+);
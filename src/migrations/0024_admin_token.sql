@@ -0,0 +1,7 @@
+-- Admin token chosen in the setup wizard of a new instance, stored as a SHA-256 hash.
+-- COMPAREWARE_ADMIN_TOKEN keeps working next to it
+CREATE TABLE IF NOT EXISTS admin_token (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    token_hash TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
@@ -0,0 +1,3 @@
+-- Unit system the exports of a comparison convert values to: metric, imperial or
+-- as_entered, empty for comparisons that never chose, which keep values as entered too
+ALTER TABLE urls ADD COLUMN unit_system TEXT NOT NULL DEFAULT '';
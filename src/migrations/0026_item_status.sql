@@ -0,0 +1,3 @@
+-- Where an item stands in the decision: considering, shortlisted, rejected, purchased or
+-- discontinued, NULL for items nobody set a status on
+ALTER TABLE items ADD COLUMN status TEXT;
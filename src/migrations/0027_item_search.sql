@@ -0,0 +1,32 @@
+-- Full-text indexes of the item values, for the search across all comparisons. Names and
+-- descriptions are values too. `item_properties` holds previews of long values, their full
+-- text is indexed from `long_values`. Triggers keep both in step with the values
+CREATE VIRTUAL TABLE item_search USING fts5(value, global_item_id UNINDEXED, property_id UNINDEXED);
+INSERT INTO item_search (value, global_item_id, property_id)
+    SELECT value, global_item_id, property_id FROM item_properties;
+
+CREATE TRIGGER item_search_insert AFTER INSERT ON item_properties BEGIN
+    INSERT INTO item_search (value, global_item_id, property_id) VALUES (NEW.value, NEW.global_item_id, NEW.property_id);
+END;
+CREATE TRIGGER item_search_update AFTER UPDATE OF value ON item_properties BEGIN
+    DELETE FROM item_search WHERE global_item_id = OLD.global_item_id AND property_id = OLD.property_id;
+    INSERT INTO item_search (value, global_item_id, property_id) VALUES (NEW.value, NEW.global_item_id, NEW.property_id);
+END;
+CREATE TRIGGER item_search_delete AFTER DELETE ON item_properties BEGIN
+    DELETE FROM item_search WHERE global_item_id = OLD.global_item_id AND property_id = OLD.property_id;
+END;
+
+CREATE VIRTUAL TABLE long_value_search USING fts5(value, global_item_id UNINDEXED, property_id UNINDEXED);
+INSERT INTO long_value_search (value, global_item_id, property_id)
+    SELECT value, global_item_id, property_id FROM long_values;
+
+CREATE TRIGGER long_value_search_insert AFTER INSERT ON long_values BEGIN
+    INSERT INTO long_value_search (value, global_item_id, property_id) VALUES (NEW.value, NEW.global_item_id, NEW.property_id);
+END;
+CREATE TRIGGER long_value_search_update AFTER UPDATE OF value ON long_values BEGIN
+    DELETE FROM long_value_search WHERE global_item_id = OLD.global_item_id AND property_id = OLD.property_id;
+    INSERT INTO long_value_search (value, global_item_id, property_id) VALUES (NEW.value, NEW.global_item_id, NEW.property_id);
+END;
+CREATE TRIGGER long_value_search_delete AFTER DELETE ON long_values BEGIN
+    DELETE FROM long_value_search WHERE global_item_id = OLD.global_item_id AND property_id = OLD.property_id;
+END;
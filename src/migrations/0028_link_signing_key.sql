@@ -0,0 +1,7 @@
+-- Key of the HMAC signatures of shared export and embed links, created with the first link.
+-- Replacing it invalidates every link handed out so far
+CREATE TABLE IF NOT EXISTS link_signing_key (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    key TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
@@ -0,0 +1,3 @@
+-- Time of the last save of an item in milliseconds since the Unix epoch, its version for
+-- detecting saves based on an outdated copy. NULL for items not saved since
+ALTER TABLE items ADD COLUMN updated_at INTEGER;
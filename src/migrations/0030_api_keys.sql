@@ -0,0 +1,10 @@
+-- Keys of scripts and integrations, sent as `Authorization: Bearer <key>`. Only their
+-- SHA-256 hash is stored. Keys limited to one comparison name its URL
+CREATE TABLE IF NOT EXISTS api_keys (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    url TEXT,
+    created_at INTEGER NOT NULL,
+    last_used_at INTEGER
+);
@@ -0,0 +1,11 @@
+-- Labels and tooltips a comparison shows for its properties instead of their
+-- Wikidata labels or raw names, NULL where the default is kept
+CREATE TABLE IF NOT EXISTS property_overrides (
+    url_id INTEGER NOT NULL,
+    property_id INTEGER NOT NULL,
+    label TEXT,
+    description TEXT,
+    PRIMARY KEY (url_id, property_id),
+    FOREIGN KEY (url_id) REFERENCES urls(id) ON DELETE CASCADE,
+    FOREIGN KEY (property_id) REFERENCES properties(id) ON DELETE CASCADE
+);
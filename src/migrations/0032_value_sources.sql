@@ -0,0 +1,6 @@
+-- Where each value came from: 'wikidata' for claims filled in from Wikidata, 'import' for
+-- values of imported comparisons and 'manual' for values typed in. Refreshing from
+-- Wikidata only overwrites the 'wikidata' ones
+ALTER TABLE item_properties ADD COLUMN source TEXT NOT NULL DEFAULT 'manual';
+-- Kept with the values of deleted properties, so undoing the deletion restores it
+ALTER TABLE property_deletion_items ADD COLUMN source TEXT;
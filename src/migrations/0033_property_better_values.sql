@@ -0,0 +1,3 @@
+-- Which end of a numeric property's values the grid highlights as best: 'higher',
+-- 'lower', or NULL for no highlighting
+ALTER TABLE property_settings ADD COLUMN better TEXT;
@@ -0,0 +1,2 @@
+-- Weight of a property in the score of the items, NULL for properties left out of it
+ALTER TABLE property_settings ADD COLUMN weight REAL;
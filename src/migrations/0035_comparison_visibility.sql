@@ -0,0 +1,2 @@
+-- Who finds and reads a comparison: 'public', 'unlisted' or 'private'
+ALTER TABLE urls ADD COLUMN visibility TEXT NOT NULL DEFAULT 'public';
@@ -0,0 +1,4 @@
+-- Time of the last change to a URL in milliseconds instead of seconds, made one more than
+-- the previous one when both fall in the same millisecond, so it tells every version of a
+-- comparison apart for the ETag of its items
+UPDATE urls SET updated_at = updated_at * 1000 WHERE updated_at IS NOT NULL;
@@ -0,0 +1,48 @@
+-- Items are upserted on (id, url_id), so saving an item ID under another URL fails instead
+-- of moving the item out of its comparison
+CREATE UNIQUE INDEX IF NOT EXISTS items_id_url ON items(id, url_id);
+
+-- Items of the same name no longer share their values across comparisons. Items sharing a
+-- global_item_id with an item of an earlier comparison get their own copy of the values
+CREATE TEMP TABLE item_copies AS
+    SELECT DISTINCT i.global_item_id AS shared_id, i.url_id, '' AS own_id
+    FROM items i
+    WHERE i.global_item_id IS NOT NULL
+        AND i.url_id > (SELECT MIN(o.url_id) FROM items o WHERE o.global_item_id = i.global_item_id);
+UPDATE item_copies SET own_id = lower(hex(randomblob(16)));
+
+INSERT INTO item_properties (global_item_id, property_id, value, quantity_amount, quantity_unit_id, quantity_unit, quantity_precision, source)
+    SELECT c.own_id, ip.property_id, ip.value, ip.quantity_amount, ip.quantity_unit_id, ip.quantity_unit, ip.quantity_precision, ip.source
+    FROM item_copies c
+    JOIN item_properties ip ON ip.global_item_id = c.shared_id;
+INSERT INTO long_values (global_item_id, property_id, value)
+    SELECT c.own_id, lv.property_id, lv.value
+    FROM item_copies c
+    JOIN long_values lv ON lv.global_item_id = c.shared_id;
+UPDATE deleted_properties SET global_item_id = (
+    SELECT c.own_id FROM item_copies c
+    WHERE c.shared_id = deleted_properties.global_item_id AND c.url_id = deleted_properties.url_id
+)
+WHERE EXISTS (
+    SELECT 1 FROM item_copies c
+    WHERE c.shared_id = deleted_properties.global_item_id AND c.url_id = deleted_properties.url_id
+);
+UPDATE property_deletion_items SET global_item_id = (
+    SELECT c.own_id FROM item_copies c
+    JOIN property_deletions d ON d.url_id = c.url_id
+    WHERE d.id = property_deletion_items.deletion_id AND c.shared_id = property_deletion_items.global_item_id
+)
+WHERE EXISTS (
+    SELECT 1 FROM item_copies c
+    JOIN property_deletions d ON d.url_id = c.url_id
+    WHERE d.id = property_deletion_items.deletion_id AND c.shared_id = property_deletion_items.global_item_id
+);
+UPDATE items SET global_item_id = (
+    SELECT c.own_id FROM item_copies c
+    WHERE c.shared_id = items.global_item_id AND c.url_id = items.url_id
+)
+WHERE EXISTS (
+    SELECT 1 FROM item_copies c
+    WHERE c.shared_id = items.global_item_id AND c.url_id = items.url_id
+);
+DROP TABLE item_copies;
@@ -0,0 +1,114 @@
+// Mirroring of comparisons published by other instances, set up at /api/urls/{url}/mirror.
+// Each enabled mirror keeps a subscription open on its relays and merges the items of
+// each comparison state it receives into the local comparison, pushing them to the tabs open on it
+use crate::db::Database;
+use crate::live::LiveUpdates;
+use crate::models::history::{HistoryChange, MIRROR_ACTOR};
+use crate::models::live::ComparisonChange;
+use crate::models::mirror::{MirrorSettings, MirrorUpdate};
+use crate::nostr::{MyError, NostrClient};
+use crate::{server_error, server_log};
+use actix_web::web;
+use nostr_sdk::{Keys, PublicKey};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, RwLock};
+
+// Received comparison states queued before the relays are read more slowly
+const RECEIVED_CAPACITY: usize = 64;
+
+pub struct ComparisonMirrors {
+    db: Arc<RwLock<Database>>,
+    live: web::Data<LiveUpdates>,
+    // Stop signals of the running mirrors, by local comparison URL
+    running: std::sync::Mutex<HashMap<String, oneshot::Sender<()>>>,
+}
+
+impl ComparisonMirrors {
+    pub fn new(db: Arc<RwLock<Database>>, live: web::Data<LiveUpdates>) -> Self {
+        ComparisonMirrors { db, live, running: Default::default() }
+    }
+
+    // Start the mirrors enabled in the database, once at startup
+    pub async fn start_enabled(&self) {
+        let mirrors = self.db.read().await.get_enabled_mirrors().await;
+        match mirrors {
+            Ok(mirrors) => {
+                for (url, settings) in mirrors {
+                    self.apply(&url, &settings);
+                }
+            }
+            Err(e) => server_error!("Failed to load the mirrored comparisons: {}", e),
+        }
+    }
+
+    // Restart the mirror of a URL with new settings, or stop it when they disable it
+    pub fn apply(&self, url: &str, settings: &MirrorSettings) {
+        let mut running = self.running.lock().unwrap();
+        if let Some(stop) = running.remove(url) {
+            let _ = stop.send(());
+        }
+        if !settings.enabled {
+            return;
+        }
+        let (stop, stopped) = oneshot::channel();
+        running.insert(url.to_string(), stop);
+        let (db, live, url, settings) = (self.db.clone(), self.live.clone(), url.to_string(), settings.clone());
+        actix_web::rt::spawn(async move {
+            if let Err(e) = run_mirror(db, live, &url, &settings, stopped).await {
+                server_error!("Mirroring {} into {} stopped: {}", settings.source_url, url, e);
+            }
+        });
+    }
+}
+
+async fn run_mirror(
+    db: Arc<RwLock<Database>>,
+    live: web::Data<LiveUpdates>,
+    url: &str,
+    settings: &MirrorSettings,
+    mut stopped: oneshot::Receiver<()>,
+) -> Result<(), MyError> {
+    let Ok(author) = PublicKey::from_hex(&settings.author) else {
+        server_error!("Not mirroring into {}, invalid publisher key {}", url, settings.author);
+        return Ok(());
+    };
+    let client = NostrClient::with_keys(Keys::generate(), &settings.relays).await?;
+    let (tx, mut received) = mpsc::channel(RECEIVED_CAPACITY);
+    client.subscribe_to_comparison(&settings.source_url, author, tx).await?;
+    server_log!("Mirroring {} into {}", settings.source_url, url);
+
+    loop {
+        tokio::select! {
+            _ = &mut stopped => break,
+            comparison = received.recv() => {
+                let Some((comparison, created_at)) = comparison else {
+                    break;
+                };
+                for item in &comparison.items {
+                    let update = db.write().await.merge_mirrored_item(url, item, created_at).await;
+                    match update {
+                        Ok(MirrorUpdate::Saved { item, added_properties }) => {
+                            let changes = [HistoryChange::ItemSaved { item: (*item).clone() }];
+                            let recorded = db.write().await.record_revisions(url, MIRROR_ACTOR, &changes, chrono::Utc::now().timestamp()).await;
+                            if let Err(e) = recorded {
+                                server_error!("Failed to record the merge of item {} into {}: {}", item.id, url, e);
+                            }
+                            // Rows of new properties come with the reloaded comparison
+                            let change = if added_properties.is_empty() {
+                                ComparisonChange::item_saved(*item)
+                            } else {
+                                ComparisonChange::Reload
+                            };
+                            live.publish(url, None, change);
+                        }
+                        Ok(MirrorUpdate::Stale | MirrorUpdate::Conflict) => {}
+                        Err(e) => server_error!("Failed to merge item {} into {}: {}", item.id, url, e),
+                    }
+                }
+            }
+        }
+    }
+    client.disconnect().await?;
+    Ok(())
+}
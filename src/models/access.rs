@@ -0,0 +1,109 @@
+/// Who may edit a comparison. The first write to a URL returns a secret owner
+/// edit key to its creator; from then on edits need the owner's key or a
+/// collaborator token, sent in the `X-Edit-Token` header or the `edit_token`
+/// query parameter. Comparisons created before edit keys stay open until
+/// someone protects them. Who may read a comparison follows its visibility.
+use serde::{Deserialize, Serialize};
+
+pub const EDIT_TOKEN_HEADER: &str = "X-Edit-Token";
+pub const EDIT_TOKEN_PARAM: &str = "edit_token";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum EditorRole {
+    // Created or protected the comparison and may invite collaborators
+    Owner,
+    Collaborator,
+}
+
+impl EditorRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EditorRole::Owner => "owner",
+            EditorRole::Collaborator => "collaborator",
+        }
+    }
+
+    pub fn parse(role: &str) -> Option<Self> {
+        match role {
+            "owner" => Some(EditorRole::Owner),
+            "collaborator" => Some(EditorRole::Collaborator),
+            _ => None,
+        }
+    }
+}
+
+/// Who finds and reads a comparison. Public ones are listed in the index and search,
+/// unlisted ones are only read by those who know their URL, and private ones only
+/// with one of their edit tokens or an API key.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Public,
+    Unlisted,
+    Private,
+}
+
+impl Visibility {
+    pub const ALL: [Visibility; 3] = [Visibility::Public, Visibility::Unlisted, Visibility::Private];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Unlisted => "unlisted",
+            Visibility::Private => "private",
+        }
+    }
+
+    pub fn parse(visibility: &str) -> Option<Self> {
+        Visibility::ALL.into_iter().find(|choice| choice.as_str() == visibility)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Visibility::Public => "Public",
+            Visibility::Unlisted => "Unlisted",
+            Visibility::Private => "Private",
+        }
+    }
+}
+
+/// Body of `PUT /api/urls/{url}/visibility`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct VisibilitySetting {
+    pub visibility: Visibility,
+}
+
+/// Edit access of the requester to a comparison.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct EditAccess {
+    pub protected: bool,
+    // Role of the edit token sent with the request, if it is valid
+    pub role: Option<EditorRole>,
+    #[serde(default)]
+    pub visibility: Visibility,
+}
+
+impl EditAccess {
+    pub fn can_edit(&self) -> bool {
+        !self.protected || self.role.is_some()
+    }
+
+    // Private comparisons are read by their editors only
+    pub fn can_read(&self) -> bool {
+        self.visibility != Visibility::Private || self.can_edit()
+    }
+}
+
+/// A newly created edit token, only ever returned once.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct EditToken {
+    pub token: String,
+    pub role: EditorRole,
+}
@@ -0,0 +1,58 @@
+/// Key letting scripts and integrations edit comparisons without a browser, sent as
+/// `Authorization: Bearer <key>` with the REST API. Created by admins.
+use serde::{Deserialize, Serialize};
+
+// Start of every key, telling them apart from admin tokens
+pub const API_KEY_PREFIX: &str = "cwk_";
+const MAX_NAME_LENGTH: usize = 100;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ApiKey {
+    pub id: i64,
+    // What the key is for, shown in the admin page and the edit history
+    pub name: String,
+    // Comparison the key may edit, every comparison when missing
+    pub url: Option<String>,
+    pub created_at: i64,
+    // Last request authenticated with it, within a minute
+    pub last_used_at: Option<i64>,
+}
+
+impl ApiKey {
+    pub fn allows(&self, url: &str) -> bool {
+        self.url.as_deref().map_or(true, |allowed| allowed == url)
+    }
+}
+
+/// Key to create, see POST /api/admin/api-keys.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ApiKeyRequest {
+    pub name: String,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+impl ApiKeyRequest {
+    // Why the key can't be created, if it can't
+    pub fn problem(&self) -> Option<String> {
+        if self.name.trim().is_empty() {
+            Some("API keys need a name".into())
+        } else if self.name.chars().count() > MAX_NAME_LENGTH {
+            Some(format!("Names of API keys are at most {} characters", MAX_NAME_LENGTH))
+        } else if self.url.as_deref().is_some_and(|url| url.trim().is_empty()) {
+            Some("The comparison URL of an API key can't be blank".into())
+        } else {
+            None
+        }
+    }
+}
+
+/// A newly created API key, the only time the key itself is returned.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct CreatedApiKey {
+    pub key: String,
+    pub api_key: ApiKey,
+}
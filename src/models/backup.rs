@@ -0,0 +1,155 @@
+/// Archive of every comparison in the workspace, used for off-site backups.
+/// Items keep their IDs, so restoring an archive twice doesn't duplicate them.
+use crate::models::item::Item;
+use crate::models::metadata::{ComparisonMetadata, MAX_RATIONALE_LENGTH};
+use crate::models::property::{PropertyOverride, PropertySettings};
+use crate::models::review::{ExportedReview, MAX_RATING, MIN_RATING};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Version of comparison exports written by this build, newer ones are refused.
+pub const COMPARISON_EXPORT_VERSION: u32 = 1;
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct WorkspaceArchive {
+    pub version: u32,
+    pub created_at: i64,
+    pub comparisons: Vec<ComparisonArchive>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ComparisonArchive {
+    pub url: String,
+    pub items: Vec<Item>,
+    // Selected properties in display order
+    pub selected_properties: Vec<String>,
+    // Title, description and decision, missing from archives made before they existed
+    #[serde(default)]
+    pub metadata: ComparisonMetadata,
+}
+
+/// Result of publishing a backup to the Nostr relays.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct BackupSummary {
+    pub backup_id: String,
+    pub chunks: usize,
+    pub comparisons: usize,
+    pub relays: Vec<String>,
+}
+
+/// Request body for restoring a backup, the latest one when `backup_id` is omitted.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct RestoreRequest {
+    #[serde(default)]
+    pub backup_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct RestoreSummary {
+    pub backup_id: String,
+    pub comparisons: usize,
+    pub items: usize,
+}
+
+/// Result of uploading a copy of the database and a workspace archive to S3-compatible storage.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct StorageBackupSummary {
+    // Folder of the upload under the configured prefix, named after the time it was made
+    pub name: String,
+    // Keys of the uploaded objects
+    pub objects: Vec<String>,
+    pub bytes: u64,
+    // Folders of older uploads removed to keep the configured number of uploads
+    pub pruned: Vec<String>,
+}
+
+/// Result of replacing the database with an uploaded copy.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct DatabaseRestoreSummary {
+    pub bytes: u64,
+    // Schema version of the restored database, once migrated to this build's
+    pub schema_version: i64,
+    pub comparisons: usize,
+}
+
+/// One comparison as a self-contained document, from `GET /api/urls/{url}/export.json`.
+/// `POST /api/urls/{url}/import.json` recreates it under another URL, on this instance
+/// or another one.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ComparisonExport {
+    pub version: u32,
+    pub exported_at: i64,
+    // Items, selected properties in display order and metadata, under the URL it was exported from
+    pub comparison: ComparisonArchive,
+    // Language and spell-checking of the properties that changed them
+    #[serde(default)]
+    pub property_settings: HashMap<String, PropertySettings>,
+    // Labels and tooltips of the properties that override their defaults
+    #[serde(default)]
+    pub property_overrides: HashMap<String, PropertyOverride>,
+    // Ratings of the items, in exports asked for them
+    #[serde(default)]
+    pub reviews: Vec<ExportedReview>,
+}
+
+impl ComparisonExport {
+    // Why the document can't be imported, None when it can
+    pub fn problem(&self) -> Option<String> {
+        if self.version > COMPARISON_EXPORT_VERSION {
+            return Some(format!("Export version {} is newer than this instance supports ({})", self.version, COMPARISON_EXPORT_VERSION));
+        }
+        let mut item_ids = HashSet::new();
+        if let Some(item) = self.comparison.items.iter().find(|item| !item_ids.insert(&item.id)) {
+            return Some(format!("Item {} appears twice in the export", item.id));
+        }
+        if let Some(review) = self.reviews.iter().find(|review| !(MIN_RATING..=MAX_RATING).contains(&review.rating)) {
+            return Some(format!("Review of item {} must rate it between {} and {} stars", review.item_id, MIN_RATING, MAX_RATING));
+        }
+        match &self.comparison.metadata.decision {
+            Some(decision) if decision.rationale.chars().count() > MAX_RATIONALE_LENGTH => {
+                Some(format!("Rationale must be at most {} characters", MAX_RATIONALE_LENGTH))
+            }
+            _ => None,
+        }
+    }
+
+    // The export under a new URL. Item IDs are unique across the instance, so the
+    // items get new ones, and the decision and reviews follow their item. The reviews get a
+    // new reviewer ID per reviewer label, nobody can rate in the name of imported reviewers
+    pub fn imported_as(&self, url: &str) -> ComparisonExport {
+        let mut export = self.clone();
+        let comparison = &mut export.comparison;
+        let new_ids: HashMap<String, String> = comparison
+            .items
+            .iter()
+            .map(|item| (item.id.clone(), uuid::Uuid::new_v4().to_string()))
+            .collect();
+        for item in &mut comparison.items {
+            item.id = new_ids[&item.id].clone();
+        }
+        if let Some(decision) = &mut comparison.metadata.decision {
+            match new_ids.get(&decision.item_id) {
+                Some(item_id) => decision.item_id = item_id.clone(),
+                None => comparison.metadata.decision = None,
+            }
+        }
+        comparison.url = url.to_string();
+        let mut reviewers: HashMap<String, String> = HashMap::new();
+        export.reviews.retain(|review| new_ids.contains_key(&review.item_id));
+        for review in &mut export.reviews {
+            review.item_id = new_ids[&review.item_id].clone();
+            review.reviewer = reviewers
+                .entry(review.reviewer.clone())
+                .or_insert_with(|| uuid::Uuid::new_v4().to_string())
+                .clone();
+        }
+        export
+    }
+}
@@ -0,0 +1,66 @@
+/// Summary of a Wikidata entity built from the values the comparisons on this
+/// instance hold for it, served at `/api/public/items/{wikidata_id}/card` for
+/// other sites to embed.
+use serde::{Deserialize, Serialize};
+
+/// Most properties shown on a card, the ones most comparisons agree on.
+pub const MAX_CARD_SPECS: usize = 12;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct SpecCard {
+    pub wikidata_id: String,
+    pub name: String,
+    pub description: String,
+    // Comparisons the entity is part of
+    pub comparisons: usize,
+    pub specs: Vec<Spec>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct Spec {
+    pub property: String,
+    // Wikidata label of the property when known, otherwise the property itself
+    pub label: String,
+    // Most common value across the comparisons, long values cut to a preview
+    pub value: String,
+    // Comparisons holding this value
+    pub comparisons: usize,
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+impl SpecCard {
+    /// Unstyled HTML fragment of the card, crediting the instance at `source`.
+    pub fn to_html(&self, source: &str) -> String {
+        let mut html = format!(
+            "<div class=\"compareware-card\" data-wikidata-id=\"{}\">\n<h3>{}</h3>\n",
+            escape_html(&self.wikidata_id),
+            escape_html(&self.name)
+        );
+        if !self.description.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", escape_html(&self.description)));
+        }
+        if !self.specs.is_empty() {
+            html.push_str("<dl>\n");
+            for spec in &self.specs {
+                html.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", escape_html(&spec.label), escape_html(&spec.value)));
+            }
+            html.push_str("</dl>\n");
+        }
+        html.push_str(&format!(
+            "<p class=\"compareware-card-source\">From <a href=\"{}\">CompareWare</a>, {} {}</p>\n</div>\n",
+            escape_html(source),
+            self.comparisons,
+            if self.comparisons == 1 { "comparison" } else { "comparisons" }
+        ));
+        html
+    }
+}
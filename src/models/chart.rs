@@ -0,0 +1,124 @@
+/// Numbers of a property across items for the chart view and the summaries of a
+/// comparison, and where they go on a bar or radar chart.
+use crate::models::item::Item;
+use crate::models::property::BetterValue;
+use crate::models::quantity::{common_unit, Quantity};
+use std::f64::consts::PI;
+
+// Item plotted on a chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartValue {
+    pub item_id: String,
+    pub label: String,
+    pub value: f64,
+    // Display text of the value, in the unit of the chart
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ChartData {
+    // Unit entity and symbol of the unit all values are in, None for plain numbers
+    pub unit_id: Option<String>,
+    pub unit: Option<String>,
+    pub values: Vec<ChartValue>,
+}
+
+impl ChartData {
+    // Largest value, which the bars and radar spokes are scaled to
+    pub fn max(&self) -> f64 {
+        self.values.iter().map(|value| value.value).fold(0.0, f64::max)
+    }
+
+    // Smallest, largest and average value, None without values
+    pub fn summary(&self) -> Option<ValueSummary> {
+        let first = self.values.first()?.value;
+        let (min, max, sum) = self.values.iter().fold((first, first, 0.0), |(min, max, sum), value| {
+            (min.min(value.value), max.max(value.value), sum + value.value)
+        });
+        let text = |amount: f64| Quantity { amount, unit_id: self.unit_id.clone(), unit: self.unit.clone(), precision: None }.to_text();
+        Some(ValueSummary { min: text(min), max: text(max), average: text(sum / self.values.len() as f64) })
+    }
+
+    // Items with the best value, ties included. None stand out when all values are the same
+    pub fn best(&self, better: BetterValue) -> Vec<String> {
+        let amounts = || self.values.iter().map(|value| value.value);
+        let (Some(min), Some(max)) = (amounts().reduce(f64::min), amounts().reduce(f64::max)) else {
+            return Vec::new();
+        };
+        if min == max {
+            return Vec::new();
+        }
+        let best = match better {
+            BetterValue::Higher => max,
+            BetterValue::Lower => min,
+        };
+        self.values.iter().filter(|value| value.value == best).map(|value| value.item_id.clone()).collect()
+    }
+
+    // Share of the largest value a value reaches, between 0 and 1. Negative values
+    // aren't drawn
+    pub fn fraction(&self, value: f64) -> f64 {
+        let max = self.max();
+        if max > 0.0 {
+            (value / max).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+// Display texts of the summary of a numeric property, in the unit of its chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueSummary {
+    pub min: String,
+    pub max: String,
+    pub average: String,
+}
+
+// Number a value typed in without a quantity starts with, "16" of "16 GB"
+fn plain_number(text: &str) -> Option<f64> {
+    text.split_whitespace().next()?.parse().ok().filter(|number: &f64| number.is_finite())
+}
+
+// Values of `property` that can be plotted: quantities converted to the unit most items
+// use, or plain numbers when no item has a unit. Items without such a value are left out
+pub fn chart_data(items: &[Item], property: &str) -> ChartData {
+    let unit_id = common_unit(items.iter().filter_map(|item| item.quantities.get(property)));
+    let unit = unit_id.as_ref().and_then(|unit_id| {
+        items
+            .iter()
+            .filter_map(|item| item.quantities.get(property))
+            .find(|quantity| quantity.unit_id.as_ref() == Some(unit_id))
+            .and_then(Quantity::unit_symbol)
+    });
+    let values = items
+        .iter()
+        .filter(|item| !item.name.is_empty())
+        .filter_map(|item| {
+            let quantity = match (item.quantities.get(property), &unit_id) {
+                (Some(quantity), Some(unit_id)) => quantity.convert_to(unit_id)?,
+                (Some(quantity), None) => quantity.clone(),
+                (None, Some(_)) => return None,
+                (None, None) => {
+                    let value = item.custom_properties.get(property)?;
+                    let amount = plain_number(value)?;
+                    return Some(ChartValue { item_id: item.id.clone(), label: item.name.clone(), value: amount, text: value.trim().to_string() });
+                }
+            };
+            Some(ChartValue { item_id: item.id.clone(), label: item.name.clone(), value: quantity.amount, text: quantity.to_text() })
+        })
+        .collect();
+    ChartData { unit_id, unit, values }
+}
+
+// Properties with a number for at least one item, the ones a chart can be drawn of
+pub fn numeric_properties(items: &[Item], properties: &[String]) -> Vec<String> {
+    properties.iter().filter(|property| !chart_data(items, property).values.is_empty()).cloned().collect()
+}
+
+// Point of spoke `index` out of `count` on a radar chart, `fraction` of `radius` away from
+// the center. The first spoke points up, the others follow clockwise
+pub fn radar_point(index: usize, count: usize, fraction: f64, radius: f64) -> (f64, f64) {
+    let angle = 2.0 * PI * index as f64 / count.max(1) as f64 - PI / 2.0;
+    (fraction * radius * angle.cos(), fraction * radius * angle.sin())
+}
@@ -0,0 +1,168 @@
+/// Geographic coordinates held as text in property cells, and where they and the
+/// OpenStreetMap tiles around them go on a map of a given size.
+use std::f64::consts::PI;
+
+// Properties holding a globe coordinate, whose values are shown on a map even when
+// they are whole degrees
+pub const COORDINATE_PROPERTIES: &[&str] = &[
+    "P625",  // coordinate location
+    "P1332", // coordinates of northernmost point
+    "P1333", // coordinates of southernmost point
+    "P1334", // coordinates of easternmost point
+    "P1335", // coordinates of westernmost point
+    "P5140", // coordinates of geographic center
+];
+
+// Side in pixels of an OpenStreetMap tile
+pub const TILE_SIZE: f64 = 256.0;
+// Closest zoom of the standard tile layer
+pub const MAX_ZOOM: u8 = 19;
+// Latitudes beyond this don't fit on Web Mercator maps
+const MAX_LATITUDE: f64 = 85.051_128_78;
+// Pixels kept free around the markers of a map
+const MAP_PADDING: f64 = 16.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinate {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+// Item shown on the map of a property
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapPoint {
+    pub label: String,
+    pub coordinate: Coordinate,
+}
+
+// Decimal places from which a pair of numbers outside coordinate properties is taken
+// for a coordinate, as copied from a map, rather than for a list of measurements
+const COORDINATE_DECIMALS: usize = 3;
+
+// Degrees of one half of a coordinate, "52.52", "-13.4" or "52.52°N", and its decimal
+// places. The hemisphere letter tells which half it is, if there is one
+fn degrees(text: &str) -> Option<(f64, usize, Option<char>)> {
+    let text = text.trim();
+    let (number, hemisphere) = match text.chars().last()?.to_ascii_uppercase() {
+        letter @ ('N' | 'S' | 'E' | 'W') => (&text[..text.len() - 1], Some(letter)),
+        _ => (text, None),
+    };
+    let number = number.trim().trim_end_matches('°').trim();
+    let degrees: f64 = number.parse().ok()?;
+    let decimals = number.split_once('.').map_or(0, |(_, decimals)| decimals.len());
+    let signed = match hemisphere {
+        Some('S' | 'W') if degrees >= 0.0 => -degrees,
+        Some(_) if degrees < 0.0 => return None,
+        _ => degrees,
+    };
+    Some((signed, decimals, hemisphere))
+}
+
+impl Coordinate {
+    // Coordinate written as "latitude, longitude", the way Wikidata's globe coordinates
+    // are shown, or with hemisphere letters as in "52.52°N, 13.40°E". Outside
+    // `coordinate_property`, plain pairs of numbers such as "13.3, 15.6" are only read as
+    // coordinates with a few decimal places
+    pub fn parse(value: &str, coordinate_property: bool) -> Option<Coordinate> {
+        let (latitude, longitude) = value.split_once(',')?;
+        let (latitude, latitude_decimals, north_south) = degrees(latitude)?;
+        let (longitude, longitude_decimals, east_west) = degrees(longitude)?;
+        if !matches!(north_south, None | Some('N' | 'S')) || !matches!(east_west, None | Some('E' | 'W')) {
+            return None;
+        }
+        let precise = latitude_decimals.min(longitude_decimals) >= COORDINATE_DECIMALS;
+        let coordinate = Coordinate { latitude, longitude };
+        let looks_like_one = coordinate_property || north_south.is_some() || east_west.is_some() || precise;
+        (coordinate.is_valid() && looks_like_one).then_some(coordinate)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.latitude.abs() <= 90.0 && self.longitude.abs() <= 180.0
+    }
+
+    // Position in pixels on the whole Web Mercator map at `zoom`
+    fn pixel(&self, zoom: u8) -> (f64, f64) {
+        let size = TILE_SIZE * f64::from(1u32 << zoom);
+        let latitude = self.latitude.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+        let x = (self.longitude + 180.0) / 360.0 * size;
+        let y = (1.0 - (latitude.tan() + 1.0 / latitude.cos()).ln() / PI) / 2.0 * size;
+        (x, y)
+    }
+
+    // Page of this place on openstreetmap.org
+    pub fn osm_url(&self) -> String {
+        format!(
+            "https://www.openstreetmap.org/?mlat={}&mlon={}#map=15/{}/{}",
+            self.latitude, self.longitude, self.latitude, self.longitude
+        )
+    }
+}
+
+// Tile of the standard OpenStreetMap layer placed on a map
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapTile {
+    pub zoom: u8,
+    pub x: u32,
+    pub y: u32,
+    // Pixels from the top left corner of the map
+    pub left: f64,
+    pub top: f64,
+}
+
+impl MapTile {
+    pub fn url(&self) -> String {
+        format!("https://tile.openstreetmap.org/{}/{}/{}.png", self.zoom, self.x, self.y)
+    }
+}
+
+// Tiles and marker positions of a map showing some coordinates
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MapLayout {
+    pub zoom: u8,
+    pub tiles: Vec<MapTile>,
+    // Pixels from the top left corner of the map, in the order of the coordinates
+    pub markers: Vec<(f64, f64)>,
+}
+
+// Map of `width` by `height` pixels showing all `coordinates` at the closest zoom, up to
+// `max_zoom`, they fit at
+pub fn map_layout(coordinates: &[Coordinate], width: f64, height: f64, max_zoom: u8) -> MapLayout {
+    if coordinates.is_empty() {
+        return MapLayout::default();
+    }
+    let bounds = |zoom: u8| {
+        let pixels: Vec<(f64, f64)> = coordinates.iter().map(|coordinate| coordinate.pixel(zoom)).collect();
+        let (mut min, mut max) = (pixels[0], pixels[0]);
+        for (x, y) in &pixels[1..] {
+            min = (min.0.min(*x), min.1.min(*y));
+            max = (max.0.max(*x), max.1.max(*y));
+        }
+        (pixels, min, max)
+    };
+    let zoom = (0..=max_zoom.min(MAX_ZOOM))
+        .rev()
+        .find(|zoom| {
+            let (_, min, max) = bounds(*zoom);
+            max.0 - min.0 <= width - 2.0 * MAP_PADDING && max.1 - min.1 <= height - 2.0 * MAP_PADDING
+        })
+        .unwrap_or(0);
+    let (pixels, min, max) = bounds(zoom);
+    let origin = ((min.0 + max.0 - width) / 2.0, (min.1 + max.1 - height) / 2.0);
+    let tile_count = 1i64 << zoom;
+    let tile_range = |from: f64, length: f64| (from / TILE_SIZE).floor() as i64..=((from + length) / TILE_SIZE).floor() as i64;
+    let mut tiles = Vec::new();
+    for y in tile_range(origin.1, height).filter(|y| (0..tile_count).contains(y)) {
+        for x in tile_range(origin.0, width) {
+            tiles.push(MapTile {
+                zoom,
+                // Maps wider than the world repeat it
+                x: x.rem_euclid(tile_count) as u32,
+                y: y as u32,
+                left: x as f64 * TILE_SIZE - origin.0,
+                top: y as f64 * TILE_SIZE - origin.1,
+            });
+        }
+    }
+    let markers = pixels.into_iter().map(|(x, y)| (x - origin.0, y - origin.1)).collect();
+    MapLayout { zoom, tiles, markers }
+}
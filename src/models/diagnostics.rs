@@ -0,0 +1,129 @@
+/// Pieces of the diagnostics bundle admins download from /api/admin/diagnostics
+/// to attach to bug reports: errors the browser reported and the audit trail of
+/// admin actions. Also the report of the heaviest comparisons of the instance.
+use serde::{Deserialize, Serialize};
+
+// Longest error message kept from a browser, longer ones are cut
+pub const MAX_CLIENT_ERROR_LENGTH: usize = 1000;
+// Comparisons in the heaviest comparisons report unless the request asks for another number
+pub const HEAVIEST_COMPARISONS: usize = 20;
+// Items from which a comparison is better paged
+const PAGING_ITEMS: usize = 50;
+// Rejected and discontinued items from which they are better moved to the trash
+const ARCHIVABLE_ITEMS: usize = 10;
+// Average response size and time from which a comparison is slow to load
+const HEAVY_RESPONSE_BYTES: u64 = 1024 * 1024;
+const SLOW_RESPONSE_MS: f64 = 500.0;
+// Share of the requests missing the Wikidata cache from which its lifetime is too short
+const CACHE_MISS_RATE: f64 = 0.2;
+
+// Error shown to a visitor, reported by the browser to /api/diagnostics/errors
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ClientErrorReport {
+    pub message: String,
+    // Path of the page that showed the error
+    #[serde(default)]
+    pub page: String,
+}
+
+impl ClientErrorReport {
+    // Report with surrounding whitespace removed, line breaks and other control characters
+    // turned into spaces so a report stays one line of the bundle, and both fields cut to
+    // the maximum length
+    pub fn trimmed(&self) -> ClientErrorReport {
+        let cut = |text: &str| {
+            text.trim()
+                .chars()
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .take(MAX_CLIENT_ERROR_LENGTH)
+                .collect()
+        };
+        ClientErrorReport { message: cut(&self.message), page: cut(&self.page) }
+    }
+}
+
+// Admin action recorded in the audit trail
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub created_at: i64,
+    // e.g. "settings" or "restore"
+    pub action: String,
+    pub detail: String,
+}
+
+/// Rows a comparison holds in the database.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ComparisonRows {
+    pub url: String,
+    // Items outside the trash
+    pub items: usize,
+    // Of those, the rejected and discontinued ones
+    pub inactive_items: usize,
+    pub trashed_items: usize,
+    pub properties: usize,
+    // Property values of the items outside the trash, names and descriptions included
+    pub values: usize,
+    pub revisions: usize,
+}
+
+/// Requests to the API of a comparison since the server started.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ComparisonRequests {
+    pub requests: u64,
+    pub total_ms: u64,
+    pub slowest_ms: u64,
+    // Size of the response bodies
+    pub total_bytes: u64,
+    // Wikidata labels, claims and suggestions the server had no unexpired copy of
+    pub wikidata_cache_misses: u64,
+}
+
+impl ComparisonRequests {
+    pub fn average_ms(&self) -> f64 {
+        if self.requests == 0 { 0.0 } else { self.total_ms as f64 / self.requests as f64 }
+    }
+
+    pub fn average_bytes(&self) -> u64 {
+        self.total_bytes.checked_div(self.requests).unwrap_or_default()
+    }
+}
+
+/// Comparison of the heaviest comparisons report at /api/admin/reports/heaviest-comparisons,
+/// with what would make it lighter.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ComparisonLoad {
+    pub rows: ComparisonRows,
+    pub requests: ComparisonRequests,
+    pub average_ms: f64,
+    pub average_bytes: u64,
+    pub remediations: Vec<String>,
+}
+
+impl ComparisonLoad {
+    pub fn new(rows: ComparisonRows, requests: ComparisonRequests) -> Self {
+        let (average_ms, average_bytes) = (requests.average_ms(), requests.average_bytes());
+        let mut remediations = Vec::new();
+        if rows.items >= PAGING_ITEMS {
+            remediations.push(format!("Page the {} item columns with \"Items per page\" above the grid", rows.items));
+        }
+        if rows.inactive_items >= ARCHIVABLE_ITEMS {
+            remediations.push(format!("Archive the {} rejected and discontinued items by moving them to the trash", rows.inactive_items));
+        }
+        if average_bytes >= HEAVY_RESPONSE_BYTES || average_ms >= SLOW_RESPONSE_MS {
+            remediations.push("Split the comparison, or shorten its long values, to make its responses lighter".to_string());
+        }
+        if requests.requests > 0 && requests.wikidata_cache_misses as f64 / requests.requests as f64 >= CACHE_MISS_RATE {
+            remediations.push("Raise wikidata.cache_ttl_secs, the Wikidata cache misses often for this comparison".to_string());
+        }
+        ComparisonLoad { rows, requests, average_ms, average_bytes, remediations }
+    }
+
+    // Time spent answering the comparison's requests, then its values, heaviest first
+    pub fn weight(&self) -> (u64, usize) {
+        (self.requests.total_ms, self.rows.values)
+    }
+}
@@ -0,0 +1,77 @@
+/// Title and category under which a comparison is listed in the public directory.
+use serde::{Deserialize, Serialize};
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ComparisonListing {
+    pub title: String,
+    #[serde(default)]
+    pub category: String,
+}
+
+/// Announcement of a public comparison, the content of its directory event on Nostr.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ComparisonAnnouncement {
+    pub url: String,
+    // Path of the comparison on its instance
+    pub slug: String,
+    pub title: String,
+    pub category: String,
+    pub item_count: usize,
+}
+
+/// Announcement received from a relay, with the instance key that signed it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct DirectoryEntry {
+    pub announcement: ComparisonAnnouncement,
+    pub author: String,
+    pub created_at: i64,
+}
+
+/// Comparisons shown per page of the index.
+pub const COMPARISONS_PER_PAGE: usize = 20;
+
+/// Comparison stored on this instance, as shown on the index page.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ComparisonSummary {
+    pub url: String,
+    // Title set by the editors, the directory title, or the path of the URL
+    pub title: String,
+    pub item_count: usize,
+    // Unix time of the last change to its items or properties
+    pub updated_at: i64,
+    // Unix time the comparison was decided, if it was
+    #[serde(default)]
+    pub decided_at: Option<i64>,
+}
+
+/// One page of the comparison index.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ComparisonIndex {
+    pub comparisons: Vec<ComparisonSummary>,
+    // Comparisons matching the search, on all pages
+    pub total: usize,
+    pub page: usize,
+    pub per_page: usize,
+}
+
+impl ComparisonIndex {
+    pub fn page_count(&self) -> usize {
+        self.total.div_ceil(self.per_page.max(1)).max(1)
+    }
+}
+
+/// Title of a comparison without one of its own: its path, or its host for the root page.
+pub fn default_title(url: &str) -> String {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let (host, path) = without_scheme.split_once('/').unwrap_or((without_scheme, ""));
+    let path = path.trim_end_matches('/');
+    if path.is_empty() {
+        host.to_string()
+    } else {
+        format!("/{}", path)
+    }
+}
@@ -0,0 +1,11 @@
+/// JSON body returned by the API when a request fails.
+/// `code` is a stable machine-readable identifier, `message` is meant for humans.
+use serde::{Deserialize, Serialize};
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ErrorResponse {
+    pub code: String,
+    pub message: String,
+    #[cfg_attr(feature = "ssr", schema(value_type = Option<Object>))]
+    pub details: Option<serde_json::Value>,
+}
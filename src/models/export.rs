@@ -0,0 +1,72 @@
+/// Format a comparison can be exported as, one of the exporters registered on the server.
+use serde::{Deserialize, Serialize};
+
+// Lifetime of signed links unless the request picks another one, and the longest allowed
+pub const DEFAULT_LINK_LIFETIME_SECS: i64 = 7 * 24 * 60 * 60;
+pub const MAX_LINK_LIFETIME_SECS: i64 = 90 * 24 * 60 * 60;
+const MIN_LINK_LIFETIME_SECS: i64 = 60;
+// Format of embeds, the only one browsers show in a frame
+pub const EMBED_FORMAT: &str = "html";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ExportFormat {
+    // Name in the export URL, /api/urls/{url}/export.{name}, and extension of the file
+    pub name: String,
+    // Shown in the export menu
+    pub label: String,
+    pub content_type: String,
+    // Whether it's a table of the items, which can keep the values quantities were entered as
+    pub table: bool,
+}
+
+impl ExportFormat {
+    pub fn file_name(&self) -> String {
+        format!("compareware-comparison.{}", self.name)
+    }
+}
+
+/// Link to an export of a comparison to sign, see POST /api/urls/{url}/signed-links.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct SignedLinkRequest {
+    // Name of the export format
+    pub format: String,
+    // Served to show in a page rather than to download, html only
+    #[serde(default)]
+    pub embed: bool,
+    // Seconds until the link expires, DEFAULT_LINK_LIFETIME_SECS when missing
+    #[serde(default)]
+    pub expires_in_secs: Option<i64>,
+}
+
+impl SignedLinkRequest {
+    pub fn lifetime_secs(&self) -> i64 {
+        self.expires_in_secs.unwrap_or(DEFAULT_LINK_LIFETIME_SECS)
+    }
+
+    // Why the link can't be signed, if it can't. Formats are checked by the server
+    pub fn problem(&self) -> Option<String> {
+        if !(MIN_LINK_LIFETIME_SECS..=MAX_LINK_LIFETIME_SECS).contains(&self.lifetime_secs()) {
+            Some(format!(
+                "Links must expire between {} seconds and {} days from now",
+                MIN_LINK_LIFETIME_SECS,
+                MAX_LINK_LIFETIME_SECS / (24 * 60 * 60)
+            ))
+        } else if self.embed && self.format != EMBED_FORMAT {
+            Some(format!("Embeds are {} pages", EMBED_FORMAT))
+        } else {
+            None
+        }
+    }
+}
+
+/// Export link signed by the server, opening the export until it expires, even of a
+/// protected comparison on a private instance.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct SignedLink {
+    // Path and query of the link on this instance, e.g. "/api/urls/…/export.csv?expires=…&signature=…"
+    pub path: String,
+    pub expires_at: i64,
+}
@@ -0,0 +1,125 @@
+/// Edit history of a comparison: every change to its items and properties, with
+/// who made it and when, listed by `GET /api/urls/{url}/history`. Saved items
+/// are kept in full, so any earlier version of an item can be saved again.
+use crate::models::item::Item;
+use crate::models::review::{atom_time, escape_xml};
+use serde::{Deserialize, Serialize};
+
+/// Revisions returned per page of the history, older ones are fetched with `before`.
+pub const HISTORY_PAGE_SIZE: usize = 50;
+
+/// Actor of changes made without an edit token, to comparisons nobody protected.
+pub const ANONYMOUS_ACTOR: &str = "anonymous";
+
+/// Actor of the changes merged from a mirrored comparison.
+pub const MIRROR_ACTOR: &str = "mirror";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "action", rename_all = "snake_case")]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum HistoryChange {
+    /// Item created or edited, with all its values after the change.
+    ItemSaved { item: Item },
+    /// Item moved to the trash.
+    ItemDeleted { item_id: String },
+    /// Item taken out of the trash, with the values it came back with.
+    ItemRestored { item: Item },
+    PropertyAdded { property: String },
+    PropertyDeleted { property: String },
+    /// Custom property renamed, its values moved to the new name.
+    PropertyRenamed { property: String, rename_to: String },
+    /// Deleted property brought back by undoing its deletion.
+    PropertyRestored { property: String },
+}
+
+impl HistoryChange {
+    /// Item the change is about, if any.
+    pub fn item_id(&self) -> Option<&str> {
+        match self {
+            HistoryChange::ItemSaved { item } | HistoryChange::ItemRestored { item } => Some(&item.id),
+            HistoryChange::ItemDeleted { item_id } => Some(item_id),
+            _ => None,
+        }
+    }
+
+    /// Property the change is about, if any.
+    pub fn property(&self) -> Option<&str> {
+        match self {
+            HistoryChange::PropertyAdded { property }
+            | HistoryChange::PropertyDeleted { property }
+            | HistoryChange::PropertyRenamed { property, .. }
+            | HistoryChange::PropertyRestored { property } => Some(property),
+            _ => None,
+        }
+    }
+
+    /// One line telling what changed, e.g. "Saved Laptop" or "Added property P2067".
+    pub fn summary(&self) -> String {
+        let name = |item: &Item| if item.name.is_empty() { "an unnamed item".to_string() } else { item.name.clone() };
+        match self {
+            HistoryChange::ItemSaved { item } => format!("Saved {}", name(item)),
+            HistoryChange::ItemDeleted { item_id } => format!("Deleted item {}", item_id),
+            HistoryChange::ItemRestored { item } => format!("Restored {}", name(item)),
+            HistoryChange::PropertyAdded { property } => format!("Added property {}", property),
+            HistoryChange::PropertyDeleted { property } => format!("Deleted property {}", property),
+            HistoryChange::PropertyRenamed { property, rename_to } => format!("Renamed property {} to {}", property, rename_to),
+            HistoryChange::PropertyRestored { property } => format!("Restored property {}", property),
+        }
+    }
+
+    /// Version of an item this change left, which restoring saves again.
+    pub fn item(&self) -> Option<&Item> {
+        match self {
+            HistoryChange::ItemSaved { item } | HistoryChange::ItemRestored { item } => Some(item),
+            _ => None,
+        }
+    }
+}
+
+/// One entry of the edit history.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct Revision {
+    /// Increasing with every change, pass it as `before` to get older revisions.
+    pub id: i64,
+    /// "owner", "collaborator" followed by the start of the hash of their token,
+    /// "anonymous" for edits of unprotected comparisons, or "mirror".
+    pub actor: String,
+    /// Unix timestamp in seconds.
+    pub created_at: i64,
+    pub change: HistoryChange,
+}
+
+/// Atom feed of a page of changes to a comparison, newest first, for watchers to follow it in
+/// a feed reader. Entries of item changes link to the item's column. `feed_url` is the
+/// feed's own address and `now` its update time when nothing changed yet.
+pub fn history_atom(comparison_url: &str, title: &str, feed_url: &str, revisions: &[Revision], now: i64) -> String {
+    let updated = revisions.iter().map(|revision| revision.created_at).max().unwrap_or(now);
+    let mut atom = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n<id>{feed}</id>\n<title>Changes to {}</title>\n<link rel=\"self\" href=\"{feed}\"/>\n<link href=\"{}\"/>\n<updated>{}</updated>\n<author><name>CompareWare</name></author>\n",
+        escape_xml(title),
+        escape_xml(comparison_url),
+        atom_time(updated),
+        feed = escape_xml(feed_url),
+    );
+    for revision in revisions {
+        let summary = revision.change.summary();
+        let link = match revision.change.item_id() {
+            Some(item_id) => format!("{}#item-{}", comparison_url, item_id),
+            None => comparison_url.to_string(),
+        };
+        atom.push_str(&format!(
+            "<entry>\n<id>{}#{}</id>\n<title>{}</title>\n<link href=\"{}\"/>\n<updated>{}</updated>\n<author><name>{}</name></author>\n<content type=\"text\">{} by {}</content>\n</entry>\n",
+            escape_xml(feed_url),
+            revision.id,
+            escape_xml(&summary),
+            escape_xml(&link),
+            atom_time(revision.created_at),
+            escape_xml(&revision.actor),
+            escape_xml(&summary),
+            escape_xml(&revision.actor),
+        ));
+    }
+    atom.push_str("</feed>\n");
+    atom
+}
@@ -0,0 +1,112 @@
+/// Options of the whole instance that admins change at runtime through
+/// /api/admin/settings, without restarting the server. Until they are first
+/// saved, the rate limits come from the environment and everything else from
+/// the defaults below.
+use crate::models::knowledge::KnowledgeSourceKind;
+use crate::models::property::lang_problem;
+use serde::{Deserialize, Serialize};
+
+// Language of Wikidata searches and imports unless the admin picks another one
+pub const DEFAULT_WIKIDATA_LANGUAGE: &str = "en";
+// Highest Wikidata requests per minute an admin can allow
+pub const MAX_REQUESTS_PER_MINUTE: u32 = 10_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct InstanceSettings {
+    // Upstream Wikidata requests per minute for one client and for the whole instance
+    pub wikidata_client_rpm: u32,
+    pub wikidata_global_rpm: u32,
+    // Language tag of Wikidata searches and imports, e.g. "en" or "de"
+    pub wikidata_language: String,
+    // Private instances hide their comparisons index from everyone but admins and
+    // don't announce comparisons in the Nostr directory
+    #[serde(default)]
+    pub private: bool,
+    #[serde(default)]
+    pub features: InstanceFeatures,
+}
+
+/// Features an admin can turn off for the whole instance. Requests using a
+/// feature that is turned off are refused with 403.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct InstanceFeatures {
+    // Creating items from Wikidata queries
+    pub imports: bool,
+    // Freezing comparisons into read-only snapshots
+    pub snapshots: bool,
+    // Lightning tips for the creators of items
+    pub tips: bool,
+    // Ratings and reviews of items
+    pub reviews: bool,
+}
+
+impl Default for InstanceFeatures {
+    fn default() -> Self {
+        InstanceFeatures { imports: true, snapshots: true, tips: true, reviews: true }
+    }
+}
+
+/// What every visitor may know of the instance settings, served at /api/instance
+/// so the pages can use the Wikidata language and hide turned off features.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct InstanceInfo {
+    pub wikidata_language: String,
+    pub private: bool,
+    pub features: InstanceFeatures,
+    // Set when the server answers the pages' wikidata.org requests from recorded
+    // fixtures, at /api/wikidata/fixtures
+    #[serde(default)]
+    pub wikidata_fixtures: bool,
+    // Knowledge base of the name typeahead, set in the server's configuration
+    #[serde(default)]
+    pub knowledge_source: KnowledgeSourceKind,
+}
+
+impl Default for InstanceInfo {
+    fn default() -> Self {
+        InstanceInfo {
+            wikidata_language: DEFAULT_WIKIDATA_LANGUAGE.to_string(),
+            private: false,
+            features: InstanceFeatures::default(),
+            wikidata_fixtures: false,
+            knowledge_source: KnowledgeSourceKind::default(),
+        }
+    }
+}
+
+impl InstanceSettings {
+    // Language tag without surrounding whitespace, the default one when blank
+    pub fn trimmed(&self) -> Self {
+        let wikidata_language = match self.wikidata_language.trim() {
+            "" => DEFAULT_WIKIDATA_LANGUAGE.to_string(),
+            lang => lang.to_string(),
+        };
+        InstanceSettings { wikidata_language, ..self.clone() }
+    }
+
+    // Rate limits out of range or inconsistent, or an invalid Wikidata language
+    pub fn problem(&self) -> Option<String> {
+        let limits = [self.wikidata_client_rpm, self.wikidata_global_rpm];
+        if limits.iter().any(|limit| *limit == 0 || *limit > MAX_REQUESTS_PER_MINUTE) {
+            Some(format!("Wikidata rate limits must be between 1 and {} requests per minute", MAX_REQUESTS_PER_MINUTE))
+        } else if self.wikidata_client_rpm > self.wikidata_global_rpm {
+            Some("The rate limit of one client can't exceed the limit of the whole instance".to_string())
+        } else {
+            lang_problem(&self.wikidata_language)
+        }
+    }
+
+    pub fn info(&self) -> InstanceInfo {
+        InstanceInfo {
+            wikidata_language: self.wikidata_language.clone(),
+            private: self.private,
+            features: self.features.clone(),
+            wikidata_fixtures: false,
+            knowledge_source: KnowledgeSourceKind::default(),
+        }
+    }
+}
@@ -1,12 +1,258 @@
 /// Represents an Item in CompareWare.
 /// Each item has metadata and key-value tags for categorization.
+use crate::models::metadata::ComparisonMetadata;
+use crate::models::property::{PropertyOverride, PropertySettings};
+use crate::models::quantity::Quantity;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-#[derive(Serialize, Deserialize, Debug, Clone)]
+use std::collections::{HashMap, HashSet};
+
+/// Longest value, in characters, accepted for a single cell.
+pub const MAX_VALUE_LENGTH: usize = 20_000;
+
+/// Property values longer than this many characters are kept in full in a separate
+/// table and sent to the grid as a preview, expanded on demand.
+pub const PREVIEW_LENGTH: usize = 300;
+
+/// Deleted items listed in the trash of a comparison, the most recent ones.
+pub const MAX_DELETED_ITEMS: usize = 50;
+
+/// Properties per item ID whose value was cut down to a preview.
+pub type TruncatedValues = HashMap<String, HashSet<String>>;
+
+// Ends the preview of a long value
+const PREVIEW_ELLIPSIS: char = '…';
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
 pub struct Item {
     pub id: String,
     pub name: String,
     pub description: String,
     pub wikidata_id: Option<String>,
     pub custom_properties: HashMap<String, String>,
+    // Amount and unit of the custom properties holding Wikidata quantities
+    #[serde(default)]
+    pub quantities: HashMap<String, Quantity>,
+    // Shared header for variant columns, e.g. "iPhone 15" over "128GB" and "256GB"
+    #[serde(default)]
+    pub group: Option<String>,
+    // Lightning address or Nostr public key (npub) of the creator, for tips
+    #[serde(default)]
+    pub tip_recipient: Option<String>,
+    // Where the item stands in the decision, None until someone sets it
+    #[serde(default)]
+    pub status: Option<ItemStatus>,
+    // Where the custom properties not typed in by hand got their value from. Saving a
+    // changed value without a source here marks it as typed in
+    #[serde(default)]
+    pub sources: HashMap<String, ValueSource>,
+    // Version of the item, the time of its last save in milliseconds since the Unix epoch.
+    // A save sending an older version is rejected, None saves without checking
+    #[serde(default)]
+    pub updated_at: Option<i64>,
+}
+
+impl Item {
+    // Mark the values of an imported item that don't come from Wikidata as imported
+    pub fn mark_imported(&mut self) {
+        for (property, value) in &self.custom_properties {
+            if !value.is_empty() && self.sources.get(property) != Some(&ValueSource::Wikidata) {
+                self.sources.insert(property.clone(), ValueSource::Import);
+            }
+        }
+    }
+}
+
+/// Lifecycle state of an item in the decision workflow.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    Considering,
+    Shortlisted,
+    Rejected,
+    Purchased,
+    Discontinued,
+}
+
+impl ItemStatus {
+    pub const ALL: [ItemStatus; 5] = [
+        ItemStatus::Considering,
+        ItemStatus::Shortlisted,
+        ItemStatus::Rejected,
+        ItemStatus::Purchased,
+        ItemStatus::Discontinued,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemStatus::Considering => "considering",
+            ItemStatus::Shortlisted => "shortlisted",
+            ItemStatus::Rejected => "rejected",
+            ItemStatus::Purchased => "purchased",
+            ItemStatus::Discontinued => "discontinued",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ItemStatus::Considering => "Considering",
+            ItemStatus::Shortlisted => "Shortlisted",
+            ItemStatus::Rejected => "Rejected",
+            ItemStatus::Purchased => "Purchased",
+            ItemStatus::Discontinued => "Discontinued",
+        }
+    }
+
+    // None for unknown names, including the empty one of items without a status
+    pub fn from_name(name: &str) -> Option<Self> {
+        ItemStatus::ALL.into_iter().find(|status| status.as_str() == name)
+    }
+}
+
+/// Where the value of a cell came from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ValueSource {
+    // A claim of the item's Wikidata entity, replaced when refreshing from Wikidata
+    Wikidata,
+    // Typed in, kept when refreshing from Wikidata
+    Manual,
+    // Part of an imported comparison
+    Import,
+}
+
+impl ValueSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValueSource::Wikidata => "wikidata",
+            ValueSource::Manual => "manual",
+            ValueSource::Import => "import",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ValueSource::Wikidata => "From Wikidata",
+            ValueSource::Manual => "Typed in",
+            ValueSource::Import => "Imported",
+        }
+    }
+
+    // Values stored before sources were tracked count as typed in
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "wikidata" => ValueSource::Wikidata,
+            "import" => ValueSource::Import,
+            _ => ValueSource::Manual,
+        }
+    }
+}
+
+/// Items whose names, descriptions or values from Wikidata changed in a refresh, and the
+/// values that changed.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct WikidataRefresh {
+    pub items: Vec<Item>,
+    pub changes: Vec<RefreshedValue>,
+}
+
+/// A value replaced in a refresh from Wikidata. The property is `name` or `description`
+/// for the item's own, `previous` is empty for cells that were.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct RefreshedValue {
+    pub item_id: String,
+    pub property: String,
+    pub previous: String,
+    pub value: String,
+}
+
+/// An item together with the comparison URL it belongs to, as saved by the API.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ItemRequest {
+    pub url: String,
+    pub item: Item,
+}
+
+/// Items of a URL together with the display order of its selected properties, the
+/// settings of its properties and its title and description, loaded through a
+/// server function for server-side rendering and hydration.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LoadedItems {
+    pub items: Vec<Item>,
+    pub selected_properties: Vec<String>,
+    #[serde(default)]
+    pub metadata: ComparisonMetadata,
+    #[serde(default)]
+    pub truncated_values: TruncatedValues,
+    // Language and spell-checking of the properties that changed them
+    #[serde(default)]
+    pub property_settings: HashMap<String, PropertySettings>,
+    // Labels and tooltips of the properties that override their defaults
+    #[serde(default)]
+    pub property_overrides: HashMap<String, PropertyOverride>,
+}
+
+/// Full value of a property of an item, for cells loaded as a preview.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct PropertyValue {
+    pub item_id: String,
+    pub property: String,
+    pub value: String,
+}
+
+/// Preview of a value longer than `PREVIEW_LENGTH` characters, `None` for shorter values.
+pub fn value_preview(value: &str) -> Option<String> {
+    let (end, _) = value.char_indices().nth(PREVIEW_LENGTH)?;
+    let mut preview = value[..end].to_string();
+    preview.push(PREVIEW_ELLIPSIS);
+    Some(preview)
+}
+
+/// Request body for importing items from a Wikidata SPARQL query.
+/// `query` is either a full SPARQL query selecting `?item`, or a class such as
+/// "Q22645" or "instances of smartphone".
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ItemQuery {
+    pub query: String,
+}
+
+/// Response of the delete endpoints, with the number of database rows removed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct DeleteSummary {
+    pub rows_affected: usize,
+}
+
+/// Item in the trash of a comparison, restored with
+/// `POST /api/urls/{url}/items/{item_id}/restore`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct DeletedItem {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Unix timestamp in seconds.
+    pub deleted_at: i64,
+}
+
+// Keep only the custom properties that are selected for the URL
+pub fn filter_selected_properties(items: Vec<Item>, selected_properties: &[String]) -> Vec<Item> {
+    items
+        .into_iter()
+        .map(|item| Item {
+            custom_properties: item
+                .custom_properties
+                .into_iter()
+                .filter(|(key, _)| selected_properties.contains(key))
+                .collect(),
+            ..item
+        })
+        .collect()
 }
@@ -0,0 +1,192 @@
+/// Knowledge bases the name typeahead searches and fills custom properties from, picked
+/// for the whole instance by the knowledge_source setting of the configuration. Like
+/// models::wikidata, they only build request URLs and read the answers, the pages send them.
+use crate::models::wikidata::{claim_values, datavalue_text, entities_url, normalized_name, search_url, WikidataSearchResponse, WikidataSuggestion};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+pub const DBPEDIA_LOOKUP: &str = "https://lookup.dbpedia.org/api/search";
+pub const DBPEDIA_RESOURCE: &str = "http://dbpedia.org/resource/";
+const DBPEDIA_DATA: &str = "https://dbpedia.org/data/";
+const DBPEDIA_ONTOLOGY: &str = "http://dbpedia.org/ontology/";
+const OWL_SAME_AS: &str = "http://www.w3.org/2002/07/owl#sameAs";
+const WIKIDATA_ENTITY: &str = "http://www.wikidata.org/entity/";
+// Suggestions shown while typing an item name
+const SEARCH_LIMIT: usize = 5;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum KnowledgeSourceKind {
+    #[default]
+    Wikidata,
+    Dbpedia,
+}
+
+impl FromStr for KnowledgeSourceKind {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "wikidata" => Ok(KnowledgeSourceKind::Wikidata),
+            "dbpedia" => Ok(KnowledgeSourceKind::Dbpedia),
+            _ => Err(()),
+        }
+    }
+}
+
+impl KnowledgeSourceKind {
+    pub fn source(self) -> &'static dyn KnowledgeSource {
+        match self {
+            KnowledgeSourceKind::Wikidata => &Wikidata,
+            KnowledgeSourceKind::Dbpedia => &Dbpedia,
+        }
+    }
+}
+
+/// Suggestions of a knowledge base for an item name, and the facts it knows of the
+/// entity picked from them.
+pub trait KnowledgeSource {
+    // Name shown on the search button and in messages
+    fn name(&self) -> &'static str;
+
+    // Typeahead search for entities labelled `query` in `language`
+    fn search_url(&self, query: &str, language: &str) -> String;
+
+    // Entities of a search_url answer, IDs as facts_url takes them
+    fn suggestions(&self, data: &serde_json::Value) -> Vec<WikidataSuggestion>;
+
+    // Request for everything the knowledge base states about entity `id`
+    fn facts_url(&self, id: &str, language: &str) -> String;
+
+    // Display text of the facts of a facts_url answer, keyed by the name of their property
+    fn facts(&self, id: &str, data: &serde_json::Value, language: &str) -> HashMap<String, String>;
+
+    // Wikidata item the entity of a facts_url answer is, if the knowledge base links it
+    fn wikidata_id(&self, id: &str, data: &serde_json::Value) -> Option<String>;
+}
+
+pub struct Wikidata;
+
+impl KnowledgeSource for Wikidata {
+    fn name(&self) -> &'static str {
+        "Wikidata"
+    }
+
+    fn search_url(&self, query: &str, language: &str) -> String {
+        search_url(query, language)
+    }
+
+    fn suggestions(&self, data: &serde_json::Value) -> Vec<WikidataSuggestion> {
+        serde_json::from_value::<WikidataSearchResponse>(data.clone()).map(|response| response.search).unwrap_or_default()
+    }
+
+    fn facts_url(&self, id: &str, _language: &str) -> String {
+        entities_url(&[id.to_string()], "claims")
+    }
+
+    // Keyed by property ID, items they refer to by their ID
+    fn facts(&self, id: &str, data: &serde_json::Value, _language: &str) -> HashMap<String, String> {
+        let (values, _) = claim_values(&data["entities"][id]["claims"]);
+        values
+            .into_iter()
+            .filter_map(|(property, datavalue)| Some((property, datavalue_text(&datavalue, &HashMap::new())?)))
+            .collect()
+    }
+
+    fn wikidata_id(&self, id: &str, _data: &serde_json::Value) -> Option<String> {
+        Some(id.to_string())
+    }
+}
+
+pub struct Dbpedia;
+
+// Last part of a DBpedia or Wikidata URI, e.g. "Douglas_Adams"
+fn uri_name(uri: &str) -> &str {
+    uri.rsplit(['/', '#']).next().unwrap_or(uri)
+}
+
+impl KnowledgeSource for Dbpedia {
+    fn name(&self) -> &'static str {
+        "DBpedia"
+    }
+
+    // DBpedia Lookup only searches the English labels
+    fn search_url(&self, query: &str, _language: &str) -> String {
+        format!("{}?query={}&format=JSON&maxResults={}", DBPEDIA_LOOKUP, urlencoding::encode(query), SEARCH_LIMIT)
+    }
+
+    fn suggestions(&self, data: &serde_json::Value) -> Vec<WikidataSuggestion> {
+        // Lookup marks the matched words with <B> tags
+        let text = |doc: &serde_json::Value, field: &str| {
+            doc[field][0].as_str().map(|text| text.replace("<B>", "").replace("</B>", ""))
+        };
+        data["docs"]
+            .as_array()
+            .map(|docs| {
+                docs.iter()
+                    .filter_map(|doc| {
+                        let id = uri_name(doc["resource"][0].as_str()?).to_string();
+                        let label = text(doc, "label").unwrap_or_else(|| id.replace('_', " "));
+                        Some(WikidataSuggestion { id, label, description: text(doc, "comment") })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn facts_url(&self, id: &str, _language: &str) -> String {
+        format!("{}{}.json", DBPEDIA_DATA, urlencoding::encode(id))
+    }
+
+    // Properties of the DBpedia ontology keyed by their name, e.g. "releaseDate", literals
+    // in `language` preferred over those in other languages
+    fn facts(&self, id: &str, data: &serde_json::Value, language: &str) -> HashMap<String, String> {
+        let mut facts = HashMap::new();
+        let Some(statements) = data[format!("{}{}", DBPEDIA_RESOURCE, id)].as_object() else {
+            return facts;
+        };
+        for (predicate, objects) in statements {
+            let (Some(property), Some(objects)) = (predicate.strip_prefix(DBPEDIA_ONTOLOGY), objects.as_array()) else {
+                continue;
+            };
+            let object = objects
+                .iter()
+                .find(|object| object["lang"] == language)
+                .or_else(|| objects.iter().find(|object| object["lang"].is_null()))
+                .or_else(|| objects.first());
+            let text = object.and_then(|object| match (&object["type"], &object["value"]) {
+                (serde_json::Value::String(kind), serde_json::Value::String(uri)) if kind == "uri" => {
+                    Some(uri_name(uri).replace('_', " "))
+                }
+                (_, serde_json::Value::String(text)) => Some(text.clone()),
+                (_, serde_json::Value::Number(number)) => Some(number.to_string()),
+                _ => None,
+            });
+            if let Some(text) = text {
+                facts.insert(property.to_string(), text);
+            }
+        }
+        facts
+    }
+
+    fn wikidata_id(&self, id: &str, data: &serde_json::Value) -> Option<String> {
+        data[format!("{}{}", DBPEDIA_RESOURCE, id)][OWL_SAME_AS]
+            .as_array()?
+            .iter()
+            .filter_map(|object| object["value"].as_str()?.strip_prefix(WIKIDATA_ENTITY))
+            .find(|id| id.starts_with('Q'))
+            .map(String::from)
+    }
+}
+
+// Fact of a custom property, matched by name regardless of case, spaces and punctuation,
+// so a "Release date" column is filled from DBpedia's releaseDate
+pub fn fact_for<'a>(facts: &'a HashMap<String, String>, property: &str) -> Option<&'a String> {
+    let property = normalized_name(property);
+    if property.is_empty() {
+        return None;
+    }
+    facts.iter().find(|(name, _)| normalized_name(name) == property).map(|(_, value)| value)
+}
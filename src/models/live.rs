@@ -0,0 +1,67 @@
+/// Changes to a comparison pushed over `/ws/urls/{url}` to the browsers that have
+/// it open, so people editing the same comparison see each other's saves.
+use crate::models::item::{value_preview, Item};
+use crate::models::property::{PropertyColumn, PropertyOverride, PropertySettings};
+use serde::{Deserialize, Serialize};
+
+/// Header carrying the live session of the browser tab making a change. The
+/// change isn't echoed back to the socket opened with the same session.
+pub const LIVE_SESSION_HEADER: &str = "X-Live-Session";
+
+/// Query parameter of the WebSocket URL naming the tab's live session.
+pub const LIVE_SESSION_PARAM: &str = "session";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub enum ComparisonChange {
+    /// Item created or updated. Long values are cut down to a preview, as when
+    /// the comparison is loaded, and `truncated` lists their properties.
+    ItemSaved {
+        item: Item,
+        #[serde(default)]
+        truncated: Vec<String>,
+    },
+    ItemDeleted { item_id: String },
+    /// Property selected, with the values it has.
+    PropertyAdded { column: PropertyColumn },
+    PropertyDeleted { property: String },
+    /// Custom property renamed, with its values now under the new name.
+    PropertyRenamed { property: String, rename_to: String },
+    /// Selected properties in their new display order.
+    PropertiesReordered { properties: Vec<String> },
+    /// Language or spell-checking of a property's cells changed.
+    PropertySettingsChanged { property: String, settings: PropertySettings },
+    /// Label or tooltip of a property changed, an empty override restores the defaults.
+    PropertyOverrideChanged { property: String, overrides: PropertyOverride },
+    /// Changes were missed, the comparison has to be loaded again.
+    Reload,
+}
+
+impl ComparisonChange {
+    pub fn item_saved(mut item: Item) -> Self {
+        let mut truncated = Vec::new();
+        for (property, value) in item.custom_properties.iter_mut() {
+            if let Some(preview) = value_preview(value) {
+                *value = preview;
+                truncated.push(property.clone());
+            }
+        }
+        truncated.sort();
+        ComparisonChange::ItemSaved { item, truncated }
+    }
+}
+
+/// Messages between the tabs of one browser that have the same comparison open,
+/// sent over a BroadcastChannel before the server has seen the change.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TabMessage {
+    /// Item edited in the sending tab, which saves it. `truncated` lists the
+    /// properties still holding a preview of a long value.
+    ItemEdited { item: Box<Item>, truncated: Vec<String> },
+    /// Number of items the sending tab hasn't saved yet, 0 once it's closed.
+    SaveStatus { tab: String, unsaved: usize },
+    /// A tab opened the comparison and asks the others for their save status.
+    Opened,
+}
@@ -0,0 +1,166 @@
+/// The Markdown subset descriptions and text cells are shown in: headings, lists, code
+/// blocks, emphasis, inline code and links. Parsed into blocks the cells render as
+/// elements, never as HTML, so markup in the text stays text; links only lead to web
+/// and mail addresses.
+
+// Block of a Markdown text
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarkdownBlock {
+    // Level 1 to 6
+    Heading(u8, Vec<MarkdownInline>),
+    Paragraph(Vec<MarkdownInline>),
+    List { ordered: bool, items: Vec<Vec<MarkdownInline>> },
+    // Text between ``` fences, as typed
+    Code(String),
+}
+
+// Piece of a line of Markdown
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarkdownInline {
+    Text(String),
+    Strong(Vec<MarkdownInline>),
+    Emphasis(Vec<MarkdownInline>),
+    Code(String),
+    Link { text: Vec<MarkdownInline>, url: String },
+    LineBreak,
+}
+
+// Schemes links may have, so a cell can't run script through a javascript: link
+const LINK_SCHEMES: &[&str] = &["https://", "http://", "mailto:"];
+
+// `url` if links may lead to it
+pub fn safe_url(url: &str) -> Option<&str> {
+    let url = url.trim();
+    let lowercase = url.to_ascii_lowercase();
+    LINK_SCHEMES.iter().any(|scheme| lowercase.starts_with(scheme)).then_some(url)
+}
+
+// Heading level and text of a "## Heading" line
+fn heading(line: &str) -> Option<(u8, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6).contains(&level).then_some((level as u8, text.trim()))
+}
+
+// Whether a line is an item of a numbered list, and its text, for "- item" and "1. item"
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    if let Some(text) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).or_else(|| line.strip_prefix("+ ")) {
+        return Some((false, text));
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let text = line[digits..].strip_prefix(". ").or_else(|| line[digits..].strip_prefix(") "))?;
+    (digits > 0).then_some((true, text))
+}
+
+pub fn parse_markdown(text: &str) -> Vec<MarkdownBlock> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+    let end_paragraph = |paragraph: &mut Vec<&str>, blocks: &mut Vec<MarkdownBlock>| {
+        if !paragraph.is_empty() {
+            blocks.push(MarkdownBlock::Paragraph(parse_inlines(&paragraph.join("\n"))));
+            paragraph.clear();
+        }
+    };
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if let Some(lines) = code.as_mut() {
+            if trimmed.starts_with("```") {
+                blocks.push(MarkdownBlock::Code(lines.join("\n")));
+                code = None;
+            } else {
+                lines.push(line);
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") {
+            end_paragraph(&mut paragraph, &mut blocks);
+            code = Some(Vec::new());
+        } else if trimmed.is_empty() {
+            end_paragraph(&mut paragraph, &mut blocks);
+        } else if let Some((level, text)) = heading(trimmed) {
+            end_paragraph(&mut paragraph, &mut blocks);
+            blocks.push(MarkdownBlock::Heading(level, parse_inlines(text)));
+        } else if let Some((ordered, text)) = list_item(trimmed) {
+            end_paragraph(&mut paragraph, &mut blocks);
+            let item = parse_inlines(text.trim());
+            match blocks.last_mut() {
+                Some(MarkdownBlock::List { ordered: list_ordered, items }) if *list_ordered == ordered => items.push(item),
+                _ => blocks.push(MarkdownBlock::List { ordered, items: vec![item] }),
+            }
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+    end_paragraph(&mut paragraph, &mut blocks);
+    // A fence left open holds the rest of the text
+    if let Some(lines) = code {
+        blocks.push(MarkdownBlock::Code(lines.join("\n")));
+    }
+    blocks
+}
+
+// Inline markup of a block's text, line breaks kept
+pub fn parse_inlines(text: &str) -> Vec<MarkdownInline> {
+    let mut inlines = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    let push_plain = |plain: &mut String, inlines: &mut Vec<MarkdownInline>| {
+        if !plain.is_empty() {
+            inlines.push(MarkdownInline::Text(std::mem::take(plain)));
+        }
+    };
+    while let Some(c) = rest.chars().next() {
+        let after = &rest[c.len_utf8()..];
+        let markup = match c {
+            '\\' if after.starts_with(|next: char| next.is_ascii_punctuation()) => {
+                let next = after.chars().next().unwrap_or_default();
+                plain.push(next);
+                rest = &after[next.len_utf8()..];
+                continue;
+            }
+            '\n' => Some((MarkdownInline::LineBreak, after)),
+            '`' => after.find('`').map(|end| (MarkdownInline::Code(after[..end].to_string()), &after[end + 1..])),
+            '*' if after.starts_with('*') => {
+                delimited(&after[1..], "**").map(|(inner, rest)| (MarkdownInline::Strong(parse_inlines(inner)), rest))
+            }
+            // Underscores inside words, as in snake_case names, aren't emphasis
+            '*' | '_' if c == '*' || !plain.ends_with(|before: char| before.is_alphanumeric()) => {
+                delimited(after, if c == '*' { "*" } else { "_" }).map(|(inner, rest)| (MarkdownInline::Emphasis(parse_inlines(inner)), rest))
+            }
+            '[' => link(after),
+            _ => None,
+        };
+        match markup {
+            Some((inline, after)) => {
+                push_plain(&mut plain, &mut inlines);
+                inlines.push(inline);
+                rest = after;
+            }
+            None => {
+                plain.push(c);
+                rest = after;
+            }
+        }
+    }
+    push_plain(&mut plain, &mut inlines);
+    inlines
+}
+
+// Text up to the closing `delimiter` and what follows it. The text can't start or end
+// with a space, so "2 * 3 * 4" stays as it is
+fn delimited<'a>(text: &'a str, delimiter: &str) -> Option<(&'a str, &'a str)> {
+    let end = text.find(delimiter)?;
+    let inner = &text[..end];
+    let spaced = inner.starts_with(char::is_whitespace) || inner.ends_with(char::is_whitespace);
+    (!inner.is_empty() && !spaced).then(|| (inner, &text[end + delimiter.len()..]))
+}
+
+// Link of a "[text](url)" after its "[". Links to addresses of other schemes aren't
+// links, their markup is shown as typed
+fn link(text: &str) -> Option<(MarkdownInline, &str)> {
+    let (label, after) = text.split_once("](")?;
+    let (url, rest) = after.split_once(')')?;
+    let url = safe_url(url)?;
+    Some((MarkdownInline::Link { text: parse_inlines(label), url: url.to_string() }, rest))
+}
@@ -0,0 +1,102 @@
+/// Title and description of a comparison, shown above its table and in the
+/// page's `<title>` and meta tags for search engines and link previews, and the
+/// decision the comparison led to, if it's decided.
+use crate::models::quantity::UnitSystem;
+use serde::{Deserialize, Serialize};
+
+// Longest accepted title and description, in characters
+pub const MAX_TITLE_LENGTH: usize = 200;
+pub const MAX_DESCRIPTION_LENGTH: usize = 1000;
+pub const MAX_RATIONALE_LENGTH: usize = 2000;
+
+// Item names spelled out in the generated description of link previews
+const PREVIEW_ITEM_NAMES: usize = 3;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ComparisonMetadata {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    // Set through the decision endpoints, saving the title and description leaves it as is
+    #[serde(default)]
+    pub decision: Option<Decision>,
+    // Units the CSV, XLSX and Markdown exports convert values to
+    #[serde(default)]
+    pub unit_system: UnitSystem,
+}
+
+/// Outcome of a comparison: the item chosen, when, and why.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct Decision {
+    pub item_id: String,
+    pub decided_at: i64,
+    #[serde(default)]
+    pub rationale: String,
+}
+
+/// Request body for marking a comparison as decided, dated by the server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct DecisionRequest {
+    pub item_id: String,
+    #[serde(default)]
+    pub rationale: String,
+}
+
+impl DecisionRequest {
+    // Why the decision can't be stored, if it can't
+    pub fn problem(&self) -> Option<String> {
+        if self.item_id.trim().is_empty() {
+            Some("A decision needs the chosen item".to_string())
+        } else if self.rationale.chars().count() > MAX_RATIONALE_LENGTH {
+            Some(format!("Rationale must be at most {} characters", MAX_RATIONALE_LENGTH))
+        } else {
+            None
+        }
+    }
+}
+
+impl ComparisonMetadata {
+    // Title and description without surrounding whitespace
+    pub fn trimmed(&self) -> Self {
+        ComparisonMetadata {
+            title: self.title.trim().to_string(),
+            description: self.description.trim().to_string(),
+            decision: self.decision.clone(),
+            unit_system: self.unit_system,
+        }
+    }
+
+    // Why the metadata can't be stored, if it can't
+    pub fn problem(&self) -> Option<String> {
+        if self.title.chars().count() > MAX_TITLE_LENGTH {
+            Some(format!("Title must be at most {} characters", MAX_TITLE_LENGTH))
+        } else if self.description.chars().count() > MAX_DESCRIPTION_LENGTH {
+            Some(format!("Description must be at most {} characters", MAX_DESCRIPTION_LENGTH))
+        } else if self.title.chars().any(char::is_control) {
+            Some("Title must not contain control characters".to_string())
+        } else {
+            None
+        }
+    }
+
+    // Description of link previews: the comparison's own, or else a summary of its items
+    pub fn link_description(&self, item_names: &[String]) -> String {
+        if !self.description.is_empty() {
+            return self.description.clone();
+        }
+        let names: Vec<&str> = item_names.iter().map(|name| name.trim()).filter(|name| !name.is_empty()).collect();
+        if names.is_empty() {
+            return String::new();
+        }
+        let listed = names[..names.len().min(PREVIEW_ITEM_NAMES)].join(", ");
+        let noun = if names.len() == 1 { "item" } else { "items" };
+        match names.len().checked_sub(PREVIEW_ITEM_NAMES).filter(|more| *more > 0) {
+            Some(more) => format!("Compare {} {}: {} and {} more", names.len(), noun, listed, more),
+            None => format!("Compare {} {}: {}", names.len(), noun, listed),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+/// Mirroring of a comparison that another instance publishes to Nostr: the items
+/// it publishes are merged into a local comparison as they arrive.
+use crate::models::item::Item;
+use crate::models::publishing::{relays_problem, trimmed_relays};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct MirrorSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    // URL of the comparison on the publishing instance, e.g. "/laptops"
+    #[serde(default)]
+    pub source_url: String,
+    // Hex public key of the publishing instance, items signed by other keys are ignored
+    #[serde(default)]
+    pub author: String,
+    // Relay URLs the publishing instance sends its items to
+    #[serde(default)]
+    pub relays: Vec<String>,
+}
+
+impl MirrorSettings {
+    pub fn trimmed(&self) -> Self {
+        MirrorSettings {
+            enabled: self.enabled,
+            source_url: self.source_url.trim().to_string(),
+            author: self.author.trim().to_lowercase(),
+            relays: trimmed_relays(&self.relays),
+        }
+    }
+
+    // Missing source, publisher key or relays of an enabled mirror, or an invalid relay
+    pub fn problem(&self) -> Option<String> {
+        if !self.enabled {
+            return relays_problem(&self.relays);
+        }
+        if self.source_url.is_empty() {
+            Some("Mirroring needs the URL of the comparison to mirror".to_string())
+        } else if self.author.len() != 64 || !self.author.chars().all(|c| c.is_ascii_hexdigit()) {
+            Some("The publisher's key must be a public key in hex, 64 characters".to_string())
+        } else if self.relays.is_empty() {
+            Some("Mirroring needs at least one relay".to_string())
+        } else {
+            relays_problem(&self.relays)
+        }
+    }
+
+    // Whether items received under the other settings come from the same comparison
+    pub fn same_source(&self, other: &MirrorSettings) -> bool {
+        self.source_url == other.source_url && self.author == other.author
+    }
+}
+
+/// Outcome of merging a received item into the mirroring comparison.
+#[derive(Debug, Clone)]
+pub enum MirrorUpdate {
+    // Received version saved, with the properties it added to the comparison
+    Saved { item: Box<Item>, added_properties: Vec<String> },
+    // Version older than, or the same as, the last one merged
+    Stale,
+    // Item changed or deleted locally since the last merge, the local version is kept
+    Conflict,
+}
@@ -1 +1,935 @@
+pub mod access;
+pub mod api_key;
+pub mod backup;
+pub mod card;
+pub mod chart;
+pub mod coordinate;
+pub mod diagnostics;
+pub mod directory;
+pub mod error;
+pub mod export;
+pub mod history;
+pub mod instance;
 pub mod item;
+pub mod knowledge;
+pub mod live;
+pub mod markdown;
+pub mod metadata;
+pub mod mirror;
+pub mod presence;
+pub mod property;
+pub mod publishing;
+pub mod quantity;
+pub mod remote;
+pub mod review;
+pub mod score;
+pub mod search;
+pub mod setup;
+pub mod snapshot;
+pub mod validation;
+pub mod wikidata;
+pub mod zap;
+
+// The models are the wire types shared by the client and the server. These tests
+// pin their JSON shape, so a change on one side can't silently break the other
+#[cfg(test)]
+mod tests {
+    use super::backup::{ComparisonArchive, ComparisonExport, COMPARISON_EXPORT_VERSION};
+    use super::card::{Spec, SpecCard};
+    use super::chart::{chart_data, numeric_properties, radar_point, ChartData, ChartValue};
+    use super::coordinate::{map_layout, Coordinate, MapLayout, TILE_SIZE};
+    use super::diagnostics::{ClientErrorReport, ComparisonLoad, ComparisonRequests, ComparisonRows, MAX_CLIENT_ERROR_LENGTH};
+    use super::directory::{default_title, ComparisonIndex, ComparisonListing, ComparisonSummary};
+    use super::error::ErrorResponse;
+    use super::history::{history_atom, HistoryChange, Revision};
+    use super::instance::{InstanceFeatures, InstanceInfo, InstanceSettings, MAX_REQUESTS_PER_MINUTE};
+    use super::item::{value_preview, DeleteSummary, Item, ItemQuery, ItemRequest, LoadedItems, PropertyValue, ValueSource, MAX_VALUE_LENGTH, PREVIEW_LENGTH};
+    use super::knowledge::{fact_for, KnowledgeSourceKind};
+    use super::live::{ComparisonChange, TabMessage};
+    use super::markdown::{parse_inlines, parse_markdown, safe_url, MarkdownBlock, MarkdownInline};
+    use super::metadata::{ComparisonMetadata, Decision, DecisionRequest, MAX_RATIONALE_LENGTH, MAX_TITLE_LENGTH};
+    use super::mirror::MirrorSettings;
+    use super::presence::{CellLock, Heartbeat, PresenceState};
+    use super::property::{canonical_property, is_property_id, BetterValue, EntityClaims, EntityTerms, PropertyColumn, PropertyDeletion, PropertyId, PropertyOrder, PropertyOverride, PropertySelection, PropertySettings, PropertySuggestion, MAX_PROPERTY_DESCRIPTION_LENGTH, MAX_WEIGHT};
+    use super::publishing::{PublishingSettings, MAX_PUBLISHING_RELAYS};
+    use super::quantity::{Quantity, UnitSystem};
+    use super::review::{reviews_atom, ExportedReview, RatingSummary, ReviewEntry, ReviewRequest};
+    use super::score::item_scores;
+    use super::search::{search_terms, snippet_parts, SearchHit, SnippetPart, MATCH_END, MATCH_START};
+    use super::snapshot::{Snapshot, SnapshotLink};
+    use super::validation::{FieldError, Validate, MAX_ID_LENGTH};
+    use super::wikidata::{best_match, entity_terms, name_similarity, pasted_entity_id, search_url, WikidataSuggestion, LIKELY_MATCH};
+    use super::zap::{TipInvoice, TipRequest};
+    use leptos::logging::log;
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use serde_json::{json, Value};
+    use std::collections::{HashMap, HashSet};
+
+    // Serialize a value, deserialize it back and check nothing was lost on the way
+    fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> Value {
+        let encoded = serde_json::to_value(value).unwrap();
+        let decoded: T = serde_json::from_value(encoded.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), encoded);
+        encoded
+    }
+
+    fn test_item() -> Item {
+        Item {
+            id: "item1".into(),
+            name: "Laptop".into(),
+            description: "Portable computer".into(),
+            wikidata_id: Some("Q3962".into()),
+            custom_properties: HashMap::from([("P2067".into(), "1.6 kg".into())]),
+            quantities: HashMap::from([(
+                "P2067".into(),
+                Quantity {
+                    amount: 1.6,
+                    unit_id: Some("Q11570".into()),
+                    unit: Some("kg".into()),
+                    precision: None,
+                },
+            )]),
+            group: Some("Laptops".into()),
+            tip_recipient: None,
+            status: None,
+            sources: HashMap::new(),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_item_round_trip() {
+        log!("[TEST] Starting test_item_round_trip");
+        let item = round_trip(&test_item());
+        assert_eq!(item["custom_properties"]["P2067"], "1.6 kg");
+        assert_eq!(item["quantities"]["P2067"]["unit_id"], "Q11570");
+
+        let request = round_trip(&ItemRequest { url: "https://example.com".into(), item: test_item() });
+        assert_eq!(request["item"]["id"], "item1");
+        round_trip(&LoadedItems {
+            items: vec![test_item()],
+            selected_properties: vec!["P2067".into()],
+            metadata: ComparisonMetadata { title: "Laptops".into(), description: "Light laptops".into(), ..Default::default() },
+            truncated_values: HashMap::from([("item1".into(), HashSet::from(["P2067".into()]))]),
+            property_settings: HashMap::from([("P1324".into(), PropertySettings { lang: None, spellcheck: Some(false), better: None, weight: None })]),
+            property_overrides: HashMap::from([("P2067".into(), PropertyOverride { label: Some("Weight".into()), description: None })]),
+        });
+        round_trip(&PropertyValue { item_id: "item1".into(), property: "P2067".into(), value: "1.6 kg".into() });
+        round_trip(&ItemQuery { query: "instances of smartphone".into() });
+        round_trip(&Snapshot {
+            id: "snapshot1".into(),
+            url: "https://example.com".into(),
+            created_at: 1718000000,
+            items: vec![test_item()],
+            selected_properties: vec!["P2067".into()],
+            property_labels: HashMap::from([("P2067".into(), "mass".into())]),
+        });
+        round_trip(&SnapshotLink { id: "snapshot1".into(), path: "/s/snapshot1".into() });
+        log!("[TEST] test_item_round_trip completed successfully");
+    }
+
+    #[test]
+    fn test_item_optional_fields() {
+        log!("[TEST] Starting test_item_optional_fields");
+        // Items saved before quantities, groups and tip recipients existed
+        let item: Item = serde_json::from_value(json!({
+            "id": "item1",
+            "name": "Laptop",
+            "description": "",
+            "wikidata_id": null,
+            "custom_properties": {}
+        }))
+        .unwrap();
+        assert!(item.quantities.is_empty());
+        assert_eq!(item.group, None);
+        assert_eq!(item.tip_recipient, None);
+        log!("[TEST] test_item_optional_fields completed successfully");
+    }
+
+    #[test]
+    fn test_value_preview() {
+        log!("[TEST] Starting test_value_preview");
+        assert_eq!(value_preview("short"), None);
+        assert_eq!(value_preview(&"a".repeat(PREVIEW_LENGTH)), None);
+        // Cut on characters, not bytes
+        let preview = value_preview(&"é".repeat(PREVIEW_LENGTH + 1)).unwrap();
+        assert_eq!(preview.chars().count(), PREVIEW_LENGTH + 1);
+        assert!(preview.ends_with('…'));
+        log!("[TEST] test_value_preview completed successfully");
+    }
+
+    #[test]
+    fn test_comparison_changes() {
+        log!("[TEST] Starting test_comparison_changes");
+        let mut item = test_item();
+        item.custom_properties.insert("notes".into(), "a".repeat(PREVIEW_LENGTH + 1));
+        let change = round_trip(&ComparisonChange::item_saved(item));
+        assert_eq!(change["type"], "item_saved");
+        assert_eq!(change["truncated"], json!(["notes"]));
+        assert_eq!(change["item"]["custom_properties"]["P2067"], "1.6 kg");
+        assert!(change["item"]["custom_properties"]["notes"].as_str().unwrap().ends_with('…'));
+
+        let deleted = round_trip(&ComparisonChange::PropertyDeleted { property: "P18".into() });
+        assert_eq!(deleted, json!({ "type": "property_deleted", "property": "P18" }));
+        let renamed = round_trip(&ComparisonChange::PropertyRenamed { property: "price".into(), rename_to: "cost".into() });
+        assert_eq!(renamed, json!({ "type": "property_renamed", "property": "price", "rename_to": "cost" }));
+        assert_eq!(round_trip(&ComparisonChange::Reload), json!({ "type": "reload" }));
+        let settings = ComparisonChange::PropertySettingsChanged {
+            property: "description".into(),
+            settings: PropertySettings { lang: Some("de".into()), spellcheck: None, better: None, weight: None },
+        };
+        assert_eq!(round_trip(&settings), json!({ "type": "property_settings_changed", "property": "description", "settings": { "lang": "de" } }));
+
+        let edited = round_trip(&TabMessage::ItemEdited { item: Box::new(test_item()), truncated: vec!["notes".into()] });
+        assert_eq!((&edited["type"], &edited["item"]["id"], &edited["truncated"]), (&json!("item_edited"), &json!("item1"), &json!(["notes"])));
+        let status = TabMessage::SaveStatus { tab: "tab1".into(), unsaved: 2 };
+        assert_eq!(round_trip(&status), json!({ "type": "save_status", "tab": "tab1", "unsaved": 2 }));
+        assert_eq!(round_trip(&TabMessage::Opened), json!({ "type": "opened" }));
+        log!("[TEST] test_comparison_changes completed successfully");
+    }
+
+    #[test]
+    fn test_revisions() {
+        log!("[TEST] Starting test_revisions");
+        let saved = Revision { id: 7, actor: "owner".into(), created_at: 1_700_000_000, change: HistoryChange::ItemSaved { item: test_item() } };
+        let encoded = round_trip(&saved);
+        assert_eq!((&encoded["change"]["action"], &encoded["change"]["item"]["id"]), (&json!("item_saved"), &json!("item1")));
+        assert_eq!((saved.change.item_id(), saved.change.property()), (Some("item1"), None));
+
+        let deleted = HistoryChange::PropertyDeleted { property: "P18".into() };
+        assert_eq!(round_trip(&deleted), json!({ "action": "property_deleted", "property": "P18" }));
+        assert_eq!((deleted.item_id(), deleted.property(), deleted.item().is_none()), (None, Some("P18"), true));
+        log!("[TEST] test_revisions completed successfully");
+    }
+
+    #[test]
+    fn test_comparison_export() {
+        log!("[TEST] Starting test_comparison_export");
+        let decision = Decision { item_id: "item1".into(), decided_at: 1_700_000_000, rationale: "Lightest".into() };
+        let export = ComparisonExport {
+            version: COMPARISON_EXPORT_VERSION,
+            exported_at: 1_700_000_000,
+            comparison: ComparisonArchive {
+                url: "https://old.example.com/laptops".into(),
+                items: vec![test_item()],
+                selected_properties: vec!["P2067".into()],
+                metadata: ComparisonMetadata { title: "Laptops".into(), description: String::new(), decision: Some(decision), ..Default::default() },
+            },
+            property_settings: HashMap::from([("P2067".into(), PropertySettings { lang: Some("de".into()), spellcheck: None, better: None, weight: None })]),
+            property_overrides: HashMap::new(),
+            reviews: vec![
+                ExportedReview { item_id: "item1".into(), reviewer: "reviewer 1a2b3c4d".into(), rating: 4, created_at: 1_700_000_000 },
+                ExportedReview { item_id: "item1".into(), reviewer: "reviewer 5e6f7a8b".into(), rating: 2, created_at: 1_700_000_100 },
+                ExportedReview { item_id: "gone".into(), reviewer: "reviewer 1a2b3c4d".into(), rating: 5, created_at: 1_700_000_200 },
+            ],
+        };
+        let encoded = round_trip(&export);
+        assert_eq!(encoded["comparison"]["items"][0]["id"], "item1");
+        assert!(export.problem().is_none());
+
+        // Test the import gets new item IDs and keeps the decision on its item
+        let imported = export.imported_as("https://new.example.com/laptops");
+        let comparison = &imported.comparison;
+        assert_eq!(comparison.url, "https://new.example.com/laptops");
+        assert_ne!(comparison.items[0].id, "item1");
+        assert_eq!(comparison.metadata.decision.as_ref().unwrap().item_id, comparison.items[0].id);
+        // Test reviews follow their item, and each reviewer label gets a new reviewer
+        assert_eq!(imported.reviews.len(), 2);
+        assert!(imported.reviews.iter().all(|review| review.item_id == comparison.items[0].id));
+        assert_ne!(imported.reviews[0].reviewer, imported.reviews[1].reviewer);
+        assert!(!imported.reviews[0].reviewer.starts_with("reviewer"));
+
+        let mut unrated = export.clone();
+        unrated.reviews[0].rating = 6;
+        assert!(unrated.problem().unwrap().contains("between 1 and 5"));
+
+        let mut twice = export.clone();
+        twice.comparison.items.push(test_item());
+        assert!(twice.problem().unwrap().contains("appears twice"));
+        let newer = ComparisonExport { version: COMPARISON_EXPORT_VERSION + 1, ..export };
+        assert!(newer.problem().is_some());
+        log!("[TEST] test_comparison_export completed successfully");
+    }
+
+    #[test]
+    fn test_spec_card() {
+        log!("[TEST] Starting test_spec_card");
+        let card = SpecCard {
+            wikidata_id: "Q3962".into(),
+            name: "<Laptop>".into(),
+            description: String::new(),
+            comparisons: 1,
+            specs: vec![Spec { property: "P2067".into(), label: "mass".into(), value: "1.6 kg & more".into(), comparisons: 1 }],
+        };
+        assert_eq!(round_trip(&card)["specs"][0]["label"], "mass");
+        let html = card.to_html("https://compareware.org/\"onload=\"x");
+        assert!(html.contains("<h3>&lt;Laptop&gt;</h3>"));
+        assert!(html.contains("<dt>mass</dt><dd>1.6 kg &amp; more</dd>"));
+        assert!(html.contains("href=\"https://compareware.org/&quot;onload=&quot;x\""));
+        assert!(html.contains("1 comparison</p>"));
+        log!("[TEST] test_spec_card completed successfully");
+    }
+
+    #[test]
+    fn test_reviews_atom() {
+        log!("[TEST] Starting test_reviews_atom");
+        let reviews = [
+            ReviewEntry { item_id: "item2".into(), item_name: "Tom & Jerry".into(), reviewer: "reviewer 1a2b3c4d".into(), rating: 1, created_at: 86400 },
+            ReviewEntry { item_id: "item1".into(), item_name: String::new(), reviewer: "reviewer 5e6f7a8b".into(), rating: 5, created_at: 0 },
+        ];
+        let feed_url = "https://compareware.org/api/urls/x/reviews.atom";
+        let atom = reviews_atom("https://example.com/a?b=1&c=2", "<Laptops>", feed_url, &reviews, 0);
+        assert!(atom.contains("<title>Reviews of &lt;Laptops&gt;</title>"));
+        assert!(atom.contains("<link href=\"https://example.com/a?b=1&amp;c=2\"/>"));
+        assert!(atom.contains("<updated>1970-01-02T00:00:00Z</updated>"));
+        assert!(atom.contains("<title>1 star for Tom &amp; Jerry</title>"));
+        assert!(atom.contains("<title>5 stars for an unnamed item</title>"));
+        assert_eq!(atom.matches("<entry>").count(), 2);
+        assert!(reviews_atom("https://example.com", "Empty", feed_url, &[], 60).contains("<updated>1970-01-01T00:01:00Z</updated>"));
+        log!("[TEST] test_reviews_atom completed successfully");
+    }
+
+    #[test]
+    fn test_history_atom() {
+        log!("[TEST] Starting test_history_atom");
+        let saved = Item { name: "Tom & Jerry".into(), ..test_item() };
+        let revisions = [
+            Revision { id: 3, actor: "owner".into(), created_at: 86400, change: HistoryChange::PropertyRenamed { property: "Colour".into(), rename_to: "Color".into() } },
+            Revision { id: 2, actor: "anonymous".into(), created_at: 60, change: HistoryChange::ItemSaved { item: saved } },
+            Revision { id: 1, actor: "mirror".into(), created_at: 0, change: HistoryChange::ItemDeleted { item_id: "item2".into() } },
+        ];
+        let feed_url = "https://compareware.org/api/urls/x/feed.atom";
+        let atom = history_atom("https://example.com/a?b=1", "<Laptops>", feed_url, &revisions, 0);
+        assert!(atom.contains("<title>Changes to &lt;Laptops&gt;</title>"));
+        assert!(atom.contains("<updated>1970-01-02T00:00:00Z</updated>"));
+        assert!(atom.contains("<id>https://compareware.org/api/urls/x/feed.atom#3</id>"));
+        assert!(atom.contains("<title>Renamed property Colour to Color</title>"));
+        // Test entries of item changes link to the item's column
+        assert!(atom.contains("<content type=\"text\">Saved Tom &amp; Jerry by anonymous</content>"));
+        assert!(atom.contains("<link href=\"https://example.com/a?b=1#item-item2\"/>"));
+        assert_eq!(atom.matches("<entry>").count(), 3);
+        assert!(history_atom("https://example.com", "Empty", feed_url, &[], 60).contains("<updated>1970-01-01T00:01:00Z</updated>"));
+        log!("[TEST] test_history_atom completed successfully");
+    }
+
+    #[test]
+    fn test_markdown() {
+        log!("[TEST] Starting test_markdown");
+        use MarkdownInline::{Code, Emphasis, LineBreak, Link, Strong, Text};
+        let text = |text: &str| Text(text.into());
+
+        // Test blocks: headings, paragraphs with their line breaks, lists and code fences
+        let blocks = parse_markdown("## Ports\nTwo USB-C\nOne HDMI\n\n- Wi-Fi 6\n- Bluetooth\n1. Charge\n```\nlet x = 1;\n```");
+        assert_eq!(
+            blocks,
+            [
+                MarkdownBlock::Heading(2, vec![text("Ports")]),
+                MarkdownBlock::Paragraph(vec![text("Two USB-C"), LineBreak, text("One HDMI")]),
+                MarkdownBlock::List { ordered: false, items: vec![vec![text("Wi-Fi 6")], vec![text("Bluetooth")]] },
+                MarkdownBlock::List { ordered: true, items: vec![vec![text("Charge")]] },
+                MarkdownBlock::Code("let x = 1;".into()),
+            ]
+        );
+        assert!(parse_markdown("").is_empty());
+
+        // Test inline markup, and text that only looks like it staying as typed
+        assert_eq!(
+            parse_inlines("**16 GB** of *fast* `DDR5`, see [specs](https://example.com)"),
+            [
+                Strong(vec![text("16 GB")]),
+                text(" of "),
+                Emphasis(vec![text("fast")]),
+                text(" "),
+                Code("DDR5".into()),
+                text(", see "),
+                Link { text: vec![text("specs")], url: "https://example.com".into() },
+            ]
+        );
+        for plain in ["2 * 3 * 4", "max_power_draw", "-5 °C", "#1 seller", "\\*not emphasis\\*"] {
+            let blocks = parse_markdown(plain);
+            assert!(matches!(&blocks[..], [MarkdownBlock::Paragraph(inlines)] if inlines.iter().all(|inline| matches!(inline, Text(_)))), "{}", plain);
+        }
+
+        // Test markup can't inject HTML or script: tags stay text, other link schemes aren't links
+        assert_eq!(parse_inlines("<script>alert(1)</script>"), [text("<script>alert(1)</script>")]);
+        assert_eq!(parse_inlines("[click](javascript:alert(1))"), [text("[click](javascript:alert(1))")]);
+        assert_eq!(safe_url(" MAILTO:sales@example.com "), Some("MAILTO:sales@example.com"));
+        assert_eq!(safe_url("data:text/html,hi"), None);
+        log!("[TEST] test_markdown completed successfully");
+    }
+
+    #[test]
+    fn test_search_hits() {
+        log!("[TEST] Starting test_search_hits");
+        assert_eq!(search_terms(" \"ThinkPad\" x1-carbon* "), ["thinkpad", "x1", "carbon"]);
+        assert!(search_terms("* - \"").is_empty());
+
+        let marked = format!("…light {}ThinkPad{} with {}Think{}Shutter", MATCH_START, MATCH_END, MATCH_START, MATCH_END);
+        let parts = snippet_parts(&marked);
+        let matched: Vec<&str> = parts.iter().filter(|part| part.matched).map(|part| part.text.as_str()).collect();
+        assert_eq!(matched, ["ThinkPad", "Think"]);
+        assert_eq!(parts.iter().map(|part| part.text.as_str()).collect::<String>(), "…light ThinkPad with ThinkShutter");
+        assert_eq!(snippet_parts("plain"), [SnippetPart { text: "plain".into(), matched: false }]);
+
+        let hit = round_trip(&SearchHit {
+            url: "https://example.com/laptops".into(),
+            title: "Laptops".into(),
+            item_id: "item1".into(),
+            item_name: "ThinkPad X1".into(),
+            property: "name".into(),
+            snippet: parts,
+        });
+        assert_eq!(hit["snippet"][1], json!({"text": "ThinkPad", "matched": true}));
+        log!("[TEST] test_search_hits completed successfully");
+    }
+
+    #[test]
+    fn test_wikidata_matches() {
+        log!("[TEST] Starting test_wikidata_matches");
+        assert_eq!(name_similarity("iPhone 15", " IPHONE-15 "), 1.0);
+        assert!(name_similarity("iPhone 15", "iPhone 15 Pro") > name_similarity("iPhone 15", "iPhone 5"));
+        assert_eq!(name_similarity("", "iPhone"), 0.0);
+        assert_eq!(name_similarity("Laptop", "Kettle"), 0.0);
+
+        let entity = |id: &str, label: &str| WikidataSuggestion { id: id.into(), label: label.into(), description: None };
+        // Test the closest label wins, and the better ranked one of equal labels
+        let (matched, confidence) = best_match("ThinkPad X1", &[entity("Q1", "ThinkPad"), entity("Q2", "ThinkPad X1")]).unwrap();
+        assert_eq!(matched.id, "Q2");
+        assert!((LIKELY_MATCH..1.0).contains(&confidence));
+        let (matched, confidence) = best_match("Kindle", &[entity("Q3", "Kindle"), entity("Q4", "Kindle")]).unwrap();
+        assert_eq!((matched.id.as_str(), confidence), ("Q3", 1.0));
+        assert!(best_match("Kindle", &[entity("Q5", "Zebra")]).is_none());
+        assert!(best_match("Kindle", &[]).is_none());
+
+        // Test labels and descriptions are read in the requested language only
+        let answer = serde_json::json!({ "entities": { "Q3962": {
+            "labels": { "de": { "language": "de", "value": "Laptop" } },
+            "descriptions": {}
+        } } });
+        let terms = entity_terms(&answer, "de");
+        assert_eq!(terms["Q3962"], EntityTerms { label: Some("Laptop".into()), description: None });
+        assert_eq!(entity_terms(&answer, "en")["Q3962"], EntityTerms::default());
+
+        // Test pasted item IDs and links are recognized, other names aren't
+        assert_eq!(pasted_entity_id(" Q42 ").as_deref(), Some("Q42"));
+        assert_eq!(pasted_entity_id("q42").as_deref(), Some("Q42"));
+        assert_eq!(pasted_entity_id("https://www.wikidata.org/wiki/Q42").as_deref(), Some("Q42"));
+        assert_eq!(pasted_entity_id("http://www.wikidata.org/entity/Q42").as_deref(), Some("Q42"));
+        assert_eq!(pasted_entity_id("https://m.wikidata.org/wiki/Q42?uselang=de#P31").as_deref(), Some("Q42"));
+        assert_eq!(pasted_entity_id("https://www.wikidata.org/wiki/Property:P31"), None);
+        assert_eq!(pasted_entity_id("https://example.com/wiki/Q42"), None);
+        assert_eq!(pasted_entity_id("Q42 laptop"), None);
+        assert_eq!(pasted_entity_id("Q"), None);
+        log!("[TEST] test_wikidata_matches completed successfully");
+    }
+
+    #[test]
+    fn test_chart_data() {
+        log!("[TEST] Starting test_chart_data");
+        let with_weight = |id: &str, name: &str, amount: f64, unit_id: &str| {
+            let mut item = Item { id: id.into(), name: name.into(), ..test_item() };
+            let quantity = Quantity { amount, unit_id: Some(unit_id.into()), unit: None, precision: None };
+            item.custom_properties.insert("P2067".into(), quantity.to_text());
+            item.quantities.insert("P2067".into(), quantity);
+            item
+        };
+        let mut untyped = Item { id: "item4".into(), name: "Tablet".into(), ..test_item() };
+        untyped.quantities.clear();
+        untyped.custom_properties.insert("P2067".into(), "900 g".into());
+        let items = vec![
+            test_item(),
+            with_weight("item2", "Netbook", 1200.0, "Q41803"),
+            with_weight("item3", "Workstation", 2.0, "Q11570"),
+            untyped,
+        ];
+
+        // Test quantities are converted to the unit most items use, untyped text left out
+        let data = chart_data(&items, "P2067");
+        assert_eq!(data.unit.as_deref(), Some("kg"));
+        let labels: Vec<&str> = data.values.iter().map(|value| value.label.as_str()).collect();
+        assert_eq!(labels, ["Laptop", "Netbook", "Workstation"]);
+        assert!((data.values[1].value - 1.2).abs() < 1e-9);
+        assert_eq!(data.max(), 2.0);
+        assert_eq!(data.fraction(1.0), 0.5);
+        assert_eq!(data.fraction(-1.0), 0.0);
+        let summary = data.summary().unwrap();
+        assert_eq!((summary.min.as_str(), summary.max.as_str(), summary.average.as_str()), ("1.2 kg", "2 kg", "1.6 kg"));
+        assert_eq!(data.best(BetterValue::Lower), ["item2"]);
+        assert_eq!(data.best(BetterValue::Higher), ["item3"]);
+
+        // Test plain numbers are charted when no item has a unit, other text isn't
+        let mut items: Vec<Item> = ["8 cores", "about six", "12", ""]
+            .iter()
+            .enumerate()
+            .map(|(index, cores)| Item {
+                id: format!("cpu{}", index),
+                name: format!("CPU {}", index),
+                custom_properties: HashMap::from([("Cores".into(), cores.to_string())]),
+                quantities: HashMap::new(),
+                ..test_item()
+            })
+            .collect();
+        items.push(Item { name: String::new(), custom_properties: HashMap::from([("Cores".into(), "4".into())]), ..test_item() });
+        let data = chart_data(&items, "Cores");
+        assert_eq!(data.unit, None);
+        assert_eq!(data.values.iter().map(|value| value.value).collect::<Vec<_>>(), [8.0, 12.0]);
+        assert_eq!(data.values[0].text, "8 cores");
+        assert_eq!(numeric_properties(&items, &["Cores".into(), "Colour".into()]), ["Cores"]);
+        assert_eq!(chart_data(&[], "Cores"), ChartData::default());
+        assert_eq!(ChartData::default().summary(), None);
+        // Test ties are all best, and nothing is when every value is the same
+        let mut tied = data.clone();
+        tied.values.push(ChartValue { item_id: "cpu3".into(), label: "CPU 3".into(), value: 12.0, text: "12".into() });
+        assert_eq!(tied.best(BetterValue::Higher), ["cpu2", "cpu3"]);
+        assert_eq!(tied.best(BetterValue::Lower), ["cpu0"]);
+        tied.values[0].value = 12.0;
+        assert!(tied.best(BetterValue::Lower).is_empty());
+        assert_eq!(tied.summary().unwrap().average, "12");
+
+        // Test radar spokes start at the top and go round clockwise
+        let (x, y) = radar_point(0, 4, 1.0, 100.0);
+        assert!(x.abs() < 1e-9 && (y + 100.0).abs() < 1e-9);
+        let (x, y) = radar_point(1, 4, 0.5, 100.0);
+        assert!((x - 50.0).abs() < 1e-9 && y.abs() < 1e-9);
+        log!("[TEST] test_chart_data completed successfully");
+    }
+
+    #[test]
+    fn test_coordinates() {
+        log!("[TEST] Starting test_coordinates");
+        let berlin = Coordinate { latitude: 52.516666666667, longitude: 13.383333333333 };
+        assert_eq!(Coordinate::parse("52.516666666667, 13.383333333333", false), Some(berlin));
+        assert_eq!(Coordinate::parse("33.9°S, 18.4°E", false), Some(Coordinate { latitude: -33.9, longitude: 18.4 }));
+        assert_eq!(Coordinate::parse("40.7 n,74.0 w", false), Some(Coordinate { latitude: 40.7, longitude: -74.0 }));
+        // Test whole degrees and short decimals only count in coordinate properties
+        assert_eq!(Coordinate::parse("52, 13", true), Some(Coordinate { latitude: 52.0, longitude: 13.0 }));
+        assert_eq!(Coordinate::parse("52, 13", false), None);
+        assert_eq!(Coordinate::parse("13.3, 15.6", false), None);
+        // Test other values, swapped hemispheres and places off the globe aren't coordinates
+        assert_eq!(Coordinate::parse("Berlin, Germany", true), None);
+        assert_eq!(Coordinate::parse("13.4°E, 52.5°N", true), None);
+        assert_eq!(Coordinate::parse("-33.9°S, 18.4°E", true), None);
+        assert_eq!(Coordinate::parse("95.0, 13.0", true), None);
+        assert_eq!(Coordinate::parse("1,200", true), None);
+
+        // Test a single place is shown at the highest zoom, centered
+        let layout = map_layout(&[berlin], 120.0, 80.0, 12);
+        assert_eq!(layout.zoom, 12);
+        assert_eq!(layout.markers, [(60.0, 40.0)]);
+        assert!(layout.tiles.iter().all(|tile| tile.zoom == 12 && tile.left <= 120.0 && tile.top <= 80.0));
+        assert!(layout.tiles.iter().any(|tile| tile.left <= 60.0 && tile.left + TILE_SIZE > 60.0 && tile.top <= 40.0 && tile.top + TILE_SIZE > 40.0));
+        assert_eq!(layout.tiles[0].url(), format!("https://tile.openstreetmap.org/12/{}/{}.png", layout.tiles[0].x, layout.tiles[0].y));
+
+        // Test places far apart zoom out until all of them fit
+        let cape_town = Coordinate { latitude: -33.9, longitude: 18.4 };
+        let layout = map_layout(&[berlin, cape_town], 640.0, 420.0, 16);
+        assert!(layout.zoom < 5);
+        for (left, top) in &layout.markers {
+            assert!((0.0..=640.0).contains(left) && (0.0..=420.0).contains(top));
+        }
+        assert!(layout.tiles.iter().all(|tile| tile.y < 1 << layout.zoom && tile.x < 1 << layout.zoom));
+        assert_eq!(map_layout(&[], 640.0, 420.0, 16), MapLayout::default());
+        log!("[TEST] test_coordinates completed successfully");
+    }
+
+    #[test]
+    fn test_knowledge_sources() {
+        log!("[TEST] Starting test_knowledge_sources");
+        assert_eq!("dbpedia".parse(), Ok(KnowledgeSourceKind::Dbpedia));
+        assert!("freebase".parse::<KnowledgeSourceKind>().is_err());
+        assert_eq!(serde_json::from_value::<InstanceInfo>(json!({
+            "wikidata_language": "en", "private": false, "features": {}
+        })).unwrap().knowledge_source, KnowledgeSourceKind::Wikidata);
+
+        // Test Wikidata suggestions and claims go through the same requests as before
+        let wikidata = KnowledgeSourceKind::Wikidata.source();
+        assert_eq!(wikidata.search_url("laptop", "de"), search_url("laptop", "de"));
+        let answer = json!({ "search": [{ "id": "Q3962", "label": "laptop", "description": "portable computer" }] });
+        assert_eq!(wikidata.suggestions(&answer)[0].id, "Q3962");
+        let claims = json!({ "entities": { "Q3962": { "claims": { "P1813": [{
+            "rank": "normal",
+            "mainsnak": { "datavalue": { "type": "monolingualtext", "value": { "text": "notebook", "language": "en" } } }
+        }] } } } });
+        assert_eq!(wikidata.facts("Q3962", &claims, "en")["P1813"], "notebook");
+        assert_eq!(wikidata.wikidata_id("Q3962", &claims).as_deref(), Some("Q3962"));
+
+        // Test DBpedia Lookup answers lose their highlighting and keep the resource name as ID
+        let dbpedia = KnowledgeSourceKind::Dbpedia.source();
+        assert!(dbpedia.search_url("think pad", "en").ends_with("?query=think%20pad&format=JSON&maxResults=5"));
+        let answer = json!({ "docs": [
+            { "resource": ["http://dbpedia.org/resource/ThinkPad_X1"], "label": ["<B>ThinkPad</B> X1"], "comment": ["Laptop by Lenovo"] },
+            { "resource": ["http://dbpedia.org/resource/Lenovo_Yoga"] },
+            { "label": ["No resource"] }
+        ] });
+        let suggestions = dbpedia.suggestions(&answer);
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0], WikidataSuggestion { id: "ThinkPad_X1".into(), label: "ThinkPad X1".into(), description: Some("Laptop by Lenovo".into()) });
+        assert_eq!(suggestions[1].label, "Lenovo Yoga");
+
+        // Test ontology properties are read in the requested language, resources by name
+        let resource = json!({ "http://dbpedia.org/resource/ThinkPad_X1": {
+            "http://dbpedia.org/ontology/releaseDate": [{ "type": "literal", "value": "2012-08-01" }],
+            "http://dbpedia.org/ontology/manufacturer": [{ "type": "uri", "value": "http://dbpedia.org/resource/Lenovo_Group" }],
+            "http://dbpedia.org/ontology/weight": [{ "type": "typed-literal", "value": 1.36 }],
+            "http://dbpedia.org/ontology/abstract": [
+                { "type": "literal", "value": "Ein Laptop", "lang": "de" },
+                { "type": "literal", "value": "A laptop", "lang": "en" }
+            ],
+            "http://www.w3.org/2000/01/rdf-schema#label": [{ "type": "literal", "value": "ThinkPad X1", "lang": "en" }],
+            "http://www.w3.org/2002/07/owl#sameAs": [
+                { "type": "uri", "value": "http://de.dbpedia.org/resource/ThinkPad_X1" },
+                { "type": "uri", "value": "http://www.wikidata.org/entity/Q1059417" }
+            ]
+        } });
+        let facts = dbpedia.facts("ThinkPad_X1", &resource, "de");
+        assert_eq!(facts.len(), 4);
+        assert_eq!(facts["manufacturer"], "Lenovo Group");
+        assert_eq!(facts["weight"], "1.36");
+        assert_eq!(facts["abstract"], "Ein Laptop");
+        assert_eq!(fact_for(&facts, "Release date").map(String::as_str), Some("2012-08-01"));
+        assert_eq!(fact_for(&facts, "Price"), None);
+        assert_eq!(fact_for(&facts, " - "), None);
+        assert_eq!(dbpedia.wikidata_id("ThinkPad_X1", &resource).as_deref(), Some("Q1059417"));
+        assert_eq!(dbpedia.wikidata_id("Lenovo_Yoga", &resource), None);
+        log!("[TEST] test_knowledge_sources completed successfully");
+    }
+
+    #[test]
+    fn test_item_scores() {
+        log!("[TEST] Starting test_item_scores");
+        let items: Vec<Item> = [("8", "300"), ("16", "500"), ("16", "500"), ("", "100")]
+            .iter()
+            .enumerate()
+            .map(|(index, (cores, price))| Item {
+                id: format!("cpu{}", index),
+                name: format!("CPU {}", index),
+                custom_properties: HashMap::from([("Cores".into(), cores.to_string()), ("Price".into(), price.to_string())]),
+                quantities: HashMap::new(),
+                ..test_item()
+            })
+            .collect();
+        let properties = ["Cores".to_string(), "Price".to_string()];
+        let weighted = |better: BetterValue, weight: f64| PropertySettings { better: Some(better), weight: Some(weight), ..Default::default() };
+        let mut settings = HashMap::from([("Cores".to_string(), weighted(BetterValue::Higher, 3.0))]);
+        settings.insert("Price".into(), weighted(BetterValue::Lower, 1.0));
+
+        // Test each property adds its weight times how close an item comes to the best value,
+        // nothing for items without a number, and tied items share a rank
+        let scores = item_scores(&items, &properties, &settings);
+        let ranking: Vec<(&str, f64, usize)> = scores.iter().map(|score| (score.item_id.as_str(), score.score, score.rank)).collect();
+        assert_eq!(ranking, [("cpu1", 75.0, 1), ("cpu2", 75.0, 1), ("cpu3", 25.0, 3), ("cpu0", 12.5, 4)]);
+
+        // Test properties without a best value or a weight are left out of the score
+        settings.get_mut("Price").unwrap().better = None;
+        assert_eq!(item_scores(&items, &properties, &settings)[0].score, 100.0);
+        settings.get_mut("Cores").unwrap().weight = Some(0.0);
+        assert!(item_scores(&items, &properties, &settings).is_empty());
+        assert!(item_scores(&items, &properties, &HashMap::new()).is_empty());
+        log!("[TEST] test_item_scores completed successfully");
+    }
+
+    #[test]
+    fn test_property_settings() {
+        log!("[TEST] Starting test_property_settings");
+        let settings = PropertySettings { lang: Some(" pt-BR ".into()), spellcheck: Some(false), better: None, weight: None }.trimmed();
+        assert_eq!(settings.lang.as_deref(), Some("pt-BR"));
+        assert!(settings.problem().is_none());
+        assert!(PropertySettings { lang: Some("  ".into()), spellcheck: None, better: None, weight: None }.trimmed().is_default());
+        for lang in ["en", "zh-Hant-TW", "de-CH-1901"] {
+            assert!(PropertySettings { lang: Some(lang.into()), spellcheck: None, better: None, weight: None }.problem().is_none(), "{}", lang);
+        }
+        for lang in ["en_US", "en-", "\"en\"", "verylongsubtag"] {
+            assert!(PropertySettings { lang: Some(lang.into()), spellcheck: None, better: None, weight: None }.problem().is_some(), "{}", lang);
+        }
+        // Settings left to the browser don't show up in the JSON
+        assert_eq!(round_trip(&PropertySettings::default()), json!({}));
+        let prices = PropertySettings { better: Some(BetterValue::Lower), ..Default::default() }.trimmed();
+        assert_eq!(round_trip(&prices), json!({"better": "lower"}));
+        assert_eq!(BetterValue::from_name(BetterValue::Higher.as_str()), Some(BetterValue::Higher));
+        assert_eq!(BetterValue::from_name(""), None);
+        for weight in [0.0, 2.5, MAX_WEIGHT] {
+            assert!(PropertySettings { weight: Some(weight), ..Default::default() }.problem().is_none(), "{}", weight);
+        }
+        for weight in [-1.0, MAX_WEIGHT + 1.0, f64::NAN] {
+            assert!(PropertySettings { weight: Some(weight), ..Default::default() }.problem().is_some(), "{}", weight);
+        }
+        log!("[TEST] test_property_settings completed successfully");
+    }
+
+    #[test]
+    fn test_property_overrides() {
+        log!("[TEST] Starting test_property_overrides");
+        let overrides = PropertyOverride { label: Some(" Weight (g) ".into()), description: Some("  ".into()) }.trimmed();
+        assert_eq!(overrides, PropertyOverride { label: Some("Weight (g)".into()), description: None });
+        assert!(overrides.problem().is_none());
+        assert!(PropertyOverride { label: Some(" ".into()), description: None }.trimmed().is_default());
+        assert!(PropertyOverride { label: Some("Weight\n".into()), description: None }.problem().is_some());
+        let long = "a".repeat(MAX_PROPERTY_DESCRIPTION_LENGTH + 1);
+        assert!(PropertyOverride { label: None, description: Some(long) }.problem().is_some());
+        // Defaults kept don't show up in the JSON
+        assert_eq!(round_trip(&PropertyOverride::default()), json!({}));
+        log!("[TEST] test_property_overrides completed successfully");
+    }
+
+    #[test]
+    fn test_instance_settings() {
+        log!("[TEST] Starting test_instance_settings");
+        let settings = InstanceSettings {
+            wikidata_client_rpm: 10,
+            wikidata_global_rpm: 60,
+            wikidata_language: " ".into(),
+            private: false,
+            features: InstanceFeatures::default(),
+        }
+        .trimmed();
+        assert_eq!(settings.wikidata_language, "en");
+        assert!(settings.problem().is_none());
+        assert_eq!(round_trip(&settings)["features"]["snapshots"], true);
+
+        // Limits must allow some traffic, but not more than one instance should send
+        for (client, global) in [(0, 60), (10, MAX_REQUESTS_PER_MINUTE + 1), (61, 60)] {
+            let limits = InstanceSettings { wikidata_client_rpm: client, wikidata_global_rpm: global, ..settings.clone() };
+            assert!(limits.problem().is_some(), "{} {}", client, global);
+        }
+        assert!(InstanceSettings { wikidata_language: "en_GB".into(), ..settings.clone() }.problem().is_some());
+
+        // Features missing from stored settings stay on
+        let features: InstanceFeatures = serde_json::from_value(json!({"tips": false})).unwrap();
+        assert!(!features.tips && features.imports && features.snapshots && features.reviews);
+        log!("[TEST] test_instance_settings completed successfully");
+    }
+
+    #[test]
+    fn test_client_error_report() {
+        log!("[TEST] Starting test_client_error_report");
+        let report = ClientErrorReport { message: format!(" {} ", "x".repeat(MAX_CLIENT_ERROR_LENGTH + 1)), page: " /laptops ".into() }.trimmed();
+        assert_eq!(report.message.len(), MAX_CLIENT_ERROR_LENGTH);
+        assert_eq!(report.page, "/laptops");
+        // Test a report can't add lines of its own to the bundle
+        let report = ClientErrorReport { message: "Failed\n2024-01-01T00:00:00+00:00 admin".into(), page: String::new() }.trimmed();
+        assert_eq!(report.message, "Failed 2024-01-01T00:00:00+00:00 admin");
+
+        // Reports without a page are accepted
+        let report: ClientErrorReport = serde_json::from_value(json!({"message": "Failed to save item"})).unwrap();
+        assert_eq!(round_trip(&report), json!({"message": "Failed to save item", "page": ""}));
+        log!("[TEST] test_client_error_report completed successfully");
+    }
+
+    #[test]
+    fn test_comparison_load() {
+        log!("[TEST] Starting test_comparison_load");
+        let rows = ComparisonRows { url: "https://example.com/laptops".into(), items: 3, values: 12, ..Default::default() };
+        let light = ComparisonLoad::new(rows.clone(), ComparisonRequests::default());
+        assert_eq!((light.average_ms, light.average_bytes), (0.0, 0));
+        assert!(light.remediations.is_empty());
+
+        // Test every remediation applies past its threshold
+        let rows = ComparisonRows { items: 60, inactive_items: 12, ..rows };
+        let requests = ComparisonRequests { requests: 4, total_ms: 2400, slowest_ms: 900, total_bytes: 4000, wikidata_cache_misses: 2 };
+        let heavy = ComparisonLoad::new(rows, requests);
+        assert_eq!((heavy.average_ms, heavy.average_bytes), (600.0, 1000));
+        assert_eq!(heavy.remediations.len(), 4, "{:?}", heavy.remediations);
+        assert!(heavy.remediations[0].contains("60 item columns"));
+        assert!(heavy.weight() > light.weight());
+        log!("[TEST] test_comparison_load completed successfully");
+    }
+
+    #[test]
+    fn test_publishing_settings() {
+        log!("[TEST] Starting test_publishing_settings");
+        let settings = PublishingSettings {
+            enabled: true,
+            relays: vec![" wss://relay.damus.io ".into(), String::new(), "wss://relay.damus.io".into(), "ws://localhost:7777".into()],
+        }
+        .trimmed();
+        assert_eq!(settings.relays, ["wss://relay.damus.io", "ws://localhost:7777"]);
+        assert!(settings.problem().is_none());
+        assert_eq!(round_trip(&settings)["enabled"], true);
+
+        // Publishing needs somewhere to publish to, disabling doesn't
+        assert!(PublishingSettings { enabled: true, relays: Vec::new() }.problem().is_some());
+        assert!(PublishingSettings::default().problem().is_none());
+        let relays = |relays: &[&str]| PublishingSettings { enabled: false, relays: relays.iter().map(|relay| relay.to_string()).collect() };
+        assert!(relays(&["https://relay.damus.io"]).problem().is_some());
+        assert!(relays(&["wss://"]).problem().is_some());
+        assert!(relays(&["wss://relay damus.io"]).problem().is_some());
+        assert!(relays(&["wss://nos.lol"; MAX_PUBLISHING_RELAYS + 1]).problem().is_some());
+        log!("[TEST] test_publishing_settings completed successfully");
+    }
+
+    #[test]
+    fn test_mirror_settings() {
+        log!("[TEST] Starting test_mirror_settings");
+        let key = "AB".repeat(32);
+        let settings = MirrorSettings {
+            enabled: true,
+            source_url: " /laptops ".into(),
+            author: format!(" {} ", key),
+            relays: vec!["wss://nos.lol".into(), " wss://nos.lol".into()],
+        }
+        .trimmed();
+        assert_eq!(settings.source_url, "/laptops");
+        assert_eq!(settings.author, "ab".repeat(32));
+        assert_eq!(settings.relays, ["wss://nos.lol"]);
+        assert!(settings.problem().is_none());
+
+        // Mirroring needs a source, its publisher and relays, disabling doesn't
+        assert!(MirrorSettings { source_url: String::new(), ..settings.clone() }.problem().is_some());
+        assert!(MirrorSettings { author: "npub1xyz".into(), ..settings.clone() }.problem().is_some());
+        assert!(MirrorSettings { relays: Vec::new(), ..settings.clone() }.problem().is_some());
+        assert!(MirrorSettings { relays: vec!["https://nos.lol".into()], ..settings.clone() }.problem().is_some());
+        assert!(MirrorSettings::default().problem().is_none());
+
+        // Items received under other settings only belong to the same mirror with the same source
+        assert!(settings.same_source(&MirrorSettings { enabled: false, relays: Vec::new(), ..settings.clone() }));
+        assert!(!settings.same_source(&MirrorSettings { source_url: "/phones".into(), ..settings.clone() }));
+        assert_eq!(round_trip(&settings)["source_url"], "/laptops");
+        log!("[TEST] test_mirror_settings completed successfully");
+    }
+
+    #[test]
+    fn test_property_ids() {
+        log!("[TEST] Starting test_property_ids");
+        let mass: PropertyId = "P2067".parse().unwrap();
+        assert_eq!(mass.to_string(), "P2067");
+        assert_eq!(mass.uri(), "http://www.wikidata.org/entity/P2067");
+        // Every form Wikidata names a property by is the same property
+        for form in ["http://www.wikidata.org/prop/P2067", "http://www.wikidata.org/prop/direct/P2067", "http://www.wikidata.org/entity/P2067", "https://www.wikidata.org/wiki/Property:P2067", "wdt:P2067", " P2067 "] {
+            assert_eq!(form.parse(), Ok(mass), "{}", form);
+            assert_eq!(canonical_property(form), "P2067");
+        }
+        for other in ["Q42", "P", "P018", "p18", "P18a", "mass", "http://www.wikidata.org/prop/Q42"] {
+            assert!(other.parse::<PropertyId>().is_err(), "{}", other);
+        }
+
+        // Custom names are kept as typed, only bare IDs count as stored Wikidata properties
+        assert_eq!(canonical_property("battery life"), "battery life");
+        assert!(is_property_id("P18"));
+        assert!(!is_property_id("wdt:P18"));
+        assert!(!is_property_id(" P18"));
+        log!("[TEST] test_property_ids completed successfully");
+    }
+
+    #[test]
+    fn test_field_errors() {
+        log!("[TEST] Starting test_field_errors");
+        let item = test_item();
+        assert!(item.field_errors().is_empty());
+        let request = ItemRequest { url: "/laptops".into(), item: item.clone() };
+        assert!(request.field_errors().is_empty());
+
+        // Fields are named by their path, nested values after the field holding them
+        let mut invalid = item.clone();
+        invalid.id = String::new();
+        invalid.name = "x".repeat(MAX_VALUE_LENGTH + 1);
+        invalid.custom_properties.insert("Q42".into(), "Douglas".into());
+        let errors = ItemRequest { url: "/laptops".into(), item: invalid.clone() }.field_errors();
+        assert_eq!(errors, vec![
+            FieldError { field: "item.id".into(), message: "must not be empty".into() },
+            FieldError { field: "item.name".into(), message: format!("must be at most {} characters", MAX_VALUE_LENGTH) },
+            FieldError { field: "item.custom_properties.Q42".into(), message: "must not look like a Wikidata ID".into() },
+        ]);
+        let fields: Vec<String> = vec![item, invalid].field_errors().into_iter().map(|error| error.field).collect();
+        assert_eq!(fields, ["[1].id", "[1].name", "[1].custom_properties.Q42"]);
+
+        // Property names are Wikidata property IDs or names fit for a column header
+        let order = |properties: &[&str]| PropertyOrder(properties.iter().map(|property| property.to_string()).collect());
+        assert!(order(&["P2067", "battery life"]).field_errors().is_empty());
+        assert_eq!(order(&["P2067", " weight"]).field_errors()[0].field, "[1]");
+        assert!(!order(&["a\u{7}b"]).field_errors().is_empty());
+
+        // Identifiers are single lines of bounded length, multi-line text keeps its line breaks
+        let heartbeat = Heartbeat { session_id: "x".repeat(MAX_ID_LENGTH + 1), cell: None };
+        assert_eq!(heartbeat.field_errors()[0].field, "session_id");
+        let decision = DecisionRequest { item_id: "item1".into(), rationale: "Lightest\nand cheapest".into() };
+        assert!(decision.field_errors().is_empty());
+        log!("[TEST] test_field_errors completed successfully");
+    }
+
+    #[test]
+    fn test_api_payload_round_trips() {
+        log!("[TEST] Starting test_api_payload_round_trips");
+        round_trip(&ErrorResponse { code: "not_found".into(), message: "Item not found".into(), details: Some(json!({"id": "item1"})) });
+        round_trip(&Heartbeat { session_id: "session".into(), cell: Some("item1:name".into()) });
+        round_trip(&PresenceState {
+            editors: 2,
+            locks: vec![CellLock { cell: "item1:name".into(), session_id: "session".into() }],
+        });
+        round_trip(&PropertySuggestion { property: "P2067".into(), label: "mass".into(), uses: 12 });
+        round_trip(&EntityClaims {
+            values: HashMap::from([("P18".into(), "Laptop.jpg".into())]),
+            quantities: HashMap::new(),
+            images: HashSet::from(["P18".into()]),
+        });
+        round_trip(&ComparisonListing { title: "Laptops".into(), category: "Hardware".into() });
+        round_trip(&ComparisonIndex {
+            comparisons: vec![ComparisonSummary {
+                url: "https://example.com/laptops".into(),
+                title: "Laptops".into(),
+                item_count: 2,
+                updated_at: 1718000000,
+                decided_at: Some(1718500000),
+            }],
+            total: 21,
+            page: 1,
+            per_page: 20,
+        });
+        round_trip(&TipRequest { amount_sats: 1000, comment: Some("Thanks".into()) });
+        round_trip(&TipInvoice { invoice: "lnbc1".into(), lightning_address: "vendor@getalby.com".into(), zap: true });
+        round_trip(&ReviewRequest { reviewer_id: "reviewer".into(), rating: 4 });
+        round_trip(&DeleteSummary { rows_affected: 3 });
+        round_trip(&PropertySelection { property: "P18".into(), custom: false });
+        round_trip(&PropertyColumn {
+            property: "P2067".into(),
+            values: HashMap::from([("item1".into(), "1.5 kg".into())]),
+            quantities: HashMap::from([("item1".into(), Quantity { amount: 1.5, unit_id: Some("Q11570".into()), unit: None, precision: None })]),
+            backfilled: 1,
+            sources: HashMap::from([("item1".into(), ValueSource::Wikidata)]),
+        });
+        round_trip(&PropertyDeletion { rows_affected: 3, deletion_id: 7 });
+
+        // Metadata is trimmed before it's checked
+        let metadata = ComparisonMetadata { title: " Laptops ".into(), description: String::new(), ..Default::default() }.trimmed();
+        assert_eq!(metadata.title, "Laptops");
+        assert!(metadata.problem().is_none());
+        assert!(ComparisonMetadata { title: "x".repeat(MAX_TITLE_LENGTH + 1), description: String::new(), ..Default::default() }.problem().is_some());
+        assert!(ComparisonMetadata { title: "a\nb".into(), description: String::new(), ..Default::default() }.problem().is_some());
+        let metadata: ComparisonMetadata = serde_json::from_value(json!({"title": "Laptops"})).unwrap();
+        assert!(metadata.description.is_empty());
+
+        // Decisions need an item and a rationale of reasonable length
+        let decided = round_trip(&ComparisonMetadata {
+            title: "Laptops".into(),
+            description: String::new(),
+            decision: Some(Decision { item_id: "item1".into(), decided_at: 1718500000, rationale: "Lightest".into() }),
+            unit_system: UnitSystem::Imperial,
+        });
+        assert_eq!(decided["unit_system"], "imperial");
+        assert_eq!(metadata.unit_system, UnitSystem::AsEntered);
+        round_trip(&DecisionRequest { item_id: "item1".into(), rationale: "Lightest".into() });
+        assert!(DecisionRequest { item_id: " ".into(), rationale: String::new() }.problem().is_some());
+        assert!(DecisionRequest { item_id: "item1".into(), rationale: "x".repeat(MAX_RATIONALE_LENGTH + 1) }.problem().is_some());
+
+        // Link previews describe comparisons without a description by their items
+        let names: Vec<String> = ["Laptop", "", "Tablet", "Phone", "Watch"].map(String::from).to_vec();
+        assert_eq!(metadata.link_description(&names), "Compare 4 items: Laptop, Tablet, Phone and 1 more");
+        assert_eq!(metadata.link_description(&names[..1]), "Compare 1 item: Laptop");
+        assert_eq!(metadata.link_description(&[]), "");
+        let described = ComparisonMetadata { title: String::new(), description: "Light laptops".into(), ..Default::default() };
+        assert_eq!(described.link_description(&names), "Light laptops");
+
+        // Unlisted comparisons are titled after their path
+        assert_eq!(default_title("https://example.com/laptops/"), "/laptops");
+        assert_eq!(default_title("https://example.com/"), "example.com");
+
+        // Selections of known properties may leave `custom` out
+        let selection: PropertySelection = serde_json::from_value(json!({"property": "price"})).unwrap();
+        assert!(!selection.custom);
+
+        // Summaries without the requester's own rating leave `mine` out
+        let summary = round_trip(&RatingSummary { average: 4.5, count: 2, mine: None });
+        assert_eq!(summary, json!({"average": 4.5, "count": 2}));
+        log!("[TEST] test_api_payload_round_trips completed successfully");
+    }
+}
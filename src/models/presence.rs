@@ -0,0 +1,42 @@
+/// Edit presence shared between the browser tabs working on a comparison URL.
+/// Each tab sends a heartbeat with the cell it is editing, which locks that cell
+/// for the other tabs until the session stops sending heartbeats.
+use serde::{Deserialize, Serialize};
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct Heartbeat {
+    pub session_id: String,
+    // Cell being edited, as "{item_id}:{property}"
+    pub cell: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct CellLock {
+    pub cell: String,
+    pub session_id: String,
+}
+
+/// Editors and cell locks of a URL, returned in response to a heartbeat.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct PresenceState {
+    pub editors: usize,
+    pub locks: Vec<CellLock>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct UrlEditors {
+    pub url: String,
+    pub editors: usize,
+}
+
+/// Concurrent editor counts across all URLs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct EditorMetrics {
+    pub active_editors: usize,
+    pub active_locks: usize,
+    pub urls: Vec<UrlEditors>,
+}
@@ -0,0 +1,304 @@
+/// A property suggested for a comparison, with how many sampled instances of
+/// the items' classes use it.
+use crate::models::item::ValueSource;
+use crate::models::quantity::Quantity;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct PropertySuggestion {
+    pub property: String,
+    pub label: String,
+    pub uses: u64,
+}
+
+/// Property values of a Wikidata entity: the display text of each property,
+/// the amount and unit of those holding quantities, and which hold Commons media files.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct EntityClaims {
+    pub values: HashMap<String, String>,
+    #[serde(default)]
+    pub quantities: HashMap<String, Quantity>,
+    #[serde(default)]
+    pub images: HashSet<String>,
+}
+
+/// Label and description of a Wikidata entity in the language of the instance, which
+/// the names and descriptions of its items are refreshed from.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct EntityTerms {
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Property values other comparisons on this instance hold for a Wikidata entity,
+/// including their editors' corrections, offered when the entity is added to a
+/// comparison instead of refetching it from Wikidata. Each property has the value
+/// most comparisons agree on.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ReusableValues {
+    pub wikidata_id: String,
+    // Other comparisons holding the entity
+    pub comparisons: usize,
+    pub values: HashMap<String, String>,
+    #[serde(default)]
+    pub quantities: HashMap<String, Quantity>,
+}
+
+/// Request body for selecting a property of a comparison. `property` is a Wikidata
+/// property ID such as "P18" or one of its URIs, or the name of a custom property.
+/// Custom properties nobody has used yet are only accepted when `custom` registers them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct PropertySelection {
+    pub property: String,
+    #[serde(default)]
+    pub custom: bool,
+}
+
+/// Request body for renaming a custom property of a comparison, keeping its values.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct PropertyRename {
+    pub rename_to: String,
+}
+
+/// Request body for reordering the selected properties of a comparison: their IDs or
+/// names in display order, sent as a plain JSON array.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+pub struct PropertyOrder(pub Vec<String>);
+
+// Longest accepted name of a custom property
+pub const MAX_CUSTOM_PROPERTY_LENGTH: usize = 100;
+
+// Why a custom property can't have a name, if it can't: names are shown as column
+// headers, and must not pass for Wikidata IDs
+pub fn custom_property_problem(property: &str) -> Option<&'static str> {
+    let digits = property.get(1..).unwrap_or_default();
+    let entity_id = (property.starts_with('Q') || property.starts_with('P')) && !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit());
+    if property.trim() != property {
+        Some("must not start or end with whitespace")
+    } else if property.chars().count() > MAX_CUSTOM_PROPERTY_LENGTH {
+        Some("is too long")
+    } else if property.chars().any(char::is_control) {
+        Some("must not contain control characters")
+    } else if entity_id {
+        Some("must not look like a Wikidata ID")
+    } else {
+        None
+    }
+}
+
+// Longest accepted language tag, e.g. "de" or "zh-Hant-TW" (BCP 47)
+pub const MAX_LANG_LENGTH: usize = 35;
+
+// Largest weight a property can have in the score of the items
+pub const MAX_WEIGHT: f64 = 100.0;
+
+/// How the grid edits the cells of a property in a comparison: the language of
+/// its values, for spell-checking, hyphenation and input methods, and whether
+/// the browser spell-checks them, which gets in the way of model numbers.
+/// Numeric properties can also tell which end of their values is best, so the
+/// grid highlights it, and how much they weigh in the score of the items. Unset
+/// fields leave the defaults. Keyed by property ID,
+/// or by "name" and "description" for the core rows.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct PropertySettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spellcheck: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub better: Option<BetterValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+}
+
+/// Which values of a numeric property are best, highlighted in the grid.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum BetterValue {
+    Higher,
+    Lower,
+}
+
+impl BetterValue {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BetterValue::Higher => "higher",
+            BetterValue::Lower => "lower",
+        }
+    }
+
+    // None for names of no direction, such as an empty one
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "higher" => Some(BetterValue::Higher),
+            "lower" => Some(BetterValue::Lower),
+            _ => None,
+        }
+    }
+}
+
+impl PropertySettings {
+    // Language tag without surrounding whitespace, left unset when blank
+    pub fn trimmed(&self) -> Self {
+        PropertySettings {
+            lang: self.lang.as_deref().map(str::trim).filter(|lang| !lang.is_empty()).map(String::from),
+            spellcheck: self.spellcheck,
+            better: self.better,
+            weight: self.weight,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == PropertySettings::default()
+    }
+
+    // Unknown cell language or weight out of range, None when the column can use them
+    pub fn problem(&self) -> Option<String> {
+        self.lang.as_deref().and_then(lang_problem).or_else(|| {
+            self.weight
+                .filter(|weight| !(0.0..=MAX_WEIGHT).contains(weight))
+                .map(|weight| format!("Weight {} is not between 0 and {}", weight, MAX_WEIGHT))
+        })
+    }
+}
+
+// Longest accepted tooltip of a property
+pub const MAX_PROPERTY_DESCRIPTION_LENGTH: usize = 500;
+
+/// Label and tooltip a comparison shows for a property instead of its Wikidata
+/// label or raw name, e.g. "Weight (g)" for P2067. Unset fields keep the defaults.
+/// Keyed like PropertySettings.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct PropertyOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl PropertyOverride {
+    // Fields without surrounding whitespace, left unset when blank
+    pub fn trimmed(&self) -> Self {
+        let trim = |field: &Option<String>| field.as_deref().map(str::trim).filter(|text| !text.is_empty()).map(String::from);
+        PropertyOverride { label: trim(&self.label), description: trim(&self.description) }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == PropertyOverride::default()
+    }
+
+    // Why the override can't be stored, if it can't
+    pub fn problem(&self) -> Option<String> {
+        let label = self.label.as_deref().unwrap_or_default();
+        let description = self.description.as_deref().unwrap_or_default();
+        if label.chars().count() > MAX_CUSTOM_PROPERTY_LENGTH {
+            Some(format!("Property labels are at most {} characters", MAX_CUSTOM_PROPERTY_LENGTH))
+        } else if label.chars().any(char::is_control) {
+            Some("Property labels must not contain control characters".into())
+        } else if description.chars().count() > MAX_PROPERTY_DESCRIPTION_LENGTH {
+            Some(format!("Property descriptions are at most {} characters", MAX_PROPERTY_DESCRIPTION_LENGTH))
+        } else {
+            None
+        }
+    }
+}
+
+// Why a language tag is not well-formed, if it isn't
+pub(crate) fn lang_problem(lang: &str) -> Option<String> {
+    let well_formed = lang.len() <= MAX_LANG_LENGTH
+        && lang.split('-').all(|part| !part.is_empty() && part.len() <= 8 && part.chars().all(|c| c.is_ascii_alphanumeric()));
+    (!well_formed).then(|| format!("{} is not a language tag, use e.g. \"en\" or \"pt-BR\"", lang))
+}
+
+/// Values of a property across the items of a comparison, keyed by item ID, as
+/// returned once the property is selected. Items without a value are left out.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct PropertyColumn {
+    pub property: String,
+    pub values: HashMap<String, String>,
+    #[serde(default)]
+    pub quantities: HashMap<String, Quantity>,
+    // Items whose value was filled in from the server's Wikidata cache
+    #[serde(default)]
+    pub backfilled: usize,
+    // Where the values not typed in came from, keyed by item ID
+    #[serde(default)]
+    pub sources: HashMap<String, ValueSource>,
+}
+
+/// Response of deleting a property, with the number of database rows changed and
+/// the ID of the deletion, which undoing it refers to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct PropertyDeletion {
+    pub rows_affected: usize,
+    pub deletion_id: i64,
+}
+
+// Forms of a property ID other than the bare one: the URIs SPARQL results name properties
+// by, links to their pages and the prefixed names of queries
+const PROPERTY_ID_PREFIXES: &[&str] = &[
+    "http://www.wikidata.org/prop/direct/",
+    "http://www.wikidata.org/prop/",
+    "http://www.wikidata.org/entity/",
+    "https://www.wikidata.org/wiki/Property:",
+    "wdt:",
+    "wd:",
+];
+
+/// Wikidata property ID such as `P18`, read from the bare ID or any of the URIs and
+/// prefixed names Wikidata uses for it, and shown as the bare ID comparisons store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PropertyId(u32);
+
+impl PropertyId {
+    // Entity URI, e.g. http://www.wikidata.org/entity/P18
+    pub fn uri(self) -> String {
+        format!("http://www.wikidata.org/entity/{}", self)
+    }
+}
+
+impl std::fmt::Display for PropertyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "P{}", self.0)
+    }
+}
+
+impl std::str::FromStr for PropertyId {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let id = PROPERTY_ID_PREFIXES.iter().find_map(|prefix| value.strip_prefix(prefix)).unwrap_or(value);
+        let digits = id.strip_prefix('P').ok_or(())?;
+        // Wikidata numbers have no leading zeros, "P018" isn't "P18"
+        if digits.starts_with('0') || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(());
+        }
+        digits.parse().map(PropertyId).map_err(|_| ())
+    }
+}
+
+// Wikidata property ID in its bare form, e.g. "P18"
+pub fn is_property_id(value: &str) -> bool {
+    value.parse::<PropertyId>().is_ok_and(|id| id.to_string() == value)
+}
+
+// Name a property is stored under: the bare ID of a Wikidata property in any of its
+// forms, the name of a custom property as it is
+pub fn canonical_property(property: &str) -> String {
+    property.parse::<PropertyId>().map_or_else(|_| property.to_string(), |id| id.to_string())
+}
@@ -0,0 +1,59 @@
+/// Nostr publishing of a comparison: whether its saved items are published as
+/// events signed with the workspace key, and the relays they are sent to.
+use serde::{Deserialize, Serialize};
+
+// Most relays a comparison publishes to
+pub const MAX_PUBLISHING_RELAYS: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct PublishingSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    // Relay URLs, e.g. "wss://relay.damus.io"
+    #[serde(default)]
+    pub relays: Vec<String>,
+}
+
+impl PublishingSettings {
+    // Relays without surrounding whitespace, blank and repeated ones left out
+    pub fn trimmed(&self) -> Self {
+        PublishingSettings { enabled: self.enabled, relays: trimmed_relays(&self.relays) }
+    }
+
+    // Publishing turned on without relays, or an invalid relay URL
+    pub fn problem(&self) -> Option<String> {
+        if self.enabled && self.relays.is_empty() {
+            Some("Publishing needs at least one relay".to_string())
+        } else {
+            relays_problem(&self.relays)
+        }
+    }
+}
+
+// Relays without surrounding whitespace, blank and repeated ones left out
+pub(crate) fn trimmed_relays(relays: &[String]) -> Vec<String> {
+    let mut trimmed: Vec<String> = Vec::new();
+    for relay in relays.iter().map(|relay| relay.trim()) {
+        if !relay.is_empty() && !trimmed.iter().any(|known| known == relay) {
+            trimmed.push(relay.to_string());
+        }
+    }
+    trimmed
+}
+
+// Why a list of relays can't be used, if it can't
+pub(crate) fn relays_problem(relays: &[String]) -> Option<String> {
+    if relays.len() > MAX_PUBLISHING_RELAYS {
+        return Some(format!("At most {} relays can be used", MAX_PUBLISHING_RELAYS));
+    }
+    relays
+        .iter()
+        .find(|relay| !is_relay_url(relay))
+        .map(|relay| format!("{} is not a relay URL, relays start with wss:// or ws://", relay))
+}
+
+fn is_relay_url(relay: &str) -> bool {
+    let host = relay.strip_prefix("wss://").or_else(|| relay.strip_prefix("ws://")).unwrap_or_default();
+    !host.is_empty() && !relay.chars().any(|c| c.is_whitespace() || c.is_control())
+}
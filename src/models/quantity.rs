@@ -0,0 +1,209 @@
+/// Numeric Wikidata value with its unit, kept next to the display text of a property
+/// so values can be converted to a common unit across items.
+use serde::{Deserialize, Serialize};
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct Quantity {
+    pub amount: f64,
+    // Unit entity, e.g. "Q11570" for kilogram. None for unitless values
+    #[serde(default)]
+    pub unit_id: Option<String>,
+    // Unit label from Wikidata, used when the unit has no known symbol
+    #[serde(default)]
+    pub unit: Option<String>,
+    // Half the width of the value's uncertainty interval
+    #[serde(default)]
+    pub precision: Option<f64>,
+}
+
+// Units that can be converted into each other: (unit ID, symbol, dimension, factor to the dimension's base unit)
+const UNITS: &[(&str, &str, &str, f64)] = &[
+    ("Q11570", "kg", "mass", 1.0),
+    ("Q41803", "g", "mass", 1e-3),
+    ("Q3241121", "mg", "mass", 1e-6),
+    ("Q191118", "t", "mass", 1e3),
+    ("Q100995", "lb", "mass", 0.453_592_37),
+    ("Q48013", "oz", "mass", 0.028_349_523_125),
+    ("Q11573", "m", "length", 1.0),
+    ("Q828224", "km", "length", 1e3),
+    ("Q174728", "cm", "length", 1e-2),
+    ("Q174789", "mm", "length", 1e-3),
+    ("Q175821", "µm", "length", 1e-6),
+    ("Q178674", "nm", "length", 1e-9),
+    ("Q218593", "in", "length", 0.0254),
+    ("Q3710", "ft", "length", 0.3048),
+    ("Q253276", "mi", "length", 1_609.344),
+    ("Q11574", "s", "time", 1.0),
+    ("Q723733", "ms", "time", 1e-3),
+    ("Q7727", "min", "time", 60.0),
+    ("Q25235", "h", "time", 3_600.0),
+    ("Q573", "d", "time", 86_400.0),
+    ("Q39369", "Hz", "frequency", 1.0),
+    ("Q732707", "MHz", "frequency", 1e6),
+    ("Q3276763", "GHz", "frequency", 1e9),
+    ("Q8799", "B", "information", 1.0),
+    ("Q79735", "kB", "information", 1e3),
+    ("Q79738", "MB", "information", 1e6),
+    ("Q79741", "GB", "information", 1e9),
+    ("Q79756", "TB", "information", 1e12),
+    ("Q11582", "L", "volume", 1e-3),
+    ("Q2332346", "mL", "volume", 1e-6),
+    ("Q25517", "m³", "volume", 1.0),
+    ("Q182429", "m/s", "speed", 1.0),
+    ("Q180154", "km/h", "speed", 1.0 / 3.6),
+    ("Q211256", "mph", "speed", 0.447_04),
+    ("Q25236", "W", "power", 1.0),
+    ("Q25250", "V", "voltage", 1.0),
+    ("Q25272", "A", "current", 1.0),
+];
+
+/// Unit system values are converted to in the exports of a comparison.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum UnitSystem {
+    // Values keep the unit they were entered in
+    #[default]
+    AsEntered,
+    Metric,
+    Imperial,
+}
+
+impl UnitSystem {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnitSystem::AsEntered => "as_entered",
+            UnitSystem::Metric => "metric",
+            UnitSystem::Imperial => "imperial",
+        }
+    }
+
+    // Unknown names, such as the empty one of comparisons that never chose, keep values as entered
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "metric" => UnitSystem::Metric,
+            "imperial" => UnitSystem::Imperial,
+            _ => UnitSystem::AsEntered,
+        }
+    }
+}
+
+// Unit of the other system a unit is converted to, of a similar size: (unit ID, counterpart ID)
+const METRIC_COUNTERPARTS: &[(&str, &str)] = &[
+    ("Q100995", "Q11570"),  // lb to kg
+    ("Q48013", "Q41803"),   // oz to g
+    ("Q218593", "Q174728"), // in to cm
+    ("Q3710", "Q11573"),    // ft to m
+    ("Q253276", "Q828224"), // mi to km
+    ("Q211256", "Q180154"), // mph to km/h
+];
+const IMPERIAL_COUNTERPARTS: &[(&str, &str)] = &[
+    ("Q11570", "Q100995"),   // kg to lb
+    ("Q191118", "Q100995"),  // t to lb
+    ("Q41803", "Q48013"),    // g to oz
+    ("Q3241121", "Q48013"),  // mg to oz
+    ("Q11573", "Q3710"),     // m to ft
+    ("Q828224", "Q253276"),  // km to mi
+    ("Q174728", "Q218593"),  // cm to in
+    ("Q174789", "Q218593"),  // mm to in
+    ("Q180154", "Q211256"),  // km/h to mph
+    ("Q182429", "Q211256"),  // m/s to mph
+];
+
+fn unit_info(unit_id: &str) -> Option<(&'static str, &'static str, f64)> {
+    UNITS
+        .iter()
+        .find(|(id, ..)| *id == unit_id)
+        .map(|(_, symbol, dimension, factor)| (*symbol, *dimension, *factor))
+}
+
+// Format an amount without more digits than its precision warrants,
+// or with up to four significant digits when the precision is unknown
+fn format_amount(amount: f64, precision: Option<f64>) -> String {
+    let decimals = match precision.filter(|precision| *precision > 0.0) {
+        Some(precision) => (-precision.log10().floor()).max(0.0) as usize,
+        None if amount == 0.0 => 0,
+        None => (3.0 - amount.abs().log10().floor()).max(0.0) as usize,
+    };
+    let text = format!("{:.*}", decimals.min(12), amount);
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+impl Quantity {
+    // Short name of the unit, "kg" rather than "kilogram" when the unit is known
+    pub fn unit_symbol(&self) -> Option<String> {
+        let unit_id = self.unit_id.as_deref()?;
+        unit_info(unit_id)
+            .map(|(symbol, ..)| symbol.to_string())
+            .or_else(|| self.unit.clone())
+            .or_else(|| Some(unit_id.to_string()))
+    }
+
+    // Display text such as "1.2 kg"
+    pub fn to_text(&self) -> String {
+        let amount = format_amount(self.amount, self.precision);
+        match self.unit_symbol() {
+            Some(symbol) => format!("{} {}", amount, symbol),
+            None => amount,
+        }
+    }
+
+    // The same quantity in another unit of the same dimension
+    pub fn convert_to(&self, unit_id: &str) -> Option<Quantity> {
+        if self.unit_id.as_deref() == Some(unit_id) {
+            return Some(self.clone());
+        }
+        let (_, dimension, factor) = unit_info(self.unit_id.as_deref()?)?;
+        let (_, target_dimension, target_factor) = unit_info(unit_id)?;
+        if dimension != target_dimension {
+            return None;
+        }
+        let ratio = factor / target_factor;
+        Some(Quantity {
+            amount: self.amount * ratio,
+            unit_id: Some(unit_id.to_string()),
+            unit: None,
+            precision: self.precision.map(|precision| precision * ratio),
+        })
+    }
+
+    // The quantity in the unit system, None when it's in that system already or has no
+    // counterpart there, like unitless values, seconds or bytes
+    pub fn in_system(&self, system: UnitSystem) -> Option<Quantity> {
+        let counterparts = match system {
+            UnitSystem::AsEntered => return None,
+            UnitSystem::Metric => METRIC_COUNTERPARTS,
+            UnitSystem::Imperial => IMPERIAL_COUNTERPARTS,
+        };
+        let unit_id = self.unit_id.as_deref()?;
+        let (_, counterpart) = counterparts.iter().find(|(id, _)| *id == unit_id)?;
+        self.convert_to(counterpart)
+    }
+
+    // Whether a text is how this quantity is displayed, in its own unit or a normalized one
+    pub fn is_shown_as(&self, text: &str) -> bool {
+        text == self.to_text()
+            || UNITS
+                .iter()
+                .filter_map(|(unit_id, ..)| self.convert_to(unit_id))
+                .any(|converted| converted.to_text() == text)
+    }
+}
+
+// Unit to compare quantities in: the most common unit among them, so most values keep
+// the unit they were entered in. Ties go to the unit that comes first
+pub fn common_unit<'a>(quantities: impl IntoIterator<Item = &'a Quantity>) -> Option<String> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for unit_id in quantities.into_iter().filter_map(|quantity| quantity.unit_id.clone()) {
+        match counts.iter_mut().find(|(id, _)| *id == unit_id) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((unit_id, 1)),
+        }
+    }
+    let max = counts.iter().map(|(_, count)| *count).max()?;
+    counts.into_iter().find(|(_, count)| *count == max).map(|(unit_id, _)| unit_id)
+}
@@ -0,0 +1,27 @@
+/// A comparison published on Nostr by another instance, stored by its event
+/// address ("kind:pubkey:d-tag") so that each comparison is kept only once.
+use serde::{Deserialize, Serialize};
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct RemoteComparison {
+    pub address: String,
+    pub event_id: String,
+    pub author: String,
+    pub created_at: i64,
+    pub content: String,
+    // Relays the current version was received from
+    pub relays: Vec<String>,
+}
+
+/// Outcome of storing a received comparison event.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteUpdate {
+    // First version seen for this address
+    Inserted,
+    // Newer version that replaced the stored one
+    Replaced,
+    // Same event again, possibly from another relay
+    Duplicate,
+    // Older version than the stored one, ignored
+    Stale,
+}
@@ -0,0 +1,100 @@
+/// A reviewer's 1–5 star rating of a comparison item. Each reviewer has one
+/// review per item, rating again replaces it.
+use serde::{Deserialize, Serialize};
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ReviewRequest {
+    // Random ID the browser keeps in local storage
+    pub reviewer_id: String,
+    pub rating: u8,
+}
+
+/// Average rating and review count of an item.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct RatingSummary {
+    pub average: f64,
+    pub count: u64,
+    // Rating of the reviewer the summary was requested for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mine: Option<u8>,
+}
+
+pub const MIN_RATING: u8 = 1;
+pub const MAX_RATING: u8 = 5;
+
+/// Review carried by a comparison export, its reviewer told apart by a label rather than
+/// their ID. An import gives each label a new reviewer, so their reviews stay together.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct ExportedReview {
+    pub item_id: String,
+    pub reviewer: String,
+    pub rating: u8,
+    pub created_at: i64,
+}
+
+/// Most reviews listed in a comparison's feed, the newest ones.
+pub const REVIEW_FEED_SIZE: usize = 50;
+
+/// Review as listed in the feed of a comparison.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReviewEntry {
+    pub item_id: String,
+    pub item_name: String,
+    // Label telling reviewers apart, never their ID, which lets anyone rate in their name
+    pub reviewer: String,
+    pub rating: u8,
+    // Unix time of the rating, rating again moves the review to the top
+    pub created_at: i64,
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+pub(crate) fn atom_time(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Atom feed of the reviews of a comparison, newest first. `feed_url` is the feed's own
+/// address and `now` its update time when there are no reviews yet.
+pub fn reviews_atom(comparison_url: &str, title: &str, feed_url: &str, reviews: &[ReviewEntry], now: i64) -> String {
+    let updated = reviews.iter().map(|review| review.created_at).max().unwrap_or(now);
+    let mut atom = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n<id>{feed}</id>\n<title>Reviews of {}</title>\n<link rel=\"self\" href=\"{feed}\"/>\n<link href=\"{}\"/>\n<updated>{}</updated>\n<author><name>CompareWare</name></author>\n",
+        escape_xml(title),
+        escape_xml(comparison_url),
+        atom_time(updated),
+        feed = escape_xml(feed_url),
+    );
+    for review in reviews {
+        let item_name = if review.item_name.is_empty() { "an unnamed item" } else { &review.item_name };
+        let stars = if review.rating == 1 { "star" } else { "stars" };
+        atom.push_str(&format!(
+            "<entry>\n<id>{}#{}/{}/{}</id>\n<title>{} {} for {}</title>\n<link href=\"{}\"/>\n<updated>{}</updated>\n<author><name>{}</name></author>\n<content type=\"text\">{} rated {} {} out of {}</content>\n</entry>\n",
+            escape_xml(feed_url),
+            escape_xml(&review.item_id),
+            escape_xml(&review.reviewer),
+            review.created_at,
+            review.rating,
+            stars,
+            escape_xml(item_name),
+            escape_xml(comparison_url),
+            atom_time(review.created_at),
+            escape_xml(&review.reviewer),
+            escape_xml(&review.reviewer),
+            escape_xml(item_name),
+            review.rating,
+            MAX_RATING,
+        ));
+    }
+    atom.push_str("</feed>\n");
+    atom
+}
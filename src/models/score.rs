@@ -0,0 +1,72 @@
+/// Weighted scores turning a comparison into a ranking: every property with a weight
+/// and a best value adds its weight times how close an item comes to the best value.
+use crate::models::chart::chart_data;
+use crate::models::item::Item;
+use crate::models::property::{BetterValue, PropertySettings};
+use std::collections::HashMap;
+
+// Score of an item out of 100 and its place in the ranking, tied items sharing one
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemScore {
+    pub item_id: String,
+    pub score: f64,
+    pub rank: usize,
+}
+
+// Share of the way from the worst to the best value `value` gets, 1 when all values are
+// the same
+fn closeness(value: f64, min: f64, max: f64, better: BetterValue) -> f64 {
+    if max == min {
+        return 1.0;
+    }
+    match better {
+        BetterValue::Higher => (value - min) / (max - min),
+        BetterValue::Lower => (max - value) / (max - min),
+    }
+}
+
+// Scores of the named items, best first, over the `properties` whose settings have both
+// a weight and a best value. Items without a number in such a property get nothing for
+// it. Empty when no property is weighted
+pub fn item_scores(items: &[Item], properties: &[String], settings: &HashMap<String, PropertySettings>) -> Vec<ItemScore> {
+    let mut points: HashMap<String, f64> = HashMap::new();
+    let mut total_weight = 0.0;
+    for property in properties {
+        let Some((weight, better)) = settings.get(property).and_then(|settings| Some((settings.weight?, settings.better?))) else {
+            continue;
+        };
+        if weight <= 0.0 {
+            continue;
+        }
+        total_weight += weight;
+        let data = chart_data(items, property);
+        let Some((min, max)) = data.values.iter().map(|value| value.value).fold(None, |range: Option<(f64, f64)>, value| {
+            Some(range.map_or((value, value), |(min, max)| (min.min(value), max.max(value))))
+        }) else {
+            continue;
+        };
+        for value in &data.values {
+            *points.entry(value.item_id.clone()).or_default() += weight * closeness(value.value, min, max, better);
+        }
+    }
+    if total_weight == 0.0 {
+        return Vec::new();
+    }
+    let mut scores: Vec<ItemScore> = items
+        .iter()
+        .filter(|item| !item.name.is_empty())
+        .map(|item| ItemScore {
+            item_id: item.id.clone(),
+            score: 100.0 * points.get(&item.id).copied().unwrap_or_default() / total_weight,
+            rank: 0,
+        })
+        .collect();
+    scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+    for index in 0..scores.len() {
+        scores[index].rank = match index.checked_sub(1).map(|previous| &scores[previous]) {
+            Some(previous) if previous.score == scores[index].score => previous.rank,
+            _ => index + 1,
+        };
+    }
+    scores
+}
@@ -0,0 +1,61 @@
+/// Item found by the search across all comparisons of the instance.
+use serde::{Deserialize, Serialize};
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct SearchHit {
+    pub url: String,
+    // Title of the comparison, as on the index page
+    pub title: String,
+    pub item_id: String,
+    pub item_name: String,
+    // Where the text was found: "name", "description" or a property ID
+    pub property: String,
+    // Text around the match, the matching words marked
+    pub snippet: Vec<SnippetPart>,
+}
+
+/// Piece of a search snippet, matching the searched words or not.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct SnippetPart {
+    pub text: String,
+    #[serde(default)]
+    pub matched: bool,
+}
+
+/// Most items returned by a search, the best matches.
+pub const SEARCH_RESULTS_LIMIT: usize = 50;
+
+/// Longest text searched for, in characters.
+pub const MAX_SEARCH_LENGTH: usize = 200;
+
+// Characters the database puts around the matching words of a snippet, control
+// characters no cell value is typed with
+pub const MATCH_START: char = '\u{2}';
+pub const MATCH_END: char = '\u{3}';
+
+/// Words of a search, each a prefix of the words to find. Punctuation separates words
+/// the way the full-text index splits values, so it's left out.
+pub fn search_terms(search: &str) -> Vec<String> {
+    search
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Split a snippet with marked matches into its parts.
+pub fn snippet_parts(marked: &str) -> Vec<SnippetPart> {
+    let mut parts = Vec::new();
+    let mut rest = marked;
+    while let Some(start) = rest.find(MATCH_START) {
+        let after = &rest[start + MATCH_START.len_utf8()..];
+        let end = after.find(MATCH_END).unwrap_or(after.len());
+        parts.push(SnippetPart { text: rest[..start].to_string(), matched: false });
+        parts.push(SnippetPart { text: after[..end].to_string(), matched: true });
+        rest = after.get(end + MATCH_END.len_utf8()..).unwrap_or("");
+    }
+    parts.push(SnippetPart { text: rest.to_string(), matched: false });
+    parts.retain(|part| !part.text.is_empty());
+    parts
+}
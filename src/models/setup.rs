@@ -0,0 +1,35 @@
+/// First run of a new instance: while the database is empty and no admin token is
+/// set, /setup offers to choose one, pick public or private mode and the Wikidata
+/// language, and seed a few demo comparisons. Once done, the setup is closed for good.
+use serde::{Deserialize, Serialize};
+
+/// Whether the setup is still open, served at `GET /api/setup`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct SetupStatus {
+    pub needed: bool,
+}
+
+/// Choices of the setup wizard, sent to `POST /api/setup`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct SetupRequest {
+    // Private instances hide their comparisons index and don't announce comparisons on Nostr
+    #[serde(default)]
+    pub private: bool,
+    // Language tag of Wikidata searches and imports, the default one when blank
+    #[serde(default)]
+    pub wikidata_language: String,
+    // Fill the instance with a few example comparisons
+    #[serde(default)]
+    pub demo_data: bool,
+}
+
+/// Outcome of the setup. The admin token is only ever shown here, the server keeps its hash.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct SetupResult {
+    pub admin_token: String,
+    // URLs of the demo comparisons created, empty without demo data
+    pub demo_comparisons: Vec<String>,
+}
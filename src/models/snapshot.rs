@@ -0,0 +1,32 @@
+/// A frozen copy of a comparison, shared at `/s/{id}`. Snapshots never change,
+/// later edits of the comparison only show up in new snapshots.
+use crate::models::item::Item;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct Snapshot {
+    pub id: String,
+    // Comparison the snapshot was taken of
+    pub url: String,
+    pub created_at: i64,
+    // Items with their selected properties only, in display order
+    pub items: Vec<Item>,
+    pub selected_properties: Vec<String>,
+    // Wikidata property labels known when the snapshot was taken
+    #[serde(default)]
+    pub property_labels: HashMap<String, String>,
+}
+
+/// Where a newly taken snapshot can be found.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct SnapshotLink {
+    pub id: String,
+    // Page showing the snapshot, e.g. "/s/0f3a…"
+    pub path: String,
+}
+
+pub fn snapshot_path(id: &str) -> String {
+    format!("/s/{}", id)
+}
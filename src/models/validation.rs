@@ -0,0 +1,380 @@
+/// Field-level checks of the JSON bodies the API accepts, run before a handler sees them,
+/// so nothing malformed reaches SQLite. A body failing them is answered with a 422 whose
+/// `details` lists every field in error, e.g.
+/// `{"fields": [{"field": "comparison.items[0].id", "message": "must not be empty"}]}`.
+/// Checks of what the values mean, such as whether an item belongs to the comparison or a
+/// relay URL is valid, stay with the handlers and the types' own `problem` methods.
+use crate::models::access::VisibilitySetting;
+use crate::models::api_key::ApiKeyRequest;
+use crate::models::backup::{ComparisonArchive, ComparisonExport, RestoreRequest};
+use crate::models::diagnostics::ClientErrorReport;
+use crate::models::directory::ComparisonListing;
+use crate::models::export::SignedLinkRequest;
+use crate::models::instance::InstanceSettings;
+use crate::models::item::{Item, ItemQuery, ItemRequest, MAX_VALUE_LENGTH};
+use crate::models::metadata::{ComparisonMetadata, DecisionRequest, MAX_DESCRIPTION_LENGTH, MAX_RATIONALE_LENGTH, MAX_TITLE_LENGTH};
+use crate::models::mirror::MirrorSettings;
+use crate::models::presence::Heartbeat;
+use crate::models::property::{custom_property_problem, is_property_id, PropertyOrder, PropertyOverride, PropertyRename, PropertySelection, PropertySettings, MAX_CUSTOM_PROPERTY_LENGTH, MAX_LANG_LENGTH, MAX_PROPERTY_DESCRIPTION_LENGTH};
+use crate::models::publishing::PublishingSettings;
+use crate::models::review::{ExportedReview, ReviewRequest};
+use crate::models::setup::SetupRequest;
+use crate::models::zap::TipRequest;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Longest accepted identifier, such as an item, session or reviewer ID
+pub const MAX_ID_LENGTH: usize = 128;
+
+// Longest accepted comparison or relay URL
+pub const MAX_URL_LENGTH: usize = 2048;
+
+// Longest accepted short text without a limit of its own, such as a tip comment
+pub const MAX_TEXT_LENGTH: usize = 1000;
+
+/// A field of a request body and what is wrong with it, the field named by its path,
+/// e.g. `item.custom_properties.P2067`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+// Problems found in a body so far, the fields of nested values prefixed with their path
+#[derive(Debug, Default)]
+pub struct FieldErrors {
+    prefix: String,
+    errors: Vec<FieldError>,
+}
+
+// Path of `field` under `prefix`, list indexes joined without a dot and an empty field
+// naming the value at `prefix` itself
+fn field_path(prefix: &str, field: &str) -> String {
+    if prefix.is_empty() || field.is_empty() || field.starts_with('[') {
+        format!("{}{}", prefix, field)
+    } else {
+        format!("{}.{}", prefix, field)
+    }
+}
+
+impl FieldErrors {
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.errors.push(FieldError { field: field_path(&self.prefix, field), message: message.into() });
+    }
+
+    // Problem found by a type's own check, if any
+    pub fn check(&mut self, field: &str, problem: Option<impl Into<String>>) {
+        if let Some(problem) = problem {
+            self.add(field, problem);
+        }
+    }
+
+    // Text of at most `max` characters, without control characters other than line breaks and tabs
+    pub fn text(&mut self, field: &str, value: &str, max: usize) {
+        if value.chars().count() > max {
+            self.add(field, format!("must be at most {} characters", max));
+        } else if value.chars().any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t')) {
+            self.add(field, "must not contain control characters");
+        }
+    }
+
+    // Single line of at most `max` characters
+    pub fn line(&mut self, field: &str, value: &str, max: usize) {
+        if value.chars().count() > max {
+            self.add(field, format!("must be at most {} characters", max));
+        } else if value.chars().any(char::is_control) {
+            self.add(field, "must not contain control characters");
+        }
+    }
+
+    // Single line that isn't blank
+    pub fn required(&mut self, field: &str, value: &str, max: usize) {
+        if value.trim().is_empty() {
+            self.add(field, "must not be empty");
+        } else {
+            self.line(field, value, max);
+        }
+    }
+
+    // Identifier such as an item ID
+    pub fn id(&mut self, field: &str, value: &str) {
+        self.required(field, value, MAX_ID_LENGTH);
+    }
+
+    // Wikidata property ID or name of a custom property
+    pub fn property(&mut self, field: &str, property: &str) {
+        if property.is_empty() {
+            self.add(field, "must not be empty");
+        } else if !is_property_id(property) {
+            self.check(field, custom_property_problem(property));
+        }
+    }
+
+    // List of property IDs or names, each field named after its index
+    pub fn properties(&mut self, field: &str, properties: &[String]) {
+        for (index, property) in properties.iter().enumerate() {
+            self.property(&format!("{}[{}]", field, index), property);
+        }
+    }
+
+    // Wikidata item or property ID, e.g. "Q42"
+    pub fn entity_id(&mut self, field: &str, value: &str) {
+        let digits = value.get(1..).unwrap_or_default();
+        let entity = (value.starts_with('Q') || value.starts_with('P')) && !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit());
+        if !entity {
+            self.add(field, "must be a Wikidata ID such as Q42");
+        }
+    }
+
+    // Fields of a nested value, named after the field holding it
+    pub fn nested(&mut self, field: &str, value: &(impl Validate + ?Sized)) {
+        let path = field_path(&self.prefix, field);
+        let outer = std::mem::replace(&mut self.prefix, path);
+        value.validate(self);
+        self.prefix = outer;
+    }
+
+    pub fn into_errors(self) -> Vec<FieldError> {
+        self.errors
+    }
+}
+
+// A request body that can tell which of its fields are invalid
+pub trait Validate {
+    fn validate(&self, errors: &mut FieldErrors);
+
+    // Every problem of the body, empty when it is valid
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = FieldErrors::default();
+        self.validate(&mut errors);
+        errors.into_errors()
+    }
+}
+
+impl<T: Validate> Validate for [T] {
+    fn validate(&self, errors: &mut FieldErrors) {
+        for (index, value) in self.iter().enumerate() {
+            errors.nested(&format!("[{}]", index), value);
+        }
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate(&self, errors: &mut FieldErrors) {
+        self.as_slice().validate(errors);
+    }
+}
+
+impl Validate for PropertyOrder {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.properties("", &self.0);
+    }
+}
+
+impl Validate for Item {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.id("id", &self.id);
+        errors.text("name", &self.name, MAX_VALUE_LENGTH);
+        errors.text("description", &self.description, MAX_VALUE_LENGTH);
+        if let Some(wikidata_id) = &self.wikidata_id {
+            errors.entity_id("wikidata_id", wikidata_id);
+        }
+        for (property, value) in &self.custom_properties {
+            let field = format!("custom_properties.{}", property);
+            errors.property(&field, property);
+            errors.text(&field, value, MAX_VALUE_LENGTH);
+        }
+        for property in self.quantities.keys() {
+            errors.property(&format!("quantities.{}", property), property);
+        }
+        for property in self.sources.keys() {
+            errors.property(&format!("sources.{}", property), property);
+        }
+        if let Some(group) = &self.group {
+            errors.line("group", group, MAX_TEXT_LENGTH);
+        }
+        if let Some(recipient) = &self.tip_recipient {
+            errors.line("tip_recipient", recipient, MAX_TEXT_LENGTH);
+        }
+    }
+}
+
+impl Validate for ItemRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.required("url", &self.url, MAX_URL_LENGTH);
+        errors.nested("item", &self.item);
+    }
+}
+
+impl Validate for ItemQuery {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.required("query", &self.query, MAX_TEXT_LENGTH);
+    }
+}
+
+impl Validate for PropertySelection {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.property.trim().is_empty() {
+            errors.add("property", "must not be empty");
+        } else {
+            errors.line("property", &self.property, MAX_CUSTOM_PROPERTY_LENGTH);
+        }
+    }
+}
+
+impl Validate for PropertyRename {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.property("rename_to", &self.rename_to);
+    }
+}
+
+impl Validate for PropertySettings {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.line("lang", self.lang.as_deref().unwrap_or_default(), MAX_LANG_LENGTH);
+    }
+}
+
+impl Validate for PropertyOverride {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.line("label", self.label.as_deref().unwrap_or_default(), MAX_CUSTOM_PROPERTY_LENGTH);
+        errors.text("description", self.description.as_deref().unwrap_or_default(), MAX_PROPERTY_DESCRIPTION_LENGTH);
+    }
+}
+
+impl Validate for ComparisonMetadata {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.line("title", &self.title, MAX_TITLE_LENGTH);
+        errors.text("description", &self.description, MAX_DESCRIPTION_LENGTH);
+    }
+}
+
+impl Validate for DecisionRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.id("item_id", &self.item_id);
+        errors.text("rationale", &self.rationale, MAX_RATIONALE_LENGTH);
+    }
+}
+
+impl Validate for ComparisonArchive {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.line("url", &self.url, MAX_URL_LENGTH);
+        errors.nested("items", &self.items);
+        errors.properties("selected_properties", &self.selected_properties);
+        errors.nested("metadata", &self.metadata);
+    }
+}
+
+impl Validate for ComparisonExport {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.nested("comparison", &self.comparison);
+        by_property(errors, "property_settings", &self.property_settings);
+        by_property(errors, "property_overrides", &self.property_overrides);
+        errors.nested("reviews", &self.reviews);
+    }
+}
+
+// Settings or overrides keyed by property
+fn by_property(errors: &mut FieldErrors, field: &str, values: &HashMap<String, impl Validate>) {
+    for (property, value) in values {
+        let field = format!("{}.{}", field, property);
+        errors.property(&field, property);
+        errors.nested(&field, value);
+    }
+}
+
+impl Validate for ComparisonListing {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.required("title", &self.title, MAX_TITLE_LENGTH);
+        errors.line("category", &self.category, MAX_TITLE_LENGTH);
+    }
+}
+
+impl Validate for ReviewRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.id("reviewer_id", &self.reviewer_id);
+    }
+}
+
+impl Validate for ExportedReview {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.id("item_id", &self.item_id);
+        errors.id("reviewer", &self.reviewer);
+    }
+}
+
+impl Validate for TipRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.text("comment", self.comment.as_deref().unwrap_or_default(), MAX_TEXT_LENGTH);
+    }
+}
+
+impl Validate for Heartbeat {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.id("session_id", &self.session_id);
+        errors.line("cell", self.cell.as_deref().unwrap_or_default(), MAX_ID_LENGTH + MAX_CUSTOM_PROPERTY_LENGTH + 1);
+    }
+}
+
+// Reports are cut to their limit rather than refused, only their size is bounded here
+impl Validate for ClientErrorReport {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.text("message", &self.message, MAX_VALUE_LENGTH);
+        errors.text("page", &self.page, MAX_URL_LENGTH);
+    }
+}
+
+impl Validate for SignedLinkRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.id("format", &self.format);
+    }
+}
+
+impl Validate for VisibilitySetting {
+    fn validate(&self, _errors: &mut FieldErrors) {}
+}
+
+impl Validate for PublishingSettings {
+    fn validate(&self, errors: &mut FieldErrors) {
+        for (index, relay) in self.relays.iter().enumerate() {
+            errors.line(&format!("relays[{}]", index), relay, MAX_URL_LENGTH);
+        }
+    }
+}
+
+impl Validate for MirrorSettings {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.line("source_url", &self.source_url, MAX_URL_LENGTH);
+        errors.line("author", &self.author, MAX_ID_LENGTH);
+        for (index, relay) in self.relays.iter().enumerate() {
+            errors.line(&format!("relays[{}]", index), relay, MAX_URL_LENGTH);
+        }
+    }
+}
+
+impl Validate for InstanceSettings {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.line("wikidata_language", &self.wikidata_language, MAX_LANG_LENGTH);
+    }
+}
+
+impl Validate for SetupRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.line("wikidata_language", &self.wikidata_language, MAX_LANG_LENGTH);
+    }
+}
+
+impl Validate for ApiKeyRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.line("name", &self.name, MAX_TEXT_LENGTH);
+        errors.line("url", self.url.as_deref().unwrap_or_default(), MAX_URL_LENGTH);
+    }
+}
+
+impl Validate for RestoreRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if let Some(backup_id) = &self.backup_id {
+            errors.id("backup_id", backup_id);
+        }
+    }
+}
@@ -0,0 +1,291 @@
+/// Requests the pages send to wikidata.org and the parsing of its answers, kept
+/// apart from the components so tests can replay recorded responses through them.
+use crate::models::property::{EntityClaims, EntityTerms};
+use crate::models::quantity::Quantity;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+pub const WIKIDATA_API: &str = "https://www.wikidata.org/w/api.php";
+pub const WIKIDATA_SPARQL: &str = "https://query.wikidata.org/sparql";
+// Wikidata suggestions shown while typing an item name
+const SEARCH_LIMIT: usize = 5;
+
+// Entity offered while typing an item name
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct WikidataSuggestion {
+    pub id: String,
+    pub label: String,
+    pub description: Option<String>,
+}
+
+// Answer of wbsearchentities
+#[derive(Deserialize, Clone, Debug)]
+pub struct WikidataSearchResponse {
+    pub search: Vec<WikidataSuggestion>,
+}
+
+// Confidence from which a proposed Wikidata match is accepted unless the editor unticks it
+pub const LIKELY_MATCH: f64 = 0.85;
+
+// Lowercase letters and digits of a name, the rest of it doesn't tell entities apart
+pub(crate) fn normalized_name(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+// Similarity of two names between 0 and 1, the Dice coefficient of their character pairs,
+// so "iPhone 15" is closer to "iPhone 15 Pro" than to "iPhone 5"
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalized_name(a), normalized_name(b));
+    if a == b {
+        return if a.is_empty() { 0.0 } else { 1.0 };
+    }
+    let pairs = |name: &str| {
+        let chars: Vec<char> = name.chars().collect();
+        chars.windows(2).map(|pair| (pair[0], pair[1])).collect::<Vec<_>>()
+    };
+    let (a, mut b) = (pairs(&a), pairs(&b));
+    let total = a.len() + b.len();
+    if total == 0 {
+        return 0.0;
+    }
+    let mut shared = 0;
+    for pair in a {
+        if let Some(position) = b.iter().position(|other| *other == pair) {
+            b.swap_remove(position);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f64 / total as f64
+}
+
+// Entity of a search answer most likely meant by an item name, with the confidence of the
+// match between 0 and 1. Wikidata ranks its answers, entities after the first count a bit less
+pub fn best_match(name: &str, candidates: &[WikidataSuggestion]) -> Option<(WikidataSuggestion, f64)> {
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(rank, candidate)| {
+            let rank_weight = if rank == 0 { 1.0 } else { 0.9 };
+            (candidate, name_similarity(name, &candidate.label) * rank_weight)
+        })
+        .filter(|(_, confidence)| *confidence > 0.0)
+        // The best ranked of equally good matches
+        .reduce(|best, next| if next.1 > best.1 { next } else { best })
+        .map(|(candidate, confidence)| (candidate.clone(), confidence))
+}
+
+// Item ID pasted into a name cell, bare ("Q42") or as a link to its Wikidata page, e.g.
+// https://www.wikidata.org/wiki/Q42 or http://www.wikidata.org/entity/Q42
+pub fn pasted_entity_id(text: &str) -> Option<String> {
+    let text = text.trim();
+    let id = match text.split_once("://") {
+        Some((_, rest)) => {
+            let (host, path) = rest.split_once('/')?;
+            if !["www.wikidata.org", "wikidata.org", "m.wikidata.org"].contains(&host) {
+                return None;
+            }
+            let path = path.split(['?', '#']).next().unwrap_or_default();
+            path.rsplit('/').next().unwrap_or_default()
+        }
+        None => text,
+    };
+    let id = id.to_uppercase();
+    (id.len() > 1 && id.starts_with('Q') && id[1..].chars().all(|c| c.is_ascii_digit())).then_some(id)
+}
+
+// Typeahead search for entities labelled `query` in `language`
+pub fn search_url(query: &str, language: &str) -> String {
+    let language = urlencoding::encode(language);
+    format!(
+        "{}?action=wbsearchentities&search={}&language={}&uselang={}&limit={}&format=json&origin=*",
+        WIKIDATA_API,
+        urlencoding::encode(query),
+        language,
+        language,
+        SEARCH_LIMIT
+    )
+}
+
+// wbgetentities request for some `props` ("claims" or "labels") of up to 50 entities
+pub fn entities_url(ids: &[String], props: &str) -> String {
+    format!(
+        "{}?action=wbgetentities&ids={}&props={}&languages=en&format=json&origin=*",
+        WIKIDATA_API,
+        urlencoding::encode(&ids.join("|")),
+        props
+    )
+}
+
+// SPARQL query of the English labels of properties
+pub fn property_labels_url(property_ids: &[String]) -> String {
+    let query = format!(
+        r#"
+            SELECT ?prop ?propLabel WHERE {{
+              VALUES ?prop {{ wd:{} }}
+              SERVICE wikibase:label {{ bd:serviceParam wikibase:language "en". }}
+            }}
+            "#,
+        property_ids.join(" wd:")
+    );
+    format!("{}?query={}&format=json", WIKIDATA_SPARQL, urlencoding::encode(&query))
+}
+
+// Claim values of an entity from wbgetentities, one per property, preferring statements
+// with the preferred rank, and the properties holding Commons images
+pub fn claim_values(claims: &serde_json::Value) -> (HashMap<String, serde_json::Value>, HashSet<String>) {
+    let mut values = HashMap::new();
+    let mut images = HashSet::new();
+    if let Some(claims) = claims.as_object() {
+        for (property, statements) in claims {
+            let Some(statements) = statements.as_array() else {
+                continue;
+            };
+            let statement = statements
+                .iter()
+                .find(|statement| statement["rank"] == "preferred")
+                .or_else(|| statements.iter().find(|statement| statement["rank"] != "deprecated"));
+            let Some(mainsnak) = statement.map(|statement| &statement["mainsnak"]) else {
+                continue;
+            };
+            let datavalue = mainsnak["datavalue"].clone();
+            if datavalue.is_null() {
+                continue;
+            }
+            if mainsnak["datatype"] == "commonsMedia" {
+                images.insert(property.clone());
+            }
+            values.insert(property.clone(), datavalue);
+        }
+    }
+    (values, images)
+}
+
+// Claims of an entity from its claim values and image properties, see claim_values, with
+// the values and units that are entities labelled from `labels`
+pub fn entity_claims(
+    values: HashMap<String, serde_json::Value>,
+    images: HashSet<String>,
+    labels: &HashMap<String, String>,
+) -> EntityClaims {
+    let mut claims = EntityClaims { images, ..EntityClaims::default() };
+    for (property, datavalue) in values {
+        if let Some(quantity) = datavalue_quantity(&datavalue, labels) {
+            claims.quantities.insert(property.clone(), quantity);
+        }
+        if let Some(text) = datavalue_text(&datavalue, labels) {
+            claims.values.insert(property, text);
+        }
+    }
+    claims
+}
+
+// English labels of the entities of a wbgetentities answer
+pub fn entity_labels(data: &serde_json::Value) -> HashMap<String, String> {
+    let mut labels = HashMap::new();
+    if let Some(entities) = data["entities"].as_object() {
+        for (id, entity) in entities {
+            if let Some(label) = entity["labels"]["en"]["value"].as_str() {
+                labels.insert(id.clone(), label.to_string());
+            }
+        }
+    }
+    labels
+}
+
+// wbgetentities request for the labels and descriptions in `language` of up to 50 entities
+pub fn entity_terms_url(ids: &[String], language: &str) -> String {
+    format!(
+        "{}?action=wbgetentities&ids={}&props=labels|descriptions&languages={}&format=json&origin=*",
+        WIKIDATA_API,
+        urlencoding::encode(&ids.join("|")),
+        urlencoding::encode(language)
+    )
+}
+
+// Labels and descriptions of the entities of an entity_terms_url answer
+pub fn entity_terms(data: &serde_json::Value, language: &str) -> HashMap<String, EntityTerms> {
+    let mut terms = HashMap::new();
+    if let Some(entities) = data["entities"].as_object() {
+        for (id, entity) in entities {
+            let term = |kind: &str| entity[kind][language]["value"].as_str().map(String::from);
+            terms.insert(id.clone(), EntityTerms { label: term("labels"), description: term("descriptions") });
+        }
+    }
+    terms
+}
+
+// Property labels of the answer to a property_labels_url query
+pub fn parse_property_labels(data: &serde_json::Value) -> HashMap<String, String> {
+    let mut labels = HashMap::new();
+    if let Some(bindings) = data["results"]["bindings"].as_array() {
+        for binding in bindings {
+            if let (Some(prop), Some(label)) = (binding["prop"]["value"].as_str(), binding["propLabel"]["value"].as_str()) {
+                let prop_id = prop.split('/').last().unwrap_or("").to_string();
+                labels.insert(prop_id, label.to_string());
+            }
+        }
+    }
+    labels
+}
+
+// IDs of the entities a claim value refers to, as value or as unit
+pub fn referenced_entity_ids(datavalue: &serde_json::Value) -> Vec<String> {
+    let mut ids = Vec::new();
+    match datavalue["type"].as_str() {
+        Some("wikibase-entityid") => {
+            if let Some(id) = datavalue["value"]["id"].as_str() {
+                ids.push(id.to_string());
+            }
+        }
+        Some("quantity") => {
+            if let Some(unit) = datavalue["value"]["unit"].as_str().and_then(|unit| unit.rsplit('/').next()) {
+                if unit != "1" {
+                    ids.push(unit.to_string());
+                }
+            }
+        }
+        _ => {}
+    }
+    ids
+}
+
+// Display text of a claim value from wbgetentities
+pub fn datavalue_text(datavalue: &serde_json::Value, labels: &HashMap<String, String>) -> Option<String> {
+    let value = &datavalue["value"];
+    match datavalue["type"].as_str()? {
+        "string" => value.as_str().map(String::from),
+        "monolingualtext" => value["text"].as_str().map(String::from),
+        "wikibase-entityid" => {
+            let id = value["id"].as_str()?;
+            Some(labels.get(id).cloned().unwrap_or_else(|| id.to_string()))
+        }
+        "quantity" => datavalue_quantity(datavalue, labels).map(|quantity| quantity.to_text()),
+        "time" => value["time"].as_str().map(|time| time.trim_start_matches('+').to_string()),
+        "globecoordinate" => Some(format!("{}, {}", value["latitude"], value["longitude"])),
+        _ => None,
+    }
+}
+
+// Amount, unit and precision of a quantity claim value. The precision is
+// taken from the bounds of the value when Wikidata has them
+pub fn datavalue_quantity(datavalue: &serde_json::Value, labels: &HashMap<String, String>) -> Option<Quantity> {
+    if datavalue["type"].as_str()? != "quantity" {
+        return None;
+    }
+    let value = &datavalue["value"];
+    let parse = |field: &str| value[field].as_str().and_then(|amount| amount.trim_start_matches('+').parse::<f64>().ok());
+    let unit_id = value["unit"]
+        .as_str()
+        .and_then(|unit| unit.rsplit('/').next())
+        .filter(|unit| *unit != "1")
+        .map(String::from);
+    Some(Quantity {
+        amount: parse("amount")?,
+        unit: unit_id.as_ref().and_then(|unit_id| labels.get(unit_id).cloned()),
+        unit_id,
+        precision: match (parse("upperBound"), parse("lowerBound")) {
+            (Some(upper), Some(lower)) => Some((upper - lower) / 2.0),
+            _ => None,
+        },
+    })
+}
@@ -0,0 +1,19 @@
+/// Tips sent to the creator or vendor of an item. The server resolves the item's
+/// lightning address and returns a BOLT11 invoice for the user's wallet to pay.
+use serde::{Deserialize, Serialize};
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct TipRequest {
+    pub amount_sats: u64,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(utoipa::ToSchema))]
+pub struct TipInvoice {
+    pub invoice: String,
+    pub lightning_address: String,
+    // Whether the invoice carries a NIP-57 zap request, so the payment shows up on Nostr
+    pub zap: bool,
+}
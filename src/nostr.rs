@@ -1,11 +1,39 @@
 use nostr_sdk::{client::Error, prelude::*, RelayPoolNotification};
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc;
 use std::fmt;
+use std::time::Duration;
+use crate::models::directory::{ComparisonAnnouncement, DirectoryEntry};
+use crate::models::backup::ComparisonArchive;
+use crate::models::remote::RemoteComparison;
+use crate::server_log;
+
+// Backup chunks are stored as NIP-78 application-specific data events
+const BACKUP_KIND: u16 = 30078;
+const BACKUP_TAG: &str = "compareware-backup";
+// Plaintext bytes per chunk, well below the NIP-44 limit of 65535 bytes
+const BACKUP_CHUNK_SIZE: usize = 32 * 1024;
+const BACKUP_FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+const PROFILE_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+// Directory announcements are application data too, one event per comparison URL
+const DIRECTORY_KIND: u16 = 30078;
+const DIRECTORY_TAG: &str = "compareware-directory";
+const DIRECTORY_FETCH_LIMIT: usize = 500;
+const DIRECTORY_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+// State of comparisons that publish to Nostr, as parameterized replaceable events (NIP-33)
+// addressed by the comparison URL, so relays keep only the latest state of a comparison
+const COMPARISON_KIND: u16 = 30078;
+const COMPARISON_TAG: &str = "compareware-comparison";
 
 #[derive(Debug)]
 pub enum MyError {
     NostrClientError(nostr_sdk::client::Error),
     NostrUnsignedError(nostr_sdk::event::unsigned::Error),
+    EncryptionError(nip44::Error),
+    ZapRequestError(nip57::Error),
+    NoRelayAccepted,
+    BackupNotFound,
+    IncompleteBackup { backup_id: String, found: usize, expected: usize },
 }
 
 impl fmt::Display for MyError {
@@ -13,6 +41,15 @@ impl fmt::Display for MyError {
         match self {
             MyError::NostrClientError(e) => write!(f, "Nostr Client Error: {}", e),
             MyError::NostrUnsignedError(e) => write!(f, "Nostr Unsigned Error: {}", e),
+            MyError::EncryptionError(e) => write!(f, "Encryption Error: {}", e),
+            MyError::ZapRequestError(e) => write!(f, "Zap Request Error: {}", e),
+            MyError::NoRelayAccepted => write!(f, "No relay accepted the event"),
+            MyError::BackupNotFound => write!(f, "No backup found on the relays"),
+            MyError::IncompleteBackup { backup_id, found, expected } => write!(
+                f,
+                "Backup {} is incomplete: found {} of {} chunks",
+                backup_id, found, expected
+            ),
         }
     }
 }
@@ -29,6 +66,41 @@ impl From<nostr_sdk::event::unsigned::Error> for MyError {
     }
 }
 
+impl From<nip44::Error> for MyError {
+    fn from(err: nip44::Error) -> MyError {
+        MyError::EncryptionError(err)
+    }
+}
+
+impl From<nip57::Error> for MyError {
+    fn from(err: nip57::Error) -> MyError {
+        MyError::ZapRequestError(err)
+    }
+}
+
+// Client with throwaway keys for reading public events, such as profiles and the
+// directory. Connects on first use and is then shared by all workers
+pub struct NostrReader {
+    relays: Vec<String>,
+    client: tokio::sync::OnceCell<NostrClient>,
+}
+
+impl NostrReader {
+    pub fn new(relays: Vec<String>) -> Self {
+        NostrReader { relays, client: tokio::sync::OnceCell::new() }
+    }
+
+    pub fn relays(&self) -> &[String] {
+        &self.relays
+    }
+
+    pub async fn client(&self) -> Result<&NostrClient, Error> {
+        self.client
+            .get_or_try_init(|| NostrClient::with_keys(Keys::generate(), &self.relays))
+            .await
+    }
+}
+
 pub struct NostrClient {
     client: Client,
     keys: Keys,
@@ -40,60 +112,394 @@ impl NostrClient {
         let client = Client::new(keys.clone());
         client.add_relay(relay_url).await?;
         client.connect().await;
-        println!("Connected to relay: {}", relay_url);
+        server_log!("Connected to relay: {}", relay_url);
 
         Ok(Self { client, keys })
     }
 
-    pub async fn publish_item(
-        &self,
-        name: String,
-        description: String,
-        tags: Vec<(String, String)>
-    ) -> Result<(), MyError> {
-        let content = format!(
-            "{{\"name\":\"{}\",\"description\":\"{}\",\"tags\":[{}]}}",
-            name,
-            description,
-            tags.iter()
-                .map(|(k, v)| format!("(\"{}\",\"{}\")", k, v))
-                .collect::<Vec<_>>()
-                .join(",")
-        );
-
-        // Create the event builder
-        let event_builder = EventBuilder::new(Kind::TextNote, content.clone());
-        // Build the unsigned event
-        let unsigned_event = event_builder.build(self.keys.public_key());
-        // Sign the event and handle the error explicitly
-        let signed_event = match unsigned_event.sign(&self.keys).await {
-            Ok(event) => event,
-            Err(e) => return Err(MyError::from(e)), // Convert the error to the expected type
+    // Connect with persistent keys to several relays, as needed for backups
+    pub async fn with_keys(keys: Keys, relay_urls: &[String]) -> Result<Self, Error> {
+        let client = Client::new(keys.clone());
+        for relay_url in relay_urls {
+            client.add_relay(relay_url.as_str()).await?;
+        }
+        client.connect().await;
+        server_log!("Connected to relays: {:?}", relay_urls);
+
+        Ok(Self { client, keys })
+    }
+
+    // Publish a backup as chunked events, each chunk encrypted to our own key
+    // so only the holder of the secret key can read the archive
+    pub async fn publish_backup(&self, backup_id: &str, archive: &str) -> Result<usize, MyError> {
+        let chunks = split_into_chunks(archive, BACKUP_CHUNK_SIZE);
+        let total = chunks.len();
+
+        for (index, chunk) in chunks.into_iter().enumerate() {
+            let content = nip44::encrypt(
+                self.keys.secret_key(),
+                &self.keys.public_key(),
+                chunk,
+                nip44::Version::V2,
+            )?;
+            let tags = vec![
+                Tag::identifier(format!("{}:{}", backup_id, index)),
+                Tag::hashtag(BACKUP_TAG),
+                Tag::custom(TagKind::Custom("backup".into()), [backup_id]),
+                Tag::custom(TagKind::Custom("chunk".into()), [index.to_string(), total.to_string()]),
+            ];
+            let unsigned_event = EventBuilder::new(Kind::Custom(BACKUP_KIND), content)
+                .tags(tags)
+                .build(self.keys.public_key());
+            let signed_event = unsigned_event.sign(&self.keys).await?;
+
+            let output = self.client.send_event(signed_event).await?;
+            if output.success.is_empty() {
+                return Err(MyError::NoRelayAccepted);
+            }
+        }
+
+        server_log!("Backup {} published in {} chunks", backup_id, total);
+        Ok(total)
+    }
+
+    // Fetch and reassemble a backup, the most recent one when no ID is given.
+    // Returns the backup ID together with the decrypted archive
+    pub async fn fetch_backup(&self, backup_id: Option<&str>) -> Result<(String, String), MyError> {
+        let filter = Filter::new()
+            .author(self.keys.public_key())
+            .kind(Kind::Custom(BACKUP_KIND))
+            .hashtag(BACKUP_TAG);
+        let events = self.client.fetch_events(vec![filter], Some(BACKUP_FETCH_TIMEOUT)).await?;
+
+        let backup_id = match backup_id {
+            Some(id) => id.to_string(),
+            None => events
+                .iter()
+                .max_by_key(|event| event.created_at)
+                .and_then(|event| tag_values(event, "backup").first().cloned())
+                .ok_or(MyError::BackupNotFound)?,
         };
 
-        // Send the event
-        self.client.send_event(signed_event.clone()).await?;
-        println!("Event published: {:?}", signed_event);
-        println!("Publishing content: {}", content);
+        // Order the chunks of the backup, relays may return duplicates
+        let mut chunks: Vec<(usize, String)> = Vec::new();
+        let mut expected = 0;
+        for event in events.iter() {
+            if tag_values(event, "backup").first() != Some(&backup_id) {
+                continue;
+            }
+            let chunk = tag_values(event, "chunk");
+            let (Some(index), Some(total)) = (
+                chunk.first().and_then(|v| v.parse::<usize>().ok()),
+                chunk.get(1).and_then(|v| v.parse::<usize>().ok()),
+            ) else {
+                continue;
+            };
+            expected = total;
+            if chunks.iter().any(|(i, _)| *i == index) {
+                continue;
+            }
+            let content = nip44::decrypt(self.keys.secret_key(), &self.keys.public_key(), &event.content)?;
+            chunks.push((index, content));
+        }
+
+        if chunks.is_empty() {
+            return Err(MyError::BackupNotFound);
+        }
+        if chunks.len() != expected {
+            return Err(MyError::IncompleteBackup { backup_id, found: chunks.len(), expected });
+        }
+        chunks.sort_by_key(|(index, _)| *index);
+        Ok((backup_id, chunks.into_iter().map(|(_, content)| content).collect()))
+    }
+
+    // Announce a public comparison. The event is addressed by the comparison URL,
+    // so announcing it again replaces the previous announcement
+    pub async fn publish_announcement(&self, announcement: &ComparisonAnnouncement) -> Result<(), MyError> {
+        let content = serde_json::to_string(announcement).unwrap_or_default();
+        let tags = vec![
+            Tag::identifier(announcement.url.clone()),
+            Tag::hashtag(DIRECTORY_TAG),
+            Tag::custom(TagKind::Custom("title".into()), [announcement.title.clone()]),
+        ];
+        let unsigned_event = EventBuilder::new(Kind::Custom(DIRECTORY_KIND), content)
+            .tags(tags)
+            .build(self.keys.public_key());
+        let signed_event = unsigned_event.sign(&self.keys).await?;
+
+        let output = self.client.send_event(signed_event).await?;
+        if output.success.is_empty() {
+            return Err(MyError::NoRelayAccepted);
+        }
+        server_log!("Announced comparison {}", announcement.url);
         Ok(())
     }
-    pub async fn subscribe_to_items(
+
+    // Ask relays to delete the announcement of a comparison (NIP-09)
+    pub async fn retract_announcement(&self, url: &str) -> Result<(), MyError> {
+        let coordinate = Coordinate::new(Kind::Custom(DIRECTORY_KIND), self.keys.public_key()).identifier(url);
+        let unsigned_event = EventBuilder::delete([coordinate]).build(self.keys.public_key());
+        let signed_event = unsigned_event.sign(&self.keys).await?;
+
+        let output = self.client.send_event(signed_event).await?;
+        if output.success.is_empty() {
+            return Err(MyError::NoRelayAccepted);
+        }
+        server_log!("Retracted announcement of {}", url);
+        Ok(())
+    }
+
+    // Collect the comparisons announced by all instances, newest first.
+    // Relays may return several versions of an announcement, only the newest is kept
+    pub async fn fetch_directory(&self) -> Result<Vec<DirectoryEntry>, MyError> {
+        let filter = Filter::new()
+            .kind(Kind::Custom(DIRECTORY_KIND))
+            .hashtag(DIRECTORY_TAG)
+            .limit(DIRECTORY_FETCH_LIMIT);
+        let events = self.client.fetch_events(vec![filter], Some(DIRECTORY_FETCH_TIMEOUT)).await?;
+
+        let mut entries: Vec<DirectoryEntry> = Vec::new();
+        for event in events.iter() {
+            let Ok(announcement) = serde_json::from_str::<ComparisonAnnouncement>(&event.content) else {
+                continue;
+            };
+            // The address must match the announced URL, or one URL could hide another
+            if tag_values(event, "d").first() != Some(&announcement.url) {
+                continue;
+            }
+            let entry = DirectoryEntry {
+                announcement,
+                author: event.pubkey.to_hex(),
+                created_at: event.created_at.as_u64() as i64,
+            };
+            match entries.iter_mut().find(|known| {
+                known.author == entry.author && known.announcement.url == entry.announcement.url
+            }) {
+                Some(known) if known.created_at < entry.created_at => *known = entry,
+                Some(_) => {}
+                None => entries.push(entry),
+            }
+        }
+        entries.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(entries)
+    }
+
+    // Look up the lightning address (lud16) published in a Nostr profile
+    pub async fn fetch_lightning_address(&self, public_key: PublicKey) -> Result<Option<String>, MyError> {
+        match self.client.fetch_metadata(public_key, Some(PROFILE_FETCH_TIMEOUT)).await {
+            Ok(metadata) => Ok(metadata.lud16.filter(|address| !address.is_empty())),
+            Err(Error::MetadataNotFound) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Publish the state of a comparison, replacing the state published before
+    pub async fn publish_comparison(&self, comparison: &ComparisonArchive) -> Result<(), MyError> {
+        let signed_event = comparison_event(comparison).build(self.keys.public_key()).sign(&self.keys).await?;
+        let output = self.client.send_event(signed_event).await?;
+        if output.success.is_empty() {
+            return Err(MyError::NoRelayAccepted);
+        }
+        server_log!("Published {} with {} items", comparison.url, comparison.items.len());
+        Ok(())
+    }
+
+    // Subscribe to the state an author publishes for a comparison URL, sending each
+    // version received to `tx` with its creation time. Ends once `tx` is closed
+    pub async fn subscribe_to_comparison(
         &self,
-        tx: mpsc::Sender<String>,
+        source_url: &str,
+        author: PublicKey,
+        tx: mpsc::Sender<(ComparisonArchive, i64)>,
     ) -> Result<(), Error> {
+        let filter = Filter::new()
+            .author(author)
+            .kind(Kind::Custom(COMPARISON_KIND))
+            .identifier(source_url)
+            .hashtag(COMPARISON_TAG);
         let mut notifications = self.client.notifications();
-         // Use tokio::spawn for async context
-         tokio::spawn(async move {
-            while let Ok(notification) = notifications.recv().await {
-                if let RelayPoolNotification::Event { relay_url: _, subscription_id: _, event } = notification {
-                    let content = event.content.clone();
-                    if tx.send(content).await.is_err() {
-                        eprintln!("Failed to send message");
-                    }
+        self.client.subscribe(vec![filter], None).await?;
+
+        tokio::spawn(async move {
+            loop {
+                let event = match notifications.recv().await {
+                    Ok(RelayPoolNotification::Event { event, .. }) => event,
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => break,
+                };
+                let Some(comparison) = comparison_from_event(&event) else {
+                    continue;
+                };
+                if tx.send((comparison, event.created_at.as_u64() as i64)).await.is_err() {
+                    break;
                 }
             }
         });
-        
         Ok(())
     }
+
+    pub async fn disconnect(&self) -> Result<(), Error> {
+        self.client.disconnect().await
+    }
+}
+
+// Unsigned event carrying the state of a comparison as JSON, addressed by its URL
+pub fn comparison_event(comparison: &ComparisonArchive) -> EventBuilder {
+    let content = serde_json::to_string(comparison).unwrap_or_default();
+    let mut tags = vec![Tag::identifier(comparison.url.clone()), Tag::hashtag(COMPARISON_TAG)];
+    if !comparison.metadata.title.is_empty() {
+        tags.push(Tag::custom(TagKind::Custom("title".into()), [comparison.metadata.title.clone()]));
+    }
+    EventBuilder::new(Kind::Custom(COMPARISON_KIND), content).tags(tags)
+}
+
+// State of a comparison event. The address must match the comparison's URL, or
+// one comparison could overwrite another
+pub fn comparison_from_event(event: &Event) -> Option<ComparisonArchive> {
+    if event.kind != Kind::Custom(COMPARISON_KIND) {
+        return None;
+    }
+    let comparison = serde_json::from_str::<ComparisonArchive>(&event.content).ok()?;
+    (tag_values(event, "d").first() == Some(&comparison.url)).then_some(comparison)
+}
+
+// Describe a comparison event received from a relay for the remote comparisons store.
+// Replaceable events are addressed by kind, author and `d` tag (NIP-01), so versions of
+// the same comparison share an address; other events are addressed by their ID
+pub fn remote_comparison_from_event(event: &Event, relay_url: &str) -> RemoteComparison {
+    let address = if event.kind.is_parameterized_replaceable() {
+        let identifier = tag_values(event, "d").first().cloned().unwrap_or_default();
+        format!("{}:{}:{}", event.kind.as_u16(), event.pubkey.to_hex(), identifier)
+    } else if event.kind.is_replaceable() {
+        format!("{}:{}:", event.kind.as_u16(), event.pubkey.to_hex())
+    } else {
+        event.id.to_hex()
+    };
+
+    RemoteComparison {
+        address,
+        event_id: event.id.to_hex(),
+        author: event.pubkey.to_hex(),
+        created_at: event.created_at.as_u64() as i64,
+        content: event.content.clone(),
+        relays: vec![relay_url.to_string()],
+    }
+}
+
+// Build an anonymous NIP-57 zap request for a tip. Wallets publish the zap receipt
+// to the given relays, so the recipient sees the tip on Nostr
+pub fn build_zap_request(
+    recipient: PublicKey,
+    relay_urls: &[String],
+    amount_msat: u64,
+    message: &str,
+) -> Result<Event, MyError> {
+    let relays = relay_urls.iter().filter_map(|relay| Url::parse(relay).ok());
+    let data = ZapRequestData::new(recipient, relays)
+        .amount(amount_msat)
+        .message(message);
+    Ok(nip57::anonymous_zap_request(data)?)
+}
+
+// Values of the first tag with the given name, without the name itself
+fn tag_values(event: &Event, name: &str) -> Vec<String> {
+    event
+        .tags
+        .iter()
+        .find(|tag| tag.as_slice().first().map(String::as_str) == Some(name))
+        .map(|tag| tag.as_slice()[1..].to_vec())
+        .unwrap_or_default()
+}
+
+// Split text into chunks of at most `max_bytes`, without cutting UTF-8 characters
+fn split_into_chunks(data: &str, max_bytes: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = data;
+    while rest.len() > max_bytes {
+        let mut end = max_bytes;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks.push(rest);
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::item::Item;
+    use crate::models::metadata::ComparisonMetadata;
+    use leptos::logging::log;
+    use std::collections::HashMap;
+
+    fn example_comparison() -> ComparisonArchive {
+        ComparisonArchive {
+            url: "/laptops".into(),
+            items: vec![Item {
+                id: "item1".into(),
+                name: "Laptop".into(),
+                description: "Light".into(),
+                wikidata_id: Some("Q1".into()),
+                custom_properties: HashMap::from([("P1324".into(), "XPS-13".into())]),
+                quantities: HashMap::new(),
+                group: None,
+                tip_recipient: None,
+                status: None,
+                sources: HashMap::new(),
+                updated_at: None,
+            }],
+            selected_properties: vec!["P1324".into()],
+            metadata: ComparisonMetadata { title: "Laptops".into(), ..Default::default() },
+        }
+    }
+
+    async fn signed(builder: EventBuilder) -> Event {
+        let keys = Keys::generate();
+        builder.build(keys.public_key()).sign(&keys).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_comparison_event_round_trip() {
+        log!("[TEST] Starting test_comparison_event_round_trip");
+        let comparison = example_comparison();
+        let event = signed(comparison_event(&comparison)).await;
+        assert_eq!(event.kind, Kind::Custom(COMPARISON_KIND));
+        assert!(event.kind.is_parameterized_replaceable());
+        assert_eq!(tag_values(&event, "d"), ["/laptops"]);
+        assert_eq!(tag_values(&event, "t"), [COMPARISON_TAG]);
+        assert_eq!(tag_values(&event, "title"), ["Laptops"]);
+
+        let received = comparison_from_event(&event).expect("Comparison event was not read");
+        assert_eq!(serde_json::to_value(&received).unwrap(), serde_json::to_value(&comparison).unwrap());
+        log!("[TEST] test_comparison_event_round_trip completed successfully");
+    }
+
+    #[tokio::test]
+    async fn test_comparison_event_address() {
+        log!("[TEST] Starting test_comparison_event_address");
+        // Test the state of one comparison can't be addressed as another
+        let comparison = example_comparison();
+        let content = serde_json::to_string(&comparison).unwrap();
+        let event = signed(
+            EventBuilder::new(Kind::Custom(COMPARISON_KIND), content.clone())
+                .tags([Tag::identifier("/phones"), Tag::hashtag(COMPARISON_TAG)]),
+        )
+        .await;
+        assert!(comparison_from_event(&event).is_none());
+
+        // Test other kinds and malformed content are ignored
+        let event = signed(EventBuilder::new(Kind::TextNote, content).tags([Tag::identifier("/laptops")])).await;
+        assert!(comparison_from_event(&event).is_none());
+        let event = signed(
+            EventBuilder::new(Kind::Custom(COMPARISON_KIND), "{\"url\": \"/laptops\"")
+                .tags([Tag::identifier("/laptops")]),
+        )
+        .await;
+        assert!(comparison_from_event(&event).is_none());
+        log!("[TEST] test_comparison_event_address completed successfully");
+    }
 }
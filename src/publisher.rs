@@ -0,0 +1,104 @@
+// Nostr publishing of comparison state, for the comparisons that enabled it at
+// /api/urls/{url}/publishing. Events are signed with the workspace key and sent
+// in the background, so saving never waits on the relays
+use crate::config::Secrets;
+use crate::models::backup::ComparisonArchive;
+use crate::nostr::{MyError, NostrClient};
+use crate::server_error;
+use nostr_sdk::Keys;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+// Saves within this delay are published together, as the latest state of the comparison
+const PUBLISH_DELAY: Duration = Duration::from_secs(2);
+
+// State waiting to be published with its relays, by comparison URL
+type PendingComparisons = HashMap<String, (Vec<String>, ComparisonArchive)>;
+
+pub struct ComparisonPublisher {
+    // Workspace key, publishing is unavailable without one
+    keys: Option<Keys>,
+    // Clients by relay list, connected on first use and shared by the comparisons
+    // publishing to the same relays
+    clients: Arc<Mutex<HashMap<Vec<String>, Arc<NostrClient>>>>,
+    pending: Arc<std::sync::Mutex<PendingComparisons>>,
+}
+
+impl ComparisonPublisher {
+    pub fn new(keys: Option<Keys>) -> Self {
+        ComparisonPublisher { keys, clients: Arc::default(), pending: Arc::default() }
+    }
+
+    // Publisher signing with the workspace key, if it's set
+    pub fn from_secrets(secrets: &Secrets) -> Self {
+        let keys = secrets
+            .nostr_secret_key
+            .as_deref()
+            .and_then(|secret_key| match Keys::parse(secret_key) {
+                Ok(keys) => Some(keys),
+                Err(e) => {
+                    server_error!("Comparison publishing disabled, invalid COMPAREWARE_NOSTR_SECRET_KEY: {}", e);
+                    None
+                }
+            });
+        ComparisonPublisher::new(keys)
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.keys.is_some()
+    }
+
+    // Publish a comparison with its named items to the relays, replacing the state
+    // published before. Failures are logged, the next save publishes the comparison again
+    pub fn publish(&self, relays: &[String], mut comparison: ComparisonArchive) {
+        let Some(keys) = self.keys.clone() else {
+            return;
+        };
+        if relays.is_empty() {
+            return;
+        }
+        comparison.items.retain(|item| !item.name.trim().is_empty());
+        let url = comparison.url.clone();
+        let already_pending = self
+            .pending
+            .lock()
+            .unwrap()
+            .insert(url.clone(), (relays.to_vec(), comparison))
+            .is_some();
+        if already_pending {
+            return;
+        }
+
+        let (clients, pending) = (self.clients.clone(), self.pending.clone());
+        actix_web::rt::spawn(async move {
+            tokio::time::sleep(PUBLISH_DELAY).await;
+            let Some((relays, comparison)) = pending.lock().unwrap().remove(&url) else {
+                return;
+            };
+            let published = match client(&clients, keys, relays).await {
+                Ok(client) => client.publish_comparison(&comparison).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = published {
+                server_error!("Failed to publish {}: {}", url, e);
+            }
+        });
+    }
+}
+
+async fn client(
+    clients: &Mutex<HashMap<Vec<String>, Arc<NostrClient>>>,
+    keys: Keys,
+    mut relays: Vec<String>,
+) -> Result<Arc<NostrClient>, MyError> {
+    relays.sort();
+    let mut clients = clients.lock().await;
+    if let Some(client) = clients.get(&relays) {
+        return Ok(client.clone());
+    }
+    let client = Arc::new(NostrClient::with_keys(keys, &relays).await?);
+    clients.insert(relays, client.clone());
+    Ok(client)
+}
@@ -0,0 +1,198 @@
+// Requests per minute each IP may send to the API, set up in the rate_limits section of the
+// configuration. Changes, snapshots and lookups through the Wikidata proxy have a token bucket
+// each, so a flood of any is answered with a 429 before it reaches the database or Wikidata,
+// while reading comparisons stays unlimited
+use crate::api::ApiError;
+use crate::config::RateLimitConfig;
+use crate::wikidata_proxy::{LruCache, TokenBucket, MAX_TRACKED_CLIENTS};
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::{web, HttpRequest, ResponseError};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Budget {
+    Writes,
+    Snapshots,
+    Wikidata,
+}
+
+// Budget a request takes a token from, None for requests that aren't limited
+fn budget(method: &Method, path: &str) -> Option<Budget> {
+    if path.starts_with("/api/wikidata/") || path.ends_with("/properties/suggestions") {
+        Some(Budget::Wikidata)
+    } else if [Method::GET, Method::HEAD, Method::OPTIONS].contains(method) {
+        None
+    } else if path.ends_with("/snapshots") {
+        // Each snapshot is a copy of the comparison kept for good
+        Some(Budget::Snapshots)
+    } else {
+        Some(Budget::Writes)
+    }
+}
+
+// IP a request counts against. That's the peer's, unless the peer is a trusted proxy:
+// then it's the last address of X-Forwarded-For that isn't one, the proxies append
+// to the header while anything before is up to the client
+pub fn client_address(req: &HttpRequest) -> String {
+    let Some(peer) = req.peer_addr().map(|address| address.ip()) else {
+        return "unknown".to_string();
+    };
+    let trusted_proxies = req
+        .app_data::<web::Data<RequestLimits>>()
+        .map(|limits| limits.config.trusted_proxies.as_slice())
+        .unwrap_or_default();
+    if !trusted_proxies.contains(&peer) {
+        return peer.to_string();
+    }
+    let forwarded: Vec<IpAddr> = req
+        .headers()
+        .get_all("X-Forwarded-For")
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|address| address.trim().parse().ok())
+        .collect();
+    forwarded
+        .into_iter()
+        .rev()
+        .find(|address| !trusted_proxies.contains(address))
+        .unwrap_or(peer)
+        .to_string()
+}
+
+pub struct RequestLimits {
+    config: RateLimitConfig,
+    buckets: Mutex<LruCache<(Budget, String), TokenBucket>>,
+}
+
+impl RequestLimits {
+    pub fn new(config: RateLimitConfig) -> Self {
+        RequestLimits { config, buckets: Mutex::new(LruCache::new(MAX_TRACKED_CLIENTS)) }
+    }
+
+    fn capacity(&self, budget: Budget) -> u32 {
+        match budget {
+            Budget::Writes => self.config.writes_per_minute,
+            Budget::Snapshots => self.config.snapshots_per_minute,
+            Budget::Wikidata => self.config.wikidata_per_minute,
+        }
+    }
+
+    // Take a token for a request of `client`, or return how long it has to wait
+    fn check(&self, budget: Budget, client: &str) -> Result<(), Duration> {
+        let capacity = self.capacity(budget) as f64;
+        if capacity == 0.0 {
+            return Ok(());
+        }
+        let now = Instant::now();
+        self.buckets
+            .lock()
+            .unwrap()
+            .get_or_insert_with((budget, client.to_string()), || TokenBucket::full(capacity, now))
+            .take(capacity, now)
+    }
+}
+
+// Middleware of the /api scope, refusing requests over the budget of their IP
+pub async fn limit_requests(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let limits = req.app_data::<web::Data<RequestLimits>>().cloned();
+    if let (Some(limits), Some(budget)) = (limits, budget(req.method(), req.path())) {
+        let client = client_address(req.request());
+        if let Err(wait) = limits.check(budget, &client) {
+            let response = ApiError::TooManyRequests { retry_after: wait.as_secs_f64().ceil() as u64 }.error_response();
+            return Ok(req.into_response(response).map_into_right_body());
+        }
+    }
+    next.call(req).await.map(ServiceResponse::map_into_left_body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::error::ErrorResponse;
+    use actix_web::http::header::RETRY_AFTER;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{self, TestRequest};
+    use actix_web::{middleware, App, HttpResponse};
+    use leptos::logging::log;
+
+    #[test]
+    fn test_budget() {
+        log!("[TEST] Starting test_budget");
+        assert_eq!(budget(&Method::GET, "/api/urls/x/items"), None);
+        assert_eq!(budget(&Method::POST, "/api/urls/x/items"), Some(Budget::Writes));
+        assert_eq!(budget(&Method::DELETE, "/api/urls/x/items/1"), Some(Budget::Writes));
+        assert_eq!(budget(&Method::POST, "/api/diagnostics/errors"), Some(Budget::Writes));
+        assert_eq!(budget(&Method::POST, "/api/urls/x/snapshots"), Some(Budget::Snapshots));
+        assert_eq!(budget(&Method::GET, "/api/snapshots/1"), None);
+        assert_eq!(budget(&Method::GET, "/api/wikidata/labels"), Some(Budget::Wikidata));
+        assert_eq!(budget(&Method::GET, "/api/wikidata/claims"), Some(Budget::Wikidata));
+        assert_eq!(budget(&Method::GET, "/api/urls/x/properties/suggestions"), Some(Budget::Wikidata));
+        log!("[TEST] test_budget completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_limit_requests() {
+        log!("[TEST] Starting test_limit_requests");
+        let trusted_proxies = vec!["10.0.0.100".parse().unwrap()];
+        let limits = RequestLimits::new(RateLimitConfig { writes_per_minute: 2, snapshots_per_minute: 0, wikidata_per_minute: 0, trusted_proxies });
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(limits))
+                .wrap(middleware::from_fn(limit_requests))
+                .default_service(web::to(|| async { HttpResponse::Ok().finish() })),
+        )
+        .await;
+        let send = |request: TestRequest, ip: &str| {
+            let request = request.uri("/api/urls/x/items").peer_addr(format!("{}:4000", ip).parse().unwrap());
+            test::call_service(&app, request.to_request())
+        };
+        for _ in 0..2 {
+            assert_eq!(send(TestRequest::post(), "10.0.0.1").await.status(), StatusCode::OK);
+        }
+        let response = send(TestRequest::post(), "10.0.0.1").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get(RETRY_AFTER).unwrap(), "30");
+        let error: ErrorResponse = test::read_body_json(response).await;
+        assert_eq!(error.code, "too_many_requests");
+
+        // Test a client can't pick another budget by claiming to be forwarded
+        let forged = TestRequest::post().insert_header(("X-Forwarded-For", "10.0.0.9"));
+        assert_eq!(send(forged, "10.0.0.1").await.status(), StatusCode::TOO_MANY_REQUESTS);
+        // Test trusted proxies are asked for the client, from the end of the header
+        for _ in 0..2 {
+            let forwarded = TestRequest::post().insert_header(("X-Forwarded-For", "10.0.0.9, 10.0.0.3"));
+            assert_eq!(send(forwarded, "10.0.0.100").await.status(), StatusCode::OK);
+        }
+        let forwarded = TestRequest::post().insert_header(("X-Forwarded-For", "10.0.0.8, 10.0.0.3"));
+        assert_eq!(send(forwarded, "10.0.0.100").await.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // Test reads, other IPs and budgets turned off aren't limited
+        assert_eq!(send(TestRequest::get(), "10.0.0.1").await.status(), StatusCode::OK);
+        assert_eq!(send(TestRequest::post(), "10.0.0.2").await.status(), StatusCode::OK);
+        for _ in 0..5 {
+            let request = TestRequest::get().uri("/api/wikidata/labels").peer_addr("10.0.0.1:4000".parse().unwrap());
+            assert_eq!(test::call_service(&app, request.to_request()).await.status(), StatusCode::OK);
+        }
+        log!("[TEST] test_limit_requests completed successfully");
+    }
+
+    #[test]
+    fn test_tracked_clients() {
+        log!("[TEST] Starting test_tracked_clients");
+        let limits = RequestLimits::new(RateLimitConfig { writes_per_minute: 2, ..Default::default() });
+        // Test clients spending a single token each can't grow the buckets past the limit
+        for client in 0..MAX_TRACKED_CLIENTS + 100 {
+            limits.check(Budget::Writes, &client.to_string()).unwrap();
+        }
+        assert_eq!(limits.buckets.lock().unwrap().len(), MAX_TRACKED_CLIENTS);
+        log!("[TEST] test_tracked_clients completed successfully");
+    }
+}
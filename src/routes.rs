@@ -0,0 +1,170 @@
+// Routes of the REST API and the live updates, registered by the server and by the
+// contract tests in api.rs, which call them the way the pages do
+use crate::api::{add_selected_property, backup_to_storage, backup_workspace, change_events, clear_decision, clone_comparison, complete_setup, create_api_key, create_item, create_items_batch, create_signed_link, create_snapshot, delete_api_key, delete_item, delete_property, download_diagnostics, download_database, editor_metrics, export_comparison, get_cached_claims, get_cached_labels, get_deleted_items, get_directory, get_edit_access, get_history, get_history_feed, get_instance_info, get_instance_settings, get_item_card, get_items, get_listing, get_metadata, get_mirror_settings, get_property_overrides, get_property_settings, get_property_suggestions, get_property_value, get_publishing_settings, get_ratings, get_reusable_values, get_reviews_feed, get_selected_properties, get_setup_status, get_snapshot, get_wikidata_fixture, heaviest_comparisons, import_comparison, import_items_from_query, invite_collaborator, json_error_handler, list_api_keys, list_comparisons, live_updates, negotiate_export, openapi_json, presence_heartbeat, protect_comparison, publish_listing, refresh_from_wikidata, remove_listing, rename_property, reorder_selected_properties, report_client_error, restore_item, restore_workspace, review_item, search_items, set_decision, set_instance_settings, set_metadata, set_mirror_settings, set_property_override, set_property_settings, set_publishing_settings, set_visibility, swagger_ui, tip_item, undo_property_deletion, upload_database, ValidJson, MAX_DATABASE_UPLOAD_BYTES};
+use crate::api_keys::authenticate_api_keys;
+use crate::comparison_metrics::measure_requests;
+use crate::db::Database;
+use crate::fault_injection::inject_faults;
+use crate::live::LiveUpdates;
+use crate::models::item::{Item, ItemRequest};
+use crate::models::property::PropertySelection;
+use crate::publisher::ComparisonPublisher;
+use crate::request_limits::limit_requests;
+use crate::visibility::enforce_visibility;
+use crate::wikidata_proxy::WikidataProxy;
+use actix_web::{middleware, web, HttpRequest, Responder};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub fn configure(config: &mut web::ServiceConfig) {
+    config
+        // Report malformed JSON bodies as structured API errors
+        .app_data(web::JsonConfig::default().error_handler(json_error_handler))
+        // Register custom API routes BEFORE Leptos server functions
+        .service(
+            web::scope("/api")
+            // API keys sent as bearer tokens, checked once for the handlers
+            .wrap(middleware::from_fn(authenticate_api_keys))
+            // Artificial latency and failures, when the configuration turns them on
+            .wrap(middleware::from_fn(inject_faults))
+            // Requests per minute of each IP, refused before they are delayed or handled
+            .wrap(middleware::from_fn(limit_requests))
+            // API description and interactive documentation
+            .route("/openapi.json", web::get().to(openapi_json))
+            .route("/docs", web::get().to(swagger_ui))
+            .route("/presence/metrics", web::get().to(editor_metrics)) // Concurrent editor counts, for admins
+            .route("/directory", web::get().to(get_directory)) // Comparisons announced on Nostr
+            .route("/urls", web::get().to(list_comparisons)) // Index of the comparisons stored here
+            .route("/search", web::get().to(search_items)) // Items of all comparisons matching a search
+            .route("/instance", web::get().to(get_instance_info)) // Wikidata language and features of the instance
+            .route("/setup", web::get().to(get_setup_status)) // Whether the first run setup is open
+            .route("/setup", web::post().to(complete_setup)) // Admin token and settings of a new instance
+            .route("/snapshots/{snapshot_id}", web::get().to(get_snapshot)) // Frozen copy of a comparison
+            .route("/public/items/{wikidata_id}/card", web::get().to(get_item_card)) // Embeddable spec card of an item
+            .route("/wikidata/labels", web::get().to(get_cached_labels)) // Wikidata labels, cached by the server
+            .route("/wikidata/claims", web::get().to(get_cached_claims)) // Wikidata claims, cached by the server
+            .route("/wikidata/fixtures", web::get().to(get_wikidata_fixture)) // Recorded Wikidata responses, in tests
+            .route("/admin/backup", web::post().to(backup_workspace)) // Publish a Nostr backup
+            .route("/admin/backup/storage", web::post().to(backup_to_storage)) // Upload a backup to S3-compatible storage
+            .route("/admin/restore", web::post().to(restore_workspace)) // Restore a Nostr backup
+            .service(
+                web::resource("/admin/database")
+                    .app_data(web::PayloadConfig::new(MAX_DATABASE_UPLOAD_BYTES))
+                    .route(web::get().to(download_database)) // Copy of the SQLite database
+                    .route(web::put().to(upload_database)), // Replace the database with a copy
+            )
+            .route("/admin/settings", web::get().to(get_instance_settings)) // Settings changed at runtime
+            .route("/admin/settings", web::put().to(set_instance_settings))
+            .route("/admin/diagnostics", web::get().to(download_diagnostics)) // Zip of logs and configuration for bug reports
+            .route("/admin/reports/heaviest-comparisons", web::get().to(heaviest_comparisons)) // Comparisons slowest to answer, with remediations
+            .route("/admin/api-keys", web::get().to(list_api_keys)) // Keys of scripts and integrations
+            .route("/admin/api-keys", web::post().to(create_api_key))
+            .route("/admin/api-keys/{id}", web::delete().to(delete_api_key))
+            .route("/diagnostics/errors", web::post().to(report_client_error)) // Errors shown in browsers, kept for the diagnostics
+            .service(
+                web::scope("/urls/{url}")
+                    // Response times and sizes, for the heaviest comparisons report
+                    .wrap(middleware::from_fn(measure_requests))
+                    // Private comparisons answered to their editors only
+                    .wrap(middleware::from_fn(enforce_visibility))
+                    .route("/items", web::get().to(get_items_handler)) // GET items by URL
+                    .route("/items", web::post().to(create_item_handler)) // Create item for URL
+                    .route("/items/batch", web::post().to(create_items_batch)) // Save several items for URL
+                    .route("/items/import", web::post().to(import_items_from_query)) // Create items from a Wikidata query
+                    .route("/items/refresh", web::post().to(refresh_from_wikidata)) // Replace the values from Wikidata with current claims
+                    .route("/items/{item_id}", web::delete().to(delete_item)) // Move item for URL to the trash
+                    .route("/items/{item_id}/restore", web::post().to(restore_item)) // Take item out of the trash
+                    .route("/items/{item_id}/values/{property}", web::get().to(get_property_value)) // Full text of a long value
+                    .route("/items/{item_id}/tip", web::post().to(tip_item)) // Invoice for tipping the item's creator
+                    .route("/items/{item_id}/review", web::put().to(review_item)) // Rate the item
+                    .route("/ratings", web::get().to(get_ratings)) // Average ratings of the items
+                    .route("/reviews.atom", web::get().to(get_reviews_feed)) // Atom feed of the newest reviews
+                    .route("/trash", web::get().to(get_deleted_items)) // Recently deleted items
+                    .route("/history", web::get().to(get_history)) // Changes to items and properties
+                    .route("/feed.atom", web::get().to(get_history_feed)) // Atom feed of the same changes
+                    .route("/export", web::get().to(negotiate_export)) // The comparison in the format the Accept header prefers
+                    .route("/export.{format}", web::get().to(export_comparison)) // The comparison in one of the export formats
+                    .route("/signed-links", web::post().to(create_signed_link)) // Expiring link to an export, signed by the server
+                    .route("/import.json", web::post().to(import_comparison)) // Recreate an exported comparison under this URL
+                    .route("/clone", web::post().to(clone_comparison)) // Copy the comparison to a new URL
+                    .route("/reusable/{wikidata_id}", web::get().to(get_reusable_values)) // Values other comparisons hold for an item
+                    .route("/properties", web::get().to(get_selected_properties_handler))
+                    .route("/properties", web::post().to(add_selected_property_handler))
+                    .route("/properties/order", web::put().to(reorder_selected_properties)) // Reorder properties for URL
+                    .route("/properties/settings", web::get().to(get_property_settings)) // Language and spell-checking of the cells
+                    .route("/properties/overrides", web::get().to(get_property_overrides)) // Labels and tooltips given to the properties
+                    .route("/properties/suggestions", web::get().to(get_property_suggestions)) // Suggest properties from item classes
+                    .route("/properties/{property}", web::delete().to(delete_property)) // Delete property for URL
+                    .route("/properties/{property}", web::patch().to(rename_property)) // Rename a custom property, keeping its values
+                    .route("/properties/{property}/settings", web::put().to(set_property_settings))
+                    .route("/properties/{property}/override", web::put().to(set_property_override))
+                    .route("/properties/deletions/{deletion_id}/undo", web::post().to(undo_property_deletion)) // Restore a deleted property
+                    .route("/presence", web::post().to(presence_heartbeat)) // Edit session heartbeat
+                    .route("/events", web::get().to(change_events)) // Server-Sent Events feed of changes
+                    .route("/access", web::get().to(get_edit_access)) // Edit protection of the comparison
+                    .route("/access", web::post().to(protect_comparison)) // Protect the comparison with an owner token
+                    .route("/collaborators", web::post().to(invite_collaborator)) // Edit token for a collaborator
+                    .route("/visibility", web::put().to(set_visibility)) // Public, unlisted or private
+                    .route("/snapshots", web::post().to(create_snapshot)) // Freeze the comparison into a snapshot
+                    .route("/metadata", web::get().to(get_metadata)) // Title and description
+                    .route("/metadata", web::put().to(set_metadata))
+                    .route("/decision", web::put().to(set_decision)) // Mark the comparison as decided
+                    .route("/decision", web::delete().to(clear_decision)) // Reopen the comparison
+                    .route("/listing", web::get().to(get_listing)) // Directory listing
+                    .route("/listing", web::put().to(publish_listing)) // Announce in the directory
+                    .route("/listing", web::delete().to(remove_listing)) // Remove from the directory
+                    .route("/publishing", web::get().to(get_publishing_settings)) // Nostr publishing of the items
+                    .route("/publishing", web::put().to(set_publishing_settings))
+                    .route("/mirror", web::get().to(get_mirror_settings)) // Items merged from another instance's comparison
+                    .route("/mirror", web::put().to(set_mirror_settings))
+            )
+            // Register server functions inside the scope, otherwise the
+            // scope answers 404 before they are reached
+            .route("/{tail:.*}", leptos_actix::handle_server_fns())
+        )
+        // Changes to a comparison pushed to the browsers that have it open
+        .route("/ws/urls/{url}", web::get().to(live_updates));
+}
+
+// Handler to get items for a specific URL
+async fn get_items_handler(
+    req: HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> impl Responder {
+    get_items(req, db, web::Query(url.into_inner())).await
+}
+
+// Handler to create an item for a specific URL
+async fn create_item_handler(
+    req: HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    live: web::Data<LiveUpdates>,
+    publisher: web::Data<ComparisonPublisher>,
+    url: web::Path<String>,
+    item: ValidJson<Item>,
+) -> impl Responder {
+    let request = ItemRequest {
+        url: url.into_inner(),
+        item: item.into_inner(),
+    };
+    create_item(req, db, live, publisher, ValidJson(request)).await
+}
+
+async fn get_selected_properties_handler(
+    db: web::Data<Arc<RwLock<Database>>>,
+    url: web::Path<String>,
+) -> impl Responder {
+    get_selected_properties(db, url).await
+}
+
+async fn add_selected_property_handler(
+    req: HttpRequest,
+    db: web::Data<Arc<RwLock<Database>>>,
+    proxy: web::Data<WikidataProxy>,
+    live: web::Data<LiveUpdates>,
+    url: web::Path<String>,
+    selection: ValidJson<PropertySelection>,
+) -> impl Responder {
+    add_selected_property(req, db, proxy, live, url, selection).await
+}
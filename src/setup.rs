@@ -0,0 +1,121 @@
+// First run of a new instance, see /setup: the settings the wizard's choices lead to,
+// and the demo comparisons it offers to seed, written like a restored backup
+use crate::models::backup::{ComparisonArchive, WorkspaceArchive};
+use crate::models::instance::InstanceSettings;
+use crate::models::item::Item;
+use crate::models::metadata::ComparisonMetadata;
+use crate::models::setup::SetupRequest;
+use std::collections::HashMap;
+
+// Demo comparisons as path, title, description, properties and items with their values
+type DemoComparison = (&'static str, &'static str, &'static str, &'static [&'static str], &'static [DemoItem]);
+type DemoItem = (&'static str, &'static str, &'static [&'static str]);
+
+const DEMO_COMPARISONS: &[DemoComparison] = &[
+    (
+        "demo/laptops",
+        "Lightweight laptops",
+        "Example comparison of travel laptops, edit or delete it as you like",
+        &["Weight", "Battery life", "Screen"],
+        &[
+            ("Traveller 13", "Fanless ultrabook", &["1.1 kg", "14 h", "13.3 in"]),
+            ("Worker 14", "Business laptop with a matte display", &["1.4 kg", "11 h", "14 in"]),
+            ("Creator 16", "Large screen for photo editing", &["1.9 kg", "9 h", "16 in"]),
+        ],
+    ),
+    (
+        "demo/languages",
+        "Programming languages",
+        "Example comparison of general-purpose programming languages",
+        &["Typing", "Memory management", "First released"],
+        &[
+            ("Rust", "Systems programming language", &["Static", "Ownership", "2015"]),
+            ("Python", "Scripting language", &["Dynamic", "Garbage collection", "1991"]),
+            ("Go", "Language for network services", &["Static", "Garbage collection", "2012"]),
+        ],
+    ),
+];
+
+// Settings of the new instance: the wizard's choices over the current ones
+pub fn setup_settings(request: &SetupRequest, current: &InstanceSettings) -> InstanceSettings {
+    InstanceSettings {
+        private: request.private,
+        wikidata_language: request.wikidata_language.clone(),
+        ..current.clone()
+    }
+    .trimmed()
+}
+
+// Demo comparisons under the site the setup was made on, e.g. "https://example.com"
+pub fn demo_archive(site: &str, now: i64) -> WorkspaceArchive {
+    let comparisons = DEMO_COMPARISONS
+        .iter()
+        .map(|(path, title, description, properties, items)| ComparisonArchive {
+            url: format!("{}/{}", site.trim_end_matches('/'), path),
+            items: items
+                .iter()
+                .map(|(name, item_description, values)| Item {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    name: name.to_string(),
+                    description: item_description.to_string(),
+                    wikidata_id: None,
+                    custom_properties: properties.iter().zip(values.iter()).map(|(property, value)| (property.to_string(), value.to_string())).collect(),
+                    quantities: HashMap::new(),
+                    group: None,
+                    tip_recipient: None,
+                    status: None,
+                    sources: HashMap::new(),
+                    updated_at: None,
+                })
+                .collect(),
+            selected_properties: properties.iter().map(|property| property.to_string()).collect(),
+            metadata: ComparisonMetadata { title: title.to_string(), description: description.to_string(), ..Default::default() },
+        })
+        .collect();
+    WorkspaceArchive { version: 1, created_at: now, comparisons }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use leptos::logging::log;
+
+    #[tokio::test]
+    async fn test_demo_archive() {
+        log!("[TEST] Starting test_demo_archive");
+        let archive = demo_archive("https://example.com/", 100);
+        assert_eq!(archive.comparisons[0].url, "https://example.com/demo/laptops");
+        for comparison in &archive.comparisons {
+            for item in &comparison.items {
+                assert_eq!(item.custom_properties.len(), comparison.selected_properties.len());
+            }
+        }
+
+        let db = Database::new(":memory:").unwrap();
+        db.create_schema().await.unwrap();
+        let items = db.restore_workspace(&archive).await.unwrap();
+        assert_eq!(items, 6);
+        let laptops = db.get_items_by_url("https://example.com/demo/laptops").await.unwrap();
+        assert!(laptops.iter().any(|item| item.custom_properties.get("Weight").map(String::as_str) == Some("1.1 kg")));
+        log!("[TEST] test_demo_archive completed successfully");
+    }
+
+    #[test]
+    fn test_setup_settings() {
+        log!("[TEST] Starting test_setup_settings");
+        let current = InstanceSettings {
+            wikidata_client_rpm: 5,
+            wikidata_global_rpm: 30,
+            wikidata_language: "en".into(),
+            private: false,
+            features: Default::default(),
+        };
+        let request = SetupRequest { private: true, wikidata_language: " de ".into(), demo_data: false };
+        let settings = setup_settings(&request, &current);
+        assert_eq!((settings.private, settings.wikidata_language.as_str(), settings.wikidata_client_rpm), (true, "de", 5));
+        let blank = SetupRequest { wikidata_language: String::new(), ..request };
+        assert_eq!(setup_settings(&blank, &current).wikidata_language, "en");
+        log!("[TEST] test_setup_settings completed successfully");
+    }
+}
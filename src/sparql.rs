@@ -0,0 +1,333 @@
+#[cfg(feature = "ssr")]
+mod sparql_impl {
+    use crate::config::WikidataConfig;
+    use crate::models::property::{is_property_id, PropertyId, PropertySuggestion};
+    use crate::models::property::EntityTerms;
+    use crate::models::wikidata::{claim_values, entity_labels, entity_terms};
+    use crate::wikidata_fixtures::{FixtureError, WikidataFixtures};
+    use leptos::logging::log;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::OnceLock;
+    use std::time::Duration;
+
+    // Values by property and image properties of an entity, see claim_values
+    pub type ClaimValues = (HashMap<String, serde_json::Value>, HashSet<String>);
+
+    // Wikidata asks clients of the query service to identify themselves
+    const USER_AGENT: &str = concat!("CompareWare/", env!("CARGO_PKG_VERSION"));
+    const SPARQL_RESULTS: &str = "application/sparql-results+json";
+    // Upper bound for items created by one import
+    pub const MAX_IMPORTED_ITEMS: usize = 200;
+    // Instances sampled from the item classes to rank property suggestions
+    const SUGGESTION_SAMPLE_SIZE: usize = 200;
+    const MAX_SUGGESTIONS: usize = 30;
+    // Entities per wbgetentities request, the most Wikidata accepts
+    const ENTITIES_PER_REQUEST: usize = 50;
+    // The query service stops queries after 60 seconds, requests don't wait much longer
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(65);
+
+    #[derive(Debug, thiserror::Error)]
+    pub enum SparqlError {
+        #[error("Query must not be empty")]
+        EmptyQuery,
+        #[error("Query must select an ?item variable")]
+        MissingItemVariable,
+        #[error("Wikidata request failed: {0}")]
+        Request(#[from] reqwest::Error),
+        #[error("Wikidata answered {0}")]
+        Status(u16),
+        #[error("Invalid Wikidata endpoint {0}")]
+        Endpoint(String),
+        #[error(transparent)]
+        Fixture(#[from] FixtureError),
+    }
+
+    // A Wikidata item returned by an import query
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct QueriedItem {
+        pub wikidata_id: String,
+        pub label: String,
+        pub description: String,
+    }
+
+    // Turn the user's input into a SPARQL query. Full queries are used as they are,
+    // anything else is taken as a class QID ("Q22645") or class label ("instances of smartphone"),
+    // matched and labelled in the given language
+    pub fn build_item_query(input: &str, language: &str) -> Result<String, SparqlError> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(SparqlError::EmptyQuery);
+        }
+
+        if input.to_uppercase().contains("SELECT") {
+            if !input.contains("?item") {
+                return Err(SparqlError::MissingItemVariable);
+            }
+            return Ok(input.to_string());
+        }
+
+        // Wikidata's labels carry lowercase language tags, e.g. "pt-br"
+        let language = language.to_lowercase();
+        let class = input
+            .strip_prefix("instances of ")
+            .or_else(|| input.strip_prefix("instance of "))
+            .unwrap_or(input)
+            .trim();
+        let class_pattern = if is_qid(class) {
+            format!("?item wdt:P31 wd:{} .", class)
+        } else {
+            format!(
+                "?class rdfs:label \"{}\"@{} .\n                ?item wdt:P31 ?class .",
+                class.replace('\\', "\\\\").replace('"', "\\\""),
+                language
+            )
+        };
+
+        Ok(format!(
+            r#"SELECT DISTINCT ?item ?itemLabel ?itemDescription WHERE {{
+                {}
+                SERVICE wikibase:label {{ bd:serviceParam wikibase:language "{},en". }}
+            }}
+            LIMIT {}"#,
+            class_pattern, language, MAX_IMPORTED_ITEMS
+        ))
+    }
+
+    pub fn is_qid(value: &str) -> bool {
+        value.len() > 1
+            && value.starts_with('Q')
+            && value[1..].chars().all(|c| c.is_ascii_digit())
+    }
+
+    // Item (Q) or property (P) ID
+    pub fn is_entity_id(value: &str) -> bool {
+        value.len() > 1
+            && (value.starts_with('Q') || value.starts_with('P'))
+            && value[1..].chars().all(|c| c.is_ascii_digit())
+    }
+
+    // Run an import query against the configured Wikidata query service
+    pub async fn query_items(config: &WikidataConfig, input: &str, language: &str) -> Result<Vec<QueriedItem>, SparqlError> {
+        let query = build_item_query(input, language)?;
+        log!("[SPARQL] Running import query: {}", query);
+
+        let data = run_query(config, &query).await?;
+        let items = parse_item_bindings(&data);
+        log!("[SPARQL] Import query returned {} items", items.len());
+        Ok(items)
+    }
+
+    // Suggest the properties most used by instances of the items' classes (P31).
+    // Usage is counted over a sample of instances to keep the query fast
+    pub async fn suggest_properties(config: &WikidataConfig, wikidata_ids: &[String]) -> Result<Vec<PropertySuggestion>, SparqlError> {
+        let values: Vec<String> = wikidata_ids
+            .iter()
+            .filter(|id| is_qid(id))
+            .map(|id| format!("wd:{}", id))
+            .collect();
+        if values.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let query = format!(
+            r#"SELECT ?property ?propertyLabel (COUNT(DISTINCT ?sample) AS ?uses) WHERE {{
+                {{
+                    SELECT DISTINCT ?sample WHERE {{
+                        VALUES ?item {{ {} }}
+                        ?item wdt:P31 ?class .
+                        ?sample wdt:P31 ?class .
+                    }}
+                    LIMIT {}
+                }}
+                ?sample ?claim ?value .
+                ?property wikibase:directClaim ?claim .
+                SERVICE wikibase:label {{ bd:serviceParam wikibase:language "en". }}
+            }}
+            GROUP BY ?property ?propertyLabel
+            ORDER BY DESC(?uses)
+            LIMIT {}"#,
+            values.join(" "),
+            SUGGESTION_SAMPLE_SIZE,
+            MAX_SUGGESTIONS
+        );
+        log!("[SPARQL] Fetching property suggestions for {} items", values.len());
+
+        let data = run_query(config, &query).await?;
+        let mut suggestions = Vec::new();
+        if let Some(bindings) = data["results"]["bindings"].as_array() {
+            for binding in bindings {
+                let Some(property) = binding["property"]["value"]
+                    .as_str()
+                    .and_then(|uri| uri.parse::<PropertyId>().ok())
+                else {
+                    continue;
+                };
+                let property = property.to_string();
+                suggestions.push(PropertySuggestion {
+                    label: binding["propertyLabel"]["value"]
+                        .as_str()
+                        .unwrap_or(&property)
+                        .to_string(),
+                    property,
+                    uses: binding["uses"]["value"]
+                        .as_str()
+                        .and_then(|uses| uses.parse().ok())
+                        .unwrap_or_default(),
+                });
+            }
+        }
+        Ok(suggestions)
+    }
+
+    // English label of a Wikidata property, falling back to its ID.
+    // None when there is no such property
+    pub async fn property_label(config: &WikidataConfig, property: &str) -> Result<Option<String>, SparqlError> {
+        if !is_property_id(property) {
+            return Ok(None);
+        }
+        let query = format!(
+            r#"SELECT ?label WHERE {{
+                wd:{0} a wikibase:Property .
+                OPTIONAL {{ wd:{0} rdfs:label ?label FILTER(LANG(?label) = "en") }}
+            }}
+            LIMIT 1"#,
+            property
+        );
+        log!("[SPARQL] Checking property {}", property);
+
+        let data = run_query(config, &query).await?;
+        let label = data["results"]["bindings"].as_array().and_then(|bindings| bindings.first()).map(|binding| {
+            binding["label"]["value"]
+                .as_str()
+                .unwrap_or(property)
+                .to_string()
+        });
+        Ok(label)
+    }
+
+    // English labels of entities from wbgetentities, entities without one are left out
+    pub async fn fetch_entity_labels(config: &WikidataConfig, ids: &[String]) -> Result<HashMap<String, String>, SparqlError> {
+        let mut labels = HashMap::new();
+        for ids in ids.chunks(ENTITIES_PER_REQUEST) {
+            labels.extend(entity_labels(&get_entities(config, ids, "labels", "en").await?));
+        }
+        Ok(labels)
+    }
+
+    // Claim values and image properties of entities from wbgetentities, see claim_values.
+    // Entities Wikidata doesn't know are left out
+    pub async fn fetch_claim_values(config: &WikidataConfig, ids: &[String]) -> Result<HashMap<String, ClaimValues>, SparqlError> {
+        let mut claims = HashMap::new();
+        for ids in ids.chunks(ENTITIES_PER_REQUEST) {
+            let data = get_entities(config, ids, "claims", "en").await?;
+            if let Some(entities) = data["entities"].as_object() {
+                for (id, entity) in entities {
+                    if entity.get("missing").is_none() {
+                        claims.insert(id.clone(), claim_values(&entity["claims"]));
+                    }
+                }
+            }
+        }
+        log!("[SPARQL] Fetched the claims of {} of {} entities", claims.len(), ids.len());
+        Ok(claims)
+    }
+
+    // Labels and descriptions of entities in `language` from wbgetentities
+    pub async fn fetch_entity_terms(
+        config: &WikidataConfig,
+        ids: &[String],
+        language: &str,
+    ) -> Result<HashMap<String, EntityTerms>, SparqlError> {
+        let mut terms = HashMap::new();
+        for ids in ids.chunks(ENTITIES_PER_REQUEST) {
+            terms.extend(entity_terms(&get_entities(config, ids, "labels|descriptions", language).await?, language));
+        }
+        Ok(terms)
+    }
+
+    // Some `props` of up to 50 entities from the Wikidata API, with their terms in `language`
+    async fn get_entities(config: &WikidataConfig, ids: &[String], props: &str, language: &str) -> Result<serde_json::Value, SparqlError> {
+        let ids = ids.join("|");
+        let params = [("action", "wbgetentities"), ("ids", &ids), ("props", props), ("languages", language), ("format", "json")];
+        let url = reqwest::Url::parse_with_params(&config.api_endpoint, params)
+            .map_err(|_| SparqlError::Endpoint(config.api_endpoint.clone()))?;
+        get_json(config, url, "application/json").await
+    }
+
+    // Send a query to the Wikidata query service and return the JSON results
+    async fn run_query(config: &WikidataConfig, query: &str) -> Result<serde_json::Value, SparqlError> {
+        let url = reqwest::Url::parse_with_params(&config.endpoint, [("query", query), ("format", "json")])
+            .map_err(|_| SparqlError::Endpoint(config.endpoint.clone()))?;
+        get_json(config, url, SPARQL_RESULTS).await
+    }
+
+    // Answer of a Wikidata request, through the recorded fixtures when the configuration
+    // has a fixture mode
+    async fn get_json(config: &WikidataConfig, url: reqwest::Url, accept: &str) -> Result<serde_json::Value, SparqlError> {
+        if let Some(fixtures) = WikidataFixtures::from_config(config) {
+            return Ok(fixtures.get(url.as_str(), accept).await?);
+        }
+
+        static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+        let client = CLIENT.get_or_init(|| {
+            reqwest::Client::builder()
+                .connect_timeout(CONNECT_TIMEOUT)
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default()
+        });
+        let response = client
+            .get(url)
+            .header(reqwest::header::ACCEPT, accept)
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(SparqlError::Status(response.status().as_u16()));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    // Collect the distinct items of a SPARQL JSON result
+    pub fn parse_item_bindings(data: &serde_json::Value) -> Vec<QueriedItem> {
+        let mut items: Vec<QueriedItem> = Vec::new();
+        if let Some(bindings) = data["results"]["bindings"].as_array() {
+            for binding in bindings {
+                let Some(wikidata_id) = binding["item"]["value"]
+                    .as_str()
+                    .and_then(|uri| uri.rsplit('/').next())
+                    .filter(|id| is_qid(id))
+                else {
+                    continue;
+                };
+                if items.iter().any(|item| item.wikidata_id == wikidata_id) {
+                    continue;
+                }
+                items.push(QueriedItem {
+                    wikidata_id: wikidata_id.to_string(),
+                    label: binding["itemLabel"]["value"]
+                        .as_str()
+                        .unwrap_or(wikidata_id)
+                        .to_string(),
+                    description: binding["itemDescription"]["value"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                });
+                if items.len() == MAX_IMPORTED_ITEMS {
+                    break;
+                }
+            }
+        }
+        items
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub use sparql_impl::{
+    build_item_query, fetch_claim_values, fetch_entity_labels, fetch_entity_terms, is_entity_id, is_qid, parse_item_bindings, property_label,
+    query_items, suggest_properties, ClaimValues, QueriedItem, SparqlError,
+    MAX_IMPORTED_ITEMS,
+};
@@ -0,0 +1,327 @@
+// Comparisons as tables for spreadsheets and documents, written as CSV, XLSX, Markdown and
+// HTML by the exporters of crate::export: a row per item with its name, description and
+// selected properties. Quantities are converted to the comparison's unit system, and the
+// values they were entered as can be kept in a column next to the converted ones
+use crate::diagnostics::zip;
+use crate::models::item::Item;
+use crate::models::quantity::UnitSystem;
+use std::collections::HashMap;
+
+pub const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+pub const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+pub const MARKDOWN_CONTENT_TYPE: &str = "text/markdown; charset=utf-8";
+pub const HTML_CONTENT_TYPE: &str = "text/html; charset=utf-8";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+// Value of an item's property in the unit system, with the value it was entered as when
+// it was converted. Values edited by hand since their quantity was fetched are kept as they are
+fn system_value(item: &Item, property: &str, system: UnitSystem) -> (String, Option<String>) {
+    let text = item.custom_properties.get(property).cloned().unwrap_or_default();
+    let converted = item
+        .quantities
+        .get(property)
+        .filter(|quantity| quantity.is_shown_as(&text))
+        .and_then(|quantity| quantity.in_system(system))
+        .map(|quantity| quantity.to_text())
+        .filter(|converted| *converted != text);
+    match converted {
+        Some(converted) => (converted, Some(text)),
+        None => (text, None),
+    }
+}
+
+impl Table {
+    // Table of the items with the selected properties in display order, headed by the
+    // labels of the properties that have one. With `keep_original`, properties with
+    // converted values get a column of the original values after theirs
+    pub fn new(
+        items: &[Item],
+        properties: &[String],
+        labels: &HashMap<String, String>,
+        system: UnitSystem,
+        keep_original: bool,
+    ) -> Self {
+        let mut headers = vec!["Name".to_string(), "Description".to_string()];
+        let mut rows: Vec<Vec<String>> = items.iter().map(|item| vec![item.name.clone(), item.description.clone()]).collect();
+        for property in properties.iter().filter(|property| !["name", "description"].contains(&property.as_str())) {
+            let label = labels.get(property).cloned().unwrap_or_else(|| property.clone());
+            let values: Vec<(String, Option<String>)> = items.iter().map(|item| system_value(item, property, system)).collect();
+            let with_original = keep_original && values.iter().any(|(_, original)| original.is_some());
+            headers.push(label.clone());
+            if with_original {
+                headers.push(format!("{} (original)", label));
+            }
+            for (row, (value, original)) in rows.iter_mut().zip(values) {
+                if with_original {
+                    // Values that needed no conversion are their own original
+                    row.push(value.clone());
+                    row.push(original.unwrap_or(value));
+                } else {
+                    row.push(value);
+                }
+            }
+        }
+        Table { headers, rows }
+    }
+
+    fn lines(&self) -> impl Iterator<Item = &Vec<String>> {
+        std::iter::once(&self.headers).chain(self.rows.iter())
+    }
+
+    pub fn to_csv(&self) -> String {
+        self.lines()
+            .map(|line| line.iter().map(|cell| csv_field(cell)).collect::<Vec<_>>().join(",") + "\r\n")
+            .collect()
+    }
+
+    // Markdown document with the title as heading, the description and the table
+    pub fn to_markdown(&self, title: &str, description: &str) -> String {
+        let mut markdown = format!("# {}\n\n", markdown_cell(title));
+        if !description.is_empty() {
+            markdown.push_str(&format!("{}\n\n", description));
+        }
+        let row = |cells: &Vec<String>| format!("| {} |\n", cells.iter().map(|cell| markdown_cell(cell)).collect::<Vec<_>>().join(" | "));
+        markdown.push_str(&row(&self.headers));
+        markdown.push_str(&format!("|{}\n", " --- |".repeat(self.headers.len())));
+        for cells in &self.rows {
+            markdown.push_str(&row(cells));
+        }
+        markdown
+    }
+
+    // Standalone HTML page with the title as heading, the description and the table
+    pub fn to_html(&self, title: &str, description: &str) -> String {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n",
+            html_text(title)
+        );
+        if !description.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", html_text(description)));
+        }
+        let row = |tag: &str, cells: &Vec<String>| {
+            let cells: Vec<String> = cells.iter().map(|cell| format!("<{0}>{1}</{0}>", tag, html_text(cell))).collect();
+            format!("<tr>{}</tr>\n", cells.concat())
+        };
+        html.push_str("<table>\n<thead>\n");
+        html.push_str(&row("th", &self.headers));
+        html.push_str("</thead>\n<tbody>\n");
+        for cells in &self.rows {
+            html.push_str(&row("td", cells));
+        }
+        html.push_str("</tbody>\n</table>\n</body>\n</html>\n");
+        html
+    }
+
+    // Workbook with the table on a single sheet, the header row in bold
+    pub fn to_xlsx(&self, now: i64) -> Vec<u8> {
+        let mut sheet = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+            "<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\"><sheetData>",
+        ));
+        for (row_index, cells) in self.lines().enumerate() {
+            sheet.push_str(&format!("<row r=\"{}\">", row_index + 1));
+            // Style 1 of styles.xml is the bold font
+            let style = if row_index == 0 { " s=\"1\"" } else { "" };
+            for (column_index, cell) in cells.iter().enumerate() {
+                sheet.push_str(&format!(
+                    "<c r=\"{}{}\" t=\"inlineStr\"{}><is><t xml:space=\"preserve\">{}</t></is></c>",
+                    column_name(column_index),
+                    row_index + 1,
+                    style,
+                    xml_text(cell)
+                ));
+            }
+            sheet.push_str("</row>");
+        }
+        sheet.push_str("</sheetData></worksheet>\n");
+        zip(
+            &[
+                ("[Content_Types].xml", XLSX_CONTENT_TYPES.to_string()),
+                ("_rels/.rels", XLSX_ROOT_RELATIONSHIPS.to_string()),
+                ("xl/workbook.xml", XLSX_WORKBOOK.to_string()),
+                ("xl/_rels/workbook.xml.rels", XLSX_WORKBOOK_RELATIONSHIPS.to_string()),
+                ("xl/styles.xml", XLSX_STYLES.to_string()),
+                ("xl/worksheets/sheet1.xml", sheet),
+            ],
+            now,
+        )
+    }
+}
+
+// Quoted when it holds a separator, quote or line break. Cells starting like a formula
+// get a leading apostrophe, so spreadsheets show the text instead of running it
+fn csv_field(cell: &str) -> String {
+    let cell = if cell.starts_with(['=', '+', '@']) { format!("'{}", cell) } else { cell.to_string() };
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell
+    }
+}
+
+fn markdown_cell(cell: &str) -> String {
+    cell.replace('\\', "\\\\").replace('|', "\\|").replace("\r\n", "<br>").replace('\n', "<br>")
+}
+
+// Text escaped for XML, without the control characters XML can't hold
+fn xml_text(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// Text escaped for HTML, line breaks kept
+fn html_text(text: &str) -> String {
+    xml_text(text).replace('"', "&quot;").replace("\r\n", "<br>").replace('\n', "<br>")
+}
+
+// Spreadsheet name of a column counted from 0: A to Z, then AA
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+const XLSX_CONTENT_TYPES: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+    "<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">",
+    "<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>",
+    "<Default Extension=\"xml\" ContentType=\"application/xml\"/>",
+    "<Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>",
+    "<Override PartName=\"/xl/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml\"/>",
+    "<Override PartName=\"/xl/worksheets/sheet1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>",
+    "</Types>\n",
+);
+
+const XLSX_ROOT_RELATIONSHIPS: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+    "<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+    "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/>",
+    "</Relationships>\n",
+);
+
+const XLSX_WORKBOOK: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+    "<workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">",
+    "<sheets><sheet name=\"Comparison\" sheetId=\"1\" r:id=\"rId1\"/></sheets>",
+    "</workbook>\n",
+);
+
+const XLSX_WORKBOOK_RELATIONSHIPS: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+    "<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+    "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet1.xml\"/>",
+    "<Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles\" Target=\"styles.xml\"/>",
+    "</Relationships>\n",
+);
+
+const XLSX_STYLES: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+    "<styleSheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">",
+    "<fonts count=\"2\"><font><sz val=\"11\"/><name val=\"Calibri\"/></font><font><b/><sz val=\"11\"/><name val=\"Calibri\"/></font></fonts>",
+    "<fills count=\"2\"><fill><patternFill patternType=\"none\"/></fill><fill><patternFill patternType=\"gray125\"/></fill></fills>",
+    "<borders count=\"1\"><border><left/><right/><top/><bottom/><diagonal/></border></borders>",
+    "<cellStyleXfs count=\"1\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\"/></cellStyleXfs>",
+    "<cellXfs count=\"2\"><xf numFmtId=\"0\" fontId=\"0\" fillId=\"0\" borderId=\"0\" xfId=\"0\"/><xf numFmtId=\"0\" fontId=\"1\" fillId=\"0\" borderId=\"0\" xfId=\"0\" applyFont=\"1\"/></cellXfs>",
+    "</styleSheet>\n",
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::quantity::Quantity;
+    use leptos::logging::log;
+
+    fn laptop(id: &str, name: &str, weight: &str, quantity: Option<Quantity>) -> Item {
+        Item {
+            id: id.into(),
+            name: name.into(),
+            description: "Light, \"thin\"".into(),
+            wikidata_id: None,
+            custom_properties: HashMap::from([("P2067".to_string(), weight.to_string()), ("notes".to_string(), "a | b\nc".to_string())]),
+            quantities: quantity.into_iter().map(|quantity| ("P2067".to_string(), quantity)).collect(),
+            group: None,
+            tip_recipient: None,
+            status: None,
+            sources: HashMap::new(),
+            updated_at: None,
+        }
+    }
+
+    fn kilograms(amount: f64) -> Quantity {
+        Quantity { amount, unit_id: Some("Q11570".into()), unit: None, precision: None }
+    }
+
+    #[test]
+    fn test_table_conversion() {
+        log!("[TEST] Starting test_table_conversion");
+        let items = [
+            laptop("item1", "Laptop", "1.5 kg", Some(kilograms(1.5))),
+            // Edited by hand since the quantity was fetched
+            laptop("item2", "=Tablet", "about a kilo", Some(kilograms(1.0))),
+        ];
+        let properties = ["P2067".to_string(), "notes".to_string()];
+        let labels = HashMap::from([("P2067".to_string(), "mass".to_string())]);
+
+        let entered = Table::new(&items, &properties, &labels, UnitSystem::AsEntered, true);
+        assert_eq!(entered.headers, ["Name", "Description", "mass", "notes"]);
+        assert_eq!(entered.rows[0][2], "1.5 kg");
+
+        let imperial = Table::new(&items, &properties, &labels, UnitSystem::Imperial, false);
+        assert_eq!(imperial.rows[0][2], "3.307 lb");
+        assert_eq!(imperial.rows[1][2], "about a kilo");
+
+        let with_original = Table::new(&items, &properties, &labels, UnitSystem::Imperial, true);
+        assert_eq!(with_original.headers, ["Name", "Description", "mass", "mass (original)", "notes"]);
+        assert_eq!(with_original.rows[0][2..4], ["3.307 lb", "1.5 kg"]);
+        assert_eq!(with_original.rows[1][2..4], ["about a kilo", "about a kilo"]);
+        log!("[TEST] test_table_conversion completed successfully");
+    }
+
+    #[test]
+    fn test_table_formats() {
+        log!("[TEST] Starting test_table_formats");
+        let items = [laptop("item1", "=Tablet", "1.5 kg", None)];
+        let table = Table::new(&items, &["notes".to_string()], &HashMap::new(), UnitSystem::Metric, false);
+
+        let csv = table.to_csv();
+        assert_eq!(csv, "Name,Description,notes\r\n'=Tablet,\"Light, \"\"thin\"\"\",\"a | b\nc\"\r\n");
+
+        let markdown = table.to_markdown("Laptops", "");
+        assert!(markdown.starts_with("# Laptops\n\n| Name | Description | notes |\n| --- | --- | --- |\n"));
+        assert!(markdown.contains("| =Tablet | Light, \"thin\" | a \\| b<br>c |"));
+
+        let xlsx = table.to_xlsx(1_700_000_000);
+        assert!(xlsx.starts_with(b"PK\x03\x04"));
+        let sheet = String::from_utf8_lossy(&xlsx);
+        assert!(sheet.contains("<c r=\"C1\" t=\"inlineStr\" s=\"1\"><is><t xml:space=\"preserve\">notes</t></is></c>"));
+        assert!(sheet.contains("<c r=\"A2\" t=\"inlineStr\"><is><t xml:space=\"preserve\">=Tablet</t></is></c>"));
+
+        let html = table.to_html("Laptops & tablets", "");
+        assert!(html.contains("<title>Laptops &amp; tablets</title>"));
+        assert!(!html.contains("<p>"));
+        assert!(html.contains("<thead>\n<tr><th>Name</th><th>Description</th><th>notes</th></tr>\n</thead>"));
+        assert!(html.contains("<tr><td>=Tablet</td><td>Light, &quot;thin&quot;</td><td>a | b<br>c</td></tr>"));
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(column_name(702), "AAA");
+        log!("[TEST] test_table_formats completed successfully");
+    }
+}
@@ -0,0 +1,123 @@
+// Paths of the comparison API, shared by the client and the server. Comparison URLs
+// are full URLs sent as a single path segment, `/api/urls/https%3A%2F%2Fexample.com%2Flaptops/items`,
+// so every segment is percent-encoded, slashes and percent signs included. actix
+// matches `{url}` on the encoded path and its path extractors decode each segment
+// exactly once, which makes `decode_segment(encode_segment(s)) == s` the whole contract
+use std::borrow::Cow;
+
+pub fn encode_segment(segment: &str) -> Cow<'_, str> {
+    urlencoding::encode(segment)
+}
+
+// None when the decoded bytes aren't UTF-8
+pub fn decode_segment(segment: &str) -> Option<String> {
+    urlencoding::decode(segment).ok().map(Cow::into_owned)
+}
+
+// `/api/urls/{url}/{segments...}` with the URL and every segment encoded
+pub fn comparison_api_path(url: &str, segments: &[&str]) -> String {
+    let mut path = format!("/api/urls/{}", encode_segment(url));
+    for segment in segments {
+        path.push('/');
+        path.push_str(&encode_segment(segment));
+    }
+    path
+}
+
+// `/ws/urls/{url}`, the WebSocket pushing the comparison's changes
+pub fn comparison_ws_path(url: &str) -> String {
+    format!("/ws/urls/{}", encode_segment(url))
+}
+
+// URL for the `attempt`-th copy of a comparison, starting at 1: its path followed by
+// `-copy`, `-copy-2` and so on, without the query and fragment
+pub fn copy_url(url: &str, attempt: usize) -> String {
+    let base = url.split(['?', '#']).next().unwrap_or(url).trim_end_matches('/');
+    let suffix = if attempt <= 1 { "copy".to_string() } else { format!("copy-{}", attempt) };
+    // The root page of a site gets a path rather than a longer host name
+    let is_root = base.split_once("://").is_some_and(|(_, rest)| !rest.contains('/'));
+    format!("{}{}{}", base, if is_root { '/' } else { '-' }, suffix)
+}
+
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use super::*;
+            use actix_web::{test as actix_test, web, App, HttpResponse};
+    use leptos::logging::log;
+
+    const NASTY_URLS: &[&str] = &[
+        "https://example.com/laptops",
+        "https://example.com/a/b/c?sort=price&order=asc",
+        "https://example.com/100%25 done",
+        "https://example.com/50% off",
+        "https://example.com/a%2Fb",
+        "https://example.com/a+b c",
+        "https://example.com/über/ñandú?q=€#section",
+        "https://example.com/%",
+        "urn:isbn:0451450523",
+    ];
+
+    #[test]
+    fn test_segment_round_trip() {
+        log!("[TEST] Starting test_segment_round_trip");
+        for url in NASTY_URLS {
+            let encoded = encode_segment(url);
+            assert!(!encoded.contains('/'), "{} still contains a slash", encoded);
+            assert_eq!(decode_segment(&encoded).as_deref(), Some(*url));
+        }
+        assert_eq!(decode_segment("%FF"), None);
+        log!("[TEST] test_segment_round_trip completed successfully");
+    }
+
+    #[test]
+    fn test_copy_url() {
+        log!("[TEST] Starting test_copy_url");
+        assert_eq!(copy_url("https://example.com/laptops?sort=name#top", 1), "https://example.com/laptops-copy");
+        assert_eq!(copy_url("https://example.com/laptops/", 3), "https://example.com/laptops-copy-3");
+        assert_eq!(copy_url("https://example.com/", 1), "https://example.com/copy");
+        assert_eq!(copy_url("urn:isbn:0451450523", 2), "urn:isbn:0451450523-copy-2");
+        log!("[TEST] test_copy_url completed successfully");
+    }
+
+    #[actix_web::test]
+    async fn test_path_round_trip_through_actix() {
+        log!("[TEST] Starting test_path_round_trip_through_actix");
+        // Same scope layout as the API routes in main.rs
+        let app = actix_test::init_service(
+            App::new()
+                .service(
+                    web::scope("/api/urls/{url}")
+                        .route("/items", web::get().to(|url: web::Path<String>| async move {
+                            HttpResponse::Ok().body(url.into_inner())
+                        }))
+                        .route("/properties/{property}", web::delete().to(|path: web::Path<(String, String)>| async move {
+                            let (url, property) = path.into_inner();
+                            HttpResponse::Ok().body(format!("{}\n{}", url, property))
+                        })),
+                ),
+        )
+        .await;
+
+        for url in NASTY_URLS {
+            let request = actix_test::TestRequest::get().uri(&comparison_api_path(url, &["items"])).to_request();
+            let body = actix_test::call_and_read_body(&app, request).await;
+            assert_eq!(String::from_utf8_lossy(&body), *url);
+
+            // Custom property names can be just as nasty
+            let request = actix_test::TestRequest::delete()
+                .uri(&comparison_api_path(url, &["properties", "price/kg (%)"]))
+                .to_request();
+            let body = actix_test::call_and_read_body(&app, request).await;
+            assert_eq!(String::from_utf8_lossy(&body), format!("{}\nprice/kg (%)", url));
+        }
+        log!("[TEST] Nasty URLs - PASSED");
+
+        // Segments that don't decode to UTF-8 are decoded lossily rather than rejected
+        let request = actix_test::TestRequest::get().uri("/api/urls/%FF/items").to_request();
+        let body = actix_test::call_and_read_body(&app, request).await;
+        assert_eq!(String::from_utf8_lossy(&body), "\u{FFFD}");
+        log!("[TEST] Invalid encoding - PASSED");
+
+        log!("[TEST] test_path_round_trip_through_actix completed successfully");
+    }
+}
@@ -0,0 +1,48 @@
+// Reads of private comparisons. Requests to the /api/urls/{url} scope of a private comparison
+// need one of its edit tokens or an API key allowed to edit it, whatever they do, so its items,
+// exports, feeds and copies stay with its editors. Unlisted comparisons are read by anyone
+// with their URL and only left out of the index and search
+use crate::api::{request_access, ApiError};
+use crate::comparison_metrics::ComparisonPath;
+use crate::db::Database;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{web, ResponseError};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+// Whether a request to the comparison scope is answered whatever the visibility: the
+// access endpoint tells pages what their visitor may do, and signed export links are
+// checked by the export itself
+fn is_exempt(req: &ServiceRequest) -> bool {
+    let rest = req.match_info().unprocessed();
+    let signed_export = rest.starts_with("/export")
+        && !rest[1..].contains('/')
+        && web::Query::<HashMap<String, String>>::from_query(req.query_string())
+            .is_ok_and(|query| query.contains_key("signature"));
+    rest == "/access" || signed_export
+}
+
+// Middleware of the /api/urls/{url} scope, refusing requests to private comparisons
+// that don't come from one of their editors
+pub async fn enforce_visibility(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let url = req.match_info().load::<ComparisonPath>().ok().map(|path| path.url);
+    let db = req.app_data::<web::Data<Arc<RwLock<Database>>>>().cloned();
+    if let (Some(url), Some(db), false) = (url, db, is_exempt(&req)) {
+        let access = request_access(&*db.read().await, &url, req.request()).await;
+        let error = match access {
+            Ok(access) if access.can_read() => None,
+            Ok(_) => Some(ApiError::Forbidden("This comparison is private, a valid edit token or API key is required".into())),
+            Err(e) => Some(e),
+        };
+        if let Some(error) = error {
+            return Ok(req.into_response(error.error_response()).map_into_right_body());
+        }
+    }
+    next.call(req).await.map(ServiceResponse::map_into_left_body)
+}
@@ -0,0 +1,250 @@
+// Recorded wikidata.org responses for tests, turned on by the fixture mode of the
+// Wikidata configuration. Recording sends requests to Wikidata and writes each response
+// to a file named after a hash of the request URL. Replaying reads those files back and
+// never leaves the machine, so the typeahead and property lookups behave the same on every run
+use crate::config::WikidataConfig;
+use crate::models::wikidata::{WIKIDATA_API, WIKIDATA_SPARQL};
+use crate::server_log;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// Wikidata requests the pages may send through /api/wikidata/fixtures
+const FIXTURE_URL_PREFIXES: [&str; 2] = [WIKIDATA_API, WIKIDATA_SPARQL];
+// Identifies recording runs to Wikidata, like the query service clients
+const USER_AGENT: &str = concat!("CompareWare/", env!("CARGO_PKG_VERSION"), " (fixture recorder)");
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FixtureMode {
+    // Send requests to Wikidata and save the responses
+    Record,
+    // Answer requests from the saved responses only
+    Replay,
+}
+
+impl FromStr for FixtureMode {
+    type Err = ();
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "record" => Ok(FixtureMode::Record),
+            "replay" => Ok(FixtureMode::Replay),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FixtureError {
+    #[error("No recorded Wikidata response for {0}")]
+    Missing(String),
+    #[error("Wikidata request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("Wikidata answered {0}")]
+    Status(u16),
+    #[error("Failed to access the fixture {path}: {source}")]
+    Io { path: String, source: std::io::Error },
+    #[error("Invalid fixture {path}: {source}")]
+    Parse { path: String, source: serde_json::Error },
+}
+
+// Whether a URL is a request the pages send to Wikidata
+pub fn is_wikidata_request(url: &str) -> bool {
+    FIXTURE_URL_PREFIXES
+        .iter()
+        .any(|prefix| url.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('?')))
+}
+
+// Contents of a fixture file. The URL is kept to tell which request it answers
+#[derive(Serialize, Deserialize, Debug)]
+struct Fixture {
+    url: String,
+    response: serde_json::Value,
+}
+
+pub struct WikidataFixtures {
+    mode: FixtureMode,
+    dir: PathBuf,
+}
+
+impl WikidataFixtures {
+    pub fn new(mode: FixtureMode, dir: impl Into<PathBuf>) -> Self {
+        WikidataFixtures { mode, dir: dir.into() }
+    }
+
+    // Fixtures of a configuration, None when it doesn't set a fixture mode
+    pub fn from_config(config: &WikidataConfig) -> Option<Self> {
+        config.fixture_mode.map(|mode| WikidataFixtures::new(mode, &config.fixture_dir))
+    }
+
+    // File of the response to a request
+    pub fn path(&self, url: &str) -> PathBuf {
+        let digest = Sha256::digest(url.as_bytes());
+        let hash = u64::from_be_bytes(digest[..8].try_into().expect("SHA-256 digests have 32 bytes"));
+        self.dir.join(format!("{:016x}.json", hash))
+    }
+
+    // JSON response to a GET request, recorded or replayed
+    pub async fn get(&self, url: &str, accept: &str) -> Result<serde_json::Value, FixtureError> {
+        let path = self.path(url);
+        match self.mode {
+            FixtureMode::Replay => match read_fixture(&path)? {
+                Some(fixture) if fixture.url == url => Ok(fixture.response),
+                _ => Err(FixtureError::Missing(url.to_string())),
+            },
+            FixtureMode::Record => {
+                let response = reqwest::Client::new()
+                    .get(url)
+                    .header(reqwest::header::ACCEPT, accept)
+                    .header(reqwest::header::USER_AGENT, USER_AGENT)
+                    .send()
+                    .await?;
+                if !response.status().is_success() {
+                    return Err(FixtureError::Status(response.status().as_u16()));
+                }
+                let fixture = Fixture { url: url.to_string(), response: response.json().await? };
+                write_fixture(&path, &fixture)?;
+                server_log!("[FIXTURES] Recorded {} into {}", url, path.display());
+                Ok(fixture.response)
+            }
+        }
+    }
+}
+
+// Fixture stored at a path, None when there is none
+fn read_fixture(path: &Path) -> Result<Option<Fixture>, FixtureError> {
+    let io_error = |source| FixtureError::Io { path: path.display().to_string(), source };
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(io_error(e)),
+    };
+    serde_json::from_str(&text)
+        .map(Some)
+        .map_err(|source| FixtureError::Parse { path: path.display().to_string(), source })
+}
+
+fn write_fixture(path: &Path, fixture: &Fixture) -> Result<(), FixtureError> {
+    let io_error = |source| FixtureError::Io { path: path.display().to_string(), source };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(io_error)?;
+    }
+    // Pretty-printed with a final newline, so re-recorded fixtures give readable diffs
+    let text = serde_json::to_string_pretty(fixture).expect("fixtures are plain JSON") + "\n";
+    std::fs::write(path, text).map_err(io_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::wikidata::{
+        claim_values, datavalue_quantity, datavalue_text, entities_url, entity_labels, parse_property_labels, property_labels_url,
+        referenced_entity_ids, search_url, WikidataSearchResponse, WikidataSuggestion,
+    };
+    use crate::sparql::{property_label, suggest_properties, SparqlError};
+    use leptos::logging::log;
+    use serde_json::json;
+    use std::collections::HashSet;
+    use std::io::{Read, Write};
+
+    const JSON: &str = "application/json";
+
+    // Answer one HTTP request on a local port with a JSON body, returning the server's URL
+    fn serve_once(body: &'static str) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.read(&mut [0; 4096]);
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+        format!("http://{}", address)
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[actix_web::test]
+    async fn test_record_and_replay() {
+        log!("[TEST] Starting test_record_and_replay");
+        let dir = std::env::temp_dir().join(format!("compareware-fixtures-{}", uuid::Uuid::new_v4()));
+        let url = format!("{}/sparql?query=ASK&format=json", serve_once(r#"{"boolean":true}"#));
+        let recorded = WikidataFixtures::new(FixtureMode::Record, &dir).get(&url, JSON).await.unwrap();
+        assert_eq!(recorded, json!({ "boolean": true }));
+
+        // Test the response is replayed from the file, the server answered its only request
+        let replay = WikidataFixtures::new(FixtureMode::Replay, &dir);
+        assert_eq!(replay.get(&url, JSON).await.unwrap(), recorded);
+        let other = format!("{}&timeout=1", url);
+        assert!(matches!(replay.get(&other, JSON).await, Err(FixtureError::Missing(missing)) if missing == other));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(is_wikidata_request(&search_url("laptop", "en")));
+        assert!(is_wikidata_request(&property_labels_url(&ids(&["P31"]))));
+        assert!(!is_wikidata_request("https://www.wikidata.org/w/api.php.example.com/?action=query"));
+        assert!(!is_wikidata_request("http://127.0.0.1:9/sparql?query=ASK"));
+        log!("[TEST] test_record_and_replay completed successfully");
+    }
+
+    // The pages' and the server's Wikidata lookups, replayed from fixtures/wikidata
+    #[actix_web::test]
+    async fn test_replayed_wikidata() {
+        log!("[TEST] Starting test_replayed_wikidata");
+        let config = WikidataConfig { fixture_mode: Some(FixtureMode::Replay), ..Default::default() };
+        let fixtures = WikidataFixtures::from_config(&config).unwrap();
+
+        // Typeahead of the item name cells
+        let search: WikidataSearchResponse = serde_json::from_value(fixtures.get(&search_url("laptop", "en"), JSON).await.unwrap()).unwrap();
+        let laptop = WikidataSuggestion {
+            id: "Q3962".into(),
+            label: "laptop".into(),
+            description: Some("portable personal computer".into()),
+        };
+        assert_eq!(search.search[0], laptop);
+        assert_eq!(search.search[1].description, None);
+
+        // Claims of the picked entity, one value per property
+        let data = fixtures.get(&entities_url(&ids(&["Q3962"]), "claims"), JSON).await.unwrap();
+        let (values, images) = claim_values(&data["entities"]["Q3962"]["claims"]);
+        assert_eq!(images, HashSet::from(["P18".to_string()]));
+        // Statements without a value are skipped
+        assert!(!values.contains_key("P571"));
+        let mut referenced: Vec<String> = values.values().flat_map(referenced_entity_ids).collect();
+        referenced.sort();
+        assert_eq!(referenced, ["Q11570", "Q68"]);
+        let labels = entity_labels(&fixtures.get(&entities_url(&referenced, "labels"), JSON).await.unwrap());
+        assert_eq!(datavalue_text(&values["P279"], &labels).as_deref(), Some("computer"));
+        assert_eq!(datavalue_text(&values["P18"], &labels).as_deref(), Some("Laptop.jpg"));
+        // The preferred statement wins over the normal one
+        let mass = datavalue_quantity(&values["P2067"], &labels).unwrap();
+        assert_eq!((mass.amount, mass.unit.as_deref(), mass.unit_id.as_deref()), (1.5, Some("kilogram"), Some("Q11570")));
+        assert!((mass.precision.unwrap() - 0.1).abs() < 1e-9);
+
+        // Labels of the new property rows
+        let mut properties: Vec<String> = values.keys().cloned().collect();
+        properties.sort();
+        let property_labels = parse_property_labels(&fixtures.get(&property_labels_url(&properties), JSON).await.unwrap());
+        assert_eq!(property_labels["P2067"], "mass");
+        assert_eq!(property_labels.len(), 3);
+
+        // Property suggestions and checks of the server
+        let suggestions = suggest_properties(&config, &ids(&["Q3962"])).await.unwrap();
+        let suggested: Vec<&str> = suggestions.iter().map(|suggestion| suggestion.property.as_str()).collect();
+        assert_eq!(suggested, ["P31", "P176", "P2067"]);
+        assert_eq!((suggestions[1].label.as_str(), suggestions[1].uses), ("manufacturer", 143));
+        assert_eq!(property_label(&config, "P2067").await.unwrap().as_deref(), Some("mass"));
+
+        // Test requests nobody recorded fail instead of reaching Wikidata
+        assert!(matches!(property_label(&config, "P31").await, Err(SparqlError::Fixture(FixtureError::Missing(_)))));
+        log!("[TEST] test_replayed_wikidata completed successfully");
+    }
+}
@@ -0,0 +1,331 @@
+// Budget of the server's Wikidata traffic: requests that reach the query service are
+// rate limited per client and globally, and answers are kept in in-memory LRU caches
+use crate::config::WikidataConfig;
+use crate::db::Database;
+use crate::models::property::{EntityClaims, PropertySuggestion};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+// Clients tracked at most, the least recently seen are forgotten first
+pub(crate) const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+// Entries of the in-memory caches
+const LABEL_CACHE_CAPACITY: usize = 20_000;
+const CLAIMS_CACHE_CAPACITY: usize = 5_000;
+const SUGGESTION_CACHE_CAPACITY: usize = 1_000;
+// Entries read from SQLite are kept in memory for this long. The SQLite cache
+// doesn't report its expiry, so this bounds how long they outlive it
+const MEMORY_TTL_SECS: i64 = 10 * 60;
+
+// Bucket of up to `capacity` tokens, refilling at `capacity` tokens per minute
+pub(crate) struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    pub(crate) fn full(capacity: f64, now: Instant) -> Self {
+        TokenBucket { tokens: capacity, updated: now }
+    }
+
+    pub(crate) fn refill(&mut self, capacity: f64, now: Instant) {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * capacity / 60.0).min(capacity);
+        self.updated = now;
+    }
+
+    // Take a token, or return how long until one is available
+    pub(crate) fn take(&mut self, capacity: f64, now: Instant) -> Result<(), Duration> {
+        self.refill(capacity, now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) * 60.0 / capacity))
+        }
+    }
+}
+
+// Token buckets refilling at a number of requests per minute, one per client and one
+// shared by all clients. A request needs a token from both
+pub struct RateLimiter {
+    // Requests per minute, changed by admins through the instance settings
+    client_requests_per_minute: AtomicU32,
+    global_requests_per_minute: AtomicU32,
+    global: Mutex<TokenBucket>,
+    clients: Mutex<LruCache<String, TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(client_requests_per_minute: u32, global_requests_per_minute: u32) -> Self {
+        let global_requests_per_minute = global_requests_per_minute.max(1);
+        RateLimiter {
+            client_requests_per_minute: AtomicU32::new(client_requests_per_minute.max(1)),
+            global_requests_per_minute: AtomicU32::new(global_requests_per_minute),
+            global: Mutex::new(TokenBucket::full(global_requests_per_minute as f64, Instant::now())),
+            clients: Mutex::new(LruCache::new(MAX_TRACKED_CLIENTS)),
+        }
+    }
+
+    // Requests per minute of one client and of the whole instance
+    pub fn limits(&self) -> (u32, u32) {
+        (
+            self.client_requests_per_minute.load(Ordering::Relaxed),
+            self.global_requests_per_minute.load(Ordering::Relaxed),
+        )
+    }
+
+    // Change the limits at runtime. Buckets keep their tokens, up to the new capacity
+    pub fn set_limits(&self, client_requests_per_minute: u32, global_requests_per_minute: u32) {
+        self.client_requests_per_minute.store(client_requests_per_minute.max(1), Ordering::Relaxed);
+        self.global_requests_per_minute.store(global_requests_per_minute.max(1), Ordering::Relaxed);
+    }
+
+    // Take a token for a request of `client`, or return how long it has to wait
+    pub async fn check(&self, client: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let capacity = self.limits().0 as f64;
+        let mut clients = self.clients.lock().await;
+        let bucket = clients.get_or_insert_with(client.to_string(), || TokenBucket::full(capacity, now));
+        bucket.take(capacity, now)?;
+        if let Err(wait) = self.check_global().await {
+            // The request isn't made, so it doesn't count against the client
+            bucket.tokens += 1.0;
+            return Err(wait);
+        }
+        Ok(())
+    }
+
+    // Take a token from the shared budget only, for requests no client waits for
+    pub async fn check_global(&self) -> Result<(), Duration> {
+        let capacity = self.limits().1 as f64;
+        self.global.lock().await.take(capacity, Instant::now())
+    }
+}
+
+// Least recently used cache with a fixed number of entries
+pub struct LruCache<K, V> {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<K, (V, u64)>,
+    order: BTreeMap<u64, K>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        LruCache {
+            capacity: capacity.max(1),
+            tick: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        self.get_mut(key).map(|value| &*value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.tick += 1;
+        let (value, used) = self.entries.get_mut(key)?;
+        self.order.remove(used);
+        *used = self.tick;
+        self.order.insert(self.tick, key.clone());
+        Some(value)
+    }
+
+    // Entry of `key`, inserted with `value` first when there is none
+    pub fn get_or_insert_with(&mut self, key: K, value: impl FnOnce() -> V) -> &mut V {
+        if !self.entries.contains_key(&key) {
+            self.insert(key.clone(), value());
+        }
+        self.get_mut(&key).expect("entry was just inserted")
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.tick += 1;
+        if let Some((_, used)) = self.entries.insert(key.clone(), (value, self.tick)) {
+            self.order.remove(&used);
+        }
+        self.order.insert(self.tick, key);
+        while self.entries.len() > self.capacity {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// A cached value with the time it was fetched
+#[derive(Clone)]
+struct Cached<V> {
+    value: V,
+    fetched_at: i64,
+}
+
+// How a cached value may be served
+pub enum Freshness<V> {
+    Fresh(V),
+    // Still served, but should be refreshed
+    Stale(V),
+    Missing,
+}
+
+pub struct WikidataProxy {
+    pub limiter: RateLimiter,
+    // Endpoint and cache lifetimes
+    pub config: WikidataConfig,
+    labels: Mutex<LruCache<String, Cached<String>>>,
+    claims: Mutex<LruCache<String, Cached<EntityClaims>>>,
+    suggestions: Mutex<LruCache<String, Cached<Vec<PropertySuggestion>>>>,
+    // Suggestion keys being refreshed in the background
+    refreshing: Mutex<HashSet<String>>,
+}
+
+impl WikidataProxy {
+    pub fn new(limiter: RateLimiter, config: WikidataConfig) -> Self {
+        WikidataProxy {
+            limiter,
+            config,
+            labels: Mutex::new(LruCache::new(LABEL_CACHE_CAPACITY)),
+            claims: Mutex::new(LruCache::new(CLAIMS_CACHE_CAPACITY)),
+            suggestions: Mutex::new(LruCache::new(SUGGESTION_CACHE_CAPACITY)),
+            refreshing: Mutex::new(HashSet::new()),
+        }
+    }
+
+    // Cached labels, from memory when possible and from SQLite otherwise
+    pub async fn cached_labels(
+        &self,
+        db: &Database,
+        entity_ids: &[String],
+        now: i64,
+    ) -> Result<HashMap<String, String>, rusqlite::Error> {
+        let mut labels = self.labels.lock().await;
+        let (mut found, missing) = lookup(&mut labels, entity_ids, now);
+        let stored = db.get_cached_labels(&missing, now).await?;
+        remember(&mut labels, &stored, now);
+        found.extend(stored);
+        Ok(found)
+    }
+
+    // Cache labels in SQLite and in memory
+    pub async fn cache_labels(
+        &self,
+        db: &Database,
+        labels: &HashMap<String, String>,
+        expires_at: i64,
+        now: i64,
+    ) -> Result<(), rusqlite::Error> {
+        db.cache_labels(labels, expires_at, now).await?;
+        remember(&mut *self.labels.lock().await, labels, now);
+        Ok(())
+    }
+
+    // Cached claims, from memory when possible and from SQLite otherwise
+    pub async fn cached_claims(
+        &self,
+        db: &Database,
+        entity_ids: &[String],
+        now: i64,
+    ) -> Result<HashMap<String, EntityClaims>, rusqlite::Error> {
+        let mut claims = self.claims.lock().await;
+        let (mut found, missing) = lookup(&mut claims, entity_ids, now);
+        let stored = db.get_cached_claims(&missing, now).await?;
+        remember(&mut claims, &stored, now);
+        found.extend(stored);
+        Ok(found)
+    }
+
+    // Cache claims in SQLite and in memory
+    pub async fn cache_claims(
+        &self,
+        db: &Database,
+        claims: &HashMap<String, EntityClaims>,
+        expires_at: i64,
+        now: i64,
+    ) -> Result<(), rusqlite::Error> {
+        db.cache_claims(claims, expires_at, now).await?;
+        remember(&mut *self.claims.lock().await, claims, now);
+        Ok(())
+    }
+
+    // Property suggestions for a set of items, keyed by their sorted IDs
+    pub async fn suggestions(&self, key: &str, now: i64) -> Freshness<Vec<PropertySuggestion>> {
+        match self.suggestions.lock().await.get(&key.to_string()) {
+            Some(cached) if now - cached.fetched_at < self.config.suggestion_ttl_secs => Freshness::Fresh(cached.value.clone()),
+            Some(cached) if now - cached.fetched_at < self.config.suggestion_ttl_secs + self.config.suggestion_stale_secs => {
+                Freshness::Stale(cached.value.clone())
+            }
+            _ => Freshness::Missing,
+        }
+    }
+
+    pub async fn store_suggestions(&self, key: &str, suggestions: Vec<PropertySuggestion>, now: i64) {
+        self.suggestions.lock().await.insert(
+            key.to_string(),
+            Cached {
+                value: suggestions,
+                fetched_at: now,
+            },
+        );
+        self.refreshing.lock().await.remove(key);
+    }
+
+    // Claim the background refresh of stale suggestions. False when one is
+    // already running or the shared budget is used up
+    pub async fn start_refresh(&self, key: &str) -> bool {
+        let mut refreshing = self.refreshing.lock().await;
+        if refreshing.contains(key) || self.limiter.check_global().await.is_err() {
+            return false;
+        }
+        refreshing.insert(key.to_string())
+    }
+
+    pub async fn end_refresh(&self, key: &str) {
+        self.refreshing.lock().await.remove(key);
+    }
+}
+
+// Split entity IDs into those with an unexpired in-memory entry and the rest
+fn lookup<V: Clone>(
+    cache: &mut LruCache<String, Cached<V>>,
+    entity_ids: &[String],
+    now: i64,
+) -> (HashMap<String, V>, Vec<String>) {
+    let mut found = HashMap::new();
+    let mut missing = Vec::new();
+    for entity_id in entity_ids {
+        match cache.get(entity_id) {
+            Some(cached) if now - cached.fetched_at < MEMORY_TTL_SECS => {
+                found.insert(entity_id.clone(), cached.value.clone());
+            }
+            _ => missing.push(entity_id.clone()),
+        }
+    }
+    (found, missing)
+}
+
+fn remember<V: Clone>(cache: &mut LruCache<String, Cached<V>>, values: &HashMap<String, V>, now: i64) {
+    for (entity_id, value) in values {
+        cache.insert(
+            entity_id.clone(),
+            Cached {
+                value: value.clone(),
+                fetched_at: now,
+            },
+        );
+    }
+}
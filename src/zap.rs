@@ -0,0 +1,241 @@
+#[cfg(feature = "ssr")]
+mod zap_impl {
+    use leptos::logging::log;
+    use std::net::IpAddr;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+
+    // LNURL-pay amounts are in millisatoshis
+    pub const MSAT_PER_SAT: u64 = 1000;
+
+    // Lightning services answer within seconds, a tip request doesn't wait longer
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+    const MAX_REDIRECTS: usize = 5;
+
+    #[derive(Debug, thiserror::Error)]
+    pub enum ZapError {
+        #[error("Invalid lightning address: {0}")]
+        InvalidLightningAddress(String),
+        #[error("Amount must be between {min_sats} and {max_sats} sats")]
+        AmountOutOfRange { min_sats: u64, max_sats: u64 },
+        #[error("Lightning service request failed: {0}")]
+        Request(#[from] reqwest::Error),
+        #[error("Lightning service answered {0}")]
+        Status(u16),
+        #[error("Lightning service error: {0}")]
+        Service(String),
+    }
+
+    // LNURL-pay parameters of a lightning address (LUD-06, LUD-16), with the
+    // NIP-57 fields telling whether the service accepts zap requests
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct PayService {
+        pub callback: String,
+        pub min_sendable: u64,
+        pub max_sendable: u64,
+        pub comment_allowed: usize,
+        pub allows_nostr: bool,
+    }
+
+    // Split a lightning address ("user@domain") into its user and domain.
+    // Addresses are case-insensitive, the user part is lowercased for the well-known URL
+    pub fn parse_lightning_address(address: &str) -> Option<(&str, &str)> {
+        let (user, domain) = address.trim().split_once('@')?;
+        let valid_user = !user.is_empty()
+            && user
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_.+".contains(c));
+        (valid_user && is_public_domain(domain)).then_some((user, domain))
+    }
+
+    // Anyone can ask for a tip, so the server only fetches domain names: no IP
+    // addresses, ports or local names that would reach the server's own network
+    fn is_public_domain(domain: &str) -> bool {
+        let labels: Vec<&str> = domain.split('.').collect();
+        let valid_labels = labels.len() > 1
+            && labels.iter().all(|label| {
+                !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            });
+        // A numeric last label makes URL parsers read the whole host as an IPv4 address
+        let named = labels
+            .last()
+            .is_some_and(|tld| tld.starts_with(|c: char| c.is_ascii_alphabetic()));
+        let local = labels.iter().any(|label| label.eq_ignore_ascii_case("localhost"))
+            || ["local", "internal", "lan", "home", "corp"]
+                .iter()
+                .any(|tld| labels.last().is_some_and(|last| last.eq_ignore_ascii_case(tld)));
+        valid_labels && named && !local && domain.parse::<IpAddr>().is_err()
+    }
+
+    // Invoices are only requested from the domain of the lightning address, over https
+    fn is_callback_of(callback: &str, domain: &str) -> bool {
+        reqwest::Url::parse(callback).is_ok_and(|url| {
+            url.scheme() == "https"
+                && url.port().is_none()
+                && url.host_str().is_some_and(|host| host.eq_ignore_ascii_case(domain))
+        })
+    }
+
+    // Client of the lightning services, shared by the tips. Redirects stay on https
+    // and on the host that was asked
+    fn http_client() -> &'static reqwest::Client {
+        static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+        CLIENT.get_or_init(|| {
+            let redirects = reqwest::redirect::Policy::custom(|attempt| {
+                let same_host = attempt
+                    .previous()
+                    .first()
+                    .is_some_and(|first| first.host_str() == attempt.url().host_str());
+                if attempt.previous().len() > MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else if same_host && attempt.url().scheme() == "https" && attempt.url().port().is_none() {
+                    attempt.follow()
+                } else {
+                    attempt.stop()
+                }
+            });
+            reqwest::Client::builder()
+                .connect_timeout(CONNECT_TIMEOUT)
+                .timeout(REQUEST_TIMEOUT)
+                .redirect(redirects)
+                .build()
+                .unwrap_or_default()
+        })
+    }
+
+    // Resolve a lightning address to its LNURL-pay service
+    pub async fn fetch_pay_service(address: &str) -> Result<PayService, ZapError> {
+        let (user, domain) = parse_lightning_address(address)
+            .ok_or_else(|| ZapError::InvalidLightningAddress(address.to_string()))?;
+        let data = get_json(&format!("https://{}/.well-known/lnurlp/{}", domain, user.to_lowercase()), &[]).await?;
+
+        let callback = data["callback"]
+            .as_str()
+            .ok_or_else(|| ZapError::Service("missing callback".into()))?;
+        if !is_callback_of(callback, domain) {
+            return Err(ZapError::Service(format!("callback outside of https://{}", domain)));
+        }
+        Ok(PayService {
+            callback: callback.to_string(),
+            min_sendable: data["minSendable"].as_u64().unwrap_or(MSAT_PER_SAT),
+            max_sendable: data["maxSendable"].as_u64().unwrap_or(u64::MAX),
+            comment_allowed: data["commentAllowed"].as_u64().unwrap_or_default() as usize,
+            allows_nostr: data["allowsNostr"].as_bool().unwrap_or(false) && data["nostrPubkey"].is_string(),
+        })
+    }
+
+    // Ask the service for an invoice. A zap request (NIP-57) replaces the comment,
+    // its content already carries the message
+    pub async fn request_invoice(
+        service: &PayService,
+        amount_sats: u64,
+        comment: Option<&str>,
+        zap_request: Option<&str>,
+    ) -> Result<String, ZapError> {
+        let amount_msat = amount_sats.saturating_mul(MSAT_PER_SAT);
+        if amount_msat < service.min_sendable || amount_msat > service.max_sendable {
+            return Err(ZapError::AmountOutOfRange {
+                min_sats: service.min_sendable.div_ceil(MSAT_PER_SAT),
+                max_sats: service.max_sendable / MSAT_PER_SAT,
+            });
+        }
+
+        let mut query = vec![("amount", amount_msat.to_string())];
+        match (zap_request, comment) {
+            (Some(zap_request), _) => query.push(("nostr", zap_request.to_string())),
+            (None, Some(comment)) if service.comment_allowed > 0 => {
+                query.push(("comment", comment.chars().take(service.comment_allowed).collect()))
+            }
+            _ => {}
+        }
+        let checked_callback = reqwest::Url::parse(&service.callback)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from))
+            .filter(|host| is_public_domain(host) && is_callback_of(&service.callback, host));
+        if checked_callback.is_none() {
+            return Err(ZapError::Service(format!("invalid callback {}", service.callback)));
+        }
+        log!("[ZAP] Requesting invoice of {} sats from {}", amount_sats, service.callback);
+
+        let data = get_json(&service.callback, &query).await?;
+        data["pr"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| ZapError::Service("missing invoice".into()))
+    }
+
+    // GET an LNURL endpoint, turning `{"status": "ERROR"}` answers into errors
+    async fn get_json(url: &str, query: &[(&str, String)]) -> Result<serde_json::Value, ZapError> {
+        let response = http_client().get(url).query(query).send().await?;
+        if !response.status().is_success() {
+            return Err(ZapError::Status(response.status().as_u16()));
+        }
+
+        let data: serde_json::Value = response.json().await?;
+        if data["status"].as_str() == Some("ERROR") {
+            return Err(ZapError::Service(
+                data["reason"].as_str().unwrap_or("unknown error").to_string(),
+            ));
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use super::zap_impl::*;
+    use leptos::logging::log;
+
+    #[test]
+    fn test_parse_lightning_address() {
+        log!("[TEST] Starting test_parse_lightning_address");
+        assert_eq!(parse_lightning_address(" Alice@getalby.com "), Some(("Alice", "getalby.com")));
+        assert_eq!(parse_lightning_address("bob@pay.example.co.uk"), Some(("bob", "pay.example.co.uk")));
+        for address in [
+            "alice",
+            "@getalby.com",
+            "alice@getalby",
+            "alice@127.0.0.1",
+            "alice@10.0.0.8",
+            "alice@0x7f.1",
+            "alice@[::1]",
+            "alice@getalby.com:8080",
+            "alice@localhost",
+            "alice@api.localhost",
+            "alice@printer.local",
+            "alice@metadata.google.internal",
+            "alice@getalby..com",
+            "alice@getalby.com/path",
+        ] {
+            assert_eq!(parse_lightning_address(address), None, "{} was accepted", address);
+        }
+        log!("[TEST] test_parse_lightning_address completed successfully");
+    }
+
+    #[tokio::test]
+    async fn test_request_invoice_checks_the_callback() {
+        log!("[TEST] Starting test_request_invoice_checks_the_callback");
+        for callback in [
+            "http://getalby.com/lnurlp/alice/callback",
+            "https://127.0.0.1/callback",
+            "https://getalby.com:8443/callback",
+            "https://localhost/callback",
+            "file:///etc/passwd",
+        ] {
+            let service = PayService {
+                callback: callback.into(),
+                min_sendable: MSAT_PER_SAT,
+                max_sendable: 1_000_000 * MSAT_PER_SAT,
+                comment_allowed: 0,
+                allows_nostr: false,
+            };
+            let result = request_invoice(&service, 21, None, None).await;
+            assert!(matches!(result, Err(ZapError::Service(_))), "{} was requested", callback);
+        }
+        log!("[TEST] test_request_invoice_checks_the_callback completed successfully");
+    }
+}
+
+#[cfg(feature = "ssr")]
+pub use zap_impl::{fetch_pay_service, parse_lightning_address, request_invoice, PayService, ZapError, MSAT_PER_SAT};